@@ -0,0 +1,250 @@
+//! Length-prefixed bincode framing. Each message goes out as one or more chunks, each led by a little-endian
+//! `u32` holding the chunk's length, with the top bit set when another chunk of the same message follows
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Largest chunk on the wire; bigger messages are split into several
+pub const MAX_CHUNK_SIZE: usize = 4096;
+/// Largest message once its chunks are put back together, enough for a `Welcome` from a packed server
+pub const MAX_MESSAGE_SIZE: usize = 1 << 20;
+/// Set in a chunk's length prefix when another chunk of the same message follows it
+const MORE_CHUNKS: u32 = 1 << 31;
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The connection failed, or closed part way through a message
+    Io(std::io::Error),
+    /// A chunk claimed more than `MAX_CHUNK_SIZE` bytes, so the stream is out of step and can't be read on
+    Corrupt { chunk_len: usize },
+    /// The message was bigger than `MAX_MESSAGE_SIZE`. Nothing was sent, or what arrived was skipped
+    TooLarge { len: usize },
+    /// The message arrived whole but didn't decode; it was dropped
+    Malformed(bincode::Error),
+}
+
+impl FrameError {
+    /// Whether the connection is still in step and the next message can be read or written
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::TooLarge { .. } | Self::Malformed(_))
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Corrupt { chunk_len } => write!(f, "chunk of {} bytes is over the {} byte limit", chunk_len, MAX_CHUNK_SIZE),
+            Self::TooLarge { len } => write!(f, "message of {} bytes is over the {} byte limit", len, MAX_MESSAGE_SIZE),
+            Self::Malformed(e) => write!(f, "malformed message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<std::io::Error> for FrameError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Serialize and send one message
+pub async fn write_message<T: Serialize>(writer: &mut (impl AsyncWrite + Unpin), message: &T) -> Result<(), FrameError> {
+    let data = bincode::serialize(message).map_err(FrameError::Malformed)?;
+    write_frame(writer, &data).await
+}
+
+/// Read and decode the next message. `None` when the stream ends cleanly between messages
+pub async fn read_message<T: DeserializeOwned>(reader: &mut (impl AsyncRead + Unpin)) -> Result<Option<T>, FrameError> {
+    let Some(data) = read_frame(reader).await? else { return Ok(None) };
+    bincode::deserialize(&data).map(Some).map_err(FrameError::Malformed)
+}
+
+/// Write one message's bytes as chunks. Messages up to `MAX_CHUNK_SIZE` go out as a single chunk
+pub async fn write_frame(writer: &mut (impl AsyncWrite + Unpin), data: &[u8]) -> Result<(), FrameError> {
+    if data.len() > MAX_MESSAGE_SIZE {
+        return Err(FrameError::TooLarge { len: data.len() });
+    }
+    let mut chunks = data.chunks(MAX_CHUNK_SIZE).peekable();
+    if chunks.peek().is_none() {
+        writer.write_all(&0u32.to_le_bytes()).await?;
+    }
+    while let Some(chunk) = chunks.next() {
+        let more = if chunks.peek().is_some() { MORE_CHUNKS } else { 0 };
+        writer.write_all(&(chunk.len() as u32 | more).to_le_bytes()).await?;
+        writer.write_all(chunk).await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read one message's bytes, reassembling its chunks. Memory only grows a chunk at a time, so a lying
+/// sender can't make us allocate much; an oversized message is read through and thrown away
+pub async fn read_frame(reader: &mut (impl AsyncRead + Unpin)) -> Result<Option<Vec<u8>>, FrameError> {
+    let mut message = Vec::new();
+    let mut skipped = 0;
+    loop {
+        let mut len_bytes = [0u8; 4];
+        match reader.read_exact(&mut len_bytes).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && message.is_empty() && skipped == 0 => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let header = u32::from_le_bytes(len_bytes);
+        let len = (header & !MORE_CHUNKS) as usize;
+        if len > MAX_CHUNK_SIZE {
+            return Err(FrameError::Corrupt { chunk_len: len });
+        }
+
+        if skipped == 0 && message.len() + len <= MAX_MESSAGE_SIZE {
+            let start = message.len();
+            message.resize(start + len, 0);
+            reader.read_exact(&mut message[start..]).await?;
+        } else {
+            // Over the limit: keep reading to the end of the message so the next one lines up
+            skipped += message.len() + len;
+            message = Vec::new();
+            let mut scratch = [0u8; MAX_CHUNK_SIZE];
+            reader.read_exact(&mut scratch[..len]).await?;
+        }
+
+        if header & MORE_CHUNKS == 0 {
+            return match skipped {
+                0 => Ok(Some(message)),
+                len => Err(FrameError::TooLarge { len }),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum TestMessage {
+        Ping,
+        Text(String),
+        Blob(Vec<u8>),
+    }
+
+    async fn round_trip(data: &[u8]) -> Vec<u8> {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let sent = data.to_vec();
+        let writer = tokio::spawn(async move { write_frame(&mut client, &sent).await.unwrap() });
+        let received = read_frame(&mut server).await.unwrap().unwrap();
+        writer.await.unwrap();
+        received
+    }
+
+    fn chunk_header(len: usize, more: bool) -> [u8; 4] {
+        (len as u32 | if more { MORE_CHUNKS } else { 0 }).to_le_bytes()
+    }
+
+    #[tokio::test]
+    async fn small_message_is_a_single_chunk() {
+        let mut wire = Vec::new();
+        write_frame(&mut wire, b"hello").await.unwrap();
+        assert_eq!(wire, [5, 0, 0, 0, b'h', b'e', b'l', b'l', b'o']);
+    }
+
+    #[tokio::test]
+    async fn empty_message_round_trips() {
+        assert!(round_trip(&[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn large_message_is_split_and_reassembled() {
+        let data: Vec<u8> = (0..MAX_CHUNK_SIZE * 3 + 17).map(|i| i as u8).collect();
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &data).await.unwrap();
+        assert_eq!(wire.len(), data.len() + 4 * 4);
+        assert_eq!(round_trip(&data).await, data);
+    }
+
+    #[tokio::test]
+    async fn exact_chunk_size_needs_no_continuation() {
+        let data = vec![7u8; MAX_CHUNK_SIZE];
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &data).await.unwrap();
+        assert_eq!(wire.len(), MAX_CHUNK_SIZE + 4);
+        assert_eq!(round_trip(&data).await, data);
+    }
+
+    #[tokio::test]
+    async fn typed_messages_round_trip_in_order() {
+        let messages = [TestMessage::Ping, TestMessage::Text("hi".to_string()), TestMessage::Blob(vec![9; MAX_CHUNK_SIZE * 2])];
+        let mut wire = Vec::new();
+        for message in &messages {
+            write_message(&mut wire, message).await.unwrap();
+        }
+        let mut reader = wire.as_slice();
+        for message in &messages {
+            assert_eq!(read_message::<TestMessage>(&mut reader).await.unwrap().as_ref(), Some(message));
+        }
+        assert!(read_message::<TestMessage>(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn malformed_message_is_skipped() {
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &[0xff; 6]).await.unwrap();
+        write_message(&mut wire, &TestMessage::Ping).await.unwrap();
+        let mut reader = wire.as_slice();
+
+        let error = read_message::<TestMessage>(&mut reader).await.unwrap_err();
+        assert!(matches!(error, FrameError::Malformed(_)));
+        assert!(error.is_recoverable());
+        assert_eq!(read_message::<TestMessage>(&mut reader).await.unwrap(), Some(TestMessage::Ping));
+    }
+
+    #[tokio::test]
+    async fn oversized_chunk_is_corrupt() {
+        let wire = chunk_header(MAX_CHUNK_SIZE + 1, false);
+        let error = read_frame(&mut wire.as_slice()).await.unwrap_err();
+        assert!(matches!(error, FrameError::Corrupt { chunk_len } if chunk_len == MAX_CHUNK_SIZE + 1));
+        assert!(!error.is_recoverable());
+    }
+
+    #[tokio::test]
+    async fn oversized_message_is_refused_before_sending() {
+        let mut wire = Vec::new();
+        let error = write_frame(&mut wire, &vec![0u8; MAX_MESSAGE_SIZE + 1]).await.unwrap_err();
+        assert!(matches!(error, FrameError::TooLarge { .. }));
+        assert!(wire.is_empty());
+    }
+
+    #[tokio::test]
+    async fn oversized_message_is_skipped_and_the_next_one_read() {
+        let chunks = MAX_MESSAGE_SIZE / MAX_CHUNK_SIZE + 2;
+        let mut wire = Vec::new();
+        for chunk in 0..chunks {
+            wire.extend_from_slice(&chunk_header(MAX_CHUNK_SIZE, chunk + 1 < chunks));
+            wire.extend_from_slice(&[0u8; MAX_CHUNK_SIZE]);
+        }
+        write_frame(&mut wire, b"next").await.unwrap();
+        let mut reader = wire.as_slice();
+
+        let error = read_frame(&mut reader).await.unwrap_err();
+        assert!(matches!(error, FrameError::TooLarge { len } if len == chunks * MAX_CHUNK_SIZE));
+        assert!(error.is_recoverable());
+        assert_eq!(read_frame(&mut reader).await.unwrap().unwrap(), b"next");
+    }
+
+    #[tokio::test]
+    async fn stream_ending_mid_message_is_an_error() {
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &vec![3u8; MAX_CHUNK_SIZE * 2]).await.unwrap();
+        wire.truncate(MAX_CHUNK_SIZE + 4);
+        let error = read_frame(&mut wire.as_slice()).await.unwrap_err();
+        assert!(matches!(error, FrameError::Io(_)));
+        assert!(!error.is_recoverable());
+    }
+}
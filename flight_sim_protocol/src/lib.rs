@@ -0,0 +1,4 @@
+//! Wire format shared by the game client and `flight_sim_server`
+
+pub mod codec;
+pub mod messages;
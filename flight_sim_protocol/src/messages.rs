@@ -0,0 +1,234 @@
+//! Messages between the game client and the server, and the session recordings the server writes
+
+use serde::{Deserialize, Serialize};
+
+/// First byte sent by the server on a new connection, before any framed message.
+/// `TRANSPORT_TLS` means the client must start a TLS handshake on the same socket.
+pub const TRANSPORT_PLAIN: u8 = 0;
+pub const TRANSPORT_TLS: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PlaneType {
+    #[default]
+    Light,
+    Jet,
+    Glider,
+}
+
+/// What a connection joins as; observers get no aircraft and can send instructions to pilots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClientRole {
+    #[default]
+    Pilot,
+    Observer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub id: u32,
+    pub name: String,
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub plane_type: PlaneType,
+    /// Wingtip smoke colour, `None` when the smoke is off
+    pub smoke: Option<[u8; 3]>,
+    /// Why the server's movement checks flagged this pilot, `None` while they look legitimate
+    pub flagged: Option<String>,
+}
+
+/// A verified time-trial result; times are in seconds from the start gate to the finish gate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub plane_type: PlaneType,
+    pub time: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Must be the first message on a new connection
+    Join { name: String, role: ClientRole },
+    UpdatePosition { name: String, position: [f32; 3], rotation: [f32; 4], plane_type: PlaneType, smoke: Option<[u8; 3]> },
+    /// A round leaving our guns, replicated so other players see the tracer
+    Fire { position: [f32; 3], velocity: [f32; 3] },
+    /// One of our rounds hit another player; the target applies a round's damage
+    Hit { target: u32 },
+    /// Our aircraft was shot down by another player
+    ShotDown { by: u32 },
+    /// Ask for the time-trial course; the server starts checking our updates against it
+    StartTimeTrial,
+    /// We crossed the finish gate; the server validates the time before ranking it
+    FinishTimeTrial { time: f32 },
+    RequestLeaderboard,
+    /// Our best time on day `day`'s daily flight. It is flown offline, so the server can only check it is plausible
+    SubmitDailyTime { day: u32, time: f32, plane_type: PlaneType },
+    RequestDailyLeaderboard,
+    /// One Opus-encoded push-to-talk frame
+    Voice { frame: Vec<u8> },
+    /// Observer text to one pilot, or to everyone when `target` is `None`
+    Instruction { target: Option<u32>, text: String },
+    /// The latest state of one component on one of our replicated entities; `entity` is our own id for it
+    Replicate { entity: u32, kind: String, data: Vec<u8> },
+    /// One of our replicated entities is gone
+    DespawnReplica { entity: u32 },
+    Disconnect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome {
+        your_id: u32,
+        seed: u32,
+        existing_players: Vec<PlayerState>,
+        time_of_day: f32,
+        speed: f32,
+        spawn_point: [f32; 2],
+    },
+    PlayerJoined {
+        player: PlayerState,
+    },
+    PlayerUpdate {
+        id: u32,
+        name: String,
+        position: [f32; 3],
+        rotation: [f32; 4],
+        plane_type: PlaneType,
+        smoke: Option<[u8; 3]>,
+    },
+    PlayerLeft {
+        id: u32,
+    },
+    Fire {
+        id: u32,
+        position: [f32; 3],
+        velocity: [f32; 3],
+    },
+    Hit {
+        shooter: u32,
+    },
+    ShotDown {
+        id: u32,
+        by: u32,
+    },
+    /// Gate centres on the ground plane, flown in order; the first is the start, the last the finish
+    TimeTrialCourse {
+        gates: Vec<[f32; 2]>,
+        gate_radius: f32,
+    },
+    TimeTrialResult {
+        accepted: bool,
+        message: String,
+    },
+    Leaderboard {
+        entries: Vec<LeaderboardEntry>,
+    },
+    DailyResult {
+        accepted: bool,
+        message: String,
+    },
+    /// Best times on day `day`'s daily flight
+    DailyLeaderboard {
+        day: u32,
+        entries: Vec<LeaderboardEntry>,
+    },
+    /// A voice frame from a player within earshot
+    Voice {
+        id: u32,
+        frame: Vec<u8>,
+    },
+    Instruction {
+        from: String,
+        text: String,
+    },
+    /// Wind shear event on the ground plane; `strength` is the peak downdraft in world units per second
+    Microburst {
+        center: [f32; 2],
+        radius: f32,
+        strength: f32,
+        duration: f32,
+    },
+    /// Lightning strike on the ground plane, under one of the storms microbursts hang from
+    Lightning {
+        position: [f32; 2],
+    },
+    /// The server's movement checks flagged a pilot for implausible speed or a teleport; sent once per session
+    PlayerFlagged {
+        id: u32,
+        reason: String,
+    },
+    /// A component of an entity another client replicates, keyed by its owner and the owner's id for it
+    Replicate {
+        owner: u32,
+        entity: u32,
+        kind: String,
+        data: Vec<u8>,
+    },
+    DespawnReplica {
+        owner: u32,
+        entity: u32,
+    },
+    /// An admin changed the map or the clock; a new seed regenerates the world as on `Welcome`
+    WorldUpdate {
+        seed: u32,
+        time_of_day: f32,
+        speed: f32,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Written into every session recording's `Start`. Bump it whenever `SessionEntry` or `PlayerState` change,
+/// since older recordings would no longer decode
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+/// One entry of a server session recording, stored as its bincode bytes after a plain little-endian `u32`
+/// length. Unlike the wire there are no chunks, since the file is read whole
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SessionEntry {
+    /// Always first, and laid out the same in every format version: the format and the world the
+    /// session starts in
+    Start { version: u32, seed: u32 },
+    /// A pilot's update, `time` seconds after the recording started
+    Position { time: f32, player: PlayerState },
+    Left { time: f32, id: u32 },
+    /// An admin changed the map part way through
+    WorldChanged { time: f32, seed: u32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{read_message, write_message, MAX_CHUNK_SIZE};
+
+    #[tokio::test]
+    async fn welcome_for_a_packed_server_round_trips() {
+        let existing_players: Vec<PlayerState> = (0..200)
+            .map(|id| PlayerState {
+                id,
+                name: format!("Pilot with a long callsign {}", id),
+                position: [id as f32; 3],
+                rotation: [0.0, 0.0, 0.0, 1.0],
+                plane_type: PlaneType::Jet,
+                smoke: Some([255, 128, 0]),
+                flagged: None,
+            })
+            .collect();
+        let welcome = ServerMessage::Welcome {
+            your_id: 201,
+            seed: 42,
+            existing_players,
+            time_of_day: 0.5,
+            speed: 0.003,
+            spawn_point: [0.0, 0.0],
+        };
+        assert!(bincode::serialize(&welcome).unwrap().len() > MAX_CHUNK_SIZE);
+
+        let mut wire = Vec::new();
+        write_message(&mut wire, &welcome).await.unwrap();
+        let received = read_message::<ServerMessage>(&mut wire.as_slice()).await.unwrap();
+        let Some(ServerMessage::Welcome { existing_players, .. }) = received else { panic!("expected Welcome") };
+        assert_eq!(existing_players.len(), 200);
+        assert_eq!(existing_players[199].name, "Pilot with a long callsign 199");
+    }
+}
@@ -0,0 +1,78 @@
+use crate::anticheat::MovementLimits;
+use flight_sim_protocol::messages::PlaneType;
+
+/// Commands typed into the server's terminal while it runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdminCommand {
+    /// Switch every client to a new map, a random one when no seed is given
+    Seed(Option<u32>),
+    /// Set the time of day, 0.0..1.0 with 0.5 at noon
+    Time(f32),
+    /// Set how fast the day advances, in days per second
+    Speed(f32),
+    /// Show the movement-check limits, or change one plane type's
+    Limits(Option<(PlaneType, MovementLimits)>),
+    Status,
+    Help,
+}
+
+pub const HELP: &str = "Admin commands:
+  seed [n]    change the map for everyone, random if n is left out
+  time <t>    set the time of day, 0.0..1.0 (0.5 is noon)
+  speed <s>   set how fast the day advances, in days per second
+  limits [plane speed accel]
+              show the movement-check limits, or set a plane type's (light, jet, glider)
+              top speed and acceleration in world units per second
+  status      show the current seed, time and speed
+  help        show this list";
+
+impl AdminCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or_else(|| "empty command".to_string())?;
+        let arguments: Vec<&str> = words.collect();
+        if name != "limits" && arguments.len() > 1 {
+            return Err(format!("too many arguments to {}", name));
+        }
+        let argument = arguments.first().copied();
+        let number = |usage: &str| -> Result<f32, String> {
+            let value = argument.ok_or_else(|| format!("usage: {}", usage))?;
+            value.parse::<f32>().ok().filter(|value| value.is_finite()).ok_or_else(|| format!("not a number: {}", value))
+        };
+
+        match name {
+            "seed" => match argument {
+                None => Ok(Self::Seed(None)),
+                Some(value) => value.parse().map(|seed| Self::Seed(Some(seed))).map_err(|_| format!("not a seed: {}", value)),
+            },
+            "time" => Ok(Self::Time(number("time <0.0..1.0>")?.rem_euclid(1.0))),
+            "speed" => {
+                let speed = number("speed <days per second>")?;
+                if speed < 0.0 {
+                    return Err("speed can't be negative".to_string());
+                }
+                Ok(Self::Speed(speed))
+            }
+            "limits" => match arguments[..] {
+                [] => Ok(Self::Limits(None)),
+                [plane, max_speed, max_acceleration] => {
+                    let plane_type = match plane {
+                        "light" => PlaneType::Light,
+                        "jet" => PlaneType::Jet,
+                        "glider" => PlaneType::Glider,
+                        _ => return Err(format!("unknown plane type {}, use light, jet or glider", plane)),
+                    };
+                    let positive = |value: &str| {
+                        value.parse::<f32>().ok().filter(|value| value.is_finite() && *value > 0.0).ok_or_else(|| format!("not a positive number: {}", value))
+                    };
+                    let limits = MovementLimits { max_speed: positive(max_speed)?, max_acceleration: positive(max_acceleration)? };
+                    Ok(Self::Limits(Some((plane_type, limits))))
+                }
+                _ => Err("usage: limits [<light|jet|glider> <max speed> <max acceleration>]".to_string()),
+            },
+            "status" => Ok(Self::Status),
+            "help" => Ok(Self::Help),
+            _ => Err(format!("unknown command {}, try help", name)),
+        }
+    }
+}
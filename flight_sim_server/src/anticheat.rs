@@ -0,0 +1,264 @@
+use flight_sim_protocol::messages::PlaneType;
+use std::fmt;
+use std::time::Instant;
+
+/// Updates can arrive bunched up behind a network stall
+const JITTER_ALLOWANCE: f32 = 0.25;
+/// Speeds are measured over windows at least this long, so bunched updates average out
+const SAMPLE_WINDOW: f32 = 2.0;
+/// A single update moving further than this is a teleport rather than fast flying
+const TELEPORT_DISTANCE: f32 = 5000.0;
+/// Jumps landing this close to the pilot's spawn point are respawns
+const RESPAWN_RADIUS: f32 = 100.0;
+/// Jumps landing this close to another pilot are the player list's "Teleport" button
+const JOIN_RADIUS: f32 = 2000.0;
+/// Implausible windows in a row before a pilot is flagged, so one odd sample doesn't count
+const STRIKES_TO_FLAG: u32 = 3;
+
+/// What a plane type can plausibly do, in world units per second and per second squared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovementLimits {
+    pub max_speed: f32,
+    pub max_acceleration: f32,
+}
+
+/// Limits for each plane type; light covers every propeller preset, airliner included.
+/// The defaults leave headroom above the fastest preset of each type for dives
+#[derive(Debug, Clone)]
+pub struct PlaneLimits {
+    pub light: MovementLimits,
+    pub jet: MovementLimits,
+    pub glider: MovementLimits,
+}
+
+impl Default for PlaneLimits {
+    fn default() -> Self {
+        Self {
+            light: MovementLimits { max_speed: 1800.0, max_acceleration: 400.0 },
+            jet: MovementLimits { max_speed: 4500.0, max_acceleration: 900.0 },
+            glider: MovementLimits { max_speed: 700.0, max_acceleration: 250.0 },
+        }
+    }
+}
+
+impl PlaneLimits {
+    pub fn get(&self, plane_type: PlaneType) -> MovementLimits {
+        match plane_type {
+            PlaneType::Light => self.light,
+            PlaneType::Jet => self.jet,
+            PlaneType::Glider => self.glider,
+        }
+    }
+
+    pub fn set(&mut self, plane_type: PlaneType, limits: MovementLimits) {
+        match plane_type {
+            PlaneType::Light => self.light = limits,
+            PlaneType::Jet => self.jet = limits,
+            PlaneType::Glider => self.glider = limits,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    Speed { speed: f32, limit: f32 },
+    Acceleration { acceleration: f32, limit: f32 },
+    Teleport { distance: f32 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Speed { speed, limit } => write!(f, "flew at {:.0} units/s, limit {:.0}", speed, limit),
+            Self::Acceleration { acceleration, limit } => {
+                write!(f, "accelerated at {:.0} units/s², limit {:.0}", acceleration, limit)
+            }
+            Self::Teleport { distance } => write!(f, "jumped {:.0} units in one update", distance),
+        }
+    }
+}
+
+/// A pilot's movement as the server sees it from their position updates.
+/// Once flagged a pilot stays flagged for the rest of their session
+pub struct MovementTracker {
+    spawn_point: [f32; 2],
+    last: Option<(Instant, [f32; 3], PlaneType)>,
+    /// Start of the current sample window
+    window_start: Option<(Instant, [f32; 3])>,
+    /// Average speed over the previous window, `None` right after a reset
+    last_speed: Option<f32>,
+    strikes: u32,
+    pub flag: Option<Violation>,
+}
+
+fn horizontal_distance(a: [f32; 3], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[2] - b[1]).powi(2)).sqrt()
+}
+
+impl MovementTracker {
+    pub fn new(spawn_point: [f32; 2]) -> Self {
+        Self {
+            spawn_point,
+            last: None,
+            window_start: None,
+            last_speed: None,
+            strikes: 0,
+            flag: None,
+        }
+    }
+
+    /// Feed one update; `others` are the other pilots' positions. Returns the violation when it's the one
+    /// that gets the pilot flagged
+    pub fn record(
+        &mut self,
+        now: Instant,
+        position: [f32; 3],
+        plane_type: PlaneType,
+        limits: &PlaneLimits,
+        others: &[[f32; 3]],
+    ) -> Option<Violation> {
+        let previous = self.last.replace((now, position, plane_type));
+        let Some((then, last, last_plane)) = previous else {
+            self.restart_window(now, position);
+            return None;
+        };
+        // A new aircraft starts from its own respawn speed, nothing to compare against
+        if plane_type != last_plane {
+            self.restart_window(now, position);
+            return None;
+        }
+
+        let limits = limits.get(plane_type);
+        let dt = (now - then).as_secs_f32();
+        let distance = (0..3).map(|i| (position[i] - last[i]).powi(2)).sum::<f32>().sqrt();
+        if distance > TELEPORT_DISTANCE && distance > limits.max_speed * (dt + JITTER_ALLOWANCE) {
+            self.restart_window(now, position);
+            let respawned = horizontal_distance(position, self.spawn_point) < RESPAWN_RADIUS;
+            let joined = others.iter().any(|other| horizontal_distance(position, [other[0], other[2]]) < JOIN_RADIUS);
+            if respawned || joined {
+                return None;
+            }
+            return self.flag(Violation::Teleport { distance });
+        }
+
+        let (window_time, window_position) = self.window_start?;
+        let elapsed = (now - window_time).as_secs_f32();
+        if elapsed < SAMPLE_WINDOW {
+            return None;
+        }
+        let travelled = (0..3).map(|i| (position[i] - window_position[i]).powi(2)).sum::<f32>().sqrt();
+        // Measured over the window plus the jitter allowance, the speed can only be underestimated
+        let speed = travelled / (elapsed + JITTER_ALLOWANCE);
+        let acceleration = self.last_speed.map(|last_speed| (speed - last_speed) / elapsed);
+        self.window_start = Some((now, position));
+        self.last_speed = Some(speed);
+
+        let violation = if speed > limits.max_speed {
+            Some(Violation::Speed { speed, limit: limits.max_speed })
+        } else {
+            // Only speeding up counts; a crash stops an aircraft dead
+            acceleration
+                .filter(|acceleration| *acceleration > limits.max_acceleration)
+                .map(|acceleration| Violation::Acceleration { acceleration, limit: limits.max_acceleration })
+        };
+        match violation {
+            Some(violation) => {
+                self.strikes += 1;
+                if self.strikes >= STRIKES_TO_FLAG {
+                    return self.flag(violation);
+                }
+            }
+            None => self.strikes = 0,
+        }
+        None
+    }
+
+    fn restart_window(&mut self, now: Instant, position: [f32; 3]) {
+        self.window_start = Some((now, position));
+        self.last_speed = None;
+        self.strikes = 0;
+    }
+
+    fn flag(&mut self, violation: Violation) -> Option<Violation> {
+        if self.flag.is_some() {
+            return None;
+        }
+        self.flag = Some(violation.clone());
+        Some(violation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Updates at 10 Hz along +X at `speed` for `seconds`, from `tracker`'s last position; returns the flags raised
+    fn fly(tracker: &mut MovementTracker, start: Instant, clock: &mut f32, speed: f32, seconds: f32, others: &[[f32; 3]]) -> Vec<Violation> {
+        let limits = PlaneLimits::default();
+        let mut position = tracker.last.map_or([0.0, 500.0, 0.0], |(_, position, _)| position);
+        let mut flags = Vec::new();
+        for _ in 0..(seconds * 10.0).round() as usize {
+            *clock += 0.1;
+            position[0] += speed * 0.1;
+            flags.extend(tracker.record(start + Duration::from_secs_f32(*clock), position, PlaneType::Light, &limits, others));
+        }
+        flags
+    }
+
+    fn jump(tracker: &mut MovementTracker, start: Instant, clock: &mut f32, to: [f32; 3], others: &[[f32; 3]]) -> Option<Violation> {
+        *clock += 0.1;
+        tracker.record(start + Duration::from_secs_f32(*clock), to, PlaneType::Light, &PlaneLimits::default(), others)
+    }
+
+    #[test]
+    fn flying_within_the_limits_is_never_flagged() {
+        let (start, mut clock) = (Instant::now(), 0.0);
+        let mut tracker = MovementTracker::new([0.0, 0.0]);
+        assert!(fly(&mut tracker, start, &mut clock, 1500.0, 30.0, &[]).is_empty());
+        assert!(tracker.flag.is_none());
+    }
+
+    #[test]
+    fn sustained_speeding_is_flagged_once() {
+        let (start, mut clock) = (Instant::now(), 0.0);
+        let mut tracker = MovementTracker::new([0.0, 0.0]);
+        let flags = fly(&mut tracker, start, &mut clock, 3000.0, 20.0, &[]);
+        assert!(matches!(flags.as_slice(), [Violation::Speed { .. }]), "{:?}", flags);
+        assert!(tracker.flag.is_some());
+    }
+
+    #[test]
+    fn a_single_fast_window_is_not_enough() {
+        let (start, mut clock) = (Instant::now(), 0.0);
+        let mut tracker = MovementTracker::new([0.0, 0.0]);
+        fly(&mut tracker, start, &mut clock, 1000.0, 4.0, &[]);
+        fly(&mut tracker, start, &mut clock, 3000.0, 2.0, &[]);
+        fly(&mut tracker, start, &mut clock, 1000.0, 20.0, &[]);
+        assert!(tracker.flag.is_none());
+    }
+
+    #[test]
+    fn jumps_are_flagged_unless_they_are_respawns_or_joins() {
+        let (start, mut clock) = (Instant::now(), 0.0);
+        let mut tracker = MovementTracker::new([100.0, 200.0]);
+        fly(&mut tracker, start, &mut clock, 1000.0, 1.0, &[]);
+
+        assert_eq!(jump(&mut tracker, start, &mut clock, [100.0, 800.0, 200.0], &[]), None, "respawn");
+        let other = [40_000.0, 900.0, 0.0];
+        assert_eq!(jump(&mut tracker, start, &mut clock, [40_500.0, 900.0, 0.0], &[other]), None, "joined a pilot");
+        assert!(matches!(jump(&mut tracker, start, &mut clock, [-30_000.0, 900.0, 0.0], &[other]), Some(Violation::Teleport { .. })));
+    }
+
+    #[test]
+    fn switching_aircraft_starts_over() {
+        let (start, mut clock) = (Instant::now(), 0.0);
+        let limits = PlaneLimits::default();
+        let mut tracker = MovementTracker::new([0.0, 0.0]);
+        fly(&mut tracker, start, &mut clock, 1000.0, 1.0, &[]);
+        // A jet spawning elsewhere is not a jump
+        clock += 0.1;
+        assert_eq!(tracker.record(start + Duration::from_secs_f32(clock), [9000.0, 500.0, 0.0], PlaneType::Jet, &limits, &[]), None);
+        assert!(tracker.flag.is_none());
+    }
+}
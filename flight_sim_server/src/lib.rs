@@ -0,0 +1,766 @@
+pub mod admin;
+pub mod anticheat;
+pub mod recording;
+pub mod time_trial;
+pub mod tls;
+
+use admin::AdminCommand;
+use flight_sim_protocol::codec;
+use anticheat::{MovementTracker, PlaneLimits};
+use flight_sim_protocol::messages::{ClientMessage, ClientRole, LeaderboardEntry, PlaneType, PlayerState, ServerMessage, SessionEntry, TRANSPORT_PLAIN, TRANSPORT_TLS};
+use recording::SessionRecorder;
+use std::collections::HashMap;
+use std::sync::Arc;
+use time_trial::{Leaderboard, TrialRun, DAILY_LEADERBOARD_PATH, LEADERBOARD_PATH};
+use tls::BoxedStream;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, RwLock};
+use tokio_rustls::TlsAcceptor;
+
+pub const SERVER_ADDR: &str = "0.0.0.0:7878";
+const SPAWN_SPACING: f32 = 600.0;
+const GOLDEN_ANGLE: f32 = 2.399_963;
+/// Voice frames are only relayed to players within this distance of the speaker
+const VOICE_RANGE: f32 = 15000.0;
+const MAX_INSTRUCTION_LENGTH: usize = 200;
+/// Seconds between rolls for a microburst, and the chance each roll starts one
+const MICROBURST_INTERVAL: u64 = 60;
+const MICROBURST_CHANCE: f32 = 0.3;
+/// Distance from the chosen pilot, so there is time to see the shaft and divert
+const MICROBURST_MIN_DISTANCE: f32 = 4000.0;
+const MICROBURST_MAX_DISTANCE: f32 = 12000.0;
+/// Milliseconds between rolls for a strike, and the chance each storm throws one per roll
+const LIGHTNING_INTERVAL_MS: u64 = 1000;
+const LIGHTNING_CHANCE: f32 = 0.12;
+/// Strikes land within this many storm radii of the core
+const LIGHTNING_REACH: f32 = 2.0;
+/// Replicated entities a single client may have live at once
+const MAX_REPLICAS_PER_PLAYER: usize = 64;
+const MAX_REPLICA_KIND_LENGTH: usize = 128;
+/// Component kinds one replicated entity may carry
+pub const MAX_REPLICA_KINDS_PER_ENTITY: usize = 16;
+/// Bytes of replicated component data, kind names included, the server holds for one client
+pub const MAX_REPLICA_BYTES_PER_PLAYER: usize = 256 * 1024;
+
+/// A storm a microburst was dropped under, throwing lightning until the burst dies out
+struct StormCell {
+    center: [f32; 2],
+    radius: f32,
+    until: std::time::Instant,
+}
+
+type PlayerId = u32;
+type PlayerMap = Arc<RwLock<HashMap<PlayerId, PlayerState>>>;
+type ClientSender = mpsc::UnboundedSender<ServerMessage>;
+type ClientSenders = Arc<RwLock<HashMap<PlayerId, ClientSender>>>;
+/// Latest component data of every replicated entity, by owner, then the owner's entity id, then component kind
+type ReplicaMap = Arc<RwLock<HashMap<PlayerId, HashMap<u32, HashMap<String, Vec<u8>>>>>>;
+
+pub struct GameServer {
+    seed: Arc<RwLock<u32>>,
+    players: PlayerMap,
+    senders: ClientSenders,
+    next_player_id: Arc<RwLock<u32>>,
+    spawn_slots: Arc<RwLock<HashMap<PlayerId, usize>>>,
+    time_of_day: Arc<RwLock<f32>>,
+    /// Days per second the clock advances
+    speed: Arc<RwLock<f32>>,
+    course: Vec<[f32; 2]>,
+    leaderboard: Arc<RwLock<Leaderboard>>,
+    trial_runs: Arc<RwLock<HashMap<PlayerId, TrialRun>>>,
+    /// Times on the daily flight, kept under the day's seed
+    daily_leaderboard: Arc<RwLock<Leaderboard>>,
+    storms: Arc<RwLock<Vec<StormCell>>>,
+    movement: Arc<RwLock<HashMap<PlayerId, MovementTracker>>>,
+    /// Movement-check thresholds per plane type, adjustable from the admin terminal
+    limits: Arc<RwLock<PlaneLimits>>,
+    replicas: ReplicaMap,
+    /// Set when the server was started with `--record`
+    recorder: Option<SessionRecorder>,
+}
+
+impl GameServer {
+    pub fn new(record_path: Option<String>) -> Self {
+        let seed = rand::random::<u32>();
+        println!("🌍 Generated world seed: {}", seed);
+
+        let recorder = record_path.and_then(|path| match SessionRecorder::create(&path, seed) {
+            Ok(recorder) => {
+                println!("⏺ Recording the session to {}", path);
+                Some(recorder)
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to start recording to {}: {}", path, e);
+                None
+            }
+        });
+
+        let leaderboard = Leaderboard::load_or_new(LEADERBOARD_PATH);
+        println!("🏁 Time-trial course seed: {} ({} leaderboard entries)", leaderboard.course_seed, leaderboard.entries.len());
+        
+        Self {
+            seed: Arc::new(RwLock::new(seed)),
+            players: Arc::new(RwLock::new(HashMap::new())),
+            senders: Arc::new(RwLock::new(HashMap::new())),
+            next_player_id: Arc::new(RwLock::new(1)),
+            spawn_slots: Arc::new(RwLock::new(HashMap::new())),
+            time_of_day: Arc::new(RwLock::new(0.50)),
+            speed: Arc::new(RwLock::new(0.003)),
+            course: time_trial::generate_course(leaderboard.course_seed),
+            leaderboard: Arc::new(RwLock::new(leaderboard)),
+            trial_runs: Arc::new(RwLock::new(HashMap::new())),
+            daily_leaderboard: Arc::new(RwLock::new(Leaderboard::load_or_new(DAILY_LEADERBOARD_PATH))),
+            storms: Arc::new(RwLock::new(Vec::new())),
+            movement: Arc::new(RwLock::new(HashMap::new())),
+            limits: Arc::new(RwLock::new(PlaneLimits::default())),
+            replicas: Arc::new(RwLock::new(HashMap::new())),
+            recorder,
+        }
+    }
+
+    async fn get_next_id(&self) -> PlayerId {
+        let mut id = self.next_player_id.write().await;
+        let current = *id;
+        *id += 1;
+        current
+    }
+
+    /// Reserve the lowest free spawn slot and return its (x, z) position.
+    /// Slots are laid out on a sunflower spiral so neighbours stay evenly spaced.
+    async fn assign_spawn_point(&self, player_id: PlayerId) -> [f32; 2] {
+        let mut slots = self.spawn_slots.write().await;
+        let slot = (0..).find(|s| !slots.values().any(|taken| taken == s)).unwrap();
+        slots.insert(player_id, slot);
+
+        let radius = SPAWN_SPACING * (slot as f32).sqrt();
+        let angle = slot as f32 * GOLDEN_ANGLE;
+        [radius * angle.cos(), radius * angle.sin()]
+    }
+
+    async fn broadcast(&self, message: ServerMessage, exclude: Option<PlayerId>) {
+        let senders = self.senders.read().await;
+        for (id, sender) in senders.iter() {
+            if let Some(excluded_id) = exclude {
+                if *id == excluded_id {
+                    continue;
+                }
+            }
+            let _ = sender.send(message.clone());
+        }
+    }
+
+    async fn send_to(&self, player_id: PlayerId, message: ServerMessage) {
+        let senders = self.senders.read().await;
+        if let Some(sender) = senders.get(&player_id) {
+            let _ = sender.send(message);
+        }
+    }
+
+    /// Forward a voice frame to everyone close enough to hear it
+    async fn relay_voice(&self, speaker: PlayerId, frame: Vec<u8>) {
+        let players = self.players.read().await;
+        let Some(origin) = players.get(&speaker).map(|p| p.position) else { return };
+        let senders = self.senders.read().await;
+        for (id, sender) in senders.iter() {
+            let in_range = players.get(id).is_some_and(|listener| {
+                let distance_squared: f32 = (0..3).map(|i| (listener.position[i] - origin[i]).powi(2)).sum();
+                distance_squared <= VOICE_RANGE * VOICE_RANGE
+            });
+            if *id != speaker && in_range {
+                let _ = sender.send(ServerMessage::Voice { id: speaker, frame: frame.clone() });
+            }
+        }
+    }
+
+    /// Keep the latest state of a client's replicated component and pass it on to everyone else.
+    /// The server never looks inside; only the clients know what the kinds are
+    async fn replicate(&self, owner: PlayerId, entity: u32, kind: String, data: Vec<u8>) {
+        if kind.len() > MAX_REPLICA_KIND_LENGTH {
+            return;
+        }
+        let mut replicas = self.replicas.write().await;
+        let owned = replicas.entry(owner).or_default();
+        if !owned.contains_key(&entity) && owned.len() >= MAX_REPLICAS_PER_PLAYER {
+            return;
+        }
+        let components = owned.get(&entity);
+        if components.is_some_and(|components| !components.contains_key(&kind) && components.len() >= MAX_REPLICA_KINDS_PER_ENTITY) {
+            return;
+        }
+        // Everything stored is counted, so no mix of entities and kinds grows the map past the limit
+        let stored: usize = owned.values().flatten().map(|(kind, data)| kind.len() + data.len()).sum();
+        let replaced = components.and_then(|components| components.get(&kind)).map_or(0, |old| kind.len() + old.len());
+        if stored - replaced + kind.len() + data.len() > MAX_REPLICA_BYTES_PER_PLAYER {
+            eprintln!("❌ Dropped replicated {} from player {}: over the {} byte limit", kind, owner, MAX_REPLICA_BYTES_PER_PLAYER);
+            return;
+        }
+        owned.entry(entity).or_default().insert(kind.clone(), data.clone());
+        drop(replicas);
+        self.broadcast(ServerMessage::Replicate { owner, entity, kind, data }, Some(owner)).await;
+    }
+
+    async fn despawn_replica(&self, owner: PlayerId, entity: u32) {
+        let removed = self.replicas.write().await.get_mut(&owner).and_then(|owned| owned.remove(&entity));
+        if removed.is_some() {
+            self.broadcast(ServerMessage::DespawnReplica { owner, entity }, Some(owner)).await;
+        }
+    }
+
+    /// Bring a newcomer up to date with every replicated entity already in the session
+    async fn send_replicas(&self, player_id: PlayerId) {
+        let replicas = self.replicas.read().await;
+        for (&owner, owned) in replicas.iter() {
+            for (&entity, components) in owned {
+                for (kind, data) in components {
+                    self.send_to(player_id, ServerMessage::Replicate { owner, entity, kind: kind.clone(), data: data.clone() }).await;
+                }
+            }
+        }
+    }
+
+    /// Drop a microburst near a random pilot so everyone in the session flies the same hazard. The weather
+    /// fronts are drawn by each client, so the server can't see where the rain is; the burst makes its own
+    /// storm cell instead, which the lightning then comes from, and may land under clear sky on screen
+    async fn schedule_microburst(&self) {
+        if rand::random::<f32>() > MICROBURST_CHANCE {
+            return;
+        }
+        let players = self.players.read().await;
+        if players.is_empty() {
+            return;
+        }
+        let Some(target) = players.values().nth(rand::random::<u32>() as usize % players.len()) else { return };
+        let random_range = |min: f32, max: f32| min + rand::random::<f32>() * (max - min);
+        let angle = random_range(0.0, std::f32::consts::TAU);
+        let distance = random_range(MICROBURST_MIN_DISTANCE, MICROBURST_MAX_DISTANCE);
+        let center = [target.position[0] + angle.cos() * distance, target.position[2] + angle.sin() * distance];
+        let (radius, duration) = (random_range(1500.0, 3000.0), random_range(120.0, 300.0));
+        let message = ServerMessage::Microburst {
+            center,
+            radius,
+            strength: random_range(55.0, 105.0),
+            duration,
+        };
+        println!("⛈ Microburst near player {} at [{:.0}, {:.0}]", target.id, center[0], center[1]);
+        drop(players);
+        self.storms.write().await.push(StormCell {
+            center,
+            radius,
+            until: std::time::Instant::now() + std::time::Duration::from_secs_f32(duration),
+        });
+        self.broadcast(message, None).await;
+    }
+
+    /// Let each live storm throw the odd strike, the same one for every client
+    async fn strike_lightning(&self) {
+        let now = std::time::Instant::now();
+        let mut strikes = Vec::new();
+        {
+            let mut storms = self.storms.write().await;
+            storms.retain(|storm| storm.until > now);
+            for storm in storms.iter() {
+                if rand::random::<f32>() > LIGHTNING_CHANCE {
+                    continue;
+                }
+                let angle = rand::random::<f32>() * std::f32::consts::TAU;
+                let distance = rand::random::<f32>().sqrt() * storm.radius * LIGHTNING_REACH;
+                strikes.push([storm.center[0] + angle.cos() * distance, storm.center[1] + angle.sin() * distance]);
+            }
+        }
+        for position in strikes {
+            self.broadcast(ServerMessage::Lightning { position }, None).await;
+        }
+    }
+
+    /// Apply a command from the terminal, sending any change to the world to every client
+    pub async fn run_admin_command(&self, command: AdminCommand) {
+        match command {
+            AdminCommand::Seed(seed) => {
+                let seed = seed.unwrap_or_else(rand::random::<u32>);
+                *self.seed.write().await = seed;
+                if let Some(recorder) = &self.recorder {
+                    recorder.record(SessionEntry::WorldChanged { time: recorder.elapsed(), seed });
+                }
+                println!("🌍 Map changed to seed {}", seed);
+            }
+            AdminCommand::Time(time_of_day) => {
+                *self.time_of_day.write().await = time_of_day;
+                println!("🕑 Time of day set to {:.3}", time_of_day);
+            }
+            AdminCommand::Speed(speed) => {
+                *self.speed.write().await = speed;
+                println!("🕑 Day speed set to {}", speed);
+            }
+            AdminCommand::Limits(Some((plane_type, limits))) => {
+                self.limits.write().await.set(plane_type, limits);
+                println!("🛡 {:?} limits set to {} units/s and {} units/s²", plane_type, limits.max_speed, limits.max_acceleration);
+                return;
+            }
+            AdminCommand::Limits(None) => {
+                let limits = self.limits.read().await;
+                for (name, limits) in [("light", limits.light), ("jet", limits.jet), ("glider", limits.glider)] {
+                    println!("🛡 {:<6} {:>6} units/s {:>6} units/s²", name, limits.max_speed, limits.max_acceleration);
+                }
+                return;
+            }
+            AdminCommand::Status => {
+                println!(
+                    "🌍 Seed {}, time of day {:.3}, day speed {}, {} players",
+                    *self.seed.read().await,
+                    *self.time_of_day.read().await,
+                    *self.speed.read().await,
+                    self.players.read().await.len(),
+                );
+                return;
+            }
+            AdminCommand::Help => {
+                println!("{}", admin::HELP);
+                return;
+            }
+        }
+        let update = ServerMessage::WorldUpdate {
+            seed: *self.seed.read().await,
+            time_of_day: *self.time_of_day.read().await,
+            speed: *self.speed.read().await,
+        };
+        self.broadcast(update, None).await;
+    }
+
+    /// Run a pilot's update through the movement checks, telling everyone the first time they fail one.
+    /// Returns the pilot's flag to carry in their `PlayerState`
+    async fn check_movement(&self, player_id: PlayerId, position: [f32; 3], plane_type: PlaneType) -> Option<String> {
+        let others: Vec<[f32; 3]> = self
+            .players
+            .read()
+            .await
+            .values()
+            .filter(|player| player.id != player_id)
+            .map(|player| player.position)
+            .collect();
+        let limits = self.limits.read().await.clone();
+        let mut movement = self.movement.write().await;
+        let tracker = movement.get_mut(&player_id)?;
+        let violation = tracker.record(std::time::Instant::now(), position, plane_type, &limits, &others);
+        let flag = tracker.flag.as_ref().map(|flag| flag.to_string());
+        drop(movement);
+
+        if let Some(violation) = violation {
+            println!("🛡 Flagged player {}: {}", player_id, violation);
+            self.broadcast(ServerMessage::PlayerFlagged { id: player_id, reason: violation.to_string() }, None).await;
+        }
+        flag
+    }
+
+    async fn leaderboard_message(&self) -> ServerMessage {
+        ServerMessage::Leaderboard {
+            entries: self.leaderboard.read().await.entries.clone(),
+        }
+    }
+
+    /// Validate a finished run against the server's view of it and rank it if it holds up
+    async fn finish_time_trial(&self, player_id: PlayerId, time: f32) {
+        let Some(run) = self.trial_runs.write().await.remove(&player_id) else {
+            self.send_to(player_id, ServerMessage::TimeTrialResult {
+                accepted: false,
+                message: "No time trial in progress".to_string(),
+            }).await;
+            return;
+        };
+        if let Err(reason) = run.validate(time, &self.course) {
+            println!("🚫 Rejected time trial from player {}: {}", player_id, reason);
+            self.send_to(player_id, ServerMessage::TimeTrialResult {
+                accepted: false,
+                message: format!("Time rejected: {}", reason),
+            }).await;
+            return;
+        }
+
+        let Some(player) = self.players.read().await.get(&player_id).cloned() else { return };
+        let mut leaderboard = self.leaderboard.write().await;
+        let rank = leaderboard.submit(LeaderboardEntry {
+            name: player.name,
+            plane_type: player.plane_type,
+            time,
+        });
+        let message = match rank {
+            Some(rank) => {
+                if let Err(e) = leaderboard.save(LEADERBOARD_PATH).await {
+                    eprintln!("❌ Failed to save leaderboard: {}", e);
+                }
+                println!("🏁 Player {} set a time of {:.2}s (rank {})", player_id, time, rank);
+                format!("{:.2}s, rank {} on the leaderboard", time, rank)
+            }
+            None => format!("{:.2}s, not a personal best or top time", time),
+        };
+        drop(leaderboard);
+
+        self.send_to(player_id, ServerMessage::TimeTrialResult { accepted: true, message }).await;
+        if rank.is_some() {
+            let leaderboard = self.leaderboard_message().await;
+            self.broadcast(leaderboard, None).await;
+        }
+    }
+
+    /// Today's daily board, emptied first if the day has turned over since it was last used
+    async fn daily_leaderboard_message(&self) -> ServerMessage {
+        let day = time_trial::current_day();
+        let mut leaderboard = self.daily_leaderboard.write().await;
+        leaderboard.reset_for(time_trial::daily_seed(day));
+        ServerMessage::DailyLeaderboard { day, entries: leaderboard.entries.clone() }
+    }
+
+    /// Rank a daily-flight time. The run was flown offline, so all the server can check is that it was
+    /// flown today and no faster than the course allows
+    async fn submit_daily_time(&self, player_id: PlayerId, name: &str, day: u32, time: f32, plane_type: PlaneType) {
+        let today = time_trial::current_day();
+        let seed = time_trial::daily_seed(today);
+        let rejection = if day != today {
+            Some("that day's flight has closed".to_string())
+        } else if !time.is_finite() || time <= 0.0 || time < time_trial::min_course_time(&time_trial::generate_course(seed)) {
+            Some(format!("{:.2}s is quicker than the course can be flown", time))
+        } else {
+            None
+        };
+        if let Some(reason) = rejection {
+            println!("🚫 Rejected daily time from player {}: {}", player_id, reason);
+            self.send_to(player_id, ServerMessage::DailyResult {
+                accepted: false,
+                message: format!("Time rejected: {}", reason),
+            }).await;
+            return;
+        }
+
+        let mut leaderboard = self.daily_leaderboard.write().await;
+        leaderboard.reset_for(seed);
+        let rank = leaderboard.submit(LeaderboardEntry { name: name.to_string(), plane_type, time });
+        let message = match rank {
+            Some(rank) => {
+                if let Err(e) = leaderboard.save(DAILY_LEADERBOARD_PATH).await {
+                    eprintln!("❌ Failed to save daily leaderboard: {}", e);
+                }
+                println!("📅 Player {} flew the daily in {:.2}s (rank {})", player_id, time, rank);
+                format!("{:.2}s, rank {} today", time, rank)
+            }
+            None => format!("{:.2}s, not a personal best or top time", time),
+        };
+        drop(leaderboard);
+
+        self.send_to(player_id, ServerMessage::DailyResult { accepted: true, message }).await;
+        let leaderboard = self.daily_leaderboard_message().await;
+        match rank {
+            Some(_) => self.broadcast(leaderboard, None).await,
+            None => self.send_to(player_id, leaderboard).await,
+        }
+    }
+}
+
+/// Keep the clock turning and roll for microbursts and lightning for as long as the server runs
+pub fn spawn_world_tasks(server: &Arc<GameServer>) {
+    let server_clone = Arc::clone(server);
+    tokio::spawn(async move {
+        let mut last_update = std::time::Instant::now();
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_millis(16)).await;
+            let now = std::time::Instant::now();
+            let delta_secs = (now - last_update).as_secs_f32();
+            last_update = now;
+
+            let mut time = server_clone.time_of_day.write().await;
+            let speed = *server_clone.speed.read().await;
+            *time = (*time + speed * delta_secs) % 1.0;
+        }
+    });
+
+    let server_clone = Arc::clone(server);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(MICROBURST_INTERVAL));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            server_clone.schedule_microburst().await;
+        }
+    });
+
+    let server_clone = Arc::clone(server);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(LIGHTNING_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            server_clone.strike_lightning().await;
+        }
+    });
+}
+
+/// Accept connections forever, running each client's session on its own task
+pub async fn serve(server: Arc<GameServer>, listener: TcpListener, tls_acceptor: Option<TlsAcceptor>) {
+    loop {
+        match listener.accept().await {
+            Ok((socket, addr)) => {
+                println!("🔌 New connection from: {}", addr);
+                let server = Arc::clone(&server);
+                let tls_acceptor = tls_acceptor.clone();
+                tokio::spawn(async move {
+                    let stream = match negotiate_transport(socket, tls_acceptor).await {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            eprintln!("❌ Handshake with {} failed: {}", addr, e);
+                            return;
+                        }
+                    };
+                    handle_client(server, stream).await;
+                });
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to accept connection: {}", e);
+            }
+        }
+    }
+}
+
+/// Tell the client which transport to use, then upgrade the socket to TLS if enabled
+async fn negotiate_transport(
+    mut socket: TcpStream,
+    tls_acceptor: Option<TlsAcceptor>,
+) -> Result<BoxedStream, Box<dyn std::error::Error + Send + Sync>> {
+    socket.set_nodelay(true)?;
+
+    match tls_acceptor {
+        Some(acceptor) => {
+            socket.write_all(&[TRANSPORT_TLS]).await?;
+            let tls_stream = acceptor.accept(socket).await?;
+            Ok(Box::new(tls_stream))
+        }
+        None => {
+            socket.write_all(&[TRANSPORT_PLAIN]).await?;
+            Ok(Box::new(socket))
+        }
+    }
+}
+
+async fn handle_client(server: Arc<GameServer>, stream: BoxedStream) {
+    let (mut read_half, mut write_half) = tokio::io::split(stream);
+
+    let (name, role) = match codec::read_message::<ClientMessage>(&mut read_half).await {
+        Ok(Some(ClientMessage::Join { name, role })) => (name, role),
+        Ok(Some(other)) => {
+            eprintln!("❌ Expected Join as the first message, got {:?}", other);
+            return;
+        }
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("❌ Failed to read Join: {}", e);
+            return;
+        }
+    };
+
+    let player_id = server.get_next_id().await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<ServerMessage>();
+    
+    server.senders.write().await.insert(player_id, tx);
+
+    let server_clone = Arc::clone(&server);
+    let write_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            match codec::write_message(&mut write_half, &message).await {
+                Ok(()) => {}
+                Err(e) if e.is_recoverable() => eprintln!("❌ Dropped a message to player {}: {}", player_id, e),
+                Err(e) => {
+                    eprintln!("❌ Failed to send to player {}: {}", player_id, e);
+                    break;
+                }
+            }
+        }
+    });
+
+    let existing_players: Vec<PlayerState> = server.players.read().await.values().cloned().collect();
+    // Observers never fly, so they don't take up a spawn slot
+    let spawn_point = match role {
+        ClientRole::Pilot => server.assign_spawn_point(player_id).await,
+        ClientRole::Observer => [0.0, 0.0],
+    };
+    if role == ClientRole::Pilot {
+        server.movement.write().await.insert(player_id, MovementTracker::new(spawn_point));
+    }
+    
+    let welcome = ServerMessage::Welcome {
+        your_id: player_id,
+        seed: *server.seed.read().await,
+        existing_players,
+        time_of_day: *server.time_of_day.read().await,
+        speed: *server.speed.read().await,
+        spawn_point,
+    };
+    
+    server.send_to(player_id, welcome).await;
+    server.send_to(player_id, server.leaderboard_message().await).await;
+    server.send_replicas(player_id).await;
+
+    match role {
+        ClientRole::Pilot => println!("✨ Player {} ({}) joined (total: {})", player_id, name, server.players.read().await.len() + 1),
+        ClientRole::Observer => println!("🗼 Observer {} ({}) joined", player_id, name),
+    }
+
+    loop {
+        match codec::read_message::<ClientMessage>(&mut read_half).await {
+            Ok(Some(msg)) => {
+                match msg {
+                    ClientMessage::Join { .. } => {
+                    }
+                    ClientMessage::UpdatePosition { .. } if role == ClientRole::Observer => {
+                    }
+                    ClientMessage::UpdatePosition { name, position, rotation, plane_type, smoke } => {
+                        let flagged = server.check_movement(player_id, position, plane_type).await;
+                        let player_state = PlayerState {
+                            id: player_id,
+                            name,
+                            position,
+                            rotation,
+                            plane_type,
+                            smoke,
+                            flagged,
+                        };
+
+                        if let Some(recorder) = &server.recorder {
+                            recorder.record(SessionEntry::Position { time: recorder.elapsed(), player: player_state.clone() });
+                        }
+
+                        if let Some(run) = server.trial_runs.write().await.get_mut(&player_id) {
+                            run.record(std::time::Instant::now(), position, &server.course);
+                        }
+
+                        let mut players = server.players.write().await;
+                        let is_new = !players.contains_key(&player_id);
+                        players.insert(player_id, player_state.clone());
+                        drop(players);
+
+                        if is_new {
+                            server.broadcast(
+                                ServerMessage::PlayerJoined {
+                                    player: player_state,
+                                },
+                                Some(player_id),
+                            ).await;
+                        } else {
+                            server.broadcast(
+                                ServerMessage::PlayerUpdate {
+                                    id: player_id,
+                                    name: player_state.name.clone(),
+                                    position,
+                                    rotation,
+                                    plane_type,
+                                    smoke,
+                                },
+                                Some(player_id),
+                            ).await;
+                        }
+                    }
+                    // Observers have no aircraft to fire from, guns to hit with or aircraft to lose
+                    ClientMessage::Fire { .. } | ClientMessage::Hit { .. } | ClientMessage::ShotDown { .. }
+                        if role == ClientRole::Observer => {
+                    }
+                    ClientMessage::Fire { position, velocity } => {
+                        server.broadcast(
+                            ServerMessage::Fire { id: player_id, position, velocity },
+                            Some(player_id),
+                        ).await;
+                    }
+                    ClientMessage::Hit { target } => {
+                        // Shooters detect their own hits; the server only routes them to the target
+                        if target != player_id && server.players.read().await.contains_key(&target) {
+                            server.send_to(target, ServerMessage::Hit { shooter: player_id }).await;
+                        }
+                    }
+                    ClientMessage::ShotDown { by } => {
+                        // Credited kills go on the scoreboards, so only to another pilot in the session
+                        if by != player_id && server.players.read().await.contains_key(&by) {
+                            println!("💥 Player {} shot down by player {}", player_id, by);
+                            server.broadcast(
+                                ServerMessage::ShotDown { id: player_id, by },
+                                Some(player_id),
+                            ).await;
+                        }
+                    }
+                    ClientMessage::StartTimeTrial => {
+                        server.trial_runs.write().await.insert(player_id, TrialRun::default());
+                        server.send_to(player_id, ServerMessage::TimeTrialCourse {
+                            gates: server.course.clone(),
+                            gate_radius: time_trial::GATE_RADIUS,
+                        }).await;
+                    }
+                    ClientMessage::FinishTimeTrial { time } => {
+                        server.finish_time_trial(player_id, time).await;
+                    }
+                    ClientMessage::RequestLeaderboard => {
+                        server.send_to(player_id, server.leaderboard_message().await).await;
+                    }
+                    ClientMessage::SubmitDailyTime { day, time, plane_type } => {
+                        server.submit_daily_time(player_id, &name, day, time, plane_type).await;
+                    }
+                    ClientMessage::RequestDailyLeaderboard => {
+                        server.send_to(player_id, server.daily_leaderboard_message().await).await;
+                    }
+                    ClientMessage::Voice { frame } => {
+                        server.relay_voice(player_id, frame).await;
+                    }
+                    ClientMessage::Instruction { target, text } => {
+                        if role != ClientRole::Observer {
+                            continue;
+                        }
+                        let text: String = text.chars().take(MAX_INSTRUCTION_LENGTH).collect();
+                        println!("🗼 {} -> {}: {}", name, target.map_or("all".to_string(), |id| format!("player {}", id)), text);
+                        let message = ServerMessage::Instruction { from: name.clone(), text };
+                        match target {
+                            Some(target) => server.send_to(target, message).await,
+                            None => server.broadcast(message, Some(player_id)).await,
+                        }
+                    }
+                    ClientMessage::Replicate { entity, kind, data } => {
+                        server.replicate(player_id, entity, kind, data).await;
+                    }
+                    ClientMessage::DespawnReplica { entity } => {
+                        server.despawn_replica(player_id, entity).await;
+                    }
+                    ClientMessage::Disconnect => {
+                        println!("👋 Player {} disconnected gracefully", player_id);
+                        break;
+                    }
+                }
+            }
+            Ok(None) => {
+                println!("👋 Player {} disconnected", player_id);
+                break;
+            }
+            Err(e) if e.is_recoverable() => {
+                eprintln!("❌ Skipped a message from player {}: {}", player_id, e);
+            }
+            Err(e) => {
+                eprintln!("❌ Error reading from player {}: {}", player_id, e);
+                break;
+            }
+        }
+    }
+
+    cleanup_player(&server_clone, player_id).await;
+    write_task.abort();
+}
+
+async fn cleanup_player(server: &GameServer, player_id: PlayerId) {
+    server.players.write().await.remove(&player_id);
+    server.senders.write().await.remove(&player_id);
+    server.spawn_slots.write().await.remove(&player_id);
+    server.trial_runs.write().await.remove(&player_id);
+    server.movement.write().await.remove(&player_id);
+    // Clients drop a leaving player's replicas along with them
+    server.replicas.write().await.remove(&player_id);
+    if let Some(recorder) = &server.recorder {
+        recorder.record(SessionEntry::Left { time: recorder.elapsed(), id: player_id });
+    }
+    
+    server.broadcast(
+        ServerMessage::PlayerLeft { id: player_id },
+        None,
+    ).await;
+    
+    println!("🧹 Player {} cleaned up (remaining: {})", player_id, server.players.read().await.len());
+}
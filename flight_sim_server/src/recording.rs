@@ -0,0 +1,72 @@
+use flight_sim_protocol::messages::{SessionEntry, SESSION_FORMAT_VERSION};
+use std::time::Instant;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc;
+
+/// Parse `--record <path>` from the command line
+pub fn parse_record_args(args: impl IntoIterator<Item = String>) -> Result<Option<String>, String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--record" {
+            return args.next().map(Some).ok_or_else(|| "--record needs a path".to_string());
+        }
+    }
+    Ok(None)
+}
+
+/// Appends every pilot update of the session to a file for clients to play back later.
+/// Writes happen on their own task so a slow disk never holds up the relay
+pub struct SessionRecorder {
+    sender: mpsc::UnboundedSender<SessionEntry>,
+    started: Instant,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &str, seed: u32) -> std::io::Result<Self> {
+        let file = tokio::fs::File::from_std(std::fs::File::create(path)?);
+        let (sender, mut receiver) = mpsc::unbounded_channel::<SessionEntry>();
+        let path = path.to_string();
+        tokio::spawn(async move {
+            let mut writer = BufWriter::new(file);
+            while let Some(entry) = receiver.recv().await {
+                let mut result = write_entry(&mut writer, &entry).await;
+                // Batch whatever else has queued up behind it into the same flush
+                while result.is_ok() {
+                    let Ok(entry) = receiver.try_recv() else { break };
+                    result = write_entry(&mut writer, &entry).await;
+                }
+                if let Err(e) = result {
+                    eprintln!("❌ Stopped recording to {}: {}", path, e);
+                    return;
+                }
+                if let Err(e) = writer.flush().await {
+                    eprintln!("❌ Stopped recording to {}: {}", path, e);
+                    return;
+                }
+            }
+        });
+
+        let recorder = Self { sender, started: Instant::now() };
+        recorder.record(SessionEntry::Start { version: SESSION_FORMAT_VERSION, seed });
+        Ok(recorder)
+    }
+
+    /// Seconds since the recording started, for timestamping entries
+    pub fn elapsed(&self) -> f32 {
+        self.started.elapsed().as_secs_f32()
+    }
+
+    pub fn record(&self, entry: SessionEntry) {
+        let _ = self.sender.send(entry);
+    }
+}
+
+async fn write_entry(
+    writer: &mut BufWriter<tokio::fs::File>,
+    entry: &SessionEntry,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = bincode::serialize(entry)?;
+    writer.write_all(&(data.len() as u32).to_le_bytes()).await?;
+    writer.write_all(&data).await?;
+    Ok(())
+}
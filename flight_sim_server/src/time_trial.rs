@@ -0,0 +1,207 @@
+use flight_sim_protocol::messages::LeaderboardEntry;
+use serde::{Deserialize, Serialize};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+pub const LEADERBOARD_PATH: &str = "leaderboard.bin";
+pub const DAILY_LEADERBOARD_PATH: &str = "daily_leaderboard.bin";
+const LEADERBOARD_SIZE: usize = 20;
+const MAX_NAME_LENGTH: usize = 24;
+
+const GATE_COUNT: usize = 8;
+pub const GATE_RADIUS: f32 = 200.0;
+/// Extra radius the server allows on top of the client's gate, for sampling and smoothing
+const GATE_SLACK: f32 = 100.0;
+const FIRST_GATE_DISTANCE: f32 = 1500.0;
+const MIN_LEG: f32 = 2000.0;
+const MAX_LEG: f32 = 3500.0;
+const MAX_TURN: f32 = 0.9;
+
+/// Fastest any aircraft preset can fly, in world units per second, with headroom for dives
+const MAX_SPEED: f32 = 4500.0;
+/// Updates can arrive bunched up behind a network stall
+const JITTER_ALLOWANCE: f32 = 0.25;
+/// How far a reported time may drift from the time measured between update arrivals
+const TIME_TOLERANCE: f32 = 1.5;
+const TIME_TOLERANCE_FRACTION: f32 = 0.03;
+/// Keeps daily seeds apart from course seeds that happen to equal a day number
+const DAILY_SALT: u64 = 0xDA11_F11E;
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Deterministic hash in `0..1` so a course seed always lays out the same gates
+pub fn splitmix(state: &mut u64) -> f32 {
+    (splitmix64(state) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Whole days since the Unix epoch in UTC, so the daily flight changes at the same moment everywhere
+pub fn current_day() -> u32 {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    (seconds / 86_400) as u32
+}
+
+/// Seed of day `day`'s daily flight; its world, weather and gate course all follow from it
+pub fn daily_seed(day: u32) -> u32 {
+    let mut state = DAILY_SALT ^ day as u64;
+    (splitmix64(&mut state) >> 32) as u32
+}
+
+/// Quickest the course could be flown at all, at top speed and cutting every gate at the edge of its radius
+pub fn min_course_time(course: &[[f32; 2]]) -> f32 {
+    let shortest: f32 = course
+        .windows(2)
+        .map(|leg| {
+            let length = ((leg[1][0] - leg[0][0]).powi(2) + (leg[1][1] - leg[0][1]).powi(2)).sqrt();
+            (length - 2.0 * (GATE_RADIUS + GATE_SLACK)).max(0.0)
+        })
+        .sum();
+    shortest / MAX_SPEED
+}
+
+/// Lay out a meandering gate course starting near the world origin
+pub fn generate_course(seed: u32) -> Vec<[f32; 2]> {
+    let mut state = seed as u64;
+    let mut heading = splitmix(&mut state) * std::f32::consts::TAU;
+    let mut position = [heading.cos() * FIRST_GATE_DISTANCE, heading.sin() * FIRST_GATE_DISTANCE];
+    let mut gates = vec![position];
+
+    for _ in 1..GATE_COUNT {
+        heading += (splitmix(&mut state) * 2.0 - 1.0) * MAX_TURN;
+        let leg = MIN_LEG + splitmix(&mut state) * (MAX_LEG - MIN_LEG);
+        position = [position[0] + heading.cos() * leg, position[1] + heading.sin() * leg];
+        gates.push(position);
+    }
+    gates
+}
+
+/// Closest approach of the segment `from -> to` to the gate on the ground plane, as (fraction, distance)
+fn closest_approach(from: [f32; 3], to: [f32; 3], gate: [f32; 2]) -> (f32, f32) {
+    let (dx, dz) = (to[0] - from[0], to[2] - from[2]);
+    let length_squared = dx * dx + dz * dz;
+    let t = if length_squared > 0.0 {
+        (((gate[0] - from[0]) * dx + (gate[1] - from[2]) * dz) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, z) = (from[0] + dx * t - gate[0], from[2] + dz * t - gate[1]);
+    (t, (x * x + z * z).sqrt())
+}
+
+/// A player's run in progress, rebuilt from the position updates the server relays.
+/// Gates are timed at the closest approach, the same way the client times them.
+#[derive(Default)]
+pub struct TrialRun {
+    last: Option<(Instant, [f32; 3])>,
+    gate_times: Vec<Instant>,
+    /// Closest approach so far to the gate being flown through, as (distance, time)
+    pending: Option<(f32, Instant)>,
+    violation: Option<String>,
+}
+
+impl TrialRun {
+    pub fn record(&mut self, now: Instant, position: [f32; 3], course: &[[f32; 2]]) {
+        if self.violation.is_some() {
+            return;
+        }
+        if let Some((then, last)) = self.last {
+            let dt = (now - then).as_secs_f32();
+            let distance = (0..3).map(|i| (position[i] - last[i]).powi(2)).sum::<f32>().sqrt();
+            if distance > MAX_SPEED * (dt + JITTER_ALLOWANCE) {
+                self.violation = Some(format!("moved {:.0} units in {:.2}s", distance, dt));
+                return;
+            }
+            if let Some(gate) = course.get(self.gate_times.len()) {
+                let (t, gate_distance) = closest_approach(last, position, *gate);
+                if gate_distance <= GATE_RADIUS + GATE_SLACK {
+                    if self.pending.is_none_or(|(best, _)| gate_distance < best) {
+                        self.pending = Some((gate_distance, then + (now - then).mul_f32(t)));
+                    }
+                } else if let Some((_, time)) = self.pending.take() {
+                    self.gate_times.push(time);
+                }
+            }
+        }
+        self.last = Some((now, position));
+    }
+
+    /// Check a reported time against what the server saw of the run
+    pub fn validate(&self, reported: f32, course: &[[f32; 2]]) -> Result<(), String> {
+        if let Some(violation) = &self.violation {
+            return Err(format!("position jump during the run ({})", violation));
+        }
+        // The finish may still be pending if the last updates stayed inside the gate
+        let gate_times: Vec<Instant> = self.gate_times.iter().copied().chain(self.pending.map(|(_, time)| time)).collect();
+        if gate_times.len() < course.len() {
+            return Err(format!("gate {} was never reached", gate_times.len() + 1));
+        }
+        if !reported.is_finite() || reported <= 0.0 {
+            return Err("invalid time".to_string());
+        }
+        let measured = (gate_times[course.len() - 1] - gate_times[0]).as_secs_f32();
+        if (reported - measured).abs() > TIME_TOLERANCE + measured * TIME_TOLERANCE_FRACTION {
+            return Err(format!("reported {:.2}s but the server measured {:.2}s", reported, measured));
+        }
+        Ok(())
+    }
+}
+
+/// The persisted board; the course seed is kept with it so times stay comparable across restarts
+#[derive(Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub course_seed: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn load_or_new(path: &str) -> Self {
+        match std::fs::read(path) {
+            Ok(bytes) => match bincode::deserialize(&bytes) {
+                Ok(leaderboard) => return leaderboard,
+                Err(e) => eprintln!("❌ Ignoring unreadable leaderboard {}: {}", path, e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("❌ Failed to read leaderboard {}: {}", path, e),
+        }
+        Self {
+            course_seed: rand::random::<u32>(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub async fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tokio::fs::write(path, bincode::serialize(self)?).await?;
+        Ok(())
+    }
+
+    /// Move the board onto another course, dropping the times set on the old one
+    pub fn reset_for(&mut self, course_seed: u32) {
+        if self.course_seed != course_seed {
+            self.course_seed = course_seed;
+            self.entries.clear();
+        }
+    }
+
+    /// Keep each name's best time; returns the 1-based rank if the time made the board
+    pub fn submit(&mut self, mut entry: LeaderboardEntry) -> Option<usize> {
+        entry.name = entry.name.trim().chars().take(MAX_NAME_LENGTH).collect();
+        if let Some(existing) = self.entries.iter().position(|e| e.name == entry.name) {
+            if self.entries[existing].time <= entry.time {
+                return None;
+            }
+            self.entries.remove(existing);
+        }
+
+        let rank = self.entries.partition_point(|e| e.time <= entry.time);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank + 1)
+    }
+}
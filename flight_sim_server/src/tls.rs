@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::rustls::{
+    self,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+};
+use tokio_rustls::TlsAcceptor;
+
+/// Any byte stream a client session can run over, plain TCP or TLS-wrapped.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncStream for T {}
+
+pub type BoxedStream = Box<dyn AsyncStream>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsMode {
+    Disabled,
+    SelfSigned,
+    Files { cert_path: String, key_path: String },
+}
+
+/// Parse `--tls-self-signed` or `--tls-cert <pem> --tls-key <pem>` from the command line
+pub fn parse_tls_args(args: impl IntoIterator<Item = String>) -> Result<TlsMode, String> {
+    let mut self_signed = false;
+    let mut cert_path = None;
+    let mut key_path = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tls-self-signed" => self_signed = true,
+            "--tls-cert" => cert_path = Some(args.next().ok_or("--tls-cert needs a path")?),
+            "--tls-key" => key_path = Some(args.next().ok_or("--tls-key needs a path")?),
+            _ => {}
+        }
+    }
+
+    match (cert_path, key_path) {
+        (Some(cert_path), Some(key_path)) => Ok(TlsMode::Files { cert_path, key_path }),
+        (None, None) if self_signed => Ok(TlsMode::SelfSigned),
+        (None, None) => Ok(TlsMode::Disabled),
+        _ => Err("--tls-cert and --tls-key must be given together".to_string()),
+    }
+}
+
+/// Build the acceptor used to wrap incoming sockets, or `None` for plaintext
+pub fn build_acceptor(mode: &TlsMode) -> Result<Option<TlsAcceptor>, String> {
+    let (certs, key) = match mode {
+        TlsMode::Disabled => return Ok(None),
+        TlsMode::SelfSigned => {
+            let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+                .map_err(|e| format!("Failed to generate certificate: {}", e))?;
+            let key = PrivateKeyDer::Pkcs8(generated.signing_key.serialize_der().into());
+            (vec![generated.cert.der().clone()], key)
+        }
+        TlsMode::Files { cert_path, key_path } => {
+            let certs = CertificateDer::pem_file_iter(cert_path)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| format!("Failed to read {}: {}", cert_path, e))?;
+            let key = PrivateKeyDer::from_pem_file(key_path)
+                .map_err(|e| format!("Failed to read {}: {}", key_path, e))?;
+            (certs, key)
+        }
+    };
+
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|e| format!("Invalid certificate or key: {}", e))?;
+
+    Ok(Some(TlsAcceptor::from(Arc::new(config))))
+}
@@ -0,0 +1,330 @@
+//! Whole sessions against a real server on an ephemeral port, with bare protocol clients standing in for the game
+
+use flight_sim_protocol::codec;
+use flight_sim_protocol::messages::{ClientMessage, ClientRole, PlaneType, ServerMessage, TRANSPORT_PLAIN};
+use flight_sim_server::GameServer;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Long enough for a loaded CI machine, short enough that a missing message fails the test quickly
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait before deciding a message is never coming
+const QUIET_PERIOD: Duration = Duration::from_millis(300);
+
+async fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = Arc::new(GameServer::new(None));
+    tokio::spawn(flight_sim_server::serve(server, listener, None));
+    addr
+}
+
+struct TestClient {
+    stream: TcpStream,
+    id: u32,
+    spawn_point: [f32; 2],
+}
+
+impl TestClient {
+    /// Connect, join and wait for the welcome
+    async fn join(addr: SocketAddr, name: &str, role: ClientRole) -> (Self, Vec<u32>) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(stream.read_u8().await.unwrap(), TRANSPORT_PLAIN);
+        codec::write_message(&mut stream, &ClientMessage::Join { name: name.to_string(), role }).await.unwrap();
+
+        let mut client = Self { stream, id: 0, spawn_point: [0.0, 0.0] };
+        let ServerMessage::Welcome { your_id, existing_players, spawn_point, .. } = client.recv().await else {
+            panic!("expected Welcome first");
+        };
+        client.id = your_id;
+        client.spawn_point = spawn_point;
+        (client, existing_players.iter().map(|player| player.id).collect())
+    }
+
+    async fn send(&mut self, message: ClientMessage) {
+        codec::write_message(&mut self.stream, &message).await.unwrap();
+    }
+
+    async fn fly_to(&mut self, position: [f32; 3]) {
+        self.send(ClientMessage::UpdatePosition {
+            name: format!("Pilot {}", self.id),
+            position,
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            plane_type: PlaneType::Light,
+            smoke: None,
+        })
+        .await;
+    }
+
+    async fn recv(&mut self) -> ServerMessage {
+        tokio::time::timeout(RECEIVE_TIMEOUT, codec::read_message::<ServerMessage>(&mut self.stream))
+            .await
+            .expect("timed out waiting for the server")
+            .unwrap()
+            .expect("server closed the connection")
+    }
+
+    /// Skip the leaderboard and other chatter until a message `matches` picks out
+    async fn recv_matching<T>(&mut self, mut matches: impl FnMut(ServerMessage) -> Option<T>) -> T {
+        loop {
+            if let Some(found) = matches(self.recv().await) {
+                return found;
+            }
+        }
+    }
+
+    /// Fail if a message `matches` picks out arrives within the quiet period
+    async fn expect_none<T: std::fmt::Debug>(&mut self, mut matches: impl FnMut(ServerMessage) -> Option<T>) {
+        let deadline = tokio::time::Instant::now() + QUIET_PERIOD;
+        while let Ok(message) = tokio::time::timeout_at(deadline, codec::read_message::<ServerMessage>(&mut self.stream)).await {
+            let Some(message) = message.unwrap() else { return };
+            if let Some(found) = matches(message) {
+                panic!("unexpected message: {:?}", found);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn join_is_welcomed_with_an_id_and_the_leaderboard() {
+    let addr = start_server().await;
+    let (mut first, existing) = TestClient::join(addr, "First", ClientRole::Pilot).await;
+    assert!(existing.is_empty());
+    first.recv_matching(|message| matches!(message, ServerMessage::Leaderboard { .. }).then_some(())).await;
+
+    let (second, _) = TestClient::join(addr, "Second", ClientRole::Pilot).await;
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.spawn_point, second.spawn_point, "pilots get their own spawn slots");
+}
+
+#[tokio::test]
+async fn first_update_announces_the_pilot_and_later_ones_are_relayed() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+
+    alice.fly_to([10.0, 500.0, 20.0]).await;
+    let joined = bob
+        .recv_matching(|message| match message {
+            ServerMessage::PlayerJoined { player } => Some(player),
+            _ => None,
+        })
+        .await;
+    assert_eq!(joined.id, alice.id);
+    assert_eq!(joined.position, [10.0, 500.0, 20.0]);
+
+    alice.fly_to([30.0, 500.0, 20.0]).await;
+    let (id, position) = bob
+        .recv_matching(|message| match message {
+            ServerMessage::PlayerUpdate { id, position, .. } => Some((id, position)),
+            _ => None,
+        })
+        .await;
+    assert_eq!(id, alice.id);
+    assert_eq!(position, [30.0, 500.0, 20.0]);
+
+    // Nobody is sent their own updates
+    alice
+        .expect_none(|message| match message {
+            ServerMessage::PlayerJoined { player } => Some(player.id),
+            ServerMessage::PlayerUpdate { id, .. } => Some(id),
+            _ => None,
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn late_joiner_is_welcomed_with_the_pilots_already_flying() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+    alice.fly_to([0.0, 500.0, 0.0]).await;
+    bob.recv_matching(|message| matches!(message, ServerMessage::PlayerJoined { .. }).then_some(())).await;
+
+    let (_carol, existing) = TestClient::join(addr, "Carol", ClientRole::Pilot).await;
+    assert_eq!(existing, vec![alice.id]);
+}
+
+#[tokio::test]
+async fn leaving_is_broadcast_whether_graceful_or_not() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+    let (carol, _) = TestClient::join(addr, "Carol", ClientRole::Pilot).await;
+
+    bob.send(ClientMessage::Disconnect).await;
+    let left = alice
+        .recv_matching(|message| match message {
+            ServerMessage::PlayerLeft { id } => Some(id),
+            _ => None,
+        })
+        .await;
+    assert_eq!(left, bob.id);
+
+    let carol_id = carol.id;
+    drop(carol);
+    let left = alice
+        .recv_matching(|message| match message {
+            ServerMessage::PlayerLeft { id } => Some(id),
+            _ => None,
+        })
+        .await;
+    assert_eq!(left, carol_id);
+}
+
+#[tokio::test]
+async fn observers_are_never_announced_as_pilots() {
+    let addr = start_server().await;
+    let (mut pilot, _) = TestClient::join(addr, "Pilot", ClientRole::Pilot).await;
+    let (mut observer, _) = TestClient::join(addr, "Tower", ClientRole::Observer).await;
+
+    observer.fly_to([0.0, 500.0, 0.0]).await;
+    pilot
+        .expect_none(|message| match message {
+            ServerMessage::PlayerJoined { player } => Some(player.id),
+            _ => None,
+        })
+        .await;
+
+    observer.send(ClientMessage::Instruction { target: Some(pilot.id), text: "Climb to 3000".to_string() }).await;
+    let (from, text) = pilot
+        .recv_matching(|message| match message {
+            ServerMessage::Instruction { from, text } => Some((from, text)),
+            _ => None,
+        })
+        .await;
+    assert_eq!((from.as_str(), text.as_str()), ("Tower", "Climb to 3000"));
+
+    // Observers have no guns, and no aircraft to be shot down in
+    pilot.fly_to([0.0, 500.0, 0.0]).await;
+    observer.send(ClientMessage::Fire { position: [0.0; 3], velocity: [0.0, 0.0, -1.0] }).await;
+    observer.send(ClientMessage::Hit { target: pilot.id }).await;
+    observer.send(ClientMessage::ShotDown { by: pilot.id }).await;
+    pilot
+        .expect_none(|message| match message {
+            ServerMessage::Fire { id, .. } | ServerMessage::Hit { shooter: id } | ServerMessage::ShotDown { id, .. } => Some(id),
+            _ => None,
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn kills_are_only_credited_to_other_pilots_in_the_session() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+    alice.fly_to([0.0, 500.0, 0.0]).await;
+    bob.fly_to([100.0, 500.0, 0.0]).await;
+
+    bob.send(ClientMessage::ShotDown { by: bob.id }).await;
+    bob.send(ClientMessage::ShotDown { by: 9999 }).await;
+    alice
+        .expect_none(|message| match message {
+            ServerMessage::ShotDown { id, by } => Some((id, by)),
+            _ => None,
+        })
+        .await;
+
+    bob.send(ClientMessage::ShotDown { by: alice.id }).await;
+    let shot_down = alice
+        .recv_matching(|message| match message {
+            ServerMessage::ShotDown { id, by } => Some((id, by)),
+            _ => None,
+        })
+        .await;
+    assert_eq!(shot_down, (bob.id, alice.id));
+}
+
+#[tokio::test]
+async fn replicated_entities_reach_everyone_including_late_joiners() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+
+    alice.send(ClientMessage::Replicate { entity: 7, kind: "Balloon".to_string(), data: vec![1, 2, 3] }).await;
+    alice.send(ClientMessage::Replicate { entity: 7, kind: "Balloon".to_string(), data: vec![4, 5, 6] }).await;
+    let replicate = |message| match message {
+        ServerMessage::Replicate { owner, entity, kind, data } => Some((owner, entity, kind, data)),
+        _ => None,
+    };
+    assert_eq!(bob.recv_matching(replicate).await, (alice.id, 7, "Balloon".to_string(), vec![1, 2, 3]));
+    assert_eq!(bob.recv_matching(replicate).await, (alice.id, 7, "Balloon".to_string(), vec![4, 5, 6]));
+
+    // Newcomers only get the latest state
+    let (mut carol, _) = TestClient::join(addr, "Carol", ClientRole::Pilot).await;
+    assert_eq!(carol.recv_matching(replicate).await, (alice.id, 7, "Balloon".to_string(), vec![4, 5, 6]));
+
+    alice.send(ClientMessage::DespawnReplica { entity: 7 }).await;
+    let despawned = carol
+        .recv_matching(|message| match message {
+            ServerMessage::DespawnReplica { owner, entity } => Some((owner, entity)),
+            _ => None,
+        })
+        .await;
+    assert_eq!(despawned, (alice.id, 7));
+
+    let (mut dave, _) = TestClient::join(addr, "Dave", ClientRole::Pilot).await;
+    dave.expect_none(replicate).await;
+}
+
+#[tokio::test]
+async fn daily_times_from_other_days_or_too_quick_to_fly_are_refused() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Observer).await;
+    let today = flight_sim_server::time_trial::current_day();
+    let result = |message| match message {
+        ServerMessage::DailyResult { accepted, message } => Some((accepted, message)),
+        _ => None,
+    };
+
+    alice.send(ClientMessage::SubmitDailyTime { day: today - 1, time: 300.0, plane_type: PlaneType::Light }).await;
+    let (accepted, message) = alice.recv_matching(result).await;
+    assert!(!accepted);
+    assert!(message.contains("closed"), "{}", message);
+
+    alice.send(ClientMessage::SubmitDailyTime { day: today, time: 0.5, plane_type: PlaneType::Jet }).await;
+    let (accepted, _) = alice.recv_matching(result).await;
+    assert!(!accepted);
+
+    alice.send(ClientMessage::RequestDailyLeaderboard).await;
+    let day = alice
+        .recv_matching(|message| match message {
+            ServerMessage::DailyLeaderboard { day, .. } => Some(day),
+            _ => None,
+        })
+        .await;
+    assert_eq!(day, today);
+}
+
+#[tokio::test]
+async fn replication_past_the_per_player_limits_is_dropped() {
+    let addr = start_server().await;
+    let (mut alice, _) = TestClient::join(addr, "Alice", ClientRole::Pilot).await;
+    let (mut bob, _) = TestClient::join(addr, "Bob", ClientRole::Pilot).await;
+    let replicate = |message| match message {
+        ServerMessage::Replicate { entity, kind, data, .. } => Some((entity, kind, data.len())),
+        _ => None,
+    };
+
+    // One entity can't collect kinds without end
+    for kind in 0..flight_sim_server::MAX_REPLICA_KINDS_PER_ENTITY {
+        alice.send(ClientMessage::Replicate { entity: 1, kind: format!("Kind{}", kind), data: vec![0] }).await;
+        bob.recv_matching(replicate).await;
+    }
+    alice.send(ClientMessage::Replicate { entity: 1, kind: "OneTooMany".to_string(), data: vec![0] }).await;
+    bob.expect_none(replicate).await;
+
+    // Nor can a client store more data than its share, however it's split up
+    let half = flight_sim_server::MAX_REPLICA_BYTES_PER_PLAYER / 2;
+    alice.send(ClientMessage::Replicate { entity: 2, kind: "Big".to_string(), data: vec![0; half] }).await;
+    assert_eq!(bob.recv_matching(replicate).await, (2, "Big".to_string(), half));
+    alice.send(ClientMessage::Replicate { entity: 3, kind: "Big".to_string(), data: vec![0; half] }).await;
+    bob.expect_none(replicate).await;
+
+    // Replacing a component only counts its new size
+    alice.send(ClientMessage::Replicate { entity: 2, kind: "Big".to_string(), data: vec![0; half] }).await;
+    assert_eq!(bob.recv_matching(replicate).await, (2, "Big".to_string(), half));
+}
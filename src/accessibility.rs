@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::hud::HudPalette;
+
+/// Per-user settings, kept outside `assets/` so the tuning file stays shared
+const SETTINGS_PATH: &str = "settings/accessibility.ron";
+
+/// HUD colors tuned for common forms of color blindness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorScheme {
+    #[default]
+    Standard,
+    /// Red-green safe for reduced green sensitivity
+    Deuteranopia,
+    /// Red-green safe for reduced red sensitivity, where reds read as dark
+    Protanopia,
+}
+
+impl ColorScheme {
+    pub const ALL: [ColorScheme; 3] = [ColorScheme::Standard, ColorScheme::Deuteranopia, ColorScheme::Protanopia];
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    pub color_scheme: ColorScheme,
+    /// Opaque windows and saturated colors
+    pub high_contrast: bool,
+    /// Soften how much turbulence and maneuvering jolt the chase camera
+    pub reduce_motion: bool,
+    /// Scales HUD text and windows
+    pub text_scale: f32,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            color_scheme: ColorScheme::Standard,
+            high_contrast: false,
+            reduce_motion: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+impl Accessibility {
+    /// `base` is the palette from the tuning file
+    fn hud_palette(&self, base: HudPalette) -> HudPalette {
+        let mut palette = base;
+        match self.color_scheme {
+            ColorScheme::Standard => {}
+            ColorScheme::Deuteranopia => {
+                palette.warning_fill = egui::Color32::from_rgba_unmultiplied(213, 94, 0, 220);
+                palette.horizon_ground = egui::Color32::from_rgb(120, 90, 30);
+            }
+            ColorScheme::Protanopia => {
+                palette.warning_fill = egui::Color32::from_rgba_unmultiplied(0, 90, 200, 220);
+                palette.horizon_ground = egui::Color32::from_rgb(120, 90, 30);
+            }
+        }
+        if self.high_contrast {
+            palette.window_fill = egui::Color32::from_rgba_unmultiplied(0, 0, 0, 230);
+            palette.warning_fill = palette.warning_fill.to_opaque();
+            palette.text = egui::Color32::WHITE;
+            palette.horizon_sky = egui::Color32::from_rgb(0, 90, 230);
+            palette.horizon_ground = egui::Color32::from_rgb(110, 60, 0);
+        }
+        palette
+    }
+}
+
+pub fn load_accessibility(mut accessibility: ResMut<Accessibility>) {
+    let Ok(text) = std::fs::read_to_string(SETTINGS_PATH) else { return };
+    match ron::from_str(&text) {
+        Ok(loaded) => {
+            *accessibility = loaded;
+            info!("Loaded accessibility settings from {}", SETTINGS_PATH);
+        }
+        Err(error) => warn!("Ignoring {}: {}", SETTINGS_PATH, error),
+    }
+}
+
+pub fn save_accessibility(accessibility: Res<Accessibility>) {
+    if !accessibility.is_changed() || accessibility.is_added() {
+        return;
+    }
+    let result = ron::ser::to_string_pretty(&*accessibility, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(SETTINGS_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", SETTINGS_PATH, error);
+    }
+}
+
+/// Layer the chosen scheme over the tuned palette, and scale the HUD
+pub fn apply_accessibility(
+    mut contexts: EguiContexts,
+    accessibility: Res<Accessibility>,
+    mut hud_palette: ResMut<HudPalette>,
+    mut ui_scale: ResMut<UiScale>,
+    mut palettes: Local<Option<(HudPalette, HudPalette)>>,
+) -> Result<(), BevyError> {
+    // Anything other than what was applied last came from the tuning file and becomes the new base
+    let base = match *palettes {
+        Some((base, applied)) if applied == *hud_palette => base,
+        _ => *hud_palette,
+    };
+    let palette = accessibility.hud_palette(base);
+    hud_palette.set_if_neq(palette);
+    *palettes = Some((base, palette));
+
+    if ui_scale.0 != accessibility.text_scale {
+        ui_scale.0 = accessibility.text_scale;
+    }
+    let ctx = contexts.ctx_mut()?;
+    if ctx.zoom_factor() != accessibility.text_scale {
+        ctx.set_zoom_factor(accessibility.text_scale);
+    }
+    Ok(())
+}
+
+/// Returns whether anything was changed
+pub fn ui_accessibility(ui: &mut egui::Ui, accessibility: &mut Accessibility) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("HUD Colors:");
+        for scheme in ColorScheme::ALL {
+            changed |= ui.selectable_value(&mut accessibility.color_scheme, scheme, format!("{:?}", scheme)).changed();
+        }
+    });
+    changed |= ui.checkbox(&mut accessibility.high_contrast, "High Contrast HUD").changed();
+    changed |= ui.checkbox(&mut accessibility.reduce_motion, "Reduce Camera Motion").changed();
+    changed |= ui.add(egui::Slider::new(&mut accessibility.text_scale, 0.75..=2.0).text("HUD Text Scale")).changed();
+    changed
+}
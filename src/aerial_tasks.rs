@@ -0,0 +1,449 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{world_units_to_meters, UnitSystem, GRAVITY};
+use crate::controls::{Aircraft, ControlMode, Wind};
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::hud::HudPalette;
+use crate::replication::{Replica, Replicate};
+use crate::world_generation::WorldGenerator;
+
+const ROPE_SEGMENTS: usize = 10;
+const ROPE_SEGMENT_LENGTH: f32 = 25.0;
+const ROPE_ITERATIONS: usize = 8;
+/// How quickly the rope's motion relaxes toward the moving air, per second
+const ROPE_AIR_DAMPING: f32 = 3.0;
+/// Tow hook distance behind the aircraft origin
+const TOW_HOOK_OFFSET: f32 = 20.0;
+const BANNER_LENGTH: f32 = 160.0;
+const BANNER_HEIGHT: f32 = 35.0;
+/// Speed bled off by the banner at the aircraft's maximum speed, per second
+const BANNER_DRAG: f32 = 40.0;
+
+const CRATE_SIZE: f32 = 12.0;
+/// Quadratic drag coefficients; terminal velocity is sqrt(gravity / drag)
+const CRATE_DRAG: f32 = 0.00076;
+const CHUTE_DRAG: f32 = 0.053;
+/// Seconds after release before the parachute opens
+const CHUTE_DELAY: f32 = 1.5;
+const DROP_COOLDOWN: f32 = 2.0;
+/// Landed crates are cleared away after this long
+const LANDED_LIFETIME: f32 = 20.0;
+
+/// Drops inside this radius of the zone centre score, more the closer they land
+const ZONE_SCORE_RADIUS: f32 = 500.0;
+const ZONE_MIN_DISTANCE: f32 = 5000.0;
+const ZONE_MAX_DISTANCE: f32 = 8000.0;
+const ZONE_BEACON_HEIGHT: f32 = 800.0;
+const ZONE_MAX_POINTS: f32 = 100.0;
+
+/// A banner on a verlet rope behind the local aircraft; B hooks it on or drops it
+#[derive(Resource, Default)]
+pub struct TowBanner {
+    pub attached: bool,
+    rope: Vec<Vec3>,
+    previous: Vec<Vec3>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DropResult {
+    pub distance: f32,
+    pub points: u32,
+}
+
+/// Cargo drop task: C releases a crate to parachute onto the current target zone
+#[derive(Resource, Default)]
+pub struct CargoDrops {
+    pub enabled: bool,
+    pub drops: u32,
+    pub score: u32,
+    pub last_result: Option<DropResult>,
+    zone: Option<Vec3>,
+    cooldown: f32,
+}
+
+#[derive(Component)]
+pub struct BannerCloth;
+
+#[derive(Component)]
+pub struct CargoCrate {
+    velocity: Vec3,
+    age: f32,
+    landed_for: Option<f32>,
+}
+
+#[derive(Component)]
+pub struct Parachute;
+
+/// The part of a falling crate other players see; crates are replicated so drops show up for everyone
+#[derive(Component, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct SharedCargo {
+    pub chute_open: bool,
+}
+
+#[derive(Resource)]
+pub struct AerialTaskAssets {
+    banner: Handle<Mesh>,
+    banner_material: Handle<StandardMaterial>,
+    cargo: Handle<Mesh>,
+    cargo_material: Handle<StandardMaterial>,
+    canopy: Handle<Mesh>,
+    canopy_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_aerial_tasks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(AerialTaskAssets {
+        banner: meshes.add(Cuboid::new(0.5, BANNER_HEIGHT, BANNER_LENGTH)),
+        banner_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.95, 0.95, 0.9),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        cargo: meshes.add(Cuboid::new(CRATE_SIZE, CRATE_SIZE, CRATE_SIZE)),
+        cargo_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.45, 0.32, 0.18),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        canopy: meshes.add(Sphere::new(1.0).mesh().uv(16, 8)),
+        canopy_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.95, 0.45, 0.1),
+            perceptual_roughness: 0.7,
+            cull_mode: None,
+            ..default()
+        }),
+    });
+}
+
+/// Stretch the rope out straight behind the tow hook
+fn reset_rope(banner: &mut TowBanner, hook: Vec3, back: Vec3) {
+    banner.rope = (0..=ROPE_SEGMENTS)
+        .map(|i| hook + back * ROPE_SEGMENT_LENGTH * i as f32)
+        .collect();
+    banner.previous = banner.rope.clone();
+}
+
+/// Simulate the tow rope, pose the banner on its end and charge its drag to the aircraft
+pub fn update_tow_banner(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    control_mode: Res<ControlMode>,
+    assets: Res<AerialTaskAssets>,
+    mut banner: ResMut<TowBanner>,
+    mut gizmos: Gizmos,
+    mut aircraft_query: Query<(&Transform, &mut Aircraft), Without<BannerCloth>>,
+    mut cloth_query: Query<(Entity, &mut Transform), With<BannerCloth>>,
+    mut commands: Commands,
+) {
+    if keyboard.just_pressed(KeyCode::KeyB) {
+        banner.attached = !banner.attached;
+        info!("Tow banner {}", if banner.attached { "hooked on" } else { "released" });
+    }
+
+    let Ok((transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    if aircraft.crashed {
+        banner.attached = false;
+    }
+
+    if !banner.attached {
+        banner.rope.clear();
+        for (entity, _) in cloth_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let back = -transform.forward().as_vec3();
+    let hook = transform.translation + back * TOW_HOOK_OFFSET;
+    // A fresh hook-up or a respawn far away snaps the rope straight again
+    if banner.rope.len() != ROPE_SEGMENTS + 1 || banner.rope[0].distance(hook) > ROPE_SEGMENT_LENGTH * ROPE_SEGMENTS as f32 {
+        reset_rope(&mut banner, hook, back);
+    }
+
+    let dt = time.delta_secs();
+    if !control_mode.physics_paused && dt > 0.0 {
+        let air = wind.wind_direction * wind.wind_speed * dt;
+        let relax = (ROPE_AIR_DAMPING * dt).min(1.0);
+        let TowBanner { rope, previous, .. } = &mut *banner;
+        for (point, last) in rope.iter_mut().zip(previous.iter_mut()).skip(1) {
+            let motion = *point - *last;
+            *last = *point;
+            *point += motion + (air - motion) * relax + Vec3::NEG_Y * GRAVITY * dt * dt;
+        }
+        rope[0] = hook;
+        previous[0] = hook;
+
+        for _ in 0..ROPE_ITERATIONS {
+            for i in 0..ROPE_SEGMENTS {
+                let delta = rope[i + 1] - rope[i];
+                let length = delta.length().max(f32::EPSILON);
+                let correction = delta * (length - ROPE_SEGMENT_LENGTH) / length;
+                if i == 0 {
+                    rope[1] -= correction;
+                } else {
+                    rope[i] += correction * 0.5;
+                    rope[i + 1] -= correction * 0.5;
+                }
+            }
+        }
+
+        let airspeed_ratio = aircraft.speed / aircraft.max_speed;
+        aircraft.speed = (aircraft.speed - BANNER_DRAG * airspeed_ratio * airspeed_ratio * dt).max(0.0);
+    }
+
+    gizmos.linestrip(banner.rope.iter().copied(), Color::srgb(0.2, 0.2, 0.2));
+
+    // The banner streams from the rope's end along its last segment
+    let end = banner.rope[ROPE_SEGMENTS];
+    let trail = (end - banner.rope[ROPE_SEGMENTS - 1]).normalize_or(back);
+    let cloth_transform = Transform::from_translation(end + trail * BANNER_LENGTH * 0.5)
+        .looking_to(trail, Vec3::Y);
+    match cloth_query.single_mut() {
+        Ok((_, mut cloth)) => *cloth = cloth_transform,
+        Err(_) => {
+            commands.spawn((
+                Mesh3d(assets.banner.clone()),
+                MeshMaterial3d(assets.banner_material.clone()),
+                cloth_transform,
+                BannerCloth,
+            ));
+        }
+    }
+}
+
+/// Pick a landing zone on dry ground somewhere ahead of the aircraft
+fn place_zone(world_gen: &WorldGenerator, from: Vec3, heading: Vec3) -> Vec3 {
+    let mut candidate = from;
+    for _ in 0..8 {
+        let angle = (rand::random::<f32>() - 0.5) * std::f32::consts::FRAC_PI_2;
+        let direction = Quat::from_rotation_y(angle) * heading;
+        let distance = ZONE_MIN_DISTANCE + rand::random::<f32>() * (ZONE_MAX_DISTANCE - ZONE_MIN_DISTANCE);
+        candidate = from + direction * distance;
+        candidate.y = world_gen.get_terrain_height(&[candidate.x, 0.0, candidate.z]);
+        if candidate.y > 0.0 {
+            break;
+        }
+    }
+    candidate.y = candidate.y.max(0.0);
+    candidate
+}
+
+/// Release crates, keep a target zone assigned and mark it in the world
+pub fn drop_cargo(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    world_gen: Res<WorldGenerator>,
+    assets: Res<AerialTaskAssets>,
+    mut cargo: ResMut<CargoDrops>,
+    mut gizmos: Gizmos,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    mut commands: Commands,
+) {
+    if !cargo.enabled {
+        cargo.zone = None;
+        return;
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    let forward = transform.forward().as_vec3();
+    let heading = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+
+    let zone = *cargo.zone.get_or_insert_with(|| place_zone(&world_gen, transform.translation, heading));
+    let rings = Isometry3d::new(zone + Vec3::Y * 5.0, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+    for ring in 1..=3 {
+        gizmos.circle(rings, ZONE_SCORE_RADIUS * ring as f32 / 3.0, Color::srgb(1.0, 0.85, 0.1));
+    }
+    gizmos.line(zone, zone + Vec3::Y * ZONE_BEACON_HEIGHT, Color::srgb(1.0, 0.85, 0.1));
+
+    cargo.cooldown = (cargo.cooldown - time.delta_secs()).max(0.0);
+    if !keyboard.just_pressed(KeyCode::KeyC) || cargo.cooldown > 0.0 || aircraft.crashed {
+        return;
+    }
+    cargo.cooldown = DROP_COOLDOWN;
+    cargo.drops += 1;
+
+    let position = transform.translation - transform.up().as_vec3() * CRATE_SIZE;
+    let crate_entity = commands.spawn((
+        Mesh3d(assets.cargo.clone()),
+        MeshMaterial3d(assets.cargo_material.clone()),
+        Transform::from_translation(position),
+        CargoCrate {
+            velocity: aircraft.velocity,
+            age: 0.0,
+            landed_for: None,
+        },
+        SharedCargo::default(),
+        Replicate,
+    )).id();
+    let canopy = commands.spawn((
+        Mesh3d(assets.canopy.clone()),
+        MeshMaterial3d(assets.canopy_material.clone()),
+        Transform::from_xyz(0.0, 50.0, 0.0).with_scale(Vec3::new(35.0, 12.0, 35.0)),
+        Visibility::Hidden,
+        NotShadowCaster,
+        Parachute,
+    )).id();
+    commands.entity(crate_entity).add_child(canopy);
+}
+
+/// Fall under gravity and drag, drift with the wind under canopy, and score the landing
+pub fn update_cargo(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    world_gen: Res<WorldGenerator>,
+    mut cargo: ResMut<CargoDrops>,
+    mut gizmos: Gizmos,
+    mut crates: Query<(Entity, &mut CargoCrate, &mut SharedCargo, &mut Transform, &Children)>,
+    mut canopies: Query<&mut Visibility, With<Parachute>>,
+    mut commands: Commands,
+) {
+    let dt = time.delta_secs();
+    let air = wind.wind_direction * wind.wind_speed;
+
+    for (entity, mut crate_state, mut shared, mut transform, children) in crates.iter_mut() {
+        if let Some(landed_for) = crate_state.landed_for.as_mut() {
+            *landed_for += dt;
+            if *landed_for >= LANDED_LIFETIME {
+                commands.entity(entity).despawn();
+            }
+            continue;
+        }
+
+        crate_state.age += dt;
+        let chute_open = crate_state.age >= CHUTE_DELAY;
+        let drag = if chute_open { CHUTE_DRAG } else { CRATE_DRAG };
+        let relative_air = air - crate_state.velocity;
+        let acceleration = Vec3::NEG_Y * GRAVITY + relative_air * relative_air.length() * drag;
+        crate_state.velocity += acceleration * dt;
+        transform.translation += crate_state.velocity * dt;
+        shared.set_if_neq(SharedCargo { chute_open });
+
+        for child in children.iter() {
+            if let Ok(mut visibility) = canopies.get_mut(child) {
+                *visibility = if chute_open { Visibility::Inherited } else { Visibility::Hidden };
+            }
+        }
+        if chute_open {
+            let top = transform.translation + Vec3::Y * 50.0;
+            for corner in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+                gizmos.line(transform.translation, top + corner * 30.0, Color::srgb(0.9, 0.9, 0.9));
+            }
+        }
+
+        let pos = transform.translation;
+        let terrain_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]);
+        let ground = terrain_height.max(0.0);
+        if pos.y - CRATE_SIZE * 0.5 > ground {
+            continue;
+        }
+
+        transform.translation.y = ground + CRATE_SIZE * 0.5;
+        crate_state.landed_for = Some(0.0);
+        shared.set_if_neq(SharedCargo { chute_open: false });
+        for child in children.iter() {
+            if let Ok(mut visibility) = canopies.get_mut(child) {
+                *visibility = Visibility::Hidden;
+            }
+        }
+        if terrain_height <= 0.0 {
+            commands.trigger(SpawnEffect {
+                kind: EffectKind::Splash,
+                position: Vec3::new(pos.x, 0.0, pos.z),
+                velocity: crate_state.velocity,
+                intensity: 1.0,
+            });
+            commands.entity(entity).despawn();
+        }
+
+        let Some(zone) = cargo.zone else { continue };
+        let distance = Vec2::new(pos.x - zone.x, pos.z - zone.z).length();
+        let points = if terrain_height > 0.0 {
+            (ZONE_MAX_POINTS * (1.0 - distance / ZONE_SCORE_RADIUS)).max(0.0).round() as u32
+        } else {
+            0
+        };
+        cargo.score += points;
+        cargo.last_result = Some(DropResult { distance: world_units_to_meters(distance), points });
+        info!("Cargo landed {:.0} m from the target for {} points", world_units_to_meters(distance), points);
+
+        // A scoring drop completes the delivery; the next zone is assigned from the aircraft's position
+        if points > 0 {
+            cargo.zone = None;
+        }
+    }
+}
+
+/// Give crates dropped by other players a body and canopy, and open the canopy when theirs opens
+pub fn show_remote_cargo(
+    assets: Res<AerialTaskAssets>,
+    new_crates: Query<(Entity, &SharedCargo), (With<Replica>, Added<SharedCargo>)>,
+    changed_crates: Query<(&SharedCargo, Option<&Children>), (With<Replica>, Changed<SharedCargo>)>,
+    mut canopies: Query<&mut Visibility, With<Parachute>>,
+    mut commands: Commands,
+) {
+    let canopy_visibility = |shared: &SharedCargo| if shared.chute_open { Visibility::Inherited } else { Visibility::Hidden };
+    for (entity, shared) in new_crates.iter() {
+        let canopy = commands.spawn((
+            Mesh3d(assets.canopy.clone()),
+            MeshMaterial3d(assets.canopy_material.clone()),
+            Transform::from_xyz(0.0, 50.0, 0.0).with_scale(Vec3::new(35.0, 12.0, 35.0)),
+            canopy_visibility(shared),
+            NotShadowCaster,
+            Parachute,
+        )).id();
+        commands.entity(entity)
+            .insert((Mesh3d(assets.cargo.clone()), MeshMaterial3d(assets.cargo_material.clone())))
+            .add_child(canopy);
+    }
+    for (shared, children) in changed_crates.iter() {
+        for child in children.into_iter().flat_map(|children| children.iter()) {
+            if let Ok(mut visibility) = canopies.get_mut(child) {
+                *visibility = canopy_visibility(shared);
+            }
+        }
+    }
+}
+
+/// Score readout and zone guidance while cargo drops are enabled
+pub fn cargo_hud(
+    mut contexts: EguiContexts,
+    cargo: Res<CargoDrops>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) -> Result<(), BevyError> {
+    if !cargo.enabled {
+        return Ok(());
+    }
+    let zone_distance = cargo.zone
+        .zip(aircraft_query.single().ok())
+        .map(|(zone, transform)| {
+            let offset = zone - transform.translation;
+            world_units_to_meters(Vec2::new(offset.x, offset.z).length())
+        });
+
+    egui::Window::new("Cargo")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 320.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("CARGO DROP").size(12.0));
+            ui.label(format!("Score: {}  ({} drops)", cargo.score, cargo.drops));
+            if let Some(distance) = zone_distance {
+                ui.label(format!("Target zone: {}", units.format_distance(distance)));
+            }
+            if let Some(result) = cargo.last_result {
+                ui.label(format!("Last drop: {} off, +{}", units.format_distance(result.distance), result.points));
+            }
+            ui.label(egui::RichText::new("C to drop").size(10.0));
+        });
+
+    Ok(())
+}
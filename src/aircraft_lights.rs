@@ -0,0 +1,239 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+
+use crate::controls::Aircraft;
+use crate::day_cycle::DayNightCycle;
+use crate::network::{PlaneType, RemotePlayer};
+use crate::trails::half_span;
+
+/// Emissive strength of the navigation lights at full night
+const NAV_LIGHT_INTENSITY: f32 = 40.0;
+const BEACON_INTENSITY: f32 = 80.0;
+const LANDING_LIGHT_INTENSITY: f32 = 60.0;
+/// Lights never go fully dark in daylight so they stay visible up close
+const DAY_LIGHT_FACTOR: f32 = 0.1;
+
+const BEACON_PERIOD: f32 = 1.2;
+const BEACON_FLASH_DURATION: f32 = 0.12;
+
+/// Lumens of the forward spot light on the local aircraft
+const LANDING_SPOT_INTENSITY: f32 = 40_000_000.0;
+const LANDING_SPOT_RANGE: f32 = 3000.0;
+
+const LIGHT_RADIUS: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExteriorLightKind {
+    NavLeft,
+    NavRight,
+    Tail,
+    Beacon,
+    Landing,
+}
+
+impl ExteriorLightKind {
+    const ALL: [ExteriorLightKind; 5] = [
+        ExteriorLightKind::NavLeft,
+        ExteriorLightKind::NavRight,
+        ExteriorLightKind::Tail,
+        ExteriorLightKind::Beacon,
+        ExteriorLightKind::Landing,
+    ];
+
+    /// Position relative to the aircraft origin in world units, before the aircraft's scale
+    fn offset(self, plane_type: PlaneType) -> Vec3 {
+        let span = half_span(plane_type);
+        let length = match plane_type {
+            PlaneType::Light | PlaneType::Glider => 5.0,
+            PlaneType::Jet => 10.0,
+        };
+        match self {
+            ExteriorLightKind::NavLeft => Vec3::new(-span, 0.0, 0.0),
+            ExteriorLightKind::NavRight => Vec3::new(span, 0.0, 0.0),
+            ExteriorLightKind::Tail => Vec3::new(0.0, length * 0.3, length),
+            ExteriorLightKind::Beacon => Vec3::new(0.0, length * 0.25, 0.0),
+            ExteriorLightKind::Landing => Vec3::new(0.0, -length * 0.1, -length),
+        }
+    }
+}
+
+/// An emissive light parented to an aircraft
+#[derive(Component)]
+pub struct ExteriorLight(pub ExteriorLightKind);
+
+/// Forward-facing spot light on the local aircraft
+#[derive(Component)]
+pub struct LandingSpotLight;
+
+/// Local player's light switch, toggled with L
+#[derive(Resource)]
+pub struct ExteriorLightSettings {
+    pub enabled: bool,
+}
+
+impl Default for ExteriorLightSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Resource)]
+pub struct ExteriorLightAssets {
+    mesh: Handle<Mesh>,
+    nav_red: Handle<StandardMaterial>,
+    nav_green: Handle<StandardMaterial>,
+    white: Handle<StandardMaterial>,
+    beacon: Handle<StandardMaterial>,
+    landing: Handle<StandardMaterial>,
+    local_off: Handle<StandardMaterial>,
+}
+
+impl ExteriorLightAssets {
+    fn material(&self, kind: ExteriorLightKind) -> Handle<StandardMaterial> {
+        match kind {
+            ExteriorLightKind::NavLeft => self.nav_red.clone(),
+            ExteriorLightKind::NavRight => self.nav_green.clone(),
+            ExteriorLightKind::Tail => self.white.clone(),
+            ExteriorLightKind::Beacon => self.beacon.clone(),
+            ExteriorLightKind::Landing => self.landing.clone(),
+        }
+    }
+}
+
+pub fn setup_exterior_lights(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut light_material = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(ExteriorLightAssets {
+        mesh: meshes.add(Sphere::new(LIGHT_RADIUS)),
+        nav_red: light_material(Color::srgb(1.0, 0.05, 0.05)),
+        nav_green: light_material(Color::srgb(0.05, 1.0, 0.1)),
+        white: light_material(Color::WHITE),
+        beacon: light_material(Color::srgb(1.0, 0.1, 0.05)),
+        landing: light_material(Color::srgb(1.0, 0.97, 0.9)),
+        local_off: light_material(Color::srgb(0.2, 0.2, 0.2)),
+    });
+}
+
+/// Attach the light set to every newly added aircraft, local or remote
+pub fn attach_exterior_lights(
+    mut commands: Commands,
+    assets: Res<ExteriorLightAssets>,
+    new_local: Query<Entity, Added<Aircraft>>,
+    new_remote: Query<Entity, Added<RemotePlayer>>,
+) {
+    for aircraft in new_local.iter().chain(new_remote.iter()) {
+        for kind in ExteriorLightKind::ALL {
+            let light = commands.spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(assets.material(kind)),
+                Transform::default(),
+                ExteriorLight(kind),
+                NotShadowCaster,
+            )).id();
+            commands.entity(aircraft).add_child(light);
+        }
+    }
+
+    for aircraft in new_local.iter() {
+        let spot = commands.spawn((
+            SpotLight {
+                intensity: 0.0,
+                range: LANDING_SPOT_RANGE,
+                color: Color::srgb(1.0, 0.97, 0.9),
+                outer_angle: 0.35,
+                inner_angle: 0.2,
+                shadows_enabled: false,
+                ..default()
+            },
+            Transform::default(),
+            LandingSpotLight,
+        )).id();
+        commands.entity(aircraft).add_child(spot);
+    }
+}
+
+pub fn toggle_exterior_lights(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<ExteriorLightSettings>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        settings.enabled = !settings.enabled;
+        info!("Exterior lights {}", if settings.enabled { "on" } else { "off" });
+    }
+}
+
+/// Position the lights against the aircraft's scale and brighten them as night falls
+pub fn update_exterior_lights(
+    time: Res<Time>,
+    cycle: Res<DayNightCycle>,
+    settings: Res<ExteriorLightSettings>,
+    assets: Res<ExteriorLightAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    local_aircraft: Query<(&Aircraft, &Transform), Without<ExteriorLight>>,
+    remote_aircraft: Query<(&RemotePlayer, &Transform), (Without<Aircraft>, Without<ExteriorLight>)>,
+    mut lights: Query<(&ExteriorLight, &ChildOf, &mut Transform, &mut MeshMaterial3d<StandardMaterial>), Without<LandingSpotLight>>,
+    mut spots: Query<(&ChildOf, &mut Transform, &mut SpotLight), (Without<ExteriorLight>, Without<Aircraft>, Without<RemotePlayer>)>,
+) {
+    // Judged where we fly, so the lights also come up as the aircraft sinks into dark water
+    let position = local_aircraft.iter().next().map_or(Vec3::ZERO, |(_, transform)| transform.translation);
+    let night = 1.0 - cycle.light_level_at(position);
+    let brightness = DAY_LIGHT_FACTOR + (1.0 - DAY_LIGHT_FACTOR) * night;
+
+    let beacon_on = time.elapsed_secs() % BEACON_PERIOD < BEACON_FLASH_DURATION;
+    let emissive_levels = [
+        (assets.nav_red.id(), LinearRgba::rgb(1.0, 0.02, 0.02) * NAV_LIGHT_INTENSITY),
+        (assets.nav_green.id(), LinearRgba::rgb(0.02, 1.0, 0.05) * NAV_LIGHT_INTENSITY),
+        (assets.white.id(), LinearRgba::rgb(1.0, 1.0, 1.0) * NAV_LIGHT_INTENSITY),
+        (
+            assets.beacon.id(),
+            if beacon_on { LinearRgba::rgb(1.0, 0.05, 0.02) * BEACON_INTENSITY } else { LinearRgba::BLACK },
+        ),
+        (assets.landing.id(), LinearRgba::rgb(1.0, 0.95, 0.85) * LANDING_LIGHT_INTENSITY),
+    ];
+    for (id, emissive) in emissive_levels {
+        if let Some(material) = materials.get_mut(id) {
+            material.emissive = emissive * brightness;
+        }
+    }
+
+    for (light, child_of, mut transform, mut material) in lights.iter_mut() {
+        let (plane_type, parent_scale, is_local) = if let Ok((aircraft, parent)) = local_aircraft.get(child_of.parent()) {
+            (aircraft.plane_type, parent.scale, true)
+        } else if let Ok((remote, parent)) = remote_aircraft.get(child_of.parent()) {
+            (remote.plane_type, parent.scale, false)
+        } else {
+            continue;
+        };
+
+        // Children inherit the model scale, so undo it to keep positions and sizes in world units
+        let inverse_scale = parent_scale.recip();
+        transform.translation = light.0.offset(plane_type) * inverse_scale;
+        transform.scale = inverse_scale;
+
+        let wanted = if is_local && !settings.enabled {
+            assets.local_off.clone()
+        } else {
+            assets.material(light.0)
+        };
+        if material.0 != wanted {
+            material.0 = wanted;
+        }
+    }
+
+    for (child_of, mut transform, mut spot) in spots.iter_mut() {
+        let Ok((aircraft, parent)) = local_aircraft.get(child_of.parent()) else { continue };
+        let plane_type = aircraft.plane_type;
+        let inverse_scale = parent.scale.recip();
+        transform.translation = ExteriorLightKind::Landing.offset(plane_type) * inverse_scale;
+        // Aim slightly below the nose so the beam lights the ground on approach
+        transform.rotation = Quat::from_rotation_x(-0.08);
+        spot.intensity = if settings.enabled { LANDING_SPOT_INTENSITY * night } else { 0.0 };
+    }
+}
@@ -0,0 +1,365 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts};
+use serde::Deserialize;
+
+use crate::controls::Aircraft;
+use crate::network::{PlaneType, RespawnAircraft};
+
+/// Folder under `assets/` holding one `.aircraft.ron` file per preset
+const AIRCRAFT_FOLDER: &str = "aircraft";
+/// Preset applied to the startup aircraft once the definitions have loaded
+const DEFAULT_AIRCRAFT: &str = "Light";
+
+/// Headline numbers shown on the selection screen
+#[derive(Debug, Clone, Deserialize)]
+pub struct AircraftStats {
+    pub max_speed_kmh: f32,
+    /// Best sustained climb in m/s
+    pub climb_rate: f32,
+    /// 0.0 sluggish to 1.0 twitchy
+    pub handling: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AircraftModelDefinition {
+    pub path: String,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AircraftCameraDefinition {
+    pub height: f32,
+    pub distance: f32,
+}
+
+/// Flight model parameters, matching the tuning fields on `Aircraft`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AircraftPhysicsDefinition {
+    pub start_speed: f32,
+    pub start_throttle: f32,
+    pub max_speed: f32,
+    pub max_throttle: f32,
+    pub thrust: f32,
+    pub gravity: f32,
+    pub g_force_drag: f32,
+    pub lift_coefficient: f32,
+    pub lift_reduction_factor: f32,
+    pub parasitic_drag_coef: f32,
+    pub pitch_strength: f32,
+    pub roll_strength: f32,
+    pub yaw_strength: f32,
+    pub bank_turn_strength: f32,
+    pub auto_level_strength: f32,
+    pub respawn_height: f32,
+    pub respawn_speed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum StationKind {
+    Fuel,
+    Passenger,
+    Cargo,
+}
+
+/// A place on the airframe that carries load, in kilograms at `arm` metres aft of the datum
+#[derive(Debug, Clone, Deserialize)]
+pub struct StationDefinition {
+    pub name: String,
+    pub kind: StationKind,
+    pub arm: f32,
+    pub max_weight: f32,
+    /// Load of the standard configuration the flight model is tuned for
+    pub default_weight: f32,
+}
+
+/// Weight-and-balance data, in kilograms and metres aft of the datum
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeightAndBalanceDefinition {
+    pub empty_weight: f32,
+    pub empty_arm: f32,
+    pub max_weight: f32,
+    /// Corners of the loading envelope as (CG arm, gross weight)
+    pub envelope: Vec<(f32, f32)>,
+    pub stations: Vec<StationDefinition>,
+}
+
+impl WeightAndBalanceDefinition {
+    pub fn default_weights(&self) -> Vec<f32> {
+        self.stations.iter().map(|station| station.default_weight).collect()
+    }
+
+    /// Gross weight and CG arm with the given load at each station
+    pub fn balance(&self, weights: &[f32]) -> (f32, f32) {
+        let (weight, moment) = self.stations.iter().zip(weights).fold(
+            (self.empty_weight, self.empty_weight * self.empty_arm),
+            |(weight, moment), (station, load)| (weight + load, moment + load * station.arm),
+        );
+        (weight, moment / weight.max(f32::EPSILON))
+    }
+
+    /// Forward and aft CG limits across the whole envelope
+    pub fn cg_limits(&self) -> (f32, f32) {
+        self.envelope.iter().fold((f32::MAX, f32::MIN), |(forward, aft), &(arm, _)| (forward.min(arm), aft.max(arm)))
+    }
+
+    pub fn in_envelope(&self, weight: f32, cg: f32) -> bool {
+        if weight > self.max_weight {
+            return false;
+        }
+        // Even-odd ray cast towards +arm
+        let mut inside = false;
+        let corners = &self.envelope;
+        for (i, &(arm_a, weight_a)) in corners.iter().enumerate() {
+            let (arm_b, weight_b) = corners[(i + 1) % corners.len()];
+            if (weight_a > weight) != (weight_b > weight)
+                && cg < arm_a + (weight - weight_a) / (weight_b - weight_a) * (arm_b - arm_a)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+/// An aircraft preset loaded from `assets/aircraft/*.aircraft.ron`
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct AircraftDefinition {
+    pub name: String,
+    pub description: String,
+    /// Airframe family sent to other players, which picks the model they see
+    pub plane_type: PlaneType,
+    pub stats: AircraftStats,
+    pub model: AircraftModelDefinition,
+    pub camera: AircraftCameraDefinition,
+    pub physics: AircraftPhysicsDefinition,
+    pub weight_and_balance: WeightAndBalanceDefinition,
+}
+
+impl AircraftDefinition {
+    pub fn to_aircraft(&self) -> Aircraft {
+        let physics = &self.physics;
+        Aircraft {
+            velocity: Vec3::ZERO,
+            speed: physics.start_speed,
+            throttle: physics.start_throttle,
+            power: physics.start_throttle,
+            pitch_velocity: 0.0,
+            roll_velocity: 0.0,
+            yaw_velocity: 0.0,
+            crashed: false,
+            angle_of_attack: 0.0,
+            spin_rate: 0.0,
+            weight_ratio: 1.0,
+            cg_position: 0.0,
+            ice: 0.0,
+            max_speed: physics.max_speed,
+            max_throttle: physics.max_throttle,
+            thrust: physics.thrust,
+            gravity: physics.gravity,
+            g_force_drag: physics.g_force_drag,
+            lift_coefficient: physics.lift_coefficient,
+            lift_reduction_factor: physics.lift_reduction_factor,
+            parasitic_drag_coef: physics.parasitic_drag_coef,
+            pitch_strength: physics.pitch_strength,
+            roll_strength: physics.roll_strength,
+            yaw_strength: physics.yaw_strength,
+            bank_turn_strength: physics.bank_turn_strength,
+            auto_level_strength: physics.auto_level_strength,
+            respawn_height: physics.respawn_height,
+            respawn_speed: physics.respawn_speed,
+            camera_height: self.camera.height,
+            camera_distance: self.camera.distance,
+            model_path: self.model.path.clone(),
+            model_scale: self.model.scale,
+            plane_type: self.plane_type,
+        }
+    }
+
+    /// Swap in this definition's tuning while keeping the aircraft's flight state
+    pub fn apply_tuning(&self, aircraft: &mut Aircraft) {
+        let tuned = Aircraft {
+            velocity: aircraft.velocity,
+            speed: aircraft.speed,
+            throttle: aircraft.throttle.min(self.physics.max_throttle),
+            power: aircraft.power.min(self.physics.max_throttle),
+            pitch_velocity: aircraft.pitch_velocity,
+            roll_velocity: aircraft.roll_velocity,
+            yaw_velocity: aircraft.yaw_velocity,
+            crashed: aircraft.crashed,
+            angle_of_attack: aircraft.angle_of_attack,
+            spin_rate: aircraft.spin_rate,
+            weight_ratio: aircraft.weight_ratio,
+            cg_position: aircraft.cg_position,
+            ice: aircraft.ice,
+            ..self.to_aircraft()
+        };
+        *aircraft = tuned;
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct AircraftDefinitionLoader;
+
+impl AssetLoader for AircraftDefinitionLoader {
+    type Asset = AircraftDefinition;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<AircraftDefinition, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aircraft.ron"]
+    }
+}
+
+/// Keeps the preset folder loaded and tracks the selection screen
+#[derive(Resource)]
+pub struct AircraftSelection {
+    pub open: bool,
+    pub selected: Option<AssetId<AircraftDefinition>>,
+    _folder: Handle<LoadedFolder>,
+}
+
+impl AircraftSelection {
+    pub fn selected_name<'a>(&self, definitions: &'a Assets<AircraftDefinition>) -> Option<&'a str> {
+        self.selected
+            .and_then(|id| definitions.get(id))
+            .map(|definition| definition.name.as_str())
+    }
+}
+
+pub fn load_aircraft_presets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // The main menu picks the first aircraft; this screen is for switching mid-flight
+    commands.insert_resource(AircraftSelection {
+        open: false,
+        selected: None,
+        _folder: asset_server.load_folder(AIRCRAFT_FOLDER),
+    });
+}
+
+/// Adopt the default preset once it loads, and re-tune the live aircraft when its file is edited
+pub fn apply_aircraft_definitions(
+    mut asset_events: MessageReader<AssetEvent<AircraftDefinition>>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    mut selection: ResMut<AircraftSelection>,
+    mut aircraft_query: Query<&mut Aircraft>,
+) {
+    for event in asset_events.read() {
+        let id = match event {
+            AssetEvent::Added { id } if selection.selected.is_none() => *id,
+            AssetEvent::Modified { id } if selection.selected == Some(*id) => *id,
+            _ => continue,
+        };
+        let Some(definition) = definitions.get(id) else { continue };
+        if selection.selected.is_none() && definition.name != DEFAULT_AIRCRAFT {
+            continue;
+        }
+
+        if let Ok(mut aircraft) = aircraft_query.single_mut() {
+            definition.apply_tuning(&mut aircraft);
+        }
+        if selection.selected.is_some() {
+            info!("Reloaded aircraft definition: {}", definition.name);
+        }
+        selection.selected = Some(id);
+    }
+}
+
+/// Swap the local aircraft over to a preset and respawn it
+pub fn fly_preset(
+    selection: &mut AircraftSelection,
+    id: AssetId<AircraftDefinition>,
+    preset: &AircraftDefinition,
+    aircraft: Option<Mut<Aircraft>>,
+    commands: &mut Commands,
+) {
+    if let Some(mut aircraft) = aircraft {
+        *aircraft = preset.to_aircraft();
+    }
+    selection.selected = Some(id);
+    commands.trigger(RespawnAircraft);
+    info!("Selected aircraft: {}", preset.name);
+}
+
+fn stat_bar(ui: &mut egui::Ui, label: &str, value: f32, text: String) {
+    ui.horizontal(|ui| {
+        ui.add_sized([70.0, 14.0], egui::Label::new(egui::RichText::new(label).size(11.0)));
+        ui.add(egui::ProgressBar::new(value.clamp(0.0, 1.0)).desired_width(140.0).text(text));
+    });
+}
+
+/// Preset picker: one card per loaded definition, slowest first
+pub fn aircraft_selection_ui(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<AircraftSelection>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    mut aircraft_query: Query<&mut Aircraft>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    if !selection.open {
+        return Ok(());
+    }
+
+    let mut presets: Vec<(AssetId<AircraftDefinition>, &AircraftDefinition)> = definitions.iter().collect();
+    presets.sort_by(|(_, a), (_, b)| a.stats.max_speed_kmh.total_cmp(&b.stats.max_speed_kmh));
+
+    // Bars are relative to the best preset so the differences read at a glance
+    let top_speed = presets.iter().map(|(_, p)| p.stats.max_speed_kmh).fold(1.0, f32::max);
+    let top_climb = presets.iter().map(|(_, p)| p.stats.climb_rate).fold(1.0, f32::max);
+
+    let mut chosen = None;
+    let mut open = selection.open;
+    egui::Window::new("Select Aircraft")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(contexts.ctx_mut()?, |ui| {
+            if presets.is_empty() {
+                ui.label("Loading aircraft...");
+                return;
+            }
+
+            egui::ScrollArea::vertical().max_height(520.0).show(ui, |ui| {
+                for (id, preset) in &presets {
+                    let is_selected = selection.selected == Some(*id);
+                    egui::Frame::group(ui.style()).show(ui, |ui| {
+                        ui.set_width(320.0);
+                        ui.horizontal(|ui| {
+                            ui.heading(&preset.name);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                let label = if is_selected { "Flying" } else { "Fly" };
+                                if ui.add_enabled(!is_selected, egui::Button::new(label)).clicked() {
+                                    chosen = Some((*id, *preset));
+                                }
+                            });
+                        });
+                        ui.label(egui::RichText::new(&preset.description).size(11.0));
+                        stat_bar(ui, "Max speed", preset.stats.max_speed_kmh / top_speed, format!("{:.0} km/h", preset.stats.max_speed_kmh));
+                        stat_bar(ui, "Climb", preset.stats.climb_rate / top_climb, format!("{:.1} m/s", preset.stats.climb_rate));
+                        stat_bar(ui, "Handling", preset.stats.handling, format!("{:.0}%", preset.stats.handling * 100.0));
+                    });
+                }
+            });
+        });
+    selection.open = open;
+
+    if let Some((id, preset)) = chosen {
+        fly_preset(&mut selection, id, preset, aircraft_query.single_mut().ok(), &mut commands);
+        selection.open = false;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,379 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::{sample_macro_wind, Wind};
+use crate::ditching::swell;
+use crate::world_generation::{Biome, ChunkManager, WorldGenerator};
+
+/// Chunks along each side of a region; every region rolls its own handful of craft
+const REGION_CHUNKS: i32 = 4;
+/// Most craft a region can hold
+const MAX_CRAFT_PER_REGION: u32 = 2;
+/// Chance a region has a blimp rather than a balloon
+const BLIMP_CHANCE: f32 = 0.3;
+/// Height above the ground each kind floats at
+const BALLOON_MIN_HEIGHT: f32 = 300.0;
+const BALLOON_MAX_HEIGHT: f32 = 2500.0;
+const BLIMP_MIN_HEIGHT: f32 = 500.0;
+const BLIMP_MAX_HEIGHT: f32 = 1500.0;
+/// Balloons ride the wind exactly; blimps motor along a little faster than it
+const BLIMP_AIRSPEED: f32 = 25.0;
+/// Slow rise and fall around the cruising height
+const BOB_HEIGHT: f32 = 15.0;
+const BALLOON_ENVELOPE_RADIUS: f32 = 45.0;
+const BLIMP_LENGTH: f32 = 240.0;
+const BLIMP_RADIUS: f32 = 40.0;
+/// Radians per second the blimp turns to follow its track
+const BLIMP_TURN_RATE: f32 = 0.2;
+
+/// Chance a row or column of regions carries a shipping lane, and a ship on each stretch of lane
+const LANE_CHANCE: f32 = 0.35;
+const SHIP_CHANCE: f32 = 0.6;
+/// Chance a ship on a lane is a freighter rather than a small boat
+const CARGO_SHIP_CHANCE: f32 = 0.4;
+/// Sea floor must be at least this deep for a ship to sail there, so they stay off the beaches
+const SHIP_MIN_DEPTH: f32 = 40.0;
+/// Cruising speeds in world units per second, about 8 and 11 m/s
+const CARGO_SHIP_SPEED: f32 = 42.0;
+const BOAT_SPEED: f32 = 58.0;
+/// How far ahead a ship checks for shallow water before turning back down its lane
+const SHIP_LOOKAHEAD: f32 = 300.0;
+/// Hull sizes as width, height, length
+const CARGO_HULL: Vec3 = Vec3::new(70.0, 30.0, 520.0);
+const BOAT_HULL: Vec3 = Vec3::new(14.0, 6.0, 50.0);
+/// Freighters ride the swell less than small boats do
+const CARGO_SHIP_ROCK: f32 = 0.01;
+const BOAT_ROCK: f32 = 0.05;
+
+const BALLOON_COLORS: [Color; 4] = [
+    Color::srgb(0.85, 0.2, 0.15),
+    Color::srgb(0.95, 0.7, 0.1),
+    Color::srgb(0.2, 0.45, 0.85),
+    Color::srgb(0.35, 0.7, 0.3),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CraftKind {
+    Balloon,
+    Blimp,
+}
+
+/// Slow traffic drifting with the macro wind, useful as a formation target
+#[derive(Component)]
+pub struct AmbientCraft {
+    pub kind: CraftKind,
+    cruise_height: f32,
+    phase: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ShipKind {
+    Boat,
+    CargoShip,
+}
+
+/// A vessel sailing back and forth along a shipping lane
+#[derive(Component)]
+pub struct Ship {
+    pub kind: ShipKind,
+    /// Unit direction along the lane it is currently sailing
+    heading: Vec3,
+    phase: f32,
+}
+
+/// Regions whose craft and ships are in the world
+#[derive(Resource, Default)]
+pub struct AmbientTraffic {
+    spawned_regions: HashSet<(i32, i32)>,
+    shipping_regions: HashSet<(i32, i32)>,
+}
+
+/// Shared meshes and materials, built on first use
+#[derive(Default)]
+pub struct TrafficAssets {
+    envelope: Handle<Mesh>,
+    basket: Handle<Mesh>,
+    hull: Handle<Mesh>,
+    gondola: Handle<Mesh>,
+    balloon_materials: Vec<Handle<StandardMaterial>>,
+    blimp_material: Handle<StandardMaterial>,
+    basket_material: Handle<StandardMaterial>,
+}
+
+/// Same hash as the thermal grid, salted with the world seed so each world has its own traffic
+fn region_hash(region: (i32, i32), seed: u32, salt: u32) -> f32 {
+    let mut h = (region.0 as u32).wrapping_mul(0x8da6_b343)
+        ^ (region.1 as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0x2c1b_3c6d)
+        ^ salt.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h & 0x00ff_ffff) as f32 / 16_777_216.0
+}
+
+fn chunk_of(pos: Vec3) -> (i32, i32) {
+    ((pos.x / CHUNK_SIZE).round() as i32, (pos.z / CHUNK_SIZE).round() as i32)
+}
+
+fn region_of(chunk: (i32, i32)) -> (i32, i32) {
+    (chunk.0.div_euclid(REGION_CHUNKS), chunk.1.div_euclid(REGION_CHUNKS))
+}
+
+/// Regions that just came into range, forgetting the ones that left so they refill when flown back to
+fn newly_loaded_regions(spawned: &mut HashSet<(i32, i32)>, chunk_manager: &ChunkManager) -> Vec<(i32, i32)> {
+    let loaded: HashSet<(i32, i32)> = chunk_manager.spawned_chunks.iter().map(|&chunk| region_of(chunk)).collect();
+    spawned.retain(|region| loaded.contains(region));
+    loaded.into_iter().filter(|&region| spawned.insert(region)).collect()
+}
+
+/// Spawn each loaded region's craft, drift them with the wind and drop them once their ground unloads
+pub fn update_ambient_traffic(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    world_gen: Res<WorldGenerator>,
+    chunk_manager: Res<ChunkManager>,
+    mut traffic: ResMut<AmbientTraffic>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<Option<TrafficAssets>>,
+    mut craft_query: Query<(Entity, &AmbientCraft, &mut Transform)>,
+) {
+    let now = time.elapsed_secs_f64();
+    let dt = time.delta_secs();
+
+    for (entity, craft, mut transform) in &mut craft_query {
+        // Culled with the chunk beneath it; the region stays marked so it doesn't refill behind the aircraft
+        if !chunk_manager.spawned_chunks.contains(&chunk_of(transform.translation)) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let (direction, speed) = sample_macro_wind(&wind, transform.translation, now);
+        let mut velocity = direction.with_y(0.0) * speed;
+        if craft.kind == CraftKind::Blimp {
+            let track = velocity.normalize_or(*transform.forward());
+            velocity += track * BLIMP_AIRSPEED;
+            let target = Transform::from_translation(transform.translation).looking_to(track, Vec3::Y).rotation;
+            transform.rotation = transform.rotation.slerp(target, (BLIMP_TURN_RATE * dt).min(1.0));
+        } else {
+            transform.rotate_y(0.05 * dt);
+        }
+        transform.translation += velocity * dt;
+        transform.translation.y = craft.cruise_height + (now as f32 * 0.3 + craft.phase).sin() * BOB_HEIGHT;
+    }
+
+    let new_regions = newly_loaded_regions(&mut traffic.spawned_regions, &chunk_manager);
+    if new_regions.is_empty() {
+        return;
+    }
+    let assets = assets.get_or_insert_with(|| TrafficAssets {
+        envelope: meshes.add(Sphere::new(BALLOON_ENVELOPE_RADIUS).mesh().uv(24, 16)),
+        basket: meshes.add(Cuboid::new(12.0, 10.0, 12.0)),
+        hull: meshes.add(Capsule3d::new(BLIMP_RADIUS, BLIMP_LENGTH - BLIMP_RADIUS * 2.0)),
+        gondola: meshes.add(Cuboid::new(14.0, 10.0, 40.0)),
+        balloon_materials: BALLOON_COLORS.iter().map(|&color| materials.add(StandardMaterial {
+            base_color: color,
+            perceptual_roughness: 0.8,
+            ..default()
+        })).collect(),
+        blimp_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.82, 0.82, 0.86),
+            perceptual_roughness: 0.5,
+            ..default()
+        }),
+        basket_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.4, 0.28, 0.15),
+            perceptual_roughness: 1.0,
+            ..default()
+        }),
+    });
+
+    for region in new_regions {
+        let count = (region_hash(region, world_gen.seed, 0) * (MAX_CRAFT_PER_REGION + 1) as f32) as u32;
+        for index in 0..count {
+            let salt = 1 + index * 8;
+            let roll = |offset: u32| region_hash(region, world_gen.seed, salt + offset);
+            let region_size = REGION_CHUNKS as f32 * CHUNK_SIZE;
+            let x = (region.0 as f32 + roll(0)) * region_size;
+            let z = (region.1 as f32 + roll(1)) * region_size;
+            // Only start over ground that is streamed in, so nothing pops up beyond the terrain
+            if !chunk_manager.spawned_chunks.contains(&chunk_of(Vec3::new(x, 0.0, z))) {
+                continue;
+            }
+            let kind = if roll(2) < BLIMP_CHANCE { CraftKind::Blimp } else { CraftKind::Balloon };
+            let (min_height, max_height) = match kind {
+                CraftKind::Balloon => (BALLOON_MIN_HEIGHT, BALLOON_MAX_HEIGHT),
+                CraftKind::Blimp => (BLIMP_MIN_HEIGHT, BLIMP_MAX_HEIGHT),
+            };
+            let ground = world_gen.get_terrain_height(&[x, 0.0, z]).max(0.0);
+            let cruise_height = ground + min_height + roll(3) * (max_height - min_height);
+            let heading = roll(4) * TAU;
+            let craft = AmbientCraft { kind, cruise_height, phase: roll(5) * TAU };
+
+            let (body, body_material, car, car_material, car_offset) = match kind {
+                CraftKind::Balloon => {
+                    let color = (roll(6) * BALLOON_COLORS.len() as f32) as usize % BALLOON_COLORS.len();
+                    (
+                        assets.envelope.clone(),
+                        assets.balloon_materials[color].clone(),
+                        assets.basket.clone(),
+                        assets.basket_material.clone(),
+                        Vec3::new(0.0, -BALLOON_ENVELOPE_RADIUS - 20.0, 0.0),
+                    )
+                }
+                CraftKind::Blimp => (
+                    assets.hull.clone(),
+                    assets.blimp_material.clone(),
+                    assets.gondola.clone(),
+                    assets.basket_material.clone(),
+                    Vec3::new(0.0, -BLIMP_RADIUS - 4.0, 0.0),
+                ),
+            };
+            // The capsule is built along Y; lay it down along the blimp's nose
+            let body_rotation = if kind == CraftKind::Blimp { Quat::from_rotation_x(std::f32::consts::FRAC_PI_2) } else { Quat::IDENTITY };
+            commands.spawn((
+                Transform::from_xyz(x, cruise_height, z).with_rotation(Quat::from_rotation_y(heading)),
+                Visibility::default(),
+                craft,
+            )).with_children(|parent| {
+                parent.spawn((Mesh3d(body), MeshMaterial3d(body_material), Transform::from_rotation(body_rotation)));
+                parent.spawn((Mesh3d(car), MeshMaterial3d(car_material), Transform::from_translation(car_offset), NotShadowCaster));
+            });
+        }
+    }
+}
+
+/// Deep enough water, away from the shore, for a ship to sail
+fn open_water(world_gen: &WorldGenerator, x: f32, z: f32) -> bool {
+    let pos = [x, 0.0, z];
+    world_gen.get_biome(&pos) == Biome::Ocean && world_gen.get_terrain_height(&pos) < -SHIP_MIN_DEPTH
+}
+
+/// Offset across a region of the lane running through a row or column of regions, if it has one
+fn lane_offset(line: i32, seed: u32, salt: u32) -> Option<f32> {
+    let key = (line, 0);
+    (region_hash(key, seed, salt) < LANE_CHANCE).then(|| 0.2 + 0.6 * region_hash(key, seed, salt + 1))
+}
+
+/// Shared meshes and materials for ships, built on first use
+pub struct ShipAssets {
+    cargo_hull: Handle<Mesh>,
+    containers: Handle<Mesh>,
+    cargo_bridge: Handle<Mesh>,
+    boat_hull: Handle<Mesh>,
+    cabin: Handle<Mesh>,
+    hull_material: Handle<StandardMaterial>,
+    container_material: Handle<StandardMaterial>,
+    white_material: Handle<StandardMaterial>,
+}
+
+/// Put ships on the lanes crossing newly loaded ocean, sail them up and down and rock them on the swell
+pub fn update_shipping(
+    mut commands: Commands,
+    time: Res<Time>,
+    world_gen: Res<WorldGenerator>,
+    chunk_manager: Res<ChunkManager>,
+    mut traffic: ResMut<AmbientTraffic>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<Option<ShipAssets>>,
+    mut ship_query: Query<(Entity, &mut Ship, &mut Transform)>,
+) {
+    let t = time.elapsed_secs();
+    let dt = time.delta_secs();
+
+    for (entity, mut ship, mut transform) in &mut ship_query {
+        if !chunk_manager.spawned_chunks.contains(&chunk_of(transform.translation)) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let ahead = transform.translation + ship.heading * SHIP_LOOKAHEAD;
+        if !open_water(&world_gen, ahead.x, ahead.z) {
+            ship.heading = -ship.heading;
+        }
+        let (speed, rock) = match ship.kind {
+            ShipKind::CargoShip => (CARGO_SHIP_SPEED, CARGO_SHIP_ROCK),
+            ShipKind::Boat => (BOAT_SPEED, BOAT_ROCK),
+        };
+        let wave_time = t + ship.phase;
+        transform.translation += ship.heading * speed * dt;
+        transform.translation.y = swell(wave_time);
+        let yaw = Transform::default().looking_to(ship.heading, Vec3::Y).rotation;
+        let target = yaw * Quat::from_euler(EulerRot::XYZ, (wave_time * 1.1).sin() * rock, 0.0, (wave_time * 0.8).cos() * rock);
+        transform.rotation = transform.rotation.slerp(target, (dt * 0.5).min(1.0));
+    }
+
+    let new_regions = newly_loaded_regions(&mut traffic.shipping_regions, &chunk_manager);
+    if new_regions.is_empty() {
+        return;
+    }
+    let assets = assets.get_or_insert_with(|| ShipAssets {
+        cargo_hull: meshes.add(Cuboid::new(CARGO_HULL.x, CARGO_HULL.y, CARGO_HULL.z)),
+        containers: meshes.add(Cuboid::new(CARGO_HULL.x * 0.85, 25.0, CARGO_HULL.z * 0.6)),
+        cargo_bridge: meshes.add(Cuboid::new(CARGO_HULL.x * 0.8, 40.0, 45.0)),
+        boat_hull: meshes.add(Cuboid::new(BOAT_HULL.x, BOAT_HULL.y, BOAT_HULL.z)),
+        cabin: meshes.add(Cuboid::new(BOAT_HULL.x * 0.7, 8.0, 16.0)),
+        hull_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.55, 0.12, 0.1),
+            perceptual_roughness: 0.7,
+            ..default()
+        }),
+        container_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.15, 0.35, 0.6),
+            perceptual_roughness: 0.8,
+            ..default()
+        }),
+        white_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.92, 0.92, 0.9),
+            perceptual_roughness: 0.6,
+            ..default()
+        }),
+    });
+
+    let seed = world_gen.seed;
+    let region_size = REGION_CHUNKS as f32 * CHUNK_SIZE;
+    for region in new_regions {
+        // Lanes run the length of a row or column of regions, so ships line up across region edges
+        let lanes = [
+            lane_offset(region.1, seed, 100).map(|offset| (Vec3::X, Vec2::new(0.0, offset))),
+            lane_offset(region.0, seed, 102).map(|offset| (Vec3::Z, Vec2::new(offset, 0.0))),
+        ];
+        for (direction, lane) in lanes.into_iter().flatten() {
+            let salt = if direction == Vec3::X { 110 } else { 114 };
+            if region_hash(region, seed, salt) >= SHIP_CHANCE {
+                continue;
+            }
+            let along = region_hash(region, seed, salt + 1);
+            let (x, z) = if direction == Vec3::X { (along, lane.y) } else { (lane.x, along) };
+            let x = (region.0 as f32 + x) * region_size;
+            let z = (region.1 as f32 + z) * region_size;
+            if !open_water(&world_gen, x, z) || !chunk_manager.spawned_chunks.contains(&chunk_of(Vec3::new(x, 0.0, z))) {
+                continue;
+            }
+            let heading = if region_hash(region, seed, salt + 2) < 0.5 { direction } else { -direction };
+            let kind = if region_hash(region, seed, salt + 3) < CARGO_SHIP_CHANCE { ShipKind::CargoShip } else { ShipKind::Boat };
+            let phase = along * 100.0;
+
+            let mut ship = commands.spawn((
+                Transform::from_xyz(x, 0.0, z).looking_to(heading, Vec3::Y),
+                Visibility::default(),
+                Ship { kind, heading, phase },
+            ));
+            // Hulls sit with most of their height below the waterline
+            match kind {
+                ShipKind::CargoShip => ship.with_children(|parent| {
+                    parent.spawn((Mesh3d(assets.cargo_hull.clone()), MeshMaterial3d(assets.hull_material.clone()), Transform::from_xyz(0.0, CARGO_HULL.y * 0.1, 0.0)));
+                    parent.spawn((Mesh3d(assets.containers.clone()), MeshMaterial3d(assets.container_material.clone()), Transform::from_xyz(0.0, CARGO_HULL.y * 0.6 + 12.5, -CARGO_HULL.z * 0.08)));
+                    parent.spawn((Mesh3d(assets.cargo_bridge.clone()), MeshMaterial3d(assets.white_material.clone()), Transform::from_xyz(0.0, CARGO_HULL.y * 0.6 + 20.0, CARGO_HULL.z * 0.4)));
+                }),
+                ShipKind::Boat => ship.with_children(|parent| {
+                    parent.spawn((Mesh3d(assets.boat_hull.clone()), MeshMaterial3d(assets.white_material.clone()), Transform::from_xyz(0.0, BOAT_HULL.y * 0.1, 0.0)));
+                    parent.spawn((Mesh3d(assets.cabin.clone()), MeshMaterial3d(assets.white_material.clone()), Transform::from_xyz(0.0, BOAT_HULL.y * 0.6 + 4.0, 4.0), NotShadowCaster));
+                }),
+            };
+        }
+    }
+}
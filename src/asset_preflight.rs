@@ -0,0 +1,139 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::AssetPath;
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+use crate::controls::DEFAULT_AIRCRAFT_MODEL;
+use crate::crash_report::ErrorReports;
+use crate::environment::Tree;
+
+/// Where the asset server reads from, under the executable's directory
+const ASSET_DIR: &str = "assets";
+/// Models the world can't be flown without, checked at startup
+const REQUIRED_MODELS: [&str; 5] = [
+    DEFAULT_AIRCRAFT_MODEL,
+    "f16_low_poly/scene.gltf#Scene0",
+    "pine.glb#Scene0",
+    "oak.glb#Scene0",
+    "dead_tree.glb#Scene0",
+];
+/// Placeholder aircraft size in world units, whatever the scale of the model it stands in for
+const PLACEHOLDER_LENGTH: f32 = 10.0;
+const PLACEHOLDER_SPAN: f32 = 12.0;
+
+/// Which asset files are on disk, so a missing model gets a stand-in instead of an invisible entity
+#[derive(Resource, Default)]
+pub struct AssetPreflight {
+    /// Required models that were missing at startup
+    pub missing: Vec<&'static str>,
+    /// Files checked so far, by path under the asset directory
+    checked: HashMap<PathBuf, bool>,
+}
+
+impl AssetPreflight {
+    /// Whether the file behind an asset path, label aside, is on disk
+    pub fn exists(&mut self, path: &AssetPath) -> bool {
+        let file = path.path().to_path_buf();
+        *self.checked.entry(file).or_insert_with_key(|file| {
+            FileAssetReader::get_base_path().join(ASSET_DIR).join(file).is_file()
+        })
+    }
+
+    /// Whether a required model was found missing at startup
+    pub fn is_missing(&self, model_path: &str) -> bool {
+        self.missing.contains(&model_path)
+    }
+}
+
+/// Shared meshes for the cone-and-wings aircraft shown when a model is missing
+#[derive(Resource)]
+pub struct PlaceholderAircraft {
+    fuselage: Handle<Mesh>,
+    wing: Handle<Mesh>,
+    fin: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// Root of a placeholder aircraft, parented to the scene it stands in for
+#[derive(Component)]
+pub struct PlaceholderModel;
+
+/// Check the required models before anything spawns, and report the ones that are gone
+pub fn preflight_assets(
+    mut commands: Commands,
+    mut preflight: ResMut<AssetPreflight>,
+    mut reports: ResMut<ErrorReports>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for model_path in REQUIRED_MODELS {
+        if !preflight.exists(&AssetPath::parse(model_path)) {
+            warn!("Missing asset {}, showing a placeholder instead", model_path);
+            preflight.missing.push(model_path);
+            reports.missing_assets.push(model_path.to_string());
+        }
+    }
+
+    commands.insert_resource(PlaceholderAircraft {
+        fuselage: meshes.add(Cone::new(PLACEHOLDER_LENGTH * 0.1, PLACEHOLDER_LENGTH)),
+        wing: meshes.add(Cuboid::new(PLACEHOLDER_SPAN, PLACEHOLDER_LENGTH * 0.03, PLACEHOLDER_LENGTH * 0.2)),
+        fin: meshes.add(Cuboid::new(PLACEHOLDER_LENGTH * 0.02, PLACEHOLDER_LENGTH * 0.2, PLACEHOLDER_LENGTH * 0.15)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.35, 0.8),
+            perceptual_roughness: 0.8,
+            ..default()
+        }),
+    });
+}
+
+/// Hang a placeholder aircraft under any scene whose file is missing, and take it down once the scene
+/// is switched to one that exists. Runs after transform propagation so the stand-in can undo the model's scale
+pub fn substitute_placeholder_models(
+    mut commands: Commands,
+    mut preflight: ResMut<AssetPreflight>,
+    placeholder: Res<PlaceholderAircraft>,
+    scenes: Query<(Entity, &SceneRoot, &GlobalTransform, Option<&Children>), (Changed<SceneRoot>, Without<Tree>)>,
+    placeholders: Query<(), With<PlaceholderModel>>,
+) {
+    for (entity, scene, global_transform, children) in &scenes {
+        let Some(path) = scene.0.path() else { continue };
+        let existing = children
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|child| placeholders.contains(*child));
+
+        match (preflight.exists(path), existing) {
+            (true, Some(stand_in)) => commands.entity(stand_in).despawn(),
+            (false, None) => {
+                // The fuselage cone points along +Z, the nose of every model after its half-turn
+                let scale = global_transform.scale().x.max(f32::EPSILON);
+                let material = MeshMaterial3d(placeholder.material.clone());
+                commands.entity(entity).with_child((
+                    PlaceholderModel,
+                    Transform::from_scale(Vec3::splat(1.0 / scale)),
+                    Visibility::default(),
+                    children![
+                        (
+                            Mesh3d(placeholder.fuselage.clone()),
+                            material.clone(),
+                            Transform::from_rotation(Quat::from_rotation_x(90f32.to_radians())),
+                        ),
+                        (
+                            Mesh3d(placeholder.wing.clone()),
+                            material.clone(),
+                            Transform::from_xyz(0.0, 0.0, PLACEHOLDER_LENGTH * 0.05),
+                        ),
+                        (
+                            Mesh3d(placeholder.fin.clone()),
+                            material,
+                            Transform::from_xyz(0.0, PLACEHOLDER_LENGTH * 0.1, -PLACEHOLDER_LENGTH * 0.4),
+                        ),
+                    ],
+                ));
+            }
+            _ => {}
+        }
+    }
+}
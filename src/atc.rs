@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use std::collections::VecDeque;
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, ControlMode, FlightMode, MainCamera};
+use crate::hud::{calculate_heading, HudPalette};
+use crate::network::{ClientMessage, ClientRole, InstructionReceived, NetworkClient, PlayerFlagged, RemotePlayer};
+
+/// Instructions stay on a pilot's screen this long
+const INSTRUCTION_DISPLAY_SECS: f32 = 30.0;
+const INSTRUCTION_LOG_SIZE: usize = 5;
+/// Where "Go to" parks the observer camera relative to the pilot
+const GO_TO_OFFSET: Vec3 = Vec3::new(0.0, 150.0, 400.0);
+
+pub struct Instruction {
+    pub from: String,
+    pub text: String,
+    pub received: f32,
+}
+
+/// Ground-control console state: the observer's outgoing draft and the pilot's received instructions
+#[derive(Resource, Default)]
+pub struct AtcConsole {
+    pub draft: String,
+    /// Player the draft goes to; `None` sends to everyone
+    pub target: Option<u32>,
+    pub instructions: VecDeque<Instruction>,
+    /// Pilots the server flagged for implausible movement, newest last
+    pub alerts: VecDeque<String>,
+}
+
+fn is_observer(client: Option<&NetworkClient>) -> bool {
+    client.is_some_and(|client| client.connected && client.role == ClientRole::Observer)
+}
+
+/// Observers have no aircraft: hide ours, stop its physics and keep the camera free
+pub fn enforce_observer_mode(
+    client: Option<Res<NetworkClient>>,
+    mut control_mode: ResMut<ControlMode>,
+    mut aircraft_query: Query<&mut Visibility, With<Aircraft>>,
+    mut was_observer: Local<bool>,
+) {
+    let observing = is_observer(client.as_deref());
+    if observing {
+        control_mode.mode = FlightMode::FreeFlight;
+        control_mode.physics_paused = true;
+    } else if *was_observer {
+        control_mode.mode = FlightMode::Aircraft;
+        control_mode.physics_paused = false;
+    }
+    if observing != *was_observer {
+        for mut visibility in aircraft_query.iter_mut() {
+            *visibility = if observing { Visibility::Hidden } else { Visibility::Inherited };
+        }
+    }
+    *was_observer = observing;
+}
+
+pub fn receive_instruction(trigger: On<InstructionReceived>, time: Res<Time>, mut console: ResMut<AtcConsole>) {
+    console.instructions.push_back(Instruction {
+        from: trigger.from.clone(),
+        text: trigger.text.clone(),
+        received: time.elapsed_secs(),
+    });
+    while console.instructions.len() > INSTRUCTION_LOG_SIZE {
+        console.instructions.pop_front();
+    }
+}
+
+/// Keep the server's movement flags where observers will see them
+pub fn record_flagged_player(trigger: On<PlayerFlagged>, mut console: ResMut<AtcConsole>) {
+    console.alerts.push_back(format!("Player {} {}", trigger.id, trigger.reason));
+    while console.alerts.len() > INSTRUCTION_LOG_SIZE {
+        console.alerts.pop_front();
+    }
+}
+
+/// Traffic table and instruction composer for observers
+pub fn atc_panel(
+    mut contexts: EguiContexts,
+    mut console: ResMut<AtcConsole>,
+    client: Option<Res<NetworkClient>>,
+    units: Res<UnitSystem>,
+    remote_players: Query<(&RemotePlayer, &Transform)>,
+    mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<RemotePlayer>)>,
+) -> Result<(), BevyError> {
+    let Some(client) = client.filter(|client| is_observer(Some(client))) else { return Ok(()) };
+
+    let mut players: Vec<_> = remote_players.iter().collect();
+    players.sort_by_key(|(remote, _)| remote.player_id);
+    if console.target.is_some_and(|target| !players.iter().any(|(remote, _)| remote.player_id == target)) {
+        console.target = None;
+    }
+
+    egui::Window::new("Ground Control")
+        .default_pos(egui::Pos2::new(20.0, 400.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            if players.is_empty() {
+                ui.label("No aircraft airborne");
+            }
+            egui::Grid::new("atc_traffic").num_columns(7).striped(true).show(ui, |ui| {
+                for header in ["", "ID", "Name", "Type", "Position", "HDG", "ALT"] {
+                    ui.label(egui::RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for (remote, transform) in &players {
+                    let position = transform.translation;
+                    ui.radio_value(&mut console.target, Some(remote.player_id), "");
+                    ui.label(remote.player_id.to_string());
+                    match &remote.flagged {
+                        Some(reason) => {
+                            ui.label(egui::RichText::new(format!("⚠ {}", remote.name)).color(egui::Color32::YELLOW))
+                                .on_hover_text(reason);
+                        }
+                        None => {
+                            ui.label(&remote.name);
+                        }
+                    }
+                    ui.label(format!("{:?}", remote.plane_type));
+                    ui.label(format!(
+                        "{} E, {} N",
+                        units.format_distance(world_units_to_meters(position.x)),
+                        units.format_distance(world_units_to_meters(-position.z))
+                    ));
+                    ui.label(format!("{:03.0}°", calculate_heading(transform.forward().as_vec3())));
+                    ui.label(units.format_altitude(world_units_to_meters(position.y)));
+                    if ui.button("Go to").clicked()
+                        && let Ok(mut camera) = camera_query.single_mut()
+                    {
+                        camera.translation = position + transform.rotation * GO_TO_OFFSET;
+                        camera.look_at(position, Vec3::Y);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if !console.alerts.is_empty() {
+                ui.separator();
+                for alert in &console.alerts {
+                    ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", alert));
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut console.target, None, "All pilots");
+                if let Some(target) = console.target {
+                    ui.label(format!("To player {}", target));
+                }
+            });
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(&mut console.draft);
+                let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if (ui.button("Send").clicked() || submitted) && !console.draft.trim().is_empty() {
+                    client.send(ClientMessage::Instruction {
+                        target: console.target,
+                        text: console.draft.trim().to_string(),
+                    });
+                    console.draft.clear();
+                }
+            });
+        });
+
+    Ok(())
+}
+
+/// Recent instructions from ground control, shown to pilots
+pub fn instruction_hud(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    console: Res<AtcConsole>,
+    palette: Res<HudPalette>,
+    client: Option<Res<NetworkClient>>,
+) -> Result<(), BevyError> {
+    if is_observer(client.as_deref()) {
+        return Ok(());
+    }
+    let now = time.elapsed_secs();
+    let recent: Vec<&Instruction> = console
+        .instructions
+        .iter()
+        .filter(|instruction| now - instruction.received < INSTRUCTION_DISPLAY_SECS)
+        .collect();
+    if recent.is_empty() {
+        return Ok(());
+    }
+
+    egui::Window::new("ATC")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -80.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            for instruction in recent {
+                ui.label(format!("🗼 {}: {}", instruction.from, instruction.text));
+            }
+        });
+
+    Ok(())
+}
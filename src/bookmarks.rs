@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::Aircraft;
+use crate::hud::{calculate_heading, HudPalette};
+use crate::network::NetworkClient;
+use crate::teleport::Teleport;
+
+/// Saved locations, kept next to the other per-user settings
+const SAVE_PATH: &str = "settings/bookmarks.ron";
+const BOOKMARKS_KEY: KeyCode = KeyCode::KeyY;
+
+/// A named place to fast travel back to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub position: [f32; 3],
+    /// Compass heading in degrees, as shown on the HUD
+    pub heading: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Bookmarks {
+    pub list: Vec<Bookmark>,
+    pub open: bool,
+    /// Name typed in for the next bookmark
+    pub new_name: String,
+}
+
+/// Level direction for a compass heading; the inverse of `calculate_heading`
+fn heading_direction(heading: f32) -> Vec3 {
+    let angle = (heading - 90.0).to_radians();
+    Vec3::new(angle.sin(), 0.0, -angle.cos())
+}
+
+pub fn load_bookmarks(mut bookmarks: ResMut<Bookmarks>) {
+    let Ok(text) = std::fs::read_to_string(SAVE_PATH) else { return };
+    match ron::from_str(&text) {
+        Ok(loaded) => {
+            bookmarks.list = loaded;
+            info!("Loaded {} bookmarks from {}", bookmarks.list.len(), SAVE_PATH);
+        }
+        Err(error) => warn!("Ignoring {}: {}", SAVE_PATH, error),
+    }
+}
+
+fn save_bookmarks(list: &[Bookmark]) {
+    let result = ron::ser::to_string_pretty(list, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(SAVE_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", SAVE_PATH, error);
+    }
+}
+
+pub fn toggle_bookmarks(keyboard: Res<ButtonInput<KeyCode>>, mut bookmarks: ResMut<Bookmarks>) {
+    if keyboard.just_pressed(BOOKMARKS_KEY) {
+        bookmarks.open = !bookmarks.open;
+    }
+}
+
+/// Save the current spot, and list the saved ones with their distance and a button to go there
+pub fn bookmarks_ui(
+    mut contexts: EguiContexts,
+    mut bookmarks: ResMut<Bookmarks>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    if !bookmarks.open {
+        return Ok(());
+    }
+    let Ok(transform) = aircraft_query.single() else { return Ok(()) };
+    // The server's movement checks would flag the jump
+    let connected = client.is_some_and(|client| client.connected);
+    let mut changed = false;
+
+    egui::Window::new("Bookmarks")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_CENTER, [20.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("BOOKMARKS").size(12.0));
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut bookmarks.new_name).hint_text("Name").desired_width(140.0));
+                if ui.button("Save here").clicked() {
+                    let name = match bookmarks.new_name.trim() {
+                        "" => format!("Bookmark {}", bookmarks.list.len() + 1),
+                        name => name.to_string(),
+                    };
+                    bookmarks.list.push(Bookmark {
+                        name,
+                        position: transform.translation.to_array(),
+                        heading: calculate_heading(transform.forward().as_vec3()),
+                    });
+                    bookmarks.new_name.clear();
+                    changed = true;
+                }
+            });
+            ui.separator();
+
+            if bookmarks.list.is_empty() {
+                ui.label("Nothing saved yet");
+            }
+            let mut removed = None;
+            let mut travelling = false;
+            egui::Grid::new("bookmark_list").num_columns(4).show(ui, |ui| {
+                for (index, bookmark) in bookmarks.list.iter().enumerate() {
+                    let offset = Vec3::from(bookmark.position) - transform.translation;
+                    ui.label(&bookmark.name);
+                    ui.label(format!(
+                        "{}, {:03.0}°",
+                        units.format_distance(world_units_to_meters(Vec2::new(offset.x, offset.z).length())),
+                        calculate_heading(offset),
+                    ));
+                    if ui.add_enabled(!connected, egui::Button::new("Go"))
+                        .on_disabled_hover_text("Fast travel is off while connected to a server")
+                        .clicked()
+                    {
+                        commands.trigger(Teleport {
+                            position: bookmark.position.into(),
+                            rotation: Transform::default().looking_to(heading_direction(bookmark.heading), Vec3::Y).rotation,
+                        });
+                        travelling = true;
+                    }
+                    if ui.small_button("✖").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = removed {
+                bookmarks.list.remove(index);
+                changed = true;
+            }
+            if travelling {
+                bookmarks.open = false;
+            }
+            ui.label(egui::RichText::new("Y to close").size(10.0));
+        });
+
+    if changed {
+        save_bookmarks(&bookmarks.list);
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::consts::world_units_to_meters;
+use crate::controls::Aircraft;
+
+/// Default distance flown between crumbs, in metres
+const DEFAULT_SPACING_METERS: f32 = 300.0;
+/// Default cap; at the default spacing this is a 60 km trail
+const DEFAULT_MAX_CRUMBS: usize = 200;
+/// Each crumb hangs a pole this far down so the trail reads against the sea
+const POLE_LENGTH: f32 = 400.0;
+const CRUMB_RADIUS: f32 = 40.0;
+/// The oldest crumb is drawn at this fraction of the newest's opacity
+const OLDEST_ALPHA: f32 = 0.15;
+const CRUMB_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+
+/// Markers dropped along the flight path so the way back can be followed home
+#[derive(Resource)]
+pub struct Breadcrumbs {
+    pub enabled: bool,
+    pub spacing_meters: f32,
+    /// The oldest crumbs drop off past this many
+    pub max_crumbs: usize,
+    crumbs: VecDeque<Vec3>,
+}
+
+impl Default for Breadcrumbs {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spacing_meters: DEFAULT_SPACING_METERS,
+            max_crumbs: DEFAULT_MAX_CRUMBS,
+            crumbs: VecDeque::new(),
+        }
+    }
+}
+
+impl Breadcrumbs {
+    pub fn count(&self) -> usize {
+        self.crumbs.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.crumbs.clear();
+    }
+}
+
+/// Drop a crumb each time the aircraft gets far enough from the last one
+pub fn drop_breadcrumbs(mut breadcrumbs: ResMut<Breadcrumbs>, aircraft_query: Query<(&Transform, &Aircraft)>) {
+    if !breadcrumbs.enabled {
+        return;
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        return;
+    }
+    let position = transform.translation;
+    let far_enough = breadcrumbs
+        .crumbs
+        .back()
+        .is_none_or(|last| world_units_to_meters(last.distance(position)) >= breadcrumbs.spacing_meters);
+    if far_enough {
+        breadcrumbs.crumbs.push_back(position);
+    }
+    while breadcrumbs.crumbs.len() > breadcrumbs.max_crumbs {
+        breadcrumbs.crumbs.pop_front();
+    }
+}
+
+/// The trail as a line through the crumbs, each with a ring and a pole, fading towards the oldest
+pub fn draw_breadcrumbs(mut gizmos: Gizmos, breadcrumbs: Res<Breadcrumbs>, aircraft_query: Query<&Transform, With<Aircraft>>) {
+    if !breadcrumbs.enabled || breadcrumbs.crumbs.is_empty() {
+        return;
+    }
+    let count = breadcrumbs.crumbs.len();
+    let alpha = |index: usize| OLDEST_ALPHA + (1.0 - OLDEST_ALPHA) * (index + 1) as f32 / count as f32;
+
+    let mut previous: Option<Vec3> = None;
+    for (index, &crumb) in breadcrumbs.crumbs.iter().enumerate() {
+        let color = CRUMB_COLOR.with_alpha(alpha(index));
+        if let Some(previous) = previous {
+            gizmos.line(previous, crumb, color.with_alpha(alpha(index) * 0.6));
+        }
+        gizmos.circle(Isometry3d::new(crumb, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)), CRUMB_RADIUS, color);
+        gizmos.line(crumb, crumb - Vec3::Y * POLE_LENGTH, color.with_alpha(alpha(index) * 0.4));
+        previous = Some(crumb);
+    }
+    // Join the newest crumb to the aircraft so the trail never looks cut short
+    if let (Some(last), Ok(transform)) = (previous, aircraft_query.single()) {
+        gizmos.line(last, transform.translation, CRUMB_COLOR.with_alpha(0.6));
+    }
+}
@@ -0,0 +1,407 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, ControlInputs, ControlMode, PilotKeys};
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::home_base::spawn_point;
+use crate::hud::HudPalette;
+use crate::network::{NetworkClient, RespawnAircraft};
+use crate::teleport::Teleport;
+use crate::world_generation::{Biome, WorldGenerator};
+
+/// Flight deck size and its height above the waterline, about 270 by 40 m and 17 m up
+const DECK_LENGTH: f32 = 1400.0;
+const DECK_WIDTH: f32 = 210.0;
+const DECK_HEIGHT: f32 = 90.0;
+/// The island superstructure on the starboard edge, as width, height above the deck, length
+const ISLAND: Vec3 = Vec3::new(30.0, 80.0, 200.0);
+const ISLAND_OFFSET: f32 = DECK_WIDTH * 0.5 - 20.0;
+/// About 15 m/s, a carrier's cruise
+const CARRIER_SPEED: f32 = 80.0;
+/// Radians per second the carrier comes round when there is shallow water ahead
+const CARRIER_TURN_RATE: f32 = 0.04;
+const CARRIER_LOOKAHEAD: f32 = 4000.0;
+/// Sea floor must be at least this deep for the carrier to sail there
+const CARRIER_MIN_DEPTH: f32 = 60.0;
+/// Rings searched around the spawn point for open water to put the carrier in
+const PLACEMENT_FIRST_RING: f32 = 3000.0;
+const PLACEMENT_RING_SPACING: f32 = 2000.0;
+const PLACEMENT_RINGS: usize = 20;
+const PLACEMENT_BEARINGS: usize = 16;
+
+/// Fastest sink onto the deck, relative to it, that the gear takes; about 6 m/s
+const MAX_SINK_RATE: f32 = 32.0;
+/// Wings must be within this much bank, as the sine of the angle, and the nose no lower than this
+const MAX_BANK_SINE: f32 = 0.26;
+const MAX_NOSE_DOWN_SINE: f32 = 0.2;
+/// Further below the deck than this and the aircraft went into the side of the hull instead
+const DECK_CONTACT_DEPTH: f32 = 25.0;
+/// Height of the aircraft's origin over the deck while on its wheels
+const GEAR_HEIGHT: f32 = 3.0;
+/// Touchdowns on this stretch of deck at the stern catch a wire, if lined up with the deck
+const WIRE_ZONE: f32 = 350.0;
+const WIRE_ALIGNMENT: f32 = 0.9;
+/// A wire stops the aircraft at about 3.5 g
+const ARREST_DECELERATION: f32 = 180.0;
+/// Engine push along the deck per unit of thrust, the same as in the air
+const DECK_THRUST: f32 = 50.0;
+/// Rolling resistance, and the brakes with the throttle closed
+const ROLLING_RESISTANCE: f32 = 15.0;
+const BRAKE_DECELERATION: f32 = 80.0;
+/// Radians per second of nosewheel steering at full rudder
+const DECK_STEER_RATE: f32 = 0.6;
+/// Airspeed over the wing, deck speed and the carrier's own included, to lift off with the stick back
+const TAKEOFF_AIRSPEED: f32 = 170.0;
+/// The deck readout shows while the carrier is this close
+const APPROACH_RANGE: f32 = 8000.0;
+
+/// An aircraft carrier steaming across the open ocean, with a flat deck to land on
+#[derive(Component)]
+pub struct Carrier {
+    /// Unit direction it is steaming
+    heading: Vec3,
+    pub velocity: Vec3,
+}
+
+/// The local aircraft on a carrier's deck. It rides along in the carrier's frame rather than flying,
+/// and is handed back to the flight model with the deck's motion added when it leaves
+pub struct DeckContact {
+    /// Pose in the carrier's frame, so the deck's motion and turns carry the aircraft with it
+    local: Transform,
+    /// Ground speed along the deck, relative to the deck
+    roll_speed: f32,
+    /// Caught a wire and being pulled to a stop
+    pub arrested: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct CarrierOps {
+    pub carrier: Option<Entity>,
+    /// World the carrier was placed for, so a new world gets its own
+    seed: Option<u32>,
+    pub on_deck: Option<DeckContact>,
+    /// Sink rate relative to the deck at the last touchdown, in world units per second
+    pub last_sink_rate: Option<f32>,
+}
+
+/// Deep water well away from the shore
+fn deep_water(world_gen: &WorldGenerator, x: f32, z: f32) -> bool {
+    let pos = [x, 0.0, z];
+    world_gen.get_biome(&pos) == Biome::Ocean && world_gen.get_terrain_height(&pos) < -CARRIER_MIN_DEPTH
+}
+
+/// Nearest open water to `center` with room ahead to steam, searched ring by ring
+fn find_station(world_gen: &WorldGenerator, center: Vec3) -> Option<(Vec3, Vec3)> {
+    for ring in 0..PLACEMENT_RINGS {
+        let radius = PLACEMENT_FIRST_RING + ring as f32 * PLACEMENT_RING_SPACING;
+        for bearing in 0..PLACEMENT_BEARINGS {
+            let angle = bearing as f32 / PLACEMENT_BEARINGS as f32 * std::f32::consts::TAU;
+            let position = center + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+            // Steam across the bearing, so the carrier passes the spawn point rather than heading at the coast
+            let heading = Vec3::new(-angle.sin(), 0.0, angle.cos());
+            let ahead = position + heading * CARRIER_LOOKAHEAD;
+            if deep_water(world_gen, position.x, position.z) && deep_water(world_gen, ahead.x, ahead.z) {
+                return Some((position.with_y(0.0), heading));
+            }
+        }
+    }
+    None
+}
+
+/// Put a carrier in the nearest open water to the spawn point whenever the world changes
+pub fn place_carrier(
+    mut commands: Commands,
+    world_gen: Res<WorldGenerator>,
+    client: Option<Res<NetworkClient>>,
+    mut ops: ResMut<CarrierOps>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if ops.seed == Some(world_gen.seed) {
+        return;
+    }
+    ops.seed = Some(world_gen.seed);
+    ops.on_deck = None;
+    if let Some(carrier) = ops.carrier.take() {
+        commands.entity(carrier).try_despawn();
+    }
+    let Some((position, heading)) = find_station(&world_gen, spawn_point(&world_gen, client.as_deref())) else {
+        info!("No open water near the spawn point for a carrier");
+        return;
+    };
+
+    let hull_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.42, 0.45, 0.48),
+        perceptual_roughness: 0.8,
+        ..default()
+    });
+    let deck_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.22, 0.23, 0.24),
+        perceptual_roughness: 0.95,
+        ..default()
+    });
+    let marking_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.95, 0.95, 0.9),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    // The hull runs well below the waterline so the sea never shows under it
+    let hull_depth = 40.0;
+    let carrier = commands.spawn((
+        Transform::from_translation(position).looking_to(heading, Vec3::Y),
+        Visibility::default(),
+        Carrier { heading, velocity: heading * CARRIER_SPEED },
+    )).with_children(|parent| {
+        parent.spawn((
+            Mesh3d(meshes.add(Cuboid::new(DECK_WIDTH * 0.7, DECK_HEIGHT + hull_depth, DECK_LENGTH * 0.92))),
+            MeshMaterial3d(hull_material.clone()),
+            Transform::from_xyz(0.0, (DECK_HEIGHT - hull_depth) * 0.5 - 2.0, 0.0),
+        ));
+        parent.spawn((
+            Mesh3d(meshes.add(Cuboid::new(DECK_WIDTH, 4.0, DECK_LENGTH))),
+            MeshMaterial3d(deck_material),
+            Transform::from_xyz(0.0, DECK_HEIGHT - 2.0, 0.0),
+        ));
+        parent.spawn((
+            Mesh3d(meshes.add(Cuboid::new(3.0, 0.5, DECK_LENGTH * 0.9))),
+            MeshMaterial3d(marking_material.clone()),
+            Transform::from_xyz(0.0, DECK_HEIGHT + 0.3, 0.0),
+            NotShadowCaster,
+        ));
+        // The wires, across the deck near the stern
+        for wire in 0..4 {
+            parent.spawn((
+                Mesh3d(meshes.add(Cuboid::new(DECK_WIDTH * 0.6, 0.5, 1.5))),
+                MeshMaterial3d(marking_material.clone()),
+                Transform::from_xyz(0.0, DECK_HEIGHT + 0.3, DECK_LENGTH * 0.5 - WIRE_ZONE * (0.3 + wire as f32 * 0.15)),
+                NotShadowCaster,
+            ));
+        }
+        parent.spawn((
+            Mesh3d(meshes.add(Cuboid::new(ISLAND.x, ISLAND.y, ISLAND.z))),
+            MeshMaterial3d(hull_material),
+            Transform::from_xyz(ISLAND_OFFSET, DECK_HEIGHT + ISLAND.y * 0.5, 0.0),
+        ));
+    }).id();
+    ops.carrier = Some(carrier);
+    info!("Carrier on station at [{:.0}, {:.0}]", position.x, position.z);
+}
+
+/// Steam ahead, coming round to starboard while there's shallow water in the way
+pub fn steam_carrier(time: Res<Time>, world_gen: Res<WorldGenerator>, mut carriers: Query<(&mut Transform, &mut Carrier)>) {
+    let dt = time.delta_secs();
+    for (mut transform, mut carrier) in &mut carriers {
+        let ahead = transform.translation + carrier.heading * CARRIER_LOOKAHEAD;
+        if !deep_water(&world_gen, ahead.x, ahead.z) {
+            carrier.heading = Quat::from_rotation_y(-CARRIER_TURN_RATE * dt) * carrier.heading;
+        }
+        carrier.velocity = carrier.heading * CARRIER_SPEED;
+        transform.translation += carrier.velocity * dt;
+        transform.look_to(carrier.heading, Vec3::Y);
+    }
+}
+
+/// Whether a point in the carrier's frame is inside the island
+fn in_island(local: Vec3) -> bool {
+    (local.x - ISLAND_OFFSET).abs() <= ISLAND.x * 0.5 && local.z.abs() <= ISLAND.z * 0.5 && local.y <= DECK_HEIGHT + ISLAND.y
+}
+
+fn on_deck_footprint(local: Vec3) -> bool {
+    local.x.abs() <= DECK_WIDTH * 0.5 && local.z.abs() <= DECK_LENGTH * 0.5
+}
+
+enum Touchdown {
+    Landed(DeckContact),
+    Crashed,
+}
+
+/// How the aircraft met the carrier this frame, if it did
+fn check_touchdown(carrier_transform: &Transform, carrier: &Carrier, transform: &Transform, aircraft: &Aircraft) -> Option<Touchdown> {
+    let to_deck = carrier_transform.rotation.inverse();
+    let local = to_deck * (transform.translation - carrier_transform.translation);
+    if in_island(local) && local.y >= 0.0 {
+        return Some(Touchdown::Crashed);
+    }
+    if !on_deck_footprint(local) || local.y > DECK_HEIGHT || local.y < 0.0 {
+        return None;
+    }
+    if local.y < DECK_HEIGHT - DECK_CONTACT_DEPTH {
+        return Some(Touchdown::Crashed);
+    }
+
+    // Everything is judged against the deck, which is moving and may be turning under the aircraft
+    let relative = to_deck * (aircraft.velocity - carrier.velocity);
+    let sink_rate = -relative.y;
+    if sink_rate > MAX_SINK_RATE || transform.right().y.abs() > MAX_BANK_SINE || transform.forward().y < -MAX_NOSE_DOWN_SINE {
+        return Some(Touchdown::Crashed);
+    }
+    let heading = (to_deck * transform.forward().as_vec3()).with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let arrested = local.z >= DECK_LENGTH * 0.5 - WIRE_ZONE && heading.dot(Vec3::NEG_Z) >= WIRE_ALIGNMENT;
+    Some(Touchdown::Landed(DeckContact {
+        local: Transform::from_xyz(local.x, DECK_HEIGHT + GEAR_HEIGHT, local.z).looking_to(heading, Vec3::Y),
+        roll_speed: relative.dot(heading).max(0.0),
+        arrested,
+    }))
+}
+
+fn crash_into_carrier(aircraft: &mut Aircraft, transform: &Transform, control_mode: &mut ControlMode, commands: &mut Commands) {
+    let impact_velocity = aircraft.velocity;
+    aircraft.crashed = true;
+    aircraft.speed = 0.0;
+    aircraft.velocity = Vec3::ZERO;
+    aircraft.pitch_velocity = 0.0;
+    aircraft.roll_velocity = 0.0;
+    aircraft.yaw_velocity = 0.0;
+    control_mode.physics_paused = true;
+    commands.trigger(SpawnEffect {
+        kind: EffectKind::Explosion,
+        position: transform.translation,
+        velocity: impact_velocity,
+        intensity: 1.0,
+    });
+    info!("Aircraft crashed into the carrier at [{:.1}, {:.1}, {:.1}]", transform.translation.x, transform.translation.y, transform.translation.z);
+}
+
+pub fn leave_deck_on_respawn(_trigger: On<RespawnAircraft>, mut ops: ResMut<CarrierOps>) {
+    ops.on_deck = None;
+}
+
+/// Otherwise the deck pose would pull the aircraft straight back aboard
+pub fn leave_deck_on_teleport(_trigger: On<Teleport>, mut ops: ResMut<CarrierOps>) {
+    ops.on_deck = None;
+}
+
+/// Catch touchdowns on the deck, then roll, steer and launch the aircraft in the carrier's frame.
+/// The flight model keeps running underneath so the engine spools as usual; its movement is overridden here
+pub fn update_deck_contact(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut ops: ResMut<CarrierOps>,
+    mut control_mode: ResMut<ControlMode>,
+    carriers: Query<(&Transform, &Carrier), Without<Aircraft>>,
+    mut aircraft_query: Query<(&mut Transform, &mut Aircraft)>,
+    mut commands: Commands,
+) {
+    let Ok((mut transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    let Some((carrier_transform, carrier)) = ops.carrier.and_then(|entity| carriers.get(entity).ok()) else {
+        ops.on_deck = None;
+        return;
+    };
+    if aircraft.crashed {
+        ops.on_deck = None;
+        return;
+    }
+
+    let Some(contact) = ops.on_deck.as_mut() else {
+        if control_mode.physics_paused {
+            return;
+        }
+        match check_touchdown(carrier_transform, carrier, &transform, &aircraft) {
+            Some(Touchdown::Landed(contact)) => {
+                let sink_rate = -(carrier_transform.rotation.inverse() * (aircraft.velocity - carrier.velocity)).y;
+                info!(
+                    "Landed on the carrier, sinking at {:.1} m/s{}",
+                    world_units_to_meters(sink_rate),
+                    if contact.arrested { ", caught a wire" } else { "" },
+                );
+                commands.trigger(SpawnEffect {
+                    kind: EffectKind::Scrape,
+                    position: transform.translation,
+                    velocity: carrier.velocity,
+                    intensity: 0.5,
+                });
+                // Put it on the deck this frame rather than leave it sunk into it
+                *transform = carrier_transform.mul_transform(contact.local);
+                ops.last_sink_rate = Some(sink_rate);
+                ops.on_deck = Some(contact);
+            }
+            Some(Touchdown::Crashed) => crash_into_carrier(&mut aircraft, &transform, &mut control_mode, &mut commands),
+            None => {}
+        }
+        return;
+    };
+
+    // Paused physics freezes the roll, but the aircraft still rides along with the deck
+    let dt = if control_mode.physics_paused { 0.0 } else { time.delta_secs() };
+    let inputs = ControlInputs::from_keyboard(&keyboard, &PilotKeys::PRIMARY);
+    if contact.arrested {
+        contact.roll_speed = (contact.roll_speed - ARREST_DECELERATION * dt).max(0.0);
+        contact.arrested = contact.roll_speed > 0.0;
+    } else {
+        let push = aircraft.power * aircraft.thrust * DECK_THRUST;
+        let resistance = if aircraft.throttle <= 0.0 { BRAKE_DECELERATION } else { ROLLING_RESISTANCE };
+        contact.roll_speed = (contact.roll_speed + (push - resistance) * dt).max(0.0);
+    }
+    contact.local.rotate_y(-inputs.yaw * DECK_STEER_RATE * dt);
+    let local_forward = contact.local.forward().as_vec3();
+    contact.local.translation += local_forward * contact.roll_speed * dt;
+
+    *transform = carrier_transform.mul_transform(contact.local);
+    let forward = transform.forward().as_vec3();
+    let deck_velocity = forward * contact.roll_speed;
+    // The carrier steaming ahead puts wind over the deck, so airspeed counts its motion along the nose too
+    let airspeed = (contact.roll_speed + carrier.velocity.dot(forward)).max(0.0);
+    aircraft.speed = airspeed;
+    aircraft.velocity = carrier.velocity + deck_velocity;
+    aircraft.pitch_velocity = 0.0;
+    aircraft.roll_velocity = 0.0;
+    aircraft.yaw_velocity = 0.0;
+    aircraft.spin_rate = 0.0;
+
+    let local = contact.local.translation;
+    if in_island(local) {
+        ops.on_deck = None;
+        crash_into_carrier(&mut aircraft, &transform, &mut control_mode, &mut commands);
+    } else if airspeed >= TAKEOFF_AIRSPEED && inputs.pitch > 0.0 {
+        // The flight model carries on from here with the deck's speed in `speed` and `velocity`
+        ops.on_deck = None;
+        info!("Launched from the carrier at {:.0} m/s", world_units_to_meters(airspeed));
+    } else if !on_deck_footprint(local) {
+        ops.on_deck = None;
+        info!("Rolled off the edge of the carrier's deck");
+    }
+}
+
+/// Deck state while aboard, and the carrier's range and speed while approaching it
+pub fn carrier_hud(
+    mut contexts: EguiContexts,
+    ops: Res<CarrierOps>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    carriers: Query<(&Transform, &Carrier)>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) -> Result<(), BevyError> {
+    let Some((carrier_transform, carrier)) = ops.carrier.and_then(|entity| carriers.get(entity).ok()) else { return Ok(()) };
+    let Ok(aircraft_transform) = aircraft_query.single() else { return Ok(()) };
+    let distance = aircraft_transform.translation.distance(carrier_transform.translation);
+    if ops.on_deck.is_none() && distance > APPROACH_RANGE {
+        return Ok(());
+    }
+
+    // Under the heading window, the return-home readout and the tag score
+    egui::Window::new("Carrier")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 310.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("CARRIER").size(12.0));
+            match &ops.on_deck {
+                Some(contact) => {
+                    ui.label(if contact.arrested { "Caught the wire" } else { "On deck" });
+                    if let Some(sink_rate) = ops.last_sink_rate {
+                        ui.label(format!("Touchdown sink rate {}", units.format_climb_rate(-world_units_to_meters(sink_rate))));
+                    }
+                    ui.label("Throttle up and pull back to launch");
+                }
+                None => {
+                    ui.label(format!("Range {}", units.format_distance(world_units_to_meters(distance))));
+                    ui.label(format!("Deck moving at {}", units.format_speed(world_units_to_meters(carrier.velocity.length()))));
+                }
+            }
+        });
+
+    Ok(())
+}
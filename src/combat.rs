@@ -0,0 +1,456 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::GRAVITY;
+use crate::controls::{Aircraft, ControlMode, Difficulty, TerrainScrape, Wind};
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::hud::HudPalette;
+use crate::network::{
+    ClientMessage, IncomingHit, NetworkClient, PlayerShotDown, RemoteGunfire, RemotePlayer, RespawnAircraft,
+};
+use crate::world_generation::WorldGenerator;
+
+const FIRE_INTERVAL: f32 = 1.0 / 12.0;
+/// Roughly 900 m/s, added to the aircraft's own velocity
+const MUZZLE_VELOCITY: f32 = 4700.0;
+const TRACER_LIFETIME: f32 = 2.5;
+const TRACER_LENGTH: f32 = 40.0;
+/// Rounds leave from ahead of the aircraft origin so they clear the nose
+const GUN_OFFSET: f32 = 30.0;
+const ROUND_DAMAGE: f32 = 10.0;
+
+const MAX_HEALTH: f32 = 100.0;
+/// Hit sphere around another player's aircraft
+const AIRCRAFT_HIT_RADIUS: f32 = 60.0;
+
+const TARGET_COUNT: usize = 10;
+const BALLOON_RADIUS: f32 = 40.0;
+const BALLOON_HEALTH: f32 = 20.0;
+const TARGET_MIN_DISTANCE: f32 = 2500.0;
+const TARGET_MAX_DISTANCE: f32 = 7000.0;
+/// Targets left this far behind are recycled near the player
+const TARGET_RECYCLE_DISTANCE: f32 = 12000.0;
+const TARGET_MIN_HEIGHT: f32 = 400.0;
+const TARGET_MAX_HEIGHT: f32 = 2000.0;
+const TARGET_BOB_HEIGHT: f32 = 15.0;
+
+/// Seconds the hit marker stays on the HUD after one of our rounds connects
+const HIT_MARKER_TIME: f32 = 0.3;
+
+#[derive(Resource, Default)]
+pub struct CombatSettings {
+    pub enabled: bool,
+}
+
+/// Local health and score for the combat mode
+#[derive(Resource)]
+pub struct CombatState {
+    pub health: f32,
+    pub targets_destroyed: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub hits: u32,
+    cooldown: f32,
+    hit_marker: f32,
+}
+
+impl Default for CombatState {
+    fn default() -> Self {
+        Self {
+            health: MAX_HEALTH,
+            targets_destroyed: 0,
+            kills: 0,
+            deaths: 0,
+            hits: 0,
+            cooldown: 0.0,
+            hit_marker: 0.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Tracer {
+    velocity: Vec3,
+    age: f32,
+    /// Only our own rounds are hit-tested; other players detect their own hits
+    owned: bool,
+}
+
+#[derive(Component)]
+pub struct BalloonTarget {
+    health: f32,
+    base_height: f32,
+    phase: f32,
+}
+
+#[derive(Resource)]
+pub struct CombatAssets {
+    tracer: Handle<Mesh>,
+    tracer_material: Handle<StandardMaterial>,
+    remote_tracer_material: Handle<StandardMaterial>,
+    balloon: Handle<Mesh>,
+    balloon_material: Handle<StandardMaterial>,
+    string: Handle<Mesh>,
+    string_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_combat(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let glowing = |color: Color, emissive: LinearRgba| StandardMaterial {
+        base_color: color,
+        emissive,
+        unlit: true,
+        ..default()
+    };
+
+    commands.insert_resource(CombatAssets {
+        tracer: meshes.add(Cuboid::new(1.5, 1.5, TRACER_LENGTH)),
+        tracer_material: materials.add(glowing(Color::srgb(1.0, 0.8, 0.3), LinearRgba::rgb(30.0, 18.0, 4.0))),
+        remote_tracer_material: materials.add(glowing(Color::srgb(1.0, 0.3, 0.2), LinearRgba::rgb(30.0, 6.0, 3.0))),
+        balloon: meshes.add(Sphere::new(BALLOON_RADIUS).mesh().uv(24, 16)),
+        balloon_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.15, 0.1),
+            perceptual_roughness: 0.35,
+            ..default()
+        }),
+        string: meshes.add(Cylinder::new(0.8, 120.0)),
+        string_material: materials.add(Color::srgb(0.85, 0.85, 0.8)),
+    });
+}
+
+fn spawn_tracer(commands: &mut Commands, assets: &CombatAssets, position: Vec3, velocity: Vec3, owned: bool) {
+    let material = if owned { &assets.tracer_material } else { &assets.remote_tracer_material };
+    commands.spawn((
+        Mesh3d(assets.tracer.clone()),
+        MeshMaterial3d(material.clone()),
+        Transform::from_translation(position).looking_to(velocity, Vec3::Y),
+        Tracer { velocity, age: 0.0, owned },
+        NotShadowCaster,
+    ));
+}
+
+/// Space fires the guns while combat is enabled
+pub fn fire_guns(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    settings: Res<CombatSettings>,
+    control_mode: Res<ControlMode>,
+    assets: Res<CombatAssets>,
+    mut state: ResMut<CombatState>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) {
+    state.cooldown = (state.cooldown - time.delta_secs()).max(0.0);
+    state.hit_marker = (state.hit_marker - time.delta_secs()).max(0.0);
+
+    if !settings.enabled || control_mode.physics_paused || !keyboard.pressed(KeyCode::Space) || state.cooldown > 0.0 {
+        return;
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed || state.health <= 0.0 {
+        return;
+    }
+    state.cooldown = FIRE_INTERVAL;
+
+    let forward = transform.forward().as_vec3();
+    let position = transform.translation + forward * GUN_OFFSET;
+    let velocity = aircraft.velocity + forward * MUZZLE_VELOCITY;
+    spawn_tracer(&mut commands, &assets, position, velocity, true);
+
+    if let Some(client) = client.filter(|client| client.connected) {
+        client.send(ClientMessage::Fire { position: position.into(), velocity: velocity.into() });
+    }
+}
+
+/// Whether the segment `start..end` passes within `radius` of `center`
+fn segment_hits_sphere(start: Vec3, end: Vec3, center: Vec3, radius: f32) -> bool {
+    let segment = end - start;
+    let t = ((center - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    (start + segment * t).distance_squared(center) <= radius * radius
+}
+
+/// Fly tracers with ballistic drop and resolve what our rounds hit
+pub fn update_tracers(
+    time: Res<Time>,
+    world_gen: Res<WorldGenerator>,
+    mut state: ResMut<CombatState>,
+    client: Option<Res<NetworkClient>>,
+    mut tracers: Query<(Entity, &mut Tracer, &mut Transform)>,
+    mut balloons: Query<(Entity, &mut BalloonTarget, &Transform), Without<Tracer>>,
+    remote_players: Query<(&RemotePlayer, &Transform), (Without<Tracer>, Without<BalloonTarget>)>,
+    mut commands: Commands,
+) {
+    let dt = time.delta_secs();
+    let client = client.filter(|client| client.connected);
+
+    for (entity, mut tracer, mut transform) in tracers.iter_mut() {
+        tracer.age += dt;
+        tracer.velocity.y -= GRAVITY * dt;
+        let start = transform.translation;
+        let end = start + tracer.velocity * dt;
+        transform.translation = end;
+        transform.look_to(tracer.velocity, Vec3::Y);
+
+        let terrain_height = world_gen.get_terrain_height(&[end.x, end.y, end.z]).max(0.0);
+        if tracer.age >= TRACER_LIFETIME || end.y <= terrain_height {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if !tracer.owned {
+            continue;
+        }
+
+        let mut hit = false;
+        for (balloon_entity, mut balloon, balloon_transform) in balloons.iter_mut() {
+            if balloon.health <= 0.0 || !segment_hits_sphere(start, end, balloon_transform.translation, BALLOON_RADIUS) {
+                continue;
+            }
+            hit = true;
+            balloon.health -= ROUND_DAMAGE;
+            if balloon.health <= 0.0 {
+                state.targets_destroyed += 1;
+                commands.trigger(SpawnEffect {
+                    kind: EffectKind::Pop,
+                    position: balloon_transform.translation,
+                    velocity: Vec3::ZERO,
+                    intensity: 1.0,
+                });
+                commands.entity(balloon_entity).despawn();
+            }
+            break;
+        }
+
+        if !hit && let Some(client) = client.as_ref() {
+            let target = remote_players.iter()
+                .find(|(_, remote)| segment_hits_sphere(start, end, remote.translation, AIRCRAFT_HIT_RADIUS));
+            if let Some((remote, _)) = target {
+                client.send(ClientMessage::Hit { target: remote.player_id });
+                hit = true;
+            }
+        }
+
+        if hit {
+            state.hits += 1;
+            state.hit_marker = HIT_MARKER_TIME;
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn random_range(min: f32, max: f32) -> f32 {
+    min + rand::random::<f32>() * (max - min)
+}
+
+/// Keep a field of balloons around the player, drifting with the wind
+pub fn maintain_targets(
+    time: Res<Time>,
+    settings: Res<CombatSettings>,
+    wind: Res<Wind>,
+    world_gen: Res<WorldGenerator>,
+    assets: Res<CombatAssets>,
+    player_query: Query<&Transform, (With<Aircraft>, Without<BalloonTarget>)>,
+    mut balloons: Query<(Entity, &BalloonTarget, &mut Transform)>,
+    mut commands: Commands,
+) {
+    if !settings.enabled {
+        for (entity, _, _) in balloons.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else { return };
+    let player_pos = player_transform.translation;
+
+    let t = time.elapsed_secs();
+    let drift = Vec3::new(wind.wind_direction.x, 0.0, wind.wind_direction.z) * wind.wind_speed * time.delta_secs();
+    let mut alive = 0;
+    for (entity, balloon, mut transform) in balloons.iter_mut() {
+        let offset = transform.translation - player_pos;
+        if Vec2::new(offset.x, offset.z).length() > TARGET_RECYCLE_DISTANCE {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        alive += 1;
+        transform.translation += drift;
+        transform.translation.y = balloon.base_height + (t * 0.6 + balloon.phase).sin() * TARGET_BOB_HEIGHT;
+    }
+
+    for _ in alive..TARGET_COUNT {
+        let angle = rand::random::<f32>() * std::f32::consts::TAU;
+        let distance = random_range(TARGET_MIN_DISTANCE, TARGET_MAX_DISTANCE);
+        let x = player_pos.x + angle.cos() * distance;
+        let z = player_pos.z + angle.sin() * distance;
+        let ground = world_gen.get_terrain_height(&[x, 0.0, z]).max(0.0);
+        let base_height = ground + random_range(TARGET_MIN_HEIGHT, TARGET_MAX_HEIGHT);
+
+        let balloon = commands.spawn((
+            Mesh3d(assets.balloon.clone()),
+            MeshMaterial3d(assets.balloon_material.clone()),
+            Transform::from_xyz(x, base_height, z),
+            BalloonTarget {
+                health: BALLOON_HEALTH,
+                base_height,
+                phase: rand::random::<f32>() * std::f32::consts::TAU,
+            },
+        )).id();
+        let string = commands.spawn((
+            Mesh3d(assets.string.clone()),
+            MeshMaterial3d(assets.string_material.clone()),
+            Transform::from_xyz(0.0, -BALLOON_RADIUS - 60.0, 0.0),
+            NotShadowCaster,
+        )).id();
+        commands.entity(balloon).add_child(string);
+    }
+}
+
+/// Show the tracers of other players' gunfire
+pub fn spawn_remote_tracer(
+    trigger: On<RemoteGunfire>,
+    assets: Res<CombatAssets>,
+    mut commands: Commands,
+) {
+    spawn_tracer(&mut commands, &assets, trigger.position, trigger.velocity, false);
+}
+
+/// Apply a round's damage for each hit another player reports, going down when health runs out.
+/// The damage is ours to decide, so a shooter can't claim more than a round does
+pub fn take_hit(
+    trigger: On<IncomingHit>,
+    settings: Res<CombatSettings>,
+    difficulty: Res<Difficulty>,
+    mut state: ResMut<CombatState>,
+    mut control_mode: ResMut<ControlMode>,
+    mut aircraft_query: Query<(&Transform, &mut Aircraft)>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) {
+    // Combat is opt-in: players who haven't enabled it can't be shot down
+    if !settings.enabled || state.health <= 0.0 {
+        return;
+    }
+    let Ok((transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    if aircraft.crashed {
+        return;
+    }
+
+    state.health = (state.health - ROUND_DAMAGE * difficulty.damage_scale).max(0.0);
+    if state.health > 0.0 {
+        return;
+    }
+
+    state.deaths += 1;
+    commands.trigger(SpawnEffect {
+        kind: EffectKind::Explosion,
+        position: transform.translation,
+        velocity: aircraft.velocity,
+        intensity: 1.0,
+    });
+    aircraft.crashed = true;
+    aircraft.speed = 0.0;
+    aircraft.velocity = Vec3::ZERO;
+    control_mode.physics_paused = true;
+
+    if let Some(client) = client.filter(|client| client.connected) {
+        client.send(ClientMessage::ShotDown { by: trigger.shooter });
+    }
+    info!("Shot down by player {}", trigger.shooter);
+}
+
+/// Skidding off the ground wears down health in combat, and crashes an aircraft that runs out
+pub fn take_scrape_damage(
+    trigger: On<TerrainScrape>,
+    settings: Res<CombatSettings>,
+    difficulty: Res<Difficulty>,
+    mut state: ResMut<CombatState>,
+    mut control_mode: ResMut<ControlMode>,
+    mut aircraft_query: Query<&mut Aircraft>,
+    mut commands: Commands,
+) {
+    if !settings.enabled || state.health <= 0.0 {
+        return;
+    }
+    let Ok(mut aircraft) = aircraft_query.single_mut() else { return };
+
+    state.health = (state.health - trigger.damage * difficulty.damage_scale).max(0.0);
+    if state.health > 0.0 {
+        return;
+    }
+
+    state.deaths += 1;
+    commands.trigger(SpawnEffect {
+        kind: EffectKind::Explosion,
+        position: trigger.position,
+        velocity: aircraft.velocity,
+        intensity: 1.0,
+    });
+    aircraft.crashed = true;
+    aircraft.speed = 0.0;
+    aircraft.velocity = Vec3::ZERO;
+    control_mode.physics_paused = true;
+    info!("Wrecked by scraping the terrain");
+}
+
+/// Blow up another player's aircraft and credit the kill if it was ours
+pub fn record_shot_down(
+    trigger: On<PlayerShotDown>,
+    mut state: ResMut<CombatState>,
+    client: Option<Res<NetworkClient>>,
+    remote_players: Query<(&RemotePlayer, &Transform)>,
+    mut commands: Commands,
+) {
+    if let Some((_, transform)) = remote_players.iter().find(|(remote, _)| remote.player_id == trigger.id) {
+        commands.trigger(SpawnEffect {
+            kind: EffectKind::Explosion,
+            position: transform.translation,
+            velocity: Vec3::ZERO,
+            intensity: 1.0,
+        });
+    }
+
+    if client.and_then(|client| client.player_id) == Some(trigger.by) {
+        state.kills += 1;
+        info!("Shot down player {}", trigger.id);
+    }
+}
+
+pub fn restore_health(_trigger: On<RespawnAircraft>, mut state: ResMut<CombatState>) {
+    state.health = MAX_HEALTH;
+}
+
+/// Health bar and score while combat is enabled
+pub fn combat_hud(
+    mut contexts: EguiContexts,
+    settings: Res<CombatSettings>,
+    state: Res<CombatState>,
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    egui::Window::new("Combat")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 140.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("COMBAT").size(12.0));
+            ui.add(
+                egui::ProgressBar::new(state.health / MAX_HEALTH)
+                    .desired_width(140.0)
+                    .text(format!("Health {:.0}", state.health)),
+            );
+            ui.label(format!("Balloons: {}", state.targets_destroyed));
+            ui.label(format!("Kills: {}  Deaths: {}", state.kills, state.deaths));
+            let marker = if state.hit_marker > 0.0 { "  HIT" } else { "" };
+            ui.label(format!("Hits: {}{}", state.hits, marker));
+            ui.label(egui::RichText::new("Space to fire").size(10.0));
+        });
+
+    Ok(())
+}
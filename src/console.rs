@@ -0,0 +1,356 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::aircraft_presets::{fly_preset, AircraftDefinition, AircraftSelection};
+use crate::controls::{Aircraft, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::hud::MultiplayerMenu;
+use crate::network::{self, NetworkClient};
+use crate::consts::CHUNK_SIZE;
+use crate::teleport::Teleport;
+use crate::world_generation::{generate_chunk_heightfield, ResetChunks, WorldGenerator};
+
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+const MAX_OUTPUT_LINES: usize = 200;
+const CONSOLE_HEIGHT: f32 = 260.0;
+/// Subdivisions `terrain_hash` samples a chunk at
+const TERRAIN_HASH_LOD: u32 = 32;
+
+/// Command names with their usage, in the order `help` lists them
+const COMMANDS: &[(&str, &str)] = &[
+    ("tp", "tp <x> <y> <z>: teleport the aircraft, in world units; offline only"),
+    ("time", "time <0-1>: set the time of day (0.5 is noon)"),
+    ("wind", "wind <speed> <heading>: hold the wind steady, heading as shown on the HUD"),
+    ("seed", "seed <n>: regenerate the world from a new seed"),
+    ("reset_terrain", "reset_terrain: undo every terrain edit on this seed"),
+    ("terrain_hash", "terrain_hash: checksum of the seed's terrain under the aircraft, to compare between clients"),
+    ("spawn", "spawn <aircraft>: switch to an aircraft preset"),
+    ("connect", "connect <host:port>: join a multiplayer server"),
+    ("help", "help: list commands"),
+    ("clear", "clear: clear the console"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum ConsoleCommand {
+    Teleport(Vec3),
+    Time(f32),
+    Wind { speed: f32, heading: f32 },
+    Seed(u32),
+    ResetTerrain,
+    TerrainHash,
+    Spawn(String),
+    Connect(String),
+    Help,
+    Clear,
+}
+
+fn parse_args<T: std::str::FromStr>(args: &[&str], count: usize, usage: &str) -> Result<Vec<T>, String> {
+    if args.len() != count {
+        return Err(format!("usage: {}", usage));
+    }
+    args.iter()
+        .map(|arg| arg.parse().map_err(|_| format!("couldn't parse '{}'; usage: {}", arg, usage)))
+        .collect()
+}
+
+impl ConsoleCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else { return Err("empty command".to_string()) };
+        let args: Vec<&str> = words.collect();
+        let usage = COMMANDS
+            .iter()
+            .find(|(command, _)| *command == name)
+            .map(|(_, usage)| *usage)
+            .ok_or_else(|| format!("unknown command '{}', try 'help'", name))?;
+
+        match name {
+            "tp" => {
+                let xyz: Vec<f32> = parse_args(&args, 3, usage)?;
+                Ok(Self::Teleport(Vec3::new(xyz[0], xyz[1], xyz[2])))
+            }
+            "time" => Ok(Self::Time(parse_args::<f32>(&args, 1, usage)?[0])),
+            "wind" => {
+                let values: Vec<f32> = parse_args(&args, 2, usage)?;
+                Ok(Self::Wind { speed: values[0], heading: values[1] })
+            }
+            "seed" => Ok(Self::Seed(parse_args::<u32>(&args, 1, usage)?[0])),
+            "reset_terrain" => Ok(Self::ResetTerrain),
+            "terrain_hash" => Ok(Self::TerrainHash),
+            "spawn" => Ok(Self::Spawn(parse_args::<String>(&args, 1, usage)?.remove(0))),
+            "connect" => Ok(Self::Connect(parse_args::<String>(&args, 1, usage)?.remove(0))),
+            "help" => Ok(Self::Help),
+            "clear" => Ok(Self::Clear),
+            _ => unreachable!("every entry in COMMANDS is handled"),
+        }
+    }
+}
+
+/// Drop-down developer console toggled with the backtick/tilde key
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    pub open: bool,
+    input: String,
+    history: Vec<String>,
+    /// Position while browsing history with the arrow keys; `None` is the line being typed
+    history_cursor: Option<usize>,
+    output: VecDeque<String>,
+    /// Lines submitted from the UI, run on the next update
+    pending: Vec<String>,
+    /// Grab keyboard focus the next time the input is drawn
+    focus_input: bool,
+}
+
+impl DevConsole {
+    fn print(&mut self, line: impl Into<String>) {
+        self.output.push_back(line.into());
+        while self.output.len() > MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        self.history_cursor = match (self.history_cursor, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (_, false) => None,
+        };
+        self.input = self.history_cursor.map(|index| self.history[index].clone()).unwrap_or_default();
+    }
+
+    /// Complete the command name, or the aircraft name after `spawn`; lists the options when ambiguous
+    fn complete(&mut self, aircraft_names: &[String]) {
+        let (prefix, partial, options): (&str, &str, Vec<String>) = match self.input.split_once(' ') {
+            None => ("", self.input.as_str(), COMMANDS.iter().map(|(name, _)| name.to_string()).collect()),
+            Some(("spawn", partial)) => ("spawn ", partial.trim_start(), aircraft_names.to_vec()),
+            Some(_) => return,
+        };
+        let partial = partial.to_lowercase();
+        let matches: Vec<String> = options.into_iter().filter(|option| option.starts_with(&partial)).collect();
+
+        match matches.as_slice() {
+            [] => {}
+            [only] => self.input = format!("{}{} ", prefix, only),
+            _ => {
+                // Extend to the longest prefix every match shares
+                let common = matches[1..].iter().fold(matches[0].clone(), |common, option| {
+                    common.chars().zip(option.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+                });
+                self.input = format!("{}{}", prefix, common);
+                let listing = matches.join("  ");
+                self.print(listing);
+            }
+        }
+    }
+}
+
+/// Toggle the console and keep flight controls from seeing keys typed into it
+pub fn toggle_console(mut keyboard: ResMut<ButtonInput<KeyCode>>, mut console: ResMut<DevConsole>) {
+    if keyboard.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+        console.focus_input = console.open;
+    }
+    if console.open {
+        keyboard.reset_all();
+    }
+}
+
+pub fn console_ui(
+    mut contexts: EguiContexts,
+    mut console: ResMut<DevConsole>,
+    definitions: Res<Assets<AircraftDefinition>>,
+) -> Result<(), BevyError> {
+    if !console.open {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+
+    egui::TopBottomPanel::top("dev_console")
+        .exact_height(CONSOLE_HEIGHT)
+        .show(ctx, |ui| {
+            let input_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.0;
+            egui::ScrollArea::vertical()
+                .max_height(CONSOLE_HEIGHT - input_height)
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &console.output {
+                        ui.label(egui::RichText::new(line).monospace());
+                    }
+                });
+
+            // Tab and the arrows would otherwise move focus or the text cursor
+            let (tab, up, down) = ui.input_mut(|input| {
+                (
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                )
+            });
+            let edited = tab || up || down;
+            if tab {
+                let mut names: Vec<String> = definitions.iter().map(|(_, definition)| definition.name.to_lowercase()).collect();
+                names.sort();
+                console.complete(&names);
+            } else if up || down {
+                console.browse_history(up);
+            }
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut console.input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true)
+                    .hint_text("help for a list of commands"),
+            );
+            // The toggle key also arrives as typed text
+            console.input.retain(|c| c != '`' && c != '~');
+
+            if console.focus_input {
+                response.request_focus();
+                console.focus_input = false;
+            }
+            if edited && let Some(mut state) = egui::text_edit::TextEditState::load(ui.ctx(), response.id) {
+                let end = egui::text::CCursor::new(console.input.chars().count());
+                state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+                state.store(ui.ctx(), response.id);
+            }
+
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                let line = std::mem::take(&mut console.input).trim().to_string();
+                if !line.is_empty() {
+                    if console.history.last() != Some(&line) {
+                        console.history.push(line.clone());
+                    }
+                    console.pending.push(line);
+                }
+                console.history_cursor = None;
+                response.request_focus();
+            }
+        });
+
+    Ok(())
+}
+
+pub fn run_console_commands(
+    mut console: ResMut<DevConsole>,
+    mut aircraft_query: Query<(&mut Transform, &mut Aircraft)>,
+    mut day_cycle: ResMut<DayNightCycle>,
+    mut wind: ResMut<Wind>,
+    mut world_generator: ResMut<WorldGenerator>,
+    (mut selection, definitions): (ResMut<AircraftSelection>, Res<Assets<AircraftDefinition>>),
+    mut menu: ResMut<MultiplayerMenu>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) {
+    if console.pending.is_empty() {
+        return;
+    }
+    let connected = client.is_some_and(|client| client.connected);
+
+    for line in std::mem::take(&mut console.pending) {
+        console.print(format!("> {}", line));
+        let command = match ConsoleCommand::parse(&line) {
+            Ok(command) => command,
+            Err(e) => {
+                console.print(e);
+                continue;
+            }
+        };
+
+        let result: Result<String, String> = match command {
+            ConsoleCommand::Teleport(_) if connected => Err("the server would flag the jump while connected".to_string()),
+            ConsoleCommand::Teleport(position) => match aircraft_query.single() {
+                Ok((transform, _)) => {
+                    commands.trigger(Teleport { position, rotation: transform.rotation });
+                    Ok(format!("Teleporting to {:.0} {:.0} {:.0}", position.x, position.y, position.z))
+                }
+                Err(_) => Err("no aircraft to teleport".to_string()),
+            },
+            ConsoleCommand::Time(time_of_day) => {
+                day_cycle.time_of_day = time_of_day.rem_euclid(1.0);
+                Ok(format!("Time of day set to {:.3}", day_cycle.time_of_day))
+            }
+            ConsoleCommand::Wind { speed, heading } => {
+                // Inverse of the HUD's wind heading; freezing the evolution keeps the noise from moving it again
+                let angle = (heading - 90.0).to_radians();
+                wind.wind_direction = Vec3::new(angle.sin(), 0.0, -angle.cos());
+                wind.min_wind_speed = speed.max(0.0);
+                wind.max_wind_speed = speed.max(0.0);
+                wind.wind_evolution_speed = 0.0;
+                Ok(format!("Wind held at {:.1} from heading {:03.0}°", speed, heading))
+            }
+            ConsoleCommand::Seed(_) if connected => Err("the server sets the seed while connected".to_string()),
+            ConsoleCommand::Seed(seed) => {
+                *world_generator = WorldGenerator::new(seed);
+                commands.trigger(ResetChunks);
+                commands.trigger(network::RespawnAircraft);
+                Ok(format!("Regenerating world with seed {}", seed))
+            }
+            ConsoleCommand::ResetTerrain => {
+                let edited: Vec<(i32, i32)> = world_generator.overrides.edited_chunks().copied().collect();
+                if edited.is_empty() {
+                    Err("no terrain edits on this seed".to_string())
+                } else {
+                    // Flattened chunks drop out of the store on the next save
+                    let overrides = Arc::make_mut(&mut world_generator.overrides);
+                    for chunk in &edited {
+                        overrides.modify(*chunk, |_, height| *height = 0.0);
+                    }
+                    commands.trigger(ResetChunks);
+                    Ok(format!("Reset terrain edits on {} chunks", edited.len()))
+                }
+            }
+            ConsoleCommand::TerrainHash => match aircraft_query.single() {
+                Ok((transform, _)) => {
+                    let chunk = ((transform.translation.x / CHUNK_SIZE).round() as i32, (transform.translation.z / CHUNK_SIZE).round() as i32);
+                    let heightfield = generate_chunk_heightfield(world_generator.seed, chunk, TERRAIN_HASH_LOD);
+                    Ok(format!("Seed {} chunk {} {}: {:016x}", world_generator.seed, chunk.0, chunk.1, heightfield.checksum()))
+                }
+                Err(_) => Err("no aircraft to check under".to_string()),
+            },
+            ConsoleCommand::Spawn(name) => {
+                match definitions.iter().find(|(_, definition)| definition.name.eq_ignore_ascii_case(&name)) {
+                    Some((id, preset)) => {
+                        let aircraft = aircraft_query.single_mut().ok().map(|(_, aircraft)| aircraft);
+                        fly_preset(&mut selection, id, preset, aircraft, &mut commands);
+                        Ok(format!("Flying the {}", preset.name))
+                    }
+                    None => Err(format!("no aircraft named '{}'", name)),
+                }
+            }
+            ConsoleCommand::Connect(_) if connected => Err("already connected, disconnect first".to_string()),
+            ConsoleCommand::Connect(_) if menu.connecting => Err("a connection attempt is already running".to_string()),
+            ConsoleCommand::Connect(address) => {
+                menu.server_address = address.clone();
+                commands.trigger(menu.connect_request());
+                Ok(format!("Connecting to {}...", address))
+            }
+            ConsoleCommand::Help => {
+                for (_, usage) in COMMANDS {
+                    console.print(format!("  {}", usage));
+                }
+                Ok(String::new())
+            }
+            ConsoleCommand::Clear => {
+                console.output.clear();
+                Ok(String::new())
+            }
+        };
+
+        match result {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => {
+                info!("Console: {}", message);
+                console.print(message);
+            }
+            Err(e) => console.print(format!("error: {}", e)),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::prelude::Color;
+use bevy::prelude::{Color, Resource, Vec3};
 
 pub const OCEAN_HUMIDITY_THRESHOLD: f32 = 0.60;
 pub const OCEAN_HUMIDITY_OFFSET: f32 = 0.1;
@@ -9,13 +9,169 @@ pub const OCEAN_TRANSITION_WIDTH: f32 = 0.3;
 pub const CHUNK_SIZE: f32 = 1000.0; 
 pub const MAP_HEIGHT_SCALE: f32 = 500.0;
 
+// Time format constants
+pub const HOURS_PER_DAY: f32 = 24.0;
+pub const TIME_OFFSET_HOURS: f32 = 4.0;
+
 pub const MAX_ILLUMANENCE: f32 = 5_300.0;
 pub const TERRAIN_HORIZONTAL_SCALE: f32 = 1.0;
 
+/// 9.81 m/s² in world units
+pub const GRAVITY: f32 = 51.2;
+
 pub fn world_units_to_meters(world_units: f32) -> f32 {
     world_units *  0.19167
 }
 
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// Where the world origin sits on the globe, for tools that want latitude and longitude.
+/// As on the HUD compass, north is along -X and east along -Z
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GeoOrigin {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Default for GeoOrigin {
+    fn default() -> Self {
+        Self { latitude: 46.0, longitude: 8.0 }
+    }
+}
+
+impl GeoOrigin {
+    /// Degrees of latitude and longitude of a world position, on a flat projection around the origin
+    pub fn to_lat_lon(self, position: Vec3) -> (f64, f64) {
+        let north = -world_units_to_meters(position.x) as f64;
+        let east = -world_units_to_meters(position.z) as f64;
+        let latitude = self.latitude + (north / EARTH_RADIUS_METERS).to_degrees();
+        let longitude = self.longitude + (east / (EARTH_RADIUS_METERS * self.latitude.to_radians().cos())).to_degrees();
+        (latitude, longitude)
+    }
+}
+
+pub const METERS_TO_FEET: f32 = 3.28084;
+const METERS_PER_MILE: f32 = 1609.344;
+const METERS_PER_NAUTICAL_MILE: f32 = 1852.0;
+const KILOGRAMS_TO_POUNDS: f32 = 2.20462;
+const MPS_TO_KMH: f32 = 3.6;
+pub const MPS_TO_MPH: f32 = 2.23694;
+pub const MPS_TO_KNOTS: f32 = 1.94384;
+
+/// How the HUD shows speeds, altitudes and distances; everything goes in as meters and m/s
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+    /// Knots, feet and nautical miles
+    Aviation,
+}
+
+impl UnitSystem {
+    pub const ALL: [UnitSystem; 3] = [UnitSystem::Metric, UnitSystem::Imperial, UnitSystem::Aviation];
+
+    pub fn speed(self, meters_per_second: f32) -> f32 {
+        match self {
+            UnitSystem::Metric => meters_per_second * MPS_TO_KMH,
+            UnitSystem::Imperial => meters_per_second * MPS_TO_MPH,
+            UnitSystem::Aviation => meters_per_second * MPS_TO_KNOTS,
+        }
+    }
+
+    pub fn speed_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "km/h",
+            UnitSystem::Imperial => "mph",
+            UnitSystem::Aviation => "kt",
+        }
+    }
+
+    pub fn altitude(self, meters: f32) -> f32 {
+        match self {
+            UnitSystem::Metric => meters,
+            UnitSystem::Imperial | UnitSystem::Aviation => meters * METERS_TO_FEET,
+        }
+    }
+
+    pub fn altitude_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "m",
+            UnitSystem::Imperial | UnitSystem::Aviation => "ft",
+        }
+    }
+
+    /// Vertical speed in m/s, or ft/min where altitude is in feet
+    pub fn climb_rate(self, meters_per_second: f32) -> f32 {
+        match self {
+            UnitSystem::Metric => meters_per_second,
+            UnitSystem::Imperial | UnitSystem::Aviation => meters_per_second * METERS_TO_FEET * 60.0,
+        }
+    }
+
+    pub fn climb_rate_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "m/s",
+            UnitSystem::Imperial | UnitSystem::Aviation => "ft/min",
+        }
+    }
+
+    pub fn format_speed(self, meters_per_second: f32) -> String {
+        format!("{:.0} {}", self.speed(meters_per_second), self.speed_unit())
+    }
+
+    pub fn format_altitude(self, meters: f32) -> String {
+        format!("{:.0} {}", self.altitude(meters), self.altitude_unit())
+    }
+
+    /// Tenths of a metre per second, but whole feet per minute
+    pub fn format_climb_rate(self, meters_per_second: f32) -> String {
+        let decimals = match self {
+            UnitSystem::Metric => 1,
+            UnitSystem::Imperial | UnitSystem::Aviation => 0,
+        };
+        format!("{:+.*} {}", decimals, self.climb_rate(meters_per_second), self.climb_rate_unit())
+    }
+
+    /// Short distances in the small unit, longer ones in the large one
+    pub fn format_distance(self, meters: f32) -> String {
+        match self {
+            UnitSystem::Metric if meters < 1000.0 => format!("{:.0} m", meters),
+            UnitSystem::Metric => format!("{:.1} km", meters / 1000.0),
+            UnitSystem::Imperial if meters < METERS_PER_MILE * 0.2 => format!("{:.0} ft", meters * METERS_TO_FEET),
+            UnitSystem::Imperial => format!("{:.1} mi", meters / METERS_PER_MILE),
+            UnitSystem::Aviation if meters < METERS_PER_NAUTICAL_MILE * 0.2 => format!("{:.0} ft", meters * METERS_TO_FEET),
+            UnitSystem::Aviation => format!("{:.1} NM", meters / METERS_PER_NAUTICAL_MILE),
+        }
+    }
+
+    pub fn weight(self, kilograms: f32) -> f32 {
+        match self {
+            UnitSystem::Metric => kilograms,
+            UnitSystem::Imperial | UnitSystem::Aviation => kilograms * KILOGRAMS_TO_POUNDS,
+        }
+    }
+
+    pub fn weight_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "kg",
+            UnitSystem::Imperial | UnitSystem::Aviation => "lb",
+        }
+    }
+
+    pub fn format_weight(self, kilograms: f32) -> String {
+        format!("{:.0} {}", self.weight(kilograms), self.weight_unit())
+    }
+
+    pub fn format_temperature(self, celsius: f32, fahrenheit: f32) -> String {
+        match self {
+            UnitSystem::Metric | UnitSystem::Aviation => format!("{:.1}°C", celsius),
+            UnitSystem::Imperial => format!("{:.1}°F", fahrenheit),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TerrainStop {
     pub height: f32,
     pub color: Color,
@@ -44,6 +200,16 @@ pub const TAIGA_TERRAIN_LEVELS: &[TerrainStop] = &[
     TerrainStop { height: 1.0,  color: Color::WHITE },               // Heavy Snow
 ];
 
+/// Vegetation tint at the peak of autumn
+pub const AUTUMN_TERRAIN_COLOR: Color = Color::srgb(0.6, 0.35, 0.1);
+/// Normalized height above which seasonal snow can settle in midsummer
+pub const SUMMER_SNOW_LINE: f32 = 2.5;
+/// How far the snow line drops in midwinter on the coldest terrain
+pub const WINTER_SNOW_LINE_DROP: f32 = 1.6;
+/// Bare rock showing on cliff faces, between its dark and light strata
+pub const CLIFF_ROCK_DARK: Color = Color::srgb(0.28, 0.26, 0.25);
+pub const CLIFF_ROCK_LIGHT: Color = Color::srgb(0.52, 0.5, 0.47);
+
 pub const FOREST_TERRAIN_LEVELS: &[TerrainStop] = &[
     TerrainStop { height: -1.0, color: Color::srgb(0.3, 0.2, 0.1) }, // Dirt
     TerrainStop { height: -0.5,  color: Color::srgb(0.2, 0.4, 0.1) }, // Deep Grass
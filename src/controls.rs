@@ -4,7 +4,18 @@ use bevy::{
 };
 use noise::{NoiseFn, Perlin};
 
-use crate::world_generation::WorldGenerator;
+use crate::world_generation::{Biome, WorldGenerator};
+use crate::effects::{EffectKind, SpawnEffect, DUST_MAX_HEIGHT};
+use crate::day_cycle::DayNightCycle;
+use crate::lift::{calculate_vertical_air, turbulence_multiplier};
+use crate::network::PlaneType;
+use crate::accessibility::Accessibility;
+use crate::external_control::ExternalControl;
+use crate::home_base::HomeBase;
+use crate::wind_shear::{microburst_air, Microburst};
+
+/// The stock light aircraft, which anything missing its model falls back to
+pub const DEFAULT_AIRCRAFT_MODEL: &str = "low-poly_airplane/scene.gltf#Scene0";
 
 // Constants for physics calculations
 const BASE_THRUST_MULTIPLIER: f32 = 50.0;
@@ -14,6 +25,45 @@ const LIFT_EFFICIENCY_MIN: f32 = 0.3;
 const LIFT_REDUCTION_CLIMBING: f32 = 0.5;
 const LIFT_REDUCTION_DIVING: f32 = 0.2;
 const STALL_THRESHOLD_RATIO: f32 = 0.33;
+/// Lift-off and best-rate climb speeds as multiples of the stall speed, for the performance figures
+const LIFTOFF_STALL_MARGIN: f32 = 1.2;
+const BEST_CLIMB_STALL_MARGIN: f32 = 1.4;
+
+// Realistic stalls
+/// Angle of attack, in degrees, past which the wing stalls
+const CRITICAL_AOA: f32 = 16.0;
+const MAX_AOA: f32 = 45.0;
+/// Degrees past the critical angle for the stall to be fully developed
+const STALL_DEPTH_RANGE: f32 = 8.0;
+/// Nose-down pitch acceleration of a fully developed stall
+const STALL_PITCH_BREAK: f32 = 2.5;
+/// Roll rate the dropping wing starts with
+const SPIN_ENTRY_RATE: f32 = 0.1;
+/// How quickly autorotation builds while the wing stays stalled
+const SPIN_BUILD_RATE: f32 = 0.8;
+const SPIN_MAX_RATE: f32 = 2.5;
+/// Past this rate the spin sustains itself until the pilot recovers it
+const SPIN_DEVELOPED_RATE: f32 = 1.0;
+/// How quickly an incipient spin dies out on its own once the wing is flying again
+const SPIN_DECAY_RATE: f32 = 0.4;
+/// How quickly forward stick and opposite rudder stop the rotation
+const SPIN_RECOVERY_RATE: f32 = 1.2;
+const SPIN_ROLL_TORQUE: f32 = 2.0;
+const SPIN_YAW_TORQUE: f32 = 1.5;
+
+// Weight and balance
+/// Pitch acceleration from the CG sitting at an envelope limit: nose down forward, nose up aft
+const CG_PITCH_MOMENT: f32 = 0.3;
+/// How much an aft CG sharpens the elevator and weakens pitch stability, and a forward one the reverse
+const CG_STABILITY_EFFECT: f32 = 0.5;
+
+// Icing
+/// Share of the wing's lift lost under a full load of ice
+const ICE_LIFT_LOSS: f32 = 0.3;
+/// Extra parasitic drag from a full load of ice, as a fraction of the clean airframe's
+const ICE_DRAG_GAIN: f32 = 1.0;
+/// Extra weight from a full load of ice, as a fraction of the standard load
+const ICE_WEIGHT_GAIN: f32 = 0.15;
 const ROTATIONAL_DAMPING: f32 = 2.0;
 const LIFT_THRESHOLD_SPEED: f32 = 150.0;
 const GRAVITY_STRENGTH: f32 = 30.0;
@@ -26,6 +76,24 @@ const TURBULENCE_VELOCITY_MULTIPLIER: f32 = 85.0;
 const TURBULENCE_COUPLING_STRENGTH: f32 = 0.7;
 const AUTO_LEVEL_PITCH_DIVISOR: f32 = 1.25;
 
+// Terrain contact
+/// Steepest approach to the ground, as the sine of the angle, that skids instead of crashing
+const SCRAPE_MAX_APPROACH_SINE: f32 = 0.25;
+/// Fraction of the into-ground speed given back as a bounce
+const SCRAPE_RESTITUTION: f32 = 0.2;
+/// Fraction of speed lost to friction on each scrape
+const SCRAPE_FRICTION: f32 = 0.15;
+/// Lift the aircraft this far clear of the surface after a scrape
+const SCRAPE_CLEARANCE: f32 = 2.0;
+/// Damage per world unit per second of into-ground speed
+const SCRAPE_DAMAGE_PER_SPEED: f32 = 0.4;
+/// Slowest fraction of top speed a water landing can be made at and still float
+const DITCH_MAX_SPEED_RATIO: f32 = 0.45;
+/// Steepest descent onto water, as the sine of the angle, that can still float
+const DITCH_MAX_DESCENT_SINE: f32 = 0.2;
+/// Wings must be within this much bank, as the sine of the angle, to ditch
+const DITCH_MAX_BANK_SINE: f32 = 0.5;
+
 // Camera control constants
 const FREE_FLIGHT_ROTATION_SPEED: f32 = 0.8;
 const FREE_FLIGHT_PAN_SPEED_NORMAL: f32 = 800.0;
@@ -41,6 +109,9 @@ const CAMERA_SPEED_THRESHOLD: f32 = 200.0;
 const CAMERA_SMOOTHNESS_BASE: f32 = 2.0;
 const CAMERA_SMOOTHNESS_MULTIPLIER: f32 = 1.5;
 const CAMERA_LOOK_AHEAD_MULTIPLIER: f32 = 0.2;
+/// With reduced motion the chase camera turns this much slower and only follows this much of the bank
+const REDUCED_MOTION_DAMPING: f32 = 0.35;
+const REDUCED_MOTION_BANK: f32 = 0.4;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FlightMode {
@@ -64,6 +135,72 @@ impl Default for ControlMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyPreset {
+    Arcade,
+    Normal,
+    Realistic,
+    /// Any mix that doesn't match a preset
+    Custom,
+}
+
+impl DifficultyPreset {
+    pub const PRESETS: [DifficultyPreset; 3] = [DifficultyPreset::Arcade, DifficultyPreset::Normal, DifficultyPreset::Realistic];
+}
+
+/// Assists and realism settings that the flight model, engine and damage systems consult
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub preset: DifficultyPreset,
+    /// Scales each aircraft's own auto-level strength
+    pub auto_level_scale: f32,
+    /// Angle-of-attack stalls with wing drop and spins, instead of a gentle nose drop at low speed
+    pub realistic_stalls: bool,
+    /// Scales damage from hits and terrain scrapes
+    pub damage_scale: f32,
+    /// Scales how quickly the engine burns fuel; zero never runs the tanks dry
+    pub fuel_burn_scale: f32,
+    /// Scales how hard wind and turbulence push the aircraft around
+    pub wind_scale: f32,
+}
+
+impl Difficulty {
+    pub fn preset(preset: DifficultyPreset) -> Self {
+        match preset {
+            DifficultyPreset::Arcade => Self {
+                preset,
+                auto_level_scale: 1.5,
+                realistic_stalls: false,
+                damage_scale: 0.5,
+                fuel_burn_scale: 0.0,
+                wind_scale: 0.5,
+            },
+            DifficultyPreset::Normal | DifficultyPreset::Custom => Self {
+                preset,
+                auto_level_scale: 1.0,
+                realistic_stalls: false,
+                damage_scale: 1.0,
+                fuel_burn_scale: 0.5,
+                wind_scale: 1.0,
+            },
+            DifficultyPreset::Realistic => Self {
+                preset,
+                auto_level_scale: 0.25,
+                realistic_stalls: true,
+                damage_scale: 2.0,
+                fuel_burn_scale: 1.0,
+                wind_scale: 1.5,
+            },
+        }
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::preset(DifficultyPreset::Normal)
+    }
+}
+
 #[derive(Component)]
 pub struct MainCamera {
     pub orbit_yaw: f32,
@@ -90,10 +227,22 @@ pub struct Aircraft {
     pub velocity: Vec3,
     pub speed: f32,
     pub throttle: f32,
+    /// Throttle the engine is actually delivering, lagging `throttle` as it spools
+    pub power: f32,
     pub pitch_velocity: f32,
     pub roll_velocity: f32,
     pub yaw_velocity: f32,
     pub crashed: bool,
+    /// Degrees, estimated from the load the wing is carrying at the current airspeed
+    pub angle_of_attack: f32,
+    /// Autorotation of a stall departure, positive to the right; only used with realistic stalls
+    pub spin_rate: f32,
+    /// Gross weight over the preset's standard load; stall speed grows with its square root
+    pub weight_ratio: f32,
+    /// Centre of gravity relative to the standard load, in half-widths of the loading envelope; positive is aft
+    pub cg_position: f32,
+    /// Ice on the airframe, 0 clean to 1 fully loaded; adds weight and drag and robs the wing of lift
+    pub ice: f32,
 
     // Physics tuning parameters
     pub max_speed: f32,
@@ -127,19 +276,27 @@ pub struct Aircraft {
     // Model settings
     pub model_path: String,
     pub model_scale: f32,
+    pub plane_type: PlaneType,
 }
 
 
 impl Aircraft {
+    /// Built-in stand-in for the startup aircraft until `assets/aircraft/light.aircraft.ron` has loaded
     pub fn light() -> Self {
         Self {
             velocity: Vec3::ZERO,
             speed: 250.0,
             throttle: 0.80,
+            power: 0.80,
             pitch_velocity: 0.0,
             roll_velocity: 0.0,
             yaw_velocity: 0.0,
             crashed: false,
+            angle_of_attack: 0.0,
+            spin_rate: 0.0,
+            weight_ratio: 1.0,
+            cg_position: 0.0,
+            ice: 0.0,
             max_speed: 600.0,
             max_throttle: 2.0,
             thrust: 1.5,
@@ -157,39 +314,9 @@ impl Aircraft {
             respawn_speed: 400.0,
             camera_height: 24.0,
             camera_distance: 30.0,
-            model_path: "low-poly_airplane/scene.gltf#Scene0".to_string(),
+            model_path: DEFAULT_AIRCRAFT_MODEL.to_string(),
             model_scale: 0.4,
-        }
-    }
-
-    pub fn jet() -> Self {
-        Self {
-            velocity: Vec3::ZERO,
-            speed: 2000.0,
-            throttle: 0.80,
-            pitch_velocity: 0.0,
-            roll_velocity: 0.0,
-            yaw_velocity: 0.0,
-            crashed: false,
-            max_speed: 3500.0,
-            max_throttle: 3.5,
-            thrust: 2.5,
-            gravity: 80.0,       
-            g_force_drag: 2.5,
-            lift_coefficient: 2.5,
-            lift_reduction_factor: 30.0,
-            parasitic_drag_coef: 100.0,
-            pitch_strength: 3.0,
-            roll_strength: 12.5,
-            yaw_strength: 0.35,
-            bank_turn_strength: 0.1,
-            auto_level_strength: 0.1,
-            respawn_height: 1000.0,
-            respawn_speed: 2000.0,
-            camera_height: 24.0,
-            camera_distance: 120.0,
-            model_path: "f16_low_poly/scene.gltf#Scene0".to_string(),
-            model_scale: 30.0,
+            plane_type: PlaneType::Light,
         }
     }
 }
@@ -213,6 +340,13 @@ pub struct Wind {
     pub turbulence_intensity: f32,
     pub turbulence_frequency: f32,
     pub gust_frequency_multiplier: f64,
+
+    // Vertical air (thermals and ridge lift)
+    pub thermal_strength: f32,
+    pub ridge_lift_strength: f32,
+
+    /// Active wind shear events, started locally or by the server
+    pub microbursts: Vec<Microburst>,
     
     pub perlin: Perlin,
 }
@@ -231,6 +365,9 @@ impl Default for Wind {
             turbulence_intensity: 0.005,
             turbulence_frequency: 6.0,
             gust_frequency_multiplier: 0.00075,
+            thermal_strength: 30.0,
+            ridge_lift_strength: 4.0,
+            microbursts: Vec::new(),
             perlin: Perlin::new(42),
         }
     }
@@ -283,6 +420,7 @@ fn handle_input_toggles(
                     aircraft.pitch_velocity = 0.0;
                     aircraft.roll_velocity = 0.0;
                     aircraft.yaw_velocity = 0.0;
+                    aircraft.spin_rate = 0.0;
                     control_mode.physics_paused = false;
                     
                     transform.translation.y = (terrain_height + aircraft.respawn_height).max(aircraft.respawn_height);
@@ -296,17 +434,32 @@ fn handle_input_toggles(
 }
 
 /// Update throttle based on keyboard input
-fn update_throttle(keyboard: &ButtonInput<KeyCode>, aircraft: &mut Aircraft, dt: f32) {
-    if keyboard.pressed(KeyCode::Equal) {
+pub fn update_throttle(keyboard: &ButtonInput<KeyCode>, keys: &PilotKeys, aircraft: &mut Aircraft, dt: f32) {
+    if keyboard.pressed(keys.throttle_up) {
         aircraft.throttle = (aircraft.throttle + THROTTLE_CHANGE_RATE * dt).min(aircraft.max_throttle);
     }
-    if keyboard.pressed(KeyCode::Minus) {
+    if keyboard.pressed(keys.throttle_down) {
         aircraft.throttle = (aircraft.throttle - THROTTLE_CHANGE_RATE * dt).max(0.0);
     }
 }
 
+/// The local aircraft set down on the water gently enough to float
+#[derive(Event)]
+pub struct Ditched {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+/// The local aircraft skidded off the terrain instead of crashing
+#[derive(Event)]
+pub struct TerrainScrape {
+    pub position: Vec3,
+    pub damage: f32,
+}
+
 struct PhysicsForces {
     engine_acceleration: f32,
+    lift_force: f32,
     gravity_acceleration: f32,
     turn_drag: f32,
     parasitic_drag: f32,
@@ -322,14 +475,14 @@ fn calculate_engine_and_drag(
 ) -> PhysicsForces {
     // Engine thrust with falloff at high speeds
     let base_thrust = BASE_THRUST_MULTIPLIER * aircraft.thrust; 
-    let max_effective_ratio = aircraft.throttle + THRUST_HEADROOM; 
+    let max_effective_ratio = aircraft.power + THRUST_HEADROOM; 
     let high_speed_falloff = (max_effective_ratio - airspeed_ratio).clamp(0.0, 1.0); 
-    let engine_acceleration = aircraft.throttle * base_thrust * high_speed_falloff;
+    let engine_acceleration = aircraft.power * base_thrust * high_speed_falloff;
     
     // Lift and gravity interaction
     let gravity_acceleration_base = -climb_angle * aircraft.gravity;
     let lift_efficiency = (1.0 - climb_angle.abs()).max(LIFT_EFFICIENCY_MIN);
-    let lift_force = aircraft.lift_coefficient * dynamic_pressure * lift_efficiency * aircraft.lift_reduction_factor;
+    let lift_force = aircraft.lift_coefficient * dynamic_pressure * lift_efficiency * aircraft.lift_reduction_factor * ice_lift_factor(aircraft);
     
     let gravity_acceleration = if climb_angle > 0.0 {
         let lift_reduction = lift_force * LIFT_REDUCTION_CLIMBING;
@@ -348,10 +501,11 @@ fn calculate_engine_and_drag(
 
     // Parasitic drag
     let high_speed_multiplier = 1.0 + (airspeed_ratio.max(1.0) - 1.0) * 0.5;
-    let parasitic_drag = dynamic_pressure * aircraft.parasitic_drag_coef * high_speed_multiplier;
+    let parasitic_drag = dynamic_pressure * aircraft.parasitic_drag_coef * high_speed_multiplier * (1.0 + ICE_DRAG_GAIN * aircraft.ice);
 
     PhysicsForces {
         engine_acceleration,
+        lift_force,
         gravity_acceleration,
         turn_drag,
         parasitic_drag,
@@ -359,6 +513,18 @@ fn calculate_engine_and_drag(
     }
 }
 
+/// World-space breakdown of the local aircraft's last physics step, drawn by the debug overlays
+#[derive(Resource, Default, Clone, Copy)]
+pub struct FlightForces {
+    pub thrust: Vec3,
+    pub lift: Vec3,
+    /// Gravity along the flight path after lift has offset it
+    pub gravity: Vec3,
+    pub drag: Vec3,
+    /// Wind velocity at the aircraft, gusts included
+    pub wind: Vec3,
+}
+
 struct WindEffects {
     current_wind: Vec3,
     wind_acceleration: f32,
@@ -367,15 +533,8 @@ struct WindEffects {
     macro_wind_yaw: f32,
 }
 
-/// Calculate wind effects on the aircraft
-fn calculate_wind_effects(
-    wind: &Wind,
-    pos: Vec3,
-    time: f64,
-    forward: Vec3,
-    right: Vec3,
-    up: Vec3,
-) -> WindEffects {
+/// Macro wind direction and speed at a point, from the drifting weather noise field
+pub fn sample_macro_wind(wind: &Wind, pos: Vec3, time: f64) -> (Vec3, f32) {
     let base_wind_velocity = wind.wind_direction * wind.wind_speed;
     let wind_drift = base_wind_velocity * time as f32;
     
@@ -400,13 +559,24 @@ fn calculate_wind_effects(
     let angle_shift = wind_dir_noise * wind.max_angle_shift;
     
     let wind_rotation = Quat::from_rotation_y(angle_shift);
-    let current_wind_dir = wind_rotation * wind.wind_direction;
-    let current_speed = wind.wind_speed * wind_multiplier;
-    let current_wind = current_wind_dir * current_speed;
+    (wind_rotation * wind.wind_direction, wind.wind_speed * wind_multiplier)
+}
+
+/// Calculate wind effects on the aircraft
+fn calculate_wind_effects(
+    wind: &Wind,
+    pos: Vec3,
+    time: f64,
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+) -> WindEffects {
+    let (current_wind_dir, current_speed) = sample_macro_wind(wind, pos, time);
+    // Microburst outflow rides on the macro wind; its downdraft is handled with the other vertical air
+    let current_wind = current_wind_dir * current_speed + microburst_air(wind, pos, time).with_y(0.0);
 
     // Wind acceleration on forward movement
-    let wind_dot = forward.dot(current_wind_dir);
-    let wind_acceleration = wind_dot * current_speed * WIND_FORWARD_COUPLING;
+    let wind_acceleration = forward.dot(current_wind) * WIND_FORWARD_COUPLING;
     
     // Rotational effects from crosswinds and updrafts
     let wind_lateral = current_wind.dot(right);
@@ -428,6 +598,39 @@ fn calculate_wind_effects(
     }
 }
 
+/// What the flight model would feel flying through a point, for instruments that look ahead of the aircraft
+#[derive(Debug, Clone, Copy)]
+pub struct WindProbe {
+    /// Macro wind velocity
+    pub wind: Vec3,
+    /// Pitch and roll disturbance from the macro wind and turbulence together, in radians per second squared
+    pub upset: f32,
+}
+
+/// Sample the wind and turbulence fields at `pos` as an aircraft on `forward` at `airspeed_ratio` would meet them
+pub fn probe_wind(
+    wind: &Wind,
+    world_gen: &WorldGenerator,
+    day_cycle: &DayNightCycle,
+    pos: Vec3,
+    time: f64,
+    forward: Vec3,
+    airspeed_ratio: f32,
+) -> WindProbe {
+    let right = forward.cross(Vec3::Y).normalize_or(Vec3::X);
+    let up = right.cross(forward);
+    let effects = calculate_wind_effects(wind, pos, time, forward, right, up);
+    let wind_drift = wind.wind_direction * wind.wind_speed * time as f32;
+    let local_intensity = turbulence_multiplier(world_gen, day_cycle, pos);
+    let turbulence = calculate_turbulence(wind, pos, wind_drift, time, airspeed_ratio, local_intensity);
+    let pitch = effects.macro_wind_pitch + turbulence.turbulence_pitch * turbulence.turbulence_scale;
+    let roll = effects.macro_wind_roll + turbulence.turbulence_roll * turbulence.turbulence_scale;
+    WindProbe {
+        wind: effects.current_wind,
+        upset: Vec2::new(pitch, roll).length(),
+    }
+}
+
 struct TurbulenceEffects {
     turbulence_force: Vec3,
     turbulence_velocity_scale: f32,
@@ -437,14 +640,16 @@ struct TurbulenceEffects {
     turbulence_scale: f32,
 }
 
-/// Calculate turbulence effects
+/// Calculate turbulence effects; `local_intensity` scales the base setting for the terrain and weather underneath
 fn calculate_turbulence(
     wind: &Wind,
     pos: Vec3,
     wind_drift: Vec3,
     time: f64,
     airspeed_ratio: f32,
+    local_intensity: f32,
 ) -> TurbulenceEffects {
+    let intensity = wind.turbulence_intensity * local_intensity;
     let freq = wind.turbulence_frequency as f64;
     let turb_sample_x = pos.x as f64 - wind_drift.x as f64;
     let turb_sample_z = pos.z as f64 - wind_drift.z as f64;
@@ -467,7 +672,7 @@ fn calculate_turbulence(
     ]) as f32;
     
     let turbulence_force = Vec3::new(turbulence_velocity_x, turbulence_velocity_y, turbulence_velocity_z);
-    let turbulence_velocity_scale = wind.turbulence_intensity * TURBULENCE_VELOCITY_MULTIPLIER 
+    let turbulence_velocity_scale = intensity * TURBULENCE_VELOCITY_MULTIPLIER 
         * (airspeed_ratio + 0.5).powf(TURBULENCE_POWER);
     
     // Rotational coupling
@@ -475,7 +680,7 @@ fn calculate_turbulence(
     let turbulence_roll = -turbulence_velocity_x * TURBULENCE_COUPLING_STRENGTH * 2.0;
     let turbulence_yaw = turbulence_velocity_x * TURBULENCE_COUPLING_STRENGTH * 0.75;
     
-    let turbulence_scale = wind.turbulence_intensity * (airspeed_ratio + 1.0).powf(TURBULENCE_POWER);
+    let turbulence_scale = intensity * (airspeed_ratio + 1.0).powf(TURBULENCE_POWER);
 
     TurbulenceEffects {
         turbulence_force,
@@ -487,46 +692,88 @@ fn calculate_turbulence(
     }
 }
 
-/// Handle player flight control inputs
+/// Stick and rudder deflection from -1.0 to 1.0, from the keyboard or an AI pilot
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ControlInputs {
+    /// Positive pulls the nose up
+    pub pitch: f32,
+    /// Positive banks right
+    pub roll: f32,
+    /// Positive yaws right
+    pub yaw: f32,
+}
+
+impl ControlInputs {
+    pub fn from_keyboard(keyboard: &ButtonInput<KeyCode>, keys: &PilotKeys) -> Self {
+        let axis = |negative: KeyCode, positive: KeyCode| {
+            keyboard.pressed(positive) as i32 as f32 - keyboard.pressed(negative) as i32 as f32
+        };
+        Self {
+            pitch: axis(keys.pitch_down, keys.pitch_up),
+            roll: axis(keys.roll_left, keys.roll_right),
+            yaw: axis(keys.yaw_left, keys.yaw_right),
+        }
+    }
+}
+
+/// One pilot's flight keys, so two pilots can share a keyboard
+#[derive(Debug, Clone, Copy)]
+pub struct PilotKeys {
+    pub pitch_down: KeyCode,
+    pub pitch_up: KeyCode,
+    pub roll_left: KeyCode,
+    pub roll_right: KeyCode,
+    pub yaw_left: KeyCode,
+    pub yaw_right: KeyCode,
+    pub throttle_up: KeyCode,
+    pub throttle_down: KeyCode,
+}
+
+impl PilotKeys {
+    pub const PRIMARY: Self = Self {
+        pitch_down: KeyCode::KeyW,
+        pitch_up: KeyCode::KeyS,
+        roll_left: KeyCode::KeyA,
+        roll_right: KeyCode::KeyD,
+        yaw_left: KeyCode::KeyQ,
+        yaw_right: KeyCode::KeyE,
+        throttle_up: KeyCode::Equal,
+        throttle_down: KeyCode::Minus,
+    };
+
+    /// The numeric keypad, laid out like WASD with 7 and 9 for the rudder
+    pub const SECOND: Self = Self {
+        pitch_down: KeyCode::Numpad8,
+        pitch_up: KeyCode::Numpad5,
+        roll_left: KeyCode::Numpad4,
+        roll_right: KeyCode::Numpad6,
+        yaw_left: KeyCode::Numpad7,
+        yaw_right: KeyCode::Numpad9,
+        throttle_up: KeyCode::NumpadAdd,
+        throttle_down: KeyCode::NumpadSubtract,
+    };
+}
+
+/// Handle flight control inputs
 fn handle_flight_controls(
-    keyboard: &ButtonInput<KeyCode>,
+    inputs: ControlInputs,
     aircraft: &mut Aircraft,
     transform: &Transform,
     pitch_strength: f32,
     roll_strength: f32,
     yaw_strength: f32,
     control_effectiveness: f32,
+    auto_level_scale: f32,
     dt: f32,
 ) {
-    let mut is_rolling = false;
-    let mut is_pitching = false;
-
-    // Pitch controls
-    if keyboard.pressed(KeyCode::KeyW) { 
-        aircraft.pitch_velocity -= pitch_strength * dt;
-        is_pitching = true;
-    }
-    if keyboard.pressed(KeyCode::KeyS) { 
-        aircraft.pitch_velocity += pitch_strength * dt;
-        is_pitching = true;
-    }
-    
-    // Roll controls
-    if keyboard.pressed(KeyCode::KeyA) { 
-        aircraft.roll_velocity += roll_strength * dt; 
-        is_rolling = true;
-    }
-    if keyboard.pressed(KeyCode::KeyD) { 
-        aircraft.roll_velocity -= roll_strength * dt; 
-        is_rolling = true;
-    }
-    
-    // Yaw controls
-    if keyboard.pressed(KeyCode::KeyQ) { aircraft.yaw_velocity += yaw_strength * dt; }
-    if keyboard.pressed(KeyCode::KeyE) { aircraft.yaw_velocity -= yaw_strength * dt; }
+    aircraft.pitch_velocity += inputs.pitch * pitch_strength * dt;
+    aircraft.roll_velocity -= inputs.roll * roll_strength * dt;
+    aircraft.yaw_velocity -= inputs.yaw * yaw_strength * dt;
 
     // Auto-stabilization
-    apply_stability_assists(aircraft, transform, control_effectiveness, is_rolling, is_pitching, dt);
+    let is_rolling = inputs.roll != 0.0;
+    let is_pitching = inputs.pitch != 0.0;
+    apply_stability_assists(aircraft, transform, control_effectiveness, auto_level_scale, is_rolling, is_pitching, dt);
 }
 
 /// Apply stability and flight assists
@@ -534,32 +781,151 @@ fn apply_stability_assists(
     aircraft: &mut Aircraft,
     transform: &Transform,
     control_effectiveness: f32,
+    auto_level_scale: f32,
     is_rolling: bool,
     is_pitching: bool,
     dt: f32,
 ) {
     let bank_angle = transform.right().y;
     let pitch_angle = transform.forward().y;
+    let auto_level_strength = aircraft.auto_level_strength * auto_level_scale;
     
     // Bank-to-turn coordination
     aircraft.yaw_velocity += bank_angle * aircraft.bank_turn_strength * control_effectiveness * dt;
 
     // Auto-level when not actively rolling
     if !is_rolling {
-        let auto_level_force = -bank_angle * aircraft.auto_level_strength * control_effectiveness;
+        let auto_level_force = -bank_angle * auto_level_strength * control_effectiveness;
         aircraft.roll_velocity += auto_level_force * dt;
     }
 
-    // Auto-level when not actively pitching
+    // Auto-level when not actively pitching; a forward CG makes the nose settle more firmly
     if !is_pitching {
-        let auto_level_force = -pitch_angle * aircraft.auto_level_strength * control_effectiveness;
+        let pitch_stability = (1.0 - aircraft.cg_position * CG_STABILITY_EFFECT).max(0.0);
+        let auto_level_force = -pitch_angle * auto_level_strength * pitch_stability * control_effectiveness;
         aircraft.pitch_velocity += auto_level_force / AUTO_LEVEL_PITCH_DIVISOR * dt;
     }
 }
 
+/// Share of the clean wing's lift left under the current ice
+fn ice_lift_factor(aircraft: &Aircraft) -> f32 {
+    1.0 - ICE_LIFT_LOSS * aircraft.ice
+}
+
+/// Level-flight stall speed at the current weight, raised further by ice weighing the aircraft down and spoiling the wing
+fn stall_speed(aircraft: &Aircraft) -> f32 {
+    let weight = aircraft.weight_ratio * (1.0 + ICE_WEIGHT_GAIN * aircraft.ice);
+    aircraft.max_speed * STALL_THRESHOLD_RATIO * (weight / ice_lift_factor(aircraft)).sqrt()
+}
+
+/// Book figures for an aircraft, worked out from the same coefficients the flight model uses
+#[derive(Debug, Clone, Copy)]
+pub struct Performance {
+    /// True airspeed of the level-flight stall
+    pub stall_speed: f32,
+    /// Climb rate from excess power at the best-climb speed and full throttle, in world units per second;
+    /// negative when the aircraft can only sink
+    pub climb_rate: f32,
+    /// Ground roll to lift-off from a level field at full throttle, in world units; `None` without an engine
+    pub takeoff_roll: Option<f32>,
+}
+
+/// Performance in air of `density_ratio` to standard sea level: indicated speeds stay put while true speeds
+/// grow as the air thins, and the engine loses thrust with density
+pub fn performance(aircraft: &Aircraft, density_ratio: f32) -> Performance {
+    let density_ratio = density_ratio.max(0.1);
+    let true_airspeed = 1.0 / density_ratio.sqrt();
+    let full_thrust = |airspeed_ratio: f32| {
+        let power = aircraft.max_throttle;
+        power * BASE_THRUST_MULTIPLIER * aircraft.thrust * (power + THRUST_HEADROOM - airspeed_ratio).clamp(0.0, 1.0) * density_ratio
+    };
+    let stall = stall_speed(aircraft);
+
+    // Accelerating from rest, thrust at half the lift-off speed stands in for the average over the roll
+    let liftoff = stall * LIFTOFF_STALL_MARGIN;
+    let roll_acceleration = full_thrust(liftoff * 0.5 / aircraft.max_speed);
+    let takeoff_roll = (roll_acceleration > 0.0).then(|| (liftoff * true_airspeed).powi(2) / (2.0 * roll_acceleration));
+
+    // Excess thrust over drag and weight, with the relief the model gives a climbing wing; left unclamped so
+    // aircraft that could climb straight up still show the air thinning
+    let climb_speed = stall * BEST_CLIMB_STALL_MARGIN;
+    let airspeed_ratio = climb_speed / aircraft.max_speed;
+    let dynamic_pressure = airspeed_ratio.powi(2);
+    let drag = dynamic_pressure * aircraft.parasitic_drag_coef * (1.0 + ICE_DRAG_GAIN * aircraft.ice);
+    let lift_relief = aircraft.lift_coefficient * dynamic_pressure * aircraft.lift_reduction_factor * ice_lift_factor(aircraft) * LIFT_REDUCTION_CLIMBING;
+    let excess_power = (full_thrust(airspeed_ratio) - drag + lift_relief) / (aircraft.gravity + lift_relief).max(f32::EPSILON);
+
+    Performance {
+        stall_speed: stall * true_airspeed,
+        climb_rate: climb_speed * true_airspeed * excess_power,
+        takeoff_roll,
+    }
+}
+
+/// G felt by the pilot: 1 in level flight, more when pulling and less when pushing
+pub fn load_factor(aircraft: &Aircraft) -> f32 {
+    1.0 + aircraft.speed * aircraft.pitch_velocity / aircraft.gravity.max(1.0)
+}
+
+/// Angle of attack needed to carry the current load: the critical angle at the stall speed in level flight,
+/// higher when pulling and lower when pushing
+fn angle_of_attack(aircraft: &Aircraft) -> f32 {
+    let stall_speed = stall_speed(aircraft);
+    (CRITICAL_AOA * load_factor(aircraft) * (stall_speed / aircraft.speed.max(1.0)).powi(2)).clamp(-MAX_AOA, MAX_AOA)
+}
+
+/// Stall past the critical angle of attack: the nose drops and a wing goes, and holding the stall autorotates
+/// into a spin that takes forward stick and opposite rudder to stop. Returns the fraction of lift lost
+fn apply_departure_dynamics(aircraft: &mut Aircraft, inputs: ControlInputs, dt: f32) -> f32 {
+    let spin_direction = aircraft.spin_rate.signum();
+    let developed = aircraft.spin_rate.abs() >= SPIN_DEVELOPED_RATE;
+    let opposite_rudder = aircraft.spin_rate != 0.0 && inputs.yaw * spin_direction < 0.0;
+    let forward_stick = inputs.pitch < 0.0;
+
+    let mut stall_depth = ((aircraft.angle_of_attack - CRITICAL_AOA) / STALL_DEPTH_RANGE).clamp(0.0, 1.0);
+    // A developed spin keeps the wing stalled until the stick goes forward
+    if developed && !forward_stick {
+        stall_depth = stall_depth.max(aircraft.spin_rate.abs() / SPIN_MAX_RATE);
+    }
+
+    if stall_depth > 0.0 {
+        aircraft.pitch_velocity -= STALL_PITCH_BREAK * stall_depth * dt;
+        if aircraft.spin_rate == 0.0 {
+            // The wing the aircraft is already yawing away from lets go first
+            let direction = if aircraft.yaw_velocity.abs() > 0.05 {
+                -aircraft.yaw_velocity.signum()
+            } else if rand::random::<bool>() {
+                1.0
+            } else {
+                -1.0
+            };
+            aircraft.spin_rate = direction * SPIN_ENTRY_RATE;
+        }
+    }
+
+    if stall_depth > 0.0 && !opposite_rudder {
+        let rate = (aircraft.spin_rate.abs() + SPIN_BUILD_RATE * stall_depth * dt).min(SPIN_MAX_RATE);
+        aircraft.spin_rate = aircraft.spin_rate.signum() * rate;
+    } else if aircraft.spin_rate != 0.0 {
+        let recovery = match (developed, opposite_rudder && forward_stick) {
+            (_, true) => SPIN_RECOVERY_RATE,
+            (false, false) => SPIN_DECAY_RATE,
+            (true, false) => 0.0,
+        };
+        let rate = (aircraft.spin_rate.abs() - recovery * dt).max(0.0);
+        aircraft.spin_rate = if rate > 0.0 { spin_direction * rate } else { 0.0 };
+    }
+
+    // Negative roll and yaw velocities turn right
+    aircraft.roll_velocity -= aircraft.spin_rate * SPIN_ROLL_TORQUE * dt;
+    aircraft.yaw_velocity -= aircraft.spin_rate * SPIN_YAW_TORQUE * dt;
+
+    stall_depth
+}
+
 /// Apply stall behavior at low speeds
 fn apply_stall_behavior(aircraft: &mut Aircraft, transform: &Transform, airspeed_ratio: f32, dt: f32) {
-    if aircraft.speed < aircraft.max_speed * STALL_THRESHOLD_RATIO {
+    if aircraft.speed < stall_speed(aircraft) {
         let stall_strength = (1.0 - airspeed_ratio).max(0.0);
         let stall_pitch_down = stall_strength * if transform.up().y.is_sign_negative() {
             -1.0
@@ -578,6 +944,8 @@ fn apply_aircraft_movement(
     current_wind: Vec3,
     turbulence_force: Vec3,
     turbulence_velocity_scale: f32,
+    vertical_air: f32,
+    lift_loss: f32,
     dt: f32,
 ) {
     // Apply rotational damping
@@ -591,16 +959,45 @@ fn apply_aircraft_movement(
     transform.rotate_local_y(aircraft.yaw_velocity * dt);
 
     // Calculate final movement vector
-    let gravity_factor = (1.0 - (aircraft.speed / LIFT_THRESHOLD_SPEED)).max(0.0);
+    let gravity_factor = (1.0 - (aircraft.speed / LIFT_THRESHOLD_SPEED)).max(lift_loss);
     let mut movement = forward * aircraft.speed;
     movement.y -= GRAVITY_STRENGTH * gravity_factor;
     movement += current_wind * WIND_LATERAL_COUPLING; 
     movement += turbulence_force * turbulence_velocity_scale;
+    movement.y += vertical_air;
 
     aircraft.velocity = movement;
     transform.translation += movement * dt;
 }
 
+/// Deflect a glancing terrain contact into a skid; returns the damage taken, or `None` for a head-on impact
+fn scrape_terrain(aircraft: &mut Aircraft, transform: &mut Transform, normal: Vec3, terrain_height: f32) -> Option<f32> {
+    let forward = transform.forward().as_vec3();
+    let speed = aircraft.velocity.length();
+    let into_ground = -aircraft.velocity.dot(normal);
+    if speed <= 0.0 || into_ground > speed * SCRAPE_MAX_APPROACH_SINE || -forward.dot(normal) > SCRAPE_MAX_APPROACH_SINE {
+        return None;
+    }
+
+    // Strip the into-ground velocity, plus a little bounce, and point the nose along what's left
+    let deflected = aircraft.velocity + normal * into_ground.max(0.0) * (1.0 + SCRAPE_RESTITUTION);
+    transform.rotate(Quat::from_rotation_arc(forward, deflected.normalize_or(forward)));
+    transform.translation.y = terrain_height + SCRAPE_CLEARANCE;
+    aircraft.speed *= 1.0 - SCRAPE_FRICTION;
+    aircraft.velocity = deflected * (1.0 - SCRAPE_FRICTION);
+    aircraft.pitch_velocity = aircraft.pitch_velocity.max(0.0);
+    Some(into_ground.max(0.0) * SCRAPE_DAMAGE_PER_SPEED)
+}
+
+/// Slow, shallow and wings-level enough to land on the water instead of crashing into it
+fn can_ditch(aircraft: &Aircraft, transform: &Transform) -> bool {
+    let speed = aircraft.velocity.length();
+    aircraft.speed <= aircraft.max_speed * DITCH_MAX_SPEED_RATIO
+        && -aircraft.velocity.y <= speed * DITCH_MAX_DESCENT_SINE
+        && transform.forward().y >= -DITCH_MAX_DESCENT_SINE
+        && transform.right().y.abs() <= DITCH_MAX_BANK_SINE
+}
+
 /// Handle free flight camera controls
 fn handle_free_flight_camera(
     keyboard: &ButtonInput<KeyCode>,
@@ -638,6 +1035,108 @@ fn handle_free_flight_camera(
     camera_transform.translation += pan_direction.normalize_or_zero() * pan_speed * dt;
 }
 
+/// Advance one aircraft through a physics step; `controls` is `None` when nobody is flying it
+pub fn step_flight(
+    aircraft: &mut Aircraft,
+    plane_transform: &mut Transform,
+    controls: Option<ControlInputs>,
+    wind: &Wind,
+    world_gen: &WorldGenerator,
+    day_cycle: &DayNightCycle,
+    time: &Time,
+    difficulty: &Difficulty,
+) -> FlightForces {
+    let dt = time.delta_secs();
+    let pos = plane_transform.translation;
+    let time_elapsed = time.elapsed_secs_f64();
+
+    let forward = plane_transform.forward().as_vec3();
+    let right = plane_transform.right().as_vec3();
+    let up = plane_transform.up().as_vec3();
+    let climb_angle = forward.y;
+    
+    let airspeed_ratio = aircraft.speed / aircraft.max_speed;
+    let dynamic_pressure = airspeed_ratio.powi(2);
+
+    // Calculate forces
+    let mut forces = calculate_engine_and_drag(aircraft, climb_angle, airspeed_ratio, dynamic_pressure);
+    let wind_effects = calculate_wind_effects(wind, pos, time_elapsed, forward, right, up);
+    let wind_scale = difficulty.wind_scale;
+    forces.wind_acceleration = wind_effects.wind_acceleration * wind_scale;
+
+    let wind_drift = wind.wind_direction * wind.wind_speed * time_elapsed as f32;
+    let turbulence = calculate_turbulence(wind, pos, wind_drift, time_elapsed, airspeed_ratio, turbulence_multiplier(world_gen, day_cycle, pos));
+    let vertical_air = calculate_vertical_air(world_gen, wind, day_cycle, pos, time_elapsed);
+
+    // Apply speed changes
+    aircraft.speed += (
+        forces.engine_acceleration + 
+        forces.gravity_acceleration - 
+        forces.turn_drag - 
+        forces.parasitic_drag + 
+        forces.wind_acceleration
+    ) * dt;
+    aircraft.speed = aircraft.speed.max(0.0);
+
+    let flight_forces = FlightForces {
+        thrust: forward * forces.engine_acceleration,
+        lift: up * forces.lift_force,
+        gravity: forward * forces.gravity_acceleration,
+        drag: -forward * (forces.turn_drag + forces.parasitic_drag),
+        wind: wind_effects.current_wind * wind_scale,
+    };
+
+    // Handle pilot input and stabilization
+    let control_effectiveness = get_control_effectiveness(airspeed_ratio);
+    let pitch_strength = aircraft.pitch_strength * (1.0 + aircraft.cg_position * CG_STABILITY_EFFECT).max(0.2) * control_effectiveness;
+    let roll_strength = aircraft.roll_strength * control_effectiveness;
+    let yaw_strength = aircraft.yaw_strength * control_effectiveness;
+
+    if let Some(inputs) = controls {
+        handle_flight_controls(
+            inputs, 
+            aircraft, 
+            plane_transform, 
+            pitch_strength, 
+            roll_strength, 
+            yaw_strength, 
+            control_effectiveness, 
+            difficulty.auto_level_scale,
+            dt
+        );
+    }
+
+    // A forward CG pulls the nose down and an aft one lifts it
+    aircraft.pitch_velocity += aircraft.cg_position * CG_PITCH_MOMENT * control_effectiveness * dt;
+
+    // Apply environmental effects
+    aircraft.pitch_velocity += (wind_effects.macro_wind_pitch + turbulence.turbulence_pitch * turbulence.turbulence_scale) * wind_scale * dt;
+    aircraft.roll_velocity += (wind_effects.macro_wind_roll + turbulence.turbulence_roll * turbulence.turbulence_scale) * wind_scale * dt;
+    aircraft.yaw_velocity += (wind_effects.macro_wind_yaw + turbulence.turbulence_yaw * turbulence.turbulence_scale) * wind_scale * dt;
+
+    aircraft.angle_of_attack = angle_of_attack(aircraft);
+    let lift_loss = if difficulty.realistic_stalls {
+        apply_departure_dynamics(aircraft, controls.unwrap_or_default(), dt)
+    } else {
+        aircraft.spin_rate = 0.0;
+        apply_stall_behavior(aircraft, plane_transform, airspeed_ratio, dt);
+        0.0
+    };
+    apply_aircraft_movement(
+        aircraft, 
+        plane_transform, 
+        forward, 
+        wind_effects.current_wind * wind_scale, 
+        turbulence.turbulence_force, 
+        turbulence.turbulence_velocity_scale * wind_scale, 
+        vertical_air.total(),
+        lift_loss,
+        dt
+    );
+
+    flight_forces
+}
+
 /// Main camera and aircraft control system
 pub fn camera_controls(
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -646,8 +1145,14 @@ pub fn camera_controls(
     mut control_mode: ResMut<ControlMode>,
     wind: Res<Wind>,
     world_gen: Res<WorldGenerator>,
+    day_cycle: Res<DayNightCycle>,
+    difficulty: Res<Difficulty>,
+    external: Res<ExternalControl>,
+    home: Res<HomeBase>,
+    mut flight_forces: ResMut<FlightForces>,
     mut camera_query: Query<&mut Transform, With<MainCamera>>,
     mut aircraft_query: Query<(&mut Transform, &mut Aircraft), (With<Aircraft>, Without<MainCamera>)>,
+    mut commands: Commands,
 ) {
     let dt = time.delta_secs();
 
@@ -661,77 +1166,60 @@ pub fn camera_controls(
     // Aircraft physics
     if !control_mode.physics_paused {
         if let Ok((mut plane_transform, mut aircraft)) = aircraft_query.single_mut() {
-            let pos = plane_transform.translation;
-            let time_elapsed = time.elapsed_secs_f64();
-
-            update_throttle(&keyboard, &mut aircraft, dt);
-
-            let forward = plane_transform.forward().as_vec3();
-            let right = plane_transform.right().as_vec3();
-            let up = plane_transform.up().as_vec3();
-            let climb_angle = forward.y;
-            
-            let airspeed_ratio = aircraft.speed / aircraft.max_speed;
-            let dynamic_pressure = airspeed_ratio.powi(2);
-
-            // Calculate forces
-            let mut forces = calculate_engine_and_drag(&aircraft, climb_angle, airspeed_ratio, dynamic_pressure);
-            let wind_effects = calculate_wind_effects(&wind, pos, time_elapsed, forward, right, up);
-            forces.wind_acceleration = wind_effects.wind_acceleration;
-
-            let wind_drift = wind.wind_direction * wind.wind_speed * time_elapsed as f32;
-            let turbulence = calculate_turbulence(&wind, pos, wind_drift, time_elapsed, airspeed_ratio);
-
-            // Apply speed changes
-            aircraft.speed += (
-                forces.engine_acceleration + 
-                forces.gravity_acceleration - 
-                forces.turn_drag - 
-                forces.parasitic_drag + 
-                forces.wind_acceleration
-            ) * dt;
-            aircraft.speed = aircraft.speed.max(0.0);
-
-            // Handle player input and stabilization
-            let control_effectiveness = get_control_effectiveness(airspeed_ratio);
-            let pitch_strength = aircraft.pitch_strength * control_effectiveness;
-            let roll_strength = aircraft.roll_strength * control_effectiveness;
-            let yaw_strength = aircraft.yaw_strength * control_effectiveness;
-
-            if control_mode.mode == FlightMode::Aircraft || control_mode.mode == FlightMode::Orbit {
-                handle_flight_controls(
-                    &keyboard, 
-                    &mut aircraft, 
-                    &plane_transform, 
-                    pitch_strength, 
-                    roll_strength, 
-                    yaw_strength, 
-                    control_effectiveness, 
-                    dt
-                );
+            update_throttle(&keyboard, &PilotKeys::PRIMARY, &mut aircraft, dt);
+            if let Some(throttle) = external.throttle.or(home.throttle) {
+                aircraft.throttle = throttle.clamp(0.0, aircraft.max_throttle);
             }
 
-            // Apply environmental effects
-            aircraft.pitch_velocity += (wind_effects.macro_wind_pitch + turbulence.turbulence_pitch * turbulence.turbulence_scale) * dt;
-            aircraft.roll_velocity += (wind_effects.macro_wind_roll + turbulence.turbulence_roll * turbulence.turbulence_scale) * dt;
-            aircraft.yaw_velocity += (wind_effects.macro_wind_yaw + turbulence.turbulence_yaw * turbulence.turbulence_scale) * dt;
-
-            apply_stall_behavior(&mut aircraft, &plane_transform, airspeed_ratio, dt);
-            apply_aircraft_movement(
-                &mut aircraft, 
-                &mut plane_transform, 
-                forward, 
-                wind_effects.current_wind, 
-                turbulence.turbulence_force, 
-                turbulence.turbulence_velocity_scale, 
-                dt
-            );
+            // A script driving the external control API flies instead of the keyboard, then the return-home autopilot
+            let piloted = control_mode.mode == FlightMode::Aircraft || control_mode.mode == FlightMode::Orbit;
+            let controls = piloted.then(|| {
+                external.inputs.or(home.inputs).unwrap_or_else(|| ControlInputs::from_keyboard(&keyboard, &PilotKeys::PRIMARY))
+            });
+            *flight_forces = step_flight(&mut aircraft, &mut plane_transform, controls, &wind, &world_gen, &day_cycle, &time, &difficulty);
 
             // Terrain and water collision detection
             let aircraft_pos = plane_transform.translation;
             let terrain_height = world_gen.get_terrain_height(&[aircraft_pos.x, aircraft_pos.y, aircraft_pos.z]);
             
-            if (aircraft_pos.y <= terrain_height || aircraft_pos.y <= 0.0) && !aircraft.crashed {
+            // Shallow contact with land skids off it; water and steep impacts still crash
+            let scrape = if aircraft_pos.y <= terrain_height && terrain_height > 0.0 && !aircraft.crashed {
+                let normal = world_gen.get_terrain_normal(aircraft_pos.x, aircraft_pos.z);
+                scrape_terrain(&mut aircraft, &mut plane_transform, normal, terrain_height)
+            } else {
+                None
+            };
+            let ditching = aircraft_pos.y <= 0.0 && terrain_height <= 0.0 && !aircraft.crashed
+                && can_ditch(&aircraft, &plane_transform);
+            if ditching {
+                // The float takes over from the flight model until the aircraft sinks or is rescued
+                let velocity = aircraft.velocity;
+                aircraft.speed = 0.0;
+                aircraft.throttle = 0.0;
+                aircraft.velocity = Vec3::ZERO;
+                aircraft.pitch_velocity = 0.0;
+                aircraft.roll_velocity = 0.0;
+                aircraft.yaw_velocity = 0.0;
+                plane_transform.translation.y = 0.0;
+                control_mode.physics_paused = true;
+                commands.trigger(SpawnEffect {
+                    kind: EffectKind::Splash,
+                    position: plane_transform.translation,
+                    velocity,
+                    intensity: 0.5,
+                });
+                commands.trigger(Ditched { position: plane_transform.translation, velocity });
+                info!("Aircraft ditched at position: [{:.1}, {:.1}]", aircraft_pos.x, aircraft_pos.z);
+            } else if let Some(damage) = scrape {
+                commands.trigger(SpawnEffect {
+                    kind: EffectKind::Scrape,
+                    position: Vec3::new(aircraft_pos.x, terrain_height, aircraft_pos.z),
+                    velocity: aircraft.velocity,
+                    intensity: 1.0,
+                });
+                commands.trigger(TerrainScrape { position: plane_transform.translation, damage });
+            } else if (aircraft_pos.y <= terrain_height || aircraft_pos.y <= 0.0) && !aircraft.crashed {
+                let impact_velocity = plane_transform.forward().as_vec3() * aircraft.speed + aircraft.velocity;
                 aircraft.crashed = true;
                 aircraft.speed = 0.0;
                 aircraft.velocity = Vec3::ZERO;
@@ -741,6 +1229,14 @@ pub fn camera_controls(
                 plane_transform.translation.y = terrain_height.max(0.0);
                 control_mode.physics_paused = true;
                 
+                let effect_kind = if terrain_height <= 0.0 { EffectKind::Splash } else { EffectKind::Explosion };
+                commands.trigger(SpawnEffect {
+                    kind: effect_kind,
+                    position: plane_transform.translation,
+                    velocity: impact_velocity,
+                    intensity: 1.0,
+                });
+                
                 if aircraft_pos.y <= 0.0 {
                     info!("Aircraft crashed into water at position: [{:.1}, {:.1}, {:.1}]", aircraft_pos.x, aircraft_pos.y, aircraft_pos.z);
                 } else {
@@ -748,6 +1244,18 @@ pub fn camera_controls(
                 }
             }
 
+            // Low passes over the desert kick up dust
+            let height_above_ground = aircraft_pos.y - terrain_height;
+            if !aircraft.crashed && terrain_height > 0.0 && height_above_ground < DUST_MAX_HEIGHT
+                && world_gen.get_biome(&[aircraft_pos.x, aircraft_pos.y, aircraft_pos.z]) == Biome::Desert {
+                commands.trigger(SpawnEffect {
+                    kind: EffectKind::Dust,
+                    position: Vec3::new(aircraft_pos.x, terrain_height, aircraft_pos.z),
+                    velocity: plane_transform.forward().as_vec3() * aircraft.speed,
+                    intensity: 1.0 - height_above_ground / DUST_MAX_HEIGHT,
+                });
+            }
+
             // Prevent further movement if crashed
             if aircraft.crashed {
                 aircraft.speed = 0.0;
@@ -840,6 +1348,7 @@ pub fn camera_follow_aircraft(
     time: Res<Time>,
     keyboard: Res<ButtonInput<KeyCode>>,
     control_mode: Res<ControlMode>,
+    accessibility: Res<Accessibility>,
     aircraft_query: Query<(&Transform, &Aircraft), (With<Aircraft>, Without<MainCamera>)>,
     mut camera_query: Query<(&mut Transform, &mut MainCamera)>,
 ) {
@@ -861,23 +1370,12 @@ pub fn camera_follow_aircraft(
         main_camera.orbit_distance = main_camera.orbit_distance.clamp(0.0, CAMERA_ORBIT_DISTANCE_MAX);
     }
 
-    // Calculate camera positioning
-    let mut actual_direction = aircraft.velocity.normalize_or_zero();
-    if actual_direction == Vec3::ZERO {
-        actual_direction = plane_transform.forward().into();
-    }
-
-    let plane_movement = aircraft.velocity * time.delta_secs();
-    camera_transform.translation += plane_movement;
-
-    let speed_ratio = (aircraft.speed / CAMERA_SPEED_THRESHOLD).clamp(0.0, 2.0);
-    let dynamic_distance = main_camera.orbit_distance + (CAMERA_MAX_EXTRA_DISTANCE * speed_ratio.powf(0.5));
-    let smoothness = CAMERA_SMOOTHNESS_BASE + (speed_ratio * CAMERA_SMOOTHNESS_MULTIPLIER);
-    let t = (time.delta_secs() * smoothness).min(1.0);
-
     // Apply camera behavior based on mode
     match control_mode.mode {
         FlightMode::Orbit => {
+            camera_transform.translation += aircraft.velocity * time.delta_secs();
+            let (dynamic_distance, t) = follow_distance_and_blend(aircraft, main_camera.orbit_distance, time.delta_secs());
+
             if keyboard.pressed(KeyCode::ArrowLeft) { 
                 main_camera.orbit_yaw -= ORBIT_ROTATION_SPEED * time.delta_secs(); 
             }
@@ -908,17 +1406,50 @@ pub fn camera_follow_aircraft(
             camera_transform.rotation = target_rotation;
         }
         FlightMode::Aircraft => {
-            let target_position = plane_transform.translation 
-                + (-actual_direction * dynamic_distance) 
-                + (plane_transform.up() * aircraft.camera_height);
-            camera_transform.translation = camera_transform.translation.lerp(target_position, t);
-
-            let look_ahead_distance = CAMERA_LOOK_AHEAD_MULTIPLIER * aircraft.speed;
-            let look_target = plane_transform.translation + (actual_direction * look_ahead_distance);
-            
-            let target_rotation = camera_transform.looking_at(look_target, plane_transform.up()).rotation;
-            camera_transform.rotation = camera_transform.rotation.slerp(target_rotation, t);
+            chase_camera(&mut camera_transform, plane_transform, aircraft, main_camera.orbit_distance, accessibility.reduce_motion, time.delta_secs());
         }
         FlightMode::FreeFlight => unreachable!(),
     }
 }
+
+/// How far back the follow cameras sit and how quickly they catch up; both grow with airspeed
+fn follow_distance_and_blend(aircraft: &Aircraft, orbit_distance: f32, dt: f32) -> (f32, f32) {
+    let speed_ratio = (aircraft.speed / CAMERA_SPEED_THRESHOLD).clamp(0.0, 2.0);
+    let dynamic_distance = orbit_distance + (CAMERA_MAX_EXTRA_DISTANCE * speed_ratio.powf(0.5));
+    let smoothness = CAMERA_SMOOTHNESS_BASE + (speed_ratio * CAMERA_SMOOTHNESS_MULTIPLIER);
+    (dynamic_distance, (dt * smoothness).min(1.0))
+}
+
+/// Ease a camera in behind an aircraft and look ahead along its flight path
+pub fn chase_camera(
+    camera_transform: &mut Transform,
+    plane_transform: &Transform,
+    aircraft: &Aircraft,
+    orbit_distance: f32,
+    reduce_motion: bool,
+    dt: f32,
+) {
+    let mut actual_direction = aircraft.velocity.normalize_or_zero();
+    if actual_direction == Vec3::ZERO {
+        actual_direction = plane_transform.forward().into();
+    }
+
+    camera_transform.translation += aircraft.velocity * dt;
+    let (dynamic_distance, t) = follow_distance_and_blend(aircraft, orbit_distance, dt);
+
+    let target_position = plane_transform.translation 
+        + (-actual_direction * dynamic_distance) 
+        + (plane_transform.up() * aircraft.camera_height);
+    camera_transform.translation = camera_transform.translation.lerp(target_position, t);
+
+    let look_ahead_distance = CAMERA_LOOK_AHEAD_MULTIPLIER * aircraft.speed;
+    let look_target = plane_transform.translation + (actual_direction * look_ahead_distance);
+    
+    let (up, turn_rate) = if reduce_motion {
+        (Vec3::Y.lerp(plane_transform.up().as_vec3(), REDUCED_MOTION_BANK), t * REDUCED_MOTION_DAMPING)
+    } else {
+        (plane_transform.up().as_vec3(), t)
+    };
+    let target_rotation = camera_transform.looking_at(look_target, up).rotation;
+    camera_transform.rotation = camera_transform.rotation.slerp(target_rotation, turn_rate);
+}
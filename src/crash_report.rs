@@ -0,0 +1,185 @@
+use bevy::asset::{AssetPath, UntypedAssetLoadFailedEvent};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::backtrace::Backtrace;
+use std::panic::PanicHookInfo;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::controls::{Aircraft, AircraftModel, DEFAULT_AIRCRAFT_MODEL};
+use crate::game_state::GameState;
+use crate::world_generation::{ChunkManager, WorldGenerator};
+use crate::RenderSettings;
+
+/// Crash reports land here, one file per crash
+const REPORT_DIR: &str = "crash_reports";
+/// Holds the path of the newest report until the next launch has shown it
+const PENDING_REPORT_PATH: &str = "crash_reports/pending.txt";
+/// Seconds between refreshes of the state a report includes
+pub const CONTEXT_INTERVAL_SECS: f32 = 1.0;
+
+/// What the sim was doing, kept outside the ECS so the panic hook can read it after the world is gone
+static CRASH_CONTEXT: Mutex<String> = Mutex::new(String::new());
+
+/// Problems worth telling the player about: a crash in the last session, and assets that failed to load in this one
+#[derive(Resource, Default)]
+pub struct ErrorReports {
+    /// Report left by the last session's crash, until dismissed
+    pub previous_crash: Option<String>,
+    pub asset_failures: Vec<String>,
+    /// Required models not on disk at startup, standing in as placeholders
+    pub missing_assets: Vec<String>,
+    pub dismissed: bool,
+}
+
+/// Write a report on any panic before the default hook prints it and the app goes down
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => eprintln!("Crash report written to {path}"),
+            Err(error) => eprintln!("Could not write a crash report: {error}"),
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> Result<String, String> {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let thread = std::thread::current();
+    // A panic while the context was being written leaves it poisoned, not useless
+    let context = CRASH_CONTEXT.lock().map_or_else(|poisoned| poisoned.into_inner().clone(), |context| context.clone());
+    let report = format!(
+        "bevy_sim {} crashed at {} seconds past the epoch\n\nPanic on thread '{}': {}\n\n{}\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        seconds,
+        thread.name().unwrap_or("unnamed"),
+        info,
+        if context.is_empty() { "No state recorded yet\n".to_string() } else { context },
+        Backtrace::force_capture(),
+    );
+    let path = format!("{}/crash_{}.txt", REPORT_DIR, seconds);
+    std::fs::create_dir_all(REPORT_DIR).map_err(|error| error.to_string())?;
+    std::fs::write(&path, report).map_err(|error| error.to_string())?;
+    std::fs::write(PENDING_REPORT_PATH, &path).map_err(|error| error.to_string())?;
+    Ok(path)
+}
+
+/// Pick up a report the last session left, to show once the window is up
+pub fn load_previous_crash(mut reports: ResMut<ErrorReports>) {
+    if let Ok(path) = std::fs::read_to_string(PENDING_REPORT_PATH) {
+        reports.previous_crash = Some(path.trim().to_string());
+    }
+}
+
+/// Refresh the state a crash report includes: seed, position and the settings most likely to matter
+pub fn record_crash_context(
+    world_gen: Res<WorldGenerator>,
+    chunk_manager: Res<ChunkManager>,
+    render_settings: Res<RenderSettings>,
+    state: Res<State<GameState>>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) {
+    let mut context = format!(
+        "State: {:?}\nSeed: {} ({:?} terrain, {} edited chunks)\n",
+        state.get(),
+        world_gen.seed,
+        world_gen.terrain,
+        world_gen.overrides.edited_chunks().count(),
+    );
+    for (transform, aircraft) in &aircraft_query {
+        context.push_str(&format!(
+            "Aircraft {} at ({:.1}, {:.1}, {:.1}), speed {:.1}, crashed {}\n",
+            aircraft.model_path,
+            transform.translation.x,
+            transform.translation.y,
+            transform.translation.z,
+            aircraft.speed,
+            aircraft.crashed,
+        ));
+    }
+    context.push_str(&format!(
+        "Render distance {}, LOD quality {}, LOD distance {}\n{:?}\n",
+        chunk_manager.render_distance,
+        chunk_manager.lod_quality_multiplier,
+        chunk_manager.lod_distance_multiplier,
+        *render_settings,
+    ));
+    if let Ok(mut shared) = CRASH_CONTEXT.lock() {
+        *shared = context;
+    }
+}
+
+/// Note every asset that failed to load, and put the stock aircraft back when a preset's model is missing
+/// rather than flying an invisible one
+pub fn handle_asset_failures(
+    mut failures: MessageReader<UntypedAssetLoadFailedEvent>,
+    mut reports: ResMut<ErrorReports>,
+    mut models: Query<&mut SceneRoot, With<AircraftModel>>,
+    asset_server: Res<AssetServer>,
+) {
+    let default_model = AssetPath::parse(DEFAULT_AIRCRAFT_MODEL);
+    for failure in failures.read() {
+        error!("Failed to load {}: {}", failure.path, failure.error);
+        reports.asset_failures.push(format!("{}: {}", failure.path, failure.error));
+        reports.dismissed = false;
+
+        for mut model in &mut models {
+            let showing_failed = model.0.path().is_some_and(|path| path.path() == failure.path.path());
+            if showing_failed && failure.path.path() != default_model.path() {
+                model.0 = asset_server.load(DEFAULT_AIRCRAFT_MODEL);
+            }
+        }
+    }
+}
+
+pub fn error_reports_ui(mut contexts: EguiContexts, mut reports: ResMut<ErrorReports>) -> Result<(), BevyError> {
+    if reports.dismissed
+        || (reports.previous_crash.is_none() && reports.asset_failures.is_empty() && reports.missing_assets.is_empty())
+    {
+        return Ok(());
+    }
+
+    let mut dismiss = false;
+    egui::Window::new("Error Report")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(contexts.ctx_mut()?, |ui| {
+            if let Some(path) = &reports.previous_crash {
+                ui.label("The last session crashed. A report with the backtrace, seed and settings was saved to:");
+                ui.monospace(path);
+                ui.label("Attach it when reporting the problem.");
+            }
+            if !reports.missing_assets.is_empty() {
+                if reports.previous_crash.is_some() {
+                    ui.separator();
+                }
+                ui.label("These models are missing from the assets folder and show as placeholders:");
+                for missing in &reports.missing_assets {
+                    ui.monospace(missing);
+                }
+            }
+            if !reports.asset_failures.is_empty() {
+                if reports.previous_crash.is_some() || !reports.missing_assets.is_empty() {
+                    ui.separator();
+                }
+                ui.label("Some assets failed to load and may be missing from the world:");
+                egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                    for failure in &reports.asset_failures {
+                        ui.monospace(failure);
+                    }
+                });
+            }
+            dismiss = ui.button("Dismiss").clicked();
+        });
+
+    if dismiss {
+        // Shown once; the report itself stays on disk
+        if reports.previous_crash.take().is_some() {
+            let _ = std::fs::remove_file(PENDING_REPORT_PATH);
+        }
+        reports.dismissed = true;
+    }
+    Ok(())
+}
@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use flight_sim_server::time_trial::{current_day, daily_seed, generate_course, splitmix, GATE_RADIUS};
+use noise::Perlin;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::consts::{HOURS_PER_DAY, TIME_OFFSET_HOURS};
+use crate::controls::{Aircraft, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::flight_track::civil_date;
+use crate::hud::HudPalette;
+use crate::network::{
+    ClientMessage, Connected, DailyLeaderboardReceived, DailyResultReceived, LeaderboardEntry, NetworkClient, PlaneType,
+    RespawnAircraft,
+};
+use crate::season::Season;
+use crate::time_trial::{format_run_time, GateCourse};
+use crate::world_generation::{TerrainPreset, WorldGenerator};
+
+/// Best times on past daily flights, kept next to the other per-user settings
+const BESTS_PATH: &str = "settings/daily_bests.ron";
+/// The day's wind blows steadily at up to this speed, with turbulence in this range
+const MAX_WIND_SPEED: f32 = 12.0;
+const MIN_TURBULENCE: f32 = 0.002;
+const MAX_TURBULENCE: f32 = 0.02;
+/// Local solar hours the flight is set between, so it is always flown in daylight
+const EARLIEST_HOUR: f32 = 8.0;
+const LATEST_HOUR: f32 = 17.0;
+
+/// Begin day `day`'s daily flight; the world generator must already be on that day's world
+#[derive(Event)]
+pub struct StartDailyFlight {
+    pub day: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DailyBest {
+    pub time: f32,
+    pub plane_type: PlaneType,
+}
+
+/// A challenge that changes every day at midnight UTC: the world, weather and gate course all follow from
+/// the date, so every player flies the same one. Bests are kept locally and can be sent to a server's board
+#[derive(Resource, Default)]
+pub struct DailyFlight {
+    /// Day being flown, while the daily flight is on
+    pub day: Option<u32>,
+    course: GateCourse,
+    bests: BTreeMap<u32, DailyBest>,
+    pub status: Option<String>,
+    /// The server's board, and the day it is for
+    pub leaderboard: Vec<LeaderboardEntry>,
+    leaderboard_day: Option<u32>,
+}
+
+impl DailyFlight {
+    pub fn best(&self, day: u32) -> Option<DailyBest> {
+        self.bests.get(&day).copied()
+    }
+
+    /// Put today's best on the server's daily board
+    pub fn submit(&mut self, client: &NetworkClient) {
+        let day = current_day();
+        let Some(best) = self.best(day) else { return };
+        client.send(ClientMessage::SubmitDailyTime { day, time: best.time, plane_type: best.plane_type });
+        self.status = Some("Submitting...".to_string());
+    }
+
+    /// Keep a finished run if it beats the day's best, saying how it went
+    fn record(&mut self, day: u32, time: f32, plane_type: PlaneType) -> String {
+        match self.best(day) {
+            Some(best) if best.time <= time => {
+                format!("Finished in {}, best {}", format_run_time(time), format_run_time(best.time))
+            }
+            _ => {
+                self.bests.insert(day, DailyBest { time, plane_type });
+                save_bests(&self.bests);
+                format!("New best: {}", format_run_time(time))
+            }
+        }
+    }
+
+    pub fn leaderboard_ui(&self, ui: &mut egui::Ui) {
+        if self.leaderboard_day != Some(current_day()) {
+            return;
+        }
+        if self.leaderboard.is_empty() {
+            ui.label("No daily times yet");
+        }
+        egui::Grid::new("daily_leaderboard").num_columns(4).show(ui, |ui| {
+            for (rank, entry) in self.leaderboard.iter().enumerate() {
+                ui.label(format!("{}.", rank + 1));
+                ui.label(&entry.name);
+                ui.label(format!("{:?}", entry.plane_type));
+                ui.label(egui::RichText::new(format_run_time(entry.time)).monospace());
+                ui.end_row();
+            }
+        });
+    }
+}
+
+/// `2024-05-01` for the day `day` days after the Unix epoch
+pub fn date_label(day: u32) -> String {
+    let (year, month, day) = civil_date(day as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// World seed and terrain of day `day`'s flight. The aircraft starts at the world's spawn point and the
+/// course is laid out from there. The world has no airports yet, so that spawn point, the ground under
+/// the origin, stands in for a departure airport and is the same for every pilot
+pub fn daily_world(day: u32) -> (u32, TerrainPreset) {
+    let seed = daily_seed(day);
+    (seed, TerrainPreset::ALL[seed as usize % TerrainPreset::ALL.len()])
+}
+
+pub fn load_daily_bests(mut daily: ResMut<DailyFlight>) {
+    let Ok(text) = std::fs::read_to_string(BESTS_PATH) else { return };
+    match ron::from_str::<BTreeMap<u32, DailyBest>>(&text) {
+        Ok(bests) => daily.bests = bests,
+        Err(error) => warn!("Ignoring {}: {}", BESTS_PATH, error),
+    }
+}
+
+fn save_bests(bests: &BTreeMap<u32, DailyBest>) {
+    let result = ron::ser::to_string_pretty(bests, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(BESTS_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", BESTS_PATH, error);
+    }
+}
+
+/// Lay out the day's course and set its weather. Wind, clock and season are held for the flight, since
+/// their usual drift would take each player's weather somewhere different
+pub fn start_daily_flight(
+    trigger: On<StartDailyFlight>,
+    mut daily: ResMut<DailyFlight>,
+    world_gen: Res<WorldGenerator>,
+    mut wind: ResMut<Wind>,
+    mut cycle: ResMut<DayNightCycle>,
+    mut season: ResMut<Season>,
+) {
+    let seed = daily_seed(trigger.day);
+    let gates = generate_course(seed).into_iter().map(Vec2::from);
+    daily.course = GateCourse::over_terrain(gates, GATE_RADIUS, &world_gen);
+    daily.day = Some(trigger.day);
+    daily.status = None;
+
+    // Rolled apart from the course, which draws from the seed itself
+    let mut state = (seed as u64) << 32;
+    let wind_heading = splitmix(&mut state) * std::f32::consts::TAU;
+    let wind_speed = splitmix(&mut state) * MAX_WIND_SPEED;
+    wind.wind_direction = Vec3::new(wind_heading.sin(), 0.0, -wind_heading.cos());
+    wind.min_wind_speed = wind_speed;
+    wind.max_wind_speed = wind_speed;
+    wind.wind_evolution_speed = 0.0;
+    wind.turbulence_intensity = MIN_TURBULENCE + splitmix(&mut state) * (MAX_TURBULENCE - MIN_TURBULENCE);
+    // Fronts, and the rain under them, sit where the day's noise puts them
+    wind.perlin = Perlin::new(seed);
+
+    let hour = EARLIEST_HOUR + splitmix(&mut state) * (LATEST_HOUR - EARLIEST_HOUR);
+    cycle.time_of_day = ((hour + TIME_OFFSET_HOURS) / HOURS_PER_DAY).rem_euclid(1.0);
+    cycle.speed = 0.0;
+    season.day_of_year = splitmix(&mut state) * season.days_per_year;
+    season.paused = true;
+
+    info!("Daily flight {} on seed {}: wind {:.1} from {:.0}°, {:.1}h", date_label(trigger.day), seed, wind_speed, wind_heading.to_degrees(), hour);
+}
+
+/// A respawn puts the aircraft back at the start, so the run starts over
+pub fn rearm_daily_on_respawn(_trigger: On<RespawnAircraft>, mut daily: ResMut<DailyFlight>) {
+    daily.course.rearm();
+}
+
+/// Joining a server moves us to its world
+pub fn end_daily_flight(_trigger: On<Connected>, mut daily: ResMut<DailyFlight>) {
+    daily.day = None;
+    daily.course = GateCourse::default();
+}
+
+/// An accepted time fetches the board again, so it shows where the run placed
+pub fn receive_daily_result(
+    trigger: On<DailyResultReceived>,
+    mut daily: ResMut<DailyFlight>,
+    client: Option<Res<NetworkClient>>,
+) {
+    daily.status = Some(trigger.message.clone());
+    if let Some(client) = client.filter(|_| trigger.accepted) {
+        client.send(ClientMessage::RequestDailyLeaderboard);
+    }
+}
+
+pub fn receive_daily_leaderboard(trigger: On<DailyLeaderboardReceived>, mut daily: ResMut<DailyFlight>) {
+    daily.leaderboard = trigger.entries.clone();
+    daily.leaderboard_day = Some(trigger.day);
+}
+
+/// Time the course, keeping finished runs and starting over after each one or a crash
+pub fn update_daily_flight(
+    time: Res<Time>,
+    mut daily: ResMut<DailyFlight>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(day) = daily.day else { return };
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        if daily.course.started() {
+            daily.course.rearm();
+            daily.status = Some("Crashed: the clock restarts at the first gate".to_string());
+        }
+        return;
+    }
+
+    if let Some(run_time) = daily.course.advance(time.delta_secs(), transform.translation) {
+        let status = daily.record(day, run_time, aircraft.plane_type);
+        info!("Daily flight {}: {}", date_label(day), status);
+        daily.status = Some(status);
+        daily.course.rearm();
+    }
+    daily.course.draw(&mut gizmos, transform.translation);
+}
+
+/// Run timer and the day's best
+pub fn daily_flight_hud(
+    mut contexts: EguiContexts,
+    daily: Res<DailyFlight>,
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
+    let Some(day) = daily.day else { return Ok(()) };
+
+    egui::Window::new("Daily Flight")
+        .title_bar(false)
+        .resizable(false)
+        // Under the lost contacts readout, clear of the time trial's slot
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 740.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new(format!("DAILY FLIGHT {}", date_label(day))).size(12.0));
+            match daily.course.elapsed() {
+                Some(elapsed) => {
+                    ui.label(egui::RichText::new(format_run_time(elapsed)).size(22.0).monospace());
+                    ui.label(format!("Gate {}/{}", daily.course.gates_passed() + 1, daily.course.gates.len()));
+                }
+                None => {
+                    ui.label(format!("Fly through the green gate to start ({} gates)", daily.course.gates.len()));
+                }
+            }
+            if let Some(best) = daily.best(day) {
+                ui.label(format!("Best {} ({:?})", format_run_time(best.time), best.plane_type));
+            }
+            if let Some(status) = &daily.status {
+                ui.label(status);
+            }
+        });
+
+    Ok(())
+}
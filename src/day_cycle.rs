@@ -1,11 +1,176 @@
-use bevy::prelude::*;
-use crate::{consts::*, world_generation::ChunkManager, controls::MainCamera};
+use bevy::{camera::Exposure, post_process::bloom::Bloom, prelude::*};
+use crate::{consts::*, world_generation::ChunkManager, controls::MainCamera, underwater::light_through_water, RenderSettings};
+
+/// How quickly exposure adapts towards its target, in EV per second
+const EXPOSURE_ADAPTATION_RATE: f32 = 1.5;
+
+/// Tilt of the earth's axis against the ecliptic
+const OBLIQUITY: f32 = 23.44 * std::f32::consts::PI / 180.0;
+/// Sun altitude at sunrise/sunset, accounting for refraction and the disc radius
+const SUNRISE_ALTITUDE: f32 = -0.833 * std::f32::consts::PI / 180.0;
+/// Calendar day of the March equinox, where the year fraction starts
+const EQUINOX_DAY: f32 = 79.0;
+const DAYS_IN_CALENDAR_YEAR: f32 = 365.0;
+/// Light level left at night from the moon and sky glow
+const NIGHT_LIGHT_LEVEL: f32 = 0.05;
+const MONTHS: [(&str, u32); 12] = [
+    ("Jan", 31), ("Feb", 28), ("Mar", 31), ("Apr", 30), ("May", 31), ("Jun", 30),
+    ("Jul", 31), ("Aug", 31), ("Sep", 30), ("Oct", 31), ("Nov", 30), ("Dec", 31),
+];
+
+/// Sun, stars, exposure and the dawn/dusk events; gameplay reads the time of day through
+/// `DayNightCycle` and reacts to `Dawn` and `Dusk`
+pub struct DayCyclePlugin;
+
+impl Plugin for DayCyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            update_daylight_cycle,
+            update_exposure.after(update_daylight_cycle),
+            announce_dawn_and_dusk.after(update_daylight_cycle),
+        ));
+    }
+}
+
+/// The sun has risen above the horizon
+#[derive(Event)]
+pub struct Dawn;
+
+/// The sun has set below the horizon
+#[derive(Event)]
+pub struct Dusk;
 
 #[derive(Resource)]
 pub struct DayNightCycle {
     pub time_of_day: f32,
     pub speed: f32, 
-    pub inclination: f32,
+    /// Observer latitude in degrees, positive north
+    pub latitude: f32,
+    /// Fraction of the year since the March equinox, fed by the seasons
+    pub year_fraction: f32,
+}
+
+/// Times of sunrise and sunset in local solar hours
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SunTimes {
+    Normal { sunrise: f32, sunset: f32 },
+    MidnightSun,
+    PolarNight,
+}
+
+impl DayNightCycle {
+    /// Local solar time on the 24 hour clock
+    pub fn clock_hours(&self) -> f32 {
+        (self.time_of_day * HOURS_PER_DAY - TIME_OFFSET_HOURS).rem_euclid(HOURS_PER_DAY)
+    }
+
+    /// Ecliptic longitude of the sun, zero at the March equinox
+    fn solar_longitude(&self) -> f32 {
+        self.year_fraction * std::f32::consts::TAU
+    }
+
+    /// Sun position on the celestial sphere (x towards the equinox, z towards the pole)
+    fn sun_equatorial(&self) -> Vec3 {
+        let longitude = self.solar_longitude();
+        Vec3::new(
+            longitude.cos(),
+            OBLIQUITY.cos() * longitude.sin(),
+            OBLIQUITY.sin() * longitude.sin(),
+        )
+    }
+
+    pub fn solar_declination(&self) -> f32 {
+        self.sun_equatorial().z.asin()
+    }
+
+    /// Local sidereal time as an angle: the sun's hour angle plus its right ascension
+    fn sidereal_angle(&self) -> f32 {
+        let hour_angle = (self.clock_hours() - 12.0) / HOURS_PER_DAY * std::f32::consts::TAU;
+        let equatorial = self.sun_equatorial();
+        hour_angle + equatorial.y.atan2(equatorial.x)
+    }
+
+    /// Maps celestial-sphere directions into the world for the current date, time and latitude.
+    /// The compass treats -X as north and -Z as east.
+    pub fn sky_rotation(&self) -> Quat {
+        let latitude = self.latitude.to_radians();
+        let pole = Vec3::new(-latitude.cos(), latitude.sin(), 0.0);
+        let meridian = Vec3::new(latitude.sin(), latitude.cos(), 0.0);
+        let west = Vec3::Z;
+
+        let sidereal = self.sidereal_angle();
+        let x_axis = meridian * sidereal.cos() + west * sidereal.sin();
+        let y_axis = meridian * sidereal.sin() - west * sidereal.cos();
+        Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, pole))
+    }
+
+    /// Direction the sunlight travels, from the sun towards the ground
+    pub fn sun_direction(&self) -> Vec3 {
+        -(self.sky_rotation() * self.sun_equatorial()).normalize()
+    }
+
+    /// Rotation for the sun entity so its -Z axis points along the sunlight
+    pub fn sun_rotation(&self) -> Quat {
+        Quat::from_rotation_arc(Vec3::NEG_Z, self.sun_direction())
+    }
+
+    /// Angle of the sun above the horizon in radians, negative once it has set
+    pub fn sun_altitude(&self) -> f32 {
+        (-self.sun_direction().y).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Whether the sun is below the horizon, by the same standard as the sunrise and sunset times
+    pub fn is_night(&self) -> bool {
+        self.sun_altitude() < SUNRISE_ALTITUDE
+    }
+
+    /// Light reaching a point in the world, from the night floor to 1.0 in full sun on the surface.
+    /// Water absorbs it with depth below sea level, the same way it dims the underwater view
+    pub fn light_level_at(&self, position: Vec3) -> f32 {
+        let sky = NIGHT_LIGHT_LEVEL + (1.0 - NIGHT_LIGHT_LEVEL) * self.daylight();
+        sky * light_through_water(-position.y)
+    }
+
+    /// Sun illumination from 0.0 (night) to 1.0 (full day)
+    pub fn daylight(&self) -> f32 {
+        let up_dot = self.sun_direction().dot(Vec3::NEG_Y);
+        ((up_dot + 0.1) * 5.0).clamp(0.0, 1.0)
+    }
+
+    /// How visible the stars are, from 0.0 until well after sunset to 1.0 in full night
+    pub fn star_visibility(&self) -> f32 {
+        let up_dot = self.sun_direction().dot(Vec3::NEG_Y);
+        ((-up_dot - 0.2) / 0.6).clamp(0.0, 1.0)
+    }
+
+    pub fn sun_times(&self) -> SunTimes {
+        let latitude = self.latitude.to_radians();
+        let declination = self.solar_declination();
+        let cos_hour_angle = (SUNRISE_ALTITUDE.sin() - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos()).max(1e-6);
+
+        if cos_hour_angle > 1.0 {
+            SunTimes::PolarNight
+        } else if cos_hour_angle < -1.0 {
+            SunTimes::MidnightSun
+        } else {
+            let half_day = cos_hour_angle.acos() / std::f32::consts::TAU * HOURS_PER_DAY;
+            SunTimes::Normal { sunrise: 12.0 - half_day, sunset: 12.0 + half_day }
+        }
+    }
+
+    /// Calendar date such as "21 Jun"
+    pub fn date_label(&self) -> String {
+        let day = (EQUINOX_DAY + self.year_fraction * DAYS_IN_CALENDAR_YEAR).rem_euclid(DAYS_IN_CALENDAR_YEAR) as u32;
+        let mut remaining = day;
+        for (name, length) in MONTHS {
+            if remaining < length {
+                return format!("{} {}", remaining + 1, name);
+            }
+            remaining -= length;
+        }
+        "31 Dec".to_string()
+    }
 }
 
 #[derive(Component)]
@@ -24,7 +189,7 @@ pub fn update_daylight_cycle(
     mut cycle: ResMut<DayNightCycle>,
     mut clear_color: ResMut<ClearColor>,
     mut sun_query: Query<(&mut Transform, &mut DirectionalLight), (With<Sun>, Without<MainCamera>)>,
-    mut env_query: Query<(&mut DistanceFog, &mut AmbientLight)>, 
+    mut env_query: Query<(&mut DistanceFog, &mut AmbientLight), With<MainCamera>>, 
     camera_query: Query<&Transform, (With<MainCamera>, Without<Sun>)>,
     chunk_manager: Res<ChunkManager>,
     mut star_query: Query<(&Star, &mut Transform, &MeshMaterial3d<StandardMaterial>), (Without<MainCamera>, Without<Sun>)>,
@@ -32,20 +197,15 @@ pub fn update_daylight_cycle(
 ) {
     cycle.time_of_day = (cycle.time_of_day + cycle.speed * time.delta_secs()) % 1.0;
 
-    let angle = cycle.time_of_day * std::f32::consts::TAU;
-    let orbit_rotation = Quat::from_rotation_x(angle);
-    let tilt_rotation = Quat::from_rotation_z(cycle.inclination);
+    let final_rotation = cycle.sun_rotation();
+    let sky_rotation = cycle.sky_rotation();
+    let sun_dir = cycle.sun_direction();
     
-    let final_rotation = tilt_rotation * orbit_rotation;
-    let sun_dir = final_rotation.mul_vec3(Vec3::NEG_Z);
-    let up_dot = sun_dir.dot(Vec3::NEG_Y);
-    
-    let daylight = ((up_dot + 0.1) * 5.0).clamp(0.0, 1.0);
+    let daylight = cycle.daylight();
         
     if let Ok((mut transform, mut light)) = sun_query.single_mut() {
         transform.rotation = final_rotation; 
         
-        let sunset_horizon_factor = (1.0 - (up_dot.abs() / 0.34)).clamp(0.0, 1.0);
         //let star_horizon_factor = (1.0 - (up_dot.abs() / 0.24)).clamp(0.0, 1.0);
         light.illuminance = daylight * MAX_ILLUMANENCE;
 
@@ -54,12 +214,7 @@ pub fn update_daylight_cycle(
             let max_ambient = 180.0;
             ambient.brightness = min_ambient + (max_ambient - min_ambient) * daylight; 
 
-            let night_fog = Vec3::new(0.1, 0.1, 0.2);
-            let day_fog = Vec3::new(0.35, 0.48, 0.66);
-            let sunset_fog = Vec3::new(0.90, 0.45, 0.2);
-            
-            let base_fog = night_fog.lerp(day_fog, daylight);
-            let current_fog = base_fog.lerp(sunset_fog, sunset_horizon_factor);
+            let current_fog = crate::sky::horizon_color(sun_dir);
             
             let final_color = Color::srgb(current_fog.x, current_fog.y, current_fog.z);
             fog.color = final_color;
@@ -75,7 +230,7 @@ pub fn update_daylight_cycle(
     }
 
     if let Ok(camera_transform) = camera_query.single() {
-        let global_star_visibility = ((-up_dot - 0.2) / 0.6).clamp(0.0, 1.0);
+        let global_star_visibility = cycle.star_visibility();
         
         let star_distance = CHUNK_SIZE * chunk_manager.render_distance as f32;
         let scale_factor = 0.2 * chunk_manager.render_distance as f32;
@@ -83,8 +238,7 @@ pub fn update_daylight_cycle(
 
         if global_star_visibility > 0.0 {
             for (star, mut star_transform, material_handle) in star_query.iter_mut() {
-                let star_rotation = final_rotation * Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
-                let current_star_dir = star_rotation.mul_vec3(-star.offset).normalize(); 
+                let current_star_dir = sky_rotation.mul_vec3(star.offset).normalize();
                 
                 let star_elevation = current_star_dir.dot(Vec3::Y);
                 let horizon_fade = ((star_elevation + 0.05) / 0.35).clamp(0.0, 1.0);
@@ -123,6 +277,17 @@ pub fn update_daylight_cycle(
     }
 }
 
+/// Trigger `Dawn` or `Dusk` when the sun crosses the horizon. Nothing fires for the time of day
+/// the world starts at, and a jump in time fires only the event for where it lands
+pub fn announce_dawn_and_dusk(mut commands: Commands, cycle: Res<DayNightCycle>, mut was_night: Local<Option<bool>>) {
+    let night = cycle.is_night();
+    match was_night.replace(night) {
+        Some(false) if night => commands.trigger(Dusk),
+        Some(true) if !night => commands.trigger(Dawn),
+        _ => {}
+    }
+}
+
 pub fn spawn_stars(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -167,4 +332,26 @@ pub fn spawn_stars(
             },
         ));
     }
-}
\ No newline at end of file
+}
+/// Drive camera exposure and bloom from the day cycle and render settings
+pub fn update_exposure(
+    time: Res<Time>,
+    cycle: Res<DayNightCycle>,
+    render_settings: Res<RenderSettings>,
+    mut camera_query: Query<(&mut Exposure, &mut Bloom), With<MainCamera>>,
+) {
+    let Ok((mut exposure, mut bloom)) = camera_query.single_mut() else { return };
+
+    let target_ev = if render_settings.auto_exposure {
+        render_settings.night_ev100.lerp(render_settings.day_ev100, cycle.daylight())
+    } else {
+        render_settings.day_ev100
+    };
+
+    let max_step = EXPOSURE_ADAPTATION_RATE * time.delta_secs();
+    exposure.ev100 += (target_ev - exposure.ev100).clamp(-max_step, max_step);
+
+    if bloom.intensity != render_settings.bloom_intensity {
+        bloom.intensity = render_settings.bloom_intensity;
+    }
+}
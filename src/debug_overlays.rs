@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::consts::{CHUNK_SIZE, MAP_HEIGHT_SCALE};
+use crate::controls::{Aircraft, FlightForces};
+use crate::world_generation::{Chunk, ChunkDespawned, ChunkManager, ChunkMeshReady, ChunkSpawned, ChunkTask, WorldGenerator};
+
+/// Wind blows at only a few units per second; stretch its arrow so it reads next to the forces
+const WIND_ARROW_SCALE: f32 = 40.0;
+const TERRAIN_NORMAL_LENGTH: f32 = 300.0;
+
+const VELOCITY_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
+const THRUST_COLOR: Color = Color::srgb(1.0, 0.6, 0.0);
+const LIFT_COLOR: Color = Color::srgb(0.2, 1.0, 0.2);
+const GRAVITY_COLOR: Color = Color::srgb(0.3, 0.5, 1.0);
+const DRAG_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const WIND_COLOR: Color = Color::srgb(0.2, 1.0, 1.0);
+const TERRAIN_NORMAL_COLOR: Color = Color::srgb(1.0, 0.2, 1.0);
+
+/// Chunk outlines are drawn at the same altitude as the LOD rings
+const CHUNK_OUTLINE_HEIGHT: f32 = MAP_HEIGHT_SCALE * 10.0 / 3.0;
+/// Outlines are inset so neighbouring chunks keep their own color
+const CHUNK_OUTLINE_INSET: f32 = 0.94;
+const QUEUED_COLOR: Color = Color::srgb(1.0, 1.0, 0.2);
+const GENERATING_COLOR: Color = Color::srgb(1.0, 0.4, 0.0);
+const DESPAWNED_COLOR: Color = Color::srgb(1.0, 0.15, 0.15);
+/// Seconds a chunk lifecycle event stays flashed around its chunk
+const CHUNK_EVENT_FLASH_SECS: f32 = 1.5;
+/// Outlines for lifecycle events sit just outside the state outline
+const CHUNK_EVENT_INSET: f32 = 0.99;
+/// Ready chunks go from the finest LOD level to the coarsest
+const LOD_COLORS: [Color; 5] = [
+    Color::srgb(0.2, 1.0, 0.3),
+    Color::srgb(0.2, 0.9, 0.8),
+    Color::srgb(0.2, 0.6, 1.0),
+    Color::srgb(0.5, 0.3, 1.0),
+    Color::srgb(0.8, 0.3, 0.8),
+];
+
+/// Gizmo overlays for eyeballing the flight model and terrain streaming
+#[derive(Resource)]
+pub struct DebugOverlays {
+    /// One second of travel at the current velocity
+    pub velocity: bool,
+    /// Thrust, lift, gravity and drag from the last physics step
+    pub forces: bool,
+    pub wind: bool,
+    pub terrain_normal: bool,
+    /// Arrow length per unit of acceleration
+    pub force_scale: f32,
+    /// Outline every chunk, colored by LOD level and generation state
+    pub chunk_inspector: bool,
+}
+
+impl Default for DebugOverlays {
+    fn default() -> Self {
+        Self {
+            velocity: false,
+            forces: false,
+            wind: false,
+            terrain_normal: false,
+            force_scale: 5.0,
+            chunk_inspector: false,
+        }
+    }
+}
+
+pub fn draw_physics_overlays(
+    mut gizmos: Gizmos,
+    overlays: Res<DebugOverlays>,
+    forces: Res<FlightForces>,
+    world_gen: Res<WorldGenerator>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) {
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    let position = transform.translation;
+
+    if overlays.velocity {
+        gizmos.arrow(position, position + aircraft.velocity, VELOCITY_COLOR);
+    }
+
+    if overlays.forces && !aircraft.crashed {
+        for (force, color) in [
+            (forces.thrust, THRUST_COLOR),
+            (forces.lift, LIFT_COLOR),
+            (forces.gravity, GRAVITY_COLOR),
+            (forces.drag, DRAG_COLOR),
+        ] {
+            if force.length_squared() > 0.0 {
+                gizmos.arrow(position, position + force * overlays.force_scale, color);
+            }
+        }
+    }
+
+    if overlays.wind {
+        gizmos.arrow(position, position + forces.wind * WIND_ARROW_SCALE, WIND_COLOR);
+    }
+
+    if overlays.terrain_normal {
+        let ground = world_gen.get_terrain_height(&[position.x, position.y, position.z]);
+        // Water is flat, so over the sea the normal is straight up at sea level
+        let (height, normal) = if ground > 0.0 {
+            (ground, world_gen.get_terrain_normal(position.x, position.z))
+        } else {
+            (0.0, Vec3::Y)
+        };
+        let foot = Vec3::new(position.x, height, position.z);
+        gizmos.line(position, foot, TERRAIN_NORMAL_COLOR.with_alpha(0.3));
+        gizmos.arrow(foot, foot + normal * TERRAIN_NORMAL_LENGTH, TERRAIN_NORMAL_COLOR);
+        gizmos.circle(
+            Isometry3d::new(foot, Quat::from_rotation_arc(Vec3::Z, normal)),
+            TERRAIN_NORMAL_LENGTH * 0.25,
+            TERRAIN_NORMAL_COLOR,
+        );
+    }
+}
+
+/// Where chunks are in the streaming pipeline, counted every frame for the debugger
+#[derive(Resource, Default)]
+pub struct ChunkPipelineStats {
+    /// Waiting in the spawn queue, no entity yet
+    pub queued_spawns: usize,
+    /// Spawned but waiting for a LOD rebuild
+    pub queued_lod: usize,
+    /// Mesh being built on the compute pool
+    pub generating: usize,
+    pub ready: usize,
+}
+
+/// Chunk lifecycle events from the last `CHUNK_EVENT_FLASH_SECS`, flashed by the chunk inspector
+#[derive(Resource, Default)]
+pub struct RecentChunkEvents {
+    events: VecDeque<(i32, i32, Color, f32)>,
+}
+
+impl RecentChunkEvents {
+    fn record(&mut self, x: i32, z: i32, color: Color, now: f32) {
+        while self.events.front().is_some_and(|event| now - event.3 > CHUNK_EVENT_FLASH_SECS) {
+            self.events.pop_front();
+        }
+        self.events.push_back((x, z, color, now));
+    }
+}
+
+pub fn record_chunk_spawned(trigger: On<ChunkSpawned>, time: Res<Time>, overlays: Res<DebugOverlays>, mut recent: ResMut<RecentChunkEvents>) {
+    if overlays.chunk_inspector {
+        recent.record(trigger.event().x, trigger.event().z, GENERATING_COLOR, time.elapsed_secs());
+    }
+}
+
+pub fn record_chunk_mesh_ready(
+    trigger: On<ChunkMeshReady>,
+    time: Res<Time>,
+    overlays: Res<DebugOverlays>,
+    chunk_manager: Res<ChunkManager>,
+    mut recent: ResMut<RecentChunkEvents>,
+) {
+    if overlays.chunk_inspector {
+        let event = trigger.event();
+        let color = LOD_COLORS[lod_level_index(&chunk_manager, event.lod).min(LOD_COLORS.len() - 1)];
+        recent.record(event.x, event.z, color, time.elapsed_secs());
+    }
+}
+
+pub fn record_chunk_despawned(trigger: On<ChunkDespawned>, time: Res<Time>, overlays: Res<DebugOverlays>, mut recent: ResMut<RecentChunkEvents>) {
+    if overlays.chunk_inspector {
+        recent.record(trigger.event().x, trigger.event().z, DESPAWNED_COLOR, time.elapsed_secs());
+    }
+}
+
+/// Index into `lod_levels` that a chunk's subdivision count came from
+fn lod_level_index(chunk_manager: &ChunkManager, subdivisions: u32) -> usize {
+    chunk_manager
+        .lod_levels
+        .iter()
+        .position(|(_, level)| level * chunk_manager.lod_quality_multiplier == subdivisions)
+        .unwrap_or(chunk_manager.lod_levels.len() - 1)
+}
+
+fn draw_chunk_outline(gizmos: &mut Gizmos, x: i32, z: i32, color: Color, inset: f32) {
+    gizmos.rect(
+        Isometry3d::new(
+            Vec3::new(x as f32 * CHUNK_SIZE, CHUNK_OUTLINE_HEIGHT, z as f32 * CHUNK_SIZE),
+            Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        ),
+        Vec2::splat(CHUNK_SIZE * inset),
+        color,
+    );
+}
+
+pub fn inspect_chunks(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    overlays: Res<DebugOverlays>,
+    recent: Res<RecentChunkEvents>,
+    chunk_manager: Res<ChunkManager>,
+    mut stats: ResMut<ChunkPipelineStats>,
+    chunks: Query<(Entity, &Chunk, &Visibility, Has<ChunkTask>)>,
+) {
+    *stats = ChunkPipelineStats {
+        queued_spawns: chunk_manager.to_spawn.len(),
+        ..default()
+    };
+    let lod_queue: std::collections::HashSet<Entity> = chunk_manager.lod_to_update.iter().copied().collect();
+
+    for (entity, chunk, visibility, generating) in &chunks {
+        // Freshly spawned chunks stay hidden until their first mesh lands
+        let color = if generating || *visibility == Visibility::Hidden {
+            stats.generating += 1;
+            GENERATING_COLOR
+        } else if lod_queue.contains(&entity) {
+            stats.queued_lod += 1;
+            QUEUED_COLOR
+        } else {
+            stats.ready += 1;
+            LOD_COLORS[lod_level_index(&chunk_manager, chunk.current_lod).min(LOD_COLORS.len() - 1)]
+        };
+        if overlays.chunk_inspector {
+            draw_chunk_outline(&mut gizmos, chunk.x, chunk.z, color, CHUNK_OUTLINE_INSET);
+        }
+    }
+
+    if overlays.chunk_inspector {
+        for &(x, z) in &chunk_manager.to_spawn {
+            draw_chunk_outline(&mut gizmos, x, z, QUEUED_COLOR.with_alpha(0.4), CHUNK_OUTLINE_INSET);
+        }
+        // Spawns, finished meshes and despawns flash and fade around their chunks
+        let now = time.elapsed_secs();
+        for &(x, z, color, at) in &recent.events {
+            let fade = 1.0 - (now - at) / CHUNK_EVENT_FLASH_SECS;
+            if fade > 0.0 {
+                draw_chunk_outline(&mut gizmos, x, z, color.with_alpha(fade), CHUNK_EVENT_INSET);
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::controls::{Aircraft, ControlMode, Ditched, Wind};
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::hud::HudPalette;
+use crate::network::RespawnAircraft;
+
+/// How long the aircraft stays afloat before it starts going under
+const FLOAT_SECS: f32 = 60.0;
+/// Time from the first water over the wings to fully sunk
+const SINK_SECS: f32 = 15.0;
+/// Depth the waterline settles at while afloat, and how deep the aircraft is when it's gone
+const FLOAT_DRAFT: f32 = 1.5;
+const SUNK_DEPTH: f32 = 12.0;
+/// Spring pulling the hull to its waterline, and the water's damping on the bob
+const BUOYANCY_STIFFNESS: f32 = 6.0;
+const BUOYANCY_DAMPING: f32 = 2.5;
+/// Fraction of the wind speed the floating aircraft drifts at
+const WIND_DRIFT: f32 = 0.6;
+/// Landing speed bleeds off this fast once the hull is in the water
+const WATER_DRAG: f32 = 1.5;
+/// Open-sea swell, shared by anything afloat
+const WAVE_HEIGHT: f32 = 1.2;
+const WAVE_PERIOD: f32 = 4.0;
+const WAVE_ROCK: f32 = 0.06;
+
+/// Height of the water surface above sea level at a moment in the swell
+pub fn swell(t: f32) -> f32 {
+    (t * std::f32::consts::TAU / WAVE_PERIOD).sin() * WAVE_HEIGHT
+}
+
+/// A ditched aircraft floating on the water
+pub struct Float {
+    /// Seconds until it starts to sink
+    pub remaining: f32,
+    /// Seconds it has been going under
+    pub sinking: f32,
+    vertical_velocity: f32,
+    drift: Vec3,
+    heading: Quat,
+}
+
+#[derive(Resource, Default)]
+pub struct Ditching {
+    pub float: Option<Float>,
+}
+
+pub fn start_floating(trigger: On<Ditched>, mut ditching: ResMut<Ditching>, aircraft_query: Query<&Transform, With<Aircraft>>) {
+    let forward = aircraft_query.single().map(|transform| transform.forward().as_vec3()).unwrap_or(Vec3::NEG_Z);
+    let yaw = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+    ditching.float = Some(Float {
+        remaining: FLOAT_SECS,
+        sinking: 0.0,
+        vertical_velocity: trigger.velocity.y,
+        drift: Vec3::new(trigger.velocity.x, 0.0, trigger.velocity.z),
+        heading: Quat::from_rotation_arc(Vec3::NEG_Z, yaw),
+    });
+    info!("Ditched at {:.0}, {:.0}", trigger.position.x, trigger.position.z);
+}
+
+pub fn end_float_on_respawn(_trigger: On<RespawnAircraft>, mut ditching: ResMut<Ditching>, mut control_mode: ResMut<ControlMode>) {
+    if ditching.float.take().is_some() {
+        control_mode.physics_paused = false;
+    }
+}
+
+/// Bob on the swell, drift with the wind and eventually go under; R calls in a rescue
+pub fn update_ditching(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut ditching: ResMut<Ditching>,
+    control_mode: Res<ControlMode>,
+    mut aircraft_query: Query<(&mut Transform, &mut Aircraft)>,
+    mut commands: Commands,
+) {
+    let Some(float) = &mut ditching.float else { return };
+    let Ok((mut transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+
+    // Anything else that moved the aircraft off the water, like a pause toggle or a crash, ends the float
+    if !control_mode.physics_paused || aircraft.crashed {
+        ditching.float = None;
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        commands.trigger(RespawnAircraft);
+        return;
+    }
+
+    let dt = time.delta_secs();
+    let t = time.elapsed_secs();
+    if float.remaining > 0.0 {
+        float.remaining = (float.remaining - dt).max(0.0);
+    } else {
+        float.sinking += dt;
+    }
+
+    // Buoyancy: a damped spring toward a waterline that drops as the hull floods
+    let flooded = (float.sinking / SINK_SECS).min(1.0);
+    let waterline = swell(t) - FLOAT_DRAFT - flooded * SUNK_DEPTH;
+    let buoyancy = (waterline - transform.translation.y) * BUOYANCY_STIFFNESS - float.vertical_velocity * BUOYANCY_DAMPING;
+    float.vertical_velocity += buoyancy * dt;
+
+    let wind_drift = wind.wind_direction * wind.wind_speed * WIND_DRIFT;
+    float.drift = float.drift.lerp(wind_drift, (WATER_DRAG * dt).min(1.0));
+    transform.translation += Vec3::new(float.drift.x, float.vertical_velocity, float.drift.z) * dt;
+
+    let rock = Quat::from_euler(
+        EulerRot::XYZ,
+        (t * 1.3).sin() * WAVE_ROCK + flooded * 0.3,
+        0.0,
+        (t * 0.9).cos() * WAVE_ROCK,
+    );
+    transform.rotation = float.heading * rock;
+    aircraft.velocity = Vec3::new(float.drift.x, float.vertical_velocity, float.drift.z);
+
+    if flooded >= 1.0 {
+        aircraft.crashed = true;
+        aircraft.velocity = Vec3::ZERO;
+        commands.trigger(SpawnEffect {
+            kind: EffectKind::Splash,
+            position: Vec3::new(transform.translation.x, 0.0, transform.translation.z),
+            velocity: Vec3::ZERO,
+            intensity: 1.0,
+        });
+        ditching.float = None;
+        info!("Ditched aircraft sank");
+    }
+}
+
+/// Float status and the rescue prompt
+pub fn ditching_hud(mut contexts: EguiContexts, ditching: Res<Ditching>, palette: Res<HudPalette>) -> Result<(), BevyError> {
+    let Some(float) = &ditching.float else { return Ok(()) };
+
+    egui::Window::new("Ditched")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .fixed_size([300.0, 100.0])
+        .frame(Frame::default().fill(if float.remaining > 0.0 { palette.window_fill } else { palette.warning_fill }))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.add_space(20.0);
+                ui.label(egui::RichText::new("🌊 DITCHED 🌊").size(24.0).strong());
+                ui.add_space(10.0);
+                if float.remaining > 0.0 {
+                    ui.label(egui::RichText::new(format!("Afloat, taking on water in {:.0}s", float.remaining)).size(14.0));
+                } else {
+                    ui.label(egui::RichText::new("Sinking!").size(14.0));
+                }
+                ui.label(egui::RichText::new("Press R to be rescued").size(14.0));
+            });
+        });
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+
+/// Hard cap so a burst of crashes can't flood the scene
+const MAX_PARTICLES: usize = 2000;
+
+const EXPLOSION_FIREBALL_COUNT: usize = 24;
+const EXPLOSION_DEBRIS_COUNT: usize = 40;
+const EXPLOSION_SMOKE_COUNT: usize = 30;
+const SPLASH_COUNT: usize = 60;
+const POP_COUNT: usize = 20;
+const SCRAPE_COUNT: usize = 16;
+
+/// Dust puffs per second at full intensity
+const DUST_RATE: f32 = 40.0;
+/// Height above the ground below which low passes kick up dust
+pub const DUST_MAX_HEIGHT: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectKind {
+    /// Fireball, debris and smoke from a terrain crash
+    Explosion,
+    /// Water thrown up by an ocean impact
+    Splash,
+    /// Dust kicked up by a low pass over the desert
+    Dust,
+    /// Shreds of a target balloon shot to pieces
+    Pop,
+    /// Dirt thrown up when the aircraft skids off the ground
+    Scrape,
+}
+
+/// Request a particle effect at a world position
+#[derive(Event)]
+pub struct SpawnEffect {
+    pub kind: EffectKind,
+    pub position: Vec3,
+    /// Velocity of the source, inherited in part by the particles
+    pub velocity: Vec3,
+    /// 0..1, scales continuous effects such as dust
+    pub intensity: f32,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+    gravity: f32,
+    drag: f32,
+    start_scale: f32,
+    end_scale: f32,
+}
+
+#[derive(Resource)]
+pub struct EffectAssets {
+    sphere: Handle<Mesh>,
+    cube: Handle<Mesh>,
+    fire: Handle<StandardMaterial>,
+    debris: Handle<StandardMaterial>,
+    smoke: Handle<StandardMaterial>,
+    water: Handle<StandardMaterial>,
+    dust: Handle<StandardMaterial>,
+    shreds: Handle<StandardMaterial>,
+}
+
+pub fn setup_effects(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let translucent = |color: Color| StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 1.0,
+        ..default()
+    };
+
+    commands.insert_resource(EffectAssets {
+        sphere: meshes.add(Sphere::new(1.0).mesh().ico(1).unwrap()),
+        cube: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        fire: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.5, 0.1),
+            emissive: LinearRgba::rgb(40.0, 12.0, 2.0),
+            unlit: true,
+            ..default()
+        }),
+        debris: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.12, 0.11, 0.1),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        smoke: materials.add(translucent(Color::srgba(0.15, 0.15, 0.15, 0.6))),
+        water: materials.add(translucent(Color::srgba(0.85, 0.92, 1.0, 0.7))),
+        dust: materials.add(translucent(Color::srgba(0.82, 0.7, 0.5, 0.35))),
+        shreds: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.15, 0.1),
+            perceptual_roughness: 0.6,
+            ..default()
+        }),
+    });
+}
+
+fn random_unit() -> Vec3 {
+    let phi = rand::random::<f32>() * std::f32::consts::TAU;
+    let cos_theta = rand::random::<f32>() * 2.0 - 1.0;
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    Vec3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin())
+}
+
+fn random_range(min: f32, max: f32) -> f32 {
+    min + rand::random::<f32>() * (max - min)
+}
+
+/// Spawn the particles for a requested effect
+pub fn spawn_effect(
+    trigger: On<SpawnEffect>,
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<EffectAssets>,
+    particles: Query<(), With<Particle>>,
+    mut dust_accumulator: Local<f32>,
+) {
+    let event = &*trigger;
+    let budget = MAX_PARTICLES.saturating_sub(particles.iter().count());
+    if budget == 0 {
+        return;
+    }
+
+    let mut spawned = 0;
+    let mut spawn = |commands: &mut Commands, mesh: &Handle<Mesh>, material: &Handle<StandardMaterial>, offset: Vec3, particle: Particle| {
+        if spawned >= budget {
+            return;
+        }
+        spawned += 1;
+        let rotation = Quat::from_euler(
+            EulerRot::XYZ,
+            rand::random::<f32>() * std::f32::consts::TAU,
+            rand::random::<f32>() * std::f32::consts::TAU,
+            0.0,
+        );
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(event.position + offset)
+                .with_rotation(rotation)
+                .with_scale(Vec3::splat(particle.start_scale)),
+            particle,
+            NotShadowCaster,
+        ));
+    };
+
+    match event.kind {
+        EffectKind::Explosion => {
+            for _ in 0..EXPLOSION_FIREBALL_COUNT {
+                let dir = random_unit();
+                spawn(&mut commands, &assets.sphere, &assets.fire, dir * 5.0, Particle {
+                    velocity: dir * random_range(20.0, 60.0) + Vec3::Y * 20.0,
+                    age: 0.0,
+                    lifetime: random_range(0.6, 1.4),
+                    gravity: -10.0,
+                    drag: 2.5,
+                    start_scale: random_range(8.0, 14.0),
+                    end_scale: 1.0,
+                });
+            }
+            for _ in 0..EXPLOSION_DEBRIS_COUNT {
+                let dir = (random_unit() + Vec3::Y).normalize_or_zero();
+                spawn(&mut commands, &assets.cube, &assets.debris, Vec3::ZERO, Particle {
+                    velocity: dir * random_range(30.0, 120.0) + event.velocity * 0.3,
+                    age: 0.0,
+                    lifetime: random_range(2.0, 4.0),
+                    gravity: 60.0,
+                    drag: 0.2,
+                    start_scale: random_range(1.0, 3.5),
+                    end_scale: 0.5,
+                });
+            }
+            for _ in 0..EXPLOSION_SMOKE_COUNT {
+                let offset = random_unit() * 10.0;
+                spawn(&mut commands, &assets.sphere, &assets.smoke, offset, Particle {
+                    velocity: Vec3::new(offset.x, random_range(8.0, 25.0), offset.z),
+                    age: 0.0,
+                    lifetime: random_range(5.0, 9.0),
+                    gravity: -2.0,
+                    drag: 0.6,
+                    start_scale: random_range(6.0, 10.0),
+                    end_scale: random_range(30.0, 45.0),
+                });
+            }
+        }
+        EffectKind::Splash => {
+            let horizontal = Vec3::new(event.velocity.x, 0.0, event.velocity.z);
+            for _ in 0..SPLASH_COUNT {
+                let spread = Vec3::new(random_range(-1.0, 1.0), 0.0, random_range(-1.0, 1.0));
+                spawn(&mut commands, &assets.sphere, &assets.water, spread * 8.0, Particle {
+                    velocity: spread * 25.0 + Vec3::Y * random_range(40.0, 110.0) + horizontal * 0.2,
+                    age: 0.0,
+                    lifetime: random_range(1.5, 3.0),
+                    gravity: 60.0,
+                    drag: 0.5,
+                    start_scale: random_range(2.0, 5.0),
+                    end_scale: random_range(6.0, 10.0),
+                });
+            }
+        }
+        EffectKind::Pop => {
+            for _ in 0..POP_COUNT {
+                let dir = random_unit();
+                spawn(&mut commands, &assets.cube, &assets.shreds, dir * 10.0, Particle {
+                    velocity: dir * random_range(40.0, 90.0) + event.velocity,
+                    age: 0.0,
+                    lifetime: random_range(1.5, 3.0),
+                    gravity: 30.0,
+                    drag: 1.5,
+                    start_scale: random_range(2.0, 5.0),
+                    end_scale: 1.0,
+                });
+            }
+        }
+        EffectKind::Scrape => {
+            let horizontal = Vec3::new(event.velocity.x, 0.0, event.velocity.z);
+            for _ in 0..SCRAPE_COUNT {
+                let spread = Vec3::new(random_range(-1.0, 1.0), 0.0, random_range(-1.0, 1.0));
+                spawn(&mut commands, &assets.sphere, &assets.dust, spread * 6.0, Particle {
+                    velocity: spread * 20.0 + Vec3::Y * random_range(10.0, 30.0) + horizontal * 0.3,
+                    age: 0.0,
+                    lifetime: random_range(1.5, 3.0),
+                    gravity: 5.0,
+                    drag: 1.0,
+                    start_scale: random_range(3.0, 6.0),
+                    end_scale: random_range(12.0, 20.0),
+                });
+            }
+        }
+        EffectKind::Dust => {
+            *dust_accumulator += DUST_RATE * event.intensity.clamp(0.0, 1.0) * time.delta_secs();
+            while *dust_accumulator >= 1.0 {
+                *dust_accumulator -= 1.0;
+                let spread = Vec3::new(random_range(-1.0, 1.0), 0.0, random_range(-1.0, 1.0));
+                spawn(&mut commands, &assets.sphere, &assets.dust, spread * 15.0, Particle {
+                    velocity: spread * 12.0 + Vec3::Y * random_range(4.0, 12.0) + event.velocity * 0.1,
+                    age: 0.0,
+                    lifetime: random_range(2.0, 4.0),
+                    gravity: -1.0,
+                    drag: 0.8,
+                    start_scale: random_range(4.0, 8.0),
+                    end_scale: random_range(20.0, 35.0),
+                });
+            }
+        }
+    }
+}
+
+/// Integrate, scale and expire live particles
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut particle, mut transform) in particles.iter_mut() {
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let drag = (1.0 - particle.drag * dt).max(0.0);
+        particle.velocity *= drag;
+        particle.velocity.y -= particle.gravity * dt;
+        transform.translation += particle.velocity * dt;
+
+        // Grow or shrink over the lifetime, then collapse away in the last fifth
+        let t = particle.age / particle.lifetime;
+        let fade_out = ((1.0 - t) / 0.2).min(1.0);
+        transform.scale = Vec3::splat(particle.start_scale.lerp(particle.end_scale, t) * fade_out);
+    }
+}
@@ -0,0 +1,315 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+};
+
+use crate::controls::{Aircraft, ControlMode};
+use crate::loadout::Loadout;
+use crate::network::PlaneType;
+
+const MAGNETO_KEY: KeyCode = KeyCode::KeyI;
+const STARTER_KEY: KeyCode = KeyCode::KeyK;
+
+const IDLE_RPM: f32 = 700.0;
+const MAX_RPM: f32 = 2700.0;
+/// What the starter motor turns the engine over at
+const CRANKING_RPM: f32 = 250.0;
+/// Seconds of cranking with the magnetos on before the engine catches
+const STARTER_CATCH_SECS: f32 = 1.5;
+/// Time constant of the RPM following the throttle, in seconds
+const SPOOL_UP_TIME: f32 = 1.2;
+/// An unpowered engine winds down more slowly than it spools up
+const SPOOL_DOWN_TIME: f32 = 2.5;
+/// A dead engine's propeller windmills at this many RPM per unit of airspeed
+const WINDMILL_RPM_PER_SPEED: f32 = 1.2;
+/// Windmilling this fast with the magnetos on restarts the engine without the starter
+const AIRSTART_RPM: f32 = 500.0;
+/// Seconds of inverted flight before the fuel feed starves the engine
+const FUEL_STARVATION_SECS: f32 = 3.0;
+
+const ENGINE_CYLINDERS: f32 = 6.0;
+const ENGINE_VOLUME: f32 = 0.08;
+const ENGINE_SAMPLE_RATE: u32 = 44_100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineState {
+    Off,
+    /// Starter engaged, waiting for the engine to catch
+    Starting,
+    Running,
+    /// Stopped by itself in flight; restarts like a cold engine
+    FlamedOut,
+}
+
+impl EngineState {
+    pub fn label(self) -> &'static str {
+        match self {
+            EngineState::Off => "OFF",
+            EngineState::Starting => "START",
+            EngineState::Running => "RUN",
+            EngineState::FlamedOut => "FLAMEOUT",
+        }
+    }
+}
+
+/// Shared between the game and the audio thread: firing frequency and volume as f32 bits
+#[derive(Default)]
+struct EngineSoundState {
+    frequency: AtomicU32,
+    volume: AtomicU32,
+}
+
+impl EngineSoundState {
+    fn set(&self, frequency: f32, volume: f32) {
+        self.frequency.store(frequency.to_bits(), Ordering::Relaxed);
+        self.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Fade out but keep the last pitch so the drone doesn't click
+    fn silence(&self) {
+        self.volume.store(0.0f32.to_bits(), Ordering::Relaxed);
+    }
+
+    fn load(&self) -> (f32, f32) {
+        (
+            f32::from_bits(self.frequency.load(Ordering::Relaxed)),
+            f32::from_bits(self.volume.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Engine of the local aircraft. Thrust and sound follow `rpm`, which lags the throttle
+#[derive(Resource)]
+pub struct Engine {
+    pub state: EngineState,
+    pub magneto: bool,
+    pub rpm: f32,
+    /// Spawn with the engine stopped and start it by hand
+    pub cold_start: bool,
+    pub sound_enabled: bool,
+    starter_time: f32,
+    inverted_time: f32,
+    sound: Arc<EngineSoundState>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            state: EngineState::Running,
+            magneto: true,
+            rpm: IDLE_RPM,
+            cold_start: false,
+            sound_enabled: true,
+            starter_time: 0.0,
+            inverted_time: 0.0,
+            sound: Arc::new(EngineSoundState::default()),
+        }
+    }
+}
+
+impl Engine {
+    /// Put the engine back the way a fresh aircraft starts: running, or stopped for a cold start
+    fn reset(&mut self, aircraft: &Aircraft) {
+        self.starter_time = 0.0;
+        self.inverted_time = 0.0;
+        if self.cold_start {
+            self.state = EngineState::Off;
+            self.magneto = false;
+            self.rpm = windmill_rpm(aircraft);
+        } else {
+            self.state = EngineState::Running;
+            self.magneto = true;
+            self.rpm = running_rpm(aircraft);
+        }
+    }
+
+    /// Fraction of full-power fuel flow the engine is drawing
+    pub fn fuel_flow(&self) -> f32 {
+        if self.state == EngineState::Running { self.rpm / MAX_RPM } else { 0.0 }
+    }
+
+    /// Throttle setting the engine is actually delivering at its current RPM
+    fn power(&self, aircraft: &Aircraft) -> f32 {
+        if self.state != EngineState::Running {
+            return 0.0;
+        }
+        ((self.rpm - IDLE_RPM) / (MAX_RPM - IDLE_RPM)).clamp(0.0, 1.0) * aircraft.max_throttle
+    }
+}
+
+/// Steady RPM for the current throttle
+fn running_rpm(aircraft: &Aircraft) -> f32 {
+    let throttle = if aircraft.max_throttle > 0.0 { aircraft.throttle / aircraft.max_throttle } else { 0.0 };
+    IDLE_RPM + (MAX_RPM - IDLE_RPM) * throttle.clamp(0.0, 1.0)
+}
+
+fn windmill_rpm(aircraft: &Aircraft) -> f32 {
+    aircraft.speed * WINDMILL_RPM_PER_SPEED
+}
+
+/// Endless procedural engine drone, steered through the shared `EngineSoundState`
+#[derive(Asset, TypePath)]
+pub struct EngineSound {
+    state: Arc<EngineSoundState>,
+}
+
+pub struct EngineSoundDecoder {
+    state: Arc<EngineSoundState>,
+    phase: f32,
+    gain: f32,
+}
+
+impl Iterator for EngineSoundDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let (frequency, volume) = self.state.load();
+        self.phase = (self.phase + frequency / ENGINE_SAMPLE_RATE as f32).fract();
+        self.gain += (volume - self.gain) * 0.002;
+
+        // A few falling harmonics give the firing pulses their buzz
+        let angle = self.phase * std::f32::consts::TAU;
+        let wave = angle.sin() + 0.6 * (angle * 2.0).sin() + 0.4 * (angle * 3.0).sin() + 0.25 * (angle * 4.0).sin();
+        Some(wave * 0.5 * self.gain)
+    }
+}
+
+impl Source for EngineSoundDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        ENGINE_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+impl Decodable for EngineSound {
+    type DecoderItem = f32;
+    type Decoder = EngineSoundDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        EngineSoundDecoder {
+            state: self.state.clone(),
+            phase: 0.0,
+            gain: 0.0,
+        }
+    }
+}
+
+pub fn setup_engine_sound(
+    mut commands: Commands,
+    engine: Res<Engine>,
+    mut sounds: ResMut<Assets<EngineSound>>,
+) {
+    let handle = sounds.add(EngineSound { state: engine.sound.clone() });
+    commands.spawn(AudioPlayer::<EngineSound>(handle));
+}
+
+/// Step the engine state machine, spool the RPM and hand the delivered power to the flight model
+pub fn update_engine(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    loadout: Res<Loadout>,
+    mut engine: ResMut<Engine>,
+    mut last_crashed: Local<Option<bool>>,
+    mut aircraft_query: Query<(&Transform, &mut Aircraft)>,
+) {
+    let Ok((transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    // Gliders have no engine to run
+    if aircraft.plane_type == PlaneType::Glider {
+        engine.rpm = 0.0;
+        aircraft.power = 0.0;
+        engine.sound.silence();
+        return;
+    }
+
+    // A fresh or respawned aircraft gets a fresh engine
+    if last_crashed.replace(aircraft.crashed).is_none_or(|was_crashed| was_crashed && !aircraft.crashed) {
+        engine.reset(&aircraft);
+    }
+    if control_mode.physics_paused {
+        engine.sound.silence();
+        return;
+    }
+
+    let dt = time.delta_secs();
+    if keyboard.just_pressed(MAGNETO_KEY) {
+        engine.magneto = !engine.magneto;
+        info!("Magnetos {}", if engine.magneto { "on" } else { "off" });
+    }
+    let cranking = keyboard.pressed(STARTER_KEY);
+
+    match engine.state {
+        EngineState::Off | EngineState::FlamedOut => {
+            if cranking {
+                engine.state = EngineState::Starting;
+                engine.starter_time = 0.0;
+            } else if engine.magneto && engine.rpm >= AIRSTART_RPM && !loadout.out_of_fuel() {
+                engine.state = EngineState::Running;
+                info!("Engine restarted from windmilling");
+            }
+        }
+        EngineState::Starting => {
+            engine.starter_time += dt;
+            if !cranking {
+                engine.state = EngineState::Off;
+            } else if engine.magneto && engine.starter_time >= STARTER_CATCH_SECS && !loadout.out_of_fuel() {
+                engine.state = EngineState::Running;
+                engine.inverted_time = 0.0;
+                info!("Engine started");
+            }
+        }
+        EngineState::Running => {
+            if transform.up().y < 0.0 {
+                engine.inverted_time += dt;
+            } else {
+                engine.inverted_time = (engine.inverted_time - dt).max(0.0);
+            }
+
+            if !engine.magneto {
+                engine.state = EngineState::Off;
+                info!("Engine shut down");
+            } else if loadout.out_of_fuel() {
+                engine.state = EngineState::FlamedOut;
+                info!("Engine flamed out: tanks dry");
+            } else if engine.inverted_time >= FUEL_STARVATION_SECS {
+                engine.state = EngineState::FlamedOut;
+                engine.inverted_time = 0.0;
+                info!("Engine flamed out from fuel starvation");
+            }
+        }
+    }
+
+    let target_rpm = match engine.state {
+        EngineState::Running => running_rpm(&aircraft),
+        EngineState::Starting => CRANKING_RPM.max(windmill_rpm(&aircraft)),
+        EngineState::Off | EngineState::FlamedOut => windmill_rpm(&aircraft),
+    };
+    let time_constant = if target_rpm > engine.rpm { SPOOL_UP_TIME } else { SPOOL_DOWN_TIME };
+    engine.rpm += (target_rpm - engine.rpm) * (1.0 - (-dt / time_constant).exp());
+    aircraft.power = engine.power(&aircraft);
+
+    if engine.sound_enabled && !aircraft.crashed {
+        // Quieter when only windmilling or cranking, without combustion
+        let firing = if engine.state == EngineState::Running { 1.0 } else { 0.3 };
+        let volume = ENGINE_VOLUME * firing * (0.3 + 0.7 * (engine.rpm / MAX_RPM).min(1.0));
+        engine.sound.set(engine.rpm / 60.0 * ENGINE_CYLINDERS / 2.0, volume);
+    } else {
+        engine.sound.silence();
+    }
+}
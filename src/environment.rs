@@ -1,9 +1,16 @@
-use bevy::prelude::*;
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::visibility::VisibilityRange,
+    light::NotShadowCaster,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
 use noise::{NoiseFn, Perlin};
 
-use crate::world_generation::{WorldGenerator, Chunk, ChunkTask, Biome};
+use crate::world_generation::{WorldGenerator, Chunk, ChunkReshaped, ChunkTask, Biome, TerrainPalette};
 use crate::controls::MainCamera;
-use crate::consts::CHUNK_SIZE;
+use crate::asset_preflight::AssetPreflight;
+use crate::consts::{CHUNK_SIZE, MAP_HEIGHT_SCALE};
 
 #[derive(Component)]
 pub struct VegetationSpawner;
@@ -11,8 +18,232 @@ pub struct VegetationSpawner;
 #[derive(Component)]
 pub struct Tree;
 
+/// Broadleaf tree whose leaves follow the seasons
+#[derive(Component)]
+pub struct Deciduous;
+
 const TREE_DENSITY: f32 = 0.5;
 const TREE_SPACING_GRID_SIZE: f32 = 270.0;
+/// Trees checked against the render distance per frame; the rest wait for the next slice
+const TREE_LOD_BATCH: usize = 4000;
+/// Impostors carry on out to this multiple of the tree render distance
+const IMPOSTOR_DISTANCE_FACTOR: f32 = 2.5;
+/// Fraction of each range spent dithering between the model and its impostor
+const TREE_FADE_FRACTION: f32 = 0.12;
+/// Trees are spawned at this scale times their random size
+const TREE_MODEL_SCALE: f32 = 40.0;
+const IMPOSTOR_WIDTH: usize = 64;
+const IMPOSTOR_HEIGHT: usize = 128;
+/// Distance either side of a tree at which the ground is sampled for its slope
+const SLOPE_SAMPLE_DISTANCE: f32 = 15.0;
+/// Steepest ground each species roots on, in degrees
+const OAK_MAX_SLOPE: f32 = 25.0;
+const PINE_MAX_SLOPE: f32 = 38.0;
+const DEAD_TREE_MAX_SLOPE: f32 = 45.0;
+/// Fractions of the summer snow line where oaks give way to pines, and pines to stunted dead trees
+const OAK_TREELINE: f32 = 0.45;
+const PINE_TREELINE: f32 = 0.85;
+/// Stunted trees near the snow line are this much smaller
+const TREELINE_SCALE: f32 = 0.6;
+
+/// Camera-facing quad drawn in place of a tree's model past the tree render distance
+#[derive(Component)]
+pub struct TreeImpostor;
+
+/// An impostor standing in for a species whose model is missing, shown close up as well
+#[derive(Component)]
+pub struct StandInTree;
+
+/// A mesh inside a tree's model, faded out where the impostor takes over
+#[derive(Component)]
+pub struct TreeModelMesh;
+
+/// Outline of each species' sprite, in the model's own units
+#[derive(Clone, Copy)]
+enum TreeShape {
+    Pine,
+    Oak,
+    Dead,
+}
+
+impl TreeShape {
+    fn from_model(model_path: &str) -> Self {
+        match model_path {
+            "oak.glb#Scene0" => TreeShape::Oak,
+            "dead_tree.glb#Scene0" => TreeShape::Dead,
+            _ => TreeShape::Pine,
+        }
+    }
+
+    /// Ground to crown, and full width
+    fn bounds(self) -> (f32, f32, f32) {
+        match self {
+            TreeShape::Pine => (-0.3, 5.75, 2.9),
+            TreeShape::Oak => (-0.3, 3.8, 2.9),
+            TreeShape::Dead => (-0.2, 3.5, 2.2),
+        }
+    }
+
+    /// Leaf and bark colours from the models' materials, linear
+    fn colors(self) -> (Vec3, Vec3) {
+        let bark = Vec3::new(0.162, 0.087, 0.06);
+        match self {
+            TreeShape::Pine => (Vec3::new(0.078, 0.114, 0.036), bark),
+            TreeShape::Oak => (Vec3::new(0.21, 0.36, 0.05), bark),
+            TreeShape::Dead => (bark, bark),
+        }
+    }
+
+    /// Whether a point on the sprite, 0..1 across and up, is leaves, bark or empty
+    fn sample(self, across: f32, up: f32) -> Option<bool> {
+        let from_middle = (across - 0.5).abs();
+        let trunk = from_middle < 0.05 && up < 0.9;
+        let leaves = match self {
+            // Three stacked cones
+            TreeShape::Pine => up > 0.12 && (0..3).any(|tier| {
+                let base = 0.12 + tier as f32 * 0.22;
+                let height = (up - base) / 0.42;
+                (0.0..1.0).contains(&height) && from_middle < 0.5 * (1.0 - height) * (1.0 - tier as f32 * 0.15)
+            }),
+            // A lumpy round crown
+            TreeShape::Oak => {
+                let (x, y) = (across - 0.5, (up - 0.62) * 1.3);
+                let lumps = (across * 19.0).sin() * (up * 23.0).sin() * 0.03;
+                (x * x + y * y).sqrt() < 0.46 + lumps
+            }
+            // Bare forks off the trunk
+            TreeShape::Dead => (0..4).any(|branch| {
+                let start = 0.35 + branch as f32 * 0.15;
+                let side = if branch % 2 == 0 { 1.0 } else { -1.0 };
+                let along = (up - start) / 0.3;
+                (0.0..1.0).contains(&along) && ((across - 0.5) * side - along * 0.35).abs() < 0.03
+            }),
+        };
+        if leaves {
+            Some(true)
+        } else if trunk {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Sprite baked from the outline, lit from above so the crown doesn't look flat
+    fn bake(self) -> Image {
+        let (leaves, bark) = self.colors();
+        let mut data = Vec::with_capacity(IMPOSTOR_WIDTH * IMPOSTOR_HEIGHT * 4);
+        for row in 0..IMPOSTOR_HEIGHT {
+            let up = 1.0 - (row as f32 + 0.5) / IMPOSTOR_HEIGHT as f32;
+            for column in 0..IMPOSTOR_WIDTH {
+                let across = (column as f32 + 0.5) / IMPOSTOR_WIDTH as f32;
+                let pixel = match self.sample(across, up) {
+                    Some(is_leaves) => {
+                        let shade = 0.75 + 0.35 * up + (across * 37.0 + up * 53.0).sin() * 0.08;
+                        let color = if is_leaves { leaves } else { bark } * shade;
+                        let srgb = Color::linear_rgb(color.x, color.y, color.z).to_srgba();
+                        [srgb.red, srgb.green, srgb.blue, 1.0].map(|channel| (channel.clamp(0.0, 1.0) * 255.0) as u8)
+                    }
+                    None => [0, 0, 0, 0],
+                };
+                data.extend(pixel);
+            }
+        }
+        Image::new(
+            Extent3d { width: IMPOSTOR_WIDTH as u32, height: IMPOSTOR_HEIGHT as u32, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+    }
+}
+
+/// Shared impostor quad and one baked sprite material per species
+#[derive(Resource)]
+pub struct TreeImpostors {
+    quad: Handle<Mesh>,
+    pine: Handle<StandardMaterial>,
+    oak: Handle<StandardMaterial>,
+    dead: Handle<StandardMaterial>,
+}
+
+impl TreeImpostors {
+    fn material(&self, shape: TreeShape) -> Handle<StandardMaterial> {
+        match shape {
+            TreeShape::Pine => self.pine.clone(),
+            TreeShape::Oak => self.oak.clone(),
+            TreeShape::Dead => self.dead.clone(),
+        }
+    }
+}
+
+pub fn setup_tree_impostors(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut sprite = |shape: TreeShape| materials.add(StandardMaterial {
+        base_color_texture: Some(images.add(shape.bake())),
+        alpha_mode: AlphaMode::Mask(0.5),
+        perceptual_roughness: 1.0,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    let (pine, oak, dead) = (sprite(TreeShape::Pine), sprite(TreeShape::Oak), sprite(TreeShape::Dead));
+    commands.insert_resource(TreeImpostors {
+        quad: meshes.add(Rectangle::new(1.0, 1.0)),
+        pine,
+        oak,
+        dead,
+    });
+}
+
+/// Full models out to the tree render distance, impostors from there to `IMPOSTOR_DISTANCE_FACTOR` times it,
+/// dithering across each handover
+fn tree_visibility_ranges(tree_render_distance: f32) -> (VisibilityRange, VisibilityRange) {
+    let model_end = tree_render_distance * CHUNK_SIZE;
+    let impostor_end = model_end * IMPOSTOR_DISTANCE_FACTOR;
+    let handover = model_end * (1.0 - TREE_FADE_FRACTION)..model_end;
+    let model = VisibilityRange { start_margin: 0.0..0.0, end_margin: handover.clone(), use_aabb: false };
+    let impostor = VisibilityRange {
+        start_margin: handover,
+        end_margin: impostor_end * (1.0 - TREE_FADE_FRACTION)..impostor_end,
+        use_aabb: false,
+    };
+    (model, impostor)
+}
+
+/// Steepness of the ground around a point, in degrees, from the heightfield either side of it
+fn terrain_slope(world_generator: &WorldGenerator, x: f32, z: f32) -> f32 {
+    let height = |x: f32, z: f32| world_generator.get_terrain_height(&[x, 0.0, z]);
+    let dx = height(x + SLOPE_SAMPLE_DISTANCE, z) - height(x - SLOPE_SAMPLE_DISTANCE, z);
+    let dz = height(x, z + SLOPE_SAMPLE_DISTANCE) - height(x, z - SLOPE_SAMPLE_DISTANCE);
+    (Vec2::new(dx, dz).length() / (2.0 * SLOPE_SAMPLE_DISTANCE)).atan().to_degrees()
+}
+
+/// The species that grows at this height, swapping up the mountain towards the snow line, or `None` above it
+fn species_at_height(model_path: &'static str, terrain_height: f32, snow_line: f32) -> Option<&'static str> {
+    let altitude = terrain_height / snow_line;
+    if altitude >= 1.0 {
+        None
+    } else if altitude >= PINE_TREELINE {
+        Some("dead_tree.glb#Scene0")
+    } else if altitude >= OAK_TREELINE && model_path == "oak.glb#Scene0" {
+        Some("pine.glb#Scene0")
+    } else {
+        Some(model_path)
+    }
+}
+
+fn max_slope(model_path: &str) -> f32 {
+    match model_path {
+        "oak.glb#Scene0" => OAK_MAX_SLOPE,
+        "dead_tree.glb#Scene0" => DEAD_TREE_MAX_SLOPE,
+        _ => PINE_MAX_SLOPE,
+    }
+}
 
 pub fn spawn_vegetation_for_chunk(
     mut commands: Commands,
@@ -20,11 +251,16 @@ pub fn spawn_vegetation_for_chunk(
     world_generator: Res<WorldGenerator>,
     chunk_manager: Res<crate::world_generation::ChunkManager>,
     asset_server: Res<AssetServer>,
+    impostors: Res<TreeImpostors>,
+    palette: Res<TerrainPalette>,
     camera: Query<&Transform, With<MainCamera>>,
+    preflight: Res<AssetPreflight>,
 ) {
+    let snow_line = palette.summer_snow_line * MAP_HEIGHT_SCALE;
+    let (_, impostor_range) = tree_visibility_ranges(chunk_manager.tree_render_distance);
     let tree_noise = Perlin::new(world_generator.seed + 9999);
     let density_noise = Perlin::new(world_generator.seed + 7777);
-    let cam_transform = camera.single().unwrap().translation;
+    let Ok(cam_transform) = camera.single().map(|camera| camera.translation) else { return };
     let cam_x = (cam_transform.x / CHUNK_SIZE).round() as i32;
     let cam_z = (cam_transform.z / CHUNK_SIZE).round() as i32;
     
@@ -33,7 +269,7 @@ pub fn spawn_vegetation_for_chunk(
         let dz = (chunk.z - cam_z) as f32;
         let distance = (dx * dx + dz * dz).sqrt();
         
-        if distance > chunk_manager.tree_render_distance {
+        if distance > chunk_manager.tree_render_distance * IMPOSTOR_DISTANCE_FACTOR {
             continue;
         }
         let chunk_world_pos = chunk_transform.translation;
@@ -93,10 +329,14 @@ pub fn spawn_vegetation_for_chunk(
                     continue;
                 }
                 
-                if let Some(mut model_path) = tree_model {
+                if let Some(model_path) = tree_model {
                     let terrain_height = world_generator.get_terrain_height(&world_pos);
-                    
-                    if terrain_height > 0.0 {
+                    let Some(mut model_path) = species_at_height(model_path, terrain_height, snow_line) else {
+                        continue;
+                    };
+                    let near_treeline = model_path == "dead_tree.glb#Scene0";
+
+                    if terrain_height > 0.0 && terrain_slope(&world_generator, world_x, world_z) <= max_slope(model_path) {
                         let dead_tree_chance = tree_noise.get([
                             world_x as f64 * 0.19,
                             world_z as f64 * 0.19,
@@ -121,14 +361,20 @@ pub fn spawn_vegetation_for_chunk(
                             123.0
                         ]) as f32 * 0.5 + 0.5) * 1.2;
                         
-                        let scale = base_scale * biome_scale_multiplier * if is_dead_tree && 
-                        (biome == Biome::Taiga || biome == Biome::Forest) { 1.3 } else { 1.0 };
+                        let scale = base_scale * biome_scale_multiplier * if near_treeline {
+                            TREELINE_SCALE
+                        } else if is_dead_tree && (biome == Biome::Taiga || biome == Biome::Forest) {
+                            1.3
+                        } else {
+                            1.0
+                        };
                         
                         let local_x = world_x - chunk_world_pos.x;
                         let local_z = world_z - chunk_world_pos.z;
                         
                         tree_spawns.push((
                             model_path,
+                            model_path == "oak.glb#Scene0",
                             Vec3::new(local_x, terrain_height, local_z),
                             rotation_y,
                             scale,
@@ -140,15 +386,34 @@ pub fn spawn_vegetation_for_chunk(
    
         if !tree_spawns.is_empty() {
             commands.entity(chunk_entity).with_children(|parent| {
-                for (model_path, position, rotation_y, scale) in tree_spawns {
-                    parent.spawn((
-                        SceneRoot(asset_server.load(model_path)),
+                for (model_path, deciduous, position, rotation_y, scale) in tree_spawns {
+                    let shape = TreeShape::from_model(model_path);
+                    let (bottom, top, width) = shape.bounds();
+                    let mut tree = parent.spawn((
                         Transform::from_translation(position)
                             .with_rotation(Quat::from_rotation_y(rotation_y))
-                            .with_scale(Vec3::splat(scale * 40.0)),
+                            .with_scale(Vec3::splat(scale * TREE_MODEL_SCALE)),
                         Tree,
                         Visibility::Hidden,
                     ));
+                    let impostor = (
+                        Mesh3d(impostors.quad.clone()),
+                        MeshMaterial3d(impostors.material(shape)),
+                        Transform::from_xyz(0.0, (bottom + top) * 0.5, 0.0).with_scale(Vec3::new(width, top - bottom, 1.0)),
+                        TreeImpostor,
+                        NotShadowCaster,
+                    );
+                    // Without its model the sprite stands in all the way to the camera
+                    if preflight.is_missing(model_path) {
+                        let range = VisibilityRange { start_margin: 0.0..0.0, ..impostor_range.clone() };
+                        tree.with_child((impostor, range, StandInTree));
+                    } else {
+                        tree.insert(SceneRoot(asset_server.load(model_path)));
+                        tree.with_child((impostor, impostor_range.clone()));
+                    }
+                    if deciduous {
+                        tree.insert(Deciduous);
+                    }
                 }
             });
         }
@@ -157,22 +422,89 @@ pub fn spawn_vegetation_for_chunk(
     }
 }
 
+/// Clear the trees off an edited chunk so they're placed again on its new ground once the mesh lands
+pub fn clear_reshaped_vegetation(
+    trigger: On<ChunkReshaped>,
+    mut commands: Commands,
+    children: Query<&Children>,
+    trees: Query<(), With<Tree>>,
+) {
+    let chunk = trigger.event().entity;
+    for child in children.get(chunk).into_iter().flatten().filter(|child| trees.contains(**child)) {
+        commands.entity(*child).despawn();
+    }
+    commands.entity(chunk).try_remove::<VegetationSpawner>();
+}
+
+/// Give each mesh of a freshly loaded tree model the range it hands over to the impostor at,
+/// and move every handover when the tree render distance changes
+pub fn fade_tree_models(
+    mut commands: Commands,
+    chunk_manager: Res<crate::world_generation::ChunkManager>,
+    new_meshes: Query<Entity, (Added<Mesh3d>, Without<TreeImpostor>)>,
+    parents: Query<&ChildOf>,
+    trees: Query<(), With<Tree>>,
+    mut models: Query<&mut VisibilityRange, (With<TreeModelMesh>, Without<TreeImpostor>)>,
+    mut impostors: Query<(&mut VisibilityRange, Has<StandInTree>), With<TreeImpostor>>,
+    mut applied_distance: Local<Option<f32>>,
+) {
+    let (model_range, impostor_range) = tree_visibility_ranges(chunk_manager.tree_render_distance);
+    for entity in &new_meshes {
+        if parents.iter_ancestors(entity).any(|ancestor| trees.contains(ancestor)) {
+            commands.entity(entity).insert((model_range.clone(), TreeModelMesh));
+        }
+    }
+
+    if *applied_distance == Some(chunk_manager.tree_render_distance) {
+        return;
+    }
+    *applied_distance = Some(chunk_manager.tree_render_distance);
+    for mut range in &mut models {
+        *range = model_range.clone();
+    }
+    for (mut range, stand_in) in &mut impostors {
+        *range = impostor_range.clone();
+        if stand_in {
+            range.start_margin = 0.0..0.0;
+        }
+    }
+}
+
+/// Walks the trees in slices of `TREE_LOD_BATCH`, so a full pass is spread over several frames
 pub fn update_tree_lod(
     chunk_manager: Res<crate::world_generation::ChunkManager>,
     camera: Query<&GlobalTransform, With<MainCamera>>,
-    mut trees: Query<(&GlobalTransform, &mut Visibility), With<Tree>>,
+    mut trees: Query<(&GlobalTransform, &mut Visibility, &Children), With<Tree>>,
+    mut impostors: Query<&mut Transform, With<TreeImpostor>>,
+    mut cursor: Local<usize>,
 ) {
     let Ok(cam_transform) = camera.single() else { return };
     let cam_pos = cam_transform.translation();
-    
-    for (tree_transform, mut visibility) in trees.iter_mut() {
-        let tree_pos = tree_transform.translation();
-        let distance = cam_pos.distance(tree_pos);
-        
-        if distance > chunk_manager.tree_render_distance * CHUNK_SIZE {
-            *visibility = Visibility::Hidden;
+    let max_distance_sq = (chunk_manager.tree_render_distance * CHUNK_SIZE * IMPOSTOR_DISTANCE_FACTOR).powi(2);
+
+    let mut processed = 0;
+    for (tree_transform, mut visibility, children) in trees.iter_mut().skip(*cursor).take(TREE_LOD_BATCH) {
+        let (_, tree_rotation, tree_position) = tree_transform.to_scale_rotation_translation();
+        let target = if cam_pos.distance_squared(tree_position) > max_distance_sq {
+            Visibility::Hidden
         } else {
-            *visibility = Visibility::Inherited;
+            Visibility::Inherited
+        };
+        // Only touch trees that flip, so visibility propagation doesn't revisit the whole forest
+        visibility.set_if_neq(target);
+        processed += 1;
+        if target == Visibility::Hidden {
+            continue;
+        }
+
+        // Turn the impostor about its trunk to face the camera; far away it only needs to be roughly right
+        let to_camera = cam_pos - tree_position;
+        let facing = Quat::from_rotation_y(to_camera.x.atan2(to_camera.z));
+        let mut impostor_iter = impostors.iter_many_mut(children);
+        while let Some(mut transform) = impostor_iter.fetch_next() {
+            transform.rotation = tree_rotation.inverse() * facing;
         }
     }
+
+    *cursor = if processed < TREE_LOD_BATCH { 0 } else { *cursor + processed };
 }
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::consts::world_units_to_meters;
+use crate::controls::{Aircraft, ControlInputs};
+use crate::hud::{calculate_heading, calculate_pitch, calculate_roll};
+use crate::network::TOKIO_RUNTIME;
+use crate::world_generation::WorldGenerator;
+
+/// Bound to localhost only: scripts on this machine can fly the aircraft, nothing beyond it can
+pub const EXTERNAL_CONTROL_PORT: u16 = 7879;
+
+/// One line of RON per request, answered with one line of RON, e.g. `State` or
+/// `Controls(pitch: 0.2, roll: 0.0, yaw: 0.0, throttle: Some(1.0))`
+#[derive(Debug, Clone, Deserialize)]
+pub enum Request {
+    State,
+    /// Take over the stick and rudder, each from -1 to 1, and optionally the throttle, from 0 to the aircraft's maximum
+    Controls { pitch: f32, roll: f32, yaw: f32, throttle: Option<f32> },
+    /// Hand the controls back to the keyboard
+    Release,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum Reply {
+    State(AircraftState),
+    Ok,
+    Error(String),
+}
+
+/// The local aircraft as a script sees it: angles in degrees, everything else in meters and seconds
+#[derive(Debug, Clone, Serialize)]
+pub struct AircraftState {
+    /// World units, Y up
+    pub position: [f32; 3],
+    /// Quaternion x, y, z, w
+    pub rotation: [f32; 4],
+    pub airspeed: f32,
+    pub altitude: f32,
+    /// Above the terrain or the water, whichever is higher
+    pub height_above_ground: f32,
+    pub heading: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    pub angle_of_attack: f32,
+    pub throttle: f32,
+    pub crashed: bool,
+    /// A script holds the controls
+    pub external: bool,
+}
+
+/// A request and where to send its reply, tagged with the connection it came in on
+type PendingRequest = (u32, Request, oneshot::Sender<Reply>);
+
+/// Local control API for autopilots and experiments outside the sim, off until enabled in the settings
+#[derive(Resource, Default)]
+pub struct ExternalControl {
+    pub enabled: bool,
+    /// Stick and rudder from the last `Controls` request, used instead of the keyboard until released
+    pub inputs: Option<ControlInputs>,
+    pub throttle: Option<f32>,
+    /// Connection whose `Controls` are in force; other scripts are turned away until it releases them
+    owner: Option<u32>,
+    pub error: Option<String>,
+    listener: Option<tokio::task::JoinHandle<Result<(), String>>>,
+    requests: Option<crossbeam_channel::Receiver<PendingRequest>>,
+}
+
+impl ExternalControl {
+    pub fn listening(&self) -> bool {
+        self.listener.is_some()
+    }
+
+    fn start(&mut self) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.requests = Some(rx);
+        self.error = None;
+        self.listener = Some(TOKIO_RUNTIME.spawn(async move {
+            let listener = TcpListener::bind(("127.0.0.1", EXTERNAL_CONTROL_PORT))
+                .await
+                .map_err(|e| format!("Couldn't listen on port {}: {}", EXTERNAL_CONTROL_PORT, e))?;
+            println!("🤖 External control listening on 127.0.0.1:{}", EXTERNAL_CONTROL_PORT);
+            let mut next_connection = 0u32;
+            loop {
+                match listener.accept().await {
+                    Ok((stream, address)) => {
+                        println!("🤖 External controller connected from {}", address);
+                        TOKIO_RUNTIME.spawn(serve_controller(next_connection, stream, tx.clone()));
+                        next_connection = next_connection.wrapping_add(1);
+                    }
+                    Err(e) => eprintln!("External control accept failed: {}", e),
+                }
+            }
+        }));
+    }
+
+    fn stop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.abort();
+        }
+        // Connected scripts notice on their next request and hang up
+        self.requests = None;
+        self.release();
+    }
+
+    /// Hand the controls back to the keyboard, whichever script holds them
+    pub fn release(&mut self) {
+        self.inputs = None;
+        self.throttle = None;
+        self.owner = None;
+    }
+}
+
+async fn serve_controller(connection: u32, stream: TcpStream, requests: crossbeam_channel::Sender<PendingRequest>) {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ron::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                if requests.send((connection, request, reply_tx)).is_err() {
+                    break;
+                }
+                reply_rx.await.unwrap_or_else(|_| Reply::Error("the sim stopped answering".to_string()))
+            }
+            Err(e) => Reply::Error(format!("couldn't parse request: {}", e)),
+        };
+        let Ok(text) = ron::to_string(&reply) else { break };
+        if write_half.write_all(format!("{}\n", text).as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    // A script that goes away mid-flight mustn't leave the aircraft stuck on its last inputs
+    let (reply_tx, _) = oneshot::channel();
+    let _ = requests.send((connection, Request::Release, reply_tx));
+    println!("🤖 External controller disconnected");
+}
+
+/// Start or stop the listener with the setting and answer whatever scripts have asked since last frame
+pub fn serve_external_control(
+    mut control: ResMut<ExternalControl>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    world_gen: Res<WorldGenerator>,
+) {
+    if let Some(listener) = &mut control.listener
+        && listener.is_finished()
+    {
+        let result = future::block_on(future::poll_once(listener));
+        control.error = Some(match result {
+            Some(Ok(Err(error))) => error,
+            _ => "The listener stopped".to_string(),
+        });
+        control.enabled = false;
+    }
+    match (control.enabled, control.listening()) {
+        (true, false) => control.start(),
+        (false, true) => control.stop(),
+        _ => {}
+    }
+
+    let pending: Vec<PendingRequest> = control.requests.as_ref().map(|rx| rx.try_iter().collect()).unwrap_or_default();
+    for (connection, request, reply_tx) in pending {
+        let reply = match request {
+            Request::State => match aircraft_query.single() {
+                Ok((transform, aircraft)) => Reply::State(aircraft_state(transform, aircraft, &world_gen, control.inputs.is_some())),
+                Err(_) => Reply::Error("no aircraft".to_string()),
+            },
+            Request::Controls { .. } if control.owner.is_some_and(|owner| owner != connection) => {
+                Reply::Error("another script holds the controls".to_string())
+            }
+            Request::Controls { pitch, roll, yaw, throttle }
+                if ![pitch, roll, yaw, throttle.unwrap_or(0.0)].iter().all(|value| value.is_finite()) =>
+            {
+                Reply::Error("control values must be finite".to_string())
+            }
+            Request::Controls { pitch, roll, yaw, throttle } => {
+                control.owner = Some(connection);
+                control.inputs = Some(ControlInputs {
+                    pitch: pitch.clamp(-1.0, 1.0),
+                    roll: roll.clamp(-1.0, 1.0),
+                    yaw: yaw.clamp(-1.0, 1.0),
+                });
+                control.throttle = throttle;
+                Reply::Ok
+            }
+            // Only the holder can let go; anyone else's release, such as on hanging up, changes nothing
+            Request::Release => {
+                if control.owner.is_none_or(|owner| owner == connection) {
+                    control.release();
+                }
+                Reply::Ok
+            }
+        };
+        let _ = reply_tx.send(reply);
+    }
+}
+
+fn aircraft_state(transform: &Transform, aircraft: &Aircraft, world_gen: &WorldGenerator, external: bool) -> AircraftState {
+    let position = transform.translation;
+    let ground = world_gen.get_terrain_height(&position.to_array()).max(0.0);
+    let forward = transform.forward().as_vec3();
+    AircraftState {
+        position: position.to_array(),
+        rotation: transform.rotation.to_array(),
+        airspeed: world_units_to_meters(aircraft.speed),
+        altitude: world_units_to_meters(position.y),
+        height_above_ground: world_units_to_meters(position.y - ground),
+        heading: calculate_heading(forward),
+        pitch: calculate_pitch(forward),
+        roll: calculate_roll(transform),
+        angle_of_attack: aircraft.angle_of_attack,
+        throttle: aircraft.throttle,
+        crashed: aircraft.crashed,
+        external,
+    }
+}
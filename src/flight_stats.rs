@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{load_factor, Aircraft, ControlMode};
+use crate::day_cycle::{Dawn, Dusk};
+use crate::ditching::Ditching;
+use crate::hud::HudPalette;
+use crate::network::RespawnAircraft;
+use crate::world_generation::{Biome, WorldGenerator};
+
+/// Lifetime totals, kept next to the other per-user settings
+const SAVE_PATH: &str = "settings/flight_stats.ron";
+/// Oldest entries drop off the event log past this many
+const MAX_EVENTS: usize = 12;
+
+/// Figures for one flight, from spawn to crash, landing or respawn. Distances in meters, speeds in m/s
+#[derive(Debug, Clone, Default)]
+pub struct FlightRecord {
+    pub time: f32,
+    pub distance: f32,
+    pub max_speed: f32,
+    pub max_altitude: f32,
+    pub max_g: f32,
+    pub biomes: Vec<Biome>,
+}
+
+/// Totals across every flight, saved between sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub flights: u32,
+    pub flight_time: f32,
+    pub distance: f32,
+    pub max_speed: f32,
+    pub max_altitude: f32,
+    pub max_g: f32,
+    pub crashes: u32,
+    pub ditchings: u32,
+    pub biomes: Vec<Biome>,
+}
+
+impl LifetimeStats {
+    fn add(&mut self, flight: &FlightRecord) {
+        self.flights += 1;
+        self.flight_time += flight.time;
+        self.distance += flight.distance;
+        self.max_speed = self.max_speed.max(flight.max_speed);
+        self.max_altitude = self.max_altitude.max(flight.max_altitude);
+        self.max_g = self.max_g.max(flight.max_g);
+        for biome in &flight.biomes {
+            if !self.biomes.contains(biome) {
+                self.biomes.push(*biome);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlightEnd {
+    Crashed,
+    Ditched,
+}
+
+#[derive(Resource, Default)]
+pub struct FlightStats {
+    pub current: FlightRecord,
+    pub lifetime: LifetimeStats,
+    /// How the last flight ended; its summary shows until the next one starts
+    pub ended: Option<FlightEnd>,
+    /// (seconds into the flight, what happened), oldest first
+    events: Vec<(f32, String)>,
+}
+
+impl FlightStats {
+    fn log(&mut self, text: String) {
+        if self.events.len() >= MAX_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push((self.current.time, text));
+    }
+
+    fn end_flight(&mut self, end: FlightEnd) {
+        self.ended = Some(end);
+        match end {
+            FlightEnd::Crashed => {
+                self.lifetime.crashes += 1;
+                self.log("Crashed".to_string());
+            }
+            FlightEnd::Ditched => {
+                self.lifetime.ditchings += 1;
+                self.log("Landed on the water".to_string());
+            }
+        }
+        self.lifetime.add(&self.current);
+        save_lifetime(&self.lifetime);
+    }
+}
+
+pub fn load_flight_stats(mut stats: ResMut<FlightStats>) {
+    let Ok(text) = std::fs::read_to_string(SAVE_PATH) else { return };
+    match ron::from_str(&text) {
+        Ok(loaded) => {
+            stats.lifetime = loaded;
+            info!("Loaded lifetime flight stats from {}", SAVE_PATH);
+        }
+        Err(error) => warn!("Ignoring {}: {}", SAVE_PATH, error),
+    }
+}
+
+fn save_lifetime(lifetime: &LifetimeStats) {
+    let result = ron::ser::to_string_pretty(lifetime, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(SAVE_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", SAVE_PATH, error);
+    }
+}
+
+/// A respawn starts a new flight. One cut short without a crash or landing still counts toward the totals
+pub fn start_new_flight(_trigger: On<RespawnAircraft>, mut stats: ResMut<FlightStats>) {
+    if stats.ended.is_none() && stats.current.time > 0.0 {
+        let flight = stats.current.clone();
+        stats.lifetime.add(&flight);
+        save_lifetime(&stats.lifetime);
+    }
+    stats.current = FlightRecord::default();
+    stats.ended = None;
+    stats.events.clear();
+}
+
+/// Sunrise and sunset go in the flight's log, so a long flight shows when it crossed into night and out
+pub fn log_dawn(_trigger: On<Dawn>, mut stats: ResMut<FlightStats>) {
+    stats.log("Sunrise".to_string());
+}
+
+pub fn log_dusk(_trigger: On<Dusk>, mut stats: ResMut<FlightStats>) {
+    stats.log("Sunset".to_string());
+}
+
+/// Accumulate the current flight's figures and close it out on a crash or water landing
+pub fn track_flight(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    world_gen: Res<WorldGenerator>,
+    ditching: Res<Ditching>,
+    mut stats: ResMut<FlightStats>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) {
+    if stats.ended.is_some() {
+        return;
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        stats.end_flight(FlightEnd::Crashed);
+        return;
+    }
+    if ditching.float.is_some() {
+        stats.end_flight(FlightEnd::Ditched);
+        return;
+    }
+    if control_mode.physics_paused {
+        return;
+    }
+
+    let dt = time.delta_secs();
+    let speed = world_units_to_meters(aircraft.speed);
+    let altitude = world_units_to_meters(transform.translation.y);
+    let flight = &mut stats.current;
+    flight.time += dt;
+    // Integrated from airspeed so teleports and respawns don't count as distance flown
+    flight.distance += speed * dt;
+    flight.max_speed = flight.max_speed.max(speed);
+    flight.max_altitude = flight.max_altitude.max(altitude);
+    flight.max_g = flight.max_g.max(load_factor(aircraft));
+
+    let biome = world_gen.get_biome(&transform.translation.to_array());
+    if !flight.biomes.contains(&biome) {
+        flight.biomes.push(biome);
+        stats.log(format!("Over {:?}", biome));
+    }
+}
+
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+fn format_biomes(biomes: &[Biome]) -> String {
+    if biomes.is_empty() {
+        return "None".to_string();
+    }
+    biomes.iter().map(|biome| format!("{:?}", biome)).collect::<Vec<_>>().join(", ")
+}
+
+/// This flight against the lifetime totals, plus what happened along the way
+pub fn flight_summary_ui(
+    mut contexts: EguiContexts,
+    stats: Res<FlightStats>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+) -> Result<(), BevyError> {
+    let Some(end) = stats.ended else { return Ok(()) };
+    let (flight, lifetime) = (&stats.current, &stats.lifetime);
+
+    egui::Window::new("Flight Summary")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 190.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            let heading = match end {
+                FlightEnd::Crashed => "FLIGHT SUMMARY · CRASHED",
+                FlightEnd::Ditched => "FLIGHT SUMMARY · WATER LANDING",
+            };
+            ui.label(egui::RichText::new(heading).size(12.0));
+            egui::Grid::new("flight_summary").num_columns(3).spacing([16.0, 2.0]).show(ui, |ui| {
+                ui.label("");
+                ui.label(egui::RichText::new("This flight").strong());
+                ui.label(egui::RichText::new("Lifetime").strong());
+                ui.end_row();
+                ui.label("Time");
+                ui.label(format_duration(flight.time));
+                ui.label(format_duration(lifetime.flight_time));
+                ui.end_row();
+                ui.label("Distance");
+                ui.label(units.format_distance(flight.distance));
+                ui.label(units.format_distance(lifetime.distance));
+                ui.end_row();
+                ui.label("Top speed");
+                ui.label(units.format_speed(flight.max_speed));
+                ui.label(units.format_speed(lifetime.max_speed));
+                ui.end_row();
+                ui.label("Max altitude");
+                ui.label(units.format_altitude(flight.max_altitude));
+                ui.label(units.format_altitude(lifetime.max_altitude));
+                ui.end_row();
+                ui.label("Max G");
+                ui.label(format!("{:.1}", flight.max_g));
+                ui.label(format!("{:.1}", lifetime.max_g));
+                ui.end_row();
+                ui.label("Biomes");
+                ui.label(format_biomes(&flight.biomes));
+                ui.label(format!("{} seen", lifetime.biomes.len()));
+                ui.end_row();
+            });
+            ui.label(format!(
+                "{} flights, {} crashes, {} water landings",
+                lifetime.flights, lifetime.crashes, lifetime.ditchings,
+            ));
+
+            if !stats.events.is_empty() {
+                ui.separator();
+                for (time, text) in &stats.events {
+                    ui.label(egui::RichText::new(format!("{}  {}", format_duration(*time), text)).size(11.0));
+                }
+            }
+        });
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::consts::{world_units_to_meters, GeoOrigin};
+use crate::controls::{Aircraft, ControlMode};
+use crate::network::RespawnAircraft;
+
+/// Exported tracks land here, one file per export
+const EXPORT_DIR: &str = "exports";
+/// Flight time between track points
+const TRACK_SAMPLE_SECS: f32 = 2.0;
+/// About eleven hours at the sample rate; the oldest points drop off past it
+const MAX_TRACK_POINTS: usize = 20_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackFormat {
+    Gpx,
+    Kml,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackPoint {
+    /// Seconds after the track started
+    time: f32,
+    position: Vec3,
+}
+
+/// The current flight's path, from spawn to now, for viewing in mapping tools
+#[derive(Resource)]
+pub struct FlightTrack {
+    started: SystemTime,
+    elapsed: f32,
+    since_sample: f32,
+    points: Vec<TrackPoint>,
+    /// Where the last export went, or why it failed
+    pub status: Option<String>,
+}
+
+impl Default for FlightTrack {
+    fn default() -> Self {
+        Self {
+            started: SystemTime::now(),
+            elapsed: 0.0,
+            since_sample: TRACK_SAMPLE_SECS,
+            points: Vec::new(),
+            status: None,
+        }
+    }
+}
+
+impl FlightTrack {
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Latitude, longitude, meters above sea level and UTC timestamp of each point
+    fn geo_points(&self, origin: GeoOrigin) -> impl Iterator<Item = (f64, f64, f32, String)> + '_ {
+        self.points.iter().map(move |point| {
+            let (latitude, longitude) = origin.to_lat_lon(point.position);
+            let time = self.started + Duration::from_secs_f32(point.time);
+            (latitude, longitude, world_units_to_meters(point.position.y), format_utc(time))
+        })
+    }
+
+    fn to_gpx(&self, origin: GeoOrigin, name: &str) -> String {
+        let mut gpx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        gpx.push_str("<gpx version=\"1.1\" creator=\"bevy_sim\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n");
+        gpx.push_str(&format!("  <trk>\n    <name>{}</name>\n    <trkseg>\n", name));
+        for (latitude, longitude, altitude, time) in self.geo_points(origin) {
+            gpx.push_str(&format!(
+                "      <trkpt lat=\"{:.7}\" lon=\"{:.7}\"><ele>{:.1}</ele><time>{}</time></trkpt>\n",
+                latitude, longitude, altitude, time,
+            ));
+        }
+        gpx.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+        gpx
+    }
+
+    /// A `gx:Track`, which keeps the timestamps so Google Earth can replay the flight
+    fn to_kml(&self, origin: GeoOrigin, name: &str) -> String {
+        let points: Vec<_> = self.geo_points(origin).collect();
+        let mut kml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        kml.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\" xmlns:gx=\"http://www.google.com/kml/ext/2.2\">\n");
+        kml.push_str(&format!("  <Document>\n    <name>{}</name>\n    <Placemark>\n      <name>{}</name>\n", name, name));
+        kml.push_str("      <gx:Track>\n        <altitudeMode>absolute</altitudeMode>\n");
+        for (_, _, _, time) in &points {
+            kml.push_str(&format!("        <when>{}</when>\n", time));
+        }
+        for (latitude, longitude, altitude, _) in &points {
+            kml.push_str(&format!("        <gx:coord>{:.7} {:.7} {:.1}</gx:coord>\n", longitude, latitude, altitude));
+        }
+        kml.push_str("      </gx:Track>\n    </Placemark>\n  </Document>\n</kml>\n");
+        kml
+    }
+
+    /// Write the track to `exports/`, returning the path written
+    pub fn export(&self, format: TrackFormat, origin: GeoOrigin) -> Result<String, String> {
+        if self.points.len() < 2 {
+            return Err("Nothing to export yet".to_string());
+        }
+        let stamp = format_utc(self.started).replace(':', "-");
+        let name = format!("Flight {}", format_utc(self.started));
+        let (extension, text) = match format {
+            TrackFormat::Gpx => ("gpx", self.to_gpx(origin, &name)),
+            TrackFormat::Kml => ("kml", self.to_kml(origin, &name)),
+        };
+        let path = format!("{}/flight_{}.{}", EXPORT_DIR, stamp, extension);
+        std::fs::create_dir_all(EXPORT_DIR).map_err(|error| error.to_string())?;
+        std::fs::write(&path, text).map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+}
+
+/// (year, month, day) of the date `days` after 1970-01-01, after Howard Hinnant's `civil_from_days`
+pub fn civil_date(days: i64) -> (i64, i64, i64) {
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// ISO 8601 UTC, e.g. `2024-05-01T14:03:09Z`
+fn format_utc(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()) as i64;
+    let (days, second_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_date(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, second_of_day / 3600, second_of_day / 60 % 60, second_of_day % 60,
+    )
+}
+
+pub fn start_new_track(_trigger: On<RespawnAircraft>, mut track: ResMut<FlightTrack>) {
+    let status = track.status.take();
+    *track = FlightTrack { status, ..default() };
+}
+
+/// Drop a point every few seconds of flight; a crashed or floating aircraft isn't going anywhere
+pub fn record_flight_track(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    mut track: ResMut<FlightTrack>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) {
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed || control_mode.physics_paused {
+        return;
+    }
+    let dt = time.delta_secs();
+    track.elapsed += dt;
+    track.since_sample += dt;
+    if track.since_sample < TRACK_SAMPLE_SECS {
+        return;
+    }
+    track.since_sample = 0.0;
+    if track.points.len() >= MAX_TRACK_POINTS {
+        track.points.remove(0);
+    }
+    let point = TrackPoint { time: track.elapsed, position: transform.translation };
+    track.points.push(point);
+}
@@ -0,0 +1,133 @@
+use bevy::{
+    audio::AudioSinkPlayback,
+    prelude::*,
+};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::Aircraft;
+use crate::hud::{HudPalette, MultiplayerMenu};
+use crate::network::{LeaveServer, NetworkClient};
+use crate::teleport::PendingTeleport;
+use crate::world_generation::{Chunk, ChunkManager, ChunkTask};
+
+const PAUSE_KEY: KeyCode = KeyCode::Escape;
+/// Chunks around the spawn point, in chunk widths, that must have terrain before control is handed over
+const SPAWN_AREA_RADIUS: i32 = 3;
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    /// Waiting for the terrain under the aircraft before handing over control
+    Loading,
+    InGame,
+    /// Simulation and the day cycle are frozen behind the pause menu
+    Paused,
+}
+
+/// Spawn-area chunks with finished terrain, for the loading screen
+#[derive(Resource, Default)]
+pub struct LoadingProgress {
+    pub ready: usize,
+    pub total: usize,
+}
+
+/// Hand over control once the terrain around the aircraft, or where it is teleporting to, has been generated
+pub fn finish_loading(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut progress: ResMut<LoadingProgress>,
+    chunk_manager: Res<ChunkManager>,
+    teleport: Res<PendingTeleport>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+    chunks: Query<Ref<Chunk>, Without<ChunkTask>>,
+) {
+    // A teleport waits for the terrain where the aircraft is going, not where it is
+    let Some(center) = teleport.destination().or_else(|| aircraft_query.single().ok().map(|transform| transform.translation)) else { return };
+    let center_x = (center.x / CHUNK_SIZE).round() as i32;
+    let center_z = (center.z / CHUNK_SIZE).round() as i32;
+    let radius = SPAWN_AREA_RADIUS.min(chunk_manager.render_distance);
+    let in_area = |x: i32, z: i32| (x - center_x).pow(2) + (z - center_z).pow(2) <= radius * radius;
+
+    progress.total = (-radius..=radius)
+        .flat_map(|dx| (-radius..=radius).map(move |dz| (dx, dz)))
+        .filter(|&(dx, dz)| in_area(center_x + dx, center_z + dz))
+        .count();
+    // A chunk spawned this frame hasn't started building its terrain yet
+    progress.ready = chunks.iter().filter(|chunk| in_area(chunk.x, chunk.z) && !chunk.is_added()).count();
+    if progress.ready >= progress.total {
+        next_state.set(GameState::InGame);
+    }
+}
+
+pub fn toggle_pause(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard.just_pressed(PAUSE_KEY) {
+        return;
+    }
+    match state.get() {
+        GameState::InGame => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::InGame),
+        GameState::MainMenu | GameState::Loading => {}
+    }
+}
+
+/// Stop the clock every time-driven system runs on, and hold any playing sounds
+pub fn pause_game(mut time: ResMut<Time<Virtual>>, sinks: Query<&AudioSink>, spatial_sinks: Query<&SpatialAudioSink>) {
+    time.pause();
+    sinks.iter().for_each(|sink| sink.pause());
+    spatial_sinks.iter().for_each(|sink| sink.pause());
+}
+
+pub fn resume_game(mut time: ResMut<Time<Virtual>>, sinks: Query<&AudioSink>, spatial_sinks: Query<&SpatialAudioSink>) {
+    time.unpause();
+    sinks.iter().for_each(|sink| sink.play());
+    spatial_sinks.iter().for_each(|sink| sink.play());
+}
+
+pub fn pause_menu_ui(
+    mut contexts: EguiContexts,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut menu: ResMut<MultiplayerMenu>,
+    client: Option<Res<NetworkClient>>,
+    palette: Res<HudPalette>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    let connected = client.is_some_and(|client| client.connected);
+
+    egui::Window::new("Paused")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(16.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new("PAUSED").size(24.0).strong());
+                if connected {
+                    ui.label(egui::RichText::new("Other players keep flying").size(11.0));
+                }
+                ui.add_space(10.0);
+                let button_size = [160.0, 28.0];
+                if ui.add_sized(button_size, egui::Button::new("Resume")).clicked() {
+                    next_state.set(GameState::InGame);
+                }
+                if ui.add_sized(button_size, egui::Button::new("Settings")).clicked() {
+                    menu.settings_open = true;
+                }
+                if connected && ui.add_sized(button_size, egui::Button::new("Disconnect")).clicked() {
+                    commands.trigger(LeaveServer);
+                    next_state.set(GameState::InGame);
+                }
+                if ui.add_sized(button_size, egui::Button::new("Quit")).clicked() {
+                    commands.write_message(AppExit::Success);
+                }
+                ui.label(egui::RichText::new("Esc to resume").size(10.0));
+            });
+        });
+
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+};
+
+use crate::consts::world_units_to_meters;
+use crate::controls::{Aircraft, ControlMode};
+use crate::network::PlaneType;
+use crate::world_generation::WorldGenerator;
+
+/// Height the glider is held above the ground during the ground roll
+const LAUNCH_GROUND_CLEARANCE: f32 = 15.0;
+/// Airspeed at which the glider leaves the ground and starts to rotate
+const LAUNCH_ROTATE_SPEED: f32 = 160.0;
+
+const WINCH_CABLE_LENGTH: f32 = 6000.0;
+const WINCH_ACCELERATION: f32 = 120.0;
+const WINCH_SPEED: f32 = 230.0;
+const WINCH_CLIMB_PITCH: f32 = 40.0;
+/// The cable back-releases once it pulls this steeply down from the glider
+const WINCH_RELEASE_ANGLE: f32 = 70.0;
+
+const AEROTOW_ACCELERATION: f32 = 45.0;
+const AEROTOW_SPEED: f32 = 220.0;
+const AEROTOW_CLIMB_PITCH: f32 = 8.0;
+const AEROTOW_RELEASE_HEIGHT: f32 = 2500.0;
+const TOW_ROPE_LENGTH: f32 = 250.0;
+const TOW_PLANE_SCALE: f32 = 0.4;
+
+/// How quickly the pitch follows the launch profile
+const LAUNCH_PITCH_RATE: f32 = 1.5;
+
+/// Smoothing time of the variometer needle, in seconds
+const VARIO_TIME_CONSTANT: f32 = 0.6;
+/// Climb rate (m/s) above which the variometer starts beeping
+const VARIO_LIFT_THRESHOLD: f32 = 0.2;
+/// Sink rate (m/s) below which the variometer drones
+const VARIO_SINK_THRESHOLD: f32 = -2.0;
+const VARIO_VOLUME: f32 = 0.15;
+const VARIO_SAMPLE_RATE: u32 = 44_100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMethod {
+    /// Ground winch reels in a long cable for a short, steep climb
+    Winch,
+    /// A tow plane pulls the glider up to release height
+    Aerotow,
+}
+
+struct ActiveLaunch {
+    method: LaunchMethod,
+    yaw: f32,
+    pitch: f32,
+    ground_height: f32,
+    winch_anchor: Vec3,
+}
+
+/// Launch state for the local glider; G starts a launch or releases the cable
+#[derive(Resource)]
+pub struct GliderLaunch {
+    pub method: LaunchMethod,
+    active: Option<ActiveLaunch>,
+}
+
+impl Default for GliderLaunch {
+    fn default() -> Self {
+        Self {
+            method: LaunchMethod::Winch,
+            active: None,
+        }
+    }
+}
+
+impl GliderLaunch {
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+#[derive(Component)]
+pub struct TowPlane;
+
+/// Start, fly and release winch and aerotow launches for the local glider
+pub fn update_glider_launch(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    world_gen: Res<WorldGenerator>,
+    asset_server: Res<AssetServer>,
+    mut launch: ResMut<GliderLaunch>,
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    mut aircraft_query: Query<(&mut Transform, &mut Aircraft), Without<TowPlane>>,
+    mut tow_planes: Query<(Entity, &mut Transform), (With<TowPlane>, Without<Aircraft>)>,
+) {
+    // The tow plane peels away as soon as its launch is over
+    if !launch.is_active() {
+        for (entity, _) in tow_planes.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Ok((mut transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+
+    if aircraft.plane_type != PlaneType::Glider || aircraft.crashed {
+        launch.active = None;
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::KeyG) {
+        if launch.is_active() {
+            launch.active = None;
+            info!("Tow released");
+            return;
+        }
+
+        let pos = transform.translation;
+        let ground_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]).max(0.0);
+        let forward = transform.forward().as_vec3();
+        let heading = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+        let yaw = f32::atan2(-heading.x, -heading.z);
+
+        transform.translation = Vec3::new(pos.x, ground_height + LAUNCH_GROUND_CLEARANCE, pos.z);
+        transform.rotation = Quat::from_rotation_y(yaw);
+        aircraft.speed = 0.0;
+        aircraft.velocity = Vec3::ZERO;
+        aircraft.pitch_velocity = 0.0;
+        aircraft.roll_velocity = 0.0;
+        aircraft.yaw_velocity = 0.0;
+
+        let anchor = transform.translation + heading * WINCH_CABLE_LENGTH;
+        let anchor_height = world_gen.get_terrain_height(&[anchor.x, anchor.y, anchor.z]).max(0.0);
+
+        if launch.method == LaunchMethod::Aerotow {
+            let tow_plane = commands.spawn((
+                Transform::from_translation(transform.translation + heading * TOW_ROPE_LENGTH)
+                    .with_rotation(transform.rotation)
+                    .with_scale(Vec3::splat(TOW_PLANE_SCALE)),
+                Visibility::default(),
+                TowPlane,
+            )).id();
+            let model = commands.spawn((
+                SceneRoot(asset_server.load("low-poly_airplane/scene.gltf#Scene0")),
+                Transform::from_rotation(Quat::from_rotation_y((180.0f32).to_radians())),
+            )).id();
+            commands.entity(tow_plane).add_child(model);
+        }
+
+        launch.active = Some(ActiveLaunch {
+            method: launch.method,
+            yaw,
+            pitch: 0.0,
+            ground_height,
+            winch_anchor: Vec3::new(anchor.x, anchor_height, anchor.z),
+        });
+        info!("{:?} launch started", launch.method);
+    }
+
+    if control_mode.physics_paused {
+        return;
+    }
+    let Some(active) = launch.active.as_mut() else { return };
+    let dt = time.delta_secs();
+
+    let pos = transform.translation;
+    let terrain_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]).max(0.0);
+    let height = pos.y - active.ground_height;
+
+    let (acceleration, target_speed, mut target_pitch) = match active.method {
+        LaunchMethod::Winch => (WINCH_ACCELERATION, WINCH_SPEED, WINCH_CLIMB_PITCH),
+        LaunchMethod::Aerotow => (AEROTOW_ACCELERATION, AEROTOW_SPEED, AEROTOW_CLIMB_PITCH),
+    };
+    aircraft.speed = (aircraft.speed + acceleration * dt).min(target_speed);
+
+    let released = match active.method {
+        LaunchMethod::Winch => {
+            let to_anchor = active.winch_anchor - pos;
+            let horizontal = Vec2::new(to_anchor.x, to_anchor.z).length();
+            let cable_angle = f32::atan2(-to_anchor.y, horizontal).to_degrees();
+            // Ease the nose over as the glider climbs above the winch
+            target_pitch *= (1.0 - cable_angle / WINCH_RELEASE_ANGLE).clamp(0.0, 1.0).sqrt();
+            gizmos.line(pos, active.winch_anchor, Color::srgb(0.8, 0.8, 0.8));
+            cable_angle >= WINCH_RELEASE_ANGLE
+        }
+        LaunchMethod::Aerotow => height >= AEROTOW_RELEASE_HEIGHT,
+    };
+
+    if aircraft.speed < LAUNCH_ROTATE_SPEED {
+        target_pitch = 0.0;
+        transform.translation.y = pos.y.max(terrain_height + LAUNCH_GROUND_CLEARANCE);
+    }
+    let pitch_step = LAUNCH_PITCH_RATE * dt;
+    active.pitch += (target_pitch.to_radians() - active.pitch).clamp(-pitch_step, pitch_step);
+
+    transform.rotation = Quat::from_rotation_y(active.yaw) * Quat::from_rotation_x(active.pitch);
+    aircraft.pitch_velocity = 0.0;
+    aircraft.roll_velocity = 0.0;
+    aircraft.yaw_velocity = 0.0;
+
+    for (_, mut tow_transform) in tow_planes.iter_mut() {
+        tow_transform.translation = transform.translation + transform.forward().as_vec3() * TOW_ROPE_LENGTH;
+        tow_transform.rotation = transform.rotation;
+        gizmos.line(transform.translation, tow_transform.translation, Color::srgb(0.9, 0.9, 0.2));
+    }
+
+    if released {
+        info!("{:?} launch released at {:.0} m", active.method, world_units_to_meters(height));
+        launch.active = None;
+    }
+}
+
+/// Shared between the game and the audio thread: frequency, beep rate and volume as f32 bits
+#[derive(Default)]
+struct ToneState {
+    frequency: AtomicU32,
+    beep_rate: AtomicU32,
+    volume: AtomicU32,
+}
+
+impl ToneState {
+    fn set(&self, frequency: f32, beep_rate: f32, volume: f32) {
+        self.frequency.store(frequency.to_bits(), Ordering::Relaxed);
+        self.beep_rate.store(beep_rate.to_bits(), Ordering::Relaxed);
+        self.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Fade out but keep the last pitch so the tail of the beep doesn't click
+    fn silence(&self) {
+        self.volume.store(0.0f32.to_bits(), Ordering::Relaxed);
+    }
+
+    fn load(&self) -> (f32, f32, f32) {
+        (
+            f32::from_bits(self.frequency.load(Ordering::Relaxed)),
+            f32::from_bits(self.beep_rate.load(Ordering::Relaxed)),
+            f32::from_bits(self.volume.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Smoothed climb rate of the local aircraft, in metres per second
+#[derive(Resource)]
+pub struct Variometer {
+    pub climb_rate: f32,
+    pub tone_enabled: bool,
+    tone: Arc<ToneState>,
+}
+
+impl Default for Variometer {
+    fn default() -> Self {
+        Self {
+            climb_rate: 0.0,
+            tone_enabled: true,
+            tone: Arc::new(ToneState::default()),
+        }
+    }
+}
+
+/// Endless procedural variometer tone, steered through the shared `ToneState`
+#[derive(Asset, TypePath)]
+pub struct VariometerTone {
+    state: Arc<ToneState>,
+}
+
+pub struct ToneDecoder {
+    state: Arc<ToneState>,
+    phase: f32,
+    beep_phase: f32,
+    gain: f32,
+}
+
+impl Iterator for ToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let (frequency, beep_rate, volume) = self.state.load();
+        let sample_rate = VARIO_SAMPLE_RATE as f32;
+
+        self.phase = (self.phase + frequency / sample_rate).fract();
+        let gate = if beep_rate > 0.0 {
+            self.beep_phase = (self.beep_phase + beep_rate / sample_rate).fract();
+            if self.beep_phase < 0.5 { 1.0 } else { 0.0 }
+        } else {
+            1.0
+        };
+
+        // Glide the gain towards its target so beeps start and stop without clicks
+        let target = volume * gate;
+        self.gain += (target - self.gain) * 0.005;
+
+        Some((self.phase * std::f32::consts::TAU).sin() * self.gain)
+    }
+}
+
+impl Source for ToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        VARIO_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+impl Decodable for VariometerTone {
+    type DecoderItem = f32;
+    type Decoder = ToneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        ToneDecoder {
+            state: self.state.clone(),
+            phase: 0.0,
+            beep_phase: 0.0,
+            gain: 0.0,
+        }
+    }
+}
+
+pub fn setup_variometer_tone(
+    mut commands: Commands,
+    variometer: Res<Variometer>,
+    mut tones: ResMut<Assets<VariometerTone>>,
+) {
+    let handle = tones.add(VariometerTone { state: variometer.tone.clone() });
+    commands.spawn(AudioPlayer::<VariometerTone>(handle));
+}
+
+/// Smooth the climb rate and drive the tone: rising beeps in lift, a low drone in heavy sink
+pub fn update_variometer(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    mut variometer: ResMut<Variometer>,
+    aircraft_query: Query<&Aircraft>,
+) {
+    let Ok(aircraft) = aircraft_query.single() else { return };
+
+    let raw = if aircraft.crashed { 0.0 } else { world_units_to_meters(aircraft.velocity.y) };
+    let blend = 1.0 - (-time.delta_secs() / VARIO_TIME_CONSTANT).exp();
+    variometer.climb_rate += (raw - variometer.climb_rate) * blend;
+
+    let climb = variometer.climb_rate;
+    let audible = variometer.tone_enabled
+        && aircraft.plane_type == PlaneType::Glider
+        && !aircraft.crashed
+        && !control_mode.physics_paused;
+
+    if !audible {
+        variometer.tone.silence();
+    } else if climb > VARIO_LIFT_THRESHOLD {
+        variometer.tone.set((600.0 + climb * 120.0).min(1500.0), 2.0 + climb * 1.2, VARIO_VOLUME);
+    } else if climb < VARIO_SINK_THRESHOLD {
+        variometer.tone.set((300.0 + climb * 20.0).max(150.0), 0.0, VARIO_VOLUME * 0.6);
+    } else {
+        variometer.tone.silence();
+    }
+}
@@ -0,0 +1,423 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::{visibility::RenderLayers, RenderTarget},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    window::{Monitor, MonitorSelection, PresentMode, PrimaryMonitor, PrimaryWindow, VideoModeSelection, WindowMode, WindowRef},
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::controls::MainCamera;
+use crate::hud::MultiplayerMenu;
+use crate::split_screen::SecondPilotCamera;
+use crate::underwater::Underwater;
+use crate::world_generation::ChunkManager;
+use crate::RenderSettings;
+
+/// Per-user settings, kept outside `assets/` like the accessibility file
+const DISPLAY_SETTINGS_PATH: &str = "settings/display.ron";
+/// Sizes offered when the monitor doesn't list its video modes
+const FALLBACK_RESOLUTIONS: [(u32, u32); 5] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440), (3840, 2160)];
+const FPS_CAP_RANGE: std::ops::RangeInclusive<u32> = 20..=240;
+const RENDER_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=1.0;
+/// Render layer only the upscaling camera and its sprite are on
+const UPSCALE_LAYER: usize = 31;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+    /// Set by hand after picking a preset
+    Custom,
+}
+
+impl GraphicsPreset {
+    /// The presets the settings panel offers; `Custom` is only ever arrived at
+    pub const CHOICES: [GraphicsPreset; 3] = [GraphicsPreset::Low, GraphicsPreset::Medium, GraphicsPreset::High];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GraphicsPreset::Low => "Low",
+            GraphicsPreset::Medium => "Medium",
+            GraphicsPreset::High => "High",
+            GraphicsPreset::Custom => "Custom",
+        }
+    }
+
+    pub fn settings(self) -> Option<GraphicsSettings> {
+        let low = GraphicsSettings {
+            render_distance: 50,
+            lod_distance_multiplier: 10.0,
+            lod_quality_multiplier: 1,
+            tree_render_distance: 12.0,
+            cascades: 0,
+            smooth_normals: false,
+            fog_density: 0.000045,
+            vsync: true,
+            msaa: Msaa::Off,
+        };
+        match self {
+            GraphicsPreset::Low => Some(low),
+            GraphicsPreset::Medium => Some(GraphicsSettings {
+                render_distance: 65,
+                lod_distance_multiplier: 12.5,
+                tree_render_distance: 14.0,
+                cascades: 1,
+                smooth_normals: true,
+                msaa: Msaa::Sample2,
+                ..low
+            }),
+            GraphicsPreset::High => Some(GraphicsSettings {
+                render_distance: 80,
+                lod_distance_multiplier: 15.0,
+                lod_quality_multiplier: 2,
+                tree_render_distance: 16.0,
+                cascades: 2,
+                smooth_normals: true,
+                msaa: Msaa::Sample4,
+                ..low
+            }),
+            GraphicsPreset::Custom => None,
+        }
+    }
+}
+
+/// Everything a preset sets, spread over the chunk manager, render settings, camera and window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphicsSettings {
+    pub render_distance: i32,
+    pub lod_distance_multiplier: f32,
+    pub lod_quality_multiplier: u32,
+    pub tree_render_distance: f32,
+    pub cascades: usize,
+    pub smooth_normals: bool,
+    pub fog_density: f32,
+    pub vsync: bool,
+    pub msaa: Msaa,
+}
+
+/// Switch every graphics setting to a preset's values at once
+#[derive(Event)]
+pub struct ApplyGraphicsPreset(pub GraphicsPreset);
+
+pub fn apply_graphics_preset(
+    trigger: On<ApplyGraphicsPreset>,
+    mut menu: ResMut<MultiplayerMenu>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut render_settings: ResMut<RenderSettings>,
+    mut camera_query: Query<(Entity, &mut DistanceFog), With<MainCamera>>,
+    mut display: ResMut<DisplaySettings>,
+    underwater: Res<Underwater>,
+    mut commands: Commands,
+) {
+    let preset = trigger.event().0;
+    let Some(settings) = preset.settings() else { return };
+    menu.graphics_preset = preset;
+
+    chunk_manager.render_distance = settings.render_distance;
+    chunk_manager.lod_distance_multiplier = settings.lod_distance_multiplier;
+    chunk_manager.lod_quality_multiplier = settings.lod_quality_multiplier;
+    chunk_manager.tree_render_distance = settings.tree_render_distance;
+    render_settings.cascades = settings.cascades;
+    render_settings.compute_smooth_normals = settings.smooth_normals;
+    // Rebuild the chunks, shadows and LODs in one go
+    render_settings.just_updated = true;
+
+    if let Ok((camera, mut fog)) = camera_query.single_mut() {
+        // Under water the sky's density is parked until the camera surfaces
+        if !underwater.submerged && let FogFalloff::ExponentialSquared { density } = &mut fog.falloff {
+            *density = settings.fog_density;
+        }
+        commands.entity(camera).insert(settings.msaa);
+    }
+    display.set_if_neq(DisplaySettings { vsync: settings.vsync, ..display.clone() });
+}
+
+/// Start on the menu's preset rather than whatever the resources were built with
+pub fn apply_initial_graphics_preset(mut commands: Commands, menu: Res<MultiplayerMenu>) {
+    commands.trigger(ApplyGraphicsPreset(menu.graphics_preset));
+}
+
+/// Fall back to `Custom` as soon as any preset value is changed by hand
+pub fn track_custom_preset(
+    mut menu: ResMut<MultiplayerMenu>,
+    chunk_manager: Res<ChunkManager>,
+    render_settings: Res<RenderSettings>,
+    camera_query: Query<(&DistanceFog, Option<&Msaa>), With<MainCamera>>,
+    display: Res<DisplaySettings>,
+    underwater: Res<Underwater>,
+) {
+    let Some(preset) = menu.graphics_preset.settings() else { return };
+    let Ok((fog, msaa)) = camera_query.single() else { return };
+    let fog_density = match fog.falloff {
+        // The water swaps its own fog in; judge the sky's by the one waiting for the surface
+        _ if underwater.submerged => preset.fog_density,
+        FogFalloff::ExponentialSquared { density } => density,
+        _ => return,
+    };
+    let current = GraphicsSettings {
+        render_distance: chunk_manager.render_distance,
+        lod_distance_multiplier: chunk_manager.lod_distance_multiplier,
+        lod_quality_multiplier: chunk_manager.lod_quality_multiplier,
+        tree_render_distance: chunk_manager.tree_render_distance,
+        cascades: render_settings.cascades,
+        smooth_normals: render_settings.compute_smooth_normals,
+        fog_density,
+        vsync: display.vsync,
+        msaa: msaa.copied().unwrap_or_default(),
+    };
+    if current != preset {
+        menu.graphics_preset = GraphicsPreset::Custom;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    /// Exclusive fullscreen, switching the monitor to the chosen resolution
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen];
+}
+
+/// Window mode, size and frame rate, changed from the settings panel and saved between sessions
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    /// Physical pixels; the window's size, or the video mode in exclusive fullscreen. `None` keeps the default window
+    pub resolution: Option<(u32, u32)>,
+    pub vsync: bool,
+    /// Frames per second to hold to, or `None` to run as fast as the present mode allows
+    pub fps_cap: Option<u32>,
+    /// Fraction of the window's resolution the world is rendered at before being stretched over it
+    pub render_scale: f32,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::Windowed,
+            resolution: None,
+            vsync: true,
+            fps_cap: None,
+            render_scale: 1.0,
+        }
+    }
+}
+
+pub fn load_display_settings(mut display: ResMut<DisplaySettings>) {
+    let Ok(text) = std::fs::read_to_string(DISPLAY_SETTINGS_PATH) else { return };
+    match ron::from_str(&text) {
+        Ok(loaded) => {
+            *display = loaded;
+            info!("Loaded display settings from {}", DISPLAY_SETTINGS_PATH);
+        }
+        Err(error) => warn!("Ignoring {}: {}", DISPLAY_SETTINGS_PATH, error),
+    }
+}
+
+pub fn save_display_settings(display: Res<DisplaySettings>) {
+    if !display.is_changed() || display.is_added() {
+        return;
+    }
+    let result = ron::ser::to_string_pretty(&*display, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(DISPLAY_SETTINGS_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", DISPLAY_SETTINGS_PATH, error);
+    }
+}
+
+/// Push the display settings to the primary window whenever they change
+pub fn apply_display_settings(
+    display: Res<DisplaySettings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    monitors: Query<&Monitor, With<PrimaryMonitor>>,
+) {
+    if !display.is_changed() {
+        return;
+    }
+    let Ok(mut window) = window_query.single_mut() else { return };
+
+    window.present_mode = if display.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    window.mode = match display.mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+        DisplayMode::Fullscreen => {
+            // The monitor's fastest mode at the chosen size, or whatever it is running now
+            let video_mode = display.resolution.and_then(|(width, height)| {
+                monitors.single().ok()?.video_modes.iter()
+                    .filter(|mode| mode.physical_size == UVec2::new(width, height))
+                    .max_by_key(|mode| (mode.refresh_rate_millihertz, mode.bit_depth))
+                    .copied()
+            });
+            WindowMode::Fullscreen(MonitorSelection::Current, video_mode.map_or(VideoModeSelection::Current, VideoModeSelection::Specific))
+        }
+    };
+    if display.mode == DisplayMode::Windowed && let Some((width, height)) = display.resolution {
+        window.resolution.set_physical_resolution(width, height);
+    }
+}
+
+/// Sleep off what's left of the frame budget when a cap is set. Runs last, so the whole frame counts against it
+pub fn limit_frame_rate(display: Res<DisplaySettings>, mut frame_start: Local<Option<Instant>>) {
+    if let (Some(cap), Some(start)) = (display.fps_cap, *frame_start) {
+        let budget = Duration::from_secs_f64(1.0 / cap.max(1) as f64);
+        let elapsed = start.elapsed();
+        if elapsed < budget {
+            std::thread::sleep(budget - elapsed);
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
+
+/// The camera and full-window sprite that stretch a scaled-down world view over the window
+#[derive(Component)]
+pub struct RenderScaleUpscaler;
+
+/// Render the main camera into a smaller image and upscale it when the render scale is below 1.
+/// Split screen draws straight to the window, as each half is already a viewport of it
+pub fn apply_render_scale(
+    mut commands: Commands,
+    display: Res<DisplaySettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    main_camera: Query<Entity, With<MainCamera>>,
+    second_cameras: Query<(), With<SecondPilotCamera>>,
+    mut upscalers: Query<(Entity, Option<&mut Sprite>), With<RenderScaleUpscaler>>,
+    mut images: ResMut<Assets<Image>>,
+    mut applied: Local<Option<(UVec2, Handle<Image>)>>,
+) {
+    let (Ok(window), Ok(camera)) = (windows.single(), main_camera.single()) else { return };
+    let scale = display.render_scale.clamp(*RENDER_SCALE_RANGE.start(), *RENDER_SCALE_RANGE.end());
+    let wanted = (scale < 1.0 && second_cameras.is_empty() && window.physical_width() > 0)
+        .then(|| (window.physical_size().as_vec2() * scale).round().as_uvec2().max(UVec2::ONE));
+    if wanted == applied.as_ref().map(|(size, _)| *size) {
+        return;
+    }
+
+    let Some(size) = wanted else {
+        commands.entity(camera).insert(RenderTarget::Window(WindowRef::Primary));
+        for (entity, _) in &upscalers {
+            commands.entity(entity).despawn();
+        }
+        *applied = None;
+        return;
+    };
+
+    let extent = Extent3d { width: size.x, height: size.y, ..default() };
+    let image = match applied.take() {
+        Some((_, image)) => {
+            if let Some(target) = images.get_mut(&image) {
+                target.resize(extent);
+            }
+            image
+        }
+        None => {
+            let mut target = Image::new_fill(
+                extent,
+                TextureDimension::D2,
+                &[0, 0, 0, 255],
+                TextureFormat::Bgra8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+            target.texture_descriptor.usage =
+                TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+            images.add(target)
+        }
+    };
+    commands.entity(camera).insert(RenderTarget::Image(image.clone().into()));
+
+    if upscalers.is_empty() {
+        commands.spawn((
+            Camera2d,
+            // Between the world camera and the egui overlay
+            Camera { order: 1, ..default() },
+            RenderLayers::layer(UPSCALE_LAYER),
+            RenderScaleUpscaler,
+        ));
+        commands.spawn((
+            Sprite { image: image.clone(), custom_size: Some(window.size()), ..default() },
+            RenderLayers::layer(UPSCALE_LAYER),
+            RenderScaleUpscaler,
+        ));
+    } else {
+        for (_, sprite) in &mut upscalers {
+            if let Some(mut sprite) = sprite {
+                sprite.custom_size = Some(window.size());
+            }
+        }
+    }
+    *applied = Some((size, image));
+}
+
+/// A window cursor position in the main camera's viewport, which is smaller than the window when the render scale is down
+pub fn cursor_in_main_view(window: &Window, camera: &Camera, cursor: Vec2) -> Vec2 {
+    match camera.logical_viewport_size() {
+        Some(viewport) if window.width() > 0.0 && window.height() > 0.0 => cursor * viewport / window.size(),
+        _ => cursor,
+    }
+}
+
+/// Sizes the primary monitor supports, largest first, or a standard list if it doesn't say
+pub fn available_resolutions(monitors: &Query<&Monitor, With<PrimaryMonitor>>) -> Vec<(u32, u32)> {
+    let mut sizes: Vec<(u32, u32)> = monitors.single().map_or_else(
+        |_| Vec::new(),
+        |monitor| monitor.video_modes.iter().map(|mode| (mode.physical_size.x, mode.physical_size.y)).collect(),
+    );
+    if sizes.is_empty() {
+        sizes = FALLBACK_RESOLUTIONS.to_vec();
+    }
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    sizes
+}
+
+/// Returns whether anything was changed
+pub fn ui_display_settings(ui: &mut egui::Ui, display: &mut DisplaySettings, resolutions: &[(u32, u32)]) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Window:");
+        for mode in DisplayMode::ALL {
+            changed |= ui.selectable_value(&mut display.mode, mode, format!("{:?}", mode)).changed();
+        }
+    });
+    let format_resolution = |resolution: Option<(u32, u32)>| {
+        resolution.map_or("Default".to_string(), |(width, height)| format!("{} × {}", width, height))
+    };
+    ui.add_enabled_ui(display.mode != DisplayMode::Borderless, |ui| {
+        egui::ComboBox::from_label("Resolution")
+            .selected_text(format_resolution(display.resolution))
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(&mut display.resolution, None, format_resolution(None)).changed();
+                for &resolution in resolutions {
+                    changed |= ui.selectable_value(&mut display.resolution, Some(resolution), format_resolution(Some(resolution))).changed();
+                }
+            });
+    });
+    changed |= ui.checkbox(&mut display.vsync, "VSync").changed();
+    ui.horizontal(|ui| {
+        let mut capped = display.fps_cap.is_some();
+        if ui.checkbox(&mut capped, "FPS Cap").changed() {
+            display.fps_cap = capped.then_some(60);
+            changed = true;
+        }
+        if let Some(cap) = &mut display.fps_cap {
+            changed |= ui.add(egui::Slider::new(cap, FPS_CAP_RANGE)).changed();
+        }
+    });
+    changed |= ui.add(egui::Slider::new(&mut display.render_scale, RENDER_SCALE_RANGE).text("Render Scale").custom_formatter(|scale, _| {
+        format!("{:.0}%", scale * 100.0)
+    })).changed();
+    changed
+}
@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::decal::{ForwardDecal, ForwardDecalMaterial, ForwardDecalMaterialExt},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::controls::Aircraft;
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::world_generation::WorldGenerator;
+use crate::RenderSettings;
+
+const DECAL_TEXTURE_SIZE: u32 = 64;
+/// Decals stay opaque over surfaces this far from their quad, which covers the gap
+/// between the analytic terrain height and the coarser LOD meshes
+const DECAL_DEPTH_FADE: f32 = 150.0;
+
+const SHADOW_SIZE: f32 = 45.0;
+const SHADOW_OPACITY: f32 = 0.55;
+/// The shadow spreads out and fades away as the aircraft climbs to this height above the ground
+const SHADOW_MAX_HEIGHT: f32 = 1500.0;
+const SHADOW_SPREAD: f32 = 1.5;
+
+const SCORCH_SIZE: f32 = 140.0;
+/// Oldest marks are cleared beyond this many
+const MAX_SCORCH_MARKS: usize = 24;
+/// Explosions this close to the ground leave a mark
+const SCORCH_MAX_HEIGHT: f32 = 60.0;
+
+#[derive(Component)]
+pub struct AircraftShadow;
+
+#[derive(Resource)]
+pub struct DecalAssets {
+    shadow: Handle<ForwardDecalMaterial<StandardMaterial>>,
+    scorch: Handle<ForwardDecalMaterial<StandardMaterial>>,
+}
+
+/// White disc whose alpha falls off toward a rim; `ragged` roughens the rim for burn marks
+fn radial_texture(ragged: bool) -> Image {
+    let size = DECAL_TEXTURE_SIZE;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32 * 2.0 - 1.0;
+            let angle = offset.y.atan2(offset.x);
+            let rim = if ragged {
+                0.8 + 0.12 * (angle * 5.0).sin() * (angle * 3.0 + 1.0).cos()
+            } else {
+                0.95
+            };
+            let alpha = 1.0 - ((offset.length() - rim * 0.5) / (rim * 0.5)).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn decal_material(color: Color, texture: Handle<Image>) -> ForwardDecalMaterial<StandardMaterial> {
+    ForwardDecalMaterial {
+        base: StandardMaterial {
+            base_color: color,
+            base_color_texture: Some(texture),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        },
+        extension: ForwardDecalMaterialExt {
+            depth_fade_factor: DECAL_DEPTH_FADE,
+        },
+    }
+}
+
+pub fn setup_ground_decals(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ForwardDecalMaterial<StandardMaterial>>>,
+) {
+    let shadow = materials.add(decal_material(
+        Color::BLACK.with_alpha(SHADOW_OPACITY),
+        images.add(radial_texture(false)),
+    ));
+    let scorch = materials.add(decal_material(Color::srgba(0.05, 0.03, 0.02, 0.85), images.add(radial_texture(true))));
+
+    commands.spawn((
+        ForwardDecal,
+        MeshMaterial3d(shadow.clone()),
+        Transform::default(),
+        Visibility::Hidden,
+        AircraftShadow,
+    ));
+    commands.insert_resource(DecalAssets { shadow, scorch });
+}
+
+/// Keep the blob shadow on the ground under the aircraft, spreading and fading with height
+pub fn update_aircraft_shadow(
+    render_settings: Res<RenderSettings>,
+    world_gen: Res<WorldGenerator>,
+    assets: Option<Res<DecalAssets>>,
+    mut materials: ResMut<Assets<ForwardDecalMaterial<StandardMaterial>>>,
+    aircraft_query: Query<(&Transform, &Aircraft, &Visibility)>,
+    mut shadow_query: Query<(&mut Transform, &mut Visibility), (With<AircraftShadow>, Without<Aircraft>)>,
+) {
+    let Some(assets) = assets else { return };
+    let Ok((mut shadow_transform, mut shadow_visibility)) = shadow_query.single_mut() else { return };
+    let Ok((transform, aircraft, visibility)) = aircraft_query.single() else { return };
+
+    let position = transform.translation;
+    let terrain_height = world_gen.get_terrain_height(&[position.x, position.y, position.z]);
+    let ground = terrain_height.max(0.0);
+    let height = (position.y - ground) / SHADOW_MAX_HEIGHT;
+    if !render_settings.aircraft_shadow || aircraft.crashed || *visibility == Visibility::Hidden || height >= 1.0 {
+        *shadow_visibility = Visibility::Hidden;
+        return;
+    }
+
+    let normal = if terrain_height > 0.0 {
+        world_gen.get_terrain_normal(position.x, position.z)
+    } else {
+        Vec3::Y
+    };
+    let size = SHADOW_SIZE * (1.0 + SHADOW_SPREAD * height.max(0.0));
+    *shadow_transform = Transform::from_translation(Vec3::new(position.x, ground, position.z))
+        .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal))
+        .with_scale(Vec3::new(size, 1.0, size));
+    *shadow_visibility = Visibility::Visible;
+
+    if let Some(material) = materials.get_mut(&assets.shadow) {
+        material.base.base_color.set_alpha(SHADOW_OPACITY * (1.0 - height.max(0.0)).powi(2));
+    }
+}
+
+/// Burn a mark into the ground where an explosion went off near it
+pub fn leave_scorch_mark(
+    trigger: On<SpawnEffect>,
+    world_gen: Res<WorldGenerator>,
+    assets: Option<Res<DecalAssets>>,
+    mut marks: Local<VecDeque<Entity>>,
+    mut commands: Commands,
+) {
+    let Some(assets) = assets else { return };
+    let position = trigger.position;
+    let terrain_height = world_gen.get_terrain_height(&[position.x, position.y, position.z]);
+    if trigger.kind != EffectKind::Explosion || terrain_height <= 0.0 || position.y - terrain_height > SCORCH_MAX_HEIGHT {
+        return;
+    }
+
+    let normal = world_gen.get_terrain_normal(position.x, position.z);
+    let spin = Quat::from_rotation_y(rand::random::<f32>() * std::f32::consts::TAU);
+    let mark = commands
+        .spawn((
+            ForwardDecal,
+            MeshMaterial3d(assets.scorch.clone()),
+            Transform::from_xyz(position.x, terrain_height, position.z)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal) * spin)
+                .with_scale(Vec3::new(SCORCH_SIZE, 1.0, SCORCH_SIZE)),
+        ))
+        .id();
+
+    marks.push_back(mark);
+    while marks.len() > MAX_SCORCH_MARKS {
+        if let Some(oldest) = marks.pop_front() {
+            commands.entity(oldest).try_despawn();
+        }
+    }
+}
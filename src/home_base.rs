@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, ControlInputs, ControlMode, PilotKeys};
+use crate::hud::{calculate_heading, HudPalette};
+use crate::network::{NetworkClient, RespawnAircraft};
+use crate::opponent::{self, Difficulty};
+use crate::world_generation::WorldGenerator;
+
+const RETURN_HOME_KEY: KeyCode = KeyCode::KeyU;
+/// Radius of the circle flown over home once there
+const ORBIT_RADIUS: f32 = 2500.0;
+/// Inside this many orbit radii the autopilot stops heading for home and starts circling it
+const ARRIVAL_RADII: f32 = 1.5;
+/// The autopilot never cruises lower than this above home
+const MIN_CRUISE_HEIGHT: f32 = 1200.0;
+/// Height error, in world units, at which the autopilot climbs or dives at its steepest
+const ALTITUDE_RESPONSE: f32 = 1000.0;
+/// Below this height above the terrain ahead, the autopilot pulls up before anything else
+const MIN_TERRAIN_CLEARANCE: f32 = 600.0;
+/// Seconds of flight ahead checked for rising terrain
+const TERRAIN_LOOK_AHEAD: f32 = 4.0;
+/// Fractions of full throttle held on the way home and while circling
+const CRUISE_THROTTLE: f32 = 0.8;
+const ORBIT_THROTTLE: f32 = 0.6;
+/// Smoothing time of the autopilot's stick inputs, in seconds
+const CONTROL_SMOOTHING: f32 = 0.5;
+const CHEVRON_SIZE: f32 = 14.0;
+
+/// A point to fly back to, and the autopilot that does it
+#[derive(Resource, Default)]
+pub struct HomeBase {
+    pub home: Option<Vec3>,
+    /// The autopilot is flying home, or circling it once there
+    pub returning: bool,
+    /// Stick and rudder the autopilot is holding; the keyboard is ignored while this is set
+    pub inputs: Option<ControlInputs>,
+    pub throttle: Option<f32>,
+    circling: bool,
+    cruise_altitude: f32,
+}
+
+impl HomeBase {
+    pub fn set_home(&mut self, position: Vec3) {
+        self.home = Some(position);
+    }
+
+    pub fn clear_home(&mut self) {
+        self.home = None;
+        self.disengage();
+    }
+
+    pub fn engage(&mut self, aircraft_position: Vec3) {
+        let Some(home) = self.home else { return };
+        self.returning = true;
+        self.circling = false;
+        self.cruise_altitude = aircraft_position.y.max(home.y + MIN_CRUISE_HEIGHT);
+    }
+
+    pub fn disengage(&mut self) {
+        self.returning = false;
+        self.circling = false;
+        self.inputs = None;
+        self.throttle = None;
+    }
+
+    pub fn is_circling(&self) -> bool {
+        self.returning && self.circling
+    }
+}
+
+/// The ground under the point the aircraft respawns at
+pub fn spawn_point(world_gen: &WorldGenerator, client: Option<&NetworkClient>) -> Vec3 {
+    let [x, z] = client
+        .filter(|client| client.connected)
+        .and_then(|client| client.spawn_point)
+        .unwrap_or([0.0, 0.0]);
+    Vec3::new(x, world_gen.get_terrain_height(&[x, 0.0, z]).max(0.0), z)
+}
+
+/// Home defaults to wherever the flight starts, so the chevron has something to point at
+pub fn default_home_to_spawn(
+    mut home: ResMut<HomeBase>,
+    world_gen: Res<WorldGenerator>,
+    client: Option<Res<NetworkClient>>,
+    aircraft_query: Query<(), With<Aircraft>>,
+) {
+    if home.home.is_none() && !aircraft_query.is_empty() {
+        home.set_home(spawn_point(&world_gen, client.as_deref()));
+    }
+}
+
+/// U engages or drops the return, and any stick input takes the aircraft back from the autopilot
+pub fn toggle_return_home(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut home: ResMut<HomeBase>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) {
+    if keyboard.just_pressed(RETURN_HOME_KEY) {
+        if home.returning {
+            home.disengage();
+        } else if let Ok(transform) = aircraft_query.single() {
+            home.engage(transform.translation);
+        }
+    }
+    if home.returning && ControlInputs::from_keyboard(&keyboard, &PilotKeys::PRIMARY) != ControlInputs::default() {
+        home.disengage();
+    }
+}
+
+pub fn cancel_return_on_respawn(_trigger: On<RespawnAircraft>, mut home: ResMut<HomeBase>) {
+    home.disengage();
+}
+
+/// Fly towards home at the cruise altitude, then circle over it, climbing away from terrain on the way
+pub fn fly_home(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    world_gen: Res<WorldGenerator>,
+    mut home: ResMut<HomeBase>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) {
+    if !home.returning || control_mode.physics_paused {
+        return;
+    }
+    let Some(target) = home.home else {
+        home.disengage();
+        return;
+    };
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        home.disengage();
+        return;
+    }
+
+    let pos = transform.translation;
+    let offset = Vec2::new(target.x - pos.x, target.z - pos.z);
+    let distance = offset.length();
+    home.circling = distance <= ORBIT_RADIUS * ARRIVAL_RADII;
+    let track = if home.circling {
+        // Fly the tangent, leaning in or out to hold the orbit radius
+        let outward = (-offset).normalize_or(Vec2::X);
+        let tangent = Vec2::new(-outward.y, outward.x);
+        let drift = ((distance - ORBIT_RADIUS) / ORBIT_RADIUS).clamp(-1.0, 1.0);
+        (tangent - outward * drift).normalize_or(tangent)
+    } else {
+        offset / distance
+    };
+    let climb = ((home.cruise_altitude - pos.y) / ALTITUDE_RESPONSE).clamp(-0.5, 0.5);
+    let mut desired = Vec3::new(track.x, climb, track.y).normalize();
+
+    // Terrain comes first: climb away from anything close below or ahead
+    let ahead = pos + transform.forward().as_vec3() * aircraft.speed * TERRAIN_LOOK_AHEAD;
+    let ground = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z])
+        .max(world_gen.get_terrain_height(&[ahead.x, ahead.y, ahead.z]))
+        .max(0.0);
+    let urgency = 1.0 - (pos.y - ground) / MIN_TERRAIN_CLEARANCE;
+    if urgency > 0.0 {
+        desired.y = desired.y.max(urgency.min(1.0));
+        desired = desired.normalize_or(Vec3::Y);
+    }
+
+    let target_inputs = opponent::steer(transform, desired, Difficulty::Easy);
+    let previous = home.inputs.unwrap_or_default();
+    let blend = (time.delta_secs() / CONTROL_SMOOTHING).min(1.0);
+    home.inputs = Some(ControlInputs {
+        pitch: previous.pitch.lerp(target_inputs.pitch, blend),
+        roll: previous.roll.lerp(target_inputs.roll, blend),
+        yaw: previous.yaw.lerp(target_inputs.yaw, blend),
+    });
+    let throttle = if home.circling { ORBIT_THROTTLE } else { CRUISE_THROTTLE };
+    home.throttle = Some(throttle * aircraft.max_throttle);
+}
+
+/// A chevron under the compass turned towards home, with its bearing and distance
+pub fn home_hud(
+    mut contexts: EguiContexts,
+    home: Res<HomeBase>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) -> Result<(), BevyError> {
+    let Some(target) = home.home else { return Ok(()) };
+    let Ok(transform) = aircraft_query.single() else { return Ok(()) };
+    let offset = target - transform.translation;
+    let bearing = calculate_heading(offset);
+    let relative = (bearing - calculate_heading(transform.forward().as_vec3()) + 540.0) % 360.0 - 180.0;
+    let distance = units.format_distance(world_units_to_meters(Vec2::new(offset.x, offset.z).length()));
+
+    egui::Window::new("Home")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 185.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.horizontal(|ui| {
+                let (response, painter) = ui.allocate_painter(egui::Vec2::splat(CHEVRON_SIZE * 2.0), egui::Sense::hover());
+                let center = response.rect.center();
+                let angle = relative.to_radians();
+                let point = |x: f32, y: f32| {
+                    let (sin, cos) = angle.sin_cos();
+                    center + egui::Vec2::new(x * cos - y * sin, x * sin + y * cos) * CHEVRON_SIZE
+                };
+                let color = if home.returning { egui::Color32::from_rgb(90, 200, 120) } else { palette.text };
+                // The notch makes the chevron concave, so it goes down as its two halves
+                let (tip, notch) = (point(0.0, -0.9), point(0.0, 0.25));
+                for wing in [point(0.7, 0.7), point(-0.7, 0.7)] {
+                    painter.add(egui::Shape::convex_polygon(vec![tip, wing, notch], color, egui::Stroke::NONE));
+                }
+
+                let mode = if home.is_circling() {
+                    " | CIRCLING"
+                } else if home.returning {
+                    " | RTH"
+                } else {
+                    ""
+                };
+                ui.label(format!("HOME {:03.0}° {}{}", bearing, distance, mode));
+            });
+        });
+
+    Ok(())
+}
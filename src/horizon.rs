@@ -0,0 +1,160 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use futures_lite::future;
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::MainCamera;
+use crate::season::{Season, SeasonalTerrain};
+use crate::world_generation::{sample_terrain_vertex, ChunkManager, SharedChunkMaterials, TerrainPalette, WorldGenerator};
+use crate::RenderSettings;
+
+const HORIZON_RINGS: usize = 24;
+const HORIZON_SEGMENTS: usize = 160;
+/// Start this many chunks inside the streamed disc so its ragged edge never shows a gap
+const HORIZON_OVERLAP_CHUNKS: f32 = 2.0;
+/// Rebuild once the streaming center has moved this many chunks; under the overlap so the seam stays covered
+const HORIZON_REBUILD_CHUNKS: f32 = 1.5;
+/// Sink the imposter so real chunks win wherever the two overlap
+const HORIZON_SINK: f32 = 40.0;
+/// Matches the water material, since the imposter has no water plane of its own
+const HORIZON_WATER_COLOR: Color = Color::srgb(0.15, 0.35, 0.7);
+
+/// Far-terrain imposter beyond the streamed chunks
+#[derive(Component)]
+pub struct HorizonTerrain;
+
+/// The build in flight and what the current mesh was built for
+#[derive(Default)]
+pub struct HorizonBuild {
+    task: Option<(Task<Mesh>, Vec2)>,
+    center: Option<Vec2>,
+    inner_radius: f32,
+    outer_radius: f32,
+}
+
+/// Polar grid around `center` with radii spaced geometrically, so detail thins out with distance
+fn build_horizon_mesh(
+    world_gen: &WorldGenerator,
+    center: Vec2,
+    inner_radius: f32,
+    outer_radius: f32,
+    smoothness: f32,
+    season: SeasonalTerrain,
+    palette: &TerrainPalette,
+) -> Mesh {
+    let water_color = HORIZON_WATER_COLOR.to_linear().to_f32_array();
+    let mut positions = Vec::with_capacity(HORIZON_RINGS * HORIZON_SEGMENTS);
+    let mut colors = Vec::with_capacity(HORIZON_RINGS * HORIZON_SEGMENTS);
+
+    for ring in 0..HORIZON_RINGS {
+        let radius = inner_radius * (outer_radius / inner_radius).powf(ring as f32 / (HORIZON_RINGS - 1) as f32);
+        for segment in 0..HORIZON_SEGMENTS {
+            let angle = segment as f32 / HORIZON_SEGMENTS as f32 * std::f32::consts::TAU;
+            let (x, z) = (angle.cos() * radius, angle.sin() * radius);
+            let (height, color) =
+                sample_terrain_vertex(world_gen, &[center.x + x, 0.0, center.y + z], smoothness, season, palette);
+            positions.push([x, height.max(0.0) - HORIZON_SINK, z]);
+            colors.push(if height > 0.0 { color } else { water_color });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((HORIZON_RINGS - 1) * HORIZON_SEGMENTS * 6);
+    for ring in 0..HORIZON_RINGS - 1 {
+        for segment in 0..HORIZON_SEGMENTS {
+            let next = (segment + 1) % HORIZON_SEGMENTS;
+            let (a, b) = ((ring * HORIZON_SEGMENTS + segment) as u32, (ring * HORIZON_SEGMENTS + next) as u32);
+            let (c, d) = (a + HORIZON_SEGMENTS as u32, b + HORIZON_SEGMENTS as u32);
+            indices.extend_from_slice(&[a, b, c, b, d, c]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices));
+    mesh.compute_smooth_normals();
+    mesh
+}
+
+/// Rebuild the horizon ring off-thread whenever the streamed disc has moved or resized
+pub fn update_horizon_terrain(
+    mut commands: Commands,
+    mut build: Local<HorizonBuild>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    (world_gen, chunk_manager, render_settings): (Res<WorldGenerator>, Res<ChunkManager>, Res<RenderSettings>),
+    (season, palette): (Res<Season>, Res<TerrainPalette>),
+    shared_materials: Option<Res<SharedChunkMaterials>>,
+    projection: Query<&Projection, With<MainCamera>>,
+    mut horizon: Query<(&mut Mesh3d, &mut Transform, &mut Visibility), With<HorizonTerrain>>,
+) {
+    let Some(shared_materials) = shared_materials else { return };
+    let Some((center_x, center_z)) = chunk_manager.last_camera_chunk else { return };
+    let center = Vec2::new(center_x as f32, center_z as f32) * CHUNK_SIZE;
+    let far = match projection.single() {
+        Ok(Projection::Perspective(perspective)) => perspective.far,
+        _ => return,
+    };
+    let inner_radius = (chunk_manager.render_distance as f32 - HORIZON_OVERLAP_CHUNKS).max(1.0) * CHUNK_SIZE;
+    // A new seed invalidates the current mesh even if a build is already in flight
+    if world_gen.is_changed() {
+        build.center = None;
+    }
+
+    // Nothing to draw when the chunks already reach the far plane
+    if !render_settings.horizon_terrain || inner_radius >= far {
+        build.task = None;
+        build.center = None;
+        for (_, _, mut visibility) in &mut horizon {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    }
+
+    if let Some((task, task_center)) = &mut build.task
+        && let Some(mesh) = future::block_on(future::poll_once(task))
+    {
+        let translation = Vec3::new(task_center.x, 0.0, task_center.y);
+        build.task = None;
+        match horizon.single_mut() {
+            Ok((mut mesh_handle, mut transform, mut visibility)) => {
+                meshes.remove(&mesh_handle.0);
+                mesh_handle.0 = meshes.add(mesh);
+                transform.translation = translation;
+                *visibility = Visibility::Visible;
+            }
+            Err(_) => {
+                commands.spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(shared_materials.terrain_material.clone()),
+                    Transform::from_translation(translation),
+                    HorizonTerrain,
+                    bevy::light::NotShadowCaster,
+                ));
+            }
+        }
+        return;
+    }
+
+    let stale = build.center.is_none_or(|built| built.distance(center) > HORIZON_REBUILD_CHUNKS * CHUNK_SIZE)
+        || build.inner_radius != inner_radius
+        || build.outer_radius != far;
+    if build.task.is_some() || !stale {
+        return;
+    }
+
+    let world_gen = world_gen.clone();
+    let smoothness = render_settings.terrain_smoothness;
+    let seasonal = season.terrain();
+    let palette = palette.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        build_horizon_mesh(&world_gen, center, inner_radius, far, smoothness, seasonal, &palette)
+    });
+    build.task = Some((task, center));
+    build.center = Some(center);
+    build.inner_radius = inner_radius;
+    build.outer_radius = far;
+}
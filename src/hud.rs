@@ -1,8 +1,37 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui::{self, Frame}};
-use crate::{consts::world_units_to_meters, controls::{Aircraft, ControlMode, FlightMode, MainCamera, Wind}};
-use crate::network::{self, NetworkClient, DEFAULT_SERVER_ADDR};
-use crossbeam_channel;
+use crate::{consts::{world_units_to_meters, UnitSystem}, controls::{Aircraft, ControlMode, Difficulty, FlightMode, MainCamera, Wind}};
+use crate::network::{self, ClientRole, DEFAULT_SERVER_ADDR};
+use crate::day_cycle::{DayNightCycle, SunTimes};
+use crate::glider::Variometer;
+use crate::engine::{Engine, EngineState};
+use crate::split_screen::SplitScreen;
+use crate::graphics::GraphicsPreset;
+
+/// How long the note on where a disconnect left the aircraft stays on screen
+const DISCONNECT_NOTICE_SECS: f32 = 8.0;
+
+/// Colours of the flight instruments, overridable from the tuning asset
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct HudPalette {
+    pub window_fill: egui::Color32,
+    pub warning_fill: egui::Color32,
+    pub text: egui::Color32,
+    pub horizon_sky: egui::Color32,
+    pub horizon_ground: egui::Color32,
+}
+
+impl Default for HudPalette {
+    fn default() -> Self {
+        Self {
+            window_fill: egui::Color32::from_rgba_unmultiplied(50, 50, 50, 100),
+            warning_fill: egui::Color32::from_rgba_unmultiplied(200, 0, 0, 200),
+            text: egui::Color32::WHITE,
+            horizon_sky: egui::Color32::from_rgb(50, 120, 200),
+            horizon_ground: egui::Color32::from_rgb(100, 70, 40),
+        }
+    }
+}
 
 pub fn flight_hud_system(
     mut contexts: EguiContexts,
@@ -11,35 +40,46 @@ pub fn flight_hud_system(
     camera_query: Query<&Transform, With<MainCamera>>,
     wind: Res<Wind>,
     remote_players_query: Query<&Transform, With<network::RemotePlayer>>,
-) {
+    (lost_contacts, time, units): (Res<crate::lost_contacts::LostContacts>, Res<Time>, Res<UnitSystem>),
+    day_cycle: Res<DayNightCycle>,
+    variometer: Res<Variometer>,
+    (engine, difficulty, split_screen): (Res<Engine>, Res<Difficulty>, Res<SplitScreen>),
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
     if control_mode.mode == FlightMode::FreeFlight {
-        return;
+        return Ok(());
     }
 
-    let Ok((plane_transform, aircraft)) = aircraft_query.single() else { return };
-    let Ok(_camera_transform) = camera_query.single() else { return };
+    let Ok((plane_transform, aircraft)) = aircraft_query.single() else { return Ok(()) };
+    let Ok(_camera_transform) = camera_query.single() else { return Ok(()) };
 
-    let altitude = world_units_to_meters(plane_transform.translation.y);
-    let speed = world_units_to_meters(aircraft.speed);
+    let units = *units;
+    let altitude_meters = world_units_to_meters(plane_transform.translation.y);
+    let speed_mps = world_units_to_meters(aircraft.speed);
+    let altitude = units.altitude(altitude_meters);
+    let speed = units.speed(speed_mps);
+    let max_speed = units.speed(world_units_to_meters(aircraft.max_speed));
     
     let forward = plane_transform.forward().as_vec3();
     let heading = calculate_heading(forward);
     let pitch = calculate_pitch(forward);
     let roll = calculate_roll(plane_transform);
     
-    let ctx = contexts.ctx_mut().unwrap();
-    let window_frame = Frame::default().fill(bevy_egui::egui::Color32::from_rgba_unmultiplied(50, 50, 50, 100));
+    let ctx = contexts.ctx_mut()?;
+    let window_frame = Frame::default().fill(palette.window_fill);
+    // With a second pilot on the right half, the instruments move into the left one
+    let split_shift = if split_screen.enabled { -ctx.content_rect().width() * 0.5 } else { 0.0 };
     
     // Display crash warning
     if aircraft.crashed {
         egui::Window::new("Crash")
             .title_bar(false)
             .resizable(false)
-            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .anchor(egui::Align2::CENTER_CENTER, [split_shift * 0.5, 0.0])
             .fixed_size([300.0, 100.0])
-            .frame(Frame::default().fill(egui::Color32::from_rgba_unmultiplied(200, 0, 0, 200)))
+            .frame(Frame::default().fill(palette.warning_fill))
             .show(ctx, |ui| {
-                ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+                ui.visuals_mut().override_text_color = Some(palette.text);
                 ui.vertical_centered(|ui| {
                     ui.add_space(20.0);
                     ui.label(egui::RichText::new("⚠ AIRCRAFT CRASHED ⚠").size(24.0).strong());
@@ -52,14 +92,14 @@ pub fn flight_hud_system(
     egui::Window::new("Attitude")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::RIGHT_BOTTOM, [-20.0, -20.0])
+        .anchor(egui::Align2::RIGHT_BOTTOM, [split_shift - 20.0, -20.0])
         .fixed_size([180.0, 220.0])
         .frame(window_frame)
         .show(ctx, |ui| {
-            ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+            ui.visuals_mut().override_text_color = Some(palette.text);
             ui.vertical_centered(|ui| {
                 ui.label(egui::RichText::new("ATTITUDE").size(12.0));
-                draw_artificial_horizon(ui, pitch, roll);
+                draw_artificial_horizon(ui, pitch, roll, &palette);
                 ui.horizontal(|ui| {
                     ui.label(format!("Pitch: {:.1}°", pitch));
                     ui.label(format!("Roll: {:.1}°", roll));
@@ -70,45 +110,70 @@ pub fn flight_hud_system(
     egui::Window::new("Altitude")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::RIGHT_BOTTOM, [-210.0, -20.0])
+        .anchor(egui::Align2::RIGHT_BOTTOM, [split_shift - 210.0, -20.0])
         .fixed_size([110.0, 200.0])
         .frame(window_frame)
         .show(ctx, |ui| {
-            ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+            ui.visuals_mut().override_text_color = Some(palette.text);
             ui.vertical_centered(|ui| {
                 ui.label(egui::RichText::new("ALTITUDE").size(12.0));
                 draw_altitude_tape(ui, altitude);
                 ui.horizontal(|ui| {
-                    ui.label(format!("{}", altitude));
+                    ui.label(units.format_altitude(altitude_meters));
                 });
             });
         });
     
-    egui::Window::new("Throttle")
+    // Gliders have no engine, so the variometer takes the throttle's place
+    let is_glider = aircraft.plane_type == network::PlaneType::Glider;
+    if !is_glider {
+        egui::Window::new("Throttle")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::LEFT_BOTTOM, [150.0, -20.0])
+            .fixed_size([120.0, 70.0])
+            .frame(window_frame)
+            .show(ctx, |ui| {
+                ui.visuals_mut().override_text_color = Some(palette.text);
+                ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new("THROTTLE").size(12.0));
+                    draw_throttle_gauge(ui, aircraft.throttle, aircraft.max_throttle, aircraft.speed, aircraft.max_speed);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("0{:?}", aircraft.throttle));
+                    });
+                    let engine_text = egui::RichText::new(format!("{} {:.0} RPM", engine.state.label(), engine.rpm)).size(11.0);
+                    if engine.state == EngineState::FlamedOut {
+                        ui.label(engine_text.color(palette.warning_fill.to_opaque()));
+                    } else {
+                        ui.label(engine_text);
+                    }
+                });
+            });
+    }
+    
+    egui::Window::new("Variometer")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::LEFT_BOTTOM, [150.0, -20.0])
-        .fixed_size([120.0, 70.0])
+        .anchor(egui::Align2::LEFT_BOTTOM, [if is_glider { 150.0 } else { 290.0 }, -20.0])
+        .fixed_size([110.0, 130.0])
         .frame(window_frame)
         .show(ctx, |ui| {
-            ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+            ui.visuals_mut().override_text_color = Some(palette.text);
             ui.vertical_centered(|ui| {
-                ui.label(egui::RichText::new("THROTTLE").size(12.0));
-                draw_throttle_gauge(ui, aircraft.throttle, aircraft.max_throttle, aircraft.speed, aircraft.max_speed);
-                ui.horizontal(|ui| {
-                    ui.label(format!("0{:?}", aircraft.throttle));
-                });
+                ui.label(egui::RichText::new("VARIO").size(12.0));
+                draw_variometer(ui, variometer.climb_rate);
+                ui.label(units.format_climb_rate(variometer.climb_rate));
             });
         });
     
     egui::Window::new("Heading")
         .title_bar(false)
         .resizable(false)
-        .anchor(egui::Align2::CENTER_TOP, [0.0, 20.0])
+        .anchor(egui::Align2::CENTER_TOP, [split_shift * 0.5, 20.0])
         .fixed_size([150.0, 150.0])
         .frame(window_frame)
         .show(ctx, |ui| {
-            ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+            ui.visuals_mut().override_text_color = Some(palette.text);
             ui.vertical_centered(|ui| {
                 ui.label("HEADING & WIND");
                 
@@ -127,16 +192,42 @@ pub fn flight_hud_system(
                         }
                     })
                     .collect();
+                let lost_headings: Vec<(f32, f32)> = lost_contacts.markers(time.elapsed_secs())
+                    .map(|(_, position, fade)| (calculate_heading(position - plane_transform.translation), fade))
+                    .collect();
                 
-                draw_wind_compass(ui, heading, wind_heading, wind.wind_speed, &player_headings);
+                draw_wind_compass(ui, heading, wind_heading, wind.wind_speed, &player_headings, &lost_headings);
                 
                 ui.label(egui::RichText::new(format!("HDG: {:.0}°", heading))
                     .size(11.0));
-                ui.label(egui::RichText::new(format!("Wind: {:.0}° @ {:.1}", wind_heading, wind.wind_speed))
+                ui.label(egui::RichText::new(format!("Wind: {:.0}° @ {}", wind_heading, units.format_speed(world_units_to_meters(wind.wind_speed))))
                     .size(10.0));
             });
         });
     
+    egui::Window::new("Clock")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 20.0])
+        .frame(window_frame)
+        .show(ctx, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(crate::format_clock_hours(day_cycle.clock_hours())).size(18.0).strong());
+                ui.label(egui::RichText::new(day_cycle.date_label()).size(11.0));
+                let sun_times = match day_cycle.sun_times() {
+                    SunTimes::Normal { sunrise, sunset } => format!(
+                        "☀ {} - {}",
+                        crate::format_clock_hours(sunrise),
+                        crate::format_clock_hours(sunset),
+                    ),
+                    SunTimes::MidnightSun => "☀ Midnight sun".to_string(),
+                    SunTimes::PolarNight => "☾ Polar night".to_string(),
+                };
+                ui.label(egui::RichText::new(sun_times).size(10.0));
+            });
+        });
+    
     egui::Window::new("Airspeed")
         .title_bar(false)
         .resizable(false)
@@ -144,18 +235,66 @@ pub fn flight_hud_system(
         .anchor(egui::Align2::LEFT_BOTTOM, [20.0, -20.0])
         .frame(window_frame)
         .show(ctx, |ui| {
-            ui.visuals_mut().override_text_color = Some(egui::Color32::WHITE);
+            ui.visuals_mut().override_text_color = Some(palette.text);
             ui.vertical_centered(|ui| {
                 ui.label(egui::RichText::new("AIRSPEED").size(12.0));
-                draw_airspeed_tape(ui, speed, aircraft);
+                draw_airspeed_tape(ui, speed, max_speed);
                 ui.horizontal(|ui| {
-                    ui.label(format!("{}", aircraft.speed));
+                    ui.label(units.format_speed(speed_mps));
                 });
+                // Angle of attack only matters to the stall model that uses it
+                if difficulty.realistic_stalls {
+                    if aircraft.spin_rate != 0.0 {
+                        ui.label(egui::RichText::new("STALL").size(11.0).strong().color(palette.warning_fill.to_opaque()));
+                    } else {
+                        ui.label(egui::RichText::new(format!("AoA {:.0}°", aircraft.angle_of_attack)).size(11.0));
+                    }
+                }
             });
         });
+
+    Ok(())
+}
+
+/// Airspeed, attitude, altitude and heading side by side, for any egui context given over to the gauges
+pub(crate) fn draw_instrument_panel(
+    ui: &mut egui::Ui,
+    plane_transform: &Transform,
+    aircraft: &Aircraft,
+    wind: &Wind,
+    units: UnitSystem,
+    palette: &HudPalette,
+) {
+    let forward = plane_transform.forward().as_vec3();
+    let heading = calculate_heading(forward);
+    let altitude_meters = world_units_to_meters(plane_transform.translation.y);
+    let speed_mps = world_units_to_meters(aircraft.speed);
+    let wind_heading = calculate_wind_heading(wind);
+
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new("AIRSPEED").size(12.0));
+            draw_airspeed_tape(ui, units.speed(speed_mps), units.speed(world_units_to_meters(aircraft.max_speed)));
+            ui.label(units.format_speed(speed_mps));
+        });
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new("ATTITUDE").size(12.0));
+            draw_artificial_horizon(ui, calculate_pitch(forward), calculate_roll(plane_transform), palette);
+        });
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new("ALTITUDE").size(12.0));
+            draw_altitude_tape(ui, units.altitude(altitude_meters));
+            ui.label(units.format_altitude(altitude_meters));
+        });
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new("HEADING").size(12.0));
+            draw_wind_compass(ui, heading, wind_heading, wind.wind_speed, &[], &[]);
+            ui.label(format!("HDG: {:.0}°", heading));
+        });
+    });
 }
 
-fn calculate_heading(forward: Vec3) -> f32 {
+pub(crate) fn calculate_heading(forward: Vec3) -> f32 {
     let angle = f32::atan2(forward.x, -forward.z).to_degrees() + 90.0;
     if angle < 0.0 {
         360.0 + angle
@@ -166,12 +305,12 @@ fn calculate_heading(forward: Vec3) -> f32 {
     }
 }
 
-fn calculate_pitch(forward: Vec3) -> f32 {
+pub(crate) fn calculate_pitch(forward: Vec3) -> f32 {
     let horizontal_magnitude = (forward.x * forward.x + forward.z * forward.z).sqrt();
     f32::atan2(forward.y, horizontal_magnitude).to_degrees()
 }
 
-fn calculate_roll(transform: &Transform) -> f32 {
+pub(crate) fn calculate_roll(transform: &Transform) -> f32 {
     let up = transform.up().as_vec3();
     let right = transform.right().as_vec3();
     
@@ -190,7 +329,8 @@ fn calculate_wind_heading(wind: &Wind) -> f32 {
     }
 }
 
-fn draw_wind_compass(ui: &mut egui::Ui, aircraft_heading: f32, wind_heading: f32, wind_speed: f32, player_headings: &[f32]) {
+/// `lost_headings` are bearings to lost contacts, with how far each has faded
+fn draw_wind_compass(ui: &mut egui::Ui, aircraft_heading: f32, wind_heading: f32, wind_speed: f32, player_headings: &[f32], lost_headings: &[(f32, f32)]) {
     let (response, painter) = ui.allocate_painter(
         egui::Vec2::new(90.0, 90.0),
         egui::Sense::hover(),
@@ -262,6 +402,20 @@ fn draw_wind_compass(ui: &mut egui::Ui, aircraft_heading: f32, wind_heading: f32
         );
     }
     
+    for &(lost_heading, fade) in lost_headings {
+        let lost_angle_rad = (lost_heading - aircraft_heading - 90.0).to_radians();
+        let lost_pos = egui::Pos2::new(
+            center.x + radius * lost_angle_rad.cos(),
+            center.y + radius * lost_angle_rad.sin(),
+        );
+        
+        painter.circle_stroke(
+            lost_pos,
+            3.5,
+            egui::Stroke::new(1.5, egui::Color32::from_rgba_unmultiplied(255, 200, 50, (fade * 255.0) as u8)),
+        );
+    }
+    
     painter.line_segment(
         [
             egui::Pos2::new(center.x, center.y - radius - 7.0),
@@ -310,7 +464,7 @@ fn draw_wind_compass(ui: &mut egui::Ui, aircraft_heading: f32, wind_heading: f32
     painter.line_segment([tip, right_point], egui::Stroke::new(2.5, wind_color));
 }
 
-fn draw_artificial_horizon(ui: &mut egui::Ui, pitch: f32, roll: f32) {
+pub(crate) fn draw_artificial_horizon(ui: &mut egui::Ui, pitch: f32, roll: f32, palette: &HudPalette) {
     ui.vertical_centered(|ui| {
         let (response, painter) = ui.allocate_painter(
             egui::Vec2::new(160.0, 160.0),
@@ -320,8 +474,8 @@ fn draw_artificial_horizon(ui: &mut egui::Ui, pitch: f32, roll: f32) {
         let center = response.rect.center();
         let radius = 75.0;
         
-        let sky_color = egui::Color32::from_rgb(50, 120, 200);
-        let ground_color = egui::Color32::from_rgb(100, 70, 40);
+        let sky_color = palette.horizon_sky;
+        let ground_color = palette.horizon_ground;
         
         let pitch_offset = -(pitch / 90.0) * radius;
         let roll_rad = roll.to_radians();
@@ -509,7 +663,8 @@ fn draw_altitude_tape(ui: &mut egui::Ui, altitude: f32) {
     });
 }
 
-fn draw_airspeed_tape(ui: &mut egui::Ui, speed: f32, aircraft: &Aircraft) {
+/// `speed` and `aircraft_max_speed` are in the selected display unit
+fn draw_airspeed_tape(ui: &mut egui::Ui, speed: f32, aircraft_max_speed: f32) {
     ui.vertical(|ui| {
         let (response, painter) = ui.allocate_painter(
             egui::Vec2::new(90.0, 160.0),
@@ -518,8 +673,6 @@ fn draw_airspeed_tape(ui: &mut egui::Ui, speed: f32, aircraft: &Aircraft) {
         
         let rect = response.rect;
         let center_y = rect.center().y;
-
-        let aircraft_max_speed = world_units_to_meters(aircraft.max_speed);
         
         let speed_ranges = [
             (0.0,                      aircraft_max_speed * 0.3, egui::Color32::from_rgb(150, 0, 0)),
@@ -613,6 +766,54 @@ fn draw_airspeed_tape(ui: &mut egui::Ui, speed: f32, aircraft: &Aircraft) {
     });
 }
 
+/// Round climb-rate dial: ±5 m/s, zero at nine o'clock like a sailplane vario
+fn draw_variometer(ui: &mut egui::Ui, climb_rate: f32) {
+    const FULL_SCALE: f32 = 5.0;
+
+    let (response, painter) = ui.allocate_painter(
+        egui::Vec2::new(90.0, 90.0),
+        egui::Sense::hover(),
+    );
+    let center = response.rect.center();
+    let radius = 40.0;
+
+    painter.circle_filled(center, radius, egui::Color32::from_rgb(30, 30, 30));
+    painter.circle_stroke(center, radius, egui::Stroke::new(1.5, egui::Color32::WHITE));
+
+    // Zero points left; climb sweeps up the top half, sink down the bottom half
+    let rate_to_angle = |rate: f32| std::f32::consts::PI - (rate / FULL_SCALE).clamp(-1.0, 1.0) * std::f32::consts::FRAC_PI_2 * 1.8;
+    let point_at = |angle: f32, r: f32| egui::Pos2::new(center.x + r * angle.cos(), center.y - r * angle.sin());
+
+    for rate in -5i32..=5 {
+        let angle = rate_to_angle(rate as f32);
+        let tick = if rate % 5 == 0 { 10.0 } else { 5.0 };
+        painter.line_segment(
+            [point_at(angle, radius - tick), point_at(angle, radius)],
+            egui::Stroke::new(1.5, egui::Color32::WHITE),
+        );
+        if rate != 0 && rate % 5 == 0 {
+            painter.text(
+                point_at(angle, radius - 18.0),
+                egui::Align2::CENTER_CENTER,
+                format!("{}", rate.abs()),
+                egui::FontId::proportional(11.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+
+    let needle_color = if climb_rate >= 0.0 {
+        egui::Color32::from_rgb(80, 220, 80)
+    } else {
+        egui::Color32::from_rgb(230, 90, 60)
+    };
+    painter.line_segment(
+        [center, point_at(rate_to_angle(climb_rate), radius - 4.0)],
+        egui::Stroke::new(2.5, needle_color),
+    );
+    painter.circle_filled(center, 3.0, egui::Color32::WHITE);
+}
+
 fn draw_throttle_gauge(ui: &mut egui::Ui, throttle: f32, max_throttle: f32, speed: f32, max_speed: f32) {
     ui.vertical_centered(|ui| {
         let (response, painter) = ui.allocate_painter(
@@ -814,28 +1015,19 @@ pub enum SettingsTab {
     Advanced,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GraphicsPreset {
-    Low,
-    High,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PlaneType {
-    Light,
-    Jet,
-}
-
 #[derive(Resource)]
 pub struct MultiplayerMenu {
     pub server_address: String,
     pub player_name: String,
     pub connection_status: String,
     pub connecting: bool,
-    pub connection_receiver: Option<crossbeam_channel::Receiver<Result<NetworkClient, String>>>,
     pub settings_tab: SettingsTab,
+    /// Closed from its title bar, reopened from the pause menu
+    pub settings_open: bool,
     pub graphics_preset: GraphicsPreset,
-    pub plane_type: PlaneType,
+    /// Skip certificate checks for servers with self-signed certificates; off unless the player opts in
+    pub accept_self_signed: bool,
+    pub role: ClientRole,
 }
 
 impl Default for MultiplayerMenu {
@@ -845,54 +1037,67 @@ impl Default for MultiplayerMenu {
             player_name: "Pilot".to_string(),
             connection_status: String::new(),
             connecting: false,
-            connection_receiver: None,
             settings_tab: SettingsTab::Basic,
+            settings_open: true,
             graphics_preset: GraphicsPreset::Low,
-            plane_type: PlaneType::Light,
+            accept_self_signed: false,
+            role: ClientRole::Pilot,
         }
     }
 }
 
-pub fn auto_connect_on_startup(
-    mut menu: ResMut<MultiplayerMenu>,
-) {
-    let address = DEFAULT_SERVER_ADDR.to_string();
-    let player_name = menu.player_name.clone();
+impl MultiplayerMenu {
+    /// Join `server_address` with the name and role picked in the menu
+    pub fn connect_request(&self) -> network::ConnectRequest {
+        network::ConnectRequest {
+            address: self.server_address.clone(),
+            player_name: self.player_name.clone(),
+            role: self.role,
+            accept_self_signed: self.accept_self_signed,
+        }
+    }
+}
+
+pub fn show_connecting(_trigger: On<network::ConnectRequest>, mut menu: ResMut<MultiplayerMenu>) {
     menu.connecting = true;
-    menu.connection_status = "Auto-connecting...".to_string();
-    
-    let (tx, rx) = crossbeam_channel::unbounded();
-    menu.connection_receiver = Some(rx);
-    
-    let value = address.clone();
-    std::thread::spawn(move || {
-        let result = network::TOKIO_RUNTIME.block_on(network::connect_to_server(&value, player_name));
-        let _ = tx.send(result);
-    });
-    
-    println!("🌐 Attempting auto-connect to {}", address);
+    menu.connection_status.clear();
 }
 
-pub fn process_connection_results(
-    mut menu: ResMut<MultiplayerMenu>,
-    mut commands: Commands,
-    world_gen: Res<crate::world_generation::WorldGenerator>,
-) {
-    if let Some(rx) = &menu.connection_receiver {
-        if let Ok(result) = rx.try_recv() {
-            menu.connecting = false;
-            menu.connection_receiver = None;
-            
-            match result {
-                Ok(mut client) => {
-                    menu.connection_status = "Connected!".to_string();
-                    client.original_seed = world_gen.seed;
-                    commands.insert_resource(client);
-                }
-                Err(e) => {
-                    menu.connection_status = format!("Connection failed: {}", e);
-                }
-            }
-        }
+pub fn show_connected(trigger: On<network::Connected>, mut menu: ResMut<MultiplayerMenu>) {
+    menu.connecting = false;
+    menu.connection_status = format!("Connected to {}", trigger.address);
+}
+
+pub fn show_connection_failed(trigger: On<network::ConnectionFailed>, mut menu: ResMut<MultiplayerMenu>) {
+    menu.connecting = false;
+    menu.connection_status = format!("Connection to {} failed: {}", trigger.address, trigger.error);
+}
+
+pub fn show_disconnected(trigger: On<network::Disconnected>, mut menu: ResMut<MultiplayerMenu>) {
+    menu.connection_status = if trigger.lost { "Connection lost" } else { "Disconnected" }.to_string();
+}
+
+pub fn disconnect_notice_hud(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    notice: Res<network::DisconnectNotice>,
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
+    let Some(shown_at) = notice.shown_at else { return Ok(()) };
+    if time.elapsed_secs() - shown_at > DISCONNECT_NOTICE_SECS {
+        return Ok(());
     }
+
+    egui::Window::new("Disconnected")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, -140.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("DISCONNECTED").size(12.0));
+            ui.label(&notice.text);
+        });
+
+    Ok(())
 }
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, ControlMode, FlightMode};
+use crate::day_cycle::DayNightCycle;
+use crate::hud::HudPalette;
+use crate::network::RespawnAircraft;
+use crate::season::Season;
+use crate::world_generation::WorldGenerator;
+
+const DEICE_KEY: KeyCode = KeyCode::KeyH;
+/// Standard lapse rate, degrees Celsius lost per metre of altitude
+pub const LAPSE_RATE: f32 = 0.0065;
+/// How much colder the air is at midnight than under a high sun
+const NIGHT_COOLING: f32 = 6.0;
+/// Climate humidity above which the air carries enough supercooled water to ice
+const ICING_HUMIDITY: f32 = 0.55;
+/// Ice forms between these temperatures and is worst at `PEAK_ICING_TEMPERATURE`; colder water is already frozen
+const ICING_MAX_TEMPERATURE: f32 = 2.0;
+const ICING_MIN_TEMPERATURE: f32 = -20.0;
+const PEAK_ICING_TEMPERATURE: f32 = -5.0;
+/// Fraction of a full load of ice gathered per second in the worst conditions
+const ACCRETION_RATE: f32 = 1.0 / 90.0;
+/// Fraction shed per second per degree above `ICING_MAX_TEMPERATURE`
+const MELT_RATE: f32 = 1.0 / 400.0;
+/// Fraction the de-ice system sheds per second
+const DEICE_RATE: f32 = 1.0 / 25.0;
+/// The indicator shows up this close to icing temperatures, so the pilot sees the trend coming
+const INDICATOR_MARGIN: f32 = 3.0;
+
+/// Outside air at the local aircraft and the pilot's de-ice switch
+#[derive(Resource, Default)]
+pub struct Icing {
+    pub deice: bool,
+    /// Degrees Celsius
+    pub outside_air_temperature: f32,
+    /// Ice is forming right now
+    pub accreting: bool,
+}
+
+/// Temperature in Celsius and 0..1 humidity at a point: the climate at sea level, cooled with height and at night
+pub fn outside_air(world_gen: &WorldGenerator, season: &Season, cycle: &DayNightCycle, pos: Vec3) -> (f32, f32) {
+    let (temperature, humidity) = world_gen.get_climate(&[pos.x, pos.y, pos.z]);
+    let (_, sea_level) = crate::map_temperature(temperature + season.temperature_shift());
+    let sun_heating = cycle.sun_altitude().sin().max(0.0);
+    let celsius = sea_level - LAPSE_RATE * world_units_to_meters(pos.y.max(0.0)) - NIGHT_COOLING * (1.0 - sun_heating);
+    (celsius, humidity)
+}
+
+/// 0..1 rate of ice forming in air at this temperature and humidity
+fn icing_severity(celsius: f32, humidity: f32) -> f32 {
+    if humidity <= ICING_HUMIDITY || !(ICING_MIN_TEMPERATURE..ICING_MAX_TEMPERATURE).contains(&celsius) {
+        return 0.0;
+    }
+    let moisture = (humidity - ICING_HUMIDITY) / (1.0 - ICING_HUMIDITY);
+    let temperature = if celsius > PEAK_ICING_TEMPERATURE {
+        (ICING_MAX_TEMPERATURE - celsius) / (ICING_MAX_TEMPERATURE - PEAK_ICING_TEMPERATURE)
+    } else {
+        (celsius - ICING_MIN_TEMPERATURE) / (PEAK_ICING_TEMPERATURE - ICING_MIN_TEMPERATURE)
+    };
+    moisture * temperature
+}
+
+pub fn clear_ice_on_respawn(_trigger: On<RespawnAircraft>, mut aircraft_query: Query<&mut Aircraft>) {
+    if let Ok(mut aircraft) = aircraft_query.single_mut() {
+        aircraft.ice = 0.0;
+    }
+}
+
+pub fn toggle_deice(keyboard: Res<ButtonInput<KeyCode>>, mut icing: ResMut<Icing>) {
+    if keyboard.just_pressed(DEICE_KEY) {
+        icing.deice = !icing.deice;
+    }
+}
+
+/// Build ice on the local aircraft in cold, humid air and shed it in warm air or with de-ice on
+pub fn accrete_ice(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    world_gen: Res<WorldGenerator>,
+    season: Res<Season>,
+    day_cycle: Res<DayNightCycle>,
+    mut icing: ResMut<Icing>,
+    mut aircraft_query: Query<(&Transform, &mut Aircraft)>,
+) {
+    let Ok((transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    let (celsius, humidity) = outside_air(&world_gen, &season, &day_cycle, transform.translation);
+    let severity = icing_severity(celsius, humidity);
+    icing.outside_air_temperature = celsius;
+    icing.accreting = severity > 0.0 && !icing.deice;
+
+    if aircraft.crashed {
+        aircraft.ice = 0.0;
+        return;
+    }
+    if control_mode.physics_paused {
+        return;
+    }
+    let melt = (celsius - ICING_MAX_TEMPERATURE).max(0.0) * MELT_RATE;
+    let shed = melt + if icing.deice { DEICE_RATE } else { 0.0 };
+    let rate = if icing.deice { -shed } else { severity * ACCRETION_RATE - shed };
+    aircraft.ice = (aircraft.ice + rate * time.delta_secs()).clamp(0.0, 1.0);
+}
+
+/// Ice load, outside air temperature and the de-ice switch, shown once icing is close
+pub fn ice_hud(
+    mut contexts: EguiContexts,
+    icing: Res<Icing>,
+    control_mode: Res<ControlMode>,
+    units: Res<UnitSystem>,
+    palette: Res<HudPalette>,
+    aircraft_query: Query<&Aircraft>,
+) -> Result<(), BevyError> {
+    if control_mode.mode == FlightMode::FreeFlight {
+        return Ok(());
+    }
+    let Ok(aircraft) = aircraft_query.single() else { return Ok(()) };
+    let near_icing = icing.outside_air_temperature <= ICING_MAX_TEMPERATURE + INDICATOR_MARGIN;
+    if aircraft.ice <= 0.0 && !icing.deice && !near_icing {
+        return Ok(());
+    }
+    let fahrenheit = icing.outside_air_temperature * 9.0 / 5.0 + 32.0;
+
+    egui::Window::new("Ice")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [150.0, -150.0])
+        .frame(Frame::default().fill(if icing.accreting { palette.warning_fill } else { palette.window_fill }).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("ICE").size(12.0));
+            ui.add(egui::ProgressBar::new(aircraft.ice).desired_width(120.0).text(format!("{:.0}%", aircraft.ice * 100.0)));
+            ui.label(format!("OAT {}", units.format_temperature(icing.outside_air_temperature, fahrenheit)));
+            let deice = if icing.deice { "De-ice ON" } else { "De-ice off" };
+            ui.label(egui::RichText::new(format!("{} (H)", deice)).size(10.0));
+        });
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use bevy::{
+    camera::RenderTarget,
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+    window::{WindowRef, WindowResolution},
+};
+use bevy_egui::{egui::{self, Frame}, EguiContext, EguiMultipassSchedule};
+
+use crate::consts::UnitSystem;
+use crate::controls::{Aircraft, Wind};
+use crate::hud::{draw_instrument_panel, HudPalette};
+
+/// The egui pass of the instrument window, separate from the main window's `EguiPrimaryContextPass`
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstrumentPanelPass;
+
+/// A second OS window showing only the flight instruments, for a monitor given over to gauges
+#[derive(Resource, Default)]
+pub struct InstrumentWindow {
+    pub enabled: bool,
+}
+
+/// The OS window itself
+#[derive(Component)]
+pub struct InstrumentPanel;
+
+/// Renders `InstrumentPanel` and hosts its egui context
+#[derive(Component)]
+pub struct InstrumentCamera;
+
+/// Open or close the window as the setting changes, and notice when the player closes it themselves
+pub fn manage_instrument_window(
+    mut instrument_window: ResMut<InstrumentWindow>,
+    mut closed: RemovedComponents<InstrumentPanel>,
+    windows: Query<Entity, With<InstrumentPanel>>,
+    cameras: Query<Entity, With<InstrumentCamera>>,
+    mut commands: Commands,
+) {
+    if closed.read().count() > 0 {
+        instrument_window.enabled = false;
+    }
+    if !instrument_window.enabled {
+        for entity in windows.iter().chain(cameras.iter()) {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if !windows.is_empty() {
+        return;
+    }
+
+    let window = commands.spawn((
+        Window {
+            title: "Flight Instruments".to_string(),
+            resolution: WindowResolution::new(520, 260),
+            ..default()
+        },
+        InstrumentPanel,
+    )).id();
+    commands.spawn((
+        Camera2d,
+        RenderTarget::Window(WindowRef::Entity(window)),
+        EguiMultipassSchedule::new(InstrumentPanelPass),
+        InstrumentCamera,
+    ));
+}
+
+pub fn instrument_panel_ui(
+    mut contexts: Query<&mut EguiContext, With<InstrumentCamera>>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    wind: Res<Wind>,
+    units: Res<UnitSystem>,
+    palette: Res<HudPalette>,
+) {
+    let Ok(mut context) = contexts.single_mut() else { return };
+    egui::CentralPanel::default()
+        .frame(Frame::default().fill(egui::Color32::from_gray(20)).inner_margin(12.0))
+        .show(context.get_mut(), |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            match aircraft_query.single() {
+                Ok((transform, aircraft)) => draw_instrument_panel(ui, transform, aircraft, &wind, *units, &palette),
+                Err(_) => {
+                    ui.label("No aircraft");
+                }
+            }
+        });
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::controls::Wind;
+use crate::day_cycle::DayNightCycle;
+use crate::wind_shear::microburst_air;
+use crate::world_generation::{Biome, WorldGenerator};
+
+// Thermals are placed on a jittered grid, at most one per cell
+const THERMAL_CELL_SIZE: f32 = 3000.0;
+const THERMAL_CELL_CHANCE: f32 = 0.55;
+const THERMAL_MIN_RADIUS: f32 = 250.0;
+const THERMAL_RADIUS_RANGE: f32 = 250.0;
+/// Fraction of the core strength that sinks in the ring around each thermal
+const THERMAL_SINK_FRACTION: f32 = 0.3;
+/// Thermals strengthen over this height above the ground
+const THERMAL_GROUND_RAMP: f32 = 200.0;
+/// Cloud base above the terrain on a fully heated day
+const THERMAL_MAX_CEILING: f32 = 5000.0;
+/// How quickly thermals cycle between building and dying away
+const THERMAL_PULSE_RATE: f32 = 0.01;
+
+/// Height above the slope over which ridge lift fades out
+const RIDGE_LIFT_DEPTH: f32 = 400.0;
+/// Distance used to sample the terrain gradient
+const RIDGE_SAMPLE_DISTANCE: f32 = 40.0;
+
+// Local turbulence
+/// Extra turbulence, in multiples of the base, over terrain as steep as 45° across the sample span
+const MOUNTAIN_TURBULENCE: f32 = 2.5;
+/// Span of the slope that counts as rugged; wider than the ridge sample so it reads whole hillsides
+const ROUGHNESS_SAMPLE_DISTANCE: f32 = 200.0;
+/// Height above the ground over which terrain-stirred air settles down
+const MECHANICAL_MIXING_DEPTH: f32 = 1500.0;
+/// Extra turbulence, in multiples of the base, from convection over fully heated ground under a high sun
+const CONVECTIVE_TURBULENCE: f32 = 1.5;
+/// Fraction of the base turbulence left over the sea with the sun down
+const NIGHT_OCEAN_TURBULENCE: f32 = 0.3;
+
+/// Vertical air movement at a point, in world units per second
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerticalAir {
+    pub thermal: f32,
+    pub ridge: f32,
+    /// Microburst cores, always sinking
+    pub downdraft: f32,
+}
+
+impl VerticalAir {
+    pub fn total(&self) -> f32 {
+        self.thermal + self.ridge + self.downdraft
+    }
+}
+
+/// Deterministic 0..1 value for a thermal cell
+fn cell_hash(x: i32, z: i32, salt: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (z as u32).wrapping_mul(0xd816_3841)
+        ^ salt.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h & 0x00ff_ffff) as f32 / 16_777_216.0
+}
+
+/// How readily the ground heats up and sheds thermals
+fn biome_heating(biome: Biome) -> f32 {
+    match biome {
+        Biome::Desert => 1.0,
+        Biome::Grasslands => 0.7,
+        Biome::Forest => 0.45,
+        Biome::Taiga => 0.35,
+        Biome::Ocean => 0.0,
+    }
+}
+
+/// Rising air from sun-heated ground; thermals drift downwind with the weather
+fn thermal_lift(
+    world_gen: &WorldGenerator,
+    wind: &Wind,
+    cycle: &DayNightCycle,
+    pos: Vec3,
+    terrain_height: f32,
+    time: f64,
+) -> f32 {
+    let sun_heating = cycle.sun_altitude().sin().max(0.0);
+    if sun_heating <= 0.0 || wind.thermal_strength <= 0.0 {
+        return 0.0;
+    }
+
+    let wind_drift = wind.wind_direction * wind.wind_speed * time as f32;
+    let sample = pos - wind_drift;
+    let cell_x = (sample.x / THERMAL_CELL_SIZE).floor() as i32;
+    let cell_z = (sample.z / THERMAL_CELL_SIZE).floor() as i32;
+    let seed = world_gen.seed;
+
+    let mut lift = 0.0;
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            let (x, z) = (cell_x + dx, cell_z + dz);
+            if cell_hash(x, z, seed) > THERMAL_CELL_CHANCE {
+                continue;
+            }
+
+            let center = Vec2::new(
+                (x as f32 + cell_hash(x, z, seed + 1)) * THERMAL_CELL_SIZE,
+                (z as f32 + cell_hash(x, z, seed + 2)) * THERMAL_CELL_SIZE,
+            );
+            let radius = THERMAL_MIN_RADIUS + cell_hash(x, z, seed + 3) * THERMAL_RADIUS_RANGE;
+            let distance = center.distance(Vec2::new(sample.x, sample.z));
+            if distance > radius * 3.0 {
+                continue;
+            }
+
+            let heating = biome_heating(world_gen.get_biome(&[center.x + wind_drift.x, 0.0, center.y + wind_drift.z]));
+            if heating <= 0.0 {
+                continue;
+            }
+
+            let phase = cell_hash(x, z, seed + 4) * std::f32::consts::TAU;
+            let pulse = ((time as f32 * THERMAL_PULSE_RATE + phase).sin() * 0.5 + 0.5).powf(0.5);
+
+            let core = (-(distance / radius).powi(2)).exp();
+            let sink = ((distance - radius * 1.8) / (radius * 0.6)).powi(2);
+            let profile = core - THERMAL_SINK_FRACTION * (-sink).exp();
+
+            lift += profile * heating * pulse;
+        }
+    }
+
+    let height_above_ground = pos.y - terrain_height.max(0.0);
+    let ceiling = THERMAL_MAX_CEILING * sun_heating.sqrt();
+    let ground_ramp = (height_above_ground / THERMAL_GROUND_RAMP).clamp(0.0, 1.0);
+    let top_fade = ((ceiling - height_above_ground) / (ceiling * 0.2)).clamp(0.0, 1.0);
+
+    lift * wind.thermal_strength * sun_heating * ground_ramp * top_fade
+}
+
+/// Air forced up windward slopes, and down on the lee side
+fn ridge_lift(world_gen: &WorldGenerator, wind: &Wind, pos: Vec3, terrain_height: f32) -> f32 {
+    if terrain_height <= 0.0 || wind.ridge_lift_strength <= 0.0 {
+        return 0.0;
+    }
+
+    let height_x = world_gen.get_terrain_height(&[pos.x + RIDGE_SAMPLE_DISTANCE, pos.y, pos.z]);
+    let height_z = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z + RIDGE_SAMPLE_DISTANCE]);
+    let gradient = Vec2::new(height_x - terrain_height, height_z - terrain_height) / RIDGE_SAMPLE_DISTANCE;
+
+    let wind_horizontal = Vec2::new(wind.wind_direction.x, wind.wind_direction.z) * wind.wind_speed;
+    let upslope_flow = wind_horizontal.dot(gradient);
+
+    let height_above_ground = (pos.y - terrain_height).max(0.0);
+    upslope_flow * wind.ridge_lift_strength * (-height_above_ground / RIDGE_LIFT_DEPTH).exp()
+}
+
+/// How much rougher or smoother than the base setting the air is at a point: rotor over rugged terrain,
+/// convective bumps over sun-baked ground, and smooth air over the sea at night
+pub fn turbulence_multiplier(world_gen: &WorldGenerator, cycle: &DayNightCycle, pos: Vec3) -> f32 {
+    let terrain_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]);
+    let sun_heating = cycle.sun_altitude().sin().max(0.0);
+    if terrain_height <= 0.0 {
+        return NIGHT_OCEAN_TURBULENCE + (1.0 - NIGHT_OCEAN_TURBULENCE) * sun_heating.sqrt();
+    }
+    let height_above_ground = (pos.y - terrain_height).max(0.0);
+
+    let height_x = world_gen.get_terrain_height(&[pos.x + ROUGHNESS_SAMPLE_DISTANCE, pos.y, pos.z]);
+    let height_z = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z + ROUGHNESS_SAMPLE_DISTANCE]);
+    let slope = Vec2::new(height_x - terrain_height, height_z - terrain_height).length() / ROUGHNESS_SAMPLE_DISTANCE;
+    let mechanical = MOUNTAIN_TURBULENCE * slope.min(1.5) * (-height_above_ground / MECHANICAL_MIXING_DEPTH).exp();
+
+    let ceiling = THERMAL_MAX_CEILING * sun_heating.sqrt();
+    let below_ceiling = if ceiling > 0.0 { (1.0 - height_above_ground / ceiling).clamp(0.0, 1.0) } else { 0.0 };
+    let convective = CONVECTIVE_TURBULENCE * biome_heating(world_gen.get_biome(&[pos.x, pos.y, pos.z])) * sun_heating * below_ceiling;
+
+    1.0 + mechanical + convective
+}
+
+/// Sample thermal and ridge lift and microburst sink at a world position
+pub fn calculate_vertical_air(
+    world_gen: &WorldGenerator,
+    wind: &Wind,
+    cycle: &DayNightCycle,
+    pos: Vec3,
+    time: f64,
+) -> VerticalAir {
+    let terrain_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]);
+    VerticalAir {
+        thermal: thermal_lift(world_gen, wind, cycle, pos, terrain_height, time),
+        ridge: ridge_lift(world_gen, wind, pos, terrain_height),
+        downdraft: microburst_air(wind, pos, time).y,
+    }
+}
@@ -0,0 +1,388 @@
+use bevy::{
+    audio::{Decodable, Source},
+    light::NotShadowCaster,
+    prelude::*,
+};
+use std::f32::consts::TAU;
+
+use crate::consts::world_units_to_meters;
+use crate::controls::{sample_macro_wind, Aircraft, MainCamera, Wind};
+use crate::network::{LightningReported, NetworkClient};
+use crate::wind_shear::{SHAFT_HEIGHT, STORM_MULTIPLIER};
+use crate::world_generation::WorldGenerator;
+
+/// Seconds between offline rolls for a strike
+const LIGHTNING_CHECK_INTERVAL: f32 = 1.0;
+/// Chance a stormy spot found on a roll throws a strike
+const LIGHTNING_CHANCE: f32 = 0.35;
+/// Spots tried per roll; only the stormy ones can strike
+const STORM_SEARCH_ATTEMPTS: usize = 4;
+/// Strikes are looked for this far around the aircraft
+const STRIKE_RANGE: f32 = 15000.0;
+/// Past this distance a flash neither lights the world nor makes a sound worth playing
+const AUDIBLE_RANGE: f32 = 40000.0;
+
+/// Peak illuminance of the flash for a strike right overhead
+const FLASH_ILLUMINANCE: f32 = 8_000.0;
+/// Seconds over which the flash fades, and the return strokes that flicker it back up
+const FLASH_DECAY: f32 = 0.12;
+const RETURN_STROKES: [f32; 2] = [0.09, 0.21];
+/// Seconds the bolt shows after the first stroke and each return stroke
+const STROKE_VISIBLE: f32 = 0.06;
+const BOLT_LIFETIME: f32 = 0.35;
+const BOLT_SEGMENTS: usize = 14;
+/// Sideways wander of each segment, as a fraction of the cloud height
+const BOLT_JITTER: f32 = 0.06;
+const BOLT_WIDTH: f32 = 18.0;
+/// Forks off the main channel, each a few segments long
+const BOLT_BRANCHES: usize = 3;
+const BRANCH_SEGMENTS: usize = 4;
+
+const SPEED_OF_SOUND_MPS: f32 = 343.0;
+const THUNDER_SAMPLE_RATE: u32 = 44_100;
+const THUNDER_VOLUME: f32 = 0.9;
+/// Nearby thunder is a short crack; distant thunder rolls on for longer
+const THUNDER_MIN_SECS: f32 = 2.5;
+const THUNDER_MAX_SECS: f32 = 8.0;
+
+#[derive(Resource)]
+pub struct Lightning {
+    /// Roll for strikes under storms when flying offline; the server calls them in multiplayer
+    pub enabled: bool,
+    pub thunder_enabled: bool,
+    check: Timer,
+    /// Seconds since the last strike, and how bright it was where we are
+    since_strike: f32,
+    flash_peak: f32,
+    /// Thunder still travelling towards the listener: seconds left and distance covered
+    pending_thunder: Vec<(f32, f32)>,
+}
+
+impl Default for Lightning {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            thunder_enabled: true,
+            check: Timer::from_seconds(LIGHTNING_CHECK_INTERVAL, TimerMode::Repeating),
+            since_strike: f32::INFINITY,
+            flash_peak: 0.0,
+            pending_thunder: Vec::new(),
+        }
+    }
+}
+
+/// A strike at this spot on the ground plane, local or called by the server
+#[derive(Event)]
+pub struct LightningStrike {
+    pub position: Vec2,
+}
+
+/// Light that washes the scene during a flash, aimed from the strike towards the camera
+#[derive(Component)]
+pub struct LightningFlash;
+
+#[derive(Component)]
+pub struct LightningBolt {
+    age: f32,
+}
+
+/// Shared white-hot look for every bolt segment
+#[derive(Resource)]
+pub struct LightningAssets {
+    segment: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub fn setup_lightning(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        LightningFlash,
+        DirectionalLight {
+            illuminance: 0.0,
+            color: Color::srgb(0.8, 0.85, 1.0),
+            shadows_enabled: false,
+            ..default()
+        },
+        Transform::default(),
+    ));
+    commands.insert_resource(LightningAssets {
+        // Unit length along Z, stretched per segment
+        segment: meshes.add(Cuboid::new(BOLT_WIDTH, BOLT_WIDTH, 1.0)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            emissive: LinearRgba::rgb(40.0, 42.0, 60.0),
+            unlit: true,
+            fog_enabled: false,
+            ..default()
+        }),
+    });
+}
+
+pub fn receive_lightning(trigger: On<LightningReported>, mut commands: Commands, lightning: Res<Lightning>) {
+    if lightning.enabled {
+        commands.trigger(LightningStrike { position: trigger.position });
+    }
+}
+
+fn random_range(min: f32, max: f32) -> f32 {
+    min + rand::random::<f32>() * (max - min)
+}
+
+/// Offline, let the stormy parts of the macro wind field around the aircraft throw the odd strike
+pub fn roll_for_lightning(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut lightning: ResMut<Lightning>,
+    client: Option<Res<NetworkClient>>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) {
+    lightning.check.tick(time.delta());
+    let online = client.is_some_and(|client| client.connected);
+    if online || !lightning.enabled || !lightning.check.just_finished() || wind.wind_speed <= 0.0 {
+        return;
+    }
+    let Ok(transform) = aircraft_query.single() else { return };
+    let now = time.elapsed_secs_f64();
+
+    for _ in 0..STORM_SEARCH_ATTEMPTS {
+        let offset = Vec2::from_angle(random_range(0.0, TAU)) * STRIKE_RANGE * rand::random::<f32>().sqrt();
+        let position = Vec2::new(transform.translation.x, transform.translation.z) + offset;
+        let (_, speed) = sample_macro_wind(&wind, Vec3::new(position.x, 0.0, position.y), now);
+        if speed >= wind.wind_speed * STORM_MULTIPLIER && rand::random::<f32>() < LIGHTNING_CHANCE {
+            commands.trigger(LightningStrike { position });
+            return;
+        }
+    }
+}
+
+/// Jagged channel from the cloud base to the ground, wandering sideways as it falls
+fn bolt_path(top: Vec3, bottom: Vec3, segments: usize, jitter: f32) -> Vec<Vec3> {
+    (0..=segments)
+        .map(|i| {
+            let t = i as f32 / segments as f32;
+            let point = top.lerp(bottom, t);
+            // The ends stay pinned to the cloud and the strike point
+            if i == 0 || i == segments {
+                point
+            } else {
+                point + Vec3::new(random_range(-jitter, jitter), 0.0, random_range(-jitter, jitter))
+            }
+        })
+        .collect()
+}
+
+/// Light the sky, draw the bolt and start the thunder on its way to the listener
+pub fn strike_lightning(
+    trigger: On<LightningStrike>,
+    mut commands: Commands,
+    mut lightning: ResMut<Lightning>,
+    assets: Res<LightningAssets>,
+    world_gen: Res<WorldGenerator>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut flash_query: Query<&mut Transform, (With<LightningFlash>, Without<MainCamera>)>,
+) {
+    let Ok(camera) = camera_query.single() else { return };
+    let position = trigger.position;
+    let ground = world_gen.get_terrain_height(&[position.x, 0.0, position.y]).max(0.0);
+    let bottom = Vec3::new(position.x, ground, position.y);
+    // Bolts come down from the same cloud base the microburst shafts hang from
+    let top = bottom.with_y(ground + SHAFT_HEIGHT);
+
+    let distance = camera.translation.distance(bottom);
+    if distance > AUDIBLE_RANGE {
+        return;
+    }
+    let nearness = (1.0 - distance / AUDIBLE_RANGE).powi(2);
+    lightning.since_strike = 0.0;
+    lightning.flash_peak = lightning.flash_peak.max(FLASH_ILLUMINANCE * nearness);
+    if let Ok(mut flash) = flash_query.single_mut() {
+        let middle = top.lerp(bottom, 0.5);
+        flash.rotation = Quat::from_rotation_arc(Vec3::NEG_Z, (camera.translation - middle).normalize_or(Vec3::NEG_Y));
+    }
+
+    let height = top.y - bottom.y;
+    let mut channels = vec![bolt_path(top, bottom, BOLT_SEGMENTS, height * BOLT_JITTER)];
+    for _ in 0..BOLT_BRANCHES {
+        let trunk = &channels[0];
+        let start = trunk[1 + rand::random::<u32>() as usize % (BOLT_SEGMENTS / 2)];
+        let reach = height * random_range(0.1, 0.25);
+        let end = start + Vec3::new(random_range(-reach, reach), -reach, random_range(-reach, reach));
+        channels.push(bolt_path(start, end, BRANCH_SEGMENTS, reach * BOLT_JITTER * 2.0));
+    }
+
+    commands
+        .spawn((LightningBolt { age: 0.0 }, Transform::default(), Visibility::default()))
+        .with_children(|bolt| {
+            for (index, channel) in channels.iter().enumerate() {
+                // Forks are thinner than the main channel
+                let width = if index == 0 { 1.0 } else { 0.5 };
+                for pair in channel.windows(2) {
+                    let length = pair[0].distance(pair[1]);
+                    bolt.spawn((
+                        Mesh3d(assets.segment.clone()),
+                        MeshMaterial3d(assets.material.clone()),
+                        Transform::from_translation(pair[0].lerp(pair[1], 0.5))
+                            .looking_at(pair[1], Vec3::X)
+                            .with_scale(Vec3::new(width, width, length)),
+                        NotShadowCaster,
+                    ));
+                }
+            }
+        });
+
+    if lightning.thunder_enabled {
+        let delay = world_units_to_meters(distance) / SPEED_OF_SOUND_MPS;
+        lightning.pending_thunder.push((delay, distance));
+    }
+}
+
+/// Fade the flash with a flicker for each return stroke, retire spent bolts and play thunder as it arrives
+pub fn update_lightning(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lightning: ResMut<Lightning>,
+    mut thunder_sounds: ResMut<Assets<ThunderSound>>,
+    mut flash_query: Query<&mut DirectionalLight, With<LightningFlash>>,
+    mut bolts: Query<(Entity, &mut LightningBolt, &mut Visibility)>,
+) {
+    let dt = time.delta_secs();
+    lightning.since_strike += dt;
+
+    if let Ok(mut flash) = flash_query.single_mut() {
+        let since = lightning.since_strike;
+        let brightness = std::iter::once(0.0)
+            .chain(RETURN_STROKES)
+            .filter(|&stroke| since >= stroke)
+            .map(|stroke| (-(since - stroke) / FLASH_DECAY).exp())
+            .fold(0.0, f32::max);
+        let illuminance = lightning.flash_peak * brightness;
+        if illuminance < 1.0 {
+            lightning.flash_peak = 0.0;
+        }
+        if flash.illuminance != illuminance {
+            flash.illuminance = illuminance;
+        }
+    }
+
+    for (entity, mut bolt, mut visibility) in &mut bolts {
+        bolt.age += dt;
+        if bolt.age >= BOLT_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Show only during the strokes so the bolt flickers with the flash
+        let lit = std::iter::once(0.0)
+            .chain(RETURN_STROKES)
+            .any(|stroke| (0.0..STROKE_VISIBLE).contains(&(bolt.age - stroke)));
+        visibility.set_if_neq(if lit { Visibility::Inherited } else { Visibility::Hidden });
+    }
+
+    let mut arrived = Vec::new();
+    lightning.pending_thunder.retain_mut(|(delay, distance)| {
+        *delay -= dt;
+        if *delay <= 0.0 {
+            arrived.push(*distance);
+        }
+        *delay > 0.0
+    });
+    for distance in arrived {
+        let farness = (distance / AUDIBLE_RANGE).clamp(0.0, 1.0);
+        let handle = thunder_sounds.add(ThunderSound {
+            seed: rand::random::<u32>(),
+            duration: THUNDER_MIN_SECS + (THUNDER_MAX_SECS - THUNDER_MIN_SECS) * farness,
+            volume: THUNDER_VOLUME * (1.0 - farness).powi(2),
+            // Distance soaks up the crack and leaves the rumble
+            smoothing: 0.02 + 0.1 * farness,
+        });
+        commands.spawn((AudioPlayer::<ThunderSound>(handle), PlaybackSettings::DESPAWN));
+    }
+}
+
+/// One roll of thunder: filtered noise with a sharp onset and a long, uneven decay
+#[derive(Asset, TypePath)]
+pub struct ThunderSound {
+    seed: u32,
+    duration: f32,
+    volume: f32,
+    /// Low-pass coefficient; smaller is muddier
+    smoothing: f32,
+}
+
+pub struct ThunderDecoder {
+    state: u32,
+    sample: u32,
+    total_samples: u32,
+    volume: f32,
+    smoothing: f32,
+    low: f32,
+    rumble: f32,
+}
+
+impl ThunderDecoder {
+    /// xorshift white noise in -1..1
+    fn noise(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Iterator for ThunderDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.sample >= self.total_samples {
+            return None;
+        }
+        let t = self.sample as f32 / self.total_samples as f32;
+        self.sample += 1;
+
+        let white = self.noise();
+        self.low += (white - self.low) * self.smoothing;
+        // A slow random walk swells and drops the rumble as echoes arrive from different parts of the channel
+        self.rumble = (self.rumble + self.noise() * 0.0005).clamp(0.4, 1.0);
+        let envelope = (t * 200.0).min(1.0) * (-t * 4.0).exp() * self.rumble;
+        Some(self.low * envelope * self.volume * 4.0)
+    }
+}
+
+impl Source for ThunderDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        THUNDER_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_secs_f32(self.total_samples as f32 / THUNDER_SAMPLE_RATE as f32))
+    }
+}
+
+impl Decodable for ThunderSound {
+    type DecoderItem = f32;
+    type Decoder = ThunderDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        ThunderDecoder {
+            // xorshift sticks at zero
+            state: self.seed.max(1),
+            sample: 0,
+            total_samples: (self.duration * THUNDER_SAMPLE_RATE as f32) as u32,
+            volume: self.volume,
+            smoothing: self.smoothing,
+            low: 0.0,
+            rumble: 0.8,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts, EguiPrimaryContextPass};
+use flight_sim_server::admin::AdminCommand;
+use flight_sim_server::{GameServer, SERVER_ADDR};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+
+use crate::game_state::GameState;
+use crate::hud::HudPalette;
+use crate::network::{ConnectionFailed, Disconnected, LeaveServer};
+
+/// Hosting from inside the game: the dedicated server's logic runs on its own runtime in this process and
+/// we join it over loopback like any other player, so LAN play doesn't need `flight_sim_server` running
+pub struct ListenServerPlugin;
+
+impl Plugin for ListenServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ListenServer>()
+            .add_observer(stop_on_disconnect)
+            .add_observer(stop_on_failed_join)
+            .add_systems(EguiPrimaryContextPass, hosting_hud.run_if(in_state(GameState::InGame).or(in_state(GameState::Paused))));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ListenServer {
+    /// Owns every task of the hosted server, so shutting it down ends all the sessions at once
+    runtime: Option<tokio::runtime::Runtime>,
+    /// Where players on the local network connect
+    pub join_address: Option<SocketAddr>,
+    /// Where we connect to ourselves
+    local_address: String,
+}
+
+impl ListenServer {
+    pub fn hosting(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    /// Serve world `seed` on the usual server port, returning the loopback address to join it on. We join
+    /// over a real socket rather than an in-process transport because the server only runs sessions on
+    /// connections it accepts, and it keeps the host on the same path as every other player
+    pub fn start(&mut self, seed: u32) -> Result<String, String> {
+        self.stop();
+        let listener = std::net::TcpListener::bind(SERVER_ADDR)
+            .map_err(|e| format!("Couldn't listen on {}: {}", SERVER_ADDR, e))?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("Couldn't start the server: {}", e))?;
+
+        let server = Arc::new(GameServer::new(None));
+        runtime.block_on(server.run_admin_command(AdminCommand::Seed(Some(seed))));
+        runtime.spawn(async move {
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Hosted server couldn't take over its socket: {}", e);
+                    return;
+                }
+            };
+            flight_sim_server::spawn_world_tasks(&server);
+            flight_sim_server::serve(server, listener, None).await;
+        });
+
+        self.runtime = Some(runtime);
+        self.join_address = Some(SocketAddr::new(lan_ip().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)), port));
+        self.local_address = format!("127.0.0.1:{}", port);
+        info!("Hosting world {} on port {}", seed, port);
+        Ok(self.local_address.clone())
+    }
+
+    pub fn stop(&mut self) {
+        // Everyone still connected sees the connection drop
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+            info!("Stopped hosting");
+        }
+        self.join_address = None;
+    }
+}
+
+/// The address other machines reach us on: that of the interface outgoing traffic would leave by.
+/// Connecting a UDP socket only picks the route, nothing is sent
+fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(10, 254, 254, 254), 1)).ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_unspecified())
+}
+
+/// The session ends with the host's own connection
+fn stop_on_disconnect(_trigger: On<Disconnected>, mut listen_server: ResMut<ListenServer>) {
+    listen_server.stop();
+}
+
+fn stop_on_failed_join(trigger: On<ConnectionFailed>, mut listen_server: ResMut<ListenServer>) {
+    if listen_server.hosting() && trigger.address == listen_server.local_address {
+        listen_server.stop();
+    }
+}
+
+/// The address to hand out while hosting
+fn hosting_hud(
+    mut contexts: EguiContexts,
+    listen_server: Res<ListenServer>,
+    palette: Res<HudPalette>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    let Some(join_address) = listen_server.join_address else { return Ok(()) };
+
+    egui::Window::new("Hosting")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("HOSTING").size(12.0));
+            ui.horizontal(|ui| {
+                ui.label(format!("Join at {}", join_address));
+                if ui.small_button("Copy").clicked() {
+                    ui.ctx().copy_text(join_address.to_string());
+                }
+            });
+            if ui.button("Stop hosting").clicked() {
+                commands.trigger(LeaveServer);
+            }
+        });
+
+    Ok(())
+}
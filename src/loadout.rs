@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::aircraft_presets::{AircraftDefinition, AircraftSelection, StationKind, WeightAndBalanceDefinition};
+use crate::consts::UnitSystem;
+use crate::controls::{Aircraft, ControlMode, Difficulty};
+use crate::engine::Engine;
+use crate::hud::HudPalette;
+use crate::network::RespawnAircraft;
+
+const ENVELOPE_DIAGRAM_SIZE: egui::Vec2 = egui::Vec2::new(280.0, 170.0);
+const ENVELOPE_DIAGRAM_MARGIN: f32 = 14.0;
+/// Seconds that full tanks last at full power, before the difficulty's fuel burn scale
+const FUEL_ENDURANCE_SECS: f32 = 1800.0;
+
+/// Station loads of the local aircraft, set from the weight-and-balance panel
+#[derive(Resource, Default)]
+pub struct Loadout {
+    pub open: bool,
+    /// Kilograms at each station of `preset`, as loaded before flight
+    weights: Vec<f32>,
+    preset: Option<AssetId<AircraftDefinition>>,
+    /// Kilograms of fuel used since the tanks were last filled
+    fuel_burned: f32,
+    out_of_fuel: bool,
+}
+
+impl Loadout {
+    pub fn out_of_fuel(&self) -> bool {
+        self.out_of_fuel
+    }
+
+    /// Top the tanks back up by up to `kilograms`, returning how much they had room for
+    pub fn take_on_fuel(&mut self, kilograms: f32) -> f32 {
+        let taken = kilograms.min(self.fuel_burned);
+        self.fuel_burned -= taken;
+        taken
+    }
+
+    /// Give `kilograms` away, as a tanker does. Running the tanks past empty is caught by the next burn
+    pub fn offload_fuel(&mut self, kilograms: f32) {
+        self.fuel_burned += kilograms;
+    }
+
+    /// Station loads after the fuel burned so far, drawn from the tanks in order
+    fn current_weights(&self, balance: &WeightAndBalanceDefinition) -> Vec<f32> {
+        let mut burned = self.fuel_burned;
+        balance.stations.iter().zip(&self.weights).map(|(station, &weight)| {
+            if station.kind != StationKind::Fuel {
+                return weight;
+            }
+            let drawn = burned.min(weight);
+            burned -= drawn;
+            weight - drawn
+        }).collect()
+    }
+
+    fn loaded_fuel(&self, balance: &WeightAndBalanceDefinition) -> f32 {
+        balance.stations.iter().zip(&self.weights)
+            .filter(|(station, _)| station.kind == StationKind::Fuel)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+}
+
+pub fn refuel_on_respawn(_trigger: On<RespawnAircraft>, mut loadout: ResMut<Loadout>) {
+    loadout.fuel_burned = 0.0;
+}
+
+/// Draw fuel from the tanks as the engine runs; a crashed aircraft comes back with them full
+pub fn burn_fuel(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    engine: Res<Engine>,
+    difficulty: Res<Difficulty>,
+    selection: Res<AircraftSelection>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    mut loadout: ResMut<Loadout>,
+    aircraft_query: Query<&Aircraft>,
+) {
+    let Some(definition) = selection.selected.and_then(|id| definitions.get(id)) else { return };
+    let Ok(aircraft) = aircraft_query.single() else { return };
+    let balance = &definition.weight_and_balance;
+    let loaded = loadout.loaded_fuel(balance);
+    let capacity: f32 = balance.stations.iter()
+        .filter(|station| station.kind == StationKind::Fuel)
+        .map(|station| station.max_weight)
+        .sum();
+
+    if aircraft.crashed {
+        loadout.fuel_burned = 0.0;
+    } else if !control_mode.physics_paused {
+        let burn_rate = capacity / FUEL_ENDURANCE_SECS * engine.fuel_flow() * difficulty.fuel_burn_scale;
+        loadout.fuel_burned = (loadout.fuel_burned + burn_rate * time.delta_secs()).min(loaded);
+    }
+    // Gliders have no tanks to run dry
+    loadout.out_of_fuel = capacity > 0.0 && loadout.fuel_burned >= loaded;
+}
+
+/// Reset the loads when the preset changes, and hand the resulting weight and CG to the flight model
+pub fn apply_loadout(
+    selection: Res<AircraftSelection>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    mut loadout: ResMut<Loadout>,
+    mut aircraft_query: Query<&mut Aircraft>,
+) {
+    let Some(definition) = selection.selected.and_then(|id| definitions.get(id)) else { return };
+    let balance = &definition.weight_and_balance;
+    if loadout.preset != selection.selected || loadout.weights.len() != balance.stations.len() {
+        loadout.weights = balance.default_weights();
+        loadout.preset = selection.selected;
+        loadout.fuel_burned = 0.0;
+    }
+
+    let Ok(mut aircraft) = aircraft_query.single_mut() else { return };
+    // Relative to the standard load, which is what the flight model is tuned for
+    let (standard_weight, standard_cg) = balance.balance(&balance.default_weights());
+    let (weight, cg) = balance.balance(&loadout.current_weights(balance));
+    let (forward, aft) = balance.cg_limits();
+    aircraft.weight_ratio = weight / standard_weight;
+    aircraft.cg_position = (cg - standard_cg) / ((aft - forward) * 0.5).max(f32::EPSILON);
+}
+
+/// Station sliders with a live plot of the loading envelope
+pub fn loadout_ui(
+    mut contexts: EguiContexts,
+    mut loadout: ResMut<Loadout>,
+    selection: Res<AircraftSelection>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    units: Res<UnitSystem>,
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
+    if !loadout.open {
+        return Ok(());
+    }
+    let Some(definition) = selection.selected.and_then(|id| definitions.get(id)) else { return Ok(()) };
+    let balance = &definition.weight_and_balance;
+    if loadout.weights.len() != balance.stations.len() {
+        return Ok(());
+    }
+    let units = *units;
+
+    let mut open = loadout.open;
+    egui::Window::new("Weight & Balance")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .default_pos(egui::Pos2::new(400.0, 120.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.heading(&definition.name);
+            for (station, weight) in balance.stations.iter().zip(loadout.weights.iter_mut()) {
+                ui.add(
+                    egui::Slider::new(weight, 0.0..=station.max_weight)
+                        .text(format!("{} ({:?})", station.name, station.kind))
+                        .custom_formatter(|kilograms, _| format!("{:.0} {}", units.weight(kilograms as f32), units.weight_unit()))
+                        .custom_parser(|text| {
+                            let value = text.trim().trim_end_matches(units.weight_unit()).trim().parse::<f64>().ok()?;
+                            Some(value / units.weight(1.0) as f64)
+                        }),
+                );
+            }
+            if ui.button("Standard Load").clicked() {
+                loadout.weights = balance.default_weights();
+            }
+
+            ui.separator();
+            if loadout.fuel_burned > 0.0 {
+                ui.label(format!("Fuel used in flight: {}", units.format_weight(loadout.fuel_burned)));
+            }
+            let (weight, cg) = balance.balance(&loadout.current_weights(balance));
+            let within = balance.in_envelope(weight, cg);
+            ui.label(format!("Gross weight: {} of {}", units.format_weight(weight), units.format_weight(balance.max_weight)));
+            ui.label(format!("CG: {:.2} m aft of datum", cg));
+            if weight > balance.max_weight {
+                ui.colored_label(palette.warning_fill.to_opaque(), "Over maximum weight");
+            } else if !within {
+                ui.colored_label(palette.warning_fill.to_opaque(), "CG outside the envelope");
+            }
+            draw_envelope(ui, balance, weight, cg, within, &palette);
+        });
+    loadout.open = open;
+
+    Ok(())
+}
+
+/// CG arm across, gross weight up, with the loading line from empty to the current load.
+/// Envelopes are drawn as convex shapes
+fn draw_envelope(
+    ui: &mut egui::Ui,
+    balance: &WeightAndBalanceDefinition,
+    weight: f32,
+    cg: f32,
+    within: bool,
+    palette: &HudPalette,
+) {
+    let (response, painter) = ui.allocate_painter(ENVELOPE_DIAGRAM_SIZE, egui::Sense::hover());
+    painter.rect_filled(response.rect, 2.0, palette.window_fill);
+    let rect = response.rect.shrink(ENVELOPE_DIAGRAM_MARGIN);
+
+    let (forward, aft) = balance.cg_limits();
+    let arm_margin = (aft - forward) * 0.3;
+    let (min_arm, max_arm) = (forward - arm_margin, aft + arm_margin);
+    let (min_weight, max_weight) = (balance.empty_weight * 0.9, balance.max_weight * 1.1);
+    let to_screen = |arm: f32, weight: f32| {
+        let x = ((arm - min_arm) / (max_arm - min_arm)).clamp(0.0, 1.0);
+        let y = ((weight - min_weight) / (max_weight - min_weight)).clamp(0.0, 1.0);
+        egui::Pos2::new(rect.left() + x * rect.width(), rect.bottom() - y * rect.height())
+    };
+
+    let corners: Vec<_> = balance.envelope.iter().map(|&(arm, weight)| to_screen(arm, weight)).collect();
+    painter.add(egui::Shape::convex_polygon(
+        corners,
+        egui::Color32::from_rgba_unmultiplied(60, 160, 90, 60),
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(90, 200, 120)),
+    ));
+    let max_y = to_screen(min_arm, balance.max_weight).y;
+    painter.hline(rect.x_range(), max_y, egui::Stroke::new(1.0, egui::Color32::from_gray(140)));
+
+    let empty = to_screen(balance.empty_arm, balance.empty_weight);
+    let current = to_screen(cg, weight);
+    painter.line_segment([empty, current], egui::Stroke::new(1.0, egui::Color32::from_gray(200)));
+    painter.circle_filled(empty, 3.0, egui::Color32::from_gray(200));
+    let marker = if within { egui::Color32::WHITE } else { palette.warning_fill.to_opaque() };
+    painter.circle_filled(current, 5.0, marker);
+
+    let font = egui::FontId::proportional(10.0);
+    painter.text(response.rect.left_bottom() + egui::Vec2::new(4.0, -2.0), egui::Align2::LEFT_BOTTOM, "FWD", font.clone(), palette.text);
+    painter.text(response.rect.right_bottom() + egui::Vec2::new(-4.0, -2.0), egui::Align2::RIGHT_BOTTOM, "AFT", font.clone(), palette.text);
+    painter.text(egui::Pos2::new(rect.left(), max_y - 2.0), egui::Align2::LEFT_BOTTOM, "MAX", font, palette.text);
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::Aircraft;
+use crate::hud::{calculate_heading, HudPalette};
+use crate::network::{DespawnRemotePlayer, Disconnected, UpdateRemotePlayer};
+
+/// Seconds of flight path kept for each remote player
+const TRACK_SECS: f32 = 60.0;
+/// Snapshots closer together than this are merged, so the buffer stays short
+const TRACK_SPACING: f32 = 50.0;
+/// A player who hasn't sent an update for this long is treated as lost
+const CONTACT_TIMEOUT_SECS: f32 = 10.0;
+/// How long the trail and marker stay up after contact is lost
+const LOST_CONTACT_SECS: f32 = 120.0;
+const MARKER_RADIUS: f32 = 80.0;
+const MARKER_HEIGHT: f32 = 400.0;
+const TRAIL_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+
+/// Recent path of a remote player, built from their network snapshots
+struct Track {
+    name: String,
+    /// (time received, position), oldest first
+    snapshots: VecDeque<(f32, Vec3)>,
+    last_heard: f32,
+    /// When contact was lost, if it has been
+    lost_at: Option<f32>,
+}
+
+impl Track {
+    fn last_known(&self) -> Option<Vec3> {
+        self.snapshots.back().map(|(_, position)| *position)
+    }
+}
+
+/// Flight paths of remote players, kept after they leave or go quiet so wingmen can be found again
+#[derive(Resource, Default)]
+pub struct LostContacts {
+    tracks: HashMap<u32, Track>,
+}
+
+impl LostContacts {
+    /// Last known positions of lost players, with how far their marker has faded
+    pub fn markers(&self, now: f32) -> impl Iterator<Item = (&str, Vec3, f32)> {
+        self.tracks.values().filter_map(move |track| {
+            let lost_at = track.lost_at?;
+            let fade = 1.0 - ((now - lost_at) / LOST_CONTACT_SECS).clamp(0.0, 1.0);
+            track.last_known().map(|position| (track.name.as_str(), position, fade))
+        })
+    }
+}
+
+pub fn record_snapshot(trigger: On<UpdateRemotePlayer>, time: Res<Time>, mut contacts: ResMut<LostContacts>) {
+    let now = time.elapsed_secs();
+    let position = Vec3::from(trigger.position);
+    let track = contacts.tracks.entry(trigger.id).or_insert_with(|| Track {
+        name: String::new(),
+        snapshots: VecDeque::new(),
+        last_heard: now,
+        lost_at: None,
+    });
+    if track.lost_at.take().is_some() {
+        info!("Regained contact with {}", trigger.name);
+    }
+    track.name = if trigger.name == "Pilot" {
+        format!("Pilot {}", trigger.id)
+    } else {
+        trigger.name.clone()
+    };
+    track.last_heard = now;
+
+    if track.last_known().is_none_or(|last| last.distance(position) >= TRACK_SPACING) {
+        track.snapshots.push_back((now, position));
+    }
+    while track.snapshots.front().is_some_and(|(t, _)| now - t > TRACK_SECS) {
+        track.snapshots.pop_front();
+    }
+}
+
+pub fn mark_departed(trigger: On<DespawnRemotePlayer>, time: Res<Time>, mut contacts: ResMut<LostContacts>) {
+    if let Some(track) = contacts.tracks.get_mut(&trigger.0) {
+        track.lost_at.get_or_insert(time.elapsed_secs());
+    }
+}
+
+pub fn clear_contacts(_trigger: On<Disconnected>, mut contacts: ResMut<LostContacts>) {
+    contacts.tracks.clear();
+}
+
+/// Time out quiet players, expire old tracks and draw the trail and marker for each lost contact
+pub fn update_lost_contacts(time: Res<Time>, mut contacts: ResMut<LostContacts>, mut gizmos: Gizmos) {
+    let now = time.elapsed_secs();
+    for track in contacts.tracks.values_mut() {
+        if track.lost_at.is_none() && now - track.last_heard > CONTACT_TIMEOUT_SECS {
+            info!("Lost contact with {}", track.name);
+            track.lost_at = Some(track.last_heard);
+        }
+    }
+    contacts.tracks.retain(|_, track| track.lost_at.is_none_or(|lost_at| now - lost_at < LOST_CONTACT_SECS));
+
+    for track in contacts.tracks.values() {
+        let Some(lost_at) = track.lost_at else { continue };
+        let Some(last_known) = track.last_known() else { continue };
+        let fade = 1.0 - (now - lost_at) / LOST_CONTACT_SECS;
+
+        // Older parts of the path are fainter
+        gizmos.linestrip_gradient(track.snapshots.iter().map(|(t, position)| {
+            let age = ((lost_at - t) / TRACK_SECS).clamp(0.0, 1.0);
+            (*position, TRAIL_COLOR.with_alpha(fade * (1.0 - age)))
+        }));
+
+        let marker_color = TRAIL_COLOR.with_alpha(fade);
+        gizmos.circle(
+            Isometry3d::new(last_known, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            MARKER_RADIUS,
+            marker_color,
+        );
+        gizmos.line(last_known, last_known + Vec3::Y * MARKER_HEIGHT, marker_color);
+    }
+}
+
+/// Range and bearing to each lost contact
+pub fn lost_contacts_hud(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    contacts: Res<LostContacts>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) -> Result<(), BevyError> {
+    let Ok(transform) = aircraft_query.single() else { return Ok(()) };
+    let now = time.elapsed_secs();
+    let mut markers: Vec<_> = contacts.markers(now).collect();
+    if markers.is_empty() {
+        return Ok(());
+    }
+    markers.sort_by(|a, b| a.0.cmp(b.0));
+
+    egui::Window::new("Lost Contacts")
+        .title_bar(false)
+        .resizable(false)
+        // Under the clock, combat, cargo and time trial windows of the right column
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 610.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("LOST CONTACT").size(12.0));
+            for (name, position, _) in markers {
+                let offset = position - transform.translation;
+                let distance = units.format_distance(world_units_to_meters(Vec2::new(offset.x, offset.z).length()));
+                ui.label(format!("{}: {}, {:03.0}°", name, distance, calculate_heading(offset)));
+            }
+        });
+
+    Ok(())
+}
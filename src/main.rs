@@ -1,16 +1,22 @@
 use bevy::{
+    audio::AddAudioSource,
     color::palettes::css::WHITE,
     light::CascadeShadowConfigBuilder,
     pbr::wireframe::{WireframeConfig, WireframePlugin},
     platform::collections::HashSet,
     prelude::*, 
     render::{RenderPlugin, settings::{WgpuFeatures, WgpuSettings}},
-    camera::ClearColorConfig,
+    camera::{ClearColorConfig, Exposure},
+    core_pipeline::{prepass::DepthPrepass, tonemapping::Tonemapping},
+    post_process::bloom::Bloom,
+    render::view::Hdr,
     window::{PresentMode, WindowPlugin},
     diagnostic::{FrameTimeDiagnosticsPlugin, DiagnosticsStore},
+    time::common_conditions::on_timer,
 };
+use std::time::Duration;
 
-use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use bevy_egui::{egui, EguiContexts, EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass, PrimaryEguiContext};
 
 use world_generation::*;
 use consts::*;
@@ -18,6 +24,7 @@ use day_cycle::*;
 use controls::*;
 use hud::*;
 use environment::*;
+use replication::ReplicationAppExt;
 
 mod world_generation;
 mod consts;
@@ -26,6 +33,68 @@ mod controls;
 mod hud;
 mod network;
 mod environment;
+mod trails;
+mod effects;
+mod aircraft_lights;
+mod sky;
+mod season;
+mod lift;
+mod glider;
+mod aircraft_presets;
+mod tuning;
+mod opponent;
+mod combat;
+mod aerial_tasks;
+mod time_trial;
+mod voice;
+mod atc;
+mod console;
+mod debug_overlays;
+mod horizon;
+mod ditching;
+mod carrier;
+mod ground_decals;
+mod nameplates;
+mod lost_contacts;
+mod weather_map;
+mod accessibility;
+mod engine;
+mod loadout;
+mod tutorial;
+mod flight_stats;
+mod game_state;
+mod main_menu;
+mod terrain_overrides;
+mod terrain_brush;
+mod external_control;
+mod telemetry;
+mod flight_track;
+mod transport;
+mod split_screen;
+mod instrument_window;
+mod wind_radar;
+mod wind_shear;
+mod icing;
+mod performance;
+mod night_sky;
+mod ambient_traffic;
+mod water;
+mod underwater;
+mod crash_report;
+mod asset_preflight;
+mod graphics;
+mod profiler;
+mod lightning;
+mod precipitation;
+mod breadcrumbs;
+mod home_base;
+mod bookmarks;
+mod teleport;
+mod session_playback;
+mod replication;
+mod listen_server;
+mod refueling;
+mod daily_flight;
 
 // Temperature conversion constants
 const TEMP_SCALE: f32 = 100.0;
@@ -34,17 +103,18 @@ const FAHRENHEIT_TO_CELSIUS_OFFSET: f32 = 32.0;
 const FAHRENHEIT_TO_CELSIUS_RATIO: f32 = 5.0 / 9.0;
 
 // Time format constants
-const HOURS_PER_DAY: f32 = 24.0;
-const TIME_OFFSET_HOURS: f32 = 4.0;
 const MINUTES_PER_HOUR: f32 = 60.0;
 
 // FPS update interval
 const FPS_UPDATE_INTERVAL: f32 = 0.5;
+/// The debugger text is rebuilt at this rate instead of every frame
+const DEBUGGER_UPDATE_INTERVAL: f32 = 0.1;
 
 // UI precision
 const TEMP_PRECISION: f32 = 10.0;
 
 fn main() {
+    crash_report::install_panic_hook();
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -74,6 +144,7 @@ fn main() {
             last_camera_chunk: None,
             to_spawn: Vec::new(),
             lod_to_update: Vec::new(),
+            to_remesh: HashSet::new(),
             render_distance: 50,
             tree_render_distance: 12.0,
             lod_levels: [
@@ -85,86 +156,353 @@ fn main() {
             ],
             lod_quality_multiplier: 1,
             lod_distance_multiplier: 10.0,
+            view_bias: Vec2::ZERO,
         })
         .insert_resource(RenderSettings {
             cascades: 0,
             just_updated: false,
             terrain_smoothness: 0.0,
             compute_smooth_normals: false,
+            cliff_slope: 35.0,
+            auto_exposure: true,
+            day_ev100: 9.7,
+            night_ev100: 8.2,
+            bloom_intensity: 0.15,
+            horizon_terrain: true,
+            aircraft_shadow: true,
         })
         .init_resource::<WorldGenerationSettings>()
         .insert_resource(DayNightCycle {
             time_of_day: 0.50,
             speed: 0.01,  
-            inclination: -1.0,     
+            latitude: 40.0,
+            year_fraction: 0.375,
         })
+        .init_state::<game_state::GameState>()
+        .init_resource::<game_state::LoadingProgress>()
+        .init_resource::<main_menu::MainMenu>()
         .init_resource::<ControlMode>()
+        .init_resource::<Difficulty>()
         .init_resource::<Wind>()
         .init_resource::<hud::MultiplayerMenu>()
-        .insert_resource(network::NetworkSmoothingSettings {
-            half_life: 0.3,
-            forward_offset: 120.0,
-        })
+        .init_resource::<trails::TrailSettings>()
+        .init_resource::<season::Season>()
+        .init_resource::<season::SeasonalFoliage>()
+        .init_resource::<aircraft_lights::ExteriorLightSettings>()
+        .init_resource::<glider::GliderLaunch>()
+        .init_resource::<glider::Variometer>()
+        .add_audio_source::<glider::VariometerTone>()
+        .add_audio_source::<engine::EngineSound>()
+        .add_audio_source::<voice::VoiceStream>()
+        .add_audio_source::<lightning::ThunderSound>()
+        .init_asset::<aircraft_presets::AircraftDefinition>()
+        .init_asset_loader::<aircraft_presets::AircraftDefinitionLoader>()
+        .init_resource::<TerrainPalette>()
+        .init_resource::<HudPalette>()
+        .init_resource::<opponent::OpponentSettings>()
+        .init_resource::<opponent::TagGame>()
+        .init_resource::<combat::CombatSettings>()
+        .init_resource::<combat::CombatState>()
+        .init_resource::<aerial_tasks::TowBanner>()
+        .init_resource::<aerial_tasks::CargoDrops>()
+        .replicate::<aerial_tasks::SharedCargo>()
+        .init_resource::<time_trial::TimeTrial>()
+        .init_resource::<daily_flight::DailyFlight>()
+        .init_resource::<voice::VoiceChat>()
+        .init_resource::<refueling::Refueling>()
+        .replicate::<refueling::Hose>()
+        .replicate::<refueling::Probe>()
+        .init_resource::<atc::AtcConsole>()
+        .init_resource::<console::DevConsole>()
+        .init_resource::<FlightForces>()
+        .init_resource::<debug_overlays::DebugOverlays>()
+        .init_resource::<debug_overlays::ChunkPipelineStats>()
+        .init_resource::<debug_overlays::RecentChunkEvents>()
+        .init_resource::<ditching::Ditching>()
+        .init_resource::<carrier::CarrierOps>()
+        .init_asset::<tuning::SimTuning>()
+        .init_asset_loader::<tuning::SimTuningLoader>()
+        .init_resource::<lost_contacts::LostContacts>()
+        .init_resource::<weather_map::WeatherMap>()
+        .init_resource::<accessibility::Accessibility>()
+        .init_resource::<UnitSystem>()
+        .init_resource::<engine::Engine>()
+        .init_resource::<loadout::Loadout>()
+        .init_resource::<tutorial::Tutorial>()
+        .init_resource::<flight_stats::FlightStats>()
+        .init_resource::<terrain_brush::TerrainBrush>()
+        .init_resource::<external_control::ExternalControl>()
+        .init_resource::<telemetry::Telemetry>()
+        .init_resource::<GeoOrigin>()
+        .init_resource::<flight_track::FlightTrack>()
+        .init_resource::<breadcrumbs::Breadcrumbs>()
+        .init_resource::<home_base::HomeBase>()
+        .init_resource::<bookmarks::Bookmarks>()
+        .init_resource::<teleport::PendingTeleport>()
+        .init_resource::<session_playback::SessionPlayback>()
+        .init_resource::<split_screen::SplitScreen>()
+        .init_resource::<instrument_window::InstrumentWindow>()
+        .init_resource::<wind_radar::WindRadar>()
+        .init_resource::<wind_shear::WindShear>()
+        .init_resource::<lightning::Lightning>()
+        .init_resource::<precipitation::Precipitation>()
+        .init_resource::<icing::Icing>()
+        .init_resource::<performance::PerformancePanel>()
+        .init_resource::<night_sky::NightSky>()
+        .init_resource::<ambient_traffic::AmbientTraffic>()
+        .init_resource::<water::WaterGrid>()
+        .init_resource::<underwater::Underwater>()
+        .init_resource::<crash_report::ErrorReports>()
+        .init_resource::<asset_preflight::AssetPreflight>()
+        .init_resource::<graphics::DisplaySettings>()
+        // The egui context lives on the 2D overlay camera rather than whichever camera spawns first
+        .insert_resource(EguiGlobalSettings { auto_create_primary_context: false, ..default() })
         .add_plugins(EguiPlugin::default())
-        .add_plugins(FrameTimeDiagnosticsPlugin::default())
-        .add_observer(network::spawn_remote_player)
-        .add_observer(network::update_remote_player)
-        .add_observer(network::despawn_remote_player)
-        .add_observer(network::cleanup_on_disconnect)
-        .add_observer(network::teleport_to_player)
-        .add_observer(network::respawn_aircraft)
-        .add_systems(Startup, setup_camera_system)
-        .add_systems(EguiPrimaryContextPass, (debugger_ui, flight_hud_system))
-        .add_systems(Startup, (setup, setup_camera_fog, spawn_stars, hud::auto_connect_on_startup, init_camera_from_aircraft).chain())
+        .add_plugins(FrameTimeDiagnosticsPlugin::new(profiler::FRAME_HISTORY))
+        .add_plugins(network::NetworkPlugin)
+        .add_plugins(replication::ReplicationPlugin)
+        .add_plugins(listen_server::ListenServerPlugin)
+        .add_plugins(profiler::ProfilerPlugin)
+        .add_plugins(day_cycle::DayCyclePlugin)
+        .add_observer(hud::show_connecting)
+        .add_observer(hud::show_connected)
+        .add_observer(hud::show_connection_failed)
+        .add_observer(hud::show_disconnected)
+        .add_observer(effects::spawn_effect)
+        .add_observer(world_generation::reset_chunks)
+        .add_observer(graphics::apply_graphics_preset)
+        .add_observer(environment::clear_reshaped_vegetation)
+        .add_observer(water::recheck_reshaped_water)
+        .add_observer(debug_overlays::record_chunk_spawned)
+        .add_observer(debug_overlays::record_chunk_mesh_ready)
+        .add_observer(debug_overlays::record_chunk_despawned)
+        .add_observer(combat::spawn_remote_tracer)
+        .add_observer(combat::take_hit)
+        .add_observer(combat::take_scrape_damage)
+        .add_observer(combat::record_shot_down)
+        .add_observer(combat::restore_health)
+        .add_observer(time_trial::receive_time_trial_course)
+        .add_observer(time_trial::receive_time_trial_result)
+        .add_observer(time_trial::receive_leaderboard)
+        .add_observer(time_trial::abort_time_trial_on_respawn)
+        .add_observer(daily_flight::start_daily_flight)
+        .add_observer(daily_flight::rearm_daily_on_respawn)
+        .add_observer(daily_flight::end_daily_flight)
+        .add_observer(daily_flight::receive_daily_result)
+        .add_observer(daily_flight::receive_daily_leaderboard)
+        .add_observer(time_trial::reset_time_trial)
+        .add_observer(voice::receive_voice_frame)
+        .add_observer(atc::receive_instruction)
+        .add_observer(atc::record_flagged_player)
+        .add_observer(ditching::start_floating)
+        .add_observer(ditching::end_float_on_respawn)
+        .add_observer(carrier::leave_deck_on_respawn)
+        .add_observer(carrier::leave_deck_on_teleport)
+        .add_observer(ground_decals::leave_scorch_mark)
+        .add_observer(lost_contacts::record_snapshot)
+        .add_observer(lost_contacts::mark_departed)
+        .add_observer(lost_contacts::clear_contacts)
+        .add_observer(loadout::refuel_on_respawn)
+        .add_observer(flight_stats::start_new_flight)
+        .add_observer(flight_stats::log_dawn)
+        .add_observer(flight_stats::log_dusk)
+        .add_observer(flight_track::start_new_track)
+        .add_observer(wind_shear::receive_microburst)
+        .add_observer(lightning::receive_lightning)
+        .add_observer(lightning::strike_lightning)
+        .add_observer(precipitation::forget_weathered_colors)
+        .add_observer(icing::clear_ice_on_respawn)
+        .add_observer(home_base::cancel_return_on_respawn)
+        .add_observer(teleport::begin_teleport)
+        .add_observer(session_playback::start_playback)
+        .add_observer(session_playback::stop_playback)
+        .add_observer(session_playback::stop_playback_on_connect)
+        .add_systems(Startup, (setup_camera_system, trails::setup_trails, effects::setup_effects, aircraft_lights::setup_exterior_lights, sky::spawn_sky_dome, night_sky::spawn_aurora, glider::setup_variometer_tone, engine::setup_engine_sound, aircraft_presets::load_aircraft_presets, tuning::load_sim_tuning, combat::setup_combat, aerial_tasks::setup_aerial_tasks, ground_decals::setup_ground_decals, accessibility::load_accessibility, flight_stats::load_flight_stats, main_menu::load_server_list, crash_report::load_previous_crash, lightning::setup_lightning, bookmarks::load_bookmarks, daily_flight::load_daily_bests))
+        .add_systems(EguiPrimaryContextPass, (debugger_ui, flight_hud_system, aircraft_presets::aircraft_selection_ui, opponent::tag_hud, combat::combat_hud, aerial_tasks::cargo_hud, (time_trial::time_trial_hud, daily_flight::daily_flight_hud), (voice::voice_hud, refueling::refueling_hud), atc::atc_panel, atc::instruction_hud, console::console_ui, (ditching::ditching_hud, carrier::carrier_hud), lost_contacts::lost_contacts_hud, weather_map::weather_map_ui, loadout::loadout_ui, tutorial::tutorial_hud, flight_stats::flight_summary_ui, hud::disconnect_notice_hud, terrain_brush::terrain_brush_ui, game_state::pause_menu_ui.run_if(in_state(game_state::GameState::Paused))).run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))))
+        .add_systems(EguiPrimaryContextPass, (
+            main_menu::main_menu_ui.run_if(in_state(game_state::GameState::MainMenu)),
+            main_menu::loading_ui.run_if(in_state(game_state::GameState::Loading)),
+            split_screen::second_pilot_hud.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            wind_radar::wind_radar_ui.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            home_base::home_hud.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            bookmarks::bookmarks_ui.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            session_playback::session_playback_ui.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            icing::ice_hud.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            performance::performance_panel_ui.run_if(in_state(game_state::GameState::InGame).or(in_state(game_state::GameState::Paused))),
+            crash_report::error_reports_ui,
+        ))
+        .add_systems(Startup, (asset_preflight::preflight_assets, setup, setup_camera_fog, spawn_stars, init_camera_from_aircraft, setup_tree_impostors, graphics::apply_initial_graphics_preset, graphics::load_display_settings).chain())
         .add_systems(Update, (
-            evolve_wind,
-            camera_controls, 
-            update_debugger, 
-            generate_chunks, 
+            update_debugger.run_if(on_timer(Duration::from_secs_f32(DEBUGGER_UPDATE_INTERVAL))),
+            // Nothing to stream until the main menu has settled which world to fly in
+            generate_chunks.run_if(not(in_state(game_state::GameState::MainMenu))).run_if(main_camera_ready),
+            water::stream_water_tiles.after(generate_chunks),
+            terrain_overrides::load_terrain_overrides.before(modify_plane),
+            terrain_overrides::save_terrain_overrides.run_if(on_timer(Duration::from_secs(2))),
             modify_plane, 
-            handle_compute_tasks, 
+            handle_compute_tasks.run_if(main_camera_ready),
             update_tree_lod,
-            update_chunk_lod, 
-            update_daylight_cycle,
-            draw_lod_rings,
+            fade_tree_models,
+            update_chunk_lod.run_if(main_camera_ready),
+            underwater::update_underwater.after(update_daylight_cycle),
+            sky::update_sky_dome.after(update_daylight_cycle),
+            night_sky::update_aurora.after(update_daylight_cycle),
+            night_sky::update_shooting_stars.after(update_daylight_cycle),
+            draw_lod_rings.run_if(|wire_frame: Res<WireframeConfig>| wire_frame.global),
             update_aircraft_model,
-            network::check_connection_status,
-            network::send_player_updates,
-            network::receive_server_messages,
-            network::lerp_remote_players,
-            network::update_player_labels,
-            hud::process_connection_results,
-            spawn_vegetation_for_chunk.after(network::receive_server_messages).after(network::check_connection_status).after(update_debugger),
+            nameplates::attach_nameplates.after(network::receive_server_messages),
+            spawn_vegetation_for_chunk.after(network::receive_server_messages).after(network::check_connection_status).after(update_debugger).run_if(main_camera_ready),
+        ))
+        .add_systems(Update, (
+            trails::update_local_smoke,
+            trails::spawn_trails_for_aircraft,
+            trails::update_trails.after(trails::spawn_trails_for_aircraft).after(network::lerp_remote_players),
+            effects::update_particles,
+            aircraft_lights::attach_exterior_lights,
+            aircraft_lights::update_exterior_lights.after(aircraft_lights::attach_exterior_lights).after(update_daylight_cycle),
+            season::advance_season.before(update_daylight_cycle),
+            season::refresh_chunks_for_season.before(update_chunk_lod),
+            season::update_seasonal_foliage,
+            aircraft_presets::apply_aircraft_definitions.before(camera_controls),
+            tuning::apply_sim_tuning.before(update_chunk_lod),
+            combat::update_tracers.after(combat::fire_guns),
+            combat::maintain_targets,
+            (home_base::default_home_to_spawn, home_base::toggle_return_home, home_base::fly_home)
+                .chain()
+                .before(camera_controls)
+                .run_if(in_state(game_state::GameState::InGame)),
+            session_playback::update_playback.run_if(in_state(game_state::GameState::InGame)),
+        ))
+        .add_systems(Update, (
+            (aerial_tasks::update_cargo.after(aerial_tasks::drop_cargo), aerial_tasks::show_remote_cargo),
+            voice::push_to_talk,
+            voice::update_voice_positions.after(camera_follow_aircraft),
+            console::run_console_commands,
+            debug_overlays::draw_physics_overlays.after(camera_controls),
+            debug_overlays::inspect_chunks.after(update_chunk_lod).after(handle_compute_tasks),
+            horizon::update_horizon_terrain.after(generate_chunks),
+            ground_decals::update_aircraft_shadow.after(camera_controls).after(ditching::update_ditching).after(carrier::update_deck_contact),
+            nameplates::rename_nameplates.after(network::receive_server_messages),
+            lost_contacts::update_lost_contacts.after(network::receive_server_messages),
+            accessibility::apply_accessibility.after(tuning::apply_sim_tuning),
+            accessibility::save_accessibility,
+            loadout::apply_loadout.before(camera_controls).after(aircraft_presets::apply_aircraft_definitions),
+            split_screen::manage_second_pilot.run_if(in_state(game_state::GameState::InGame)),
+            split_screen::fly_second_pilot.after(camera_controls).run_if(in_state(game_state::GameState::InGame)),
+            split_screen::follow_second_pilot.after(split_screen::fly_second_pilot).run_if(in_state(game_state::GameState::InGame)),
+            split_screen::mirror_main_camera.after(update_exposure),
+            split_screen::update_split_viewports,
+            wind_radar::toggle_wind_radar.run_if(in_state(game_state::GameState::InGame)),
+            (
+                wind_shear::update_microbursts.before(camera_controls),
+                wind_shear::draw_microburst_shafts,
+                lightning::roll_for_lightning,
+                lightning::update_lightning,
+                precipitation::update_precipitation.after(update_daylight_cycle),
+                precipitation::weather_terrain_colors.after(precipitation::update_precipitation).after(handle_compute_tasks),
+            ).run_if(in_state(game_state::GameState::InGame)),
         ))
+        // Flying and anything driven by the pilot's input stops behind the menus and the pause screen
+        .add_systems(Update, (
+            camera_controls, 
+            evolve_wind,
+            glider::update_glider_launch.before(camera_controls),
+            glider::update_variometer.after(camera_controls),
+            opponent::manage_opponent,
+            opponent::fly_opponent.after(camera_controls),
+            opponent::update_tag_game.after(opponent::fly_opponent),
+            combat::fire_guns.after(camera_controls),
+            aerial_tasks::update_tow_banner.after(camera_controls),
+            aerial_tasks::drop_cargo.after(camera_controls),
+            (time_trial::update_time_trial.after(camera_controls), daily_flight::update_daily_flight.after(camera_controls)),
+            atc::enforce_observer_mode.before(camera_controls),
+            (ditching::update_ditching.after(camera_controls), carrier::update_deck_contact.after(camera_controls).after(carrier::steam_carrier)),
+            engine::update_engine.before(camera_controls).after(aircraft_presets::apply_aircraft_definitions),
+            (weather_map::toggle_weather_map, bookmarks::toggle_bookmarks),
+            aircraft_lights::toggle_exterior_lights,
+            loadout::burn_fuel.after(engine::update_engine).before(loadout::apply_loadout),
+            tutorial::update_tutorial.after(camera_controls).after(ditching::update_ditching),
+            flight_stats::track_flight.after(camera_controls).after(ditching::update_ditching),
+            terrain_brush::apply_terrain_brush.after(camera_controls).before(update_chunk_lod),
+        ).run_if(in_state(game_state::GameState::InGame)))
+        .add_systems(Update, (
+            game_state::finish_loading.run_if(in_state(game_state::GameState::Loading)),
+            game_state::toggle_pause,
+            external_control::serve_external_control.before(camera_controls),
+            telemetry::broadcast_telemetry.after(camera_controls),
+            flight_track::record_flight_track.after(camera_controls).run_if(in_state(game_state::GameState::InGame)),
+            (breadcrumbs::drop_breadcrumbs.after(camera_controls), breadcrumbs::draw_breadcrumbs).run_if(in_state(game_state::GameState::InGame)),
+            instrument_window::manage_instrument_window,
+            (icing::toggle_deice, icing::accrete_ice.before(camera_controls)).run_if(in_state(game_state::GameState::InGame)),
+            performance::toggle_performance_panel.run_if(in_state(game_state::GameState::InGame)),
+            ambient_traffic::update_ambient_traffic.after(generate_chunks).run_if(in_state(game_state::GameState::InGame)),
+            ambient_traffic::update_shipping.after(generate_chunks).run_if(in_state(game_state::GameState::InGame)),
+            (carrier::place_carrier, carrier::steam_carrier.after(carrier::place_carrier)).run_if(in_state(game_state::GameState::InGame)),
+            (refueling::update_tanker.after(camera_controls), refueling::update_receiver.after(camera_controls).after(network::lerp_remote_players)).run_if(in_state(game_state::GameState::InGame)),
+            crash_report::record_crash_context.run_if(on_timer(Duration::from_secs_f32(crash_report::CONTEXT_INTERVAL_SECS))),
+            crash_report::handle_asset_failures,
+            graphics::track_custom_preset,
+            graphics::apply_display_settings,
+            graphics::apply_render_scale,
+            graphics::save_display_settings,
+        ))
+        .add_systems(instrument_window::InstrumentPanelPass, instrument_window::instrument_panel_ui)
+        .add_systems(OnEnter(game_state::GameState::InGame), teleport::complete_teleport)
+        .add_systems(OnEnter(game_state::GameState::Paused), game_state::pause_game)
+        .add_systems(OnExit(game_state::GameState::Paused), game_state::resume_game)
+        .add_systems(PreUpdate, console::toggle_console.after(bevy::input::InputSystems))
+        .add_systems(Last, graphics::limit_frame_rate)
         .add_systems(PostUpdate, (
-            despawn_out_of_bounds_chunks,
-            camera_follow_aircraft,
+            despawn_out_of_bounds_chunks.run_if(main_camera_ready),
+            camera_follow_aircraft.run_if(not(teleport::is_teleporting)),
+            nameplates::update_nameplates
+                .after(camera_follow_aircraft)
+                .before(bevy::transform::TransformSystems::Propagate)
+                .run_if(any_with_component::<network::RemotePlayer>),
+            asset_preflight::substitute_placeholder_models.after(bevy::transform::TransformSystems::Propagate),
+            session_playback::follow_playback_camera
+                .after(camera_follow_aircraft)
+                .before(bevy::transform::TransformSystems::Propagate),
         ))
         .run();
 }
 
-#[derive(Resource)]
+#[derive(Resource, Debug)]
 pub struct RenderSettings {
     cascades: usize,
     just_updated: bool,
     terrain_smoothness: f32,
     compute_smooth_normals: bool,
+    /// Terrain steeper than this many degrees shows bare rock
+    cliff_slope: f32,
+    /// Follow the day cycle with the EV curve below instead of a fixed day exposure
+    auto_exposure: bool,
+    day_ev100: f32,
+    night_ev100: f32,
+    bloom_intensity: f32,
+    /// Low-res terrain ring from the chunk edge out to the far plane
+    horizon_terrain: bool,
+    /// Blob shadow decal on the ground under the aircraft
+    aircraft_shadow: bool,
 }
 
 fn setup_camera_system(mut commands: Commands) {
     commands.spawn((
         Camera2d,
+        // Above the split-screen cameras, so the HUD draws over both halves
         Camera {
-            order: 1,
+            order: 2,
             clear_color: ClearColorConfig::None,
             ..default()
         },
+        PrimaryEguiContext,
     ));
 }
 
 fn setup(
     mut commands: Commands,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
 ) {
@@ -183,6 +521,8 @@ fn setup(
         terrain_material: materials.add(StandardMaterial {
             base_color: Color::WHITE,
             perceptual_roughness: 0.9,
+            normal_map_texture: Some(images.add(build_detail_normal_map())),
+            uv_transform: bevy::math::Affine2::from_scale(Vec2::splat(DETAIL_REPEATS_PER_CHUNK)),
             ..default()
         }),
         water_material: materials.add(StandardMaterial {
@@ -190,6 +530,9 @@ fn setup(
             alpha_mode: AlphaMode::Blend,
             perceptual_roughness: 0.1,
             metallic: 0.1,
+            // Seen from below when the camera dives
+            cull_mode: None,
+            double_sided: true,
             ..default()
         }),
     });
@@ -250,6 +593,12 @@ fn setup(
 fn setup_camera_fog(mut commands: Commands) {
     commands.spawn((
         Camera3d::default(),
+        Hdr,
+        Tonemapping::TonyMcMapface,
+        Bloom::NATURAL,
+        // Ground decals fade against the scene depth
+        DepthPrepass,
+        Exposure::default(),
         Projection::from(PerspectiveProjection {
             far: 50000.0,
             ..default()
@@ -282,10 +631,12 @@ fn map_temperature(t: f32) -> (f32, f32) {
 
 /// Format time of day (0-1) as HH:MM
 fn format_game_time(t: f32) -> String {
-    let mut total_hours = (t * HOURS_PER_DAY - TIME_OFFSET_HOURS) % HOURS_PER_DAY;
-    if total_hours < 0.0 {
-        total_hours += HOURS_PER_DAY;
-    }
+    format_clock_hours(t * HOURS_PER_DAY - TIME_OFFSET_HOURS)
+}
+
+/// Format hours on the 24 hour clock as HH:MM
+fn format_clock_hours(hours: f32) -> String {
+    let total_hours = hours.rem_euclid(HOURS_PER_DAY);
     let hours = total_hours.floor() as u32;
     let minutes = ((total_hours - hours as f32) * MINUTES_PER_HOUR).round() as u32;
 
@@ -303,11 +654,11 @@ fn update_debugger(
     camera: Query<&Transform, With<MainCamera>>,
     world: Res<WorldGenerator>,
     chunks: Res<ChunkManager>,
-    cycle: Res<DayNightCycle>,
-    control_mode: Res<ControlMode>,
+    (cycle, season): (Res<DayNightCycle>, Res<season::Season>),
+    (control_mode, pipeline): (Res<ControlMode>, Res<debug_overlays::ChunkPipelineStats>),
     mut debugger: Query<&mut Text, With<Debugger>>,
     diagnostics: Res<DiagnosticsStore>,
-    time: Res<Time>,
+    (time, units, underwater): (Res<Time>, Res<UnitSystem>, Res<underwater::Underwater>),
     mut last_update: Local<f32>,
     mut cached_fps: Local<f32>,
 ) {
@@ -325,6 +676,7 @@ fn update_debugger(
     
     let biome = world.get_biome(&camera_pos_arr);
     let climate = world.get_climate(&camera_pos_arr);
+    let temperature = map_temperature(climate.0 + season.temperature_shift());
 
     let Ok(mut text_component) = debugger.single_mut() else { return };
     let message = &mut text_component.0;
@@ -332,8 +684,18 @@ fn update_debugger(
     message.clear();
     message.push_str(&format!("FPS: {:.0}\n", *cached_fps));
     message.push_str(&format!("Position: [{:.0}, {:.0}, {:.0}]\n", cam_trans.x.round(), cam_trans.y.round(), cam_trans.z.round()));
-    message.push_str(&format!("Biome: {:?} | Tempature: {:?}F / {:?}C\n", biome, map_temperature(climate.0).0, map_temperature(climate.0).1));
+    message.push_str(&format!("Biome: {:?} | Tempature: {} | {:?}\n", biome, units.format_temperature(temperature.1, temperature.0), season.name()));
     message.push_str(&format!("Chunks: {} | Time: {} ({:.2})\n", chunks.spawned_chunks.len(), format_game_time(cycle.time_of_day), cycle.time_of_day));
+    message.push_str(&format!(
+        "Chunk Pipeline: {} queued (+{} LOD) | {} generating | {} ready\n",
+        pipeline.queued_spawns, pipeline.queued_lod, pipeline.generating, pipeline.ready
+    ));
+    if underwater.submerged {
+        message.push_str(&format!(
+            "Underwater: {} deep | light {:.0}% | muffle {:.0}%\n",
+            units.format_altitude(world_units_to_meters(underwater.depth)), underwater.light * 100.0, underwater.muffle * 100.0
+        ));
+    }
 
     message.push_str("\n--- CONTROLS ---\n");
     message.push_str(&format!("Camera Mode: {:?} (Press F to toggle)\n", control_mode.mode));
@@ -439,13 +801,26 @@ fn ui_wind_weather(ui: &mut egui::Ui, wind: &mut Wind) {
     ui.add(egui::Slider::new(&mut wind.turbulence_intensity, 0.0..=0.10).text("Intensity").logarithmic(true));
     ui.add(egui::Slider::new(&mut wind.turbulence_frequency, 0.1..=10.0).text("Frequency"));
     ui.add(egui::Slider::new(&mut wind.gust_frequency_multiplier, 0.0001..=0.01).text("Gust Multiplier"));
+
+    ui.separator();
+
+    ui.label(egui::RichText::new("Vertical Air (Soaring)").strong());
+    ui.add(egui::Slider::new(&mut wind.thermal_strength, 0.0..=100.0).text("Thermal Strength"));
+    ui.add(egui::Slider::new(&mut wind.ridge_lift_strength, 0.0..=20.0).text("Ridge Lift"));
 }
 
 /// Display world and time controls
-fn ui_world_time(ui: &mut egui::Ui, day_cycle: &mut DayNightCycle) {
+fn ui_world_time(ui: &mut egui::Ui, day_cycle: &mut DayNightCycle, season: &mut season::Season) {
     ui.add(egui::Slider::new(&mut day_cycle.time_of_day, 0.0..=1.0).text("Time of Day"));
     ui.add(egui::Slider::new(&mut day_cycle.speed, 0.0..=0.2).text("Time Speed").logarithmic(true));
-    ui.add(egui::Slider::new(&mut day_cycle.inclination, -1.0..=1.0).text("Inclination"));
+    ui.add(egui::Slider::new(&mut day_cycle.latitude, -90.0..=90.0).text("Latitude"));
+
+    ui.separator();
+    ui.label(format!("Season: {:?} (day {:.0})", season.name(), season.day_of_year.floor() + 1.0));
+    let days_per_year = season.days_per_year;
+    ui.add(egui::Slider::new(&mut season.day_of_year, 0.0..=days_per_year).text("Day of Year"));
+    ui.add(egui::Slider::new(&mut season.days_per_year, 4.0..=365.0).text("Days per Year").logarithmic(true));
+    ui.checkbox(&mut season.paused, "Pause Seasons");
 }
 
 /// Display render settings controls
@@ -464,6 +839,9 @@ fn ui_render_settings(
     }
     ui.add(egui::Slider::new(&mut chunk_manager.tree_render_distance, 1.0..=50.0).text("Tree Render Distance"));
     ui.add(egui::Slider::new(&mut world_settings.max_chunks_per_frame, 1..=500).text("Max Gen / Frame"));
+    ui.add(egui::Slider::new(&mut world_settings.lookahead_seconds, 0.0..=10.0).text("Streaming Look-ahead (s)"));
+    ui.add(egui::Slider::new(&mut world_settings.near_lane_radius, 0..=10).text("Near Lane Radius (chunks)"));
+    ui.add(egui::Slider::new(&mut world_settings.near_lane_threads, 0..=8).text("Threads Kept for Near Lane"));
 
     if ui.add(egui::Slider::new(&mut render_settings.cascades, 0..=4).text("Cascades")).changed() {
         render_settings.just_updated = true;
@@ -474,6 +852,11 @@ fn ui_render_settings(
     if ui.checkbox(&mut render_settings.compute_smooth_normals, "Smooth Normals").changed() {
         render_settings.just_updated = true;
     }
+    if ui.add(egui::Slider::new(&mut render_settings.cliff_slope, 15.0..=75.0).text("Cliff Slope (°)")).changed() {
+        render_settings.just_updated = true;
+    }
+    ui.checkbox(&mut render_settings.horizon_terrain, "Horizon Terrain");
+    ui.checkbox(&mut render_settings.aircraft_shadow, "Aircraft Shadow");
     if ui.add(egui::Slider::new(&mut chunk_manager.lod_quality_multiplier, 1..=4).text("LOD Quality")).changed() {
         render_settings.just_updated = true;
     }
@@ -486,28 +869,52 @@ fn ui_render_settings(
             ui.add(egui::Slider::new(density, 0.000005..=0.001).text("Fog Density").logarithmic(true));
         }
     }
+
+    ui.separator();
+    ui.checkbox(&mut render_settings.auto_exposure, "Exposure Follows Day Cycle");
+    ui.add(egui::Slider::new(&mut render_settings.day_ev100, 6.0..=14.0).text("Day EV100"));
+    ui.add_enabled(
+        render_settings.auto_exposure,
+        egui::Slider::new(&mut render_settings.night_ev100, 4.0..=12.0).text("Night EV100"),
+    );
+    ui.add(egui::Slider::new(&mut render_settings.bloom_intensity, 0.0..=0.6).text("Bloom"));
+}
+
+/// Display the physics gizmo overlay toggles
+fn ui_debug_overlays(ui: &mut egui::Ui, overlays: &mut debug_overlays::DebugOverlays) {
+    ui.checkbox(&mut overlays.velocity, "Velocity (white)");
+    ui.checkbox(&mut overlays.forces, "Forces: thrust (orange), lift (green), gravity (blue), drag (red)");
+    ui.add_enabled(
+        overlays.forces,
+        egui::Slider::new(&mut overlays.force_scale, 0.5..=50.0).text("Force Scale").logarithmic(true),
+    );
+    ui.checkbox(&mut overlays.wind, "Wind (cyan)");
+    ui.checkbox(&mut overlays.terrain_normal, "Terrain Normal (magenta)");
+    ui.checkbox(&mut overlays.chunk_inspector, "Chunk Inspector: queued (yellow), generating (orange), ready (by LOD)");
 }
 
 /// Main debugger UI system
 pub fn debugger_ui(
     mut contexts: EguiContexts,
-    mut day_cycle: ResMut<DayNightCycle>,
+    (mut day_cycle, mut season): (ResMut<DayNightCycle>, ResMut<season::Season>),
     mut wireframe_config: ResMut<WireframeConfig>,
     mut chunk_manager: ResMut<ChunkManager>,
     mut world_settings: ResMut<WorldGenerationSettings>,
     mut fog_query: Query<&mut DistanceFog, With<MainCamera>>,
     mut render_settings: ResMut<RenderSettings>,
-    mut aircraft_query: Query<&mut Aircraft, Without<MainCamera>>,
-    mut wind: ResMut<Wind>,
+    mut aircraft_query: Query<(&mut Aircraft, &Transform), Without<MainCamera>>,
+    (mut wind, mut trail_settings, mut glider_launch, mut variometer, mut engine, mut difficulty, mut loadout, mut tutorial, mut external_control, mut telemetry, mut geo_origin, mut flight_track, mut split_screen, mut instrument_window, mut breadcrumbs, mut home): (ResMut<Wind>, ResMut<trails::TrailSettings>, ResMut<glider::GliderLaunch>, ResMut<glider::Variometer>, ResMut<engine::Engine>, ResMut<Difficulty>, ResMut<loadout::Loadout>, ResMut<tutorial::Tutorial>, ResMut<external_control::ExternalControl>, ResMut<telemetry::Telemetry>, ResMut<GeoOrigin>, ResMut<flight_track::FlightTrack>, ResMut<split_screen::SplitScreen>, ResMut<instrument_window::InstrumentWindow>, ResMut<breadcrumbs::Breadcrumbs>, ResMut<home_base::HomeBase>),
     mut client: Option<ResMut<network::NetworkClient>>,
     remote_players: Query<(&network::RemotePlayer, &GlobalTransform)>,
     mut commands: Commands,
-    mut menu: ResMut<hud::MultiplayerMenu>,
+    (mut menu, mut aircraft_selection, aircraft_definitions, mut opponent_settings, mut combat_settings, mut tow_banner, mut cargo_drops, mut time_trial, mut voice_chat, mut debug_overlays, mut accessibility, mut units, mut wind_shear, mut lightning, mut precipitation, mut session_playback): (ResMut<hud::MultiplayerMenu>, ResMut<aircraft_presets::AircraftSelection>, Res<Assets<aircraft_presets::AircraftDefinition>>, ResMut<opponent::OpponentSettings>, ResMut<combat::CombatSettings>, ResMut<aerial_tasks::TowBanner>, ResMut<aerial_tasks::CargoDrops>, ResMut<time_trial::TimeTrial>, ResMut<voice::VoiceChat>, ResMut<debug_overlays::DebugOverlays>, ResMut<accessibility::Accessibility>, ResMut<UnitSystem>, ResMut<wind_shear::WindShear>, ResMut<lightning::Lightning>, ResMut<precipitation::Precipitation>, ResMut<session_playback::SessionPlayback>),
     mut smoothing_settings: ResMut<network::NetworkSmoothingSettings>,
-    mut world_generator: ResMut<WorldGenerator>,
-    chunks: Query<(Entity, &Chunk, Option<&Children>)>,
+    (mut display, monitors): (ResMut<graphics::DisplaySettings>, Query<&bevy::window::Monitor, With<bevy::window::PrimaryMonitor>>),
+    (mut refueling, mut daily_flight): (ResMut<refueling::Refueling>, ResMut<daily_flight::DailyFlight>),
 ) -> Result<(), > { 
+    let mut settings_open = menu.settings_open;
     egui::Window::new("Settings")
+    .open(&mut settings_open)
     .default_pos(egui::Pos2::new(20.0, 20.0))
     .default_size(egui::Vec2::new(150.0, 300.0))
     .show(contexts.ctx_mut()?, |ui| {
@@ -522,31 +929,13 @@ pub fn debugger_ui(
             hud::SettingsTab::Basic => {
                 ui.heading("Graphics");
                 ui.horizontal(|ui| {
-                    if ui.selectable_label(menu.graphics_preset == hud::GraphicsPreset::Low, "Low").clicked() {
-                        menu.graphics_preset = hud::GraphicsPreset::Low;
-                        chunk_manager.render_distance = 50;
-                        render_settings.just_updated = true;
-                        render_settings.cascades = 0;
-                        chunk_manager.lod_distance_multiplier = 10.0;
-                        chunk_manager.tree_render_distance = 12.0;
-                        if let Ok(mut fog) = fog_query.single_mut() {
-                            if let FogFalloff::ExponentialSquared { density } = &mut fog.falloff {
-                                *density = 0.000045;
-                            }
+                    for preset in graphics::GraphicsPreset::CHOICES {
+                        if ui.selectable_label(menu.graphics_preset == preset, preset.label()).clicked() {
+                            commands.trigger(graphics::ApplyGraphicsPreset(preset));
                         }
                     }
-                    if ui.selectable_label(menu.graphics_preset == hud::GraphicsPreset::High, "High").clicked() {
-                        menu.graphics_preset = hud::GraphicsPreset::High;
-                        chunk_manager.render_distance = 80;
-                        render_settings.just_updated = true;
-                        render_settings.cascades = 2;
-                        chunk_manager.lod_distance_multiplier = 15.0;
-                        chunk_manager.tree_render_distance = 16.0;
-                        if let Ok(mut fog) = fog_query.single_mut() {
-                            if let FogFalloff::ExponentialSquared { density } = &mut fog.falloff {
-                                *density = 0.000045;
-                            }
-                        }
+                    if menu.graphics_preset == graphics::GraphicsPreset::Custom {
+                        ui.label("Custom");
                     }
                 });
                 
@@ -560,22 +949,127 @@ pub fn debugger_ui(
                     }
                 }
                 
+                ui.separator();
+                ui.heading("Display");
+                // Only flag a change on real edits, so the settings file isn't rewritten every frame
+                if graphics::ui_display_settings(ui, display.bypass_change_detection(), &graphics::available_resolutions(&monitors)) {
+                    display.set_changed();
+                }
+
                 ui.separator();
                 ui.heading("Aircraft");
                 ui.horizontal(|ui| {
-                    if ui.selectable_label(menu.plane_type == hud::PlaneType::Light, "Light").clicked() {
-                        menu.plane_type = hud::PlaneType::Light;
-                        if let Ok(mut aircraft) = aircraft_query.single_mut() {
-                            *aircraft = controls::Aircraft::light();
+                    ui.label(aircraft_selection.selected_name(&aircraft_definitions).unwrap_or("Loading..."));
+                    if ui.button("Choose Aircraft...").clicked() {
+                        aircraft_selection.open = true;
+                    }
+                    if ui.button("Weight & Balance...").clicked() {
+                        loadout.open = true;
+                    }
+                });
+
+                ui.separator();
+                ui.heading("Difficulty");
+                ui.horizontal(|ui| {
+                    for preset in DifficultyPreset::PRESETS {
+                        if ui.selectable_label(difficulty.preset == preset, format!("{:?}", preset)).clicked() {
+                            *difficulty = Difficulty::preset(preset);
                         }
                     }
-                    if ui.selectable_label(menu.plane_type == hud::PlaneType::Jet, "Jet").clicked() {
-                        menu.plane_type = hud::PlaneType::Jet;
-                        if let Ok(mut aircraft) = aircraft_query.single_mut() {
-                            *aircraft = controls::Aircraft::jet();
+                    if difficulty.preset == DifficultyPreset::Custom {
+                        ui.label("(Custom)");
+                    }
+                });
+                let mut custom = *difficulty;
+                ui.add(egui::Slider::new(&mut custom.auto_level_scale, 0.0..=2.0).text("Auto-Level Assist"));
+                ui.checkbox(&mut custom.realistic_stalls, "Realistic Stalls & Spins");
+                ui.add(egui::Slider::new(&mut custom.damage_scale, 0.0..=3.0).text("Damage"));
+                ui.add(egui::Slider::new(&mut custom.fuel_burn_scale, 0.0..=2.0).text("Fuel Burn"));
+                ui.add(egui::Slider::new(&mut custom.wind_scale, 0.0..=2.0).text("Wind & Turbulence"));
+                if custom != *difficulty {
+                    *difficulty = Difficulty { preset: DifficultyPreset::Custom, ..custom };
+                }
+
+                ui.separator();
+                ui.heading("Flight School");
+                ui.horizontal_wrapped(|ui| {
+                    for lesson in tutorial::Lesson::ALL {
+                        if ui.selectable_label(tutorial.lesson() == Some(lesson), lesson.title()).clicked() {
+                            tutorial.start(lesson);
                         }
                     }
+                    if tutorial.lesson().is_some() && ui.button("Stop").clicked() {
+                        tutorial.stop();
+                    }
                 });
+                let is_glider = aircraft_query.single().is_ok_and(|(aircraft, _)| aircraft.plane_type == network::PlaneType::Glider);
+                if is_glider {
+                    ui.horizontal(|ui| {
+                        ui.label("Launch (G):");
+                        ui.add_enabled_ui(!glider_launch.is_active(), |ui| {
+                            ui.selectable_value(&mut glider_launch.method, glider::LaunchMethod::Winch, "Winch");
+                            ui.selectable_value(&mut glider_launch.method, glider::LaunchMethod::Aerotow, "Aerotow");
+                        });
+                    });
+                    ui.checkbox(&mut variometer.tone_enabled, "Variometer Tone");
+                } else {
+                    ui.checkbox(&mut engine.cold_start, "Cold Start (I magnetos, hold K to crank)");
+                    ui.checkbox(&mut engine.sound_enabled, "Engine Sound");
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut trail_settings.smoke_enabled, "Smoke (V)");
+                    ui.color_edit_button_srgb(&mut trail_settings.smoke_color);
+                });
+                ui.checkbox(&mut trail_settings.contrails_enabled, "Contrails");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut breadcrumbs.enabled, "Breadcrumbs");
+                    if ui.button(format!("Clear ({})", breadcrumbs.count())).clicked() {
+                        breadcrumbs.clear();
+                    }
+                });
+                if breadcrumbs.enabled {
+                    ui.add(egui::Slider::new(&mut breadcrumbs.spacing_meters, 100.0..=1000.0).text("Spacing (m)"));
+                    ui.add(egui::Slider::new(&mut breadcrumbs.max_crumbs, 20..=1000).text("Max crumbs"));
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Home:");
+                    if ui.button("Set here").clicked()
+                        && let Ok((_, transform)) = aircraft_query.single()
+                    {
+                        home.set_home(transform.translation);
+                    }
+                    // Clearing it lets home fall back to the spawn point on the next frame
+                    if ui.button("Spawn point").clicked() {
+                        home.clear_home();
+                    }
+                });
+                let return_label = if home.returning { "Cancel return (U)" } else { "Return to home (U)" };
+                if ui.add_enabled(home.home.is_some(), egui::Button::new(return_label)).clicked() {
+                    if home.returning {
+                        home.disengage();
+                    } else if let Ok((_, transform)) = aircraft_query.single() {
+                        home.engage(transform.translation);
+                    }
+                }
+                
+                ui.separator();
+                ui.heading("AI Opponent");
+                ui.checkbox(&mut opponent_settings.enabled, "Tag Mode");
+                ui.horizontal(|ui| {
+                    ui.label("Difficulty:");
+                    for difficulty in opponent::Difficulty::ALL {
+                        ui.selectable_value(&mut opponent_settings.difficulty, difficulty, format!("{:?}", difficulty));
+                    }
+                });
+                
+                ui.separator();
+                ui.heading("Combat");
+                ui.checkbox(&mut combat_settings.enabled, "Guns & Targets (Space to fire)");
+                
+                ui.separator();
+                ui.heading("Aerial Work");
+                ui.checkbox(&mut tow_banner.attached, "Tow Banner (B)");
+                ui.checkbox(&mut cargo_drops.enabled, "Cargo Drops (C to drop)");
                 
                 ui.separator();
                 ui.heading("Time & Weather");
@@ -595,6 +1089,21 @@ pub fn debugger_ui(
                 ui.add(egui::Slider::new(&mut day_cycle.speed, 0.0..=0.05).text("Time Speed"));
                 ui.add(egui::Slider::new(&mut wind.max_wind_speed, 0.0..=200.0).text("Wind Speed"));
                 
+                ui.separator();
+                ui.heading("Units");
+                ui.horizontal(|ui| {
+                    for system in UnitSystem::ALL {
+                        ui.selectable_value(&mut *units, system, format!("{:?}", system));
+                    }
+                });
+                
+                ui.separator();
+                ui.heading("Accessibility");
+                // Only flag a change on real edits, so the settings file isn't rewritten every frame
+                if accessibility::ui_accessibility(ui, accessibility.bypass_change_detection()) {
+                    accessibility.set_changed();
+                }
+                
                 ui.separator();
                 ui.heading("Multiplayer");
                 
@@ -616,7 +1125,10 @@ pub fn debugger_ui(
                         } else {
                             for (remote_player, transform) in remote_players.iter() {
                                 ui.horizontal(|ui| {
-                                    ui.label(format!("Player {}", remote_player.player_id));
+                                    let label = ui.label(remote_player.list_label());
+                                    if let Some(reason) = &remote_player.flagged {
+                                        label.on_hover_text(reason);
+                                    }
                                     if ui.button("Teleport").clicked() {
                                         commands.trigger(network::TeleportToPlayer {
                                             player_id: remote_player.player_id,
@@ -629,32 +1141,55 @@ pub fn debugger_ui(
                         }
                         
                         ui.separator();
+                        ui.label(egui::RichText::new("Time Trial").strong());
+                        ui.horizontal(|ui| {
+                            let idle = matches!(time_trial.state, time_trial::TrialState::Idle);
+                            let pilot = client.role == network::ClientRole::Pilot;
+                            if ui.add_enabled(idle && pilot, egui::Button::new("Start Run")).clicked() {
+                                time_trial.start(client);
+                            }
+                            if ui.button("Refresh Leaderboard").clicked() {
+                                client.send(network::ClientMessage::RequestLeaderboard);
+                            }
+                        });
+                        ui.checkbox(&mut time_trial.show_leaderboard, "Show Leaderboard");
                         
-                        if ui.button("Disconnect").clicked() {
-                            let original_seed = client.original_seed;
-                            client.disconnect();
-                            commands.remove_resource::<network::NetworkClient>();
-                            
-                            *world_generator = WorldGenerator::new(original_seed);
-                            
-                            for (entity, chunk, children) in chunks.iter() {
-                                chunk_manager.spawned_chunks.remove(&(chunk.x, chunk.z));
-                                if let Some(children) = children {
-                                    for child in children.iter() {
-                                        commands.entity(child).despawn();
-                                    }
-                                }
-                                commands.entity(entity).despawn();
+                        ui.separator();
+                        ui.label(egui::RichText::new("Daily Flight").strong());
+                        ui.horizontal(|ui| {
+                            let best = daily_flight.best(flight_sim_server::time_trial::current_day());
+                            if ui.add_enabled(best.is_some(), egui::Button::new("Submit Today's Best"))
+                                .on_disabled_hover_text("Fly today's daily flight from the main menu first")
+                                .clicked()
+                            {
+                                daily_flight.submit(client);
                             }
-                            
-                            chunk_manager.last_camera_chunk = None;
-                            chunk_manager.to_spawn.clear();
-                            chunk_manager.lod_to_update.clear();
-                            render_settings.just_updated = true;
-                            
-                            commands.trigger(network::DisconnectCleanup);
-                            commands.trigger(network::RespawnAircraft);
-                            menu.connection_status = "Disconnected".to_string();
+                            if ui.button("Refresh Daily Leaderboard").clicked() {
+                                client.send(network::ClientMessage::RequestDailyLeaderboard);
+                            }
+                        });
+                        if let Some(status) = &daily_flight.status {
+                            ui.label(status);
+                        }
+                        daily_flight.leaderboard_ui(ui);
+                        
+                        ui.separator();
+                        ui.label(egui::RichText::new("Voice").strong());
+                        ui.checkbox(&mut voice_chat.enabled, "Voice Chat (hold M to talk)");
+                        ui.add(egui::Slider::new(&mut voice_chat.volume, 0.0..=2.0).text("Voice Volume"));
+                        if let Some(error) = &voice_chat.capture_error {
+                            ui.label(format!("Microphone unavailable: {}", error));
+                        }
+                        
+                        ui.separator();
+                        ui.label(egui::RichText::new("Refueling").strong());
+                        let pilot = client.role == network::ClientRole::Pilot;
+                        ui.add_enabled(pilot, egui::Checkbox::new(&mut refueling.tanker, "Fly as tanker (trail a hose for others to refuel from)"));
+                        
+                        ui.separator();
+                        
+                        if ui.button("Disconnect").clicked() {
+                            commands.trigger(network::LeaveServer);
                         }
                     } else {
                         ui.separator();
@@ -663,24 +1198,19 @@ pub fn debugger_ui(
                         
                         ui.label("Server Address:");
                         ui.text_edit_singleline(&mut menu.server_address);
+                        ui.checkbox(&mut menu.accept_self_signed, "Accept self-signed TLS certificates");
+                        ui.horizontal(|ui| {
+                            ui.label("Join as:");
+                            ui.selectable_value(&mut menu.role, network::ClientRole::Pilot, "Pilot");
+                            ui.selectable_value(&mut menu.role, network::ClientRole::Observer, "Observer (ATC)");
+                        });
                         
                         ui.add_space(5.0);
                         
                         if menu.connecting {
                             ui.label("Connecting...");
                         } else if ui.button("Connect").clicked() {
-                            let address = menu.server_address.clone();
-                            let player_name = menu.player_name.clone();
-                            menu.connecting = true;
-                            menu.connection_status.clear();
-                            
-                            let (tx, rx) = crossbeam_channel::unbounded();
-                            menu.connection_receiver = Some(rx);
-                            
-                            std::thread::spawn(move || {
-                                let result = network::TOKIO_RUNTIME.block_on(network::connect_to_server(&address, player_name));
-                                let _ = tx.send(result);
-                            });
+                            commands.trigger(menu.connect_request());
                         }
                         
                         if !menu.connection_status.is_empty() {
@@ -695,24 +1225,19 @@ pub fn debugger_ui(
                     
                     ui.label("Server Address:");
                     ui.text_edit_singleline(&mut menu.server_address);
+                    ui.checkbox(&mut menu.accept_self_signed, "Accept self-signed TLS certificates");
+                    ui.horizontal(|ui| {
+                        ui.label("Join as:");
+                        ui.selectable_value(&mut menu.role, network::ClientRole::Pilot, "Pilot");
+                        ui.selectable_value(&mut menu.role, network::ClientRole::Observer, "Observer (ATC)");
+                    });
                     
                     ui.add_space(5.0);
                     
                     if menu.connecting {
                         ui.label("Connecting...");
                     } else if ui.button("Connect").clicked() {
-                        let address = menu.server_address.clone();
-                        let player_name = menu.player_name.clone();
-                        menu.connecting = true;
-                        menu.connection_status.clear();
-                        
-                        let (tx, rx) = crossbeam_channel::unbounded();
-                        menu.connection_receiver = Some(rx);
-                        
-                        std::thread::spawn(move || {
-                            let result = network::TOKIO_RUNTIME.block_on(network::connect_to_server(&address, player_name));
-                            let _ = tx.send(result);
-                        });
+                        commands.trigger(menu.connect_request());
                     }
                     
                     if !menu.connection_status.is_empty() {
@@ -720,11 +1245,16 @@ pub fn debugger_ui(
                         ui.colored_label(egui::Color32::RED, &menu.connection_status);
                     }
                 }
+                
+                ui.separator();
+                if ui.button("Session Playback").clicked() {
+                    session_playback.open = !session_playback.open;
+                }
             },
             
             hud::SettingsTab::Advanced => {
                 ui.collapsing("✈ Aircraft Physics", |ui| {
-                    if let Ok(mut aircraft) = aircraft_query.single_mut() {
+                    if let Ok((mut aircraft, _)) = aircraft_query.single_mut() {
                         ui_aircraft_physics(ui, &mut aircraft);
                     } else {
                         ui.label(egui::RichText::new("No Aircraft found in scene.").color(egui::Color32::RED));
@@ -735,8 +1265,43 @@ pub fn debugger_ui(
                     ui_wind_weather(ui, &mut wind);
                 });
 
+                ui.collapsing("⛈ Wind Shear", |ui| {
+                    ui.label(format!("Active microbursts: {}", wind.microbursts.len()));
+                    if client.as_ref().is_some_and(|client| client.connected) {
+                        ui.label("The server schedules microbursts in multiplayer");
+                    } else {
+                        ui.checkbox(&mut wind_shear.random_events, "Random microbursts under storms");
+                        if ui.button("Trigger one ahead").clicked() {
+                            wind_shear.trigger_requested = true;
+                        }
+                    }
+                    ui.label("Look for a rain shaft with a dust ring at its foot; the outflow turns a headwind into a tailwind");
+                });
+
+                ui.collapsing("⚡ Lightning", |ui| {
+                    if client.as_ref().is_some_and(|client| client.connected) {
+                        ui.label("The server calls strikes under its storms in multiplayer");
+                    }
+                    ui.checkbox(&mut lightning.enabled, "Lightning under storms");
+                    ui.checkbox(&mut lightning.thunder_enabled, "Thunder");
+                    ui.label("Count the seconds to the thunder: every three is about a kilometre");
+                });
+
+                ui.collapsing("🌧 Rain & Snow", |ui| {
+                    ui.checkbox(&mut precipitation.enabled, "Rain and snow under fronts");
+                    let falling = if precipitation.snow > 0.0 {
+                        format!("Snowing ({:.0}%)", precipitation.snow * 100.0)
+                    } else if precipitation.rain > 0.0 {
+                        format!("Raining ({:.0}%)", precipitation.rain * 100.0)
+                    } else {
+                        "Dry".to_string()
+                    };
+                    ui.label(format!("{} at {}", falling, units.format_temperature(precipitation.ground_temperature, precipitation.ground_temperature * 1.8 + 32.0)));
+                    ui.label(format!("Ground wetness {:.0}% | snow cover {:.0}%", precipitation.wetness * 100.0, precipitation.snow_cover * 100.0));
+                });
+
                 ui.collapsing("🌍 World & Time", |ui| {
-                    ui_world_time(ui, &mut day_cycle);
+                    ui_world_time(ui, &mut day_cycle, &mut season);
                 });
 
                 ui.collapsing("📷 Render Settings", |ui| {
@@ -750,6 +1315,76 @@ pub fn debugger_ui(
                     );
                 });
 
+                ui.collapsing("🐞 Debug Overlays", |ui| {
+                    ui_debug_overlays(ui, &mut debug_overlays);
+                });
+
+                ui.collapsing("🤖 External Control", |ui| {
+                    ui.checkbox(
+                        &mut external_control.enabled,
+                        format!("Accept scripts on 127.0.0.1:{}", external_control::EXTERNAL_CONTROL_PORT),
+                    );
+                    ui.label("One RON request per line: State, Controls(pitch: _, roll: _, yaw: _, throttle: _) or Release");
+                    if external_control.inputs.is_some() {
+                        ui.label("🟢 A script is flying the aircraft");
+                        if ui.button("Take Back Controls").clicked() {
+                            external_control.release();
+                        }
+                    }
+                    if let Some(error) = &external_control.error {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+                });
+
+                ui.collapsing("📡 Telemetry Output", |ui| {
+                    ui.checkbox(&mut telemetry.enabled, "Stream flight data over UDP");
+                    ui.horizontal(|ui| {
+                        ui.label("Send to:");
+                        ui.text_edit_singleline(&mut telemetry.target);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Format:");
+                        ui.selectable_value(&mut telemetry.format, telemetry::TelemetryFormat::XPlane, "X-Plane DATA");
+                        ui.selectable_value(&mut telemetry.format, telemetry::TelemetryFormat::Json, "JSON");
+                    });
+                    ui.add(egui::Slider::new(&mut telemetry.rate, 1.0..=60.0).text("Packets per second"));
+                    ui.label("World origin on the globe");
+                    ui.add(egui::Slider::new(&mut geo_origin.latitude, -85.0..=85.0).text("Latitude"));
+                    ui.add(egui::Slider::new(&mut geo_origin.longitude, -180.0..=180.0).text("Longitude"));
+                    if let Some(error) = &telemetry.error {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+                });
+
+                ui.collapsing("🗺 Flight Track Export", |ui| {
+                    ui.label(format!("{} points recorded since the last spawn", flight_track.point_count()));
+                    ui.label("Positions use the world origin set under Telemetry Output");
+                    ui.horizontal(|ui| {
+                        for (label, format) in [("Export GPX", flight_track::TrackFormat::Gpx), ("Export KML", flight_track::TrackFormat::Kml)] {
+                            if ui.button(label).clicked() {
+                                flight_track.status = Some(match flight_track.export(format, *geo_origin) {
+                                    Ok(path) => format!("Saved {}", path),
+                                    Err(error) => format!("Export failed: {}", error),
+                                });
+                            }
+                        }
+                    });
+                    if let Some(status) = &flight_track.status {
+                        ui.label(status);
+                    }
+                });
+
+                ui.collapsing("🎮 Split Screen", |ui| {
+                    ui.checkbox(&mut split_screen.enabled, "Second local pilot on the right half");
+                    ui.label("Pilot 2 flies with the numeric keypad: 8/5 pitch, 4/6 roll, 7/9 rudder, +/− throttle");
+                    ui.label("Terrain streams around pilot 1, so stay in the same area");
+                });
+
+                ui.collapsing("🖥 Instrument Window", |ui| {
+                    ui.checkbox(&mut instrument_window.enabled, "Show the gauges in a separate window");
+                    ui.label("Drag it onto a second monitor; closing it turns this off");
+                });
+
                 ui.collapsing("🌐 Multiplayer (Advanced)", |ui| {
                     if let Some(client) = &mut client {
                         if client.connected {
@@ -767,7 +1402,10 @@ pub fn debugger_ui(
                             } else {
                                 for (remote_player, transform) in remote_players.iter() {
                                     ui.horizontal(|ui| {
-                                        ui.label(format!("Player {}", remote_player.player_id));
+                                        let label = ui.label(remote_player.list_label());
+                                        if let Some(reason) = &remote_player.flagged {
+                                            label.on_hover_text(reason);
+                                        }
                                         if ui.button("Teleport").clicked() {
                                             commands.trigger(network::TeleportToPlayer {
                                                 player_id: remote_player.player_id,
@@ -795,30 +1433,7 @@ pub fn debugger_ui(
                             ui.separator();
                             
                             if ui.button("Disconnect").clicked() {
-                                let original_seed = client.original_seed;
-                                client.disconnect();
-                                commands.remove_resource::<network::NetworkClient>();
-                                
-                                *world_generator = WorldGenerator::new(original_seed);
-                                
-                                for (entity, chunk, children) in chunks.iter() {
-                                    chunk_manager.spawned_chunks.remove(&(chunk.x, chunk.z));
-                                    if let Some(children) = children {
-                                        for child in children.iter() {
-                                            commands.entity(child).despawn();
-                                        }
-                                    }
-                                    commands.entity(entity).despawn();
-                                }
-                                
-                                chunk_manager.last_camera_chunk = None;
-                                chunk_manager.to_spawn.clear();
-                                chunk_manager.lod_to_update.clear();
-                                render_settings.just_updated = true;
-                                
-                                commands.trigger(network::DisconnectCleanup);
-                                commands.trigger(network::RespawnAircraft);
-                                menu.connection_status = "Disconnected".to_string();
+                                commands.trigger(network::LeaveServer);
                             }
                         } else {
                             ui.label("🔴 Not Connected");
@@ -831,7 +1446,7 @@ pub fn debugger_ui(
                 ui.separator();
                 if ui.button("Reset Simulation State").clicked() {
                     day_cycle.time_of_day = 0.5;
-                    if let Ok(mut aircraft) = aircraft_query.single_mut() {
+                    if let Ok((mut aircraft, _)) = aircraft_query.single_mut() {
                         aircraft.speed = 250.0;
                         aircraft.throttle = 0.8;
                     }
@@ -839,6 +1454,7 @@ pub fn debugger_ui(
             }
         }
     });
+    menu.settings_open = settings_open;
     
     Ok(())
 }
@@ -846,12 +1462,8 @@ pub fn debugger_ui(
 fn draw_lod_rings(
     mut gizmos: Gizmos,
     query: Query<&GlobalTransform, With<MainCamera>>,
-    wire_frame: Res<WireframeConfig>,
     chunk_manager: Res<ChunkManager>,
 ) {
-    if !wire_frame.global {
-        return;
-    }
     let Ok(transform) = query.single() else { return };
     let translation = transform.translation();
 
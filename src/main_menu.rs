@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::aircraft_presets::{fly_preset, AircraftDefinition, AircraftSelection};
+use crate::controls::Aircraft;
+use crate::daily_flight::{self, DailyFlight, StartDailyFlight};
+use crate::game_state::{GameState, LoadingProgress};
+use crate::hud::{HudPalette, MultiplayerMenu};
+use crate::listen_server::ListenServer;
+use crate::network::{ClientRole, ConnectRequest, NetworkClient, RespawnAircraft, DEFAULT_SERVER_ADDR};
+use crate::time_trial::format_run_time;
+use crate::world_generation::{TerrainPreset, WorldGenerator};
+
+/// Saved servers, kept next to the other per-user settings
+const SERVERS_PATH: &str = "settings/servers.ron";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MenuTab {
+    SinglePlayer,
+    Multiplayer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServerEntry {
+    name: String,
+    address: String,
+}
+
+/// Choices made on the main menu before the world is entered
+#[derive(Resource)]
+pub struct MainMenu {
+    tab: MenuTab,
+    seed: String,
+    terrain: TerrainPreset,
+    aircraft: Option<AssetId<AircraftDefinition>>,
+    servers: Vec<ServerEntry>,
+    new_server_name: String,
+    new_server_address: String,
+    error: Option<String>,
+}
+
+impl Default for MainMenu {
+    fn default() -> Self {
+        Self {
+            tab: MenuTab::SinglePlayer,
+            seed: rand::random::<u32>().to_string(),
+            terrain: TerrainPreset::default(),
+            aircraft: None,
+            servers: vec![ServerEntry {
+                name: "Public Server".to_string(),
+                address: DEFAULT_SERVER_ADDR.to_string(),
+            }],
+            new_server_name: String::new(),
+            new_server_address: String::new(),
+            error: None,
+        }
+    }
+}
+
+pub fn load_server_list(mut menu: ResMut<MainMenu>) {
+    let Ok(text) = std::fs::read_to_string(SERVERS_PATH) else { return };
+    match ron::from_str::<Vec<ServerEntry>>(&text) {
+        Ok(servers) => {
+            menu.servers = servers;
+            info!("Loaded {} servers from {}", menu.servers.len(), SERVERS_PATH);
+        }
+        Err(error) => warn!("Ignoring {}: {}", SERVERS_PATH, error),
+    }
+}
+
+fn save_server_list(servers: &[ServerEntry]) {
+    let result = ron::ser::to_string_pretty(servers, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all("settings").map_err(|error| error.to_string())?;
+            std::fs::write(SERVERS_PATH, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("Couldn't save {}: {}", SERVERS_PATH, error);
+    }
+}
+
+/// Put the chosen aircraft on the local player, respawning it either way so it starts over the new terrain
+fn board_aircraft(
+    menu: &MainMenu,
+    selection: &mut AircraftSelection,
+    definitions: &Assets<AircraftDefinition>,
+    aircraft_query: &mut Query<&mut Aircraft>,
+    commands: &mut Commands,
+) {
+    match menu.aircraft.and_then(|id| definitions.get(id).map(|preset| (id, preset))) {
+        Some((id, preset)) => fly_preset(selection, id, preset, aircraft_query.single_mut().ok(), commands),
+        None => commands.trigger(RespawnAircraft),
+    }
+}
+
+pub fn main_menu_ui(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<MainMenu>,
+    mut multiplayer: ResMut<MultiplayerMenu>,
+    mut listen_server: ResMut<ListenServer>,
+    daily: Res<DailyFlight>,
+    client: Option<Res<NetworkClient>>,
+    (mut selection, definitions): (ResMut<AircraftSelection>, Res<Assets<AircraftDefinition>>),
+    mut aircraft_query: Query<&mut Aircraft>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut next_state: ResMut<NextState<GameState>>,
+    palette: Res<HudPalette>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    // Joined a server: the welcome has already moved the world to its seed and spawn point
+    if client.as_ref().is_some_and(|client| client.connected && client.world_seed.is_some()) {
+        next_state.set(GameState::Loading);
+        return Ok(());
+    }
+    if menu.aircraft.is_none() {
+        menu.aircraft = selection.selected;
+    }
+
+    let mut presets: Vec<(AssetId<AircraftDefinition>, &AircraftDefinition)> = definitions.iter().collect();
+    presets.sort_by(|(_, a), (_, b)| a.stats.max_speed_kmh.total_cmp(&b.stats.max_speed_kmh));
+
+    egui::Window::new("Main Menu")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .min_width(360.0)
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(16.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new("FLIGHT SIM").size(28.0).strong());
+            });
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Pilot name:");
+                ui.text_edit_singleline(&mut multiplayer.player_name);
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Aircraft:");
+                if presets.is_empty() {
+                    ui.label("Loading...");
+                }
+                for (id, preset) in &presets {
+                    ui.selectable_value(&mut menu.aircraft, Some(*id), &preset.name);
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut menu.tab, MenuTab::SinglePlayer, "Single Player");
+                ui.selectable_value(&mut menu.tab, MenuTab::Multiplayer, "Multiplayer");
+            });
+            ui.add_space(4.0);
+
+            match menu.tab {
+                MenuTab::SinglePlayer => {
+                    ui.horizontal(|ui| {
+                        ui.label("World seed:");
+                        ui.add(egui::TextEdit::singleline(&mut menu.seed).desired_width(120.0));
+                        if ui.button("Random").clicked() {
+                            menu.seed = rand::random::<u32>().to_string();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Terrain:");
+                        for preset in TerrainPreset::ALL {
+                            ui.selectable_value(&mut menu.terrain, preset, format!("{:?}", preset));
+                        }
+                    });
+                    ui.add_space(8.0);
+                    if ui.add_sized([ui.available_width(), 32.0], egui::Button::new("Fly")).clicked() {
+                        match menu.seed.trim().parse::<u32>() {
+                            Ok(seed) => {
+                                *world_generator = WorldGenerator::with_terrain(seed, menu.terrain);
+                                board_aircraft(&menu, &mut selection, &definitions, &mut aircraft_query, &mut commands);
+                                menu.error = None;
+                                next_state.set(GameState::Loading);
+                                info!("Starting single player on seed {} ({:?})", seed, menu.terrain);
+                            }
+                            Err(_) => menu.error = Some("The seed must be a whole number".to_string()),
+                        }
+                    }
+                    let today = flight_sim_server::time_trial::current_day();
+                    let label = match daily.best(today) {
+                        Some(best) => format!("Daily Flight {}  ·  best {}", daily_flight::date_label(today), format_run_time(best.time)),
+                        None => format!("Daily Flight {}", daily_flight::date_label(today)),
+                    };
+                    if ui.add_sized([ui.available_width(), 24.0], egui::Button::new(label))
+                        .on_hover_text("Today's world, weather and gate course, the same for every pilot")
+                        .clicked()
+                    {
+                        let (seed, terrain) = daily_flight::daily_world(today);
+                        *world_generator = WorldGenerator::with_terrain(seed, terrain);
+                        board_aircraft(&menu, &mut selection, &definitions, &mut aircraft_query, &mut commands);
+                        commands.trigger(StartDailyFlight { day: today });
+                        menu.error = None;
+                        next_state.set(GameState::Loading);
+                        info!("Starting the daily flight for {}", daily_flight::date_label(today));
+                    }
+                }
+                MenuTab::Multiplayer => {
+                    let mut remove = None;
+                    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                        for (index, server) in menu.servers.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let text = format!("{}  ·  {}", server.name, server.address);
+                                if ui.selectable_label(server.address == multiplayer.server_address, text).clicked() {
+                                    multiplayer.server_address = server.address.clone();
+                                }
+                                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                        }
+                    });
+                    if let Some(index) = remove {
+                        menu.servers.remove(index);
+                        save_server_list(&menu.servers);
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut menu.new_server_name).hint_text("Name").desired_width(100.0));
+                        ui.add(egui::TextEdit::singleline(&mut menu.new_server_address).hint_text("host:port").desired_width(140.0));
+                        let address = menu.new_server_address.trim().to_string();
+                        if ui.add_enabled(!address.is_empty(), egui::Button::new("Add")).clicked() {
+                            let name = match menu.new_server_name.trim() {
+                                "" => address.clone(),
+                                name => name.to_string(),
+                            };
+                            menu.servers.push(ServerEntry { name, address: address.clone() });
+                            menu.new_server_name.clear();
+                            menu.new_server_address.clear();
+                            multiplayer.server_address = address;
+                            save_server_list(&menu.servers);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Join as:");
+                        ui.selectable_value(&mut multiplayer.role, ClientRole::Pilot, "Pilot");
+                        ui.selectable_value(&mut multiplayer.role, ClientRole::Observer, "Observer (ATC)");
+                    });
+                    ui.checkbox(&mut multiplayer.accept_self_signed, "Accept self-signed TLS certificates");
+                    ui.add_space(8.0);
+
+                    if multiplayer.connecting {
+                        ui.vertical_centered(|ui| {
+                            ui.spinner();
+                            if listen_server.hosting() {
+                                ui.label("Joining your server...");
+                            } else {
+                                ui.label(format!("Connecting to {}...", multiplayer.server_address));
+                            }
+                        });
+                    } else if client.is_some() {
+                        ui.label("Waiting for the server to send the world...");
+                    } else if ui.add_sized([ui.available_width(), 32.0], egui::Button::new("Join")).clicked() {
+                        board_aircraft(&menu, &mut selection, &definitions, &mut aircraft_query, &mut commands);
+                        commands.trigger(multiplayer.connect_request());
+                    } else if ui.add_sized([ui.available_width(), 24.0], egui::Button::new("Host"))
+                        .on_hover_text("Run a server in the game on the world seed from Single Player and join it")
+                        .clicked()
+                    {
+                        let seed = menu.seed.trim().parse::<u32>().unwrap_or_else(|_| rand::random());
+                        match listen_server.start(seed) {
+                            // The saved server address is left alone for the next time we join one
+                            Ok(address) => {
+                                board_aircraft(&menu, &mut selection, &definitions, &mut aircraft_query, &mut commands);
+                                commands.trigger(ConnectRequest { address, ..multiplayer.connect_request() });
+                                menu.error = None;
+                            }
+                            Err(error) => menu.error = Some(error),
+                        }
+                    }
+                    if !multiplayer.connection_status.is_empty() && !multiplayer.connecting {
+                        ui.colored_label(palette.warning_fill.to_opaque(), &multiplayer.connection_status);
+                    }
+                }
+            }
+
+            if let Some(error) = &menu.error {
+                ui.colored_label(palette.warning_fill.to_opaque(), error);
+            }
+        });
+
+    Ok(())
+}
+
+pub fn loading_ui(mut contexts: EguiContexts, progress: Res<LoadingProgress>, palette: Res<HudPalette>) -> Result<(), BevyError> {
+    egui::Window::new("Loading")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(16.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new("GENERATING TERRAIN").size(14.0));
+                let fraction = if progress.total > 0 { progress.ready as f32 / progress.total as f32 } else { 0.0 };
+                ui.add(egui::ProgressBar::new(fraction).desired_width(260.0).text(format!("{}/{} chunks", progress.ready, progress.total)));
+            });
+        });
+
+    Ok(())
+}
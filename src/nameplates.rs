@@ -0,0 +1,194 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::RenderTarget,
+    light::NotShadowCaster,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, MainCamera};
+use crate::network::RemotePlayer;
+
+const NAMEPLATE_TEXTURE_WIDTH: u32 = 256;
+const NAMEPLATE_TEXTURE_HEIGHT: u32 = 64;
+/// Height of the plate above the aircraft
+const NAMEPLATE_OFFSET: f32 = 40.0;
+/// Plate width per unit of camera distance, which keeps it a steady size on screen
+const NAMEPLATE_WIDTH_PER_DISTANCE: f32 = 0.06;
+const NAMEPLATE_MIN_WIDTH: f32 = 20.0;
+/// Distance readouts change slowly, so their text is only rebuilt a few times a second
+const DISTANCE_LABEL_INTERVAL: f32 = 0.25;
+
+/// Text entities of a remote player's nameplate, rendered off-screen into the plate's texture
+#[derive(Component)]
+pub struct Nameplate {
+    name: Entity,
+    distance: Entity,
+}
+
+/// The billboarded quad showing the nameplate, a child of the remote aircraft
+#[derive(Component)]
+pub struct NameplateQuad;
+
+/// Texture camera and UI root behind a nameplate. They can't be children of the aircraft, since the
+/// UI root has to stay a root node, so they're linked to it and despawn along with it
+#[derive(Component)]
+#[relationship(relationship_target = NameplateParts)]
+pub struct NameplateOf(pub Entity);
+
+#[derive(Component)]
+#[relationship_target(relationship = NameplateOf, linked_spawn)]
+pub struct NameplateParts(Vec<Entity>);
+
+fn display_name(remote: &RemotePlayer) -> String {
+    let name = if remote.name == "Pilot" {
+        format!("Pilot {}", remote.player_id)
+    } else {
+        remote.name.clone()
+    };
+    // The nameplate font has no warning sign
+    match remote.flagged {
+        Some(_) => format!("[!] {}", name),
+        None => name,
+    }
+}
+
+/// Give every newly joined player a world-space nameplate
+pub fn attach_nameplates(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    new_remote: Query<(Entity, &RemotePlayer), Added<RemotePlayer>>,
+) {
+    for (aircraft, remote) in new_remote.iter() {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: NAMEPLATE_TEXTURE_WIDTH,
+                height: NAMEPLATE_TEXTURE_HEIGHT,
+                ..default()
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Bgra8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage =
+            TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+        let image = images.add(image);
+
+        let camera = commands.spawn((
+            Camera2d,
+            Camera {
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            RenderTarget::Image(image.clone().into()),
+            NameplateOf(aircraft),
+        )).id();
+
+        let name = commands.spawn((
+            Text::new(display_name(remote)),
+            TextFont {
+                font_size: 28.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        )).id();
+        let distance = commands.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        )).id();
+
+        commands.spawn((
+            Node {
+                width: percent(100),
+                height: percent(100),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            UiTargetCamera(camera),
+            NameplateOf(aircraft),
+        )).add_children(&[name, distance]);
+
+        let aspect = NAMEPLATE_TEXTURE_HEIGHT as f32 / NAMEPLATE_TEXTURE_WIDTH as f32;
+        let quad = commands.spawn((
+            Mesh3d(meshes.add(Rectangle::new(1.0, aspect))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color_texture: Some(image),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                fog_enabled: false,
+                ..default()
+            })),
+            Transform::default(),
+            NameplateQuad,
+            NotShadowCaster,
+        )).id();
+
+        commands.entity(aircraft).add_child(quad).insert(Nameplate { name, distance });
+    }
+}
+
+/// Follow name changes coming in over the network
+pub fn rename_nameplates(
+    renamed: Query<(&RemotePlayer, &Nameplate), Changed<RemotePlayer>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (remote, nameplate) in renamed.iter() {
+        if let Ok(mut text) = texts.get_mut(nameplate.name) {
+            let name = display_name(remote);
+            if **text != name {
+                **text = name;
+            }
+        }
+    }
+}
+
+/// Face each plate toward the camera above its aircraft, scaled so it reads the same at any range
+pub fn update_nameplates(
+    time: Res<Time>,
+    units: Res<UnitSystem>,
+    mut last_distance_update: Local<f32>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    aircraft_query: Query<&Transform, (With<Aircraft>, Without<MainCamera>)>,
+    remote_players: Query<(&Transform, &Nameplate), (With<RemotePlayer>, Without<NameplateQuad>)>,
+    mut quads: Query<(&ChildOf, &mut Transform), (With<NameplateQuad>, Without<Aircraft>, Without<MainCamera>)>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok(camera_transform) = camera_query.single() else { return };
+    let camera_pos = camera_transform.translation;
+    let refresh_distances = time.elapsed_secs() - *last_distance_update >= DISTANCE_LABEL_INTERVAL;
+    if refresh_distances {
+        *last_distance_update = time.elapsed_secs();
+    }
+
+    for (child_of, mut transform) in quads.iter_mut() {
+        let Ok((player_transform, nameplate)) = remote_players.get(child_of.parent()) else { continue };
+        let position = player_transform.translation + Vec3::Y * NAMEPLATE_OFFSET;
+        let width = (camera_pos.distance(position) * NAMEPLATE_WIDTH_PER_DISTANCE).max(NAMEPLATE_MIN_WIDTH);
+
+        // Work out the pose in world space, then undo the aircraft's own transform and model scale
+        let world = Transform::from_translation(position)
+            .with_rotation(camera_transform.rotation)
+            .with_scale(Vec3::splat(width));
+        *transform = Transform::from_matrix(Mat4::from(player_transform.compute_affine().inverse() * world.compute_affine()));
+
+        if refresh_distances
+            && let Ok(aircraft_transform) = aircraft_query.single()
+            && let Ok(mut text) = texts.get_mut(nameplate.distance)
+        {
+            let distance = aircraft_transform.translation.distance(player_transform.translation);
+            **text = units.format_distance(world_units_to_meters(distance));
+        }
+    }
+}
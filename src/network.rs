@@ -1,12 +1,10 @@
-use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
-use tokio::sync::mpsc;
-use std::sync::Arc;
+use bevy::{platform::collections::HashMap, prelude::*};
 use once_cell::sync::Lazy;
 
-use crate::consts::world_units_to_meters;
+use crate::transport::NetTransport;
+pub use flight_sim_protocol::messages::{
+    ClientMessage, ClientRole, LeaderboardEntry, PlaneType, PlayerState, ServerMessage, SessionEntry, SESSION_FORMAT_VERSION,
+};
 
 pub static TOKIO_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
     tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime")
@@ -14,63 +12,43 @@ pub static TOKIO_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
 
 pub const _DEFAULT_SERVER_PORT: u16 = 7878;
 pub const DEFAULT_SERVER_ADDR: &str = "75.237.222.254:7878";
-const MAX_MESSAGE_SIZE: usize = 4096;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum PlaneType {
-    Light,
-    Jet,
-}
-
-impl Default for PlaneType {
-    fn default() -> Self {
-        PlaneType::Light
+/// Height above the offline terrain, in world units, that a disconnected aircraft must clear to keep flying where it is
+const DISCONNECT_TERRAIN_CLEARANCE: f32 = 150.0;
+
+/// The client side of multiplayer: connecting, replicating our aircraft, and the remote players.
+/// UI asks for a connection with `ConnectRequest` and hears back through `Connected`,
+/// `ConnectionFailed` and `Disconnected`
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemotePlayers>()
+            .init_resource::<PendingConnection>()
+            .init_resource::<DisconnectNotice>()
+            .insert_resource(NetworkSmoothingSettings {
+                half_life: 0.3,
+                forward_offset: 120.0,
+            })
+            .add_observer(start_connection)
+            .add_observer(spawn_remote_player)
+            .add_observer(update_remote_player)
+            .add_observer(despawn_remote_player)
+            .add_observer(flag_remote_player)
+            .add_observer(cleanup_on_disconnect)
+            .add_observer(teleport_to_player)
+            .add_observer(respawn_aircraft)
+            .add_observer(leave_server)
+            .add_observer(settle_after_disconnect)
+            .add_systems(Update, (
+                finish_connection,
+                check_connection_status,
+                send_player_updates,
+                receive_server_messages,
+                lerp_remote_players,
+            ));
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlayerState {
-    pub id: u32,
-    pub name: String,
-    pub position: [f32; 3],
-    pub rotation: [f32; 4],
-    pub plane_type: PlaneType,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ClientMessage {
-    Join { name: String },
-    UpdatePosition { name: String, position: [f32; 3], rotation: [f32; 4], plane_type: PlaneType },
-    Disconnect,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ServerMessage {
-    Welcome {
-        your_id: u32,
-        seed: u32,
-        existing_players: Vec<PlayerState>,
-        time_of_day: f32,
-        speed: f32,
-    },
-    PlayerJoined {
-        player: PlayerState,
-    },
-    PlayerUpdate {
-        id: u32,
-        name: String,
-        position: [f32; 3],
-        rotation: [f32; 4],
-        plane_type: PlaneType,
-    },
-    PlayerLeft {
-        id: u32,
-    },
-    Error {
-        message: String,
-    },
-}
-
 #[derive(Resource)]
 pub struct NetworkClient {
     pub player_id: Option<u32>,
@@ -78,19 +56,20 @@ pub struct NetworkClient {
     pub connected: bool,
     pub world_seed: Option<u32>,
     pub original_seed: u32,
-    send_tx: mpsc::UnboundedSender<ClientMessage>,
-    recv_rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<ServerMessage>>>,
-    disconnect_rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<()>>>,
+    pub original_terrain: crate::world_generation::TerrainPreset,
+    pub spawn_point: Option<[f32; 2]>,
+    pub role: ClientRole,
+    /// TCP today; anything that moves `ClientMessage`s and `ServerMessage`s will do
+    transport: Box<dyn NetTransport>,
 }
 
 impl NetworkClient {
     pub fn send(&self, message: ClientMessage) {
-        let _ = self.send_tx.send(message);
+        self.transport.send(message);
     }
 
-    pub async fn try_recv(&self) -> Option<ServerMessage> {
-        let mut rx = self.recv_rx.lock().await;
-        rx.try_recv().ok()
+    pub fn try_recv(&self) -> Option<ServerMessage> {
+        self.transport.try_recv()
     }
 
     pub fn disconnect(&mut self) {
@@ -101,57 +80,69 @@ impl NetworkClient {
     }
 }
 
-pub async fn connect_to_server(address: &str, player_name: String) -> Result<NetworkClient, String> {
-    let stream = TcpStream::connect(address)
-        .await
-        .map_err(|e| format!("Failed to connect: {}", e))?;
+/// The connection attempt in flight, if any
+#[derive(Resource, Default)]
+pub struct PendingConnection {
+    address: String,
+    receiver: Option<crossbeam_channel::Receiver<Result<NetworkClient, String>>>,
+}
 
-    let (read_half, write_half) = stream.into_split();
+impl PendingConnection {
+    pub fn in_progress(&self) -> bool {
+        self.receiver.is_some()
+    }
+}
 
-    let (send_tx, mut send_rx) = mpsc::unbounded_channel::<ClientMessage>();
-    let (recv_tx, recv_rx) = mpsc::unbounded_channel::<ServerMessage>();
-    let (disconnect_tx, disconnect_rx) = mpsc::unbounded_channel::<()>();
+pub fn start_connection(
+    trigger: On<ConnectRequest>,
+    mut pending: ResMut<PendingConnection>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) {
+    let request = trigger.event().clone();
+    if pending.in_progress() {
+        return;
+    }
+    if client.is_some_and(|client| client.connected) {
+        commands.trigger(ConnectionFailed { address: request.address, error: "Already connected".to_string() });
+        return;
+    }
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    pending.address = request.address.clone();
+    pending.receiver = Some(rx);
 
-    let disconnect_tx_write = disconnect_tx.clone();
+    println!("🌐 Connecting to {}", request.address);
     TOKIO_RUNTIME.spawn(async move {
-        let mut write_half = write_half;
-        while let Some(message) = send_rx.recv().await {
-            //println!("Client sending message: {:?}", message);
-            if let Err(e) = send_message(&mut write_half, &message).await {
-                eprintln!("Failed to send message: {}", e);
-                let _ = disconnect_tx_write.send(());
-                break;
-            }
-        }
-        println!("Client write task ended");
+        let result = connect_to_server(&request.address, request.player_name, request.role, request.accept_self_signed).await;
+        let _ = tx.send(result);
     });
+}
 
-    let recv_tx_clone = recv_tx.clone();
-    TOKIO_RUNTIME.spawn(async move {
-        let mut read_half = read_half;
-        println!("Client read task started");
-        loop {
-            match receive_message(&mut read_half).await {
-                Ok(Some(message)) => {
-                    //println!("Client received message: {:?}", message);
-                    if recv_tx_clone.send(message).is_err() {
-                        break;
-                    }
-                }
-                Ok(None) => {
-                    println!("Server disconnected");
-                    let _ = disconnect_tx.send(());
-                    break;
-                }
-                Err(e) => {
-                    eprintln!("Error receiving message: {}", e);
-                    let _ = disconnect_tx.send(());
-                    break;
-                }
-            }
+/// Install the client once its connection attempt comes back, remembering the offline world to return to
+pub fn finish_connection(
+    mut pending: ResMut<PendingConnection>,
+    mut commands: Commands,
+    world_gen: Res<crate::world_generation::WorldGenerator>,
+) {
+    let Some(result) = pending.receiver.as_ref().and_then(|rx| rx.try_recv().ok()) else { return };
+    pending.receiver = None;
+    let address = std::mem::take(&mut pending.address);
+
+    match result {
+        Ok(mut client) => {
+            client.original_seed = world_gen.seed;
+            client.original_terrain = world_gen.terrain;
+            commands.insert_resource(client);
+            commands.trigger(Connected { address });
         }
-        println!("Client read task ended");
-    });
+        Err(error) => commands.trigger(ConnectionFailed { address, error }),
+    }
+}
+
+pub async fn connect_to_server(address: &str, player_name: String, role: ClientRole, accept_self_signed: bool) -> Result<NetworkClient, String> {
+    let transport = crate::transport::connect(address, accept_self_signed).await?;
+    transport.send(ClientMessage::Join { name: player_name.clone(), role });
 
     Ok(NetworkClient {
         player_id: None,
@@ -159,60 +150,21 @@ pub async fn connect_to_server(address: &str, player_name: String) -> Result<Net
         connected: true,
         world_seed: None,
         original_seed: rand::random::<u32>(),
-        send_tx,
-        recv_rx: Arc::new(tokio::sync::Mutex::new(recv_rx)),
-        disconnect_rx: Arc::new(tokio::sync::Mutex::new(disconnect_rx)),
+        original_terrain: crate::world_generation::TerrainPreset::default(),
+        spawn_point: None,
+        role,
+        transport,
     })
 }
 
-async fn send_message(
-    writer: &mut tokio::net::tcp::OwnedWriteHalf,
-    message: &ClientMessage,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let data = bincode::serialize(message)?;
-    let len = data.len() as u32;
-    
-    writer.write_all(&len.to_le_bytes()).await?;
-    writer.write_all(&data).await?;
-    writer.flush().await?;
-    
-    Ok(())
-}
-
-async fn receive_message(
-    reader: &mut tokio::net::tcp::OwnedReadHalf,
-) -> Result<Option<ServerMessage>, Box<dyn std::error::Error>> {
-    let mut len_bytes = [0u8; 4];
-    
-    match reader.read_exact(&mut len_bytes).await {
-        Ok(_) => {}
-        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
-            return Ok(None);
-        }
-        Err(e) => return Err(Box::new(e)),
-    }
-    
-    let len = u32::from_le_bytes(len_bytes) as usize;
-    
-    if len > MAX_MESSAGE_SIZE {
-        return Err("Message too large".into());
-    }
-    
-    let mut buffer = vec![0u8; len];
-    reader.read_exact(&mut buffer).await?;
-    
-    let message = bincode::deserialize(&buffer)?;
-    Ok(Some(message))
-}
-
 pub fn send_player_updates(
     client: Option<ResMut<NetworkClient>>,
-    aircraft_query: Query<(&Transform, &crate::controls::Aircraft)>,
+    aircraft_query: Query<(&Transform, &crate::controls::Aircraft, Option<&crate::trails::SmokeTrail>)>,
     time: Res<Time>,
     mut last_send: Local<f32>,
 ) {
     let Some(client) = client else { return };
-    if !client.connected {
+    if !client.connected || client.role == ClientRole::Observer {
         return;
     }
 
@@ -222,21 +174,16 @@ pub fn send_player_updates(
     }
     *last_send = time.elapsed_secs();
 
-    if let Ok((transform, aircraft)) = aircraft_query.single() {
+    if let Ok((transform, aircraft, smoke)) = aircraft_query.single() {
         let position = transform.translation;
         let rotation = transform.rotation;
         
-        let plane_type = if aircraft.model_path.contains("f16") {
-            PlaneType::Jet
-        } else {
-            PlaneType::Light
-        };
-
         client.send(ClientMessage::UpdatePosition {
             name: client.player_name.clone(),
             position: [position.x, position.y, position.z],
             rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
-            plane_type,
+            plane_type: aircraft.plane_type,
+            smoke: smoke.and_then(|s| s.to_network()),
         });
     }
 }
@@ -245,58 +192,49 @@ pub fn check_connection_status(
     client: Option<ResMut<NetworkClient>>,
     mut commands: Commands,
     mut world_generator: ResMut<crate::world_generation::WorldGenerator>,
-    mut chunk_manager: ResMut<crate::world_generation::ChunkManager>,
-    chunks: Query<(Entity, &crate::world_generation::Chunk, Option<&Children>)>,
-    mut render_settings: ResMut<crate::RenderSettings>,
 ) {
     let Some(mut client) = client else { return };
     if !client.connected {
         return;
     }
 
-    let disconnected = TOKIO_RUNTIME.block_on(async {
-        let mut disconnect_rx = client.disconnect_rx.lock().await;
-        disconnect_rx.try_recv().is_ok()
-    });
-
-    if disconnected {
+    if client.transport.connection_lost() {
         println!("🔌 Server connection lost, triggering cleanup");
         client.connected = false;
         client.player_id = None;
         client.world_seed = None;
+        client.spawn_point = None;
         
         let original_seed = client.original_seed;
         println!("🔄 Restoring original world seed {}", original_seed);
         
-        *world_generator = crate::world_generation::WorldGenerator::new(original_seed);
-        
-        for (entity, chunk, children) in chunks.iter() {
-            chunk_manager.spawned_chunks.remove(&(chunk.x, chunk.z));
-            if let Some(children) = children {
-                for child in children.iter() {
-                    commands.entity(child).despawn();
-                }
-            }
-            commands.entity(entity).despawn();
-        }
-        
-        chunk_manager.last_camera_chunk = None;
-        chunk_manager.to_spawn.clear();
-        chunk_manager.lod_to_update.clear();
-        render_settings.just_updated = true;
-        
-        commands.trigger(DisconnectCleanup);
-        commands.trigger(RespawnAircraft);
+        *world_generator = crate::world_generation::WorldGenerator::with_terrain(original_seed, client.original_terrain);
+        commands.trigger(crate::world_generation::ResetChunks);
+        commands.trigger(Disconnected { lost: true });
     }
 }
 
+/// Switch to the server's seed, rebuild the terrain and put the aircraft back at its spawn point
+fn regenerate_world(
+    seed: u32,
+    client: &mut NetworkClient,
+    world_generator: &mut crate::world_generation::WorldGenerator,
+    commands: &mut Commands,
+) {
+    client.world_seed = Some(seed);
+    *world_generator = crate::world_generation::WorldGenerator::new(seed);
+
+    println!("🔄 Regenerating world with seed {}", seed);
+    commands.trigger(crate::world_generation::ResetChunks);
+
+    // Respawn aircraft at the server-assigned spawn point
+    commands.trigger(RespawnAircraft);
+}
+
 pub fn receive_server_messages(
     client: Option<ResMut<NetworkClient>>,
     mut commands: Commands,
     mut world_generator: ResMut<crate::world_generation::WorldGenerator>,
-    mut chunk_manager: ResMut<crate::world_generation::ChunkManager>,
-    chunks: Query<(Entity, &crate::world_generation::Chunk, Option<&Children>)>,
-    mut render_settings: ResMut<crate::RenderSettings>,
     mut day_cycle: ResMut<crate::day_cycle::DayNightCycle>,
 ) {
     let Some(mut client) = client else { return };
@@ -304,66 +242,99 @@ pub fn receive_server_messages(
         return; 
     }
 
-    TOKIO_RUNTIME.block_on(async {
-        while let Some(message) = client.try_recv().await {
-                match message {
-                    ServerMessage::Welcome { your_id, seed, existing_players, time_of_day, speed } => {
-                        println!("✅ Connected to server! Player ID: {}, Seed: {}", your_id, seed);
-                        client.player_id = Some(your_id);
-                        client.world_seed = Some(seed);
-                        
-                        day_cycle.time_of_day = time_of_day;
-                        day_cycle.speed = speed;
-                        
-                        // Update world generator with server seed
-                        *world_generator = crate::world_generation::WorldGenerator::new(seed);
-                        
-                        // Despawn all existing chunks and their vegetation
-                        for (entity, chunk, children) in chunks.iter() {
-                            chunk_manager.spawned_chunks.remove(&(chunk.x, chunk.z));
-                            if let Some(children) = children {
-                                for child in children.iter() {
-                                    commands.entity(child).despawn();
-                                }
-                            }
-                            commands.entity(entity).despawn();
-                        }
-                        
-                        println!("🔄 Regenerating world with seed {}", seed);
-                        
-                        // Reset chunk manager state to trigger regeneration
-                        chunk_manager.last_camera_chunk = None;
-                        chunk_manager.to_spawn.clear();
-                        chunk_manager.lod_to_update.clear();
-                        
-                        // Force chunk regeneration
-                        render_settings.just_updated = true;
-                        
-                        // Respawn aircraft at spawn position
-                        commands.trigger(RespawnAircraft);
-                        
-                        for player in existing_players {
-                            println!("Player {} already in game", player.id);
-                            commands.trigger(SpawnRemotePlayer(player));
-                        }
-                    }
-                    ServerMessage::PlayerJoined { player } => {
-                        println!("Player {} joined", player.id);
-                        commands.trigger(SpawnRemotePlayer(player));
-                    }
-                    ServerMessage::PlayerUpdate { id, name, position, rotation, plane_type } => {
-                        commands.trigger(UpdateRemotePlayer { id, name, position, rotation, plane_type });
-                    }
-                    ServerMessage::PlayerLeft { id } => {
-                        println!("Player {} left", id);
-                        commands.trigger(DespawnRemotePlayer(id));
-                    }
-                    ServerMessage::Error { message } => {
-                        eprintln!("Server error: {}", message);
-                    }
+    while let Some(message) = client.try_recv() {
+        match message {
+            ServerMessage::Welcome { your_id, seed, existing_players, time_of_day, speed, spawn_point } => {
+                println!("✅ Connected to server! Player ID: {}, Seed: {}", your_id, seed);
+                client.player_id = Some(your_id);
+                client.spawn_point = Some(spawn_point);
+                
+                day_cycle.time_of_day = time_of_day;
+                day_cycle.speed = speed;
+                
+                regenerate_world(seed, &mut client, &mut world_generator, &mut commands);
+                
+                for player in existing_players {
+                    println!("Player {} already in game", player.id);
+                    commands.trigger(SpawnRemotePlayer(player));
                 }
             }
-    });
+            ServerMessage::PlayerJoined { player } => {
+                println!("Player {} joined", player.id);
+                commands.trigger(SpawnRemotePlayer(player));
+            }
+            ServerMessage::PlayerUpdate { id, name, position, rotation, plane_type, smoke } => {
+                commands.trigger(UpdateRemotePlayer { id, name, position, rotation, plane_type, smoke });
+            }
+            ServerMessage::PlayerLeft { id } => {
+                println!("Player {} left", id);
+                commands.trigger(DespawnRemotePlayer(id));
+            }
+            ServerMessage::Fire { id: _, position, velocity } => {
+                commands.trigger(RemoteGunfire { position: position.into(), velocity: velocity.into() });
+            }
+            ServerMessage::Hit { shooter } => {
+                commands.trigger(IncomingHit { shooter });
+            }
+            ServerMessage::ShotDown { id, by } => {
+                commands.trigger(PlayerShotDown { id, by });
+            }
+            ServerMessage::TimeTrialCourse { gates, gate_radius } => {
+                commands.trigger(TimeTrialCourseReceived {
+                    gates: gates.into_iter().map(Vec2::from).collect(),
+                    gate_radius,
+                });
+            }
+            ServerMessage::TimeTrialResult { accepted, message } => {
+                println!("🏁 Time trial: {}", message);
+                commands.trigger(TimeTrialResultReceived { accepted, message });
+            }
+            ServerMessage::Leaderboard { entries } => {
+                commands.trigger(LeaderboardReceived(entries));
+            }
+            ServerMessage::DailyResult { accepted, message } => {
+                println!("📅 Daily flight: {}", message);
+                commands.trigger(DailyResultReceived { accepted, message });
+            }
+            ServerMessage::DailyLeaderboard { day, entries } => {
+                commands.trigger(DailyLeaderboardReceived { day, entries });
+            }
+            ServerMessage::Voice { id, frame } => {
+                commands.trigger(VoiceFrameReceived { id, frame });
+            }
+            ServerMessage::Instruction { from, text } => {
+                println!("🗼 {}: {}", from, text);
+                commands.trigger(InstructionReceived { from, text });
+            }
+            ServerMessage::Microburst { center, radius, strength, duration } => {
+                commands.trigger(MicroburstReported { center: Vec2::from(center), radius, strength, duration });
+            }
+            ServerMessage::Lightning { position } => {
+                commands.trigger(LightningReported { position: Vec2::from(position) });
+            }
+            ServerMessage::PlayerFlagged { id, reason } => {
+                println!("🛡 Player {} flagged: {}", id, reason);
+                commands.trigger(PlayerFlagged { id, reason });
+            }
+            ServerMessage::Replicate { owner, entity, kind, data } => {
+                commands.trigger(crate::replication::ReplicaReceived { owner, entity, kind, data });
+            }
+            ServerMessage::DespawnReplica { owner, entity } => {
+                commands.trigger(crate::replication::ReplicaDespawned { owner, entity });
+            }
+            ServerMessage::WorldUpdate { seed, time_of_day, speed } => {
+                day_cycle.time_of_day = time_of_day;
+                day_cycle.speed = speed;
+                if client.world_seed != Some(seed) {
+                    println!("🗺 Server changed the map");
+                    regenerate_world(seed, &mut client, &mut world_generator, &mut commands);
+                }
+            }
+            ServerMessage::Error { message } => {
+                eprintln!("Server error: {}", message);
+            }
+        }
+    }
 }
 
 #[derive(Event)]
@@ -376,13 +347,129 @@ pub struct UpdateRemotePlayer {
     pub position: [f32; 3],
     pub rotation: [f32; 4],
     pub plane_type: PlaneType,
+    pub smoke: Option<[u8; 3]>,
 }
 
 #[derive(Event)]
 pub struct DespawnRemotePlayer(pub u32);
 
+/// Join `address` in the background; ignored while another attempt is running
+#[derive(Event, Debug, Clone)]
+pub struct ConnectRequest {
+    pub address: String,
+    pub player_name: String,
+    pub role: ClientRole,
+    pub accept_self_signed: bool,
+}
+
+/// A connection attempt succeeded and `NetworkClient` is now a resource
+#[derive(Event)]
+pub struct Connected {
+    pub address: String,
+}
+
+#[derive(Event)]
+pub struct ConnectionFailed {
+    pub address: String,
+    pub error: String,
+}
+
+/// The server is gone and the offline world is being restored
+#[derive(Event)]
+pub struct Disconnected {
+    /// The connection dropped, rather than the player leaving
+    pub lost: bool,
+}
+
+/// Leave the server on purpose and go back to the offline world
 #[derive(Event)]
-pub struct DisconnectCleanup;
+pub struct LeaveServer;
+
+/// A round fired by another player
+#[derive(Event)]
+pub struct RemoteGunfire {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+/// Another player's round hit our aircraft
+#[derive(Event)]
+pub struct IncomingHit {
+    pub shooter: u32,
+}
+
+/// Another player was shot down
+#[derive(Event)]
+pub struct PlayerShotDown {
+    pub id: u32,
+    pub by: u32,
+}
+
+/// The server's movement checks caught another player flying implausibly
+#[derive(Event)]
+pub struct PlayerFlagged {
+    pub id: u32,
+    pub reason: String,
+}
+
+/// The server's time-trial course, gate centres on the ground plane in flying order
+#[derive(Event)]
+pub struct TimeTrialCourseReceived {
+    pub gates: Vec<Vec2>,
+    pub gate_radius: f32,
+}
+
+/// The server's verdict on a submitted time-trial run
+#[derive(Event)]
+pub struct TimeTrialResultReceived {
+    pub accepted: bool,
+    pub message: String,
+}
+
+#[derive(Event)]
+pub struct LeaderboardReceived(pub Vec<LeaderboardEntry>);
+
+/// The server's verdict on a submitted daily-flight time
+#[derive(Event)]
+pub struct DailyResultReceived {
+    pub accepted: bool,
+    pub message: String,
+}
+
+#[derive(Event)]
+pub struct DailyLeaderboardReceived {
+    pub day: u32,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// An Opus voice frame from another player
+#[derive(Event)]
+pub struct VoiceFrameReceived {
+    pub id: u32,
+    pub frame: Vec<u8>,
+}
+
+/// A text instruction from a ground-control observer
+#[derive(Event)]
+pub struct InstructionReceived {
+    pub from: String,
+    pub text: String,
+}
+
+/// A microburst scheduled by the server, shared by everyone in the session
+#[derive(Event)]
+pub struct MicroburstReported {
+    pub center: Vec2,
+    pub radius: f32,
+    pub strength: f32,
+    pub duration: f32,
+}
+
+/// A lightning strike called by the server, seen and heard by everyone in the session
+#[derive(Event)]
+pub struct LightningReported {
+    pub position: Vec2,
+}
 
 #[derive(Event)]
 pub struct RespawnAircraft;
@@ -399,8 +486,24 @@ pub struct RemotePlayer {
     pub player_id: u32,
     pub name: String,
     pub plane_type: PlaneType,
+    /// Why the server flagged this pilot, shown as a warning next to their name
+    pub flagged: Option<String>,
+}
+
+impl RemotePlayer {
+    /// Label for player lists, with a warning sign in front of flagged pilots
+    pub fn list_label(&self) -> String {
+        match self.flagged {
+            Some(_) => format!("⚠ Player {}", self.player_id),
+            None => format!("Player {}", self.player_id),
+        }
+    }
 }
 
+/// Remote player entities by server id, so per-frame and per-message lookups don't scan every player
+#[derive(Resource, Default)]
+pub struct RemotePlayers(pub HashMap<u32, Entity>);
+
 #[derive(Component)]
 pub struct LerpTarget {
     pub position: Vec3,
@@ -417,14 +520,13 @@ pub struct NetworkSmoothingSettings {
 #[derive(Component)]
 pub struct PlayerLabel;
 
-#[derive(Component)]
-pub struct PlayerLabelText {
-    pub player_id: u32,
-}
 
-#[derive(Component)]
-pub struct PlayerDistanceLabel {
-    pub player_id: u32,
+/// Scene and scale other players' aircraft are drawn with
+pub fn remote_model(plane_type: PlaneType) -> (&'static str, f32) {
+    match plane_type {
+        PlaneType::Light | PlaneType::Glider => ("low-poly_airplane/scene.gltf#Scene0", 0.4),
+        PlaneType::Jet => ("f16_low_poly/scene.gltf#Scene0", 30.0),
+    }
 }
 
 pub fn spawn_remote_player(
@@ -432,29 +534,27 @@ pub fn spawn_remote_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut players: ResMut<RemotePlayers>,
 ) {
     let player_state = &trigger.0;
+    // A repeated join for the same id replaces the old aircraft rather than leaving a ghost behind
+    if let Some(stale) = players.0.remove(&player_state.id) {
+        commands.entity(stale).try_despawn();
+    }
     
     let position = Vec3::from(player_state.position);
     let rotation = Quat::from_array(player_state.rotation);
 
-    let (model_path, model_scale) = match player_state.plane_type { 
-        PlaneType::Light => ("low-poly_airplane/scene.gltf#Scene0", 0.4),
-        PlaneType::Jet => ("f16_low_poly/scene.gltf#Scene0", 30.0),
-    };
-
-    let display_name = if player_state.name == "Pilot" {
-        format!("Pilot {}", player_state.id)
-    } else {
-        player_state.name.clone()
-    };
+    let (model_path, model_scale) = remote_model(player_state.plane_type);
 
     let plane_entity = commands.spawn((
         RemotePlayer { 
             player_id: player_state.id,
             name: player_state.name.clone(),
             plane_type: player_state.plane_type,
+            flagged: player_state.flagged.clone(),
         },
+        crate::trails::SmokeTrail::from_network(player_state.smoke),
         Transform::from_translation(position)
             .with_rotation(rotation)
             .with_scale(Vec3::splat(model_scale)),
@@ -484,104 +584,51 @@ pub fn spawn_remote_player(
         PlayerLabel,
     )).id();
 
-    commands.spawn((
-        Text::new(display_name),
-        Node {
-            position_type: PositionType::Absolute,
-            ..default()
-        },
-        TextFont {
-            font_size: 20.0,
-            ..default()
-        },
-        TextColor(Color::srgb(1.0, 1.0, 1.0)),
-        PlayerLabelText {
-            player_id: player_state.id,
-        },
-        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-    ));
-
-    commands.spawn((
-        Text::new("0m"),
-        Node {
-            position_type: PositionType::Absolute,
-            ..default()
-        },
-        TextFont {
-            font_size: 16.0,
-            ..default()
-        },
-        TextColor(Color::srgb(0.8, 0.8, 0.8)),
-        PlayerDistanceLabel {
-            player_id: player_state.id,
-        },
-        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-    ));
-
     commands.entity(plane_entity).add_children(&[model_correction, label]);
+    players.0.insert(player_state.id, plane_entity);
 }
 
 pub fn update_remote_player(
     trigger: On<UpdateRemotePlayer>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut query: Query<(&mut LerpTarget, &mut Transform, &Children), With<RemotePlayer>>,
-    mut remote_players: Query<(Entity, &mut RemotePlayer)>,
+    players: Res<RemotePlayers>,
+    mut query: Query<(&mut RemotePlayer, &mut LerpTarget, &mut Transform, &Children, &mut crate::trails::SmokeTrail)>,
     scene_query: Query<Entity, With<SceneRoot>>,
-    mut label_query: Query<(&mut Text, &PlayerLabelText)>,
 ) {
     let event = &trigger;
+    let Some(&entity) = players.0.get(&event.id) else { return };
+    let Ok((mut remote_player, mut lerp_target, mut transform, children, mut smoke)) = query.get_mut(entity) else { return };
+
+    lerp_target.last_position = lerp_target.position;
+    lerp_target.position = Vec3::from(event.position);
+    lerp_target.rotation = Quat::from_array(event.rotation);
+    *smoke = crate::trails::SmokeTrail::from_network(event.smoke);
     
-    for (entity, mut remote_player) in remote_players.iter_mut() {
-        if remote_player.player_id == event.id {
-            if let Ok((mut lerp_target, mut transform, children)) = query.get_mut(entity) {
-                lerp_target.last_position = lerp_target.position;
-                lerp_target.position = Vec3::from(event.position);
-                lerp_target.rotation = Quat::from_array(event.rotation);
+    // The nameplate picks up the change from here
+    if remote_player.name != event.name {
+        remote_player.name = event.name.clone();
+    }
+    
+    if remote_player.plane_type != event.plane_type {
+        remote_player.plane_type = event.plane_type;
+        
+        let (model_path, model_scale) = remote_model(event.plane_type);
+        
+        transform.scale = Vec3::splat(model_scale);
+        
+        for child in children.iter() {
+            if scene_query.contains(child) {
+                commands.entity(child).despawn();
                 
-                if remote_player.name != event.name {
-                    remote_player.name = event.name.clone();
-                    
-                    let display_name = if event.name == "Pilot" {
-                        format!("Pilot {}", event.id)
-                    } else {
-                        event.name.clone()
-                    };
-                    
-                    for (mut text, label) in label_query.iter_mut() {
-                        if label.player_id == event.id {
-                            **text = display_name;
-                            break;
-                        }
-                    }
-                }
+                let model_correction = commands.spawn((
+                    SceneRoot(asset_server.load(model_path)),
+                    Transform::from_rotation(Quat::from_rotation_y((180.0f32).to_radians())),
+                )).id();
                 
-                if remote_player.plane_type != event.plane_type {
-                    remote_player.plane_type = event.plane_type;
-                    
-                    let (model_path, model_scale) = match event.plane_type {
-                        PlaneType::Light => ("low-poly_airplane/scene.gltf#Scene0", 0.2),
-                        PlaneType::Jet => ("f16_low_poly/scene.gltf#Scene0", 3.0),
-                    };
-                    
-                    transform.scale = Vec3::splat(model_scale);
-                    
-                    for child in children.iter() {
-                        if scene_query.contains(child) {
-                            commands.entity(child).despawn();
-                            
-                            let model_correction = commands.spawn((
-                                SceneRoot(asset_server.load(model_path)),
-                                Transform::from_rotation(Quat::from_rotation_y((180.0f32).to_radians())),
-                            )).id();
-                            
-                            commands.entity(entity).add_child(model_correction);
-                            break;
-                        }
-                    }
-                }
+                commands.entity(entity).add_child(model_correction);
+                break;
             }
-            break;
         }
     }
 }
@@ -606,126 +653,91 @@ pub fn lerp_remote_players(
 pub fn despawn_remote_player(
     trigger: On<DespawnRemotePlayer>,
     mut commands: Commands,
-    query: Query<(Entity, &RemotePlayer)>,
-    label_query: Query<(Entity, &PlayerLabelText)>,
-    distance_label_query: Query<(Entity, &PlayerDistanceLabel)>,
+    mut players: ResMut<RemotePlayers>,
 ) {
-    let player_id = trigger.0;
-    
-    for (entity, remote_player) in query.iter() {
-        if remote_player.player_id == player_id {
-            commands.entity(entity).despawn();
-            break;
-        }
-    }
-    
-    for (entity, label_text) in label_query.iter() {
-        if label_text.player_id == player_id {
-            commands.entity(entity).despawn();
-            break;
-        }
-    }
-    
-    for (entity, distance_label) in distance_label_query.iter() {
-        if distance_label.player_id == player_id {
-            commands.entity(entity).despawn();
-            break;
-        }
+    // The nameplate goes with the aircraft
+    if let Some(entity) = players.0.remove(&trigger.0) {
+        commands.entity(entity).try_despawn();
     }
 }
 
+pub fn flag_remote_player(trigger: On<PlayerFlagged>, players: Res<RemotePlayers>, mut query: Query<&mut RemotePlayer>) {
+    let Some(&entity) = players.0.get(&trigger.id) else { return };
+    if let Ok(mut remote_player) = query.get_mut(entity) {
+        remote_player.flagged = Some(trigger.reason.clone());
+    }
+}
 
 pub fn cleanup_on_disconnect(
-    _trigger: On<DisconnectCleanup>,
+    _trigger: On<Disconnected>,
     mut commands: Commands,
-    query: Query<Entity, With<RemotePlayer>>,
-    label_query: Query<Entity, With<PlayerLabelText>>,
-    distance_label_query: Query<Entity, With<PlayerDistanceLabel>>,
+    mut players: ResMut<RemotePlayers>,
 ) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
-    }
-    
-    for entity in label_query.iter() {
-        commands.entity(entity).despawn();
-    }
-    
-    for entity in distance_label_query.iter() {
-        commands.entity(entity).despawn();
+    for (_, entity) in players.0.drain() {
+        commands.entity(entity).try_despawn();
     }
 }
 
-pub fn update_player_labels(
-    camera_query: Query<(&GlobalTransform, &Camera), With<crate::controls::MainCamera>>,
-    remote_players: Query<(&GlobalTransform, &RemotePlayer)>,
-    aircraft_query: Query<&Transform, With<crate::controls::Aircraft>>,
-    mut label_text_query: Query<(&mut Node, &PlayerLabelText)>,
-    mut distance_label_query: Query<(&mut Node, &mut Text, &PlayerDistanceLabel), Without<PlayerLabelText>>,
+pub fn leave_server(
+    _trigger: On<LeaveServer>,
+    client: Option<ResMut<NetworkClient>>,
+    mut commands: Commands,
+    mut world_generator: ResMut<crate::world_generation::WorldGenerator>,
 ) {
-    let Ok((camera_transform, camera)) = camera_query.single() else { return };
-    let Ok(aircraft_transform) = aircraft_query.single() else { return };
-    
-    for (mut style, label_text) in label_text_query.iter_mut() {
-        for (player_transform, remote_player) in remote_players.iter() {
-            if remote_player.player_id == label_text.player_id {
-                let player_pos = player_transform.translation() + Vec3::new(0.0, 40.0, 0.0);
-                
-                if let Ok(screen_pos) = camera.world_to_viewport(camera_transform, player_pos) {
-                    style.left = Val::Px(screen_pos.x);
-                    style.top = Val::Px(screen_pos.y);
-                } else {
-                    style.left = Val::Px(-1000.0);
-                    style.top = Val::Px(-1000.0);
-                }
-                break;
-            }
-        }
-    }
-    
-    for (mut style, mut text, distance_label) in distance_label_query.iter_mut() {
-        for (player_transform, remote_player) in remote_players.iter() {
-            if remote_player.player_id == distance_label.player_id {
-                let distance = aircraft_transform.translation.distance(player_transform.translation());
-                let meters = world_units_to_meters(distance);
-                **text =  if distance < 1000.0 {
-                    format!("{:.0}m", meters)
-                } else {
-                    format!("{}km", (meters / 100.0).round() / 10.0)
-                };
-                
-                let player_pos = player_transform.translation() + Vec3::new(0.0, 40.0, 0.0);
-                
-                if let Ok(screen_pos) = camera.world_to_viewport(camera_transform, player_pos) {
-                    style.left = Val::Px(screen_pos.x);
-                    style.top = Val::Px(screen_pos.y + 24.0);
-                } else {
-                    style.left = Val::Px(-1000.0);
-                    style.top = Val::Px(-1000.0);
-                }
-                break;
-            }
-        }
-    }
+    let Some(mut client) = client else { return };
+    let original_seed = client.original_seed;
+    client.disconnect();
+    commands.remove_resource::<NetworkClient>();
+
+    *world_generator = crate::world_generation::WorldGenerator::with_terrain(original_seed, client.original_terrain);
+
+    commands.trigger(crate::world_generation::ResetChunks);
+    commands.trigger(Disconnected { lost: false });
 }
 
-pub fn teleport_to_player(
-    trigger: On<TeleportToPlayer>,
-    mut aircraft_query: Query<&mut Transform, With<crate::controls::Aircraft>>,
-    mut camera_query: Query<&mut Transform, (With<crate::controls::MainCamera>, Without<crate::controls::Aircraft>)>,
+/// Why the aircraft moved, or didn't, when the world changed back after leaving a server
+#[derive(Resource, Default)]
+pub struct DisconnectNotice {
+    pub text: String,
+    /// Elapsed seconds when the notice was raised, `None` once there's nothing to show
+    pub shown_at: Option<f32>,
+}
+
+/// The offline world has different terrain under the same coordinates. Keep flying from where we are
+/// if that's still clear of the ground, otherwise climb clear of it, and respawn a wreck outright
+pub fn settle_after_disconnect(
+    trigger: On<Disconnected>,
+    mut aircraft_query: Query<(&mut Transform, &crate::controls::Aircraft)>,
+    world_gen: Res<crate::world_generation::WorldGenerator>,
+    time: Res<Time>,
+    mut notice: ResMut<DisconnectNotice>,
+    mut commands: Commands,
 ) {
+    let Ok((mut transform, aircraft)) = aircraft_query.single_mut() else { return };
+    let reason = if trigger.lost { "Connection lost" } else { "Left the server" };
+    let terrain_height = world_gen.get_terrain_height(&transform.translation.to_array());
+
+    let outcome = if aircraft.crashed {
+        commands.trigger(RespawnAircraft);
+        "respawned over your own world"
+    } else if transform.translation.y < terrain_height + DISCONNECT_TERRAIN_CLEARANCE {
+        transform.translation.y = terrain_height + aircraft.respawn_height;
+        "climbed clear of your own world's terrain, which is higher here"
+    } else {
+        "still flying, over your own world's terrain"
+    };
+    println!("🔄 {}: {}", reason, outcome);
+    notice.text = format!("{}: {}", reason, outcome);
+    notice.shown_at = Some(time.elapsed_secs());
+}
+
+pub fn teleport_to_player(trigger: On<TeleportToPlayer>, mut commands: Commands) {
     let event = &trigger;
-    
-    if let Ok(mut transform) = aircraft_query.single_mut() {
-        transform.translation = Vec3::from(event.position);
-        transform.rotation = Quat::from_array(event.rotation);
-        
-        if let Ok(mut camera_transform) = camera_query.single_mut() {
-            camera_transform.translation = transform.translation + Vec3::new(0.0, 9.0, 0.0);
-            camera_transform.rotation = camera_transform.looking_at(transform.translation, Vec3::Y).rotation;
-        }
-        
-        println!("Teleported to Player {}", event.player_id);
-    }
+    commands.trigger(crate::teleport::Teleport {
+        position: Vec3::from(event.position),
+        rotation: Quat::from_array(event.rotation),
+    });
+    println!("Teleporting to Player {}", event.player_id);
 }
 
 pub fn respawn_aircraft(
@@ -733,13 +745,18 @@ pub fn respawn_aircraft(
     mut aircraft_query: Query<(&mut Transform, &mut crate::controls::Aircraft)>,
     mut camera_query: Query<(&mut Transform, &mut crate::controls::MainCamera), Without<crate::controls::Aircraft>>,
     world_gen: Res<crate::world_generation::WorldGenerator>,
+    client: Option<Res<NetworkClient>>,
 ) {
     if let Ok((mut transform, mut aircraft)) = aircraft_query.single_mut() {
-        let spawn_pos = [0.0, 0.0, 0.0];
+        let [spawn_x, spawn_z] = client
+            .filter(|client| client.connected)
+            .and_then(|client| client.spawn_point)
+            .unwrap_or([0.0, 0.0]);
+        let spawn_pos = [spawn_x, 0.0, spawn_z];
         let terrain_height = world_gen.get_terrain_height(&spawn_pos);
         let spawn_height = (terrain_height + aircraft.respawn_height).max(aircraft.respawn_height * 2.0);
         
-        transform.translation = Vec3::new(0.0, spawn_height, 0.0);
+        transform.translation = Vec3::new(spawn_x, spawn_height, spawn_z);
         transform.rotation = Quat::IDENTITY;
         
         aircraft.crashed = false;
@@ -749,6 +766,7 @@ pub fn respawn_aircraft(
         aircraft.pitch_velocity = 0.0;
         aircraft.roll_velocity = 0.0;
         aircraft.yaw_velocity = 0.0;
+        aircraft.spin_rate = 0.0;
         
         if let Ok((mut camera_transform, mut main_camera)) = camera_query.single_mut() {
             main_camera.orbit_yaw = 0.0;
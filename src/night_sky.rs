@@ -0,0 +1,250 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::visibility::NoFrustumCulling,
+    light::NotShadowCaster,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::MainCamera;
+use crate::day_cycle::DayNightCycle;
+use crate::season::Season;
+use crate::world_generation::{ChunkManager, WorldGenerator};
+
+/// Columns along the aurora's arc and rows up its curtain
+const AURORA_COLUMNS: usize = 96;
+const AURORA_ROWS: usize = 6;
+/// Half the arc the aurora spans across the poleward sky
+const AURORA_HALF_SPAN: f32 = 75.0 * PI / 180.0;
+/// Elevation of the curtain's lower edge and how tall it hangs
+const AURORA_BASE_ELEVATION: f32 = 12.0 * PI / 180.0;
+const AURORA_HEIGHT: f32 = 28.0 * PI / 180.0;
+/// In front of the stars, which sit at one render distance
+const AURORA_DISTANCE_FACTOR: f32 = 0.9;
+/// Chance a given night has an aurora at all
+const AURORA_NIGHT_CHANCE: f32 = 0.35;
+/// Normalized climate temperature at which auroras start to show, and where they reach full strength
+const AURORA_WARM_LIMIT: f32 = 0.45;
+const AURORA_FULL_COLD: f32 = 0.2;
+/// Latitudes, in degrees, where the auroral oval starts to reach overhead and where it is strongest
+const AURORA_MIN_LATITUDE: f32 = 50.0;
+const AURORA_MAX_LATITUDE: f32 = 70.0;
+/// How quickly the aurora fades in and out, in strength per second
+const AURORA_FADE_RATE: f32 = 0.1;
+const AURORA_LOWER_COLOR: Vec3 = Vec3::new(0.15, 1.0, 0.45);
+const AURORA_UPPER_COLOR: Vec3 = Vec3::new(0.55, 0.2, 0.85);
+
+/// Shooting stars per second under a fully dark sky
+const SHOOTING_STAR_RATE: f32 = 0.08;
+const SHOOTING_STAR_MIN_LIFETIME: f32 = 0.5;
+const SHOOTING_STAR_MAX_LIFETIME: f32 = 1.2;
+/// Arc travelled across the sky, in radians per second
+const SHOOTING_STAR_SPEED: f32 = 0.35;
+/// Streak size per chunk of render distance, matching the scale the stars use
+const SHOOTING_STAR_WIDTH: f32 = 1.2;
+const SHOOTING_STAR_LENGTH: f32 = 40.0;
+const SHOOTING_STAR_BRIGHTNESS: f32 = 14.0;
+
+/// Whether tonight has an aurora, and how far it has faded in
+#[derive(Resource, Default)]
+pub struct NightSky {
+    aurora_tonight: bool,
+    was_dark: bool,
+    aurora_strength: f32,
+}
+
+#[derive(Component)]
+pub struct Aurora;
+
+#[derive(Component)]
+pub struct ShootingStar {
+    start: Vec3,
+    axis: Vec3,
+    age: f32,
+    lifetime: f32,
+    material: Handle<StandardMaterial>,
+}
+
+/// Ribbon of vertex-coloured quads, rebuilt on the CPU each frame like the sky dome
+pub fn spawn_aurora(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let vertex_count = AURORA_COLUMNS * AURORA_ROWS;
+    let mut indices = Vec::with_capacity((AURORA_COLUMNS - 1) * (AURORA_ROWS - 1) * 6);
+    for column in 0..AURORA_COLUMNS - 1 {
+        for row in 0..AURORA_ROWS - 1 {
+            let i = (column * AURORA_ROWS + row) as u32;
+            let next = i + AURORA_ROWS as u32;
+            indices.extend_from_slice(&[i, next, i + 1, i + 1, next, next + 1]);
+        }
+    }
+    let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0f32, 0.0, 0.0]; vertex_count])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0f32, -1.0, 0.0]; vertex_count])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.0f32, 0.0, 0.0, 0.0]; vertex_count])
+        .with_inserted_indices(Indices::U32(indices));
+
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            fog_enabled: false,
+            cull_mode: None,
+            alpha_mode: AlphaMode::Add,
+            ..default()
+        })),
+        Transform::default(),
+        Visibility::Hidden,
+        Aurora,
+        NoFrustumCulling,
+        NotShadowCaster,
+    ));
+}
+
+/// Direction on the unit sky sphere for an azimuth measured from world north (-X) towards east (-Z)
+fn sky_direction(azimuth: f32, elevation: f32) -> Vec3 {
+    Vec3::new(-azimuth.cos() * elevation.cos(), elevation.sin(), -azimuth.sin() * elevation.cos())
+}
+
+/// Roll for tonight's aurora at dusk, then ripple it across the poleward sky over cold ground
+pub fn update_aurora(
+    time: Res<Time>,
+    cycle: Res<DayNightCycle>,
+    season: Res<Season>,
+    world_gen: Res<WorldGenerator>,
+    chunk_manager: Res<ChunkManager>,
+    mut night_sky: ResMut<NightSky>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<Aurora>)>,
+    mut aurora_query: Query<(&mut Transform, &mut Visibility, &Mesh3d), With<Aurora>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else { return };
+    let Ok((mut transform, mut visibility, mesh_handle)) = aurora_query.single_mut() else { return };
+
+    let darkness = cycle.star_visibility();
+    let dark = darkness > 0.0;
+    if dark && !night_sky.was_dark {
+        night_sky.aurora_tonight = rand::random::<f32>() < AURORA_NIGHT_CHANCE;
+    }
+    night_sky.was_dark = dark;
+
+    // The sim has one latitude for the sky, so the temperature field stands in for how far poleward the aircraft is
+    let camera = camera_transform.translation;
+    let (temperature, _) = world_gen.get_climate(&[camera.x, camera.y, camera.z]);
+    let cold = ((AURORA_WARM_LIMIT - (temperature + season.temperature_shift())) / (AURORA_WARM_LIMIT - AURORA_FULL_COLD)).clamp(0.0, 1.0);
+    let polar = ((cycle.latitude.abs() - AURORA_MIN_LATITUDE) / (AURORA_MAX_LATITUDE - AURORA_MIN_LATITUDE)).clamp(0.0, 1.0);
+    let target = if night_sky.aurora_tonight { darkness * (cold + polar).min(1.0) } else { 0.0 };
+    let max_step = AURORA_FADE_RATE * time.delta_secs();
+    night_sky.aurora_strength += (target - night_sky.aurora_strength).clamp(-max_step, max_step);
+
+    let strength = night_sky.aurora_strength;
+    if strength <= 0.001 {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+    transform.translation = camera;
+    transform.scale = Vec3::splat(CHUNK_SIZE * chunk_manager.render_distance as f32 * AURORA_DISTANCE_FACTOR);
+
+    let Some(mesh) = meshes.get_mut(&mesh_handle.0) else { return };
+    // Towards the magnetic pole: north in the northern hemisphere, south in the southern
+    let poleward = if cycle.latitude >= 0.0 { 0.0 } else { PI };
+    let t = time.elapsed_secs();
+    let mut positions = Vec::with_capacity(AURORA_COLUMNS * AURORA_ROWS);
+    let mut colors = Vec::with_capacity(AURORA_COLUMNS * AURORA_ROWS);
+    for column in 0..AURORA_COLUMNS {
+        let along = column as f32 / (AURORA_COLUMNS - 1) as f32;
+        // Slow folds drift along the curtain while faster rays flicker through it
+        let fold = (along * 9.0 + t * 0.15).sin() * 0.6 + (along * 23.0 - t * 0.4).sin() * 0.4;
+        let rays = ((along * 61.0 + t * 1.3).sin() * (along * 17.0 - t * 0.7).sin()).abs();
+        let azimuth = poleward + (along * 2.0 - 1.0) * AURORA_HALF_SPAN + fold * 0.04;
+        let base = AURORA_BASE_ELEVATION + fold * 0.05;
+        let ends = (along * PI).sin();
+        let brightness = strength * ends * (0.35 + 0.65 * rays);
+        for row in 0..AURORA_ROWS {
+            let up = row as f32 / (AURORA_ROWS - 1) as f32;
+            let elevation = (base + up * AURORA_HEIGHT * (0.7 + 0.3 * rays)).min(FRAC_PI_2);
+            positions.push(sky_direction(azimuth, elevation).to_array());
+            // Bright lower edge fading out towards the top, green shading into violet
+            let profile = (up * 6.0).min(1.0) * (1.0 - up).powf(1.5);
+            let color = AURORA_LOWER_COLOR.lerp(AURORA_UPPER_COLOR, up) * brightness * profile;
+            colors.push(Color::srgb(color.x, color.y, color.z).to_linear().to_f32_array());
+        }
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+/// Occasional meteors streaking across the same sky the stars twinkle in, fading with them at dawn
+pub fn update_shooting_stars(
+    mut commands: Commands,
+    time: Res<Time>,
+    cycle: Res<DayNightCycle>,
+    chunk_manager: Res<ChunkManager>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<ShootingStar>)>,
+    mut stars: Query<(Entity, &mut ShootingStar, &mut Transform)>,
+    mut streak_mesh: Local<Option<Handle<Mesh>>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else { return };
+    let dt = time.delta_secs();
+    let visibility = cycle.star_visibility();
+    let render_distance = chunk_manager.render_distance as f32;
+    let star_distance = CHUNK_SIZE * render_distance;
+
+    if visibility > 0.0 && rand::random::<f32>() < SHOOTING_STAR_RATE * visibility * dt {
+        let azimuth = rand::random::<f32>() * TAU;
+        let elevation = 0.35 + rand::random::<f32>() * 0.9;
+        let start = sky_direction(azimuth, elevation);
+        // Mostly falling towards the horizon, at a random slant
+        let heading = sky_direction(azimuth + (rand::random::<f32>() - 0.5) * PI, elevation - 0.3) - start;
+        let axis = start.cross(heading).normalize_or(Vec3::X);
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgba(1.0, 1.0, 1.0, 0.0),
+            alpha_mode: AlphaMode::Blend,
+            fog_enabled: false,
+            ..default()
+        });
+        let mesh = streak_mesh.get_or_insert_with(|| meshes.add(Cuboid::new(1.0, 1.0, 1.0))).clone();
+        commands.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(material.clone()),
+            Transform::from_scale(Vec3::ZERO),
+            NotShadowCaster,
+            ShootingStar {
+                start,
+                axis,
+                age: 0.0,
+                lifetime: SHOOTING_STAR_MIN_LIFETIME + rand::random::<f32>() * (SHOOTING_STAR_MAX_LIFETIME - SHOOTING_STAR_MIN_LIFETIME),
+                material,
+            },
+        ));
+    }
+
+    for (entity, mut star, mut transform) in &mut stars {
+        star.age += dt;
+        if star.age >= star.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let direction = Quat::from_axis_angle(star.axis, star.age * SHOOTING_STAR_SPEED) * star.start;
+        let travel = star.axis.cross(direction);
+        // Flares up, then burns out
+        let life = star.age / star.lifetime;
+        let brightness = (life * 5.0).min(1.0) * (1.0 - life) * visibility;
+
+        *transform = Transform::from_translation(camera_transform.translation + direction * star_distance)
+            .looking_to(travel, direction)
+            .with_scale(Vec3::new(SHOOTING_STAR_WIDTH, SHOOTING_STAR_WIDTH, SHOOTING_STAR_LENGTH * (0.3 + 0.7 * (life * 2.0).min(1.0))) * render_distance);
+        if let Some(material) = materials.get_mut(&star.material) {
+            material.base_color = Color::srgba(1.0, 1.0, 1.0, brightness);
+            material.emissive = LinearRgba::rgb(1.0, 0.95, 0.85) * SHOOTING_STAR_BRIGHTNESS * brightness;
+        }
+    }
+}
@@ -0,0 +1,447 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::aircraft_presets::{AircraftDefinition, AircraftSelection};
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{self, step_flight, Aircraft, ControlInputs, ControlMode, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::world_generation::WorldGenerator;
+
+/// How far away the opponent appears when it joins or respawns
+const SPAWN_DISTANCE: f32 = 2500.0;
+const RESPAWN_DELAY: f32 = 3.0;
+
+/// The chaser has to hold the target inside this range and cone to tag it
+const TAG_RANGE: f32 = 800.0;
+const TAG_CONE_DEGREES: f32 = 15.0;
+/// Seconds of continuous lock needed for a tag
+const TAG_LOCK_TIME: f32 = 1.5;
+/// Breathing room for the new evader after roles swap
+const TAG_GRACE_TIME: f32 = 5.0;
+
+/// Gains of the steering controller, in stick deflection per radian of error
+const BANK_GAIN: f32 = 1.5;
+const ROLL_GAIN: f32 = 2.5;
+const PITCH_GAIN: f32 = 3.0;
+const YAW_GAIN: f32 = 0.5;
+/// Extra back pressure per radian of bank, so turns don't bleed altitude
+const TURN_PULL: f32 = 0.4;
+const MAX_CLIMB_DEGREES: f32 = 30.0;
+
+/// Below this height above the terrain ahead, the AI pulls up regardless of its target
+const MIN_TERRAIN_CLEARANCE: f32 = 600.0;
+/// Seconds of flight ahead checked for rising terrain
+const TERRAIN_LOOK_AHEAD: f32 = 2.0;
+/// How fast the evading AI weaves from side to side
+const EVADE_WEAVE_RATE: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    /// Fraction of full stick deflection the AI will use
+    fn control_authority(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.55,
+            Difficulty::Medium => 0.8,
+            Difficulty::Hard => 1.0,
+        }
+    }
+
+    /// Seconds of target motion the AI leads its pursuit by
+    fn lead_time(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.0,
+            Difficulty::Medium => 0.8,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Fraction of the aircraft's maximum throttle the AI will use
+    fn throttle_fraction(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Medium => 0.8,
+            Difficulty::Hard => 1.0,
+        }
+    }
+
+    fn max_bank_degrees(self) -> f32 {
+        match self {
+            Difficulty::Easy => 45.0,
+            Difficulty::Medium => 60.0,
+            Difficulty::Hard => 80.0,
+        }
+    }
+
+    /// Smoothing time of the AI's stick inputs, in seconds
+    fn reaction_time(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Medium => 0.3,
+            Difficulty::Hard => 0.1,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct OpponentSettings {
+    pub enabled: bool,
+    pub difficulty: Difficulty,
+}
+
+/// Who is "it" in the tag game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagChaser {
+    Opponent,
+    Player,
+}
+
+/// Tag scoring: hold the other aircraft in your sights long enough to tag it, then roles swap
+#[derive(Resource)]
+pub struct TagGame {
+    pub chaser: TagChaser,
+    pub player_tags: u32,
+    pub opponent_tags: u32,
+    /// 0..1 progress of the chaser's current lock
+    pub lock: f32,
+    grace: f32,
+}
+
+impl Default for TagGame {
+    fn default() -> Self {
+        Self {
+            chaser: TagChaser::Opponent,
+            player_tags: 0,
+            opponent_tags: 0,
+            lock: 0.0,
+            grace: TAG_GRACE_TIME,
+        }
+    }
+}
+
+/// An AI-flown aircraft; it keeps its own `Aircraft` so the player queries stay single
+#[derive(Component)]
+pub struct Opponent {
+    aircraft: Aircraft,
+    controls: ControlInputs,
+    respawn_timer: Option<f32>,
+}
+
+/// Spot a fresh opponent away from the player: behind it when chasing, ahead when evading
+fn spawn_transform(chaser: TagChaser, player_transform: &Transform, world_gen: &WorldGenerator) -> Transform {
+    let forward = player_transform.forward().as_vec3();
+    let heading = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+    let offset = match chaser {
+        TagChaser::Opponent => -heading * SPAWN_DISTANCE,
+        TagChaser::Player => heading * SPAWN_DISTANCE,
+    };
+    let mut position = player_transform.translation + offset;
+    let ground = world_gen.get_terrain_height(&[position.x, position.y, position.z]).max(0.0);
+    position.y = position.y.max(ground + MIN_TERRAIN_CLEARANCE * 2.0);
+    Transform::from_translation(position).looking_to(heading, Vec3::Y)
+}
+
+/// Spawn or remove the opponent as the setting is toggled
+pub fn manage_opponent(
+    settings: Res<OpponentSettings>,
+    selection: Option<Res<AircraftSelection>>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    world_gen: Res<WorldGenerator>,
+    asset_server: Res<AssetServer>,
+    mut tag_game: ResMut<TagGame>,
+    player_query: Query<&Transform, (With<Aircraft>, Without<Opponent>)>,
+    opponents: Query<Entity, With<Opponent>>,
+    mut commands: Commands,
+) {
+    if !settings.enabled {
+        for entity in opponents.iter() {
+            commands.entity(entity).despawn();
+            *tag_game = TagGame::default();
+        }
+        return;
+    }
+    if !opponents.is_empty() {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else { return };
+
+    // The opponent flies whatever the player picked, so the contest is even
+    let aircraft = selection
+        .and_then(|selection| selection.selected)
+        .and_then(|id| definitions.get(id))
+        .map(|definition| definition.to_aircraft())
+        .unwrap_or_else(Aircraft::light);
+
+    let transform = spawn_transform(tag_game.chaser, player_transform, &world_gen)
+        .with_scale(Vec3::splat(aircraft.model_scale));
+    let model_path = aircraft.model_path.clone();
+
+    let opponent = commands.spawn((
+        transform,
+        Visibility::default(),
+        Opponent {
+            aircraft,
+            controls: ControlInputs::default(),
+            respawn_timer: None,
+        },
+    )).id();
+    let model = commands.spawn((
+        SceneRoot(asset_server.load(model_path)),
+        Transform::from_rotation(Quat::from_rotation_y((180.0f32).to_radians())),
+    )).id();
+    commands.entity(opponent).add_child(model);
+
+    info!("AI opponent joined ({:?})", settings.difficulty);
+}
+
+/// Turn a desired flight direction into stick inputs: bank toward it, then pull
+pub(crate) fn steer(transform: &Transform, desired: Vec3, difficulty: Difficulty) -> ControlInputs {
+    let forward = transform.forward().as_vec3();
+    let right = transform.right().as_vec3();
+    let up = transform.up().as_vec3();
+
+    let flat_forward = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+    let flat_right = flat_forward.cross(Vec3::Y);
+    let heading_error = desired.dot(flat_right).atan2(desired.dot(flat_forward));
+
+    let max_bank = difficulty.max_bank_degrees().to_radians();
+    let target_bank = (heading_error * BANK_GAIN).clamp(-max_bank, max_bank);
+    let bank = (-right.y).atan2(up.y);
+
+    let max_climb = MAX_CLIMB_DEGREES.to_radians();
+    let target_climb = desired.y.clamp(-1.0, 1.0).asin().clamp(-max_climb, max_climb);
+    let climb = forward.y.clamp(-1.0, 1.0).asin();
+
+    let authority = difficulty.control_authority();
+    ControlInputs {
+        pitch: ((target_climb - climb) * PITCH_GAIN + bank.abs() * TURN_PULL).clamp(-1.0, 1.0) * authority,
+        roll: ((target_bank - bank) * ROLL_GAIN).clamp(-1.0, 1.0) * authority,
+        yaw: (heading_error * YAW_GAIN).clamp(-1.0, 1.0) * authority,
+    }
+}
+
+/// Pursue or evade the player with the same flight model the player flies
+pub fn fly_opponent(
+    time: Res<Time>,
+    settings: Res<OpponentSettings>,
+    control_mode: Res<ControlMode>,
+    tag_game: Res<TagGame>,
+    (wind, world_gen, day_cycle, sim_difficulty): (Res<Wind>, Res<WorldGenerator>, Res<DayNightCycle>, Res<controls::Difficulty>),
+    player_query: Query<(&Transform, &Aircraft), Without<Opponent>>,
+    mut opponents: Query<(&mut Transform, &mut Visibility, &mut Opponent)>,
+    mut commands: Commands,
+) {
+    if control_mode.physics_paused {
+        return;
+    }
+    let Ok((player_transform, player)) = player_query.single() else { return };
+    let Ok((mut transform, mut visibility, mut opponent)) = opponents.single_mut() else { return };
+    let dt = time.delta_secs();
+    let difficulty = settings.difficulty;
+
+    if let Some(timer) = opponent.respawn_timer.as_mut() {
+        *timer -= dt;
+        if *timer > 0.0 {
+            return;
+        }
+        let respawned = spawn_transform(tag_game.chaser, player_transform, &world_gen);
+        transform.translation = respawned.translation;
+        transform.rotation = respawned.rotation;
+        *visibility = Visibility::Inherited;
+        let aircraft = &mut opponent.aircraft;
+        aircraft.crashed = false;
+        aircraft.speed = aircraft.respawn_speed;
+        aircraft.velocity = Vec3::ZERO;
+        aircraft.pitch_velocity = 0.0;
+        aircraft.roll_velocity = 0.0;
+        aircraft.yaw_velocity = 0.0;
+        opponent.controls = ControlInputs::default();
+        opponent.respawn_timer = None;
+        return;
+    }
+
+    let pos = transform.translation;
+    let to_player = player_transform.translation - pos;
+    let distance = to_player.length();
+
+    let mut desired = match tag_game.chaser {
+        TagChaser::Opponent => {
+            let lead_point = player_transform.translation + player.velocity * difficulty.lead_time();
+            (lead_point - pos).normalize_or_zero()
+        }
+        TagChaser::Player => {
+            let away = -to_player.normalize_or_zero();
+            let weave = away.cross(Vec3::Y).normalize_or_zero() * (time.elapsed_secs() * EVADE_WEAVE_RATE).sin() * 0.6;
+            Vec3::new(away.x + weave.x, away.y.clamp(-0.2, 0.2), away.z + weave.z).normalize_or_zero()
+        }
+    };
+
+    // Terrain comes first: climb away from anything close below or ahead
+    let ahead = pos + transform.forward().as_vec3() * opponent.aircraft.speed * TERRAIN_LOOK_AHEAD;
+    let ground = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z])
+        .max(world_gen.get_terrain_height(&[ahead.x, ahead.y, ahead.z]))
+        .max(0.0);
+    let urgency = 1.0 - (pos.y - ground) / MIN_TERRAIN_CLEARANCE;
+    if urgency > 0.0 {
+        desired.y = desired.y.max(urgency.min(1.0));
+        desired = desired.normalize_or(Vec3::Y);
+    }
+
+    let target = steer(&transform, desired, difficulty);
+    let blend = (dt / difficulty.reaction_time()).min(1.0);
+    let smoothed = ControlInputs {
+        pitch: opponent.controls.pitch.lerp(target.pitch, blend),
+        roll: opponent.controls.roll.lerp(target.roll, blend),
+        yaw: opponent.controls.yaw.lerp(target.yaw, blend),
+    };
+    opponent.controls = smoothed;
+
+    // Close the gap flat out, then ease off so a pursuit doesn't overshoot
+    let max_throttle = opponent.aircraft.max_throttle * difficulty.throttle_fraction();
+    opponent.aircraft.throttle = match tag_game.chaser {
+        TagChaser::Opponent => max_throttle * (distance / (TAG_RANGE * 3.0)).clamp(0.5, 1.0),
+        TagChaser::Player => max_throttle,
+    };
+    // The AI's engine is always running and answers the throttle at once
+    opponent.aircraft.power = opponent.aircraft.throttle;
+
+    // The AI flies in the same air as the player, but doesn't know how to recover from a spin
+    let flight_difficulty = controls::Difficulty { realistic_stalls: false, ..*sim_difficulty };
+    step_flight(&mut opponent.aircraft, &mut transform, Some(smoothed), &wind, &world_gen, &day_cycle, &time, &flight_difficulty);
+
+    let pos = transform.translation;
+    let terrain_height = world_gen.get_terrain_height(&[pos.x, pos.y, pos.z]);
+    if pos.y <= terrain_height || pos.y <= 0.0 {
+        let kind = if terrain_height <= 0.0 { EffectKind::Splash } else { EffectKind::Explosion };
+        commands.trigger(SpawnEffect {
+            kind,
+            position: Vec3::new(pos.x, terrain_height.max(0.0), pos.z),
+            velocity: opponent.aircraft.velocity,
+            intensity: 1.0,
+        });
+        opponent.aircraft.crashed = true;
+        opponent.respawn_timer = Some(RESPAWN_DELAY);
+        *visibility = Visibility::Hidden;
+        info!("AI opponent crashed");
+    }
+}
+
+/// Build the chaser's lock and score a tag when it completes
+pub fn update_tag_game(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    mut tag_game: ResMut<TagGame>,
+    mut gizmos: Gizmos,
+    player_query: Query<(&Transform, &Aircraft), Without<Opponent>>,
+    opponents: Query<(&Transform, &Opponent)>,
+) {
+    let Ok((player_transform, player)) = player_query.single() else { return };
+    let Ok((opponent_transform, opponent)) = opponents.single() else { return };
+    if opponent.respawn_timer.is_some() {
+        return;
+    }
+
+    // Ring the opponent so it can be found at a distance: red while it hunts you
+    let marker_color = match tag_game.chaser {
+        TagChaser::Opponent => Color::srgb(1.0, 0.2, 0.2),
+        TagChaser::Player => Color::srgb(0.2, 1.0, 0.4),
+    };
+    gizmos.sphere(Isometry3d::from_translation(opponent_transform.translation), 60.0, marker_color);
+
+    if control_mode.physics_paused || player.crashed {
+        tag_game.lock = 0.0;
+        return;
+    }
+
+    let dt = time.delta_secs();
+    if tag_game.grace > 0.0 {
+        tag_game.grace -= dt;
+        tag_game.lock = 0.0;
+        return;
+    }
+
+    let (chaser, target) = match tag_game.chaser {
+        TagChaser::Opponent => (opponent_transform, player_transform),
+        TagChaser::Player => (player_transform, opponent_transform),
+    };
+    let to_target = target.translation - chaser.translation;
+    let in_sights = to_target.length() <= TAG_RANGE
+        && chaser.forward().as_vec3().angle_between(to_target) <= TAG_CONE_DEGREES.to_radians();
+
+    let lock_rate = dt / TAG_LOCK_TIME;
+    tag_game.lock = (tag_game.lock + if in_sights { lock_rate } else { -lock_rate }).clamp(0.0, 1.0);
+    if tag_game.lock < 1.0 {
+        return;
+    }
+
+    tag_game.chaser = match tag_game.chaser {
+        TagChaser::Opponent => {
+            tag_game.opponent_tags += 1;
+            info!("Tagged by the AI opponent");
+            TagChaser::Player
+        }
+        TagChaser::Player => {
+            tag_game.player_tags += 1;
+            info!("Tagged the AI opponent");
+            TagChaser::Opponent
+        }
+    };
+    tag_game.lock = 0.0;
+    tag_game.grace = TAG_GRACE_TIME;
+}
+
+/// Score and role readout for the tag game
+pub fn tag_hud(
+    mut contexts: EguiContexts,
+    settings: Res<OpponentSettings>,
+    mut tag_game: ResMut<TagGame>,
+    units: Res<UnitSystem>,
+    player_query: Query<&Transform, (With<Aircraft>, Without<Opponent>)>,
+    opponents: Query<&Transform, With<Opponent>>,
+) -> Result<(), BevyError> {
+    if !settings.enabled {
+        return Ok(());
+    }
+    let distance = player_query.single().ok()
+        .zip(opponents.single().ok())
+        .map(|(player, opponent)| world_units_to_meters(player.translation.distance(opponent.translation)));
+
+    // Sits under the heading window, which owns the top-centre slot
+    egui::Window::new("Tag")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 240.0])
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(format!("You {}  -  {} AI", tag_game.player_tags, tag_game.opponent_tags)).size(18.0).strong());
+                let (role, color) = match tag_game.chaser {
+                    TagChaser::Player => ("You're it: chase!", egui::Color32::from_rgb(80, 220, 120)),
+                    TagChaser::Opponent => ("Evade!", egui::Color32::from_rgb(230, 80, 80)),
+                };
+                ui.label(egui::RichText::new(role).color(color).strong());
+                if tag_game.grace > 0.0 {
+                    ui.label(format!("Roles swapped, {:.0}s head start", tag_game.grace.ceil()));
+                } else {
+                    ui.add(egui::ProgressBar::new(tag_game.lock).desired_width(160.0).text("Lock"));
+                }
+                if let Some(distance) = distance {
+                    ui.label(format!("Distance: {}", units.format_distance(distance)));
+                }
+                if ui.small_button("Reset score").clicked() {
+                    tag_game.player_tags = 0;
+                    tag_game.opponent_tags = 0;
+                }
+            });
+        });
+
+    Ok(())
+}
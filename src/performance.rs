@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{performance, Aircraft, Performance};
+use crate::day_cycle::DayNightCycle;
+use crate::hud::HudPalette;
+use crate::icing::outside_air;
+use crate::season::Season;
+use crate::world_generation::WorldGenerator;
+
+const PERFORMANCE_KEY: KeyCode = KeyCode::KeyO;
+/// International Standard Atmosphere at sea level and its lapse rate, in Celsius and Celsius per metre
+const ISA_SEA_LEVEL_TEMPERATURE: f32 = 15.0;
+const ISA_LAPSE_RATE: f32 = 0.0065;
+const CELSIUS_TO_KELVIN: f32 = 273.15;
+
+#[derive(Resource, Default)]
+pub struct PerformancePanel {
+    pub open: bool,
+}
+
+pub fn toggle_performance_panel(keyboard: Res<ButtonInput<KeyCode>>, mut panel: ResMut<PerformancePanel>) {
+    if keyboard.just_pressed(PERFORMANCE_KEY) {
+        panel.open = !panel.open;
+    }
+}
+
+/// Air at one height, with the standard atmosphere's pressure there
+struct DensityAltitude {
+    /// Metres; the sim has no pressure systems, so this is the height above sea level
+    pressure_altitude: f32,
+    celsius: f32,
+    /// Density over standard sea-level density
+    density_ratio: f32,
+    /// Metres in the standard atmosphere with the same density
+    density_altitude: f32,
+}
+
+impl DensityAltitude {
+    fn new(pressure_altitude: f32, celsius: f32) -> Self {
+        let pressure_ratio = (1.0 - 2.255_77e-5 * pressure_altitude).max(0.0).powf(5.255_88);
+        let temperature_ratio = (celsius + CELSIUS_TO_KELVIN) / (ISA_SEA_LEVEL_TEMPERATURE + CELSIUS_TO_KELVIN);
+        let density_ratio = pressure_ratio / temperature_ratio.max(f32::EPSILON);
+        Self {
+            pressure_altitude,
+            celsius,
+            density_ratio,
+            density_altitude: 44_330.8 * (1.0 - density_ratio.powf(0.234_969)),
+        }
+    }
+
+    fn isa_deviation(&self) -> f32 {
+        self.celsius - (ISA_SEA_LEVEL_TEMPERATURE - ISA_LAPSE_RATE * self.pressure_altitude)
+    }
+}
+
+fn percent_change(value: f32, standard: f32) -> String {
+    if standard.abs() <= f32::EPSILON {
+        return String::new();
+    }
+    format!("{:+.0}%", (value / standard - 1.0) * 100.0)
+}
+
+/// Density altitude from the climate around the aircraft, and what it does to stall speed, climb and takeoff roll
+pub fn performance_panel_ui(
+    mut contexts: EguiContexts,
+    panel: Res<PerformancePanel>,
+    world_gen: Res<WorldGenerator>,
+    season: Res<Season>,
+    day_cycle: Res<DayNightCycle>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) -> Result<(), BevyError> {
+    if !panel.open {
+        return Ok(());
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return Ok(()) };
+    let position = transform.translation;
+    let here = DensityAltitude::new(world_units_to_meters(position.y.max(0.0)), outside_air(&world_gen, &season, &day_cycle, position).0);
+    let field_height = world_gen.get_terrain_height(&[position.x, 0.0, position.z]).max(0.0);
+    let field_position = position.with_y(field_height);
+    let field = DensityAltitude::new(world_units_to_meters(field_height), outside_air(&world_gen, &season, &day_cycle, field_position).0);
+
+    let standard = performance(aircraft, 1.0);
+    let aloft = performance(aircraft, here.density_ratio);
+    let takeoff = performance(aircraft, field.density_ratio);
+    let fahrenheit = |celsius: f32| celsius * 9.0 / 5.0 + 32.0;
+    let speed = |world: f32| units.format_speed(world_units_to_meters(world));
+    let climb = |performance: &Performance| units.format_climb_rate(world_units_to_meters(performance.climb_rate));
+    let roll = |performance: &Performance| performance.takeoff_roll
+        .map_or("—".to_string(), |roll| units.format_distance(world_units_to_meters(roll)));
+
+    egui::Window::new("Performance")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_CENTER, [-20.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("PERFORMANCE").size(12.0));
+            egui::Grid::new("density_altitude").num_columns(2).show(ui, |ui| {
+                ui.label("Pressure altitude");
+                ui.label(units.format_altitude(here.pressure_altitude));
+                ui.end_row();
+                ui.label("Outside air");
+                ui.label(format!(
+                    "{} (ISA {:+.0}°C)",
+                    units.format_temperature(here.celsius, fahrenheit(here.celsius)),
+                    here.isa_deviation(),
+                ));
+                ui.end_row();
+                ui.label("Density altitude");
+                ui.label(egui::RichText::new(units.format_altitude(here.density_altitude)).strong());
+                ui.end_row();
+                ui.label("Density");
+                ui.label(format!("{:.0}% of standard", here.density_ratio * 100.0));
+                ui.end_row();
+            });
+
+            ui.separator();
+            egui::Grid::new("performance_figures").num_columns(3).show(ui, |ui| {
+                ui.label("");
+                ui.label("Now");
+                ui.label("Std sea level");
+                ui.end_row();
+                ui.label("Stall (true)");
+                ui.label(format!("{} {}", speed(aloft.stall_speed), percent_change(aloft.stall_speed, standard.stall_speed)));
+                ui.label(speed(standard.stall_speed));
+                ui.end_row();
+                ui.label("Climb");
+                ui.label(format!("{} {}", climb(&aloft), percent_change(aloft.climb_rate, standard.climb_rate)));
+                ui.label(climb(&standard));
+                ui.end_row();
+                ui.label("Takeoff roll");
+                let change = match (takeoff.takeoff_roll, standard.takeoff_roll) {
+                    (Some(takeoff_roll), Some(standard_roll)) => percent_change(takeoff_roll, standard_roll),
+                    _ => String::new(),
+                };
+                ui.label(format!("{} {}", roll(&takeoff), change));
+                ui.label(roll(&standard));
+                ui.end_row();
+            });
+            ui.label(egui::RichText::new(format!(
+                "Takeoff from the ground below: {} density altitude",
+                units.format_altitude(field.density_altitude),
+            )).size(10.0));
+            ui.label(egui::RichText::new("O to close").size(10.0));
+        });
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use bevy::mesh::VertexAttributeValues;
+use bevy::prelude::*;
+
+use crate::consts::{world_units_to_meters, CHUNK_SIZE};
+use crate::controls::{sample_macro_wind, MainCamera, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::icing::{outside_air, LAPSE_RATE};
+use crate::season::Season;
+use crate::wind_shear::STORM_MULTIPLIER;
+use crate::world_generation::{Chunk, ChunkMeshReady, ChunkTask, SharedChunkMaterials, WorldGenerator};
+
+/// Macro wind this far above the base speed starts to rain; it pours by the time it counts as a storm
+const RAIN_MULTIPLIER: f32 = 1.2;
+/// Climate humidity at which a front rains at its full rate; drier air gives less
+const FULL_RAIN_HUMIDITY: f32 = 0.5;
+/// At or below this ground temperature, in Celsius, fronts bring snow instead of rain
+const SNOW_TEMPERATURE: f32 = 0.5;
+/// Fractions gained per second in a full downpour or blizzard
+const WETTING_RATE: f32 = 1.0 / 40.0;
+const SNOWING_RATE: f32 = 1.0 / 240.0;
+/// Fraction of wetness that dries per second without rain, quicker in the sun
+const DRYING_RATE: f32 = 1.0 / 180.0;
+/// Fraction of snow cover that melts per second per degree above freezing
+const MELT_RATE: f32 = 1.0 / 900.0;
+
+/// Wet ground is this much darker, and shinier down to `WET_ROUGHNESS`
+const WET_DARKENING: f32 = 0.4;
+const DRY_ROUGHNESS: f32 = 0.9;
+const WET_ROUGHNESS: f32 = 0.35;
+const SNOW_COLOR: LinearRgba = LinearRgba::rgb(0.85, 0.87, 0.92);
+/// Snow lies where the air is below this, fading in over `SNOW_BLEND` degrees, so it creeps down from the peaks
+const SNOW_HOLD_TEMPERATURE: f32 = 1.0;
+const SNOW_BLEND: f32 = 3.0;
+/// Snow slides off anything steeper than this, from fully covered at `SNOW_FLAT` of the normal's height
+const SNOW_STEEP: f32 = 0.6;
+const SNOW_FLAT: f32 = 0.85;
+/// Chunks within this many chunks of the camera show the weather; further ones go back to their dry colours
+const WEATHER_RADIUS: i32 = 8;
+/// Chunks recoloured per frame, nearest first
+const RECOLOR_BUDGET: usize = 4;
+/// Wetness and snow are rounded to this step so chunks are only recoloured a few times a shower
+const WEATHER_STEP: f32 = 0.1;
+
+/// Rain or snow falling at the camera, and what it has left on the ground around it
+#[derive(Resource)]
+pub struct Precipitation {
+    pub enabled: bool,
+    /// 0..1 fall rate at the camera
+    pub rain: f32,
+    pub snow: f32,
+    /// 0..1 wetness of the ground, darkening it and adding shine
+    pub wetness: f32,
+    /// 0..1 snow lying on cold, level ground
+    pub snow_cover: f32,
+    /// Celsius at the ground under the camera
+    pub ground_temperature: f32,
+}
+
+impl Default for Precipitation {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rain: 0.0,
+            snow: 0.0,
+            wetness: 0.0,
+            snow_cover: 0.0,
+            ground_temperature: 15.0,
+        }
+    }
+}
+
+impl Precipitation {
+    /// Wetness and snow cover on the ground, rounded to the step the terrain is recoloured at
+    fn ground_state(&self) -> (u8, u8) {
+        let step = |amount: f32| (amount / WEATHER_STEP).round() as u8;
+        (step(self.wetness), step(self.snow_cover))
+    }
+}
+
+/// The dry colours of a chunk's mesh, kept while the weather is painted over them
+#[derive(Component)]
+pub struct WeatheredChunk {
+    base_colors: Vec<[f32; 4]>,
+    /// Ground state the mesh colours show
+    applied: (u8, u8),
+}
+
+/// Read the fronts in the macro wind field over the camera, then wet, dry, snow and melt the ground
+pub fn update_precipitation(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    world_gen: Res<WorldGenerator>,
+    season: Res<Season>,
+    cycle: Res<DayNightCycle>,
+    mut precipitation: ResMut<Precipitation>,
+    shared_materials: Option<Res<SharedChunkMaterials>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let Ok(camera) = camera_query.single() else { return };
+    let position = camera.translation;
+    let ground = position.with_y(world_gen.get_terrain_height(&[position.x, 0.0, position.z]).max(0.0));
+    let (celsius, humidity) = outside_air(&world_gen, &season, &cycle, ground);
+    precipitation.ground_temperature = celsius;
+
+    let fall = if precipitation.enabled && wind.wind_speed > 0.0 {
+        let (_, speed) = sample_macro_wind(&wind, position, time.elapsed_secs_f64());
+        let front = (speed / wind.wind_speed - RAIN_MULTIPLIER) / (STORM_MULTIPLIER - RAIN_MULTIPLIER);
+        front.clamp(0.0, 1.0) * (humidity / FULL_RAIN_HUMIDITY).min(1.0)
+    } else {
+        0.0
+    };
+    let snowing = celsius <= SNOW_TEMPERATURE;
+    precipitation.rain = if snowing { 0.0 } else { fall };
+    precipitation.snow = if snowing { fall } else { 0.0 };
+
+    let dt = time.delta_secs();
+    let drying = DRYING_RATE * (1.0 + cycle.daylight()) * (1.0 - precipitation.rain);
+    precipitation.wetness = (precipitation.wetness + (precipitation.rain * WETTING_RATE - drying) * dt).clamp(0.0, 1.0);
+    let melting = MELT_RATE * celsius.max(0.0);
+    precipitation.snow_cover = (precipitation.snow_cover + (precipitation.snow * SNOWING_RATE - melting) * dt).clamp(0.0, 1.0);
+
+    // Snow hides the shine of wet ground
+    let roughness = DRY_ROUGHNESS.lerp(WET_ROUGHNESS, precipitation.wetness * (1.0 - precipitation.snow_cover));
+    let roughness = (roughness / WEATHER_STEP * 2.0).round() * WEATHER_STEP / 2.0;
+    if let Some(shared_materials) = shared_materials
+        && let Some(material) = materials.get(&shared_materials.terrain_material)
+        && material.perceptual_roughness != roughness
+        && let Some(material) = materials.get_mut(&shared_materials.terrain_material)
+    {
+        material.perceptual_roughness = roughness;
+    }
+}
+
+/// A rebuilt mesh comes with fresh dry colours, so whatever was kept for the old one no longer applies
+pub fn forget_weathered_colors(trigger: On<ChunkMeshReady>, mut commands: Commands) {
+    commands.entity(trigger.entity).try_remove::<WeatheredChunk>();
+}
+
+/// Repaint the vertex colours of the nearest chunks whose look is behind the weather, a few per frame
+pub fn weather_terrain_colors(
+    mut commands: Commands,
+    precipitation: Res<Precipitation>,
+    world_gen: Res<WorldGenerator>,
+    season: Res<Season>,
+    cycle: Res<DayNightCycle>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut chunks: Query<(Entity, &Chunk, &Mesh3d, &Transform, Option<&mut WeatheredChunk>), (Without<ChunkTask>, Without<MainCamera>)>,
+) {
+    let Ok(camera) = camera_query.single() else { return };
+    let cam_x = (camera.translation.x / CHUNK_SIZE).round() as i32;
+    let cam_z = (camera.translation.z / CHUNK_SIZE).round() as i32;
+    let current = precipitation.ground_state();
+
+    let mut stale: Vec<(Entity, i32, (u8, u8))> = chunks
+        .iter()
+        .filter_map(|(entity, chunk, _, _, weathered)| {
+            let distance = (chunk.x - cam_x).abs().max((chunk.z - cam_z).abs());
+            let target = if distance <= WEATHER_RADIUS { current } else { (0, 0) };
+            let applied = weathered.map_or((0, 0), |weathered| weathered.applied);
+            (applied != target).then_some((entity, distance, target))
+        })
+        .collect();
+    stale.sort_by_key(|(_, distance, _)| *distance);
+
+    for (entity, _, target) in stale.into_iter().take(RECOLOR_BUDGET) {
+        let Ok((_, _, mesh_handle, transform, weathered)) = chunks.get_mut(entity) else { continue };
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else { continue };
+
+        if target == (0, 0) {
+            // Dry and clear again: put the kept colours back and let them go
+            if let Some(weathered) = weathered
+                && let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+                && colors.len() == weathered.base_colors.len()
+            {
+                colors.copy_from_slice(&weathered.base_colors);
+            }
+            commands.entity(entity).remove::<WeatheredChunk>();
+            continue;
+        }
+
+        let base_colors = match &weathered {
+            Some(weathered) => weathered.base_colors.clone(),
+            None => match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+                Some(VertexAttributeValues::Float32x4(colors)) => colors.clone(),
+                _ => continue,
+            },
+        };
+        let (Some(VertexAttributeValues::Float32x3(positions)), Some(VertexAttributeValues::Float32x3(normals))) =
+            (mesh.attribute(Mesh::ATTRIBUTE_POSITION), mesh.attribute(Mesh::ATTRIBUTE_NORMAL))
+        else {
+            continue;
+        };
+
+        let wetness = target.0 as f32 * WEATHER_STEP;
+        let snow_cover = target.1 as f32 * WEATHER_STEP;
+        // Air temperature at sea level over the chunk; each vertex is colder by its height
+        let (sea_level, _) = outside_air(&world_gen, &season, &cycle, transform.translation.with_y(0.0));
+        let painted: Vec<[f32; 4]> = base_colors
+            .iter()
+            .zip(positions.iter().zip(normals))
+            .map(|(base, (position, normal))| {
+                // Under water the lake or sea covers it either way
+                if position[1] < 0.0 {
+                    return *base;
+                }
+                let celsius = sea_level - LAPSE_RATE * world_units_to_meters(position[1]);
+                let cold = ((SNOW_HOLD_TEMPERATURE - celsius) / SNOW_BLEND).clamp(0.0, 1.0);
+                let level = ((normal[1] - SNOW_STEEP) / (SNOW_FLAT - SNOW_STEEP)).clamp(0.0, 1.0);
+                let snow = snow_cover * cold * level;
+
+                let base = LinearRgba::from_f32_array(*base);
+                let wet = base * (1.0 - WET_DARKENING * wetness);
+                wet.with_alpha(base.alpha).mix(&SNOW_COLOR, snow).to_f32_array()
+            })
+            .collect();
+
+        if let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR) {
+            *colors = painted;
+        }
+        match weathered {
+            Some(mut weathered) => weathered.applied = target,
+            None => {
+                commands.entity(entity).insert(WeatheredChunk { base_colors, applied: target });
+            }
+        }
+    }
+}
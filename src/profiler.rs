@@ -0,0 +1,257 @@
+use bevy::app::MainScheduleOrder;
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+    RegisterDiagnostic,
+};
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::mesh::Indices;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use std::time::{Duration, Instant};
+
+use crate::debug_overlays::ChunkPipelineStats;
+use crate::hud::HudPalette;
+use crate::world_generation::{ChunkManager, ChunkTask};
+
+const PROFILER_KEY: KeyCode = KeyCode::F3;
+/// Frame times kept for the graph, a few seconds at typical frame rates
+pub const FRAME_HISTORY: usize = 240;
+/// The graph tops out here unless a frame took longer
+const GRAPH_MIN_CEILING_MS: f32 = 33.3;
+const GRAPH_SIZE: egui::Vec2 = egui::Vec2::new(320.0, 80.0);
+/// Budget lines drawn across the graph, in milliseconds
+const BUDGET_LINES_MS: [(f32, &str); 2] = [(16.7, "60"), (33.3, "30")];
+/// Counting meshes and summing asset sizes walks every chunk, so it only happens this often
+const SCENE_STATS_INTERVAL: f32 = 0.5;
+
+/// Time spent in each main schedule, measured between marker schedules inserted around them.
+/// Update includes state transitions and the fixed step that run just before it, and PostUpdate
+/// includes scene spawning
+const SCHEDULE_TIMES: [(ScheduleMark, DiagnosticPath, &str); 5] = [
+    (ScheduleMark::First, DiagnosticPath::const_new("schedule/first"), "First"),
+    (ScheduleMark::PreUpdate, DiagnosticPath::const_new("schedule/pre_update"), "PreUpdate"),
+    (ScheduleMark::Update, DiagnosticPath::const_new("schedule/update"), "Update + fixed"),
+    (ScheduleMark::PostUpdate, DiagnosticPath::const_new("schedule/post_update"), "PostUpdate"),
+    (ScheduleMark::Last, DiagnosticPath::const_new("schedule/last"), "Last"),
+];
+
+/// Frame time graph, schedule timings and scene counts, toggled with F3
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(EntityCountDiagnosticsPlugin::default())
+            .init_resource::<ProfilerOverlay>()
+            .init_resource::<ScheduleClock>()
+            .init_resource::<SceneStats>()
+            .add_systems(Update, (
+                toggle_profiler,
+                update_scene_stats
+                    .run_if(|overlay: Res<ProfilerOverlay>| overlay.open)
+                    .run_if(on_timer(Duration::from_secs_f32(SCENE_STATS_INTERVAL))),
+            ))
+            .add_systems(EguiPrimaryContextPass, profiler_ui);
+
+        app.add_systems(ScheduleMark::Start, |mut clock: ResMut<ScheduleClock>| {
+            clock.last = Some(Instant::now());
+        });
+        let mut order = app.world_mut().resource_mut::<MainScheduleOrder>();
+        order.insert_before(First, ScheduleMark::Start);
+        order.insert_after(First, ScheduleMark::First);
+        order.insert_after(PreUpdate, ScheduleMark::PreUpdate);
+        order.insert_after(Update, ScheduleMark::Update);
+        order.insert_after(PostUpdate, ScheduleMark::PostUpdate);
+        order.insert_after(Last, ScheduleMark::Last);
+
+        for (mark, path, _) in SCHEDULE_TIMES {
+            app.register_diagnostic(Diagnostic::new(path.clone()).with_suffix("ms"));
+            app.add_systems(mark, move |mut clock: ResMut<ScheduleClock>, mut diagnostics: Diagnostics| {
+                let now = Instant::now();
+                if let Some(last) = clock.last.replace(now) {
+                    diagnostics.add_measurement(&path, || (now - last).as_secs_f64() * 1000.0);
+                }
+            });
+        }
+    }
+}
+
+/// Marker schedules between the main ones; each notes how long the schedule before it took
+#[derive(ScheduleLabel, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ScheduleMark {
+    Start,
+    First,
+    PreUpdate,
+    Update,
+    PostUpdate,
+    Last,
+}
+
+/// When the last marker schedule ran
+#[derive(Resource, Default)]
+struct ScheduleClock {
+    last: Option<Instant>,
+}
+
+#[derive(Resource, Default)]
+pub struct ProfilerOverlay {
+    pub open: bool,
+}
+
+/// Counts that take a walk over the world to gather, refreshed while the overlay is open
+#[derive(Resource, Default)]
+struct SceneStats {
+    /// Meshes that passed culling for some view, one draw each before batching and shadow passes
+    visible_meshes: usize,
+    total_meshes: usize,
+    mesh_bytes: usize,
+    texture_bytes: usize,
+}
+
+fn toggle_profiler(keyboard: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<ProfilerOverlay>) {
+    if keyboard.just_pressed(PROFILER_KEY) {
+        overlay.open = !overlay.open;
+    }
+}
+
+fn update_scene_stats(
+    mut stats: ResMut<SceneStats>,
+    mesh_query: Query<&ViewVisibility, With<Mesh3d>>,
+    meshes: Res<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+) {
+    stats.total_meshes = mesh_query.iter().len();
+    stats.visible_meshes = mesh_query.iter().filter(|visibility| visibility.get()).count();
+    // Meshes that only live in the render world have handed their data over and can't be measured here
+    stats.mesh_bytes = meshes
+        .iter()
+        .map(|(_, mesh)| {
+            let vertices = mesh
+                .try_attributes()
+                .map_or(0, |attributes| attributes.map(|(_, values)| values.get_bytes().len()).sum());
+            let indices = match mesh.try_indices_option() {
+                Ok(Some(Indices::U16(indices))) => indices.len() * 2,
+                Ok(Some(Indices::U32(indices))) => indices.len() * 4,
+                _ => 0,
+            };
+            vertices + indices
+        })
+        .sum();
+    stats.texture_bytes = images
+        .iter()
+        .map(|(_, image)| {
+            image.data.as_ref().map_or_else(
+                // Render targets have no CPU copy; assume four bytes a texel
+                || {
+                    let size = image.texture_descriptor.size;
+                    (size.width * size.height * size.depth_or_array_layers) as usize * 4
+                },
+                Vec::len,
+            )
+        })
+        .sum();
+}
+
+fn megabytes(bytes: usize) -> f32 {
+    bytes as f32 / (1024.0 * 1024.0)
+}
+
+fn profiler_ui(
+    mut contexts: EguiContexts,
+    overlay: Res<ProfilerOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    stats: Res<SceneStats>,
+    palette: Res<HudPalette>,
+    chunks: Res<ChunkManager>,
+    pipeline: Res<ChunkPipelineStats>,
+    tasks: Query<(), With<ChunkTask>>,
+) -> Result<(), BevyError> {
+    if !overlay.open {
+        return Ok(());
+    }
+
+    let smoothed = |path: &DiagnosticPath| diagnostics.get(path).and_then(|diagnostic| diagnostic.smoothed()).unwrap_or(0.0);
+    let frame_times: Vec<f32> = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .map(|diagnostic| diagnostic.values().map(|&ms| ms as f32).collect())
+        .unwrap_or_default();
+    let frame_times = &frame_times[frame_times.len().saturating_sub(FRAME_HISTORY)..];
+
+    egui::Window::new("Profiler")
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .show(contexts.ctx_mut()?, |ui| {
+            let worst = frame_times.iter().copied().fold(0.0, f32::max);
+            ui.label(format!(
+                "{:.0} FPS | {:.2} ms frame | {:.2} ms worst",
+                smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+                smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+                worst,
+            ));
+            draw_frame_graph(ui, frame_times, &palette);
+
+            ui.separator();
+            ui.strong("CPU time per schedule");
+            egui::Grid::new("schedule_times").num_columns(2).show(ui, |ui| {
+                for (_, path, label) in &SCHEDULE_TIMES {
+                    ui.label(*label);
+                    ui.monospace(format!("{:6.2} ms", smoothed(path)));
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            egui::Grid::new("scene_counts").num_columns(2).show(ui, |ui| {
+                ui.label("Chunk tasks in flight");
+                ui.monospace(format!("{}", tasks.iter().len()));
+                ui.end_row();
+                ui.label("Chunk queue");
+                ui.monospace(format!("{} spawn, {} LOD, {} loaded", pipeline.queued_spawns, pipeline.queued_lod, chunks.spawned_chunks.len()));
+                ui.end_row();
+                ui.label("Entities");
+                ui.monospace(format!("{:.0}", smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)));
+                ui.end_row();
+                ui.label("Draw calls (est.)");
+                ui.monospace(format!("{} of {} meshes", stats.visible_meshes, stats.total_meshes));
+                ui.end_row();
+                ui.label("VRAM (est.)");
+                ui.monospace(format!(
+                    "{:.0} MB ({:.0} meshes, {:.0} textures)",
+                    megabytes(stats.mesh_bytes + stats.texture_bytes),
+                    megabytes(stats.mesh_bytes),
+                    megabytes(stats.texture_bytes),
+                ));
+                ui.end_row();
+            });
+        });
+    Ok(())
+}
+
+/// Frame times left to right, oldest first, with lines at the 60 and 30 FPS budgets
+fn draw_frame_graph(ui: &mut egui::Ui, frame_times: &[f32], palette: &HudPalette) {
+    let (response, painter) = ui.allocate_painter(GRAPH_SIZE, egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, palette.window_fill);
+
+    let ceiling = frame_times.iter().copied().fold(GRAPH_MIN_CEILING_MS, f32::max);
+    let to_y = |ms: f32| rect.bottom() - (ms / ceiling).clamp(0.0, 1.0) * rect.height();
+
+    let font = egui::FontId::proportional(10.0);
+    for (ms, label) in BUDGET_LINES_MS {
+        let y = to_y(ms);
+        painter.hline(rect.x_range(), y, egui::Stroke::new(1.0, egui::Color32::from_gray(90)));
+        painter.text(egui::Pos2::new(rect.left() + 2.0, y - 1.0), egui::Align2::LEFT_BOTTOM, label, font.clone(), palette.text);
+    }
+
+    if frame_times.len() < 2 {
+        return;
+    }
+    let step = rect.width() / (FRAME_HISTORY - 1) as f32;
+    let start = rect.right() - step * (frame_times.len() - 1) as f32;
+    let points = frame_times
+        .iter()
+        .enumerate()
+        .map(|(i, &ms)| egui::Pos2::new(start + step * i as f32, to_y(ms)))
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, egui::Color32::from_rgb(90, 200, 120))));
+}
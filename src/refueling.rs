@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::consts::UnitSystem;
+use crate::consts::world_units_to_meters;
+use crate::controls::Aircraft;
+use crate::hud::HudPalette;
+use crate::loadout::Loadout;
+use crate::network::{ClientRole, NetworkClient, RemotePlayer, RemotePlayers};
+use crate::replication::{Replica, Replicate};
+
+/// Where the drogue trails in the tanker's frame, behind and below the tail
+const DROGUE_OFFSET: Vec3 = Vec3::new(0.0, -12.0, 90.0);
+/// Hose attachment under the tanker's tail
+const HOSE_ROOT: Vec3 = Vec3::new(0.0, -3.0, 12.0);
+/// The drogue wanders on the hose by up to this much in the tanker's wake
+const DROGUE_SWAY: f32 = 1.5;
+const DROGUE_RADIUS: f32 = 3.0;
+/// Probe tip in the receiver's frame, ahead of the nose
+const PROBE_OFFSET: Vec3 = Vec3::new(2.0, 1.0, -14.0);
+/// The probe is in formation within this distance of the drogue and inside a cone opening aft from it,
+/// given as the cosine of its half-angle, with the receiver pointing the tanker's way
+const CONTACT_RANGE: f32 = 12.0;
+const FORMATION_CONE_COS: f32 = 0.94;
+const FORMATION_HEADING_COS: f32 = 0.97;
+/// Seconds in formation before fuel starts to flow
+const HOLD_SECS: f32 = 3.0;
+/// Kilograms per second through the hose
+const TRANSFER_RATE: f32 = 15.0;
+/// Receivers get a readout for drogues within this distance
+const TANKER_SHOW_RANGE: f32 = 3000.0;
+
+/// A tanker's trailing hose as the other clients see it. Only its state is replicated: the drogue is drawn
+/// off the tanker's smoothed aircraft, so it moves exactly as the tanker is seen to
+#[derive(Component, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Hose {
+    /// Pilot plugged in and taking fuel
+    pub receiver: Option<u32>,
+}
+
+/// A receiver's probe, so its tanker knows when fuel is flowing and to whom
+#[derive(Component, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Probe {
+    pub tanker: Option<u32>,
+    pub flowing: bool,
+}
+
+/// Mid-air refueling between players: a tanker trails a hose, and a receiver who holds its probe in the
+/// drogue for `HOLD_SECS` takes on fuel from it
+#[derive(Resource, Default)]
+pub struct Refueling {
+    /// Trail the hose for other pilots to plug into
+    pub tanker: bool,
+    hose: Option<Entity>,
+    probe: Option<Entity>,
+    /// The tanker being flown on, and seconds held in formation with it
+    pub tanker_id: Option<u32>,
+    pub hold: f32,
+    pub flowing: bool,
+    /// Name of and distance to the closest trailing drogue
+    pub nearest_tanker: Option<(String, f32)>,
+    /// Pilot taking fuel from our hose
+    pub receiver_id: Option<u32>,
+    /// Kilograms taken on, or given away as a tanker, since connecting
+    pub transferred: f32,
+}
+
+fn drogue_position(tanker: &Transform, time: f32) -> Vec3 {
+    let sway = Vec3::new((time * 1.7).sin(), (time * 1.1).cos() * 0.5, 0.0) * DROGUE_SWAY;
+    tanker.translation + tanker.rotation * (DROGUE_OFFSET + sway)
+}
+
+fn draw_hose(gizmos: &mut Gizmos, tanker: &Transform, drogue: Vec3, color: Color) {
+    gizmos.line(tanker.translation + tanker.rotation * HOSE_ROOT, drogue, Color::srgb(0.2, 0.2, 0.2));
+    gizmos.circle(Isometry3d::new(drogue, tanker.rotation), DROGUE_RADIUS, color);
+}
+
+/// Trail the hose while flying as a tanker and pass fuel to whoever reports being plugged in
+pub fn update_tanker(
+    time: Res<Time>,
+    client: Option<Res<NetworkClient>>,
+    mut refueling: ResMut<Refueling>,
+    mut loadout: ResMut<Loadout>,
+    mut gizmos: Gizmos,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    probes: Query<(&Replica, &Probe)>,
+    mut hoses: Query<&mut Hose, Without<Replica>>,
+    mut commands: Commands,
+) {
+    let my_id = client.as_ref().filter(|client| client.connected && client.role == ClientRole::Pilot).and_then(|client| client.player_id);
+    let aircraft = aircraft_query.single().ok().filter(|(_, aircraft)| !aircraft.crashed);
+    if refueling.tanker && loadout.out_of_fuel() {
+        refueling.tanker = false;
+        info!("Tanks dry, hose retracted");
+    }
+    let (Some(my_id), Some((transform, _)), true) = (my_id, aircraft, refueling.tanker) else {
+        if let Some(hose) = refueling.hose.take() {
+            commands.entity(hose).despawn();
+        }
+        refueling.receiver_id = None;
+        return;
+    };
+
+    let hose = *refueling.hose.get_or_insert_with(|| commands.spawn((Hose::default(), Replicate)).id());
+    let receiver = probes.iter()
+        .find(|(_, probe)| probe.tanker == Some(my_id) && probe.flowing)
+        .map(|(replica, _)| replica.owner);
+    if receiver.is_some() {
+        let given = TRANSFER_RATE * time.delta_secs();
+        loadout.offload_fuel(given);
+        refueling.transferred += given;
+    }
+    if receiver != refueling.receiver_id {
+        match receiver {
+            Some(id) => info!("Player {} plugged in", id),
+            None => info!("Receiver disconnected from the hose"),
+        }
+        refueling.receiver_id = receiver;
+    }
+    if let Ok(mut state) = hoses.get_mut(hose) {
+        state.set_if_neq(Hose { receiver });
+    }
+
+    let color = if receiver.is_some() { Color::srgb(0.2, 0.9, 0.3) } else { Color::WHITE };
+    draw_hose(&mut gizmos, transform, drogue_position(transform, time.elapsed_secs()), color);
+}
+
+/// Draw other tankers' hoses, and take on fuel while our probe holds formation in one's drogue
+pub fn update_receiver(
+    time: Res<Time>,
+    client: Option<Res<NetworkClient>>,
+    mut refueling: ResMut<Refueling>,
+    mut loadout: ResMut<Loadout>,
+    mut gizmos: Gizmos,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    hoses: Query<(&Replica, &Hose)>,
+    remote_players: Res<RemotePlayers>,
+    tankers: Query<(&Transform, &RemotePlayer), Without<Aircraft>>,
+    mut probes: Query<&mut Probe, Without<Replica>>,
+    mut commands: Commands,
+) {
+    let my_id = client.as_ref().filter(|client| client.connected && client.role == ClientRole::Pilot).and_then(|client| client.player_id);
+    let aircraft = aircraft_query.single().ok().filter(|(_, aircraft)| !aircraft.crashed);
+    let t = time.elapsed_secs();
+
+    // Closest trailing drogue to the probe, drawn along with every other one
+    let probe_tip = aircraft.map(|(transform, _)| transform.translation + transform.rotation * PROBE_OFFSET);
+    let mut nearest: Option<(u32, &Transform, &RemotePlayer, Vec3, f32)> = None;
+    for (replica, hose) in hoses.iter() {
+        let Some((tanker_transform, tanker)) = remote_players.0.get(&replica.owner).and_then(|&entity| tankers.get(entity).ok()) else { continue };
+        let drogue = drogue_position(tanker_transform, t);
+        let color = match hose.receiver {
+            Some(id) if Some(id) == my_id => Color::srgb(0.2, 0.9, 0.3),
+            Some(_) => Color::srgb(0.95, 0.75, 0.1),
+            None => Color::WHITE,
+        };
+        draw_hose(&mut gizmos, tanker_transform, drogue, color);
+        let Some(probe_tip) = probe_tip else { continue };
+        let distance = probe_tip.distance(drogue);
+        if nearest.is_none_or(|(.., closest)| distance < closest) {
+            nearest = Some((replica.owner, tanker_transform, tanker, drogue, distance));
+        }
+    }
+    refueling.nearest_tanker = nearest
+        .filter(|(.., distance)| *distance <= TANKER_SHOW_RANGE)
+        .map(|(_, _, tanker, _, distance)| (tanker.name.clone(), distance));
+
+    let in_formation = match (nearest, aircraft, probe_tip, my_id) {
+        (Some((tanker_id, tanker_transform, _, drogue, distance)), Some((transform, _)), Some(probe_tip), Some(_)) => {
+            let aft = tanker_transform.back().as_vec3();
+            let offset = probe_tip - drogue;
+            let in_cone = offset.length() <= DROGUE_RADIUS || offset.normalize_or_zero().dot(aft) >= FORMATION_CONE_COS;
+            let aligned = transform.forward().dot(*tanker_transform.forward()) >= FORMATION_HEADING_COS;
+            (distance <= CONTACT_RANGE && in_cone && aligned).then_some(tanker_id)
+        }
+        _ => None,
+    };
+
+    // The hold restarts whenever the probe drops out of formation or moves to another tanker
+    if in_formation.is_some() && in_formation == refueling.tanker_id {
+        refueling.hold += time.delta_secs();
+    } else {
+        refueling.hold = 0.0;
+    }
+    refueling.tanker_id = in_formation;
+    let hose_free = in_formation.is_some_and(|tanker_id| {
+        hoses.iter().any(|(replica, hose)| replica.owner == tanker_id && hose.receiver.is_none_or(|id| Some(id) == my_id))
+    });
+    // Fuel only flows while there's room for it, since the tanker gives away whatever we report taking
+    let taken = if hose_free && refueling.hold >= HOLD_SECS {
+        loadout.take_on_fuel(TRANSFER_RATE * time.delta_secs())
+    } else {
+        0.0
+    };
+    let flowing = taken > 0.0;
+    if flowing != refueling.flowing {
+        info!("Fuel {}", if flowing { "flowing" } else { "stopped" });
+        refueling.flowing = flowing;
+    }
+    refueling.transferred += taken;
+
+    let state = Probe { tanker: in_formation, flowing };
+    match (my_id, refueling.probe) {
+        (None, Some(probe)) => {
+            commands.entity(probe).despawn();
+            refueling.probe = None;
+        }
+        (Some(_), None) => refueling.probe = Some(commands.spawn((state, Replicate)).id()),
+        (Some(_), Some(probe)) => {
+            if let Ok(mut current) = probes.get_mut(probe) {
+                current.set_if_neq(state);
+            }
+        }
+        (None, None) => {}
+    }
+    if my_id.is_none() {
+        refueling.transferred = 0.0;
+    }
+}
+
+/// Hose state for the tanker, and the approach and hold for a receiver near one
+pub fn refueling_hud(
+    mut contexts: EguiContexts,
+    refueling: Res<Refueling>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+) -> Result<(), BevyError> {
+    if !refueling.tanker && refueling.nearest_tanker.is_none() {
+        return Ok(());
+    }
+
+    egui::Window::new("Refueling")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_CENTER, [-10.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            if refueling.tanker {
+                ui.label(egui::RichText::new("TANKER").size(12.0));
+                match refueling.receiver_id {
+                    Some(id) => ui.label(format!("Player {} taking fuel", id)),
+                    None => ui.label("Hose trailing"),
+                };
+                if refueling.transferred > 0.0 {
+                    ui.label(format!("Passed {}", units.format_weight(refueling.transferred)));
+                }
+                return;
+            }
+            let Some((name, distance)) = &refueling.nearest_tanker else { return };
+            ui.label(egui::RichText::new(format!("TANKER {}", name)).size(12.0));
+            if refueling.flowing {
+                ui.label("Fuel flowing");
+            } else if refueling.tanker_id.is_some() && refueling.hold >= HOLD_SECS {
+                ui.label("Tanks full");
+            } else if refueling.tanker_id.is_some() {
+                ui.add(egui::ProgressBar::new(refueling.hold / HOLD_SECS).desired_width(160.0).text("Hold formation"));
+            } else {
+                ui.label(format!("Drogue {}", units.format_distance(world_units_to_meters(*distance))));
+            }
+            if refueling.transferred > 0.0 {
+                ui.label(format!("Taken on {}", units.format_weight(refueling.transferred)));
+            }
+        });
+
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use bevy::{ecs::system::EntityCommands, platform::collections::HashMap, prelude::*};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::network::{ClientMessage, Disconnected, DespawnRemotePlayer, NetworkClient};
+
+/// Changed components are sent at most this often, so a component touched every frame doesn't flood the link
+const SEND_INTERVAL: f32 = 1.0 / 20.0;
+
+/// Generic entity sync over the multiplayer connection. Give a local entity `Replicate` and every component
+/// type registered with `App::replicate` is copied onto a `Replica` entity on every other client, kept up to
+/// date as it changes and despawned with it. Game code builds visuals for replicas with `Added<T>` as usual:
+///
+/// ```ignore
+/// #[derive(Component, Clone, Serialize, Deserialize)]
+/// struct Flare { color: [u8; 3] }
+///
+/// app.replicate::<Flare>();
+/// commands.spawn((Flare { color: [255, 0, 0] }, Transform::default(), Replicate));
+/// ```
+pub struct ReplicationPlugin;
+
+impl Plugin for ReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplicationRegistry>()
+            .init_resource::<Replicas>()
+            .init_resource::<NextNetId>()
+            .add_observer(apply_replica)
+            .add_observer(despawn_replica)
+            .add_observer(despawn_owner_replicas)
+            .add_observer(clear_replicas)
+            .configure_sets(Update, ReplicationSet::Assign.before(ReplicationSet::Send))
+            .add_systems(Update, (
+                (assign_net_ids, capture_transforms).in_set(ReplicationSet::Assign),
+                send_despawns.in_set(ReplicationSet::Send),
+                apply_transforms,
+            ))
+            .replicate::<NetTransform>();
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReplicationSet {
+    Assign,
+    Send,
+}
+
+/// Marks a local entity whose registered components are shared with the other clients
+#[derive(Component, Default)]
+pub struct Replicate;
+
+/// Our own id for a replicated entity, which other clients know it by together with our player id
+#[derive(Component, Clone, Copy)]
+pub struct NetId(pub u32);
+
+/// Where a replicated entity is, copied from its `Transform` on the owner and back onto the replica's.
+/// Every replicated entity carries one, so replicas always have somewhere to be
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct NetTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+}
+
+/// A copy of an entity another client replicates
+#[derive(Component, Clone, Copy)]
+pub struct Replica {
+    pub owner: u32,
+    pub entity: u32,
+}
+
+#[derive(Resource, Default)]
+struct NextNetId(u32);
+
+type ApplyFn = fn(&mut EntityCommands, &[u8]) -> Result<(), bincode::Error>;
+
+/// How to decode each registered component kind onto a replica
+#[derive(Resource, Default)]
+pub struct ReplicationRegistry {
+    kinds: HashMap<&'static str, ApplyFn>,
+}
+
+/// Replica entities by (owner, owner's id)
+#[derive(Resource, Default)]
+pub struct Replicas(HashMap<(u32, u32), Entity>);
+
+/// A replicated component came in from the server
+#[derive(Event)]
+pub struct ReplicaReceived {
+    pub owner: u32,
+    pub entity: u32,
+    pub kind: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Event)]
+pub struct ReplicaDespawned {
+    pub owner: u32,
+    pub entity: u32,
+}
+
+/// The name a component kind travels under. Every client runs the same build, so the type name matches
+fn kind_name<T>() -> &'static str {
+    std::any::type_name::<T>()
+}
+
+fn apply_component<T: Component + DeserializeOwned>(entity: &mut EntityCommands, data: &[u8]) -> Result<(), bincode::Error> {
+    entity.insert(bincode::deserialize::<T>(data)?);
+    Ok(())
+}
+
+pub trait ReplicationAppExt {
+    /// Share component `T` of every `Replicate` entity with the other clients
+    fn replicate<T: Component + Serialize + DeserializeOwned>(&mut self) -> &mut Self;
+}
+
+impl ReplicationAppExt for App {
+    fn replicate<T: Component + Serialize + DeserializeOwned>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<ReplicationRegistry>()
+            .kinds
+            .insert(kind_name::<T>(), apply_component::<T>);
+        self.add_systems(Update, send_component::<T>.in_set(ReplicationSet::Send))
+    }
+}
+
+fn assign_net_ids(
+    mut commands: Commands,
+    mut next_id: ResMut<NextNetId>,
+    new_entities: Query<Entity, (With<Replicate>, Without<NetId>)>,
+) {
+    for entity in new_entities.iter() {
+        next_id.0 += 1;
+        commands.entity(entity).insert(NetId(next_id.0));
+    }
+}
+
+/// Send changed `T`s, and all of them when we've just joined a server
+fn send_component<T: Component + Serialize>(
+    client: Option<Res<NetworkClient>>,
+    time: Res<Time>,
+    mut last_send: Local<f32>,
+    mut joined_as: Local<Option<u32>>,
+    mut pending: Local<Vec<Entity>>,
+    changed: Query<Entity, (With<Replicate>, Changed<T>)>,
+    components: Query<(&T, &NetId), With<Replicate>>,
+    awaiting_id: Query<(), (With<Replicate>, Without<NetId>)>,
+) {
+    let Some(client) = client.filter(|client| client.connected && client.player_id.is_some()) else {
+        *joined_as = None;
+        pending.clear();
+        return;
+    };
+
+    // Changes between sends are remembered so a quick change followed by a pause isn't lost
+    pending.extend(changed.iter());
+    let rejoined = *joined_as != client.player_id;
+    if !rejoined && time.elapsed_secs() - *last_send < SEND_INTERVAL {
+        return;
+    }
+    *last_send = time.elapsed_secs();
+    *joined_as = client.player_id;
+
+    let send = |component: &T, net_id: &NetId| match bincode::serialize(component) {
+        Ok(data) => client.send(ClientMessage::Replicate { entity: net_id.0, kind: kind_name::<T>().to_string(), data }),
+        Err(error) => warn!("Couldn't replicate {}: {}", kind_name::<T>(), error),
+    };
+    if rejoined {
+        pending.clear();
+        for (component, net_id) in components.iter() {
+            send(component, net_id);
+        }
+        return;
+    }
+    pending.sort_unstable();
+    pending.dedup();
+    // Entities spawned this frame have no id yet and wait for one
+    pending.retain(|&entity| match components.get(entity) {
+        Ok((component, net_id)) => {
+            send(component, net_id);
+            false
+        }
+        Err(_) => awaiting_id.contains(entity),
+    });
+}
+
+fn capture_transforms(
+    mut commands: Commands,
+    moved: Query<(Entity, &Transform, Option<&NetTransform>), (With<Replicate>, Changed<Transform>)>,
+) {
+    for (entity, transform, current) in moved.iter() {
+        let captured = NetTransform { translation: transform.translation.to_array(), rotation: transform.rotation.to_array() };
+        if current.is_none_or(|current| current.translation != captured.translation || current.rotation != captured.rotation) {
+            commands.entity(entity).insert(captured);
+        }
+    }
+}
+
+fn apply_transforms(mut replicas: Query<(&NetTransform, &mut Transform), (With<Replica>, Changed<NetTransform>)>) {
+    for (net_transform, mut transform) in replicas.iter_mut() {
+        transform.translation = Vec3::from(net_transform.translation);
+        transform.rotation = Quat::from_array(net_transform.rotation);
+    }
+}
+
+fn send_despawns(
+    client: Option<Res<NetworkClient>>,
+    mut removed: RemovedComponents<NetId>,
+    mut ids: Local<HashMap<Entity, u32>>,
+    net_ids: Query<(Entity, &NetId), Added<NetId>>,
+) {
+    // Removal only hands back the entity, so remember which id each one had
+    for (entity, net_id) in net_ids.iter() {
+        ids.insert(entity, net_id.0);
+    }
+    for entity in removed.read() {
+        let Some(net_id) = ids.remove(&entity) else { continue };
+        if let Some(client) = client.as_ref().filter(|client| client.connected) {
+            client.send(ClientMessage::DespawnReplica { entity: net_id });
+        }
+    }
+}
+
+fn apply_replica(
+    trigger: On<ReplicaReceived>,
+    registry: Res<ReplicationRegistry>,
+    mut replicas: ResMut<Replicas>,
+    existing: Query<&Replica>,
+    mut commands: Commands,
+) {
+    let Some(apply) = registry.kinds.get(trigger.kind.as_str()) else {
+        warn!("Ignoring replicated {}, which this build doesn't register", trigger.kind);
+        return;
+    };
+    let key = (trigger.owner, trigger.entity);
+    // Game code may have despawned the replica itself, or taken its `Replica` off; a fresh one takes its place
+    let current = replicas.0.get(&key).copied().filter(|&entity| {
+        existing.get(entity).is_ok_and(|replica| (replica.owner, replica.entity) == key)
+    });
+    let mut entity = match current.and_then(|entity| commands.get_entity(entity).ok()) {
+        Some(entity) => entity,
+        None => commands.spawn((
+            Replica { owner: trigger.owner, entity: trigger.entity },
+            Transform::default(),
+            Visibility::default(),
+        )),
+    };
+    replicas.0.insert(key, entity.id());
+    if let Err(error) = apply(&mut entity, &trigger.data) {
+        warn!("Couldn't decode replicated {}: {}", trigger.kind, error);
+    }
+}
+
+fn despawn_replica(trigger: On<ReplicaDespawned>, mut replicas: ResMut<Replicas>, mut commands: Commands) {
+    if let Some(entity) = replicas.0.remove(&(trigger.owner, trigger.entity)) {
+        commands.entity(entity).try_despawn();
+    }
+}
+
+/// A player's replicas leave with them
+fn despawn_owner_replicas(trigger: On<DespawnRemotePlayer>, mut replicas: ResMut<Replicas>, mut commands: Commands) {
+    replicas.0.retain(|&(owner, _), entity| {
+        if owner == trigger.0 {
+            commands.entity(*entity).try_despawn();
+        }
+        owner != trigger.0
+    });
+}
+
+fn clear_replicas(_trigger: On<Disconnected>, mut replicas: ResMut<Replicas>, mut commands: Commands) {
+    for (_, entity) in replicas.0.drain() {
+        commands.entity(entity).try_despawn();
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::day_cycle::DayNightCycle;
+use crate::environment::Deciduous;
+use crate::world_generation::Chunk;
+use crate::RenderSettings;
+
+/// Game days in one full cycle of the seasons
+pub const DEFAULT_DAYS_PER_YEAR: f32 = 24.0;
+/// How far the normalized temperature swings between midsummer and midwinter
+const TEMPERATURE_SWING: f32 = 0.12;
+/// Seasonal terrain factors are rounded to this step so chunks are only rebuilt a few times per season
+const TERRAIN_STEP: f32 = 0.1;
+
+const AUTUMN_FOLIAGE: Vec3 = Vec3::new(0.85, 0.42, 0.08);
+const WINTER_FOLIAGE: Vec3 = Vec3::new(0.35, 0.28, 0.2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeasonName {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// Seasonal inputs to the terrain colour function, cheap to copy into chunk tasks
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SeasonalTerrain {
+    /// 0.0 in summer to 1.0 in midwinter, pushes the snow line down
+    pub snow: f32,
+    /// 0.0 outside autumn to 1.0 at its peak, browns the vegetation bands
+    pub autumn: f32,
+}
+
+#[derive(Resource)]
+pub struct Season {
+    /// Days into the year; spring starts at 0
+    pub day_of_year: f32,
+    pub days_per_year: f32,
+    pub paused: bool,
+}
+
+impl Default for Season {
+    fn default() -> Self {
+        // Start at midsummer so a fresh world looks like it always has
+        Self {
+            day_of_year: DEFAULT_DAYS_PER_YEAR * 0.375,
+            days_per_year: DEFAULT_DAYS_PER_YEAR,
+            paused: false,
+        }
+    }
+}
+
+impl Season {
+    /// Fraction of the year since the spring equinox
+    pub fn year_fraction(&self) -> f32 {
+        (self.day_of_year / self.days_per_year).rem_euclid(1.0)
+    }
+
+    pub fn name(&self) -> SeasonName {
+        match (self.year_fraction() * 4.0) as u32 {
+            0 => SeasonName::Spring,
+            1 => SeasonName::Summer,
+            2 => SeasonName::Autumn,
+            _ => SeasonName::Winter,
+        }
+    }
+
+    /// 1.0 at midsummer, -1.0 at midwinter
+    pub fn warmth(&self) -> f32 {
+        ((self.year_fraction() - 0.375) * std::f32::consts::TAU).cos()
+    }
+
+    /// 0.0 outside autumn, peaking at 1.0 mid-autumn
+    pub fn autumn(&self) -> f32 {
+        let distance = (self.year_fraction() - 0.625).abs();
+        let t = (1.0 - distance / 0.2).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Shift applied to the normalized (0-1) climate temperature
+    pub fn temperature_shift(&self) -> f32 {
+        self.warmth() * TEMPERATURE_SWING
+    }
+
+    pub fn terrain(&self) -> SeasonalTerrain {
+        let quantize = |v: f32| (v / TERRAIN_STEP).round() * TERRAIN_STEP;
+        SeasonalTerrain {
+            snow: quantize((-self.warmth()).max(0.0)),
+            autumn: quantize(self.autumn()),
+        }
+    }
+
+    /// Tint applied over the original leaf colour of deciduous trees
+    fn foliage_tint(&self) -> (Vec3, f32) {
+        let winter = (-self.warmth()).max(0.0);
+        if self.autumn() >= winter {
+            (AUTUMN_FOLIAGE, self.autumn() * 0.85)
+        } else {
+            (WINTER_FOLIAGE, winter * 0.7)
+        }
+    }
+}
+
+/// Advance the year with game days and keep the day cycle's date in step
+pub fn advance_season(
+    mut season: ResMut<Season>,
+    mut cycle: ResMut<DayNightCycle>,
+    mut last_time_of_day: Local<Option<f32>>,
+) {
+    if let Some(last) = *last_time_of_day {
+        let elapsed = (cycle.time_of_day - last).rem_euclid(1.0);
+        // Large jumps come from scrubbing the time slider backwards, not from days passing
+        if elapsed < 0.5 && !season.paused {
+            season.day_of_year = (season.day_of_year + elapsed).rem_euclid(season.days_per_year);
+        }
+    }
+    *last_time_of_day = Some(cycle.time_of_day);
+
+    let year_fraction = season.year_fraction();
+    if cycle.year_fraction != year_fraction {
+        cycle.year_fraction = year_fraction;
+    }
+}
+
+/// Queue every chunk for a rebuild when the seasonal terrain colours move on a step
+pub fn refresh_chunks_for_season(
+    season: Res<Season>,
+    mut render_settings: ResMut<RenderSettings>,
+    mut chunks: Query<&mut Chunk>,
+    mut applied: Local<Option<SeasonalTerrain>>,
+) {
+    let terrain = season.terrain();
+    match *applied {
+        None => *applied = Some(terrain),
+        Some(previous) if previous != terrain => {
+            *applied = Some(terrain);
+            // An impossible LOD makes the LOD pass regenerate each chunk, nearest first
+            for mut chunk in chunks.iter_mut() {
+                chunk.current_lod = u32::MAX;
+            }
+            render_settings.just_updated = true;
+        }
+        _ => {}
+    }
+}
+
+/// Original leaf colours of the deciduous tree materials, keyed by material
+#[derive(Resource, Default)]
+pub struct SeasonalFoliage {
+    originals: HashMap<AssetId<StandardMaterial>, Color>,
+    applied: Option<(Vec3, f32)>,
+}
+
+/// Recolour the shared leaf materials of deciduous trees for the current season
+pub fn update_seasonal_foliage(
+    season: Res<Season>,
+    mut foliage: ResMut<SeasonalFoliage>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    new_meshes: Query<(Entity, &MeshMaterial3d<StandardMaterial>), Added<MeshMaterial3d<StandardMaterial>>>,
+    parents: Query<&ChildOf>,
+    deciduous: Query<(), With<Deciduous>>,
+) {
+    let mut discovered = false;
+    for (entity, material) in new_meshes.iter() {
+        if foliage.originals.contains_key(&material.id()) {
+            continue;
+        }
+        if !parents.iter_ancestors(entity).any(|ancestor| deciduous.contains(ancestor)) {
+            continue;
+        }
+        if let Some(original) = materials.get(material.id()) {
+            let linear = original.base_color.to_linear();
+            // Only the leaves: bark and branches keep their colour all year
+            if linear.green > linear.red && linear.green > linear.blue {
+                foliage.originals.insert(material.id(), original.base_color);
+                discovered = true;
+            }
+        }
+    }
+
+    let (tint, amount) = season.foliage_tint();
+    let amount = (amount / TERRAIN_STEP).round() * TERRAIN_STEP;
+    if !discovered && foliage.applied == Some((tint, amount)) {
+        return;
+    }
+    foliage.applied = Some((tint, amount));
+
+    let tint = Color::srgb(tint.x, tint.y, tint.z);
+    for (id, original) in foliage.originals.iter() {
+        if let Some(material) = materials.get_mut(*id) {
+            material.base_color = original.mix(&tint, amount);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::collections::BTreeMap;
+
+use crate::controls::{Aircraft, ControlMode, FlightMode, MainCamera};
+use crate::network::{remote_model, ConnectRequest, NetworkClient, PlaneType, SessionEntry, SESSION_FORMAT_VERSION};
+use crate::world_generation::{ResetChunks, WorldGenerator};
+
+const DEFAULT_PATH: &str = "session.rec";
+const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 4.0, 8.0];
+/// Where the camera sits relative to a followed aircraft, like the chase camera after a respawn
+const FOLLOW_DISTANCE: f32 = 150.0;
+const FOLLOW_HEIGHT: f32 = 30.0;
+
+struct Sample {
+    time: f32,
+    position: Vec3,
+    rotation: Quat,
+}
+
+/// One pilot's flight through the session
+struct Track {
+    name: String,
+    plane_type: PlaneType,
+    samples: Vec<Sample>,
+}
+
+impl Track {
+    /// Position and rotation at `time`, `None` before the pilot's first update or after their last
+    fn pose_at(&self, time: f32) -> Option<(Vec3, Quat)> {
+        let first = self.samples.first()?;
+        let last = self.samples.last()?;
+        if time < first.time || time > last.time {
+            return None;
+        }
+        let next = self.samples.partition_point(|sample| sample.time <= time).min(self.samples.len() - 1);
+        let (a, b) = (&self.samples[next.saturating_sub(1)], &self.samples[next]);
+        let t = if b.time > a.time { ((time - a.time) / (b.time - a.time)).clamp(0.0, 1.0) } else { 1.0 };
+        Some((a.position.lerp(b.position, t), a.rotation.slerp(b.rotation, t)))
+    }
+}
+
+/// A server session recording, decoded into a track per pilot
+pub struct SessionRecording {
+    tracks: BTreeMap<u32, Track>,
+    /// Seeds in force from each time on, the first at zero
+    seeds: Vec<(f32, u32)>,
+    duration: f32,
+}
+
+impl SessionRecording {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|error| format!("Couldn't read {}: {}", path, error))?;
+        let mut recording = Self { tracks: BTreeMap::new(), seeds: Vec::new(), duration: 0.0 };
+        let mut rest = bytes.as_slice();
+        while rest.len() >= 4 {
+            let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            // A server stopped mid-write leaves a partial entry at the end
+            let Some(data) = rest.get(4..4 + len) else { break };
+            rest = &rest[4 + len..];
+            let entry: SessionEntry = bincode::deserialize(data).map_err(|error| format!("Corrupt recording: {}", error))?;
+            match entry {
+                SessionEntry::Start { version, .. } if version != SESSION_FORMAT_VERSION => {
+                    return Err(format!(
+                        "{} was recorded in format {}, but this version plays format {}",
+                        path, version, SESSION_FORMAT_VERSION,
+                    ));
+                }
+                SessionEntry::Start { seed, .. } => recording.seeds.push((0.0, seed)),
+                SessionEntry::WorldChanged { time, seed } => recording.seeds.push((time, seed)),
+                SessionEntry::Position { time, player } => {
+                    let track = recording.tracks.entry(player.id).or_insert_with(|| Track {
+                        name: player.name.clone(),
+                        plane_type: player.plane_type,
+                        samples: Vec::new(),
+                    });
+                    track.name = player.name;
+                    track.plane_type = player.plane_type;
+                    track.samples.push(Sample {
+                        time,
+                        position: Vec3::from(player.position),
+                        rotation: Quat::from_array(player.rotation),
+                    });
+                    recording.duration = recording.duration.max(time);
+                }
+                // The track ends at the pilot's last update either way
+                SessionEntry::Left { time, .. } => recording.duration = recording.duration.max(time),
+            }
+        }
+        if recording.seeds.is_empty() {
+            return Err(format!("{} isn't a session recording", path));
+        }
+        Ok(recording)
+    }
+
+    fn seed_at(&self, time: f32) -> u32 {
+        self.seeds.iter().rev().find(|(from, _)| *from <= time).unwrap_or(&self.seeds[0]).1
+    }
+}
+
+/// Watching a recorded multiplayer session. Our own aircraft is parked and hidden, like an observer's,
+/// and the camera either flies free or chases one of the recorded pilots
+#[derive(Resource)]
+pub struct SessionPlayback {
+    pub open: bool,
+    pub path: String,
+    recording: Option<SessionRecording>,
+    error: Option<String>,
+    playing: bool,
+    paused: bool,
+    time: f32,
+    speed: f32,
+    follow: Option<u32>,
+    ghosts: HashMap<u32, Entity>,
+    /// The world and camera mode to go back to when playback stops
+    restore: Option<(WorldGenerator, FlightMode)>,
+}
+
+impl Default for SessionPlayback {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: DEFAULT_PATH.to_string(),
+            recording: None,
+            error: None,
+            playing: false,
+            paused: false,
+            time: 0.0,
+            speed: 1.0,
+            follow: None,
+            ghosts: HashMap::new(),
+            restore: None,
+        }
+    }
+}
+
+/// A recorded pilot's aircraft during playback
+#[derive(Component)]
+pub struct PlaybackGhost;
+
+#[derive(Event)]
+pub struct StartPlayback;
+
+#[derive(Event)]
+pub struct StopPlayback;
+
+pub fn start_playback(
+    _trigger: On<StartPlayback>,
+    mut playback: ResMut<SessionPlayback>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut control_mode: ResMut<ControlMode>,
+    client: Option<Res<NetworkClient>>,
+    mut aircraft_query: Query<&mut Visibility, With<Aircraft>>,
+    mut commands: Commands,
+) {
+    if playback.playing || client.is_some_and(|client| client.connected) {
+        return;
+    }
+    let Some(seed) = playback.recording.as_ref().map(|recording| recording.seed_at(0.0)) else { return };
+
+    playback.restore = Some((world_generator.clone(), control_mode.mode));
+    control_mode.mode = FlightMode::FreeFlight;
+    control_mode.physics_paused = true;
+    for mut visibility in aircraft_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    *world_generator = WorldGenerator::new(seed);
+    commands.trigger(ResetChunks);
+
+    playback.playing = true;
+    playback.paused = false;
+    playback.time = 0.0;
+    playback.follow = playback.recording.as_ref().and_then(|recording| recording.tracks.keys().next().copied());
+}
+
+pub fn stop_playback(
+    _trigger: On<StopPlayback>,
+    mut playback: ResMut<SessionPlayback>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut control_mode: ResMut<ControlMode>,
+    mut aircraft_query: Query<&mut Visibility, With<Aircraft>>,
+    mut commands: Commands,
+) {
+    if !playback.playing {
+        return;
+    }
+    playback.playing = false;
+    for (_, entity) in playback.ghosts.drain() {
+        commands.entity(entity).try_despawn();
+    }
+    if let Some((generator, mode)) = playback.restore.take() {
+        let reseeded = generator.seed != world_generator.seed;
+        *world_generator = generator;
+        if reseeded {
+            commands.trigger(ResetChunks);
+        }
+        control_mode.mode = mode;
+    }
+    control_mode.physics_paused = false;
+    for mut visibility in aircraft_query.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// Joining a server ends playback first, so the recording's world and the parked aircraft don't stay
+/// over the server's
+pub fn stop_playback_on_connect(_trigger: On<ConnectRequest>, mut commands: Commands) {
+    commands.trigger(StopPlayback);
+}
+
+/// Advance the clock and put every recorded aircraft where it was at that moment
+pub fn update_playback(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut playback: ResMut<SessionPlayback>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<PlaybackGhost>>,
+    mut commands: Commands,
+) {
+    if !playback.playing {
+        return;
+    }
+    let playback = &mut *playback;
+    let Some(recording) = &playback.recording else { return };
+    if !playback.paused {
+        playback.time = (playback.time + time.delta_secs() * playback.speed).min(recording.duration);
+    }
+
+    // The map may have changed part way through, or the slider moved back across a change
+    let seed = recording.seed_at(playback.time);
+    if seed != world_generator.seed {
+        *world_generator = WorldGenerator::new(seed);
+        commands.trigger(ResetChunks);
+    }
+
+    for (id, track) in &recording.tracks {
+        let pose = track.pose_at(playback.time);
+        let (_, scale) = remote_model(track.plane_type);
+        let Some(&entity) = playback.ghosts.get(id) else {
+            let Some((position, rotation)) = pose else { continue };
+            let entity = spawn_ghost(&mut commands, &asset_server, track.plane_type, Transform::from_translation(position).with_rotation(rotation));
+            playback.ghosts.insert(*id, entity);
+            continue;
+        };
+        let Ok((mut transform, mut visibility)) = ghosts.get_mut(entity) else { continue };
+        match pose {
+            Some((position, rotation)) => {
+                *transform = Transform::from_translation(position).with_rotation(rotation).with_scale(Vec3::splat(scale));
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn spawn_ghost(commands: &mut Commands, asset_server: &AssetServer, plane_type: PlaneType, transform: Transform) -> Entity {
+    let (model_path, scale) = remote_model(plane_type);
+    let model = commands.spawn((
+        SceneRoot(asset_server.load(model_path)),
+        Transform::from_rotation(Quat::from_rotation_y(180.0f32.to_radians())),
+    )).id();
+    commands
+        .spawn((PlaybackGhost, transform.with_scale(Vec3::splat(scale)), Visibility::default()))
+        .add_children(&[model])
+        .id()
+}
+
+/// Chase the followed pilot; otherwise the free-flight camera is left alone
+pub fn follow_playback_camera(
+    playback: Res<SessionPlayback>,
+    ghosts: Query<(&Transform, &Visibility), (With<PlaybackGhost>, Without<MainCamera>)>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    if !playback.playing {
+        return;
+    }
+    let Some(entity) = playback.follow.and_then(|id| playback.ghosts.get(&id)) else { return };
+    let Ok((ghost, visibility)) = ghosts.get(*entity) else { return };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let Ok(mut camera_transform) = camera_query.single_mut() else { return };
+    camera_transform.translation = ghost.translation + ghost.back() * FOLLOW_DISTANCE + Vec3::Y * FOLLOW_HEIGHT;
+    camera_transform.rotation = camera_transform.looking_at(ghost.translation, Vec3::Y).rotation;
+}
+
+fn format_clock(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Load a recording, then play, pause, seek and pick whose aircraft the camera follows
+pub fn session_playback_ui(
+    mut contexts: EguiContexts,
+    mut playback: ResMut<SessionPlayback>,
+    client: Option<Res<NetworkClient>>,
+    mut commands: Commands,
+) -> Result<(), BevyError> {
+    if !playback.open {
+        return Ok(());
+    }
+    let connected = client.is_some_and(|client| client.connected);
+    let mut open = playback.open;
+
+    egui::Window::new("Session Playback")
+        .open(&mut open)
+        .resizable(false)
+        .default_pos(egui::Pos2::new(20.0, 400.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            let playback = &mut *playback;
+            ui.horizontal(|ui| {
+                ui.add_enabled(!playback.playing, egui::TextEdit::singleline(&mut playback.path).desired_width(180.0));
+                if ui.add_enabled(!playback.playing, egui::Button::new("Load")).clicked() {
+                    match SessionRecording::load(playback.path.trim()) {
+                        Ok(recording) => {
+                            playback.recording = Some(recording);
+                            playback.error = None;
+                        }
+                        Err(error) => playback.error = Some(error),
+                    }
+                }
+            });
+            if let Some(error) = &playback.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            let Some(recording) = &playback.recording else {
+                ui.label("Record a session by starting the server with --record <path>");
+                return;
+            };
+            ui.label(format!(
+                "{} pilots, {}, seed {}",
+                recording.tracks.len(),
+                format_clock(recording.duration),
+                recording.seed_at(playback.time),
+            ));
+
+            if !playback.playing {
+                if connected {
+                    ui.label("Leave the server to watch a recording");
+                } else if ui.button("▶ Play").clicked() {
+                    commands.trigger(StartPlayback);
+                }
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                let label = if playback.paused { "▶" } else { "⏸" };
+                if ui.button(label).clicked() {
+                    playback.paused = !playback.paused;
+                }
+                if ui.button("⏹ Stop").clicked() {
+                    commands.trigger(StopPlayback);
+                }
+                ui.add(
+                    egui::Slider::new(&mut playback.time, 0.0..=recording.duration)
+                        .custom_formatter(|seconds, _| format_clock(seconds as f32)),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Speed:");
+                for speed in SPEEDS {
+                    ui.selectable_value(&mut playback.speed, speed, format!("{}×", speed));
+                }
+            });
+
+            ui.separator();
+            ui.selectable_value(&mut playback.follow, None, "Free camera");
+            for (id, track) in &recording.tracks {
+                let flying = track.pose_at(playback.time).is_some();
+                let label = if flying { track.name.clone() } else { format!("{} (not flying)", track.name) };
+                ui.selectable_value(&mut playback.follow, Some(*id), label);
+            }
+        });
+
+    playback.open = open;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use bevy::{camera::visibility::NoFrustumCulling, light::NotShadowCaster, prelude::*};
+
+use crate::{consts::*, controls::MainCamera, day_cycle::DayNightCycle, world_generation::ChunkManager};
+
+/// Dome sits just beyond the sun and stars so they draw in front of it
+const SKY_DOME_DISTANCE_FACTOR: f32 = 1.5;
+
+const NIGHT_HORIZON: Vec3 = Vec3::new(0.1, 0.1, 0.2);
+const DAY_HORIZON: Vec3 = Vec3::new(0.35, 0.48, 0.66);
+const NIGHT_ZENITH: Vec3 = Vec3::new(0.01, 0.01, 0.04);
+const DAY_ZENITH: Vec3 = Vec3::new(0.08, 0.22, 0.55);
+const SUNSET_GLOW: Vec3 = Vec3::new(0.90, 0.45, 0.2);
+const SUN_HALO: Vec3 = Vec3::new(1.0, 0.85, 0.6);
+
+#[derive(Component)]
+pub struct SkyDome;
+
+/// Sun elevation terms shared by the sky and the fog: (daylight, sunset factor)
+fn sun_terms(sun_dir: Vec3) -> (f32, f32) {
+    let up_dot = sun_dir.dot(Vec3::NEG_Y);
+    let daylight = ((up_dot + 0.1) * 5.0).clamp(0.0, 1.0);
+    let sunset = (1.0 - (up_dot.abs() / 0.34)).clamp(0.0, 1.0);
+    (daylight, sunset)
+}
+
+/// Colour of the sky at the horizon; the fog uses this so distant terrain melts into the sky
+pub fn horizon_color(sun_dir: Vec3) -> Vec3 {
+    let (daylight, sunset) = sun_terms(sun_dir);
+    NIGHT_HORIZON.lerp(DAY_HORIZON, daylight).lerp(SUNSET_GLOW, sunset * 0.5)
+}
+
+/// Sky colour seen along `view_dir` for a sun shining along `sun_dir`
+pub fn sky_color(view_dir: Vec3, sun_dir: Vec3) -> Vec3 {
+    let (daylight, sunset) = sun_terms(sun_dir);
+    let to_sun = -sun_dir;
+    let horizon = horizon_color(sun_dir);
+
+    // Below the horizon we only ever see fog
+    if view_dir.y <= 0.0 {
+        return horizon;
+    }
+
+    let zenith = NIGHT_ZENITH.lerp(DAY_ZENITH, daylight);
+    let height = view_dir.y.sqrt();
+    let mut color = horizon.lerp(zenith, height);
+
+    // Sunset glow hugs the horizon on the sun's side of the sky
+    let view_flat = Vec3::new(view_dir.x, 0.0, view_dir.z).normalize_or_zero();
+    let sun_flat = Vec3::new(to_sun.x, 0.0, to_sun.z).normalize_or_zero();
+    let facing_sun = (view_flat.dot(sun_flat) * 0.5 + 0.5).powi(3);
+    let glow = sunset * facing_sun * (1.0 - height).powi(3);
+    color = color.lerp(SUNSET_GLOW, glow * 0.8);
+
+    // Forward scattering around the sun disc
+    let sun_dot = view_dir.dot(to_sun).max(0.0);
+    let halo = sun_dot.powi(64) * 0.6 + sun_dot.powi(8) * 0.15;
+    color += SUN_HALO * halo * daylight.max(sunset);
+
+    color
+}
+
+pub fn spawn_sky_dome(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = Sphere::new(1.0).mesh().uv(48, 24);
+    let vertex_count = mesh.count_vertices();
+    let mesh = mesh.with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0f32, 1.0, 1.0, 1.0]; vertex_count]);
+
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            fog_enabled: false,
+            cull_mode: None,
+            ..default()
+        })),
+        Transform::default(),
+        SkyDome,
+        NoFrustumCulling,
+        NotShadowCaster,
+    ));
+}
+
+/// Keep the dome centred on the camera and recolour it from the sun direction
+pub fn update_sky_dome(
+    cycle: Res<DayNightCycle>,
+    chunk_manager: Res<ChunkManager>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<SkyDome>)>,
+    mut dome_query: Query<(&mut Transform, &Mesh3d), With<SkyDome>>,
+) {
+    let Ok((mut dome_transform, mesh_handle)) = dome_query.single_mut() else { return };
+
+    if let Ok(camera_transform) = camera_query.single() {
+        dome_transform.translation = camera_transform.translation;
+    }
+    dome_transform.scale = Vec3::splat(CHUNK_SIZE * chunk_manager.render_distance as f32 * SKY_DOME_DISTANCE_FACTOR);
+
+    if !cycle.is_changed() && !chunk_manager.is_changed() {
+        return;
+    }
+
+    let sun_dir = cycle.sun_direction();
+    let Some(mesh) = meshes.get_mut(&mesh_handle.0) else { return };
+    let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION).and_then(|p| p.as_float3()) else { return };
+
+    let colors: Vec<[f32; 4]> = positions
+        .iter()
+        .map(|p| {
+            let color = sky_color(Vec3::from(*p).normalize_or_zero(), sun_dir);
+            Color::srgb(color.x, color.y, color.z).to_linear().to_f32_array()
+        })
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
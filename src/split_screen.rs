@@ -0,0 +1,275 @@
+use bevy::{
+    camera::{Exposure, Viewport},
+    core_pipeline::{prepass::DepthPrepass, tonemapping::Tonemapping},
+    post_process::bloom::Bloom,
+    prelude::*,
+    render::view::Hdr,
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::accessibility::Accessibility;
+use crate::aircraft_presets::{AircraftDefinition, AircraftSelection};
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{self, chase_camera, step_flight, update_throttle, Aircraft, ControlInputs, MainCamera, PilotKeys, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::effects::{EffectKind, SpawnEffect};
+use crate::hud::{calculate_heading, calculate_pitch, calculate_roll, draw_artificial_horizon, HudPalette};
+use crate::world_generation::WorldGenerator;
+
+/// The second pilot joins off the first one's wing, close enough to share the streamed terrain
+const WINGMAN_OFFSET: Vec3 = Vec3::new(300.0, 0.0, 300.0);
+const RESPAWN_DELAY: f32 = 3.0;
+
+/// Two pilots on one keyboard, each with half the screen: the first keeps WASD and the usual keys,
+/// the second flies with `PilotKeys::SECOND` on the numeric keypad.
+/// Terrain streams around the first pilot, so the pair should stay in the same area
+#[derive(Resource, Default)]
+pub struct SplitScreen {
+    pub enabled: bool,
+}
+
+/// The second local aircraft. Like the AI opponent it keeps its own `Aircraft`,
+/// so everything written for the one player aircraft carries on unchanged
+#[derive(Component)]
+pub struct SecondPilot {
+    pub aircraft: Aircraft,
+    respawn_timer: Option<f32>,
+}
+
+/// The chase camera that fills the right half of the screen for the second pilot
+#[derive(Component)]
+pub struct SecondPilotCamera;
+
+/// Beside and a little behind the first pilot, on the same heading
+fn wingman_transform(leader: &Transform, aircraft: &Aircraft, world_gen: &WorldGenerator) -> Transform {
+    let mut position = leader.translation + leader.rotation * WINGMAN_OFFSET;
+    let ground = world_gen.get_terrain_height(&position.to_array()).max(0.0);
+    position.y = position.y.max(ground + aircraft.respawn_height);
+    let forward = leader.forward().as_vec3();
+    let heading = Vec3::new(forward.x, 0.0, forward.z).normalize_or(Vec3::NEG_Z);
+    Transform::from_translation(position).looking_to(heading, Vec3::Y)
+}
+
+/// Bring the second pilot and their camera in or out as split screen is toggled
+pub fn manage_second_pilot(
+    split_screen: Res<SplitScreen>,
+    selection: Option<Res<AircraftSelection>>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    world_gen: Res<WorldGenerator>,
+    asset_server: Res<AssetServer>,
+    player_query: Query<&Transform, (With<Aircraft>, Without<SecondPilot>)>,
+    main_camera: Query<(&Transform, &DistanceFog, &AmbientLight), With<MainCamera>>,
+    pilots: Query<Entity, With<SecondPilot>>,
+    cameras: Query<Entity, With<SecondPilotCamera>>,
+    mut commands: Commands,
+) {
+    if !split_screen.enabled {
+        for entity in pilots.iter().chain(cameras.iter()) {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if !pilots.is_empty() {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else { return };
+    let Ok((camera_transform, fog, ambient)) = main_camera.single() else { return };
+
+    let mut aircraft = selection
+        .and_then(|selection| selection.selected)
+        .and_then(|id| definitions.get(id))
+        .map(|definition| definition.to_aircraft())
+        .unwrap_or_else(Aircraft::light);
+    aircraft.speed = aircraft.respawn_speed;
+
+    let transform = wingman_transform(player_transform, &aircraft, &world_gen)
+        .with_scale(Vec3::splat(aircraft.model_scale));
+    let model_path = aircraft.model_path.clone();
+    let pilot = commands.spawn((
+        transform,
+        Visibility::default(),
+        SecondPilot { aircraft, respawn_timer: None },
+    )).id();
+    let model = commands.spawn((
+        SceneRoot(asset_server.load(model_path)),
+        Transform::from_rotation(Quat::from_rotation_y((180.0f32).to_radians())),
+    )).id();
+    commands.entity(pilot).add_child(model);
+
+    // Same look as the main camera; `mirror_main_camera` keeps the fog and light in step with the day
+    commands.spawn((
+        Camera3d::default(),
+        Camera { order: 1, ..default() },
+        Hdr,
+        Tonemapping::TonyMcMapface,
+        Bloom::NATURAL,
+        DepthPrepass,
+        Exposure::default(),
+        Projection::from(PerspectiveProjection {
+            far: 50000.0,
+            ..default()
+        }),
+        Transform::from_translation(camera_transform.translation + transform.translation - player_transform.translation)
+            .with_rotation(camera_transform.rotation),
+        fog.clone(),
+        ambient.clone(),
+        SecondPilotCamera,
+    ));
+
+    info!("Second pilot joined");
+}
+
+/// Fly the second aircraft from the keypad, and bring it back beside the first after a crash
+pub fn fly_second_pilot(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    (wind, world_gen, day_cycle, difficulty): (Res<Wind>, Res<WorldGenerator>, Res<DayNightCycle>, Res<controls::Difficulty>),
+    player_query: Query<&Transform, (With<Aircraft>, Without<SecondPilot>)>,
+    mut pilots: Query<(&mut Transform, &mut Visibility, &mut SecondPilot)>,
+    mut commands: Commands,
+) {
+    let Ok((mut transform, mut visibility, mut pilot)) = pilots.single_mut() else { return };
+    let dt = time.delta_secs();
+
+    if let Some(timer) = pilot.respawn_timer.as_mut() {
+        *timer -= dt;
+        if *timer > 0.0 {
+            return;
+        }
+        let Ok(player_transform) = player_query.single() else { return };
+        let respawned = wingman_transform(player_transform, &pilot.aircraft, &world_gen);
+        transform.translation = respawned.translation;
+        transform.rotation = respawned.rotation;
+        *visibility = Visibility::Inherited;
+        let aircraft = &mut pilot.aircraft;
+        aircraft.crashed = false;
+        aircraft.speed = aircraft.respawn_speed;
+        aircraft.throttle = 0.8;
+        aircraft.velocity = Vec3::ZERO;
+        aircraft.pitch_velocity = 0.0;
+        aircraft.roll_velocity = 0.0;
+        aircraft.yaw_velocity = 0.0;
+        aircraft.spin_rate = 0.0;
+        pilot.respawn_timer = None;
+        return;
+    }
+
+    let keys = PilotKeys::SECOND;
+    update_throttle(&keyboard, &keys, &mut pilot.aircraft, dt);
+    // The second aircraft has no engine simulation; its power follows the throttle at once
+    pilot.aircraft.power = pilot.aircraft.throttle;
+    let inputs = ControlInputs::from_keyboard(&keyboard, &keys);
+    step_flight(&mut pilot.aircraft, &mut transform, Some(inputs), &wind, &world_gen, &day_cycle, &time, &difficulty);
+
+    let pos = transform.translation;
+    let terrain_height = world_gen.get_terrain_height(&pos.to_array());
+    if pos.y <= terrain_height || pos.y <= 0.0 {
+        let kind = if terrain_height <= 0.0 { EffectKind::Splash } else { EffectKind::Explosion };
+        commands.trigger(SpawnEffect {
+            kind,
+            position: Vec3::new(pos.x, terrain_height.max(0.0), pos.z),
+            velocity: transform.forward().as_vec3() * pilot.aircraft.speed + pilot.aircraft.velocity,
+            intensity: 1.0,
+        });
+        pilot.aircraft.crashed = true;
+        pilot.aircraft.speed = 0.0;
+        pilot.respawn_timer = Some(RESPAWN_DELAY);
+        *visibility = Visibility::Hidden;
+        info!("Second pilot crashed");
+    }
+}
+
+pub fn follow_second_pilot(
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    pilots: Query<(&Transform, &SecondPilot), Without<SecondPilotCamera>>,
+    mut cameras: Query<&mut Transform, With<SecondPilotCamera>>,
+) {
+    let Ok((plane_transform, pilot)) = pilots.single() else { return };
+    let Ok(mut camera_transform) = cameras.single_mut() else { return };
+    if pilot.respawn_timer.is_some() {
+        return;
+    }
+    let aircraft = &pilot.aircraft;
+    chase_camera(&mut camera_transform, plane_transform, aircraft, aircraft.camera_distance, accessibility.reduce_motion, time.delta_secs());
+}
+
+/// Carry the day cycle's fog, ambient light and exposure over from the main camera
+pub fn mirror_main_camera(
+    main_camera: Query<(&DistanceFog, &AmbientLight, &Exposure, &Bloom), (With<MainCamera>, Without<SecondPilotCamera>)>,
+    mut cameras: Query<(&mut DistanceFog, &mut AmbientLight, &mut Exposure, &mut Bloom), With<SecondPilotCamera>>,
+) {
+    let Ok((fog, ambient, exposure, bloom)) = main_camera.single() else { return };
+    for (mut second_fog, mut second_ambient, mut second_exposure, mut second_bloom) in cameras.iter_mut() {
+        *second_fog = fog.clone();
+        *second_ambient = ambient.clone();
+        *second_exposure = *exposure;
+        *second_bloom = bloom.clone();
+    }
+}
+
+/// Left half to the first pilot and right half to the second while both are flying, the whole window otherwise
+pub fn update_split_viewports(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    second_cameras: Query<Entity, With<SecondPilotCamera>>,
+    mut cameras: Query<(&mut Camera, Has<MainCamera>, Has<SecondPilotCamera>)>,
+) {
+    let Ok(window) = windows.single() else { return };
+    let size = window.physical_size();
+    let half = UVec2::new(size.x / 2, size.y);
+    let split = !second_cameras.is_empty() && half.x > 0 && half.y > 0;
+
+    for (mut camera, is_main, is_second) in cameras.iter_mut() {
+        let viewport = match (split, is_main, is_second) {
+            (true, true, _) => Some(Viewport { physical_position: UVec2::ZERO, physical_size: half, ..default() }),
+            (true, _, true) => Some(Viewport { physical_position: UVec2::new(half.x, 0), physical_size: half, ..default() }),
+            (false, true, _) => None,
+            _ => continue,
+        };
+        let unchanged = match (&camera.viewport, &viewport) {
+            (Some(current), Some(wanted)) => current.physical_position == wanted.physical_position && current.physical_size == wanted.physical_size,
+            (None, None) => true,
+            _ => false,
+        };
+        if !unchanged {
+            camera.viewport = viewport;
+        }
+    }
+}
+
+/// Compact instruments for the second pilot, in the corner of their half of the screen
+pub fn second_pilot_hud(
+    mut contexts: EguiContexts,
+    units: Res<UnitSystem>,
+    palette: Res<HudPalette>,
+    pilots: Query<(&Transform, &SecondPilot)>,
+) -> Result<(), BevyError> {
+    let Ok((transform, pilot)) = pilots.single() else { return Ok(()) };
+    let aircraft = &pilot.aircraft;
+    let forward = transform.forward().as_vec3();
+
+    egui::Window::new("Second Pilot")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-20.0, -20.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new("PILOT 2").size(12.0));
+                if aircraft.crashed {
+                    ui.label(egui::RichText::new("⚠ CRASHED: rejoining").strong());
+                    return;
+                }
+                draw_artificial_horizon(ui, calculate_pitch(forward), calculate_roll(transform), &palette);
+                ui.label(format!("HDG {:03.0}°", calculate_heading(forward)));
+                ui.label(units.format_speed(world_units_to_meters(aircraft.speed)));
+                ui.label(units.format_altitude(world_units_to_meters(transform.translation.y)));
+                ui.label(format!("Throttle {:.0}%", aircraft.throttle / aircraft.max_throttle.max(f32::EPSILON) * 100.0));
+                ui.label(egui::RichText::new("Numpad 8/5/4/6 stick, 7/9 rudder, +/− throttle").size(10.0));
+            });
+        });
+
+    Ok(())
+}
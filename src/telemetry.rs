@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use std::net::UdpSocket;
+
+use crate::consts::{world_units_to_meters, GeoOrigin, METERS_TO_FEET, MPS_TO_KNOTS, MPS_TO_MPH};
+use crate::controls::{load_factor, Aircraft};
+use crate::hud::{calculate_heading, calculate_pitch, calculate_roll};
+use crate::world_generation::WorldGenerator;
+
+/// X-Plane's own default for incoming data
+pub const DEFAULT_TELEMETRY_TARGET: &str = "127.0.0.1:49000";
+/// X-Plane's marker for a field a DATA record doesn't fill
+const XPLANE_UNUSED: f32 = -999.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    /// X-Plane `DATA` packets: speeds (3), Mach/VVI/G (4), attitude (17), position (20) and throttle (25)
+    XPlane,
+    /// One JSON object per datagram, fields as in `TelemetryFrame`
+    Json,
+}
+
+/// Flight data streamed over UDP for instrument panels, motion rigs and loggers
+#[derive(Resource)]
+pub struct Telemetry {
+    pub enabled: bool,
+    pub target: String,
+    pub format: TelemetryFormat,
+    /// Datagrams per second
+    pub rate: f32,
+    pub error: Option<String>,
+    socket: Option<UdpSocket>,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: DEFAULT_TELEMETRY_TARGET.to_string(),
+            format: TelemetryFormat::XPlane,
+            rate: 20.0,
+            error: None,
+            socket: None,
+        }
+    }
+}
+
+/// One sample of the local aircraft. Angles in degrees, speeds in m/s, altitudes in meters
+#[derive(Debug, Clone, Copy)]
+struct TelemetryFrame {
+    latitude: f64,
+    longitude: f64,
+    altitude: f32,
+    height_above_ground: f32,
+    heading: f32,
+    pitch: f32,
+    roll: f32,
+    airspeed: f32,
+    vertical_speed: f32,
+    g_load: f32,
+    throttle: f32,
+}
+
+impl TelemetryFrame {
+    fn to_json(self) -> String {
+        format!(
+            "{{\"latitude\":{:.7},\"longitude\":{:.7},\"altitude\":{:.2},\"height_above_ground\":{:.2},\"heading\":{:.2},\"pitch\":{:.2},\"roll\":{:.2},\"airspeed\":{:.2},\"vertical_speed\":{:.2},\"g_load\":{:.3},\"throttle\":{:.3}}}",
+            self.latitude, self.longitude, self.altitude, self.height_above_ground, self.heading,
+            self.pitch, self.roll, self.airspeed, self.vertical_speed, self.g_load, self.throttle,
+        )
+    }
+
+    fn to_xplane(self) -> Vec<u8> {
+        let knots = self.airspeed * MPS_TO_KNOTS;
+        let mph = self.airspeed * MPS_TO_MPH;
+        let records: [(i32, [f32; 8]); 5] = [
+            (3, [knots, knots, knots, knots, XPLANE_UNUSED, mph, mph, mph]),
+            (4, [XPLANE_UNUSED, XPLANE_UNUSED, self.vertical_speed * METERS_TO_FEET * 60.0, XPLANE_UNUSED, self.g_load, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED]),
+            (17, [self.pitch, self.roll, self.heading, self.heading, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED]),
+            (20, [
+                self.latitude as f32,
+                self.longitude as f32,
+                self.altitude * METERS_TO_FEET,
+                self.height_above_ground * METERS_TO_FEET,
+                0.0,
+                self.altitude * METERS_TO_FEET,
+                XPLANE_UNUSED,
+                XPLANE_UNUSED,
+            ]),
+            (25, [self.throttle, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED, XPLANE_UNUSED]),
+        ];
+
+        // "DATA", an internal-use byte, then 36-byte records of an index and eight little-endian floats
+        let mut packet = b"DATA\0".to_vec();
+        for (index, values) in records {
+            packet.extend_from_slice(&index.to_le_bytes());
+            for value in values {
+                packet.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        packet
+    }
+}
+
+fn sample(transform: &Transform, aircraft: &Aircraft, world_gen: &WorldGenerator, origin: &GeoOrigin) -> TelemetryFrame {
+    let position = transform.translation;
+    let forward = transform.forward().as_vec3();
+    let (latitude, longitude) = origin.to_lat_lon(position);
+    let ground = world_gen.get_terrain_height(&position.to_array()).max(0.0);
+    let velocity = forward * aircraft.speed + aircraft.velocity;
+    TelemetryFrame {
+        latitude,
+        longitude,
+        altitude: world_units_to_meters(position.y),
+        height_above_ground: world_units_to_meters(position.y - ground),
+        heading: calculate_heading(forward),
+        pitch: calculate_pitch(forward),
+        roll: calculate_roll(transform),
+        airspeed: world_units_to_meters(aircraft.speed),
+        vertical_speed: world_units_to_meters(velocity.y),
+        g_load: load_factor(aircraft),
+        throttle: aircraft.throttle / aircraft.max_throttle.max(f32::EPSILON),
+    }
+}
+
+/// Send a frame to the target at the configured rate, opening the socket the first time round
+pub fn broadcast_telemetry(
+    time: Res<Time<Real>>,
+    mut telemetry: ResMut<Telemetry>,
+    mut since_last: Local<f32>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    world_gen: Res<WorldGenerator>,
+    origin: Res<GeoOrigin>,
+) {
+    if !telemetry.enabled {
+        telemetry.socket = None;
+        return;
+    }
+    *since_last += time.delta_secs();
+    if *since_last < 1.0 / telemetry.rate.max(1.0) {
+        return;
+    }
+    *since_last = 0.0;
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+
+    if telemetry.socket.is_none() {
+        match UdpSocket::bind("0.0.0.0:0").and_then(|socket| socket.set_nonblocking(true).map(|_| socket)) {
+            Ok(socket) => telemetry.socket = Some(socket),
+            Err(e) => {
+                telemetry.error = Some(format!("Couldn't open a UDP socket: {}", e));
+                telemetry.enabled = false;
+                return;
+            }
+        }
+    }
+
+    let frame = sample(transform, aircraft, &world_gen, &origin);
+    let packet = match telemetry.format {
+        TelemetryFormat::XPlane => frame.to_xplane(),
+        TelemetryFormat::Json => frame.to_json().into_bytes(),
+    };
+    let Some(socket) = &telemetry.socket else { return };
+    // Nobody listening is normal for UDP; only a bad address is worth reporting
+    telemetry.error = match socket.send_to(&packet, telemetry.target.as_str()) {
+        Err(e) if e.kind() != std::io::ErrorKind::WouldBlock && e.kind() != std::io::ErrorKind::ConnectionRefused => {
+            Some(format!("Couldn't send to {}: {}", telemetry.target, e))
+        }
+        _ => None,
+    };
+}
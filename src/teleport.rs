@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::{Aircraft, MainCamera};
+use crate::game_state::GameState;
+use crate::world_generation::{ResetChunks, WorldGenerator};
+
+/// Chunks on each side of the destination whose heights bound the arrival altitude
+const SAFE_AREA_RADIUS: i32 = 1;
+/// Subdivisions the destination heightfields are sampled at; coarse, but peaks between samples are covered by the clearance
+const SAFE_AREA_LOD: u32 = 16;
+/// Height kept above the highest ground around the destination
+const SAFE_CLEARANCE: f32 = 300.0;
+
+/// Move the aircraft anywhere in the world. The terrain around the destination is generated behind the
+/// loading screen first, and the aircraft only arrives once it exists
+#[derive(Event)]
+pub struct Teleport {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// Where the aircraft is headed while the terrain there is generated
+#[derive(Resource, Default)]
+pub struct PendingTeleport {
+    target: Option<(Vec3, Quat)>,
+}
+
+impl PendingTeleport {
+    pub fn destination(&self) -> Option<Vec3> {
+        self.target.map(|(position, _)| position)
+    }
+}
+
+pub fn is_teleporting(pending: Res<PendingTeleport>) -> bool {
+    pending.target.is_some()
+}
+
+/// Lowest altitude clear of every peak in the chunks around `position`, read from their heightfields
+pub fn safe_altitude(world_gen: &WorldGenerator, position: Vec3) -> f32 {
+    let center_x = (position.x / CHUNK_SIZE).round() as i32;
+    let center_z = (position.z / CHUNK_SIZE).round() as i32;
+    let highest = (-SAFE_AREA_RADIUS..=SAFE_AREA_RADIUS)
+        .flat_map(|dx| (-SAFE_AREA_RADIUS..=SAFE_AREA_RADIUS).map(move |dz| (center_x + dx, center_z + dz)))
+        .flat_map(|chunk| world_gen.chunk_heightfield(chunk, SAFE_AREA_LOD).heights)
+        .fold(0.0, f32::max);
+    highest + SAFE_CLEARANCE
+}
+
+/// Throw away the terrain here, point the chunk streaming at the destination and wait on the loading
+/// screen; the aircraft stays put until `complete_teleport`
+pub fn begin_teleport(
+    trigger: On<Teleport>,
+    world_gen: Res<WorldGenerator>,
+    mut pending: ResMut<PendingTeleport>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut camera_query: Query<(&mut Transform, &MainCamera)>,
+    mut commands: Commands,
+) {
+    let mut position = trigger.position;
+    position.y = position.y.max(safe_altitude(&world_gen, position));
+    pending.target = Some((position, trigger.rotation));
+
+    // Chunks stream around the camera, so it goes ahead and waits where the aircraft will appear
+    if let Ok((mut camera_transform, main_camera)) = camera_query.single_mut() {
+        let behind = trigger.rotation * Vec3::Z * main_camera.orbit_distance * 5.0;
+        camera_transform.translation = position + behind + Vec3::Y * 9.0;
+        camera_transform.rotation = camera_transform.looking_at(position, Vec3::Y).rotation;
+    }
+    commands.trigger(ResetChunks);
+    next_state.set(GameState::Loading);
+}
+
+/// The destination's terrain is ready: bring the aircraft over, keeping its speed but none of its motion
+pub fn complete_teleport(mut pending: ResMut<PendingTeleport>, mut aircraft_query: Query<(&mut Transform, &mut Aircraft)>) {
+    let Some((position, rotation)) = pending.target.take() else { return };
+    let Ok((mut transform, mut aircraft)) = aircraft_query.single_mut() else { return };
+    transform.translation = position;
+    transform.rotation = rotation;
+
+    aircraft.crashed = false;
+    aircraft.speed = aircraft.speed.max(aircraft.respawn_speed);
+    aircraft.velocity = Vec3::ZERO;
+    aircraft.pitch_velocity = 0.0;
+    aircraft.roll_velocity = 0.0;
+    aircraft.yaw_velocity = 0.0;
+    aircraft.spin_rate = 0.0;
+    info!("Teleported to {:.0} {:.0} {:.0}", position.x, position.y, position.z);
+}
@@ -0,0 +1,171 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui::{self, Frame}, input::EguiWantsInput, EguiContexts};
+use std::sync::Arc;
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::{ControlMode, FlightMode, MainCamera};
+use crate::hud::HudPalette;
+use crate::world_generation::{ChunkManager, WorldGenerator};
+
+/// Furthest the cursor ray looks for terrain, in world units
+const BRUSH_REACH: f32 = 30000.0;
+/// Shortest ray-march step; steps grow with the height above the ground
+const BRUSH_RAY_STEP: f32 = 25.0;
+const BRUSH_RAY_REFINE_STEPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushMode {
+    Raise,
+    Lower,
+    /// Level toward the height where the stroke started
+    Flatten,
+}
+
+/// Debug terrain sculpting in free flight, painted through the chunk override layer with the left mouse button
+#[derive(Resource)]
+pub struct TerrainBrush {
+    pub enabled: bool,
+    pub mode: BrushMode,
+    /// World units
+    pub radius: f32,
+    /// World units per second at the centre, fading to nothing at the rim
+    pub strength: f32,
+    flatten_height: Option<f32>,
+}
+
+impl Default for TerrainBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: BrushMode::Raise,
+            radius: 400.0,
+            strength: 200.0,
+            flatten_height: None,
+        }
+    }
+}
+
+/// Where a ray first meets the terrain, marching in steps and then bisecting the crossing
+fn raycast_terrain(world_gen: &WorldGenerator, ray: Ray3d) -> Option<Vec3> {
+    let clearance = |point: Vec3| point.y - world_gen.get_terrain_height(&point.to_array());
+    let mut previous = 0.0;
+    let mut distance = 0.0;
+    while distance < BRUSH_REACH {
+        let above = clearance(ray.get_point(distance));
+        if above < 0.0 {
+            let (mut near, mut far) = (previous, distance);
+            for _ in 0..BRUSH_RAY_REFINE_STEPS {
+                let middle = (near + far) * 0.5;
+                if clearance(ray.get_point(middle)) < 0.0 { far = middle } else { near = middle }
+            }
+            return Some(ray.get_point(far));
+        }
+        previous = distance;
+        distance += (above * 0.5).max(BRUSH_RAY_STEP);
+    }
+    None
+}
+
+/// Outline the brush where the cursor meets the ground and sculpt there while the left button is held
+pub fn apply_terrain_brush(
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    egui_input: Res<EguiWantsInput>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut brush: ResMut<TerrainBrush>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut gizmos: Gizmos,
+) {
+    if !brush.enabled || control_mode.mode != FlightMode::FreeFlight {
+        brush.flatten_height = None;
+        return;
+    }
+    let Ok(window) = windows.single() else { return };
+    let Some(cursor) = window.cursor_position() else { return };
+    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+    let cursor = crate::graphics::cursor_in_main_view(window, camera, cursor);
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else { return };
+    let Some(target) = raycast_terrain(&world_generator, ray) else { return };
+
+    let color = match brush.mode {
+        BrushMode::Raise => Color::srgb(0.3, 0.9, 0.4),
+        BrushMode::Lower => Color::srgb(0.9, 0.4, 0.3),
+        BrushMode::Flatten => Color::srgb(0.4, 0.6, 1.0),
+    };
+    let ground = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    gizmos.circle(Isometry3d::new(target + Vec3::Y * 5.0, ground), brush.radius, color);
+    gizmos.circle(Isometry3d::new(target + Vec3::Y * 5.0, ground), brush.radius * 0.5, color.with_alpha(0.4));
+
+    if !mouse.pressed(MouseButton::Left) || egui_input.wants_pointer_input() {
+        brush.flatten_height = None;
+        return;
+    }
+    let flatten_height = *brush.flatten_height.get_or_insert(target.y);
+    let (mode, radius) = (brush.mode, brush.radius);
+    let step = brush.strength * time.delta_secs();
+    let center = target.xz();
+
+    // With the edits lifted out, the generator reports the bare procedural terrain
+    let mut overrides = std::mem::take(&mut world_generator.overrides);
+    let edits = Arc::make_mut(&mut overrides);
+    let chunk_of = |coordinate: f32| (coordinate / CHUNK_SIZE).round() as i32;
+    for chunk_x in chunk_of(center.x - radius)..=chunk_of(center.x + radius) {
+        for chunk_z in chunk_of(center.y - radius)..=chunk_of(center.y + radius) {
+            edits.modify((chunk_x, chunk_z), |position, offset| {
+                let t = position.distance(center) / radius;
+                if t >= 1.0 {
+                    return;
+                }
+                let weight = 1.0 - t * t * (3.0 - 2.0 * t);
+                *offset += match mode {
+                    BrushMode::Raise => step * weight,
+                    BrushMode::Lower => -step * weight,
+                    BrushMode::Flatten => {
+                        let height = world_generator.get_terrain_height(&[position.x, 0.0, position.y]) + *offset;
+                        (flatten_height - height).clamp(-step, step) * weight
+                    }
+                };
+            });
+            chunk_manager.to_remesh.insert((chunk_x, chunk_z));
+        }
+    }
+    world_generator.overrides = overrides;
+}
+
+pub fn terrain_brush_ui(
+    mut contexts: EguiContexts,
+    control_mode: Res<ControlMode>,
+    mut brush: ResMut<TerrainBrush>,
+    palette: Res<HudPalette>,
+) -> Result<(), BevyError> {
+    if control_mode.mode != FlightMode::FreeFlight {
+        return Ok(());
+    }
+
+    egui::Window::new("Terrain Brush")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_CENTER, [-20.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("TERRAIN BRUSH").size(12.0));
+            ui.checkbox(&mut brush.enabled, "Sculpt with the left mouse button");
+            if !brush.enabled {
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut brush.mode, BrushMode::Raise, "Raise");
+                ui.selectable_value(&mut brush.mode, BrushMode::Lower, "Lower");
+                ui.selectable_value(&mut brush.mode, BrushMode::Flatten, "Flatten");
+            });
+            ui.add(egui::Slider::new(&mut brush.radius, 50.0..=3000.0).logarithmic(true).text("Radius"));
+            ui.add(egui::Slider::new(&mut brush.strength, 10.0..=2000.0).logarithmic(true).text("Strength"));
+            ui.label(egui::RichText::new("reset_terrain in the console undoes every edit").size(10.0));
+        });
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::consts::CHUNK_SIZE;
+use crate::world_generation::WorldGenerator;
+
+/// Edited terrain, one directory per world seed and one file per chunk
+const SAVE_DIR: &str = "saves/terrain";
+/// Height samples along each side of a chunk's override grid, edges included so neighbours line up
+pub const OVERRIDE_GRID: usize = 33;
+
+/// Height offsets over one chunk, in world units, on a regular grid from its -X -Z corner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkOverride {
+    heights: Vec<f32>,
+}
+
+impl Default for ChunkOverride {
+    fn default() -> Self {
+        Self { heights: vec![0.0; OVERRIDE_GRID * OVERRIDE_GRID] }
+    }
+}
+
+impl ChunkOverride {
+    /// Bilinear offset at `local`, the position across the chunk from 0 to 1 on each axis
+    fn sample(&self, local: Vec2) -> f32 {
+        let cells = (OVERRIDE_GRID - 1) as f32;
+        let grid = (local * cells).clamp(Vec2::ZERO, Vec2::splat(cells));
+        let (x0, z0) = (grid.x.floor() as usize, grid.y.floor() as usize);
+        let (x1, z1) = ((x0 + 1).min(OVERRIDE_GRID - 1), (z0 + 1).min(OVERRIDE_GRID - 1));
+        let (tx, tz) = (grid.x.fract(), grid.y.fract());
+        let at = |x: usize, z: usize| self.heights[z * OVERRIDE_GRID + x];
+        let near = at(x0, z0) + (at(x1, z0) - at(x0, z0)) * tx;
+        let far = at(x0, z1) + (at(x1, z1) - at(x0, z1)) * tx;
+        near + (far - near) * tz
+    }
+
+    fn is_flat(&self) -> bool {
+        self.heights.iter().all(|height| *height == 0.0)
+    }
+}
+
+/// Deltas layered on top of the procedural terrain of one seed, read by the height queries and the chunk meshes
+#[derive(Debug, Clone, Default)]
+pub struct TerrainOverrides {
+    /// World the store was read for; `None` until it has been loaded
+    seed: Option<u32>,
+    chunks: HashMap<(i32, i32), ChunkOverride>,
+    /// Chunks edited since they were last written out
+    unsaved: HashSet<(i32, i32)>,
+}
+
+impl TerrainOverrides {
+    /// Height offset at a world position, zero wherever nothing has been edited
+    pub fn offset_at(&self, x: f32, z: f32) -> f32 {
+        if self.chunks.is_empty() {
+            return 0.0;
+        }
+        let chunk = ((x / CHUNK_SIZE).round() as i32, (z / CHUNK_SIZE).round() as i32);
+        let Some(grid) = self.chunks.get(&chunk) else { return 0.0 };
+        // Chunk meshes are centred on their coordinates
+        let local = Vec2::new(x, z) / CHUNK_SIZE - Vec2::new(chunk.0 as f32, chunk.1 as f32) + 0.5;
+        grid.sample(local)
+    }
+
+    /// Chunks with edits, so a change can remesh just those
+    pub fn edited_chunks(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.chunks.keys()
+    }
+
+    /// Change the offsets of one chunk: `edit` sees the world XZ of every grid sample and its offset.
+    /// Samples on shared edges exist in both neighbours, so apply position-based edits to each of them
+    pub fn modify(&mut self, chunk: (i32, i32), mut edit: impl FnMut(Vec2, &mut f32)) {
+        let corner = (Vec2::new(chunk.0 as f32, chunk.1 as f32) - 0.5) * CHUNK_SIZE;
+        let spacing = CHUNK_SIZE / (OVERRIDE_GRID - 1) as f32;
+        let grid = self.chunks.entry(chunk).or_default();
+        for (index, height) in grid.heights.iter_mut().enumerate() {
+            let sample = Vec2::new((index % OVERRIDE_GRID) as f32, (index / OVERRIDE_GRID) as f32);
+            edit(corner + sample * spacing, height);
+        }
+        self.unsaved.insert(chunk);
+    }
+
+    fn load(seed: u32) -> Self {
+        let mut overrides = Self { seed: Some(seed), ..default() };
+        let dir = format!("{}/{}", SAVE_DIR, seed);
+        let Ok(entries) = std::fs::read_dir(&dir) else { return overrides };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(chunk) = path.file_stem().and_then(|stem| stem.to_str()).and_then(parse_chunk_name) else { continue };
+            let loaded = std::fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| ron::from_str::<ChunkOverride>(&text).map_err(|error| error.to_string()));
+            match loaded {
+                Ok(grid) if grid.heights.len() == OVERRIDE_GRID * OVERRIDE_GRID => {
+                    overrides.chunks.insert(chunk, grid);
+                }
+                Ok(_) => warn!("Ignoring {}: wrong grid size", path.display()),
+                Err(error) => warn!("Ignoring {}: {}", path.display(), error),
+            }
+        }
+        if !overrides.chunks.is_empty() {
+            info!("Loaded terrain edits for {} chunks from {}", overrides.chunks.len(), dir);
+        }
+        overrides
+    }
+
+    /// Write out the chunks edited since the last save. Chunks edited back to flat lose their file
+    fn save(&mut self) {
+        let Some(seed) = self.seed else { return };
+        let dir = format!("{}/{}", SAVE_DIR, seed);
+        for chunk in std::mem::take(&mut self.unsaved) {
+            let path = format!("{}/{}_{}.ron", dir, chunk.0, chunk.1);
+            let result = match self.chunks.get(&chunk) {
+                Some(grid) if !grid.is_flat() => ron::ser::to_string(grid)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| {
+                        std::fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+                        std::fs::write(&path, text).map_err(|error| error.to_string())
+                    }),
+                _ => {
+                    self.chunks.remove(&chunk);
+                    std::fs::remove_file(&path).or_else(|error| match error.kind() {
+                        std::io::ErrorKind::NotFound => Ok(()),
+                        _ => Err(error.to_string()),
+                    })
+                }
+            };
+            if let Err(error) = result {
+                warn!("Couldn't save {}: {}", path, error);
+            }
+        }
+    }
+}
+
+/// `x_z` file stems back to chunk coordinates
+fn parse_chunk_name(stem: &str) -> Option<(i32, i32)> {
+    let (x, z) = stem.split_once('_')?;
+    Some((x.parse().ok()?, z.parse().ok()?))
+}
+
+/// Pick up the saved edits whenever the world changes seed, before any of its chunks are meshed
+pub fn load_terrain_overrides(mut world_generator: ResMut<WorldGenerator>) {
+    let seed = world_generator.seed;
+    if world_generator.overrides.seed != Some(seed) {
+        world_generator.overrides = Arc::new(TerrainOverrides::load(seed));
+    }
+}
+
+pub fn save_terrain_overrides(mut world_generator: ResMut<WorldGenerator>) {
+    if !world_generator.overrides.unsaved.is_empty() {
+        Arc::make_mut(&mut world_generator.overrides).save();
+    }
+}
@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::controls::Aircraft;
+use crate::hud::HudPalette;
+use crate::network::{
+    ClientMessage, Disconnected, LeaderboardEntry, LeaderboardReceived, NetworkClient, RespawnAircraft,
+    TimeTrialCourseReceived, TimeTrialResultReceived,
+};
+use crate::world_generation::WorldGenerator;
+
+/// Gates hang this far above the ground or water beneath them
+const GATE_CLEARANCE: f32 = 400.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrialState {
+    #[default]
+    Idle,
+    /// Asked the server for the course
+    WaitingForCourse,
+    /// Course placed, the clock starts at the first gate
+    Armed,
+    Running,
+    /// Finished, waiting for the server to validate the time
+    Submitted,
+}
+
+/// Gates flown in order and timed at the closest approach of the aircraft to each
+#[derive(Default)]
+pub struct GateCourse {
+    pub gates: Vec<Vec3>,
+    pub gate_radius: f32,
+    /// Seconds since the course was laid out; gate times are taken on this clock
+    clock: f32,
+    gate_times: Vec<f32>,
+    /// Closest approach so far to the gate being flown through, as (distance, clock time)
+    pending: Option<(f32, f32)>,
+    previous_position: Option<Vec3>,
+}
+
+impl GateCourse {
+    /// Hang gates over points on the ground plane, clear of the terrain or water beneath each
+    pub fn over_terrain(points: impl IntoIterator<Item = Vec2>, gate_radius: f32, world_gen: &WorldGenerator) -> Self {
+        let gates = points
+            .into_iter()
+            .map(|gate| {
+                let ground = world_gen.get_terrain_height(&[gate.x, 0.0, gate.y]).max(0.0);
+                Vec3::new(gate.x, ground + GATE_CLEARANCE, gate.y)
+            })
+            .collect();
+        Self { gates, gate_radius, ..default() }
+    }
+
+    /// Forget the run so far; the clock starts again at the first gate
+    pub fn rearm(&mut self) {
+        *self = Self { gates: std::mem::take(&mut self.gates), gate_radius: self.gate_radius, ..default() };
+    }
+
+    pub fn started(&self) -> bool {
+        !self.gate_times.is_empty()
+    }
+
+    pub fn gates_passed(&self) -> usize {
+        self.gate_times.len()
+    }
+
+    pub fn elapsed(&self) -> Option<f32> {
+        self.gate_times.first().map(|start| self.clock - start)
+    }
+
+    /// Move the aircraft on by a frame, returning the run time once it leaves the finish gate
+    pub fn advance(&mut self, dt: f32, position: Vec3) -> Option<f32> {
+        let previous_clock = self.clock;
+        self.clock += dt;
+        let next_gate = self.gate_times.len();
+
+        if let (Some(previous), Some(&gate)) = (self.previous_position, self.gates.get(next_gate)) {
+            let segment = position - previous;
+            let t = if segment.length_squared() > 0.0 {
+                ((gate - previous).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = (previous + segment * t).distance(gate);
+            if distance <= self.gate_radius {
+                if self.pending.is_none_or(|(best, _)| distance < best) {
+                    self.pending = Some((distance, previous_clock + (self.clock - previous_clock) * t));
+                }
+            } else if let Some((_, gate_time)) = self.pending.take() {
+                self.gate_times.push(gate_time);
+                if self.gate_times.len() == self.gates.len() {
+                    self.previous_position = Some(position);
+                    return Some(gate_time - self.gate_times[0]);
+                }
+            }
+        }
+        self.previous_position = Some(position);
+        None
+    }
+
+    /// Gates still to fly, facing along the course with the next one highlighted
+    pub fn draw(&self, gizmos: &mut Gizmos, position: Vec3) {
+        let next_gate = self.gate_times.len();
+        for (index, &gate) in self.gates.iter().enumerate().skip(next_gate) {
+            let along = match (self.gates.get(index + 1), index.checked_sub(1).and_then(|i| self.gates.get(i))) {
+                (Some(&next), _) => next - gate,
+                (None, Some(&previous)) => gate - previous,
+                (None, None) => Vec3::NEG_Z,
+            };
+            let facing = Vec3::new(along.x, 0.0, along.z).normalize_or(Vec3::NEG_Z);
+            let color = if index == next_gate {
+                Color::srgb(0.1, 1.0, 0.3)
+            } else if index + 1 == self.gates.len() {
+                Color::srgb(1.0, 0.3, 0.2)
+            } else {
+                Color::srgba(1.0, 1.0, 1.0, 0.5)
+            };
+            let ring = Isometry3d::new(gate, Quat::from_rotation_arc(Vec3::Z, facing));
+            gizmos.circle(ring, self.gate_radius, color);
+            gizmos.circle(ring, self.gate_radius * 0.9, color);
+        }
+        if let Some(&gate) = self.gates.get(next_gate) {
+            gizmos.line(position, gate, Color::srgba(0.1, 1.0, 0.3, 0.3));
+        }
+    }
+}
+
+/// Server-hosted time trial through a gate course, plus the shared leaderboard
+#[derive(Resource, Default)]
+pub struct TimeTrial {
+    pub show_leaderboard: bool,
+    pub state: TrialState,
+    pub status: Option<String>,
+    pub leaderboard: Vec<LeaderboardEntry>,
+    course: GateCourse,
+}
+
+impl TimeTrial {
+    pub fn start(&mut self, client: &NetworkClient) {
+        client.send(ClientMessage::StartTimeTrial);
+        self.state = TrialState::WaitingForCourse;
+        self.status = None;
+    }
+
+    fn abort(&mut self, reason: &str) {
+        self.state = TrialState::Idle;
+        self.course = GateCourse::default();
+        self.status = Some(reason.to_string());
+    }
+}
+
+pub fn receive_time_trial_course(
+    trigger: On<TimeTrialCourseReceived>,
+    mut trial: ResMut<TimeTrial>,
+    world_gen: Res<WorldGenerator>,
+) {
+    trial.course = GateCourse::over_terrain(trigger.gates.iter().copied(), trigger.gate_radius, &world_gen);
+    trial.state = TrialState::Armed;
+}
+
+pub fn receive_time_trial_result(trigger: On<TimeTrialResultReceived>, mut trial: ResMut<TimeTrial>) {
+    trial.state = TrialState::Idle;
+    trial.course = GateCourse::default();
+    trial.status = Some(trigger.message.clone());
+    if trigger.accepted {
+        trial.show_leaderboard = true;
+    }
+}
+
+pub fn receive_leaderboard(trigger: On<LeaderboardReceived>, mut trial: ResMut<TimeTrial>) {
+    trial.leaderboard = trigger.0.clone();
+}
+
+/// A respawn teleports the aircraft, which the server would reject anyway
+pub fn abort_time_trial_on_respawn(_trigger: On<RespawnAircraft>, mut trial: ResMut<TimeTrial>) {
+    if matches!(trial.state, TrialState::Armed | TrialState::Running) {
+        trial.abort("Run aborted: aircraft respawned");
+    }
+}
+
+pub fn reset_time_trial(_trigger: On<Disconnected>, mut trial: ResMut<TimeTrial>) {
+    *trial = TimeTrial {
+        show_leaderboard: trial.show_leaderboard,
+        ..default()
+    };
+}
+
+/// Time gates at the closest approach of the aircraft, mark the course and submit the finish
+pub fn update_time_trial(
+    time: Res<Time>,
+    mut trial: ResMut<TimeTrial>,
+    client: Option<Res<NetworkClient>>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    mut gizmos: Gizmos,
+) {
+    if !matches!(trial.state, TrialState::Armed | TrialState::Running) {
+        return;
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        trial.abort("Run aborted: crashed");
+        return;
+    }
+
+    if let Some(run_time) = trial.course.advance(time.delta_secs(), transform.translation) {
+        match client.as_deref().filter(|client| client.connected) {
+            Some(client) => {
+                client.send(ClientMessage::FinishTimeTrial { time: run_time });
+                trial.state = TrialState::Submitted;
+                trial.status = Some(format!("Finished in {:.2}s, verifying...", run_time));
+            }
+            None => trial.abort("Run aborted: disconnected"),
+        }
+        trial.course = GateCourse::default();
+        return;
+    }
+    if trial.course.started() {
+        trial.state = TrialState::Running;
+    }
+    trial.course.draw(&mut gizmos, transform.translation);
+}
+
+pub fn format_run_time(seconds: f32) -> String {
+    format!("{}:{:05.2}", (seconds / 60.0) as u32, seconds % 60.0)
+}
+
+/// Run timer and leaderboard
+pub fn time_trial_hud(
+    mut contexts: EguiContexts,
+    trial: Res<TimeTrial>,
+    palette: Res<HudPalette>,
+    client: Option<Res<NetworkClient>>,
+) -> Result<(), BevyError> {
+    let connected = client.is_some_and(|client| client.connected);
+    if !connected || (trial.state == TrialState::Idle && !trial.show_leaderboard && trial.status.is_none()) {
+        return Ok(());
+    }
+
+    egui::Window::new("Time Trial")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 460.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("TIME TRIAL").size(12.0));
+            match trial.state {
+                TrialState::WaitingForCourse => {
+                    ui.label("Fetching course...");
+                }
+                TrialState::Armed => {
+                    ui.label(format!("Fly through the green gate to start ({} gates)", trial.course.gates.len()));
+                }
+                TrialState::Running => {
+                    ui.label(egui::RichText::new(format_run_time(trial.course.elapsed().unwrap_or(0.0))).size(22.0).monospace());
+                    ui.label(format!("Gate {}/{}", trial.course.gates_passed() + 1, trial.course.gates.len()));
+                }
+                TrialState::Idle | TrialState::Submitted => {}
+            }
+            if let Some(status) = &trial.status {
+                ui.label(status);
+            }
+
+            if trial.show_leaderboard {
+                ui.separator();
+                ui.label(egui::RichText::new("LEADERBOARD").size(12.0));
+                if trial.leaderboard.is_empty() {
+                    ui.label("No times yet");
+                }
+                egui::Grid::new("leaderboard").num_columns(4).show(ui, |ui| {
+                    for (rank, entry) in trial.leaderboard.iter().enumerate() {
+                        ui.label(format!("{}.", rank + 1));
+                        ui.label(&entry.name);
+                        ui.label(format!("{:?}", entry.plane_type));
+                        ui.label(egui::RichText::new(format_run_time(entry.time)).monospace());
+                        ui.end_row();
+                    }
+                });
+            }
+        });
+
+    Ok(())
+}
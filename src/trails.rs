@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::visibility::NoFrustumCulling,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+
+use crate::controls::{Aircraft, MainCamera};
+use crate::network::{PlaneType, RemotePlayer};
+
+// Smoke emitted from the wingtips when toggled on
+const SMOKE_LIFETIME: f32 = 8.0;
+const SMOKE_WIDTH: f32 = 4.0;
+const SMOKE_SPREAD_RATE: f32 = 1.5;
+
+// Condensation trails emitted automatically at altitude
+const CONTRAIL_LIFETIME: f32 = 20.0;
+const CONTRAIL_WIDTH: f32 = 3.0;
+const CONTRAIL_SPREAD_RATE: f32 = 0.6;
+const CONTRAIL_MIN_ALTITUDE: f32 = 6000.0;
+const CONTRAIL_FADE_IN_HEIGHT: f32 = 1000.0;
+const CONTRAIL_ENGINE_SPAN_RATIO: f32 = 0.3;
+
+const TRAIL_SAMPLE_INTERVAL: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailKind {
+    Smoke,
+    Contrail,
+}
+
+/// Per-aircraft wingtip smoke state, present on both the local and remote aircraft
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SmokeTrail {
+    pub enabled: bool,
+    pub color: [u8; 3],
+}
+
+impl SmokeTrail {
+    /// Network representation: `None` when the smoke is off
+    pub fn to_network(self) -> Option<[u8; 3]> {
+        self.enabled.then_some(self.color)
+    }
+
+    pub fn from_network(smoke: Option<[u8; 3]>) -> Self {
+        match smoke {
+            Some(color) => Self { enabled: true, color },
+            None => Self::default(),
+        }
+    }
+}
+
+/// Local player's smoke settings, edited from the settings panel or toggled with V
+#[derive(Resource)]
+pub struct TrailSettings {
+    pub smoke_enabled: bool,
+    pub smoke_color: [u8; 3],
+    pub contrails_enabled: bool,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        Self {
+            smoke_enabled: false,
+            smoke_color: [255, 255, 255],
+            contrails_enabled: true,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct TrailAssets {
+    pub material: Handle<StandardMaterial>,
+}
+
+struct TrailSample {
+    position: Vec3,
+    age: f32,
+    strength: f32,
+    connected: bool,
+}
+
+/// A world-space ribbon that follows one emitter point on its owner aircraft
+#[derive(Component)]
+pub struct Trail {
+    owner: Entity,
+    kind: TrailKind,
+    span_ratio: f32,
+    samples: VecDeque<TrailSample>,
+    since_last_sample: f32,
+    emitting: bool,
+}
+
+/// Half the wingspan in world units, used to place the wingtip emitters
+pub fn half_span(plane_type: PlaneType) -> f32 {
+    match plane_type {
+        PlaneType::Light | PlaneType::Glider => 6.0,
+        PlaneType::Jet => 15.0,
+    }
+}
+
+pub fn setup_trails(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(TrailAssets {
+        material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            double_sided: true,
+            ..default()
+        }),
+    });
+}
+
+/// Spawn smoke and contrail ribbons for every newly added aircraft, local or remote
+pub fn spawn_trails_for_aircraft(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    trail_assets: Res<TrailAssets>,
+    new_aircraft: Query<(Entity, Has<SmokeTrail>), Or<(Added<Aircraft>, Added<RemotePlayer>)>>,
+) {
+    for (owner, has_smoke) in new_aircraft.iter() {
+        if !has_smoke {
+            commands.entity(owner).try_insert(SmokeTrail::default());
+        }
+
+        let emitters = [
+            (TrailKind::Smoke, -1.0),
+            (TrailKind::Smoke, 1.0),
+            (TrailKind::Contrail, -CONTRAIL_ENGINE_SPAN_RATIO),
+            (TrailKind::Contrail, CONTRAIL_ENGINE_SPAN_RATIO),
+        ];
+
+        for (kind, span_ratio) in emitters {
+            let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+            commands.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(trail_assets.material.clone()),
+                Transform::IDENTITY,
+                Trail {
+                    owner,
+                    kind,
+                    span_ratio,
+                    samples: VecDeque::new(),
+                    since_last_sample: 0.0,
+                    emitting: false,
+                },
+                NoFrustumCulling,
+                bevy::light::NotShadowCaster,
+            ));
+        }
+    }
+}
+
+/// Apply the V toggle and settings panel values to the local aircraft
+pub fn update_local_smoke(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<TrailSettings>,
+    mut aircraft_query: Query<&mut SmokeTrail, With<Aircraft>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        settings.smoke_enabled = !settings.smoke_enabled;
+        info!("Smoke {}", if settings.smoke_enabled { "on" } else { "off" });
+    }
+
+    let Ok(mut smoke) = aircraft_query.single_mut() else { return };
+    let desired = SmokeTrail {
+        enabled: settings.smoke_enabled,
+        color: settings.smoke_color,
+    };
+    if *smoke != desired {
+        *smoke = desired;
+    }
+}
+
+/// Record new trail samples, age old ones, and rebuild each ribbon mesh
+pub fn update_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<TrailSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trails: Query<(Entity, &mut Trail, &Mesh3d)>,
+    local_aircraft: Query<(&GlobalTransform, &Aircraft, &SmokeTrail)>,
+    remote_aircraft: Query<(&GlobalTransform, &RemotePlayer, &SmokeTrail), Without<Aircraft>>,
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+) {
+    let dt = time.delta_secs();
+    let camera_pos = camera.single().map(|t| t.translation()).unwrap_or_default();
+
+    for (trail_entity, mut trail, mesh_handle) in trails.iter_mut() {
+        let owner_state = if let Ok((transform, aircraft, smoke)) = local_aircraft.get(trail.owner) {
+            Some((*transform, aircraft.plane_type, *smoke, aircraft.crashed))
+        } else if let Ok((transform, remote, smoke)) = remote_aircraft.get(trail.owner) {
+            Some((*transform, remote.plane_type, *smoke, false))
+        } else {
+            None
+        };
+
+        let Some((owner_transform, plane_type, smoke, crashed)) = owner_state else {
+            commands.entity(trail_entity).despawn();
+            continue;
+        };
+
+        let (lifetime, _, _) = trail_style(trail.kind);
+        for sample in trail.samples.iter_mut() {
+            sample.age += dt;
+        }
+        while trail.samples.front().is_some_and(|s| s.age > lifetime) {
+            trail.samples.pop_front();
+        }
+
+        let owner_pos = owner_transform.translation();
+        let strength = if crashed {
+            0.0
+        } else {
+            match trail.kind {
+                TrailKind::Smoke => if smoke.enabled { 1.0 } else { 0.0 },
+                TrailKind::Contrail if settings.contrails_enabled => {
+                    ((owner_pos.y - CONTRAIL_MIN_ALTITUDE) / CONTRAIL_FADE_IN_HEIGHT).clamp(0.0, 1.0)
+                }
+                TrailKind::Contrail => 0.0,
+            }
+        };
+
+        let emitter_pos = owner_pos
+            + owner_transform.right().as_vec3() * half_span(plane_type) * trail.span_ratio;
+
+        if strength > 0.0 {
+            trail.since_last_sample += dt;
+            if !trail.emitting || trail.since_last_sample >= TRAIL_SAMPLE_INTERVAL {
+                let connected = trail.emitting;
+                trail.samples.push_back(TrailSample { position: emitter_pos, age: 0.0, strength, connected });
+                trail.since_last_sample = 0.0;
+            } else if let Some(head) = trail.samples.back_mut() {
+                // Keep the newest point glued to the emitter between samples
+                head.position = emitter_pos;
+                head.strength = strength;
+            }
+            trail.emitting = true;
+        } else {
+            trail.emitting = false;
+        }
+
+        let color = match trail.kind {
+            TrailKind::Smoke => Color::srgb_u8(smoke.color[0], smoke.color[1], smoke.color[2]),
+            TrailKind::Contrail => Color::WHITE,
+        };
+
+        if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+            build_ribbon_mesh(mesh, &trail, camera_pos, color);
+        }
+    }
+}
+
+/// (lifetime, base width, spread rate) for each kind of trail
+fn trail_style(kind: TrailKind) -> (f32, f32, f32) {
+    match kind {
+        TrailKind::Smoke => (SMOKE_LIFETIME, SMOKE_WIDTH, SMOKE_SPREAD_RATE),
+        TrailKind::Contrail => (CONTRAIL_LIFETIME, CONTRAIL_WIDTH, CONTRAIL_SPREAD_RATE),
+    }
+}
+
+/// Build a camera-facing ribbon through the samples, fading out with age
+fn build_ribbon_mesh(mesh: &mut Mesh, trail: &Trail, camera_pos: Vec3, color: Color) {
+    let (lifetime, width, spread) = trail_style(trail.kind);
+    let base = color.to_linear();
+
+    let count = trail.samples.len();
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(count * 2);
+    let mut colors: Vec<[f32; 4]> = Vec::with_capacity(count * 2);
+    let mut normals: Vec<[f32; 3]> = Vec::with_capacity(count * 2);
+    let mut indices: Vec<u32> = Vec::with_capacity(count * 6);
+
+    for (i, sample) in trail.samples.iter().enumerate() {
+        let prev = if i > 0 { trail.samples[i - 1].position } else { sample.position };
+        let next = trail.samples.get(i + 1).map_or(sample.position, |s| s.position);
+        let along = (next - prev).normalize_or_zero();
+        let to_camera = (camera_pos - sample.position).normalize_or_zero();
+        let side = along.cross(to_camera).normalize_or_zero();
+
+        let half_width = 0.5 * width * (1.0 + sample.age * spread);
+        let alpha = (1.0 - sample.age / lifetime).clamp(0.0, 1.0) * sample.strength;
+        let vertex_color = [base.red, base.green, base.blue, alpha * 0.8];
+
+        positions.push((sample.position - side * half_width).to_array());
+        positions.push((sample.position + side * half_width).to_array());
+        colors.push(vertex_color);
+        colors.push(vertex_color);
+        normals.push(to_camera.to_array());
+        normals.push(to_camera.to_array());
+
+        if i > 0 && sample.connected {
+            let a = (i as u32 - 1) * 2;
+            let b = i as u32 * 2;
+            indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
@@ -0,0 +1,309 @@
+use flight_sim_protocol::codec;
+use flight_sim_protocol::messages::{TRANSPORT_PLAIN, TRANSPORT_TLS};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+use std::sync::Arc;
+
+use crate::network::{ClientMessage, ServerMessage, TOKIO_RUNTIME};
+
+/// A live link to a server as the game sees it: messages out, messages in, and word when it drops.
+/// Sockets, framing and encryption belong to the backend, so game code never changes with it
+pub trait NetTransport: Send + Sync {
+    /// Queue a message for the server. A failed send shows up as a lost connection
+    fn send(&self, message: ClientMessage);
+    /// The next message from the server, if one has arrived
+    fn try_recv(&self) -> Option<ServerMessage>;
+    /// Whether the connection has dropped since this was last asked
+    fn connection_lost(&self) -> bool;
+}
+
+/// Open a transport for `address`. A `scheme://` prefix picks the backend; plain `host:port` means TCP
+pub async fn connect(address: &str, accept_self_signed: bool) -> Result<Box<dyn NetTransport>, String> {
+    match address.split_once("://") {
+        None => TcpTransport::connect(address, accept_self_signed).await,
+        Some(("tcp", address)) => TcpTransport::connect(address, accept_self_signed).await,
+        Some((scheme, _)) => Err(format!("Unsupported transport {}://", scheme)),
+    }
+}
+
+/// Length-prefixed bincode over TCP, upgraded to TLS when the server asks for it
+struct TcpTransport {
+    send_tx: mpsc::UnboundedSender<ClientMessage>,
+    /// Fed by the read task; systems drain it without touching the Tokio runtime
+    recv_rx: crossbeam_channel::Receiver<ServerMessage>,
+    disconnect_rx: crossbeam_channel::Receiver<()>,
+}
+
+impl NetTransport for TcpTransport {
+    fn send(&self, message: ClientMessage) {
+        let _ = self.send_tx.send(message);
+    }
+
+    fn try_recv(&self) -> Option<ServerMessage> {
+        self.recv_rx.try_recv().ok()
+    }
+
+    fn connection_lost(&self) -> bool {
+        self.disconnect_rx.try_recv().is_ok()
+    }
+}
+
+impl TcpTransport {
+    async fn connect(address: &str, accept_self_signed: bool) -> Result<Box<dyn NetTransport>, String> {
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|e| format!("Failed to connect: {}", e))?;
+        let _ = stream.set_nodelay(true);
+
+        let stream = negotiate_transport(address, stream, accept_self_signed).await?;
+        let (read_half, write_half) = tokio::io::split(stream);
+
+        let (send_tx, mut send_rx) = mpsc::unbounded_channel::<ClientMessage>();
+        let (recv_tx, recv_rx) = crossbeam_channel::unbounded::<ServerMessage>();
+        let (disconnect_tx, disconnect_rx) = crossbeam_channel::unbounded::<()>();
+
+        let disconnect_tx_write = disconnect_tx.clone();
+        TOKIO_RUNTIME.spawn(async move {
+            let mut write_half = write_half;
+            while let Some(message) = send_rx.recv().await {
+                //println!("Client sending message: {:?}", message);
+                match codec::write_message(&mut write_half, &message).await {
+                    Ok(()) => {}
+                    Err(e) if e.is_recoverable() => eprintln!("Dropped a message: {}", e),
+                    Err(e) => {
+                        eprintln!("Failed to send message: {}", e);
+                        let _ = disconnect_tx_write.send(());
+                        break;
+                    }
+                }
+            }
+            println!("Client write task ended");
+        });
+
+        TOKIO_RUNTIME.spawn(async move {
+            let mut read_half = read_half;
+            println!("Client read task started");
+            loop {
+                match codec::read_message::<ServerMessage>(&mut read_half).await {
+                    Ok(Some(message)) => {
+                        //println!("Client received message: {:?}", message);
+                        if recv_tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {
+                        println!("Server disconnected");
+                        let _ = disconnect_tx.send(());
+                        break;
+                    }
+                    Err(e) if e.is_recoverable() => {
+                        eprintln!("Skipped a message from the server: {}", e);
+                    }
+                    Err(e) => {
+                        eprintln!("Error receiving message: {}", e);
+                        let _ = disconnect_tx.send(());
+                        break;
+                    }
+                }
+            }
+            println!("Client read task ended");
+        });
+
+        Ok(Box::new(TcpTransport { send_tx, recv_rx, disconnect_rx }))
+    }
+}
+
+trait AsyncStream: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncStream for T {}
+
+/// Accepts any server certificate while still checking handshake signatures.
+/// This keeps traffic encrypted against passive sniffing when the server uses a
+/// self-signed certificate, but does not protect against an active man-in-the-middle.
+#[derive(Debug)]
+struct AcceptSelfSigned(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptSelfSigned {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Read the server's transport byte and wrap the socket in TLS if it asks for it
+async fn negotiate_transport(
+    address: &str,
+    mut stream: TcpStream,
+    accept_self_signed: bool,
+) -> Result<Box<dyn AsyncStream>, String> {
+    let mut transport = [0u8; 1];
+    stream.read_exact(&mut transport)
+        .await
+        .map_err(|e| format!("Handshake failed: {}", e))?;
+
+    match transport[0] {
+        TRANSPORT_PLAIN => Ok(Box::new(stream)),
+        TRANSPORT_TLS => {
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+                .with_safe_default_protocol_versions()
+                .map_err(|e| e.to_string())?;
+
+            let config = if accept_self_signed {
+                builder
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(AcceptSelfSigned(provider)))
+                    .with_no_client_auth()
+            } else {
+                let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                builder.with_root_certificates(roots).with_no_client_auth()
+            };
+
+            let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+            let server_name = ServerName::try_from(host.to_string())
+                .map_err(|e| format!("Invalid server name {}: {}", host, e))?;
+
+            let tls_stream = TlsConnector::from(Arc::new(config))
+                .connect(server_name, stream)
+                .await
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+            println!("🔒 Connection encrypted with TLS");
+            Ok(Box::new(tls_stream))
+        }
+        other => Err(format!("Unknown transport mode {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{ClientRole, PlaneType};
+    use flight_sim_server::{tls, GameServer};
+    use std::time::{Duration, Instant};
+
+    /// Long enough for a loaded CI machine, short enough that a missing message fails the test quickly
+    const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// A real server on an ephemeral port, encrypted when `tls_mode` asks for it
+    fn start_server(tls_mode: tls::TlsMode) -> String {
+        let listener = TOKIO_RUNTIME.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let acceptor = tls::build_acceptor(&tls_mode).unwrap();
+        TOKIO_RUNTIME.spawn(flight_sim_server::serve(Arc::new(GameServer::new(None)), listener, acceptor));
+        address
+    }
+
+    /// Poll the transport as the game does until a message `matches` picks out arrives
+    fn recv_matching<T>(transport: &dyn NetTransport, mut matches: impl FnMut(ServerMessage) -> Option<T>) -> T {
+        let deadline = Instant::now() + RECEIVE_TIMEOUT;
+        while Instant::now() < deadline {
+            match transport.try_recv() {
+                Some(message) => {
+                    if let Some(found) = matches(message) {
+                        return found;
+                    }
+                }
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        panic!("timed out waiting for the server");
+    }
+
+    fn join(address: &str, name: &str, accept_self_signed: bool) -> (Box<dyn NetTransport>, u32) {
+        let transport = TOKIO_RUNTIME.block_on(connect(address, accept_self_signed)).unwrap();
+        transport.send(ClientMessage::Join { name: name.to_string(), role: ClientRole::Pilot });
+        let id = recv_matching(&*transport, |message| match message {
+            ServerMessage::Welcome { your_id, .. } => Some(your_id),
+            _ => None,
+        });
+        (transport, id)
+    }
+
+    #[test]
+    fn plain_tcp_carries_a_session() {
+        let address = start_server(tls::TlsMode::Disabled);
+        let (first, _) = join(&address, "First", false);
+        let (second, second_id) = join(&format!("tcp://{}", address), "Second", false);
+        // Pilots are announced with their first update
+        second.send(ClientMessage::UpdatePosition {
+            name: "Second".to_string(),
+            position: [0.0, 500.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            plane_type: PlaneType::Light,
+            smoke: None,
+        });
+        let joined = recv_matching(&*first, |message| match message {
+            ServerMessage::PlayerJoined { player } => Some(player.id),
+            _ => None,
+        });
+        assert_eq!(joined, second_id);
+
+        second.send(ClientMessage::Disconnect);
+        let left = recv_matching(&*first, |message| match message {
+            ServerMessage::PlayerLeft { id } => Some(id),
+            _ => None,
+        });
+        assert_eq!(left, second_id);
+        assert!(!first.connection_lost());
+    }
+
+    #[test]
+    fn tls_is_negotiated_when_the_server_asks_for_it() {
+        let address = start_server(tls::TlsMode::SelfSigned);
+        let (transport, _) = join(&address, "Encrypted", true);
+        assert!(!transport.connection_lost());
+    }
+
+    #[test]
+    fn self_signed_certificates_are_refused_unless_accepted() {
+        let address = start_server(tls::TlsMode::SelfSigned);
+        let Err(error) = TOKIO_RUNTIME.block_on(connect(&address, false)) else {
+            panic!("connected to a server with an unverifiable certificate");
+        };
+        assert!(error.starts_with("TLS handshake failed"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_schemes_are_refused() {
+        let Err(error) = TOKIO_RUNTIME.block_on(connect("udp://127.0.0.1:7878", false)) else {
+            panic!("connected over an unsupported transport");
+        };
+        assert_eq!(error, "Unsupported transport udp://");
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use bevy_egui::egui;
+use serde::Deserialize;
+
+use crate::consts::TerrainStop;
+use crate::controls::Wind;
+use crate::hud::HudPalette;
+use crate::world_generation::{Chunk, ChunkManager, TerrainPalette};
+use crate::RenderSettings;
+
+/// Live-editable sim tuning; the file under `assets/` mirrors the built-in defaults
+const TUNING_PATH: &str = "tuning/sim.tuning.ron";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WindTuning {
+    pub wind_evolution_speed: f64,
+    pub min_wind_speed: f32,
+    pub max_wind_speed: f32,
+    pub macro_wind_freq: f64,
+    pub weather_evolution_rate: f64,
+    pub max_angle_shift_degrees: f32,
+    pub turbulence_intensity: f32,
+    pub turbulence_frequency: f32,
+    pub gust_frequency_multiplier: f64,
+    pub thermal_strength: f32,
+    pub ridge_lift_strength: f32,
+}
+
+/// Terrain LOD table as (distance in chunks, subdivisions), nearest first
+#[derive(Debug, Clone, Deserialize)]
+pub struct LodTuning {
+    /// Must have exactly as many entries as `ChunkManager::lod_levels`
+    pub levels: Vec<(f32, u32)>,
+    pub tree_render_distance: f32,
+}
+
+/// A palette band; colours are sRGB 0-1
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainStopTuning {
+    pub height: f32,
+    pub color: (f32, f32, f32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainTuning {
+    pub grasslands: Vec<TerrainStopTuning>,
+    pub desert: Vec<TerrainStopTuning>,
+    pub taiga: Vec<TerrainStopTuning>,
+    pub forest: Vec<TerrainStopTuning>,
+    pub autumn: (f32, f32, f32),
+    pub summer_snow_line: f32,
+    pub winter_snow_line_drop: f32,
+}
+
+/// HUD colours as sRGB 0-255, with alpha on the window fills
+#[derive(Debug, Clone, Deserialize)]
+pub struct HudTuning {
+    pub window_fill: (u8, u8, u8, u8),
+    pub warning_fill: (u8, u8, u8, u8),
+    pub text: (u8, u8, u8),
+    pub horizon_sky: (u8, u8, u8),
+    pub horizon_ground: (u8, u8, u8),
+}
+
+/// Sim tuning loaded from `assets/tuning/sim.tuning.ron`
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct SimTuning {
+    pub wind: WindTuning,
+    pub lod: LodTuning,
+    pub terrain: TerrainTuning,
+    pub hud: HudTuning,
+}
+
+fn terrain_stops(stops: &[TerrainStopTuning]) -> Vec<TerrainStop> {
+    stops
+        .iter()
+        .map(|stop| TerrainStop {
+            height: stop.height,
+            color: Color::srgb(stop.color.0, stop.color.1, stop.color.2),
+        })
+        .collect()
+}
+
+impl SimTuning {
+    fn apply_wind(&self, wind: &mut Wind) {
+        let tuning = &self.wind;
+        wind.wind_evolution_speed = tuning.wind_evolution_speed;
+        wind.min_wind_speed = tuning.min_wind_speed;
+        wind.max_wind_speed = tuning.max_wind_speed;
+        wind.macro_wind_freq = tuning.macro_wind_freq;
+        wind.weather_evolution_rate = tuning.weather_evolution_rate;
+        wind.max_angle_shift = tuning.max_angle_shift_degrees.to_radians();
+        wind.turbulence_intensity = tuning.turbulence_intensity;
+        wind.turbulence_frequency = tuning.turbulence_frequency;
+        wind.gust_frequency_multiplier = tuning.gust_frequency_multiplier;
+        wind.thermal_strength = tuning.thermal_strength;
+        wind.ridge_lift_strength = tuning.ridge_lift_strength;
+    }
+
+    /// The terrain bands, as long as every biome has some and their heights strictly increase, since
+    /// colouring looks up the first stop and divides by the gap between neighbours
+    fn terrain_palette(&self) -> Result<TerrainPalette, String> {
+        let terrain = &self.terrain;
+        let biomes = [
+            ("grasslands", &terrain.grasslands),
+            ("desert", &terrain.desert),
+            ("taiga", &terrain.taiga),
+            ("forest", &terrain.forest),
+        ];
+        for (name, stops) in biomes {
+            if stops.is_empty() {
+                return Err(format!("{} has no stops", name));
+            }
+            if !stops.windows(2).all(|pair| pair[0].height < pair[1].height) {
+                return Err(format!("{} heights must strictly increase", name));
+            }
+        }
+        Ok(TerrainPalette {
+            grasslands: terrain_stops(&terrain.grasslands),
+            desert: terrain_stops(&terrain.desert),
+            taiga: terrain_stops(&terrain.taiga),
+            forest: terrain_stops(&terrain.forest),
+            autumn: Color::srgb(terrain.autumn.0, terrain.autumn.1, terrain.autumn.2),
+            summer_snow_line: terrain.summer_snow_line,
+            winter_snow_line_drop: terrain.winter_snow_line_drop,
+        })
+    }
+
+    fn hud_palette(&self) -> HudPalette {
+        let hud = &self.hud;
+        let rgba = |(r, g, b, a): (u8, u8, u8, u8)| egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+        let rgb = |(r, g, b): (u8, u8, u8)| egui::Color32::from_rgb(r, g, b);
+        HudPalette {
+            window_fill: rgba(hud.window_fill),
+            warning_fill: rgba(hud.warning_fill),
+            text: rgb(hud.text),
+            horizon_sky: rgb(hud.horizon_sky),
+            horizon_ground: rgb(hud.horizon_ground),
+        }
+    }
+}
+
+#[derive(Default, TypePath)]
+pub struct SimTuningLoader;
+
+impl AssetLoader for SimTuningLoader {
+    type Asset = SimTuning;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<SimTuning, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+/// Keeps the tuning asset loaded so file edits are picked up
+#[derive(Resource)]
+pub struct SimTuningHandle(Handle<SimTuning>);
+
+pub fn load_sim_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SimTuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+/// Push the tuning file into the live resources whenever it loads or is saved
+pub fn apply_sim_tuning(
+    mut asset_events: MessageReader<AssetEvent<SimTuning>>,
+    handle: Res<SimTuningHandle>,
+    tunings: Res<Assets<SimTuning>>,
+    mut wind: ResMut<Wind>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut terrain_palette: ResMut<TerrainPalette>,
+    mut hud_palette: ResMut<HudPalette>,
+    mut render_settings: ResMut<RenderSettings>,
+    mut chunks: Query<&mut Chunk>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else { continue };
+        if *id != handle.0.id() {
+            continue;
+        }
+        let Some(tuning) = tunings.get(*id) else { continue };
+
+        tuning.apply_wind(&mut wind);
+        chunk_manager.tree_render_distance = tuning.lod.tree_render_distance;
+        match tuning.lod.levels.as_slice().try_into() {
+            Ok(levels) if chunk_manager.lod_levels != levels => {
+                chunk_manager.lod_levels = levels;
+                render_settings.just_updated = true;
+            }
+            Ok(_) => {}
+            Err(_) => warn!(
+                "Ignoring LOD table in {}: expected {} levels, found {}",
+                TUNING_PATH,
+                chunk_manager.lod_levels.len(),
+                tuning.lod.levels.len()
+            ),
+        }
+
+        match tuning.terrain_palette() {
+            Ok(palette) if *terrain_palette != palette => {
+                *terrain_palette = palette;
+                // Same trick as the season refresh: an impossible LOD regenerates every chunk
+                for mut chunk in chunks.iter_mut() {
+                    chunk.current_lod = u32::MAX;
+                }
+                render_settings.just_updated = true;
+            }
+            Ok(_) => {}
+            Err(error) => warn!("Ignoring terrain palette in {}: {}", TUNING_PATH, error),
+        }
+
+        hud_palette.set_if_neq(tuning.hud_palette());
+        info!("Applied sim tuning from {}", TUNING_PATH);
+    }
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{Aircraft, ControlMode};
+use crate::ditching::Ditching;
+use crate::engine::{Engine, EngineState};
+use crate::hud::HudPalette;
+
+/// Length of the upwind and downwind legs of the practice pattern, in world units
+const PATTERN_LENGTH: f32 = 9000.0;
+/// Spacing between the upwind and downwind legs
+const PATTERN_WIDTH: f32 = 5000.0;
+/// How close, across the ground, counts as reaching a pattern corner
+const PATTERN_CORNER_RADIUS: f32 = 600.0;
+/// Wings within this many degrees of level, and the nose within this of the horizon, count as level
+const LEVEL_TOLERANCE_DEG: f32 = 5.0;
+
+/// What a step is waiting for the pilot to do
+#[derive(Debug, Clone, Copy)]
+enum Goal {
+    /// Hold down a key, shown with its label
+    Key(KeyCode, &'static str),
+    /// Throttle at or above this fraction of the aircraft's maximum
+    ThrottleAbove(f32),
+    ThrottleBelow(f32),
+    /// Airspeed between these fractions of the aircraft's top speed
+    SpeedBetween(f32, f32),
+    /// Meters gained since the step began; negative to descend
+    Climb(f32),
+    /// Bank at least this many degrees either way
+    Bank(f32),
+    WingsLevel,
+    EngineRunning,
+    /// Reach this corner of the practice pattern
+    PatternCorner(usize),
+    /// Set down on the water and stay afloat
+    Ditched,
+}
+
+impl Goal {
+    fn describe(self, aircraft: &Aircraft, units: UnitSystem) -> String {
+        match self {
+            Goal::Key(_, label) => format!("Hold {}", label),
+            Goal::ThrottleAbove(fraction) => format!("Throttle above {:.0}% (= key)", fraction * 100.0),
+            Goal::ThrottleBelow(fraction) => format!("Throttle below {:.0}% (- key)", fraction * 100.0),
+            Goal::SpeedBetween(low, high) => format!(
+                "Airspeed {} to {}",
+                units.format_speed(world_units_to_meters(aircraft.max_speed * low)),
+                units.format_speed(world_units_to_meters(aircraft.max_speed * high)),
+            ),
+            Goal::Climb(meters) if meters >= 0.0 => format!("Climb {}", units.format_altitude(meters)),
+            Goal::Climb(meters) => format!("Descend {}", units.format_altitude(-meters)),
+            Goal::Bank(degrees) => format!("Bank at least {:.0}°", degrees),
+            Goal::WingsLevel => "Wings level, nose on the horizon".to_string(),
+            Goal::EngineRunning => "Engine running (I for magnetos, hold K to crank)".to_string(),
+            Goal::PatternCorner(_) => "Fly to the green ring".to_string(),
+            Goal::Ditched => "Touch down on the water and float".to_string(),
+        }
+    }
+}
+
+struct Step {
+    prompt: &'static str,
+    goal: Goal,
+    /// Seconds the goal must be held continuously; zero completes on the first frame it's met
+    hold_secs: f32,
+}
+
+const fn step(prompt: &'static str, goal: Goal, hold_secs: f32) -> Step {
+    Step { prompt, goal, hold_secs }
+}
+
+const BASICS: &[Step] = &[
+    step("Push the stick forward to lower the nose", Goal::Key(KeyCode::KeyW, "W"), 1.0),
+    step("Pull back to raise the nose", Goal::Key(KeyCode::KeyS, "S"), 1.0),
+    step("Roll to the left", Goal::Key(KeyCode::KeyA, "A"), 1.0),
+    step("Roll to the right", Goal::Key(KeyCode::KeyD, "D"), 1.0),
+    step("Yaw with the rudder", Goal::Key(KeyCode::KeyQ, "Q"), 0.5),
+    step("And the other way", Goal::Key(KeyCode::KeyE, "E"), 0.5),
+    step("Level off and let the aircraft settle", Goal::WingsLevel, 3.0),
+    step("Bank into a turn and hold it", Goal::Bank(30.0), 3.0),
+    step("Roll out and hold a cruise speed", Goal::SpeedBetween(0.4, 0.6), 10.0),
+];
+
+// There are no runways, so departures begin in the air and landings are made on water
+const DEPARTURE: &[Step] = &[
+    step("Make sure the engine is running", Goal::EngineRunning, 0.0),
+    step("Set full power for the climb", Goal::ThrottleAbove(0.95), 0.0),
+    step("Climb away", Goal::Climb(300.0), 0.0),
+    step("Level off at your new altitude", Goal::WingsLevel, 5.0),
+];
+
+const PATTERN: &[Step] = &[
+    step("Fly the upwind leg straight ahead", Goal::PatternCorner(0), 0.0),
+    step("Turn left onto the crosswind leg", Goal::PatternCorner(1), 0.0),
+    step("Turn left onto the downwind leg", Goal::PatternCorner(2), 0.0),
+    step("Turn left onto base and final, back to where you started", Goal::PatternCorner(3), 0.0),
+];
+
+const WATER_LANDING: &[Step] = &[
+    step("Bring the power back", Goal::ThrottleBelow(0.3), 0.0),
+    step("Start a gentle descent toward open water", Goal::Climb(-150.0), 0.0),
+    step("Slow to an approach speed", Goal::SpeedBetween(0.2, 0.35), 3.0),
+    step("Wings level, shallow descent, and touch down on the water", Goal::Ditched, 0.0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lesson {
+    Basics,
+    Departure,
+    Pattern,
+    WaterLanding,
+}
+
+impl Lesson {
+    pub const ALL: [Lesson; 4] = [Lesson::Basics, Lesson::Departure, Lesson::Pattern, Lesson::WaterLanding];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Lesson::Basics => "Basic Controls",
+            Lesson::Departure => "Departure",
+            Lesson::Pattern => "Traffic Pattern",
+            Lesson::WaterLanding => "Water Landing",
+        }
+    }
+
+    fn steps(self) -> &'static [Step] {
+        match self {
+            Lesson::Basics => BASICS,
+            Lesson::Departure => DEPARTURE,
+            Lesson::Pattern => PATTERN,
+            Lesson::WaterLanding => WATER_LANDING,
+        }
+    }
+}
+
+/// The flight school lesson in progress, if any
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    lesson: Option<Lesson>,
+    step: usize,
+    /// Seconds the current goal has been held
+    held: f32,
+    /// Where the aircraft was when the current step began
+    step_start: Option<Vec3>,
+    /// Corners of the practice pattern, laid out from where the lesson began
+    pattern: Vec<Vec3>,
+    pub status: Option<String>,
+}
+
+impl Tutorial {
+    pub fn lesson(&self) -> Option<Lesson> {
+        self.lesson
+    }
+
+    pub fn start(&mut self, lesson: Lesson) {
+        *self = Tutorial {
+            lesson: Some(lesson),
+            ..default()
+        };
+        info!("Flight school: {}", lesson.title());
+    }
+
+    pub fn stop(&mut self) {
+        self.lesson = None;
+        self.status = None;
+    }
+
+    fn current_step(&self) -> Option<&'static Step> {
+        self.lesson.and_then(|lesson| lesson.steps().get(self.step))
+    }
+
+    fn advance(&mut self, lesson: Lesson) {
+        self.step += 1;
+        self.held = 0.0;
+        self.step_start = None;
+        if self.step >= lesson.steps().len() {
+            self.lesson = None;
+            self.pattern.clear();
+            self.status = Some(format!("Lesson complete: {}", lesson.title()));
+            info!("Flight school: completed {}", lesson.title());
+        }
+    }
+}
+
+/// Rectangle of left turns from `origin`, flown from the heading the aircraft had there
+fn lay_out_pattern(origin: Vec3, forward: Vec3) -> Vec<Vec3> {
+    let forward = forward.with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let left = Vec3::Y.cross(forward);
+    let upwind_end = origin + forward * PATTERN_LENGTH;
+    vec![
+        upwind_end,
+        upwind_end + left * PATTERN_WIDTH,
+        origin + left * PATTERN_WIDTH,
+        origin,
+    ]
+}
+
+fn goal_met(
+    goal: Goal,
+    keyboard: &ButtonInput<KeyCode>,
+    transform: &Transform,
+    aircraft: &Aircraft,
+    engine: &Engine,
+    ditching: &Ditching,
+    step_start: Vec3,
+    pattern: &[Vec3],
+) -> bool {
+    let forward = transform.forward().as_vec3();
+    let roll = f32::atan2(transform.right().y, transform.up().y).to_degrees();
+    let pitch = forward.y.clamp(-1.0, 1.0).asin().to_degrees();
+    let throttle = if aircraft.max_throttle > 0.0 { aircraft.throttle / aircraft.max_throttle } else { 0.0 };
+    match goal {
+        Goal::Key(key, _) => keyboard.pressed(key),
+        Goal::ThrottleAbove(fraction) => throttle >= fraction,
+        Goal::ThrottleBelow(fraction) => throttle <= fraction,
+        Goal::SpeedBetween(low, high) => (aircraft.max_speed * low..=aircraft.max_speed * high).contains(&aircraft.speed),
+        Goal::Climb(meters) => {
+            let gained = world_units_to_meters(transform.translation.y - step_start.y);
+            if meters >= 0.0 { gained >= meters } else { gained <= meters }
+        }
+        Goal::Bank(degrees) => roll.abs() >= degrees,
+        Goal::WingsLevel => roll.abs() <= LEVEL_TOLERANCE_DEG && pitch.abs() <= LEVEL_TOLERANCE_DEG,
+        Goal::EngineRunning => engine.state == EngineState::Running,
+        Goal::PatternCorner(index) => pattern.get(index).is_some_and(|&corner| {
+            (transform.translation - corner).with_y(0.0).length() <= PATTERN_CORNER_RADIUS
+        }),
+        Goal::Ditched => ditching.float.is_some(),
+    }
+}
+
+/// Check the current step's goal, move through the lesson and mark the pattern
+pub fn update_tutorial(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    control_mode: Res<ControlMode>,
+    engine: Res<Engine>,
+    ditching: Res<Ditching>,
+    mut tutorial: ResMut<Tutorial>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(lesson) = tutorial.lesson else { return };
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return };
+    if aircraft.crashed {
+        tutorial.lesson = None;
+        tutorial.pattern.clear();
+        tutorial.status = Some(format!("Lesson failed: crashed during {}", lesson.title()));
+        return;
+    }
+    if lesson == Lesson::Pattern && tutorial.pattern.is_empty() {
+        tutorial.pattern = lay_out_pattern(transform.translation, transform.forward().as_vec3());
+    }
+
+    // Landing on the water pauses the physics, and that's the lesson's last goal
+    let Some(step) = tutorial.current_step() else { return };
+    if !control_mode.physics_paused || matches!(step.goal, Goal::Ditched) {
+        let step_start = *tutorial.step_start.get_or_insert(transform.translation);
+        if goal_met(step.goal, &keyboard, transform, aircraft, &engine, &ditching, step_start, &tutorial.pattern) {
+            tutorial.held += time.delta_secs();
+            if tutorial.held >= step.hold_secs {
+                tutorial.advance(lesson);
+            }
+        } else {
+            tutorial.held = 0.0;
+        }
+    }
+
+    let next_corner = match tutorial.current_step().map(|step| step.goal) {
+        Some(Goal::PatternCorner(index)) => index,
+        _ => return,
+    };
+    let up = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+    let mut previous = tutorial.pattern.last().copied();
+    for (index, &corner) in tutorial.pattern.iter().enumerate() {
+        let color = if index == next_corner { Color::srgb(0.1, 1.0, 0.3) } else { Color::srgba(1.0, 1.0, 1.0, 0.4) };
+        gizmos.circle(Isometry3d::new(corner, up), PATTERN_CORNER_RADIUS, color);
+        if let Some(previous) = previous {
+            gizmos.line(previous, corner, Color::srgba(1.0, 1.0, 1.0, 0.2));
+        }
+        previous = Some(corner);
+    }
+    if let Some(&corner) = tutorial.pattern.get(next_corner) {
+        gizmos.line(transform.translation, corner, Color::srgba(0.1, 1.0, 0.3, 0.3));
+    }
+}
+
+/// The current prompt, what it's waiting for, and how long is left to hold it
+pub fn tutorial_hud(
+    mut contexts: EguiContexts,
+    mut tutorial: ResMut<Tutorial>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<&Aircraft>,
+) -> Result<(), BevyError> {
+    if tutorial.lesson.is_none() && tutorial.status.is_none() {
+        return Ok(());
+    }
+    let Ok(aircraft) = aircraft_query.single() else { return Ok(()) };
+
+    // Under the heading window and the home, tag and carrier readouts stacked below it
+    egui::Window::new("Flight School")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 420.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("FLIGHT SCHOOL").size(12.0));
+            if let (Some(lesson), Some(step)) = (tutorial.lesson, tutorial.current_step()) {
+                ui.label(format!("{} · step {}/{}", lesson.title(), tutorial.step + 1, lesson.steps().len()));
+                ui.label(egui::RichText::new(step.prompt).size(18.0).strong());
+                ui.label(step.goal.describe(aircraft, *units));
+                if step.hold_secs > 0.0 {
+                    let fraction = (tutorial.held / step.hold_secs).clamp(0.0, 1.0);
+                    ui.add(egui::ProgressBar::new(fraction).desired_width(240.0).text(format!("{:.0}s", step.hold_secs)));
+                }
+                if ui.small_button("Quit lesson").clicked() {
+                    tutorial.stop();
+                }
+            } else if let Some(status) = tutorial.status.clone() {
+                ui.label(status);
+                if ui.small_button("Dismiss").clicked() {
+                    tutorial.status = None;
+                }
+            }
+        });
+
+    Ok(())
+}
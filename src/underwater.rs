@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::controls::MainCamera;
+use crate::day_cycle::{DayNightCycle, Sun};
+
+/// Fog colour just under the surface and far down, before the daylight dims it
+const SHALLOW_WATER_COLOR: Vec3 = Vec3::new(0.1, 0.45, 0.45);
+const DEEP_WATER_COLOR: Vec3 = Vec3::new(0.02, 0.12, 0.2);
+/// Depth over which the water shades from shallow to deep
+const COLOR_DEPTH: f32 = 600.0;
+/// Visibility is tens of metres under water, against tens of kilometres in air
+const UNDERWATER_FOG_DENSITY: f32 = 0.0012;
+/// Depth, in world units, over which sunlight falls to about a third
+const LIGHT_ATTENUATION_DEPTH: f32 = 250.0;
+/// How much the audio low-pass closes just under the surface and at full depth
+const SURFACE_MUFFLE: f32 = 0.7;
+const MUFFLE_DEPTH: f32 = 500.0;
+
+/// Fraction of the light at the surface that reaches `depth` world units below it
+pub fn light_through_water(depth: f32) -> f32 {
+    (-depth.max(0.0) / LIGHT_ATTENUATION_DEPTH).exp()
+}
+
+/// Whether the main camera is below the water, and how the scene should sound and light down there
+#[derive(Resource, Default)]
+pub struct Underwater {
+    pub submerged: bool,
+    /// World units below sea level
+    pub depth: f32,
+    /// 0..1 fraction of daylight reaching the camera
+    pub light: f32,
+    /// 0..1 low-pass amount for sounds heard from under water
+    pub muffle: f32,
+    /// Fog density above the surface, put back on the way up
+    surface_fog_density: Option<f32>,
+}
+
+/// Swap the sky fog for murky water when the camera dives, dimming the light with depth
+pub fn update_underwater(
+    cycle: Res<DayNightCycle>,
+    mut underwater: ResMut<Underwater>,
+    mut clear_color: ResMut<ClearColor>,
+    mut camera_query: Query<(&Transform, &mut DistanceFog, &mut AmbientLight), With<MainCamera>>,
+    mut sun_query: Query<&mut DirectionalLight, With<Sun>>,
+) {
+    let Ok((transform, mut fog, mut ambient)) = camera_query.single_mut() else { return };
+    let depth = -transform.translation.y;
+
+    if depth <= 0.0 {
+        if underwater.submerged {
+            if let (Some(density), FogFalloff::ExponentialSquared { density: fog_density }) = (underwater.surface_fog_density.take(), &mut fog.falloff) {
+                *fog_density = density;
+            }
+            *underwater = Underwater::default();
+        }
+        return;
+    }
+
+    if !underwater.submerged && let FogFalloff::ExponentialSquared { density } = fog.falloff {
+        underwater.surface_fog_density = Some(density);
+    }
+    underwater.submerged = true;
+    underwater.depth = depth;
+    underwater.light = light_through_water(depth);
+    underwater.muffle = SURFACE_MUFFLE + (1.0 - SURFACE_MUFFLE) * (depth / MUFFLE_DEPTH).min(1.0);
+
+    // The day cycle has already set the sky's fog and light this frame; dim them from there
+    let color = SHALLOW_WATER_COLOR.lerp(DEEP_WATER_COLOR, (depth / COLOR_DEPTH).min(1.0))
+        * (0.15 + 0.85 * cycle.daylight())
+        * (0.3 + 0.7 * underwater.light);
+    fog.color = Color::srgb(color.x, color.y, color.z);
+    fog.falloff = FogFalloff::ExponentialSquared { density: UNDERWATER_FOG_DENSITY };
+    clear_color.0 = fog.color;
+    ambient.brightness *= underwater.light;
+    if let Ok(mut sun) = sun_query.single_mut() {
+        sun.illuminance *= underwater.light;
+    }
+}
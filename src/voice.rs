@@ -0,0 +1,456 @@
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+};
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+use audiopus::{
+    coder::{Decoder, Encoder},
+    packet::Packet,
+    Application, Bitrate, Channels, MutSignals, SampleRate,
+};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use crossbeam_channel::{Receiver, Sender};
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Mutex,
+};
+
+use crate::controls::MainCamera;
+use crate::hud::HudPalette;
+use crate::network::{ClientMessage, NetworkClient, RemotePlayer, VoiceFrameReceived};
+
+const PUSH_TO_TALK: KeyCode = KeyCode::KeyM;
+
+/// Opus works at 48 kHz in 20 ms frames
+const VOICE_SAMPLE_RATE: u32 = 48_000;
+const FRAME_SAMPLES: usize = 960;
+const VOICE_BITRATE: i32 = 24_000;
+const MAX_PACKET_BYTES: usize = 1275;
+
+/// Speakers fade out completely at this range; the server stops relaying beyond it
+const VOICE_RANGE: f32 = 15000.0;
+/// Frames buffered before playback starts, to ride out network jitter
+const JITTER_FRAMES: usize = 3;
+/// Drop the backlog beyond this so a stalled connection doesn't leave voice lagging behind
+const MAX_QUEUED_FRAMES: usize = 25;
+/// How long after the last frame a speaker is still shown as talking
+const TALKING_INDICATOR_SECS: f32 = 0.3;
+
+/// Push-to-talk voice over the multiplayer connection, attenuated by distance to the speaker
+#[derive(Resource)]
+pub struct VoiceChat {
+    pub enabled: bool,
+    pub volume: f32,
+    pub transmitting: bool,
+    pub capture_error: Option<String>,
+    capture: Option<VoiceCapture>,
+}
+
+impl Default for VoiceChat {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 1.0,
+            transmitting: false,
+            capture_error: None,
+            capture: None,
+        }
+    }
+}
+
+/// What the audio callback hands the encoder thread
+enum Captured {
+    /// Mono samples at the microphone's rate, taken while push-to-talk is held
+    Samples(Vec<f32>),
+    /// Push-to-talk was let go, so the transmission has ended
+    Released,
+}
+
+/// Handle to the microphone thread, which owns the cpal stream and the Opus encoder
+struct VoiceCapture {
+    talking: Arc<AtomicBool>,
+    packets: Receiver<Vec<u8>>,
+}
+
+/// Open the default microphone on its own thread; the stream stays alive as long as the thread does
+fn start_capture() -> Result<VoiceCapture, String> {
+    let talking = Arc::new(AtomicBool::new(false));
+    let (packet_tx, packets) = crossbeam_channel::unbounded();
+    let (ready_tx, ready_rx) = crossbeam_channel::bounded(1);
+
+    let thread_talking = talking.clone();
+    std::thread::Builder::new()
+        .name("voice-capture".to_string())
+        .spawn(move || {
+            let (raw_tx, raw_rx) = crossbeam_channel::unbounded::<Captured>();
+            let (stream, input_rate) = match open_input_stream(raw_tx, thread_talking) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+            encode_loop(raw_rx, packet_tx, input_rate);
+            drop(stream);
+        })
+        .map_err(|e| e.to_string())?;
+
+    ready_rx.recv().map_err(|e| e.to_string())??;
+    Ok(VoiceCapture { talking, packets })
+}
+
+fn open_input_stream(raw_tx: Sender<Captured>, talking: Arc<AtomicBool>) -> Result<(cpal::Stream, u32), String> {
+    let device = cpal::default_host().default_input_device().ok_or("No microphone found")?;
+    let supported = device.default_input_config().map_err(|e| e.to_string())?;
+    let input_rate = supported.sample_rate().0;
+    let config = supported.config();
+
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_input_stream::<f32>(&device, &config, raw_tx, talking),
+        SampleFormat::I16 => build_input_stream::<i16>(&device, &config, raw_tx, talking),
+        SampleFormat::U16 => build_input_stream::<u16>(&device, &config, raw_tx, talking),
+        format => return Err(format!("Unsupported microphone sample format {:?}", format)),
+    }?;
+    stream.play().map_err(|e| e.to_string())?;
+    info!("Voice capture started at {} Hz", input_rate);
+    Ok((stream, input_rate))
+}
+
+/// Downmix to mono in the audio callback and hand the samples to the encoder thread, saying when
+/// push-to-talk is let go
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    raw_tx: Sender<Captured>,
+    talking: Arc<AtomicBool>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let mut was_talking = false;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if !talking.load(Ordering::Relaxed) {
+                    if std::mem::take(&mut was_talking) {
+                        let _ = raw_tx.send(Captured::Released);
+                    }
+                    return;
+                }
+                was_talking = true;
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / channels as f32)
+                    .collect();
+                let _ = raw_tx.send(Captured::Samples(mono));
+            },
+            |e| warn!("Voice capture error: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Resample to 48 kHz, cut into 20 ms frames and Opus-encode them while push-to-talk is held
+fn encode_loop(raw_rx: Receiver<Captured>, packet_tx: Sender<Vec<u8>>, input_rate: u32) {
+    let mut encoder = match Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip) {
+        Ok(encoder) => encoder,
+        Err(e) => {
+            warn!("Failed to create Opus encoder: {}", e);
+            return;
+        }
+    };
+    let _ = encoder.set_bitrate(Bitrate::BitsPerSecond(VOICE_BITRATE));
+
+    let step = input_rate as f64 / VOICE_SAMPLE_RATE as f64;
+    let mut input: Vec<f32> = Vec::new();
+    let mut position = 0.0f64;
+    let mut frame: Vec<f32> = Vec::with_capacity(FRAME_SAMPLES);
+    let mut packet = [0u8; MAX_PACKET_BYTES];
+
+    while let Ok(captured) = raw_rx.recv() {
+        let samples = match captured {
+            Captured::Samples(samples) => samples,
+            Captured::Released => {
+                // Don't tack the tail of the last transmission onto the next one
+                input.clear();
+                frame.clear();
+                position = 0.0;
+                continue;
+            }
+        };
+        input.extend(samples);
+
+        // Linear interpolation is plenty for speech
+        while position + 1.0 < input.len() as f64 {
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            frame.push(input[index] + (input[index + 1] - input[index]) * fraction);
+            position += step;
+
+            if frame.len() == FRAME_SAMPLES {
+                match encoder.encode_float(&frame, &mut packet) {
+                    Ok(length) => {
+                        if packet_tx.send(packet[..length].to_vec()).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => warn!("Opus encode failed: {}", e),
+                }
+                frame.clear();
+            }
+        }
+        let consumed = position as usize;
+        input.drain(..consumed);
+        position -= consumed as f64;
+    }
+}
+
+/// Frames from one remote speaker, shared with its playback decoder on the audio thread
+#[derive(Default)]
+struct VoiceStreamState {
+    packets: Mutex<VecDeque<Vec<u8>>>,
+    gain: AtomicU32,
+    pan: AtomicU32,
+}
+
+impl VoiceStreamState {
+    fn set(&self, gain: f32, pan: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+        self.pan.store(pan.to_bits(), Ordering::Relaxed);
+    }
+
+    fn load(&self) -> (f32, f32) {
+        (
+            f32::from_bits(self.gain.load(Ordering::Relaxed)),
+            f32::from_bits(self.pan.load(Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Endless stereo stream of one player's voice, silent between transmissions
+#[derive(Asset, TypePath)]
+pub struct VoiceStream {
+    state: Arc<VoiceStreamState>,
+}
+
+pub struct VoiceDecoder {
+    state: Arc<VoiceStreamState>,
+    decoder: Option<Decoder>,
+    samples: VecDeque<f32>,
+    buffering: bool,
+    right: Option<f32>,
+}
+
+impl VoiceDecoder {
+    /// Decode the next queued frame, waiting for a small jitter buffer after each gap
+    fn refill(&mut self) {
+        let Some(decoder) = &mut self.decoder else { return };
+        let packet = {
+            let mut packets = self.state.packets.lock().unwrap();
+            if self.buffering && packets.len() < JITTER_FRAMES {
+                return;
+            }
+            self.buffering = false;
+            match packets.pop_front() {
+                Some(packet) => packet,
+                None => {
+                    self.buffering = true;
+                    return;
+                }
+            }
+        };
+
+        let mut output = [0.0f32; FRAME_SAMPLES];
+        let (Ok(packet), Ok(signals)) = (Packet::try_from(&packet[..]), MutSignals::try_from(&mut output[..])) else { return };
+        if let Ok(decoded) = decoder.decode_float(Some(packet), signals, false) {
+            self.samples.extend(&output[..decoded]);
+        }
+    }
+}
+
+impl Iterator for VoiceDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+        if self.samples.is_empty() {
+            self.refill();
+        }
+        let sample = self.samples.pop_front().unwrap_or(0.0);
+        let (gain, pan) = self.state.load();
+        let left = sample * gain * (1.0 - pan).min(1.0);
+        self.right = Some(sample * gain * (1.0 + pan).min(1.0));
+        Some(left)
+    }
+}
+
+impl Source for VoiceDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        VOICE_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+impl Decodable for VoiceStream {
+    type DecoderItem = f32;
+    type Decoder = VoiceDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        VoiceDecoder {
+            state: self.state.clone(),
+            decoder: Decoder::new(SampleRate::Hz48000, Channels::Mono).ok(),
+            samples: VecDeque::new(),
+            buffering: true,
+            right: None,
+        }
+    }
+}
+
+/// Playback for one remote player, parented to their aircraft so it goes away with them
+#[derive(Component)]
+pub struct VoiceSpeaker {
+    pub player_id: u32,
+    pub last_heard: f32,
+    state: Arc<VoiceStreamState>,
+}
+
+/// Hold M to transmit while connected
+pub fn push_to_talk(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut voice: ResMut<VoiceChat>,
+    client: Option<Res<NetworkClient>>,
+) {
+    let connected = client.as_ref().is_some_and(|client| client.connected);
+    let transmitting = voice.enabled && connected && keyboard.pressed(PUSH_TO_TALK);
+
+    if transmitting && voice.capture.is_none() && voice.capture_error.is_none() {
+        match start_capture() {
+            Ok(capture) => voice.capture = Some(capture),
+            Err(e) => {
+                warn!("Voice chat unavailable: {}", e);
+                voice.capture_error = Some(e);
+            }
+        }
+    }
+    voice.transmitting = transmitting && voice.capture.is_some();
+
+    let Some(capture) = &voice.capture else { return };
+    capture.talking.store(voice.transmitting, Ordering::Relaxed);
+    for frame in capture.packets.try_iter() {
+        if let Some(client) = client.as_ref().filter(|client| client.connected) {
+            client.send(ClientMessage::Voice { frame });
+        }
+    }
+}
+
+pub fn receive_voice_frame(
+    trigger: On<VoiceFrameReceived>,
+    time: Res<Time>,
+    voice: Res<VoiceChat>,
+    mut streams: ResMut<Assets<VoiceStream>>,
+    mut speakers: Query<&mut VoiceSpeaker>,
+    remote_players: Query<(Entity, &RemotePlayer)>,
+    mut commands: Commands,
+) {
+    if !voice.enabled {
+        return;
+    }
+    let event = &*trigger;
+
+    if let Some(mut speaker) = speakers.iter_mut().find(|speaker| speaker.player_id == event.id) {
+        speaker.last_heard = time.elapsed_secs();
+        let mut packets = speaker.state.packets.lock().unwrap();
+        packets.push_back(event.frame.clone());
+        if packets.len() > MAX_QUEUED_FRAMES {
+            let stale = packets.len() - JITTER_FRAMES;
+            packets.drain(..stale);
+        }
+        return;
+    }
+
+    let Some((entity, _)) = remote_players.iter().find(|(_, remote)| remote.player_id == event.id) else { return };
+    let state = Arc::new(VoiceStreamState::default());
+    state.packets.lock().unwrap().push_back(event.frame.clone());
+    let handle = streams.add(VoiceStream { state: state.clone() });
+    commands.entity(entity).with_child((
+        AudioPlayer::<VoiceStream>(handle),
+        VoiceSpeaker {
+            player_id: event.id,
+            last_heard: time.elapsed_secs(),
+            state,
+        },
+    ));
+}
+
+/// Fade speakers with distance and pan them by where they are relative to the camera
+pub fn update_voice_positions(
+    voice: Res<VoiceChat>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    speakers: Query<(&VoiceSpeaker, &ChildOf)>,
+    remote_players: Query<&GlobalTransform, With<RemotePlayer>>,
+) {
+    let Ok(camera) = camera_query.single() else { return };
+    for (speaker, child_of) in speakers.iter() {
+        let Ok(remote) = remote_players.get(child_of.parent()) else { continue };
+        let offset = remote.translation() - camera.translation();
+        let falloff = (1.0 - offset.length() / VOICE_RANGE).clamp(0.0, 1.0);
+        let gain = if voice.enabled { voice.volume * falloff * falloff } else { 0.0 };
+        let pan = camera.right().dot(offset.normalize_or_zero()) * 0.8;
+        speaker.state.set(gain, pan);
+    }
+}
+
+/// Transmit indicator and who is talking
+pub fn voice_hud(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    voice: Res<VoiceChat>,
+    palette: Res<HudPalette>,
+    speakers: Query<&VoiceSpeaker>,
+) -> Result<(), BevyError> {
+    let now = time.elapsed_secs();
+    let talking: Vec<u32> = speakers
+        .iter()
+        .filter(|speaker| now - speaker.last_heard < TALKING_INDICATOR_SECS)
+        .map(|speaker| speaker.player_id)
+        .collect();
+    if !voice.transmitting && talking.is_empty() {
+        return Ok(());
+    }
+
+    egui::Window::new("Voice")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -20.0])
+        .frame(Frame::default().fill(palette.window_fill))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            if voice.transmitting {
+                ui.label(egui::RichText::new("🎙 TRANSMITTING").strong());
+            }
+            for player_id in talking {
+                ui.label(format!("🔊 Player {}", player_id));
+            }
+        });
+
+    Ok(())
+}
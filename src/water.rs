@@ -0,0 +1,145 @@
+use bevy::{light::NotShadowCaster, platform::collections::HashMap, prelude::*};
+
+use crate::consts::CHUNK_SIZE;
+use crate::controls::MainCamera;
+use crate::world_generation::{ChunkManager, ChunkReshaped, SharedChunkMaterials, WorldGenerator};
+
+/// Terrain chunks along each side of a water tile
+const WATER_TILE_CHUNKS: i32 = 4;
+const WATER_TILE_SIZE: f32 = CHUNK_SIZE * WATER_TILE_CHUNKS as f32;
+/// Height samples along each side of a tile when checking it for water
+const WATER_SAMPLES: usize = 9;
+/// Ground this close above sea level still gets a tile, so beaches between samples aren't left dry
+const SHORE_MARGIN: f32 = 20.0;
+/// Tiles checked and spawned per frame, so a render distance change doesn't stall on height samples
+const MAX_TILES_PER_FRAME: usize = 8;
+/// Subdivisions for tiles within this many tiles of the camera, and for the rest
+const NEAR_WATER_TILES: i32 = 1;
+const NEAR_WATER_SUBDIVISIONS: u32 = 16;
+const FAR_WATER_SUBDIVISIONS: u32 = 1;
+
+/// A sea-level plane on the water grid, streamed separately from the terrain chunks
+#[derive(Component)]
+pub struct WaterTile {
+    pub x: i32,
+    pub z: i32,
+    near: bool,
+}
+
+/// Water tiles around the streaming center, and the tiles checked and found dry
+#[derive(Resource, Default)]
+pub struct WaterGrid {
+    /// Every tile that has been checked: `Some` holds its plane, `None` means dry land
+    tiles: HashMap<(i32, i32), Option<Entity>>,
+    near_mesh: Handle<Mesh>,
+    far_mesh: Handle<Mesh>,
+    /// World the tiles were checked against
+    seed: Option<u32>,
+}
+
+fn tile_center(tile: (i32, i32)) -> Vec2 {
+    // Terrain chunks are centered on their grid points, so a tile spans half a chunk either side of its first and last
+    let first_chunk = Vec2::new(tile.0 as f32, tile.1 as f32) * WATER_TILE_CHUNKS as f32;
+    (first_chunk + Vec2::splat((WATER_TILE_CHUNKS - 1) as f32 * 0.5)) * CHUNK_SIZE
+}
+
+/// Whether any of the tile's height samples dips to sea level
+fn has_water(world_gen: &WorldGenerator, tile: (i32, i32)) -> bool {
+    let corner = tile_center(tile) - Vec2::splat(WATER_TILE_SIZE * 0.5);
+    let step = WATER_TILE_SIZE / (WATER_SAMPLES - 1) as f32;
+    (0..WATER_SAMPLES * WATER_SAMPLES).any(|i| {
+        let x = corner.x + (i % WATER_SAMPLES) as f32 * step;
+        let z = corner.y + (i / WATER_SAMPLES) as f32 * step;
+        world_gen.get_terrain_height(&[x, 0.0, z]) < SHORE_MARGIN
+    })
+}
+
+/// Terrain edits can dig below sea level, so check the edited chunk's tile again if it was dry
+pub fn recheck_reshaped_water(trigger: On<ChunkReshaped>, mut grid: ResMut<WaterGrid>) {
+    let event = trigger.event();
+    let tile = (event.x.div_euclid(WATER_TILE_CHUNKS), event.z.div_euclid(WATER_TILE_CHUNKS));
+    if grid.tiles.get(&tile).is_some_and(Option::is_none) {
+        grid.tiles.remove(&tile);
+    }
+}
+
+/// Keep water tiles over the sea within the streamed disc, independent of terrain chunk churn
+pub fn stream_water_tiles(
+    mut commands: Commands,
+    mut grid: ResMut<WaterGrid>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    world_gen: Res<WorldGenerator>,
+    chunk_manager: Res<ChunkManager>,
+    shared_materials: Option<Res<SharedChunkMaterials>>,
+    camera: Query<&Transform, With<MainCamera>>,
+    mut tiles: Query<(&mut WaterTile, &mut Mesh3d)>,
+) {
+    let Some(shared_materials) = shared_materials else { return };
+    let Some((center_x, center_z)) = chunk_manager.last_camera_chunk else { return };
+    let Ok(camera) = camera.single() else { return };
+
+    // A new world means new coastlines
+    if grid.seed != Some(world_gen.seed) {
+        grid.seed = Some(world_gen.seed);
+        for entity in grid.tiles.drain().filter_map(|(_, entity)| entity) {
+            commands.entity(entity).despawn();
+        }
+    }
+    if grid.near_mesh == Handle::default() {
+        grid.near_mesh = meshes.add(Plane3d::default().mesh().size(WATER_TILE_SIZE, WATER_TILE_SIZE).subdivisions(NEAR_WATER_SUBDIVISIONS));
+        grid.far_mesh = meshes.add(Plane3d::default().mesh().size(WATER_TILE_SIZE, WATER_TILE_SIZE).subdivisions(FAR_WATER_SUBDIVISIONS));
+    }
+
+    let center = Vec2::new(center_x as f32, center_z as f32) * CHUNK_SIZE;
+    let radius = chunk_manager.render_distance as f32 * CHUNK_SIZE;
+    let tile_of = |x: f32, z: f32| {
+        (((x / CHUNK_SIZE).round() as i32).div_euclid(WATER_TILE_CHUNKS), ((z / CHUNK_SIZE).round() as i32).div_euclid(WATER_TILE_CHUNKS))
+    };
+    // Wanted while any part of the tile is inside the streamed disc
+    let in_range = |tile: (i32, i32)| {
+        let offset = (tile_center(tile) - center).abs() - Vec2::splat(WATER_TILE_SIZE * 0.5);
+        offset.max(Vec2::ZERO).length() <= radius
+    };
+
+    let WaterGrid { tiles: grid_tiles, near_mesh, far_mesh, .. } = &mut *grid;
+    grid_tiles.retain(|&tile, entity| {
+        let keep = in_range(tile);
+        if !keep && let Some(entity) = entity {
+            commands.entity(*entity).despawn();
+        }
+        keep
+    });
+
+    let camera_tile = tile_of(camera.translation.x, camera.translation.z);
+    for (mut tile, mut mesh) in &mut tiles {
+        let near = (tile.x - camera_tile.0).abs().max((tile.z - camera_tile.1).abs()) <= NEAR_WATER_TILES;
+        if near != tile.near {
+            tile.near = near;
+            mesh.0 = if near { near_mesh.clone() } else { far_mesh.clone() };
+        }
+    }
+
+    let (min_tile, max_tile) = (tile_of(center.x - radius, center.y - radius), tile_of(center.x + radius, center.y + radius));
+    let mut missing: Vec<(i32, i32)> = (min_tile.0..=max_tile.0)
+        .flat_map(|x| (min_tile.1..=max_tile.1).map(move |z| (x, z)))
+        .filter(|&tile| in_range(tile) && !grid_tiles.contains_key(&tile))
+        .collect();
+    missing.sort_by(|a, b| tile_center(*a).distance_squared(center).total_cmp(&tile_center(*b).distance_squared(center)));
+
+    for tile in missing.into_iter().take(MAX_TILES_PER_FRAME) {
+        if !has_water(&world_gen, tile) {
+            grid_tiles.insert(tile, None);
+            continue;
+        }
+        let near = (tile.0 - camera_tile.0).abs().max((tile.1 - camera_tile.1).abs()) <= NEAR_WATER_TILES;
+        let position = tile_center(tile);
+        let entity = commands.spawn((
+            Mesh3d(if near { near_mesh.clone() } else { far_mesh.clone() }),
+            MeshMaterial3d(shared_materials.water_material.clone()),
+            Transform::from_xyz(position.x, 0.0, position.y),
+            WaterTile { x: tile.0, z: tile.1, near },
+            NotShadowCaster,
+        )).id();
+        grid_tiles.insert(tile, Some(entity));
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::bookmarks::Bookmarks;
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{sample_macro_wind, Aircraft, Wind};
+use crate::hud::HudPalette;
+use crate::network::RemotePlayer;
+
+const WEATHER_MAP_KEY: KeyCode = KeyCode::KeyN;
+/// World units from the aircraft to the edge of the map
+const MAP_RANGE: f32 = 12000.0;
+const MAP_SIZE: f32 = 300.0;
+const MAP_CELLS: usize = 15;
+/// Wind this far above the base speed reads as a front
+const FRONT_MULTIPLIER: f32 = 1.5;
+
+#[derive(Resource, Default)]
+pub struct WeatherMap {
+    pub open: bool,
+}
+
+pub fn toggle_weather_map(keyboard: Res<ButtonInput<KeyCode>>, mut map: ResMut<WeatherMap>) {
+    if keyboard.just_pressed(WEATHER_MAP_KEY) {
+        map.open = !map.open;
+    }
+}
+
+/// North is world -X and east is world -Z, matching the heading compass
+fn to_map(offset: Vec3) -> egui::Vec2 {
+    egui::Vec2::new(-offset.z, offset.x)
+}
+
+/// Top-down view of the macro wind around the aircraft, sampled from the same field the flight model uses
+pub fn weather_map_ui(
+    mut contexts: EguiContexts,
+    map: Res<WeatherMap>,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    bookmarks: Res<Bookmarks>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+    remote_players: Query<&Transform, (With<RemotePlayer>, Without<Aircraft>)>,
+) -> Result<(), BevyError> {
+    if !map.open {
+        return Ok(());
+    }
+    let Ok(transform) = aircraft_query.single() else { return Ok(()) };
+    let center = transform.translation;
+    let t = time.elapsed_secs_f64();
+    let scale = MAP_SIZE / (MAP_RANGE * 2.0);
+    // Strongest speed the noise can produce, for shading
+    let peak_speed = (wind.wind_speed * 1.8).max(f32::EPSILON);
+
+    egui::Window::new("Weather Map")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("WEATHER MAP").size(12.0));
+            let (response, painter) = ui.allocate_painter(egui::Vec2::splat(MAP_SIZE), egui::Sense::hover());
+            let rect = response.rect;
+            let cell_size = MAP_SIZE / MAP_CELLS as f32;
+
+            for row in 0..MAP_CELLS {
+                for column in 0..MAP_CELLS {
+                    let cell_min = rect.min + egui::Vec2::new(column as f32, row as f32) * cell_size;
+                    let cell_center = cell_min + egui::Vec2::splat(cell_size * 0.5);
+                    let map_offset = (cell_center - rect.center()) / scale;
+                    let world = center + Vec3::new(map_offset.y, 0.0, -map_offset.x);
+                    let (direction, speed) = sample_macro_wind(&wind, world, t);
+
+                    let strength = (speed / peak_speed).clamp(0.0, 1.0);
+                    let fill = if wind.wind_speed > 0.0 && speed >= wind.wind_speed * FRONT_MULTIPLIER {
+                        egui::Color32::from_rgba_unmultiplied(220, 60, 40, (80.0 + 120.0 * strength) as u8)
+                    } else {
+                        egui::Color32::from_rgba_unmultiplied(40, 90, 200, (30.0 + 120.0 * strength) as u8)
+                    };
+                    painter.rect_filled(egui::Rect::from_min_size(cell_min, egui::Vec2::splat(cell_size)), 0.0, fill);
+
+                    let arrow = to_map(direction.with_y(0.0).normalize_or_zero()) * cell_size * (0.2 + 0.35 * strength);
+                    painter.arrow(cell_center - arrow * 0.5, arrow, egui::Stroke::new(1.0, egui::Color32::WHITE));
+                }
+            }
+
+            for player in remote_players.iter() {
+                let offset = to_map(player.translation - center) * scale;
+                if offset.x.abs() < MAP_SIZE * 0.5 && offset.y.abs() < MAP_SIZE * 0.5 {
+                    painter.circle_filled(rect.center() + offset, 3.5, egui::Color32::from_rgb(0, 255, 150));
+                }
+            }
+
+            for bookmark in &bookmarks.list {
+                let offset = to_map(Vec3::from(bookmark.position) - center) * scale;
+                if offset.x.abs() < MAP_SIZE * 0.5 && offset.y.abs() < MAP_SIZE * 0.5 {
+                    let point = rect.center() + offset;
+                    let marker = [egui::Vec2::new(0.0, -5.0), egui::Vec2::new(4.0, 0.0), egui::Vec2::new(0.0, 5.0), egui::Vec2::new(-4.0, 0.0)];
+                    painter.add(egui::Shape::convex_polygon(marker.map(|corner| point + corner).to_vec(), egui::Color32::from_rgb(255, 170, 40), egui::Stroke::NONE));
+                    painter.text(point + egui::Vec2::new(6.0, 0.0), egui::Align2::LEFT_CENTER, &bookmark.name, egui::FontId::proportional(10.0), egui::Color32::WHITE);
+                }
+            }
+
+            let forward = to_map(transform.forward().as_vec3().with_y(0.0).normalize_or_zero()) * 10.0;
+            painter.arrow(rect.center() - forward * 0.5, forward, egui::Stroke::new(2.5, egui::Color32::YELLOW));
+            painter.text(rect.center_top() + egui::Vec2::new(0.0, 8.0), egui::Align2::CENTER_CENTER, "N", egui::FontId::proportional(14.0), egui::Color32::WHITE);
+
+            ui.label(format!(
+                "{} across · base wind {} · red marks fronts, orange bookmarks",
+                units.format_distance(world_units_to_meters(MAP_RANGE * 2.0)),
+                units.format_speed(world_units_to_meters(wind.wind_speed)),
+            ));
+            ui.label(egui::RichText::new("N to close").size(10.0));
+        });
+
+    Ok(())
+}
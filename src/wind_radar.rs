@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Frame}, EguiContexts};
+
+use crate::consts::{world_units_to_meters, UnitSystem};
+use crate::controls::{probe_wind, Aircraft, Wind};
+use crate::day_cycle::DayNightCycle;
+use crate::hud::HudPalette;
+use crate::world_generation::WorldGenerator;
+
+const WIND_RADAR_KEY: KeyCode = KeyCode::KeyJ;
+/// World units ahead of the aircraft the radar reaches, and across its full width
+const RADAR_RANGE: f32 = 8000.0;
+const RADAR_WIDTH: f32 = 6000.0;
+const RADAR_COLUMNS: usize = 9;
+const RADAR_ROWS: usize = 12;
+/// Screen size of the sweep, in points; cells are square because width over columns matches range over rows
+const RADAR_PIXEL_WIDTH: f32 = 180.0;
+/// Disturbance, in radians per second squared, drawn fully red
+const SEVERE_UPSET: f32 = 0.08;
+/// Wind turning this far from the wind at the aircraft is marked as a shift
+const SHIFT_WARNING_DEGREES: f32 = 20.0;
+/// Slowest speed used to work out when the aircraft reaches a cell, so a near-stall doesn't look hours ahead
+const MIN_ARRIVAL_SPEED: f32 = 50.0;
+
+/// Heading-up look at the air ahead: gusts as a heatmap, wind shifts as amber arrows
+#[derive(Resource, Default)]
+pub struct WindRadar {
+    pub open: bool,
+}
+
+pub fn toggle_wind_radar(keyboard: Res<ButtonInput<KeyCode>>, mut radar: ResMut<WindRadar>) {
+    if keyboard.just_pressed(WIND_RADAR_KEY) {
+        radar.open = !radar.open;
+    }
+}
+
+/// Green through yellow to red as the disturbance approaches `SEVERE_UPSET`
+fn upset_color(upset: f32) -> egui::Color32 {
+    let severity = (upset / SEVERE_UPSET).clamp(0.0, 1.0);
+    let (red, green) = if severity < 0.5 {
+        (severity * 2.0, 1.0)
+    } else {
+        (1.0, 2.0 - severity * 2.0)
+    };
+    egui::Color32::from_rgba_unmultiplied((red * 230.0) as u8, (green * 200.0) as u8, 40, (40.0 + 140.0 * severity) as u8)
+}
+
+/// Sample the wind field ahead of the aircraft, each cell at the moment the aircraft would arrive there
+pub fn wind_radar_ui(
+    mut contexts: EguiContexts,
+    radar: Res<WindRadar>,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    (world_gen, day_cycle): (Res<WorldGenerator>, Res<DayNightCycle>),
+    palette: Res<HudPalette>,
+    units: Res<UnitSystem>,
+    aircraft_query: Query<(&Transform, &Aircraft)>,
+) -> Result<(), BevyError> {
+    if !radar.open {
+        return Ok(());
+    }
+    let Ok((transform, aircraft)) = aircraft_query.single() else { return Ok(()) };
+    let position = transform.translation;
+    let forward = transform.forward().as_vec3();
+    let ahead = forward.with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let right = ahead.cross(Vec3::Y);
+    let airspeed_ratio = aircraft.speed / aircraft.max_speed.max(f32::EPSILON);
+    let arrival_speed = aircraft.speed.max(MIN_ARRIVAL_SPEED);
+    let now = time.elapsed_secs_f64();
+
+    let here = probe_wind(&wind, &world_gen, &day_cycle, position, now, forward, airspeed_ratio);
+    let here_direction = here.wind.with_y(0.0).normalize_or_zero();
+    let cell_world = RADAR_WIDTH / RADAR_COLUMNS as f32;
+    let scale = RADAR_PIXEL_WIDTH / RADAR_WIDTH;
+    let size = egui::Vec2::new(RADAR_PIXEL_WIDTH, RADAR_RANGE * scale);
+    let mut worst_upset: f32 = 0.0;
+
+    egui::Window::new("Wind Radar")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_CENTER, [20.0, 0.0])
+        .frame(Frame::default().fill(palette.window_fill).inner_margin(8.0))
+        .show(contexts.ctx_mut()?, |ui| {
+            ui.visuals_mut().override_text_color = Some(palette.text);
+            ui.label(egui::RichText::new("WIND RADAR").size(12.0));
+            let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+            let rect = response.rect;
+            let cell_size = cell_world * scale;
+
+            for row in 0..RADAR_ROWS {
+                for column in 0..RADAR_COLUMNS {
+                    let distance = (row as f32 + 0.5) * cell_world;
+                    let across = (column as f32 + 0.5) * cell_world - RADAR_WIDTH * 0.5;
+                    let sample_at = position + ahead * distance + right * across;
+                    let arrival = now + (distance / arrival_speed) as f64;
+                    let probe = probe_wind(&wind, &world_gen, &day_cycle, sample_at, arrival, forward, airspeed_ratio);
+                    worst_upset = worst_upset.max(probe.upset);
+
+                    let cell_min = egui::Pos2::new(rect.min.x + column as f32 * cell_size, rect.max.y - (row + 1) as f32 * cell_size);
+                    let cell = egui::Rect::from_min_size(cell_min, egui::Vec2::splat(cell_size));
+                    painter.rect_filled(cell, 0.0, upset_color(probe.upset));
+
+                    // Heading-up: the aircraft's right is screen right and ahead is screen up
+                    let direction = probe.wind.with_y(0.0).normalize_or_zero();
+                    if direction == Vec3::ZERO {
+                        continue;
+                    }
+                    let shift = direction.angle_between(here_direction).to_degrees();
+                    let color = if here_direction != Vec3::ZERO && shift >= SHIFT_WARNING_DEGREES {
+                        egui::Color32::from_rgb(255, 180, 0)
+                    } else {
+                        egui::Color32::from_white_alpha(120)
+                    };
+                    let arrow = egui::Vec2::new(direction.dot(right), -direction.dot(ahead)) * cell_size * 0.6;
+                    painter.arrow(cell.center() - arrow * 0.5, arrow, egui::Stroke::new(1.0, color));
+                }
+            }
+
+            let nose = rect.center_bottom();
+            painter.arrow(nose + egui::Vec2::new(0.0, -2.0), egui::Vec2::new(0.0, -10.0), egui::Stroke::new(2.5, egui::Color32::YELLOW));
+
+            ui.label(format!(
+                "{} ahead · wind here {}",
+                units.format_distance(world_units_to_meters(RADAR_RANGE)),
+                units.format_speed(world_units_to_meters(here.wind.length())),
+            ));
+            ui.label(egui::RichText::new(if worst_upset >= SEVERE_UPSET { "⚠ Severe gusts ahead" } else { "Red: gusts · amber: wind shifts" }).size(10.0));
+            ui.label(egui::RichText::new("J to close").size(10.0));
+        });
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use bevy::{light::NotShadowCaster, prelude::*};
+use std::collections::HashSet;
+use std::f32::consts::PI;
+
+use crate::controls::{sample_macro_wind, Aircraft, Wind};
+use crate::network::{MicroburstReported, NetworkClient};
+use crate::world_generation::WorldGenerator;
+
+/// Seconds between rolls for a new event when flying offline
+const MICROBURST_CHECK_INTERVAL: f32 = 45.0;
+/// Chance each roll finds a storm worth searching for
+const MICROBURST_CHANCE: f32 = 0.25;
+/// Candidate spots tried per roll before giving up on finding a front
+const STORM_SEARCH_ATTEMPTS: usize = 8;
+/// Macro wind this far above the base speed counts as a storm, a little stronger than the weather map's fronts
+pub const STORM_MULTIPLIER: f32 = 1.4;
+/// Events are placed this far ahead of the aircraft so there is time to see the shaft and divert
+const SPAWN_MIN_DISTANCE: f32 = 4000.0;
+const SPAWN_MAX_DISTANCE: f32 = 12000.0;
+/// Half-angle either side of the heading that candidate spots are drawn from
+const SPAWN_SPREAD: f32 = PI / 3.0;
+const MIN_RADIUS: f32 = 1500.0;
+const MAX_RADIUS: f32 = 3000.0;
+/// Peak downdraft in world units per second, roughly 10 to 20 m/s
+const MIN_STRENGTH: f32 = 55.0;
+const MAX_STRENGTH: f32 = 105.0;
+const MIN_DURATION: f32 = 120.0;
+const MAX_DURATION: f32 = 300.0;
+/// Outflow speed at the edge of the core as a fraction of the downdraft
+const OUTFLOW_RATIO: f32 = 1.2;
+/// Depth of the outflow layer; the downdraft turns outward as it falls through it
+const OUTFLOW_DEPTH: f32 = 600.0;
+/// Outflow dies out at this many core radii from the centre
+const OUTFLOW_REACH: f32 = 3.0;
+/// Cloud base the shaft hangs from; the column weakens towards it
+pub const SHAFT_HEIGHT: f32 = 9000.0;
+const SHAFT_ALPHA: f32 = 0.22;
+const DUST_ALPHA: f32 = 0.3;
+/// Height of the dust skirt kicked up by the outflow
+const DUST_HEIGHT: f32 = 150.0;
+
+/// A column of falling air spreading out along the ground, the classic landing hazard under a storm
+#[derive(Debug, Clone, Copy)]
+pub struct Microburst {
+    pub id: u32,
+    /// World x and z of the core
+    pub center: Vec2,
+    /// Terrain height under the core, where the outflow spreads
+    pub ground: f32,
+    pub radius: f32,
+    /// Peak downdraft, in world units per second
+    pub strength: f32,
+    pub started: f64,
+    pub duration: f32,
+}
+
+impl Microburst {
+    /// 0..1 over the event's life: builds, peaks halfway, then collapses
+    pub fn intensity(&self, time: f64) -> f32 {
+        let age = ((time - self.started) as f32 / self.duration).clamp(0.0, 1.0);
+        (age * PI).sin()
+    }
+
+    /// Air velocity the event adds at a world position
+    pub fn air_velocity(&self, pos: Vec3, time: f64) -> Vec3 {
+        let intensity = self.intensity(time);
+        let offset = Vec2::new(pos.x, pos.z) - self.center;
+        let reach = offset.length() / self.radius;
+        if intensity <= 0.0 || reach > OUTFLOW_REACH {
+            return Vec3::ZERO;
+        }
+        let height = (pos.y - self.ground).max(0.0);
+        let near_ground = (-height / OUTFLOW_DEPTH).exp();
+        let below_cloud = (1.0 - height / SHAFT_HEIGHT).clamp(0.0, 1.0);
+
+        let downdraft = self.strength * (-reach * reach).exp() * (1.0 - near_ground) * below_cloud;
+        // Peaks at one core radius, where the headwind turns to tailwind across the core
+        let outflow = self.strength * OUTFLOW_RATIO * reach * (0.5 * (1.0 - reach * reach)).exp() * near_ground;
+        let spread = offset.normalize_or_zero() * outflow;
+        Vec3::new(spread.x, -downdraft, spread.y) * intensity
+    }
+}
+
+/// Total air velocity from every active event at a world position
+pub fn microburst_air(wind: &Wind, pos: Vec3, time: f64) -> Vec3 {
+    wind.microbursts.iter().map(|burst| burst.air_velocity(pos, time)).sum()
+}
+
+#[derive(Resource)]
+pub struct WindShear {
+    /// Roll for events near storms when flying offline; the server schedules them in multiplayer
+    pub random_events: bool,
+    /// Set from the settings to drop one straight ahead, storm or not
+    pub trigger_requested: bool,
+    check: Timer,
+    next_id: u32,
+}
+
+impl Default for WindShear {
+    fn default() -> Self {
+        Self {
+            random_events: true,
+            trigger_requested: false,
+            check: Timer::from_seconds(MICROBURST_CHECK_INTERVAL, TimerMode::Repeating),
+            next_id: 0,
+        }
+    }
+}
+
+impl WindShear {
+    fn start(&mut self, wind: &mut Wind, world_gen: &WorldGenerator, center: Vec2, radius: f32, strength: f32, duration: f32, now: f64) {
+        let ground = world_gen.get_terrain_height(&[center.x, 0.0, center.y]).max(0.0);
+        wind.microbursts.push(Microburst { id: self.next_id, center, ground, radius, strength, started: now, duration });
+        self.next_id = self.next_id.wrapping_add(1);
+        info!("Microburst at [{:.0}, {:.0}], {:.0} across", center.x, center.y, radius * 2.0);
+    }
+}
+
+pub fn receive_microburst(
+    trigger: On<MicroburstReported>,
+    mut wind_shear: ResMut<WindShear>,
+    mut wind: ResMut<Wind>,
+    world_gen: Res<WorldGenerator>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs_f64();
+    wind_shear.start(&mut wind, &world_gen, trigger.center, trigger.radius, trigger.strength, trigger.duration, now);
+}
+
+fn random_range(min: f32, max: f32) -> f32 {
+    min + rand::random::<f32>() * (max - min)
+}
+
+/// Retire spent events and, offline, occasionally start one under a storm ahead of the aircraft
+pub fn update_microbursts(
+    time: Res<Time>,
+    mut wind: ResMut<Wind>,
+    mut wind_shear: ResMut<WindShear>,
+    world_gen: Res<WorldGenerator>,
+    client: Option<Res<NetworkClient>>,
+    aircraft_query: Query<&Transform, With<Aircraft>>,
+) {
+    let now = time.elapsed_secs_f64();
+    wind.microbursts.retain(|burst| now < burst.started + burst.duration as f64);
+
+    let online = client.is_some_and(|client| client.connected);
+    let Ok(transform) = aircraft_query.single() else { return };
+    let heading = transform.forward().as_vec3().with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let spot_ahead = |angle: f32, distance: f32| {
+        let position = transform.translation + Quat::from_rotation_y(angle) * heading * distance;
+        Vec2::new(position.x, position.z)
+    };
+
+    if std::mem::take(&mut wind_shear.trigger_requested) && !online {
+        let center = spot_ahead(0.0, SPAWN_MIN_DISTANCE);
+        wind_shear.start(&mut wind, &world_gen, center, MAX_RADIUS, MAX_STRENGTH, MAX_DURATION, now);
+        return;
+    }
+
+    wind_shear.check.tick(time.delta());
+    if online || !wind_shear.random_events || !wind_shear.check.just_finished() || wind.wind_speed <= 0.0 {
+        return;
+    }
+    if rand::random::<f32>() > MICROBURST_CHANCE {
+        return;
+    }
+    for _ in 0..STORM_SEARCH_ATTEMPTS {
+        let center = spot_ahead(random_range(-SPAWN_SPREAD, SPAWN_SPREAD), random_range(SPAWN_MIN_DISTANCE, SPAWN_MAX_DISTANCE));
+        let (_, speed) = sample_macro_wind(&wind, Vec3::new(center.x, 0.0, center.y), now);
+        if speed >= wind.wind_speed * STORM_MULTIPLIER {
+            let (radius, strength, duration) = (
+                random_range(MIN_RADIUS, MAX_RADIUS),
+                random_range(MIN_STRENGTH, MAX_STRENGTH),
+                random_range(MIN_DURATION, MAX_DURATION),
+            );
+            wind_shear.start(&mut wind, &world_gen, center, radius, strength, duration, now);
+            return;
+        }
+    }
+}
+
+/// Rain shaft or dust skirt drawn for one event, faded with its intensity
+#[derive(Component)]
+pub struct MicroburstVisual {
+    id: u32,
+    material: Handle<StandardMaterial>,
+    peak_alpha: f32,
+}
+
+/// Keep a faint rain shaft over each core and a dust skirt where the outflow meets the ground
+pub fn draw_microburst_shafts(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    visuals: Query<(Entity, &MicroburstVisual)>,
+) {
+    let now = time.elapsed_secs_f64();
+    let mut drawn = HashSet::new();
+    for (entity, visual) in &visuals {
+        let Some(burst) = wind.microbursts.iter().find(|burst| burst.id == visual.id) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        drawn.insert(visual.id);
+        if let Some(material) = materials.get_mut(&visual.material) {
+            material.base_color.set_alpha(visual.peak_alpha * burst.intensity(now));
+        }
+    }
+
+    let translucent = |color: Color| StandardMaterial {
+        base_color: color.with_alpha(0.0),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    };
+    for burst in wind.microbursts.iter().filter(|burst| !drawn.contains(&burst.id)) {
+        let shaft_material = materials.add(translucent(Color::srgb(0.45, 0.5, 0.58)));
+        commands.spawn((
+            Mesh3d(meshes.add(Cylinder::new(burst.radius, SHAFT_HEIGHT))),
+            MeshMaterial3d(shaft_material.clone()),
+            Transform::from_xyz(burst.center.x, burst.ground + SHAFT_HEIGHT * 0.5, burst.center.y),
+            NotShadowCaster,
+            MicroburstVisual { id: burst.id, material: shaft_material, peak_alpha: SHAFT_ALPHA },
+        ));
+        let dust_material = materials.add(translucent(Color::srgb(0.62, 0.52, 0.4)));
+        commands.spawn((
+            Mesh3d(meshes.add(Cylinder::new(burst.radius * 2.0, DUST_HEIGHT))),
+            MeshMaterial3d(dust_material.clone()),
+            Transform::from_xyz(burst.center.x, burst.ground + DUST_HEIGHT * 0.5, burst.center.y),
+            NotShadowCaster,
+            MicroburstVisual { id: burst.id, material: dust_material, peak_alpha: DUST_ALPHA },
+        ));
+    }
+}
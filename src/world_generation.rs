@@ -3,21 +3,24 @@ use bevy::light::CascadeShadowConfig;
 use noise::{NoiseFn, Perlin};
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use bevy::{
+    asset::RenderAssetUsages,
+    image::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
     mesh::VertexAttributeValues,
     platform::collections::HashSet,
-    prelude::*
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 
-use crate::{RenderSettings, consts::*};
+use crate::{RenderSettings, consts::*, season::{Season, SeasonalTerrain}};
 use crate::controls::MainCamera;
-
-#[derive(Component)]
-pub struct WaterChunk;
+use crate::terrain_overrides::TerrainOverrides;
 
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Biome {
     Desert,
     Grasslands,
@@ -26,28 +29,60 @@ pub enum Biome {
     Ocean,
 }
 
+/// How rugged single-player terrain is. Multiplayer worlds are always `Rolling` so everyone flies over the same ground
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerrainPreset {
+    Lowlands,
+    #[default]
+    Rolling,
+    Mountains,
+}
+
+impl TerrainPreset {
+    pub const ALL: [TerrainPreset; 3] = [TerrainPreset::Lowlands, TerrainPreset::Rolling, TerrainPreset::Mountains];
+
+    /// Scales the noise relief; the biome elevation offsets, and with them the coastlines, stay put
+    fn relief(self) -> f32 {
+        match self {
+            TerrainPreset::Lowlands => 0.5,
+            TerrainPreset::Rolling => 1.0,
+            TerrainPreset::Mountains => 1.8,
+        }
+    }
+}
+
 #[derive(Resource, Clone)]
 pub struct WorldGenerator {
     pub seed: u32,
+    pub terrain: TerrainPreset,
     terrain_layers: Vec<PerlinLayer>,
     temperature_layer: PerlinLayer,
     humidity_layer: PerlinLayer,
+    /// Edits on top of the noise; shared with the mesh tasks, copied on write
+    pub overrides: Arc<TerrainOverrides>,
 }
 
 impl WorldGenerator {
     pub fn new(seed: u32) -> Self {
+        Self::with_terrain(seed, TerrainPreset::default())
+    }
+
+    pub fn with_terrain(seed: u32, terrain: TerrainPreset) -> Self {
+        let relief = terrain.relief();
         Self {
             seed,
+            terrain,
             terrain_layers: vec![
-                PerlinLayer::new(seed,       0.08 * TERRAIN_HORIZONTAL_SCALE, 4.5),    
-                PerlinLayer::new(seed,       0.20 * TERRAIN_HORIZONTAL_SCALE, 3.5),      
-                PerlinLayer::new(seed + 100, 0.5 * TERRAIN_HORIZONTAL_SCALE, 1.75), 
-                PerlinLayer::new(seed + 200, 1.0 * TERRAIN_HORIZONTAL_SCALE, 0.5),  
-                PerlinLayer::new(seed + 300, 2.0 * TERRAIN_HORIZONTAL_SCALE, 0.4),  
+                PerlinLayer::new(seed,       0.08 * TERRAIN_HORIZONTAL_SCALE, 4.5 * relief),    
+                PerlinLayer::new(seed,       0.20 * TERRAIN_HORIZONTAL_SCALE, 3.5 * relief),      
+                PerlinLayer::new(seed + 100, 0.5 * TERRAIN_HORIZONTAL_SCALE, 1.75 * relief), 
+                PerlinLayer::new(seed + 200, 1.0 * TERRAIN_HORIZONTAL_SCALE, 0.5 * relief),  
+                PerlinLayer::new(seed + 300, 2.0 * TERRAIN_HORIZONTAL_SCALE, 0.4 * relief),  
             ],
             // Note: Temperature and humidity need to be broad, so keep scales low!
             temperature_layer: PerlinLayer::new(seed + 400, 0.06 * TERRAIN_HORIZONTAL_SCALE, 1.0),
             humidity_layer: PerlinLayer::new(seed + 500, 0.06 * TERRAIN_HORIZONTAL_SCALE, 1.0),
+            overrides: Arc::default(),
         }
     }
 
@@ -109,10 +144,25 @@ impl WorldGenerator {
         let elevation_offset = get_biome_elevation_offset(temp, humidity);
 
         let final_height = base_height * height_multiplier + elevation_offset;
-        final_height * MAP_HEIGHT_SCALE
+        final_height * MAP_HEIGHT_SCALE + self.overrides.offset_at(pos[0], pos[2])
+    }
+
+    /// Upward surface normal from central differences of the terrain height
+    pub fn get_terrain_normal(&self, x: f32, z: f32) -> Vec3 {
+        let height = |x: f32, z: f32| self.get_terrain_height(&[x, 0.0, z]);
+        let d = TERRAIN_NORMAL_SAMPLE;
+        Vec3::new(
+            height(x - d, z) - height(x + d, z),
+            2.0 * d,
+            height(x, z - d) - height(x, z + d),
+        )
+        .normalize_or(Vec3::Y)
     }
 }
 
+/// Half the spacing of the height samples a terrain normal is taken from
+const TERRAIN_NORMAL_SAMPLE: f32 = 4.0;
+
 #[derive(Resource, Clone)]
 struct PerlinLayer {
     perlin: Perlin,
@@ -203,6 +253,64 @@ fn get_biome_height_multiplier(temp: f32, humidity: f32) -> f32 {
     land_mult + (ocean_mult - land_mult) * ocean_factor
 }
 
+/// Throw away every chunk, and the trees and props parented to them, so the terrain streams back in
+/// from the current generator
+#[derive(Event)]
+pub struct ResetChunks;
+
+/// A chunk entity was spawned at these chunk coordinates; its mesh is still a flat plane being shaped
+#[derive(EntityEvent)]
+pub struct ChunkSpawned {
+    pub entity: Entity,
+    pub x: i32,
+    pub z: i32,
+}
+
+/// A chunk's heightfield mesh finished building and is now shown, at `lod` subdivisions
+#[derive(EntityEvent)]
+pub struct ChunkMeshReady {
+    pub entity: Entity,
+    pub x: i32,
+    pub z: i32,
+    pub lod: u32,
+}
+
+/// A chunk is about to be despawned, along with everything parented to it
+#[derive(EntityEvent)]
+pub struct ChunkDespawned {
+    pub entity: Entity,
+    pub x: i32,
+    pub z: i32,
+}
+
+/// A chunk's terrain was edited and its mesh is being rebuilt, so anything standing on it needs placing again
+#[derive(EntityEvent)]
+pub struct ChunkReshaped {
+    pub entity: Entity,
+    pub x: i32,
+    pub z: i32,
+}
+
+pub fn reset_chunks(
+    _trigger: On<ResetChunks>,
+    mut commands: Commands,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut render_settings: ResMut<RenderSettings>,
+    chunks: Query<(Entity, &Chunk)>,
+) {
+    // Despawning is recursive, so vegetation goes with its chunk
+    for (entity, chunk) in &chunks {
+        commands.trigger(ChunkDespawned { entity, x: chunk.x, z: chunk.z });
+        commands.entity(entity).despawn();
+    }
+    chunk_manager.spawned_chunks.clear();
+    chunk_manager.last_camera_chunk = None;
+    chunk_manager.to_spawn.clear();
+    chunk_manager.lod_to_update.clear();
+    chunk_manager.to_remesh.clear();
+    render_settings.just_updated = true;
+}
+
 #[derive(Component)]
 pub struct ChunkTask {
     pub task: Task<Mesh>,
@@ -215,6 +323,8 @@ pub fn modify_plane(
     world_generator: Res<WorldGenerator>,
     meshes: Res<Assets<Mesh>>,
     render_settings: Res<RenderSettings>,
+    season: Res<Season>,
+    palette: Res<TerrainPalette>,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
     for (entity, mesh_handle, transform) in &query {
@@ -225,6 +335,9 @@ pub fn modify_plane(
 
             let smoothness = render_settings.terrain_smoothness;
             let compute_smooth_normals = render_settings.compute_smooth_normals;
+            let cliff_slope = render_settings.cliff_slope;
+            let seasonal = season.terrain();
+            let palette = palette.clone();
 
             let task = thread_pool.spawn(async move {
                 let mut colors: Vec<[f32; 4]> = Vec::new();
@@ -251,8 +364,9 @@ pub fn modify_plane(
                         let height_multiplier = get_biome_height_multiplier(temp, humidity);
                         let elevation_offset = get_biome_elevation_offset(temp, humidity);
 
-                        let final_height = base_height * height_multiplier + elevation_offset;
-                        colors.push(get_terrain_color(final_height, temp, humidity, smoothness));
+                        let final_height = base_height * height_multiplier + elevation_offset
+                            + world_gen.overrides.offset_at(world_pos[0], world_pos[2]) / MAP_HEIGHT_SCALE;
+                        colors.push(get_terrain_color(final_height, temp, humidity, smoothness, seasonal, &palette));
                         pos[1] = final_height * MAP_HEIGHT_SCALE;
                     }
                 }
@@ -265,6 +379,10 @@ pub fn modify_plane(
                     mesh_clone.duplicate_vertices();
                     mesh_clone.compute_flat_normals()
                 }
+                paint_cliffs(&mut mesh_clone, transform_clone.translation, cliff_slope);
+                if let Err(error) = mesh_clone.generate_tangents() {
+                    warn!("Terrain chunk has no tangents for its detail normals: {error}");
+                }
                 mesh_clone
             });
 
@@ -283,8 +401,9 @@ pub fn handle_compute_tasks(
     mut meshes: ResMut<Assets<Mesh>>,
     camera: Query<&Transform, With<MainCamera>>,
     settings: Res<WorldGenerationSettings>,
+    chunk_manager: Res<ChunkManager>,
 ) {
-    let cam_transform = camera.single().unwrap().translation;
+    let Ok(cam_transform) = camera.single().map(|camera| camera.translation) else { return };
     let cam_x = (cam_transform.x / CHUNK_SIZE).round() as i32;
     let cam_z = (cam_transform.z / CHUNK_SIZE).round() as i32;
 
@@ -293,12 +412,11 @@ pub fn handle_compute_tasks(
         .map(|(entity, _, _, chunk)| {
             let dx = (chunk.x - cam_x) as f32;
             let dz = (chunk.z - cam_z) as f32;
-            let distance_sq = dx * dx + dz * dz;
-            (entity, distance_sq)
+            (entity, chunk_priority(dx, dz, chunk_manager.view_bias))
         })
         .collect();
 
-    task_priorities.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    task_priorities.sort_by(|a, b| a.1.total_cmp(&b.1));
 
     let mut processed_count = 0;
     
@@ -307,7 +425,7 @@ pub fn handle_compute_tasks(
             break;
         }
 
-        if let Ok((entity, mesh_handle, mut task, _chunk)) = tasks.get_mut(entity)
+        if let Ok((entity, mesh_handle, mut task, chunk)) = tasks.get_mut(entity)
             && let Some(new_mesh) = future::block_on(future::poll_once(&mut task.task)) {
                 if let Some(new_handle) = task.new_handle.take() {
                     if let Some(mesh) = meshes.get_mut(&new_handle) {
@@ -321,6 +439,7 @@ pub fn handle_compute_tasks(
 
                 commands.entity(entity).try_remove::<ChunkTask>();
                 commands.entity(entity).try_insert(Visibility::Visible);
+                commands.trigger(ChunkMeshReady { entity, x: chunk.x, z: chunk.z, lod: chunk.current_lod });
                 processed_count += 1;
             }
     }
@@ -336,14 +455,53 @@ pub struct Chunk {
 #[derive(Resource, Default)]
 pub struct ChunkManager {
     pub spawned_chunks: HashSet<(i32, i32)>,
+    /// Chunk the last scan was centered on: the camera's, led ahead when flying fast
     pub last_camera_chunk: Option<(i32, i32)>,
     pub to_spawn: Vec<(i32, i32)>,
     pub lod_to_update: Vec<Entity>,
+    /// Chunks whose terrain was edited, rebuilt at their current detail
+    pub to_remesh: HashSet<(i32, i32)>,
     pub render_distance: i32,
     pub tree_render_distance: f32,
     pub lod_levels: [(f32, u32); 5],
     pub lod_quality_multiplier: u32,
     pub lod_distance_multiplier: f32,
+    /// Ground-plane direction the queues favor, from where the camera looks and moves
+    pub view_bias: Vec2,
+}
+
+/// How much looking along the ground pulls chunks forward in the queues
+const VIEW_BIAS_CAMERA_WEIGHT: f32 = 0.35;
+/// Camera speed, in world units per second, at which movement adds its full weight
+const VIEW_BIAS_FULL_SPEED: f32 = 1500.0;
+const VIEW_BIAS_VELOCITY_WEIGHT: f32 = 0.45;
+/// Chunks straight behind still count for at least this fraction of their distance ahead
+const VIEW_BIAS_MAX: f32 = 0.8;
+/// Re-sort the spawn queue once the bias has swung this far
+const VIEW_BIAS_RESORT_THRESHOLD: f32 = 0.15;
+
+/// Smoothing time for the velocity that leads the streaming center, so it doesn't jitter across chunk edges
+const LOOKAHEAD_SMOOTHING_SECS: f32 = 0.5;
+/// The lead never exceeds this fraction of the render distance, keeping the camera well inside the loaded disc
+const LOOKAHEAD_MAX_FRACTION: f32 = 0.33;
+/// Faster than any aircraft; camera jumps beyond this are teleports, not motion
+const TELEPORT_SPEED: f32 = 20000.0;
+
+/// Blend of the camera's look direction and its motion on the ground plane
+fn compute_view_bias(forward: Vec3, velocity: Vec3) -> Vec2 {
+    let look = Vec2::new(forward.x, forward.z).normalize_or_zero() * VIEW_BIAS_CAMERA_WEIGHT;
+    let motion = Vec2::new(velocity.x, velocity.z) / VIEW_BIAS_FULL_SPEED * VIEW_BIAS_VELOCITY_WEIGHT;
+    (look + motion.clamp_length_max(VIEW_BIAS_VELOCITY_WEIGHT)).clamp_length_max(VIEW_BIAS_MAX)
+}
+
+/// Queue priority of a chunk `(dx, dz)` chunks from the camera; lower goes first.
+/// Distance is shrunk for chunks in the view direction and stretched for those behind.
+fn chunk_priority(dx: f32, dz: f32, view_bias: Vec2) -> f32 {
+    let distance = (dx * dx + dz * dz).sqrt();
+    if distance == 0.0 {
+        return 0.0;
+    }
+    distance * (1.0 - Vec2::new(dx, dz).dot(view_bias) / distance)
 }
 
 #[derive(Resource)]
@@ -367,16 +525,48 @@ fn get_lod_subdivisions(distance_sq: f32, chunk_manager: &ChunkManager) -> u32 {
 #[derive(Resource)]
 pub struct WorldGenerationSettings {
     pub max_chunks_per_frame: usize,
+    /// Seconds of travel the streaming center is led ahead of the camera; 0 keeps it centered
+    pub lookahead_seconds: f32,
+    /// Chunks within this many chunks of the camera mesh in the near lane, which is never held back
+    pub near_lane_radius: i32,
+    /// Task pool threads the far lane leaves free, so a chunk under the aircraft never waits behind the horizon
+    pub near_lane_threads: usize,
 }
 
 impl Default for WorldGenerationSettings {
     fn default() -> Self {
         Self {
             max_chunks_per_frame: 100,
+            lookahead_seconds: 4.0,
+            near_lane_radius: 3,
+            near_lane_threads: 2,
         }
     }
 }
 
+impl WorldGenerationSettings {
+    /// Whether a chunk `(dx, dz)` chunks from the camera meshes in the near lane
+    fn in_near_lane(&self, dx: i32, dz: i32) -> bool {
+        dx * dx + dz * dz <= self.near_lane_radius * self.near_lane_radius
+    }
+
+    /// Far-lane mesh tasks allowed in flight at once
+    fn far_task_budget(&self) -> usize {
+        AsyncComputeTaskPool::get().thread_num().saturating_sub(self.near_lane_threads).max(1)
+    }
+
+    /// Of these meshing chunks, how many are in the far lane
+    fn far_tasks_in_flight<'a>(&self, meshing: impl Iterator<Item = &'a Chunk>, cam_x: i32, cam_z: i32) -> usize {
+        meshing.filter(|chunk| !self.in_near_lane(chunk.x - cam_x, chunk.z - cam_z)).count()
+    }
+}
+
+/// Run condition for the chunk pipeline, which streams around the main camera and has nothing to do without
+/// exactly one: before it spawns, or mid-transition while a menu or state swaps cameras
+pub fn main_camera_ready(camera: Query<(), With<MainCamera>>) -> bool {
+    camera.single().is_ok()
+}
+
 pub fn generate_chunks(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -387,30 +577,58 @@ pub fn generate_chunks(
     settings: Res<WorldGenerationSettings>,
     mut sun_query: Query<&mut CascadeShadowConfig, (With<crate::day_cycle::Sun>, Without<MainCamera>)>,
     mut render_settings: ResMut<crate::RenderSettings>,
+    time: Res<Time>,
+    mut last_cam_translation: Local<Option<Vec3>>,
+    mut smoothed_velocity: Local<Vec3>,
+    // Last frame's spawns may not have their mesh task yet
+    meshing: Query<&Chunk, Or<(With<ChunkTask>, Added<Chunk>)>>,
 ) {
-    let mut cascade = sun_query.single_mut().unwrap();
-
-    let cam_transform = camera.single().unwrap().translation;
+    let Ok(camera_transform) = camera.single() else { return };
+    let cam_transform = camera_transform.translation;
     
     let cam_x = (cam_transform.x / CHUNK_SIZE).round() as i32;
     let cam_z = (cam_transform.z / CHUNK_SIZE).round() as i32;
 
-    // Only re-scan if the camera has moved to a new chunk or render distance changed
-    if chunk_manager.last_camera_chunk != Some((cam_x, cam_z)) ||
+    // The camera rides along with the aircraft, so its motion is the aircraft's velocity when following
+    let dt = time.delta_secs();
+    let cam_velocity = match *last_cam_translation {
+        Some(last) if dt > 0.0 => (cam_transform - last) / dt,
+        _ => Vec3::ZERO,
+    };
+    let cam_velocity = if cam_velocity.length() > TELEPORT_SPEED { Vec3::ZERO } else { cam_velocity };
+    *last_cam_translation = Some(cam_transform);
+    let view_bias = compute_view_bias(camera_transform.forward().as_vec3(), cam_velocity);
+    let view_changed = view_bias.distance(chunk_manager.view_bias) > VIEW_BIAS_RESORT_THRESHOLD;
+    if view_changed {
+        chunk_manager.view_bias = view_bias;
+    }
+
+    // Stream around a point ahead of fast flight so terrain is ready before we get there
+    if dt > 0.0 {
+        let smoothing = 1.0 - (-dt / LOOKAHEAD_SMOOTHING_SECS).exp();
+        *smoothed_velocity = smoothed_velocity.lerp(cam_velocity, smoothing);
+    }
+    let max_lead = chunk_manager.render_distance as f32 * LOOKAHEAD_MAX_FRACTION * CHUNK_SIZE;
+    let lead = (Vec2::new(smoothed_velocity.x, smoothed_velocity.z) * settings.lookahead_seconds).clamp_length_max(max_lead);
+    let center_x = ((cam_transform.x + lead.x) / CHUNK_SIZE).round() as i32;
+    let center_z = ((cam_transform.z + lead.y) / CHUNK_SIZE).round() as i32;
+
+    // Only re-scan if the streaming center has moved to a new chunk or render distance changed
+    let rescanned = chunk_manager.last_camera_chunk != Some((center_x, center_z)) ||
     *last_render_distance != Some(chunk_manager.render_distance) ||
-    render_settings.just_updated
-    {
-        chunk_manager.last_camera_chunk = Some((cam_x, cam_z));
+    render_settings.just_updated;
+    if rescanned {
+        chunk_manager.last_camera_chunk = Some((center_x, center_z));
         *last_render_distance = Some(chunk_manager.render_distance);
         render_settings.just_updated = false;
         
         let render_distance_sq = (chunk_manager.render_distance as f32).powi(2);
         chunk_manager.to_spawn.clear();
 
-        for x in (cam_x - chunk_manager.render_distance)..=(cam_x + chunk_manager.render_distance) {
-            for z in (cam_z - chunk_manager.render_distance)..=(cam_z + chunk_manager.render_distance) {
-                let dx = (x - cam_x) as f32;
-                let dz = (z - cam_z) as f32;
+        for x in (center_x - chunk_manager.render_distance)..=(center_x + chunk_manager.render_distance) {
+            for z in (center_z - chunk_manager.render_distance)..=(center_z + chunk_manager.render_distance) {
+                let dx = (x - center_x) as f32;
+                let dz = (z - center_z) as f32;
                 let distance_sq = dx * dx + dz * dz;
 
                 if distance_sq <= render_distance_sq
@@ -420,47 +638,63 @@ pub fn generate_chunks(
             }
         }
         
-        // Sort by distance to camera so we spawn closest chunks first
-        chunk_manager.to_spawn.sort_by(|a, b| {
-            let da = ((a.0 - cam_x).pow(2) + (a.1 - cam_z).pow(2)) as f32;
-            let db = ((b.0 - cam_x).pow(2) + (b.1 - cam_z).pow(2)) as f32;
-            da.partial_cmp(&db).unwrap()
-        });
-
         // Update cascades
-        *cascade = bevy::light::CascadeShadowConfigBuilder {
-        first_cascade_far_bound: chunk_manager.render_distance as f32 * CHUNK_SIZE / 10.0,
-        maximum_distance: if render_settings.cascades > 0 {
-            chunk_manager.render_distance as f32 * CHUNK_SIZE
-        } else {
-            0.01
-        },
-        minimum_distance: 0.0,
-        num_cascades: render_settings.cascades.max(1),
-        ..default()
+        if let Ok(mut cascade) = sun_query.single_mut() {
+            *cascade = bevy::light::CascadeShadowConfigBuilder {
+                first_cascade_far_bound: chunk_manager.render_distance as f32 * CHUNK_SIZE / 10.0,
+                maximum_distance: if render_settings.cascades > 0 {
+                    chunk_manager.render_distance as f32 * CHUNK_SIZE
+                } else {
+                    0.01
+                },
+                minimum_distance: 0.0,
+                num_cascades: render_settings.cascades.max(1),
+                ..default()
+            }
+            .build();
         }
-        .build();
     }
 
-    // Spawn a limited number of chunks from the queue
+    // Spawn the closest chunks first, favoring the direction we're looking and flying
+    if rescanned || view_changed {
+        let view_bias = chunk_manager.view_bias;
+        chunk_manager.to_spawn.sort_by(|a, b| {
+            let pa = chunk_priority((a.0 - cam_x) as f32, (a.1 - cam_z) as f32, view_bias);
+            let pb = chunk_priority((b.0 - cam_x) as f32, (b.1 - cam_z) as f32, view_bias);
+            pa.total_cmp(&pb)
+        });
+    }
+
+    // Spawn a limited number of chunks from the queue. Far chunks wait while their lane is full,
+    // so the pool always has threads for the ground under the camera
+    let far_budget = settings.far_task_budget();
+    let mut far_in_flight = settings.far_tasks_in_flight(meshing.iter(), cam_x, cam_z);
     let mut spawned_count = 0;
-    while spawned_count < settings.max_chunks_per_frame && !chunk_manager.to_spawn.is_empty() {
-        let (x, z) = chunk_manager.to_spawn.remove(0);
+    let mut index = 0;
+    while spawned_count < settings.max_chunks_per_frame && index < chunk_manager.to_spawn.len() {
+        let (x, z) = chunk_manager.to_spawn[index];
+        let near = settings.in_near_lane(x - cam_x, z - cam_z);
+        if !near && far_in_flight >= far_budget {
+            index += 1;
+            continue;
+        }
+        chunk_manager.to_spawn.remove(index);
         
         // Final check: Is it still within range and not already spawned?
-        let dx = (x - cam_x) as f32;
-        let dz = (z - cam_z) as f32;
-        let distance_sq = dx * dx + dz * dz;
+        let dx = (x - center_x) as f32;
+        let dz = (z - center_z) as f32;
         let render_distance_sq = (chunk_manager.render_distance as f32).powi(2);
 
-        if distance_sq <= render_distance_sq && !chunk_manager.spawned_chunks.contains(&(x, z)) {
+        if dx * dx + dz * dz <= render_distance_sq && !chunk_manager.spawned_chunks.contains(&(x, z)) {
             chunk_manager.spawned_chunks.insert((x, z));
             
             let x_pos = x as f32 * CHUNK_SIZE;
             let z_pos = z as f32 * CHUNK_SIZE;
-            let lod = get_lod_subdivisions(distance_sq, &chunk_manager);
+            // Detail still follows the camera, not the streaming center
+            let camera_distance_sq = ((x - cam_x).pow(2) + (z - cam_z).pow(2)) as f32;
+            let lod = get_lod_subdivisions(camera_distance_sq, &chunk_manager);
             
-            commands.spawn((
+            let entity = commands.spawn((
                 Mesh3d(meshes.add(
                     Plane3d::default().mesh()
                     .size(CHUNK_SIZE, CHUNK_SIZE)
@@ -470,16 +704,12 @@ pub fn generate_chunks(
                 Transform::from_xyz(x_pos, 0.0, z_pos),
                 Chunk { x, z, current_lod: lod },
                 Visibility::Hidden,
-            )).with_children(|parent| {
-                parent.spawn((
-                    Mesh3d(meshes.add(Plane3d::default().mesh().size(CHUNK_SIZE, CHUNK_SIZE).subdivisions(1))),
-                    MeshMaterial3d(shared_materials.water_material.clone()),
-                    Transform::from_xyz(0.0, 0.0, 0.0),
-                    WaterChunk,
-                    bevy::light::NotShadowCaster
-                ));
-            });
+            )).id();
+            commands.trigger(ChunkSpawned { entity, x, z });
             spawned_count += 1;
+            if !near {
+                far_in_flight += 1;
+            }
         }
     }
 }
@@ -494,8 +724,11 @@ pub fn update_chunk_lod(
     mut last_cam_pos: Local<Option<(i32, i32)>>,
     settings: Res<WorldGenerationSettings>,
     render_settings: ResMut<RenderSettings>,
+    season: Res<Season>,
+    palette: Res<TerrainPalette>,
+    meshing: Query<&Chunk, With<ChunkTask>>,
 ) {
-    let cam_transform = camera.single().unwrap().translation;
+    let Ok(cam_transform) = camera.single().map(|camera| camera.translation) else { return };
     let cam_x = (cam_transform.x / CHUNK_SIZE).round() as i32;
     let cam_z = (cam_transform.z / CHUNK_SIZE).round() as i32;
 
@@ -513,21 +746,44 @@ pub fn update_chunk_lod(
             let desired_lod = get_lod_subdivisions(distance_sq, &chunk_manager);
             
             if desired_lod != chunk.current_lod {
-                candidates.push((entity, distance_sq));
+                candidates.push((entity, chunk_priority(dx, dz, chunk_manager.view_bias)));
             }
         }
 
-        // Sort candidates by distance so we prioritize updating closer chunks
-        candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        // Sort candidates so we prioritize updating closer chunks in the view direction
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
         chunk_manager.lod_to_update = candidates.into_iter().map(|(e, _)| e).collect();
     }
 
+    // Edited chunks jump the queue; one still meshing stays pending until its task lands
+    if !chunk_manager.to_remesh.is_empty() {
+        let ChunkManager { to_remesh, spawned_chunks, .. } = &mut *chunk_manager;
+        to_remesh.retain(|coords| spawned_chunks.contains(coords));
+        let edited: Vec<Entity> = chunks
+            .iter()
+            .filter(|(entity, chunk, ..)| {
+                chunk_manager.to_remesh.contains(&(chunk.x, chunk.z)) && !chunk_manager.lod_to_update.contains(entity)
+            })
+            .map(|(entity, ..)| entity)
+            .collect();
+        chunk_manager.lod_to_update.splice(0..0, edited);
+    }
+
     let thread_pool = AsyncComputeTaskPool::get();
+    let far_budget = settings.far_task_budget();
+    let mut far_in_flight = settings.far_tasks_in_flight(meshing.iter(), cam_x, cam_z);
     let mut processed_count = 0;
-
-    // Process a limited number of LOD updates from the queue
-    while processed_count < settings.max_chunks_per_frame && !chunk_manager.lod_to_update.is_empty() {
-        let entity = chunk_manager.lod_to_update.remove(0);
+    let mut index = 0;
+
+    // Process a limited number of LOD updates from the queue, holding far chunks back while their lane is full
+    while processed_count < settings.max_chunks_per_frame && index < chunk_manager.lod_to_update.len() {
+        let entity = chunk_manager.lod_to_update[index];
+        let near = chunks.get(entity).is_ok_and(|(_, chunk, ..)| settings.in_near_lane(chunk.x - cam_x, chunk.z - cam_z));
+        if !near && far_in_flight >= far_budget {
+            index += 1;
+            continue;
+        }
+        chunk_manager.lod_to_update.remove(index);
 
         if let Ok((entity, chunk, _mesh_handle, transform, _children)) = chunks.get_mut(entity) {
             let dx = (chunk.x - cam_x) as f32;
@@ -537,9 +793,13 @@ pub fn update_chunk_lod(
 
 
             // Double check if it still needs an update (might have been updated by another frame's scan)
-            if desired_lod == chunk.current_lod {
+            let edited = chunk_manager.to_remesh.remove(&(chunk.x, chunk.z));
+            if desired_lod == chunk.current_lod && !edited {
                 continue;
             }
+            if edited {
+                commands.trigger(ChunkReshaped { entity, x: chunk.x, z: chunk.z });
+            }
 
 
             let new_mesh_handle = meshes.add(
@@ -555,6 +815,9 @@ pub fn update_chunk_lod(
 
                 let smoothness = render_settings.terrain_smoothness;
                 let compute_smooth_normals = render_settings.compute_smooth_normals;
+                let cliff_slope = render_settings.cliff_slope;
+                let seasonal = season.terrain();
+                let palette = palette.clone();
 
                 let task = thread_pool.spawn(async move {
                     let mut colors: Vec<[f32; 4]> = Vec::new();
@@ -575,8 +838,9 @@ pub fn update_chunk_lod(
                             }
                             let height_multiplier = get_biome_height_multiplier(temp, humidity);
                             let elevation_offset = get_biome_elevation_offset(temp, humidity);
-                            let final_height = base_height * height_multiplier + elevation_offset;
-                            colors.push(get_terrain_color(final_height, temp, humidity, smoothness));
+                            let final_height = base_height * height_multiplier + elevation_offset
+                                + world_gen.overrides.offset_at(world_pos[0], world_pos[2]) / MAP_HEIGHT_SCALE;
+                            colors.push(get_terrain_color(final_height, temp, humidity, smoothness, seasonal, &palette));
                             pos[1] = final_height * MAP_HEIGHT_SCALE;
                         }
                     }
@@ -587,6 +851,10 @@ pub fn update_chunk_lod(
                         mesh_clone.duplicate_vertices();
                         mesh_clone.compute_flat_normals()
                     }
+                    paint_cliffs(&mut mesh_clone, transform_clone.translation, cliff_slope);
+                    if let Err(error) = mesh_clone.generate_tangents() {
+                        warn!("Terrain chunk has no tangents for its detail normals: {error}");
+                    }
                     mesh_clone
                 });
 
@@ -601,6 +869,9 @@ pub fn update_chunk_lod(
                     chunk.current_lod = desired_lod;
                 }
                 processed_count += 1;
+                if !near {
+                    far_in_flight += 1;
+                }
             }
         }
     }
@@ -609,39 +880,37 @@ pub fn update_chunk_lod(
 pub fn despawn_out_of_bounds_chunks(
     mut commands: Commands,
     camera: Query<&Transform, With<MainCamera>>,
-    chunks: Query<(Entity, &Chunk, Option<&Children>)>,
+    chunks: Query<(Entity, &Chunk)>,
     mut chunk_manager: ResMut<ChunkManager>,
     settings: Res<WorldGenerationSettings>,
 ) {
-    let cam_transform = camera.single().unwrap().translation;
+    let Ok(cam_transform) = camera.single().map(|camera| camera.translation) else { return };
     
     let cam_x = (cam_transform.x / CHUNK_SIZE).round() as i32;
     let cam_z = (cam_transform.z / CHUNK_SIZE).round() as i32;
+    // Keep whatever the led streaming center still wants
+    let (cam_x, cam_z) = chunk_manager.last_camera_chunk.unwrap_or((cam_x, cam_z));
 
     let despawn_distance_sq = ((chunk_manager.render_distance + 1) as f32).powi(2);
     
     let mut chunks_to_despawn = Vec::new();
 
-    for (entity, chunk, children) in &chunks {
+    for (entity, chunk) in &chunks {
         let dx = (chunk.x - cam_x) as f32;
         let dz = (chunk.z - cam_z) as f32;
         let distance_sq = dx * dx + dz * dz; 
 
         if distance_sq > despawn_distance_sq {
-            let child_vec = children.map(|c| c.iter().collect::<Vec<_>>());
-            chunks_to_despawn.push((entity, chunk.x, chunk.z, distance_sq, child_vec));
+            chunks_to_despawn.push((entity, chunk.x, chunk.z, distance_sq));
         }
     }
 
-    chunks_to_despawn.sort_by(|a, b| b.3.partial_cmp(&a.3).unwrap());
+    chunks_to_despawn.sort_by(|a, b| b.3.total_cmp(&a.3));
 
-    for (entity, x, z, _, children) in chunks_to_despawn.iter().take(settings.max_chunks_per_frame * 2) {
+    // Despawning is recursive, so vegetation goes with its chunk
+    for (entity, x, z, _) in chunks_to_despawn.iter().take(settings.max_chunks_per_frame * 2) {
         chunk_manager.spawned_chunks.remove(&(*x, *z));
-        if let Some(children) = children {
-            for child in children {
-                commands.entity(*child).despawn();
-            }
-        }
+        commands.trigger(ChunkDespawned { entity: *entity, x: *x, z: *z });
         commands.entity(*entity).despawn();
     }
 }
@@ -675,13 +944,219 @@ fn get_color_from_palette(height: f32, palette: &[TerrainStop], smoothness: f32)
     }
 }
 
-// Notice the signature changed to accept temp and humidity
-fn get_terrain_color(height: f32, temp: f32, humidity: f32, smoothness: f32) -> [f32; 4] {
+/// Terrain colour bands per biome plus the seasonal overlays; defaults to the tables in `consts.rs`
+#[derive(Resource, Clone, PartialEq)]
+pub struct TerrainPalette {
+    pub grasslands: Vec<TerrainStop>,
+    pub desert: Vec<TerrainStop>,
+    pub taiga: Vec<TerrainStop>,
+    pub forest: Vec<TerrainStop>,
+    pub autumn: Color,
+    pub summer_snow_line: f32,
+    pub winter_snow_line_drop: f32,
+}
+
+impl Default for TerrainPalette {
+    fn default() -> Self {
+        Self {
+            grasslands: GRASSLANDS_TERRAIN_LEVELS.to_vec(),
+            desert: DESERT_TERRAIN_LEVELS.to_vec(),
+            taiga: TAIGA_TERRAIN_LEVELS.to_vec(),
+            forest: FOREST_TERRAIN_LEVELS.to_vec(),
+            autumn: AUTUMN_TERRAIN_COLOR,
+            summer_snow_line: SUMMER_SNOW_LINE,
+            winter_snow_line_drop: WINTER_SNOW_LINE_DROP,
+        }
+    }
+}
+
+/// Terrain heights over one chunk on the same vertex grid as its mesh, row by row from -Z to +Z
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightfield {
+    /// Vertices along each side
+    pub resolution: usize,
+    pub heights: Vec<f32>,
+}
+
+impl Heightfield {
+    /// FNV-1a over the exact bits of every height, so two machines can compare terrain with one number
+    pub fn checksum(&self) -> u64 {
+        self.heights.iter().flat_map(|height| height.to_bits().to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+/// Unedited terrain of one chunk at `lod` subdivisions, from the seed alone, on the `Rolling` preset
+/// multiplayer worlds use. Clients sharing a seed must get bit-identical heightfields, so this touches
+/// no ECS state and no per-machine settings
+pub fn generate_chunk_heightfield(seed: u32, chunk: (i32, i32), lod: u32) -> Heightfield {
+    WorldGenerator::new(seed).chunk_heightfield(chunk, lod)
+}
+
+impl WorldGenerator {
+    /// A chunk's heights at `lod` subdivisions, including any terrain edits
+    pub fn chunk_heightfield(&self, chunk: (i32, i32), lod: u32) -> Heightfield {
+        // `Plane3d` subdivisions are cuts between the edges, so the grid has two more vertices than cuts
+        let resolution = lod as usize + 2;
+        let step = CHUNK_SIZE / (resolution - 1) as f32;
+        let corner = Vec2::new(chunk.0 as f32, chunk.1 as f32) * CHUNK_SIZE - Vec2::splat(CHUNK_SIZE * 0.5);
+        let heights = (0..resolution * resolution)
+            .map(|i| {
+                let x = corner.x + (i % resolution) as f32 * step;
+                let z = corner.y + (i / resolution) as f32 * step;
+                self.get_terrain_height(&[x, 0.0, z])
+            })
+            .collect();
+        Heightfield { resolution, heights }
+    }
+}
+
+/// Scaled height and vertex color at a world position, computed the same way as the chunk meshes
+pub fn sample_terrain_vertex(
+    world_gen: &WorldGenerator,
+    pos: &[f32; 3],
+    smoothness: f32,
+    season: SeasonalTerrain,
+    palette: &TerrainPalette,
+) -> (f32, [f32; 4]) {
+    let (temp, humidity) = world_gen.get_climate(pos);
+    let base_height: f32 = world_gen.terrain_layers.iter().map(|layer| layer.get_level(pos)).sum();
+    let height = base_height * get_biome_height_multiplier(temp, humidity) + get_biome_elevation_offset(temp, humidity)
+        + world_gen.overrides.offset_at(pos[0], pos[2]) / MAP_HEIGHT_SCALE;
+    (height * MAP_HEIGHT_SCALE, get_terrain_color(height, temp, humidity, smoothness, season, palette))
+}
+
+/// Degrees either side of the cliff slope over which grass and snow give way to rock
+const CLIFF_BLEND_DEGREES: f32 = 6.0;
+/// Vertical spacing of the rock strata, and the horizontal scale of the mottling on ledges
+const STRATA_SPACING: f32 = 45.0;
+const MOTTLE_SCALE: f32 = 0.013;
+
+/// Rock pattern projected along each axis and blended by the normal, so it doesn't stretch down steep faces
+fn triplanar_rock(pos: Vec3, normal: Vec3) -> f32 {
+    let weights = normal.abs().powf(4.0);
+    let weights = weights / (weights.x + weights.y + weights.z).max(f32::EPSILON);
+    // Strata run level on the side projections, wavering a little with the face's horizontal coordinate
+    let strata = |across: f32| ((pos.y / STRATA_SPACING + (across * MOTTLE_SCALE).sin() * 0.4) * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+    let mottle = ((pos.x * MOTTLE_SCALE * 3.0).sin() * (pos.z * MOTTLE_SCALE * 2.3).sin()) * 0.5 + 0.5;
+    strata(pos.z) * weights.x + mottle * weights.y + strata(pos.x) * weights.z
+}
+
+/// Blend triplanar rock over the vertex colours wherever the ground is steeper than `cliff_slope` degrees
+fn paint_cliffs(mesh: &mut Mesh, offset: Vec3, cliff_slope: f32) {
+    let (Some(VertexAttributeValues::Float32x3(positions)), Some(VertexAttributeValues::Float32x3(normals))) =
+        (mesh.attribute(Mesh::ATTRIBUTE_POSITION), mesh.attribute(Mesh::ATTRIBUTE_NORMAL))
+    else {
+        return;
+    };
+    let rock: Vec<(f32, f32)> = positions.iter().zip(normals).map(|(pos, normal)| {
+        let normal = Vec3::from_array(*normal);
+        let steepness = normal.y.clamp(-1.0, 1.0).acos().to_degrees();
+        let cover = ((steepness - cliff_slope + CLIFF_BLEND_DEGREES) / (2.0 * CLIFF_BLEND_DEGREES)).clamp(0.0, 1.0);
+        (cover, triplanar_rock(Vec3::from_array(*pos) + offset, normal))
+    }).collect();
+    let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR) else { return };
+    let (dark, light) = (CLIFF_ROCK_DARK.to_linear(), CLIFF_ROCK_LIGHT.to_linear());
+    for (color, (cover, pattern)) in colors.iter_mut().zip(rock) {
+        if cover <= 0.0 {
+            continue;
+        }
+        let rock_color = dark.mix(&light, pattern);
+        *color = LinearRgba::from_f32_array(*color).mix(&rock_color, cover).to_f32_array();
+    }
+}
+
+/// Texels along each side of the detail normal map, and how many times it repeats across a chunk
+const DETAIL_TEXTURE_SIZE: usize = 256;
+pub const DETAIL_REPEATS_PER_CHUNK: f32 = 16.0;
+/// Slope of the detail bumps at full strength
+const DETAIL_STRENGTH: f32 = 6.0;
+/// Bump strength kept in each mip level; the GPU picks coarser levels further away, so the bumps fade out
+/// past a couple of hundred metres without a custom shader
+const DETAIL_MIP_FADE: [f32; 5] = [1.0, 0.75, 0.4, 0.12, 0.0];
+
+/// Tileable value noise for the detail bumps, wrapping every `period` lattice cells
+fn detail_noise(x: f32, y: f32, period: u32, salt: u32) -> f32 {
+    let lattice = |x: u32, y: u32| {
+        let mut h = (x % period).wrapping_mul(0x8da6_b343) ^ (y % period).wrapping_mul(0xd816_3841) ^ salt.wrapping_mul(0xcb1a_b31f);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0x5bd1_e995);
+        h ^= h >> 15;
+        (h & 0xffff) as f32 / 65_535.0
+    };
+    let (cell_x, cell_y) = (x.floor(), y.floor());
+    let (fx, fy) = (x - cell_x, y - cell_y);
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (cx, cy) = (cell_x as u32, cell_y as u32);
+    let top = lattice(cx, cy) + (lattice(cx + 1, cy) - lattice(cx, cy)) * sx;
+    let bottom = lattice(cx, cy + 1) + (lattice(cx + 1, cy + 1) - lattice(cx, cy + 1)) * sx;
+    top + (bottom - top) * sy
+}
+
+/// Tiling tangent-space normal map of small bumps, with a mip chain that flattens towards the coarse levels
+pub fn build_detail_normal_map() -> Image {
+    let size = DETAIL_TEXTURE_SIZE;
+    let mut heights: Vec<f32> = (0..size * size).map(|i| {
+        let (x, y) = ((i % size) as f32, (i / size) as f32);
+        [(16, 0.5), (32, 0.3), (64, 0.2)].iter().enumerate().map(|(octave, &(period, amplitude))| {
+            let scale = period as f32 / size as f32;
+            detail_noise(x * scale, y * scale, period, octave as u32) * amplitude
+        }).sum()
+    }).collect();
+
+    let levels = size.ilog2() + 1;
+    let mut data = Vec::new();
+    let mut level_size = size;
+    for level in 0..levels {
+        let fade = DETAIL_MIP_FADE.get(level as usize).copied().unwrap_or(0.0);
+        // Slopes stay in full-resolution texels so the coarse levels show the same bumps, blurred
+        let spacing = 2.0 * (1 << level) as f32 / size as f32;
+        let height = |x: usize, y: usize| heights[(y % level_size) * level_size + x % level_size];
+        for y in 0..level_size {
+            for x in 0..level_size {
+                let dx = (height(x + 1, y) - height(x + level_size - 1, y)) / spacing;
+                let dy = (height(x, y + 1) - height(x, y + level_size - 1)) / spacing;
+                let normal = Vec3::new(-dx, -dy, 1.0 / (DETAIL_STRENGTH * fade).max(f32::EPSILON)).normalize();
+                data.extend([normal.x, normal.y, normal.z].map(|n| ((n * 0.5 + 0.5) * 255.0).round() as u8));
+                data.push(255);
+            }
+        }
+        if level_size > 1 {
+            let half = level_size / 2;
+            heights = (0..half * half).map(|i| {
+                let (x, y) = ((i % half) * 2, (i / half) * 2);
+                (height(x, y) + height(x + 1, y) + height(x, y + 1) + height(x + 1, y + 1)) * 0.25
+            }).collect();
+            level_size = half;
+        }
+    }
+
+    let mut image = Image::new_uninit(
+        Extent3d { width: size as u32, height: size as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.data = Some(data);
+    image.texture_descriptor.mip_level_count = levels;
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        mag_filter: ImageFilterMode::Linear,
+        min_filter: ImageFilterMode::Linear,
+        mipmap_filter: ImageFilterMode::Linear,
+        ..default()
+    });
+    image
+}
+
+fn get_terrain_color(height: f32, temp: f32, humidity: f32, smoothness: f32, season: SeasonalTerrain, palette: &TerrainPalette) -> [f32; 4] {
     // 1. Get what the color *would* be if the world was 100% this biome
-    let forest_color = get_color_from_palette(height, FOREST_TERRAIN_LEVELS, smoothness).to_linear();
-    let desert_color = get_color_from_palette(height, DESERT_TERRAIN_LEVELS, smoothness).to_linear();
-    let taiga_color = get_color_from_palette(height, TAIGA_TERRAIN_LEVELS, smoothness).to_linear();
-    let grass_color = get_color_from_palette(height, GRASSLANDS_TERRAIN_LEVELS, smoothness).to_linear();
+    let forest_color = get_color_from_palette(height, &palette.forest, smoothness).to_linear();
+    let desert_color = get_color_from_palette(height, &palette.desert, smoothness).to_linear();
+    let taiga_color = get_color_from_palette(height, &palette.taiga, smoothness).to_linear();
+    let grass_color = get_color_from_palette(height, &palette.grasslands, smoothness).to_linear();
 
     // 2. Bilinear Interpolation
     // First, blend the humidity axis (dry -> wet) for both hot and cold extremes
@@ -689,7 +1164,52 @@ fn get_terrain_color(height: f32, temp: f32, humidity: f32, smoothness: f32) ->
     let hot_blend = desert_color.mix(&forest_color, humidity);  
 
     // Finally, blend between those two results along the temperature axis (cold -> hot)
-    let final_color = cold_blend.mix(&hot_blend, temp);
+    let mut final_color = cold_blend.mix(&hot_blend, temp);
+
+    // 3. Seasons: autumn browns the green bands, winter drags the snow line down cold slopes
+    if season.autumn > 0.0 {
+        let greenness = (final_color.green - final_color.red.max(final_color.blue)).clamp(0.0, 0.2) * 5.0;
+        final_color = final_color.mix(&palette.autumn.to_linear(), season.autumn * greenness * 0.6);
+    }
+    if season.snow > 0.0 && height > 0.0 {
+        let coldness = ((0.8 - temp) / 0.3).clamp(0.0, 1.0);
+        let snow_line = palette.summer_snow_line - season.snow * palette.winter_snow_line_drop * (0.5 + coldness);
+        let snow_cover = ((height - snow_line) / 0.4).clamp(0.0, 1.0) * coldness * season.snow.min(1.0);
+        final_color = final_color.mix(&LinearRgba::WHITE, snow_cover);
+    }
 
     final_color.to_f32_array()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checksums recorded from `generate_chunk_heightfield`. If one changes, every client on an older build
+    /// sees different terrain for the same seed, so only update them on purpose
+    const GOLDEN: [(u32, (i32, i32), u32, u64); 3] = [
+        (42, (0, 0), 4, 0x2425_3fb7_9eac_62ea),
+        (1234, (-3, 7), 8, 0xc21d_22e3_63aa_602d),
+        (7, (15, -2), 2, 0x522a_2762_5dee_7f5e),
+    ];
+
+    #[test]
+    fn heightfields_match_golden_checksums() {
+        for (seed, chunk, lod, checksum) in GOLDEN {
+            let field = generate_chunk_heightfield(seed, chunk, lod);
+            assert_eq!(field.heights.len(), field.resolution * field.resolution);
+            assert_eq!(field.checksum(), checksum, "seed {seed} chunk {chunk:?} lod {lod}");
+        }
+    }
+
+    #[test]
+    fn neighbouring_chunks_share_edge_heights() {
+        let left = generate_chunk_heightfield(42, (0, 0), 4);
+        let right = generate_chunk_heightfield(42, (1, 0), 4);
+        // The left chunk's last column against the right chunk's first
+        for row in 0..left.resolution {
+            let start = row * left.resolution;
+            assert_eq!(left.heights[start + left.resolution - 1], right.heights[start]);
+        }
+    }
+}